@@ -1,27 +1,50 @@
 use bevy::prelude::*;
 use bevy::window::{PresentMode, WindowMode, WindowPlugin};
 use bevy_framepace::FramepacePlugin;
+use emergence_lib::control_api::ControlApiPlugin;
+use emergence_lib::fog_of_war::FogOfWarPlugin;
+use emergence_lib::game_state::GameStatePlugin;
+use emergence_lib::multiplayer::MultiplayerPlugin;
+use emergence_lib::player_interaction::input_recording::InputRecordingPlugin;
+use emergence_lib::simulation::telemetry::TelemetryPlugin;
+use emergence_lib::viewer_events::ViewerEventsPlugin;
 use emergence_lib::world_gen::GenerationConfig;
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Emergence".to_string(),
-                present_mode: PresentMode::AutoNoVsync,
-                mode: WindowMode::BorderlessFullscreen,
-                ..default()
-            }),
-            ..Default::default()
-        }))
-        // This is turned on and off in the world gen state management code.
-        .add_plugins(FramepacePlugin)
-        .add_plugins(emergence_lib::asset_management::AssetManagementPlugin)
-        .add_plugins(emergence_lib::simulation::SimulationPlugin {
-            gen_config: GenerationConfig::standard(),
-        })
-        .add_plugins(emergence_lib::player_interaction::InteractionPlugin)
-        .add_plugins(emergence_lib::graphics::GraphicsPlugin)
-        .add_plugins(emergence_lib::ui::UiPlugin)
-        .run();
+    let mut app = App::new();
+
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "Emergence".to_string(),
+            present_mode: PresentMode::AutoNoVsync,
+            mode: WindowMode::BorderlessFullscreen,
+            ..default()
+        }),
+        ..Default::default()
+    }))
+    // This is turned on and off in the world gen state management code.
+    .add_plugins(FramepacePlugin)
+    .add_plugins(emergence_lib::asset_management::AssetManagementPlugin)
+    // Start in the main menu, rather than generating a world immediately
+    .add_plugins(GameStatePlugin)
+    .add_plugins(emergence_lib::simulation::SimulationPlugin {
+        gen_config: GenerationConfig::standard(),
+    })
+    .add_plugins(emergence_lib::player_interaction::InteractionPlugin)
+    .add_plugins(emergence_lib::graphics::GraphicsPlugin)
+    .add_plugins(emergence_lib::audio::AudioFeedbackPlugin)
+    .add_plugins(emergence_lib::ui::UiPlugin)
+    // Telemetry is only exported if requested through environment variables
+    .add_plugins(TelemetryPlugin::from_env())
+    // Likewise, multiplayer is only enabled when hosting or joining a game
+    .add_plugins(MultiplayerPlugin::from_env())
+    .add_plugins(ViewerEventsPlugin::from_env())
+    .add_plugins(ControlApiPlugin::from_env())
+    .add_plugins(FogOfWarPlugin::from_env())
+    .add_plugins(InputRecordingPlugin::from_env());
+
+    #[cfg(feature = "dev-tools")]
+    app.add_plugins(emergence_lib::dev_tools::DevToolsPlugin);
+
+    app.run();
 }
@@ -4,6 +4,7 @@ use emergence_lib::asset_management::manifest::Id;
 use emergence_lib::crafting::item_tags::ItemKind;
 use emergence_lib::geometry::{MapGeometry, VoxelPos};
 use emergence_lib::signals::{SignalStrength, SignalType, Signals, DIFFUSION_FRACTION};
+use emergence_lib::simulation::weather::Wind;
 
 /// Setup function
 fn setup(settings: Settings) -> (Signals, MapGeometry) {
@@ -65,17 +66,21 @@ impl Settings {
 fn criterion_benchmark(c: &mut Criterion) {
     let (mut minimal_signals, minimal_map_geometry) = setup(Settings::MINIMAL);
     c.bench_function("signal_diffusion_minimal", |b| {
-        b.iter(|| minimal_signals.diffuse(&minimal_map_geometry, DIFFUSION_FRACTION));
+        b.iter(|| {
+            minimal_signals.diffuse(&minimal_map_geometry, |_| DIFFUSION_FRACTION, &Wind::CALM)
+        });
     });
 
     let (mut tiny_signals, tiny_map_geometry) = setup(Settings::TINY);
     c.bench_function("signal_diffusion_tiny", |b| {
-        b.iter(|| tiny_signals.diffuse(&tiny_map_geometry, DIFFUSION_FRACTION));
+        b.iter(|| tiny_signals.diffuse(&tiny_map_geometry, |_| DIFFUSION_FRACTION, &Wind::CALM));
     });
 
     let (mut modest_signals, modest_map_geometry) = setup(Settings::MODEST);
     c.bench_function("signal_diffusion_modest", |b| {
-        b.iter(|| modest_signals.diffuse(&modest_map_geometry, DIFFUSION_FRACTION));
+        b.iter(|| {
+            modest_signals.diffuse(&modest_map_geometry, |_| DIFFUSION_FRACTION, &Wind::CALM)
+        });
     });
 }
 
@@ -0,0 +1,315 @@
+//! Generates a huge, densely populated map and runs the game on it, reporting how long each part of the frame took.
+//!
+//! This gives contributors a shared target for performance work:
+//! run it before and after a change, and compare the summaries printed at exit.
+//!
+//! ```text
+//! cargo run --release --example stress_test -- [--radius N] [--density X] [--frames N] [--seed N] [--rendered]
+//! ```
+//!
+//! - `--radius`: the radius of the generated map, in tiles. Defaults to 100.
+//! - `--density`: how many starting units and wild structures are generated, relative to the standard game rules. Defaults to 3.
+//! - `--frames`: how many frames to run for after world generation completes. Defaults to 1000.
+//! - `--seed`: the world generation seed. Defaults to the seed of the standard map.
+//! - `--rendered`: open a window and draw the game, rather than running only the simulation.
+//!
+//! In headless mode, time advances by exactly one simulation tick per frame,
+//! and the time spent in each schedule of the main loop is reported separately.
+//! In rendered mode, time advances in real time and only whole frame times are reported.
+//!
+//! Bevy does not record the time taken by individual systems by default.
+//! For a per-system breakdown, build with Bevy's `trace_chrome` or `trace_tracy` feature enabled
+//! and open the resulting trace in a profiler.
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    app::{AppExit, MainScheduleOrder},
+    prelude::*,
+    time::TimeUpdateStrategy,
+    utils::HashMap,
+};
+use emergence_lib::{
+    asset_management::manifest::Id,
+    simulation::{game_rules::GameRules, SimulationPlugin},
+    structures::structure_manifest::Structure,
+    testing::simulation_app,
+    units::unit_manifest::Unit,
+    world_gen::{GenerationConfig, WorldGenState},
+};
+
+/// The maximum number of frames to wait for world generation to complete before giving up.
+const MAX_WORLD_GEN_FRAMES: u32 = 10_000;
+
+/// Settings for a stress test run, parsed from the command line.
+#[derive(Resource, Debug, Clone)]
+struct StressTestConfig {
+    /// The radius of the generated map, in tiles.
+    map_radius: u32,
+    /// How many starting units and wild structures are generated, relative to the standard game rules.
+    density: f32,
+    /// How many frames to run for after world generation completes.
+    frames: u32,
+    /// The world generation seed.
+    seed: u64,
+    /// Should the game be drawn in a window?
+    rendered: bool,
+}
+
+impl Default for StressTestConfig {
+    fn default() -> Self {
+        StressTestConfig {
+            map_radius: 100,
+            density: 3.,
+            frames: 1000,
+            seed: GenerationConfig::standard().seed,
+            rendered: false,
+        }
+    }
+}
+
+impl StressTestConfig {
+    /// Parses the settings from the command line arguments, falling back to the defaults.
+    fn from_args() -> Self {
+        let mut config = StressTestConfig::default();
+        let mut args = std::env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--radius" => config.map_radius = parse_value(&arg, args.next()),
+                "--density" => config.density = parse_value(&arg, args.next()),
+                "--frames" => config.frames = parse_value(&arg, args.next()),
+                "--seed" => config.seed = parse_value(&arg, args.next()),
+                "--rendered" => config.rendered = true,
+                _ => panic!("Unrecognized argument {arg}. See the documentation at the top of examples/stress_test.rs for usage."),
+            }
+        }
+
+        config
+    }
+
+    /// The world generation settings for this stress test.
+    fn gen_config(&self) -> GenerationConfig {
+        let mut gen_config = GenerationConfig::standard();
+        gen_config.map_radius = self.map_radius;
+        gen_config.seed = self.seed;
+        gen_config
+    }
+
+    /// The game rules for this stress test, which scale up the number of starting units and wild structures.
+    fn game_rules(&self) -> GameRules {
+        GameRules {
+            starting_workforce: self.density,
+            starting_resources: self.density,
+            ..GameRules::default()
+        }
+    }
+}
+
+/// Parses the value passed after the command line flag `flag`.
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| panic!("{flag} must be followed by a valid value"))
+}
+
+/// The recorded durations of one part of the frame.
+#[derive(Debug, Clone, Default)]
+struct Timings {
+    /// The total time spent, across all frames.
+    total: Duration,
+    /// The longest time spent in a single frame.
+    max: Duration,
+    /// The number of frames recorded.
+    count: u32,
+}
+
+impl Timings {
+    /// Records the time taken during a single frame.
+    fn record(&mut self, duration: Duration) {
+        self.total += duration;
+        self.max = self.max.max(duration);
+        self.count += 1;
+    }
+
+    /// The average time taken per frame.
+    fn mean(&self) -> Duration {
+        self.total.checked_div(self.count).unwrap_or_default()
+    }
+}
+
+/// Prints a table summarizing each of the `timings`, sorted from most to least total time.
+fn print_summary(title: &str, timings: &HashMap<String, Timings>, frame_total: Duration) {
+    let mut rows: Vec<(&String, &Timings)> = timings.iter().collect();
+    rows.sort_by(|a, b| b.1.total.cmp(&a.1.total));
+
+    println!("\n{title}");
+    println!(
+        "{:<32} {:>12} {:>12} {:>12} {:>8}",
+        "name", "total (ms)", "mean (ms)", "max (ms)", "share"
+    );
+    for (name, timing) in rows {
+        let share = if frame_total.is_zero() {
+            0.
+        } else {
+            timing.total.as_secs_f64() / frame_total.as_secs_f64() * 100.
+        };
+
+        println!(
+            "{:<32} {:>12.2} {:>12.3} {:>12.3} {:>7.1}%",
+            name,
+            timing.total.as_secs_f64() * 1000.,
+            timing.mean().as_secs_f64() * 1000.,
+            timing.max.as_secs_f64() * 1000.,
+            share
+        );
+    }
+}
+
+/// Prints the number of units and structures currently in the world.
+fn print_population(world: &mut World) {
+    let n_units = world
+        .query_filtered::<(), With<Id<Unit>>>()
+        .iter(world)
+        .count();
+    let n_structures = world
+        .query_filtered::<(), With<Id<Structure>>>()
+        .iter(world)
+        .count();
+
+    println!("The world contains {n_units} units and {n_structures} structures.");
+}
+
+fn main() {
+    let config = StressTestConfig::from_args();
+    println!("Running stress test with {config:?}");
+
+    if config.rendered {
+        run_rendered(config);
+    } else {
+        run_headless(config);
+    }
+}
+
+/// Runs only the simulation, timing each schedule of the main loop separately.
+fn run_headless(config: StressTestConfig) {
+    let mut app = simulation_app(config.gen_config());
+    app.insert_resource(config.game_rules());
+    let timestep = app.world.resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+
+    let world_gen_start = Instant::now();
+    let mut world_gen_frames = 0;
+    while *app.world.resource::<State<WorldGenState>>().get() != WorldGenState::Complete {
+        assert!(
+            world_gen_frames < MAX_WORLD_GEN_FRAMES,
+            "World generation did not complete within {MAX_WORLD_GEN_FRAMES} frames"
+        );
+        app.update();
+        world_gen_frames += 1;
+    }
+    println!(
+        "World generation took {:.2} s over {world_gen_frames} frames.",
+        world_gen_start.elapsed().as_secs_f64()
+    );
+    print_population(&mut app.world);
+
+    // Startup schedules have already run, so each frame can be driven one schedule at a time
+    let mut schedule_timings: HashMap<String, Timings> = HashMap::default();
+    let mut frame_timings = Timings::default();
+    for _ in 0..config.frames {
+        let frame_start = Instant::now();
+        app.world
+            .resource_scope(|world, order: Mut<MainScheduleOrder>| {
+                for label in &order.labels {
+                    let schedule_start = Instant::now();
+                    let _ = world.try_run_schedule(&**label);
+                    schedule_timings
+                        .entry(format!("{label:?}"))
+                        .or_default()
+                        .record(schedule_start.elapsed());
+                }
+            });
+        app.world.clear_trackers();
+        frame_timings.record(frame_start.elapsed());
+    }
+
+    print_population(&mut app.world);
+    print_summary(
+        "Time spent in each schedule",
+        &schedule_timings,
+        frame_timings.total,
+    );
+    println!(
+        "\n{} frames took {:.2} s in total, {:.3} ms per frame on average, and {:.3} ms at worst.",
+        frame_timings.count,
+        frame_timings.total.as_secs_f64(),
+        frame_timings.mean().as_secs_f64() * 1000.,
+        frame_timings.max.as_secs_f64() * 1000.
+    );
+}
+
+/// Draws the game in a window, and times each frame once world generation completes.
+fn run_rendered(config: StressTestConfig) {
+    let mut app = App::new();
+
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "Emergence stress test".to_string(),
+            present_mode: bevy::window::PresentMode::AutoNoVsync,
+            ..default()
+        }),
+        ..default()
+    }))
+    .add_plugins(emergence_lib::asset_management::AssetManagementPlugin)
+    // The game state plugin is skipped, so that the world is generated immediately rather than waiting in the main menu
+    .add_plugins(SimulationPlugin {
+        gen_config: config.gen_config(),
+    })
+    .add_plugins(emergence_lib::player_interaction::InteractionPlugin)
+    .add_plugins(emergence_lib::graphics::GraphicsPlugin)
+    .add_plugins(emergence_lib::ui::UiPlugin)
+    .insert_resource(config.game_rules())
+    .insert_resource(config)
+    .init_resource::<FrameTimings>()
+    .add_systems(OnEnter(WorldGenState::Complete), print_population)
+    .add_systems(
+        Last,
+        record_frame_times.run_if(in_state(WorldGenState::Complete)),
+    );
+
+    app.run();
+}
+
+/// The frame times recorded in rendered mode.
+#[derive(Resource, Debug, Default)]
+struct FrameTimings {
+    /// The time at which the last frame was recorded.
+    last_frame: Option<Instant>,
+    /// The duration of each frame so far.
+    timings: Timings,
+}
+
+/// Records how long each frame took, and exits once enough frames have been recorded.
+fn record_frame_times(
+    mut frame_timings: ResMut<FrameTimings>,
+    config: Res<StressTestConfig>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let now = Instant::now();
+    if let Some(last_frame) = frame_timings.last_frame {
+        frame_timings.timings.record(now - last_frame);
+    }
+    frame_timings.last_frame = Some(now);
+
+    if frame_timings.timings.count >= config.frames {
+        let mut timings = HashMap::default();
+        timings.insert("frame".to_string(), frame_timings.timings.clone());
+        print_summary(
+            "Time spent on each frame",
+            &timings,
+            frame_timings.timings.total,
+        );
+        app_exit_events.send(AppExit);
+    }
+}
@@ -27,6 +27,9 @@ where
 
     /// The human-readable name associated with each Id.
     name_map: HashMap<Id<T>, String>,
+
+    /// The optional descriptive text associated with each Id, shown in the codex.
+    flavor_text_map: HashMap<Id<T>, String>,
 }
 
 impl<T: 'static, Data: Debug> Default for Manifest<T, Data> {
@@ -44,6 +47,7 @@ where
         Self {
             data_map: HashMap::default(),
             name_map: HashMap::default(),
+            flavor_text_map: HashMap::default(),
         }
     }
 
@@ -67,6 +71,27 @@ where
         self.name_map.insert(id, name);
     }
 
+    /// Adds an entry to the manifest, just like [`Manifest::insert`], along with its optional `flavor_text`.
+    pub fn insert_with_flavor_text(
+        &mut self,
+        name: String,
+        data: Data,
+        flavor_text: Option<String>,
+    ) {
+        let id = Id::from_name(name.clone());
+        self.insert(name, data);
+
+        match flavor_text {
+            Some(flavor_text) => self.flavor_text_map.insert(id, flavor_text),
+            None => self.flavor_text_map.remove(&id),
+        };
+    }
+
+    /// Returns the descriptive text associated with the provided `id`, if any.
+    pub fn flavor_text(&self, id: Id<T>) -> Option<&str> {
+        self.flavor_text_map.get(&id).map(String::as_str)
+    }
+
     /// Get the data entry for the given ID.
     ///
     /// # Panics
@@ -109,6 +134,27 @@ where
     pub fn variants(&self) -> impl IntoIterator<Item = Id<T>> + '_ {
         self.data_map.keys().copied()
     }
+
+    /// Returns the IDs of all entries whose name contains `query`, sorted by name.
+    ///
+    /// Matching ignores case, and treats spaces as underscores so that players can type names naturally.
+    /// An empty query matches nothing.
+    pub fn search(&self, query: &str) -> Vec<Id<T>> {
+        let query = query.trim().to_lowercase().replace(' ', "_");
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<(Id<T>, &String)> = self
+            .name_map
+            .iter()
+            .filter(|(_, name)| name.to_lowercase().contains(&query))
+            .map(|(id, name)| (*id, name))
+            .collect();
+        matches.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        matches.into_iter().map(|(id, _)| id).collect()
+    }
 }
 
 /// A plugin that adds the default manifests to the app.
@@ -155,3 +201,40 @@ impl Plugin for DummyManifestPlugin {
         app.insert_resource(recipe_manifest);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::structure_manifest::Structure;
+
+    fn test_manifest() -> Manifest<Structure, ()> {
+        let mut manifest = Manifest::new();
+        manifest.insert("acacia_seedling".to_string(), ());
+        manifest.insert("acacia".to_string(), ());
+        manifest.insert("leuco_chunk".to_string(), ());
+        manifest
+    }
+
+    #[test]
+    fn search_matches_substrings_in_name_order() {
+        let manifest = test_manifest();
+
+        let matches: Vec<&str> = manifest
+            .search("ACACIA")
+            .into_iter()
+            .map(|id| manifest.name(id))
+            .collect();
+        assert_eq!(matches, vec!["acacia", "acacia_seedling"]);
+    }
+
+    #[test]
+    fn search_treats_spaces_as_underscores() {
+        let manifest = test_manifest();
+
+        assert_eq!(
+            manifest.search("leuco chunk"),
+            vec![Id::from_name("leuco_chunk".to_string())]
+        );
+        assert!(manifest.search("  ").is_empty());
+    }
+}
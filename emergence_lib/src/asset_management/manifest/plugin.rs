@@ -2,7 +2,7 @@
 
 use std::marker::PhantomData;
 
-use bevy::prelude::*;
+use bevy::{asset::UntypedHandle, prelude::*};
 
 use crate::asset_management::{AssetCollectionExt, AssetState, Loadable};
 
@@ -85,12 +85,8 @@ where
         world.insert_resource(Self { handle });
     }
 
-    fn load_state(&self, asset_server: &AssetServer) -> Option<bevy::asset::LoadState> {
-        let load_state = asset_server.get_load_state(self.handle.clone_weak());
-
-        debug!("Load state: {load_state:?}");
-
-        load_state
+    fn handles(&self) -> Vec<UntypedHandle> {
+        vec![self.handle.clone_weak().untyped()]
     }
 }
 
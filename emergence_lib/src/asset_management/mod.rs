@@ -15,14 +15,18 @@ use std::{
     fmt::{Display, Formatter},
 };
 
-use self::manifest::plugin::DetectManifestCreationSet;
+use self::{
+    manifest::plugin::{DetectManifestCreationSet, ManifestPlugin},
+    models::RawModelManifest,
+};
 use bevy::{
-    asset::LoadState,
+    asset::{LoadState, UntypedHandle},
     prelude::*,
     utils::{get_short_name, HashMap},
 };
 
 pub mod manifest;
+pub mod models;
 
 /// Collects asset management systems and resources.
 pub struct AssetManagementPlugin;
@@ -30,6 +34,7 @@ pub struct AssetManagementPlugin;
 impl Plugin for AssetManagementPlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<AssetState>()
+            .add_plugins(ManifestPlugin::<RawModelManifest>::new())
             .init_resource::<AssetsToLoad>()
             .add_systems(
                 Update,
@@ -65,11 +70,22 @@ pub enum AssetState {
 pub struct AssetsToLoad {
     /// The set of [`Loadable`] types that still need to be loaded
     remaining: HashMap<TypeId, String>,
+    /// How far along each of the remaining [`Loadable`] types are, once they have started loading.
+    progress: HashMap<TypeId, LoadProgress>,
+    /// Should collections whose only unloaded assets have failed be treated as loaded?
+    skip_failures: bool,
 }
 
 impl Display for AssetsToLoad {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut remaining: Vec<String> = self.remaining.values().cloned().collect();
+        let mut remaining: Vec<String> = self
+            .remaining
+            .iter()
+            .map(|(type_id, name)| match self.progress.get(type_id) {
+                Some(progress) => format!("{name}: {}/{}", progress.loaded, progress.total),
+                None => name.clone(),
+            })
+            .collect();
         remaining.sort();
 
         write!(f, "{}", remaining.join("\n"))
@@ -96,6 +112,87 @@ impl AssetsToLoad {
     pub fn remove<T: Loadable>(&mut self) {
         let type_id = TypeId::of::<T>();
         self.remaining.remove(&type_id);
+        self.progress.remove(&type_id);
+    }
+
+    /// The number of assets that have loaded so far, out of the total number of assets that have started loading.
+    pub fn overall_progress(&self) -> (usize, usize) {
+        self.progress
+            .values()
+            .fold((0, 0), |(loaded, total), progress| {
+                (loaded + progress.loaded, total + progress.total)
+            })
+    }
+
+    /// The paths of all assets that failed to load, sorted alphabetically.
+    pub fn failures(&self) -> Vec<&str> {
+        let mut failures: Vec<&str> = self
+            .progress
+            .values()
+            .flat_map(|progress| progress.failed.iter().map(String::as_str))
+            .collect();
+        failures.sort();
+        failures
+    }
+
+    /// Gives up on any assets that failed to load, so that loading can finish without them.
+    ///
+    /// Anything that used these assets will be drawn with placeholder materials.
+    pub fn skip_failures(&mut self) {
+        self.skip_failures = true;
+    }
+
+    /// Records the latest [`LoadProgress`] for `T`, marking it as loaded if it is complete.
+    fn update_progress<T: Loadable>(&mut self, progress: LoadProgress) {
+        let type_id = TypeId::of::<T>();
+
+        if progress.is_complete() || (self.skip_failures && progress.is_stuck()) {
+            self.remove::<T>();
+        } else {
+            self.progress.insert(type_id, progress);
+        }
+    }
+}
+
+/// How far along the loading of a single [`Loadable`] collection is.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LoadProgress {
+    /// The number of assets that have finished loading.
+    pub loaded: usize,
+    /// The total number of assets in the collection.
+    pub total: usize,
+    /// The paths of the assets that failed to load.
+    pub failed: Vec<String>,
+}
+
+impl LoadProgress {
+    /// Tallies up the [`LoadState`] of each asset in a collection, along with its path.
+    fn new(load_states: impl IntoIterator<Item = (Option<LoadState>, String)>) -> Self {
+        let mut progress = LoadProgress::default();
+
+        for (load_state, path) in load_states {
+            progress.total += 1;
+            match load_state {
+                Some(LoadState::Loaded) => progress.loaded += 1,
+                Some(LoadState::Failed) => progress.failed.push(path),
+                _ => (),
+            }
+        }
+
+        progress.failed.sort();
+        progress
+    }
+
+    /// Have all of the assets loaded?
+    pub fn is_complete(&self) -> bool {
+        self.loaded == self.total
+    }
+
+    /// Has every asset either loaded or failed to load?
+    ///
+    /// Stuck collections will never finish loading without intervention.
+    pub fn is_stuck(&self) -> bool {
+        self.loaded + self.failed.len() == self.total && !self.failed.is_empty()
     }
 }
 
@@ -122,7 +219,7 @@ fn check_assets_loaded(
         info!("All assets loaded: transitioning to AssetState::Ready");
 
         next_state.set(AssetState::FullyLoaded);
-    } else {
+    } else if assets_to_load.is_changed() {
         info!("Waiting for assets to load:\n{}", *assets_to_load);
     }
 }
@@ -144,8 +241,8 @@ pub trait Loadable: Resource + Sized {
         assets_to_load.insert::<Self>();
     }
 
-    /// How far along are we in loading these assets?
-    fn load_state(&self, asset_server: &AssetServer) -> Option<LoadState>;
+    /// The handles to every asset in this collection, which must all be loaded before the game can start.
+    fn handles(&self) -> Vec<UntypedHandle>;
 
     /// A system that checks if the asset collection of type `T` loaded.
     fn check_loaded(
@@ -153,9 +250,36 @@ pub trait Loadable: Resource + Sized {
         asset_server: Res<AssetServer>,
         mut assets_to_load: ResMut<AssetsToLoad>,
     ) {
-        let load_state = asset_collection.load_state(&asset_server);
-        if load_state == Some(LoadState::Loaded) && assets_to_load.contains::<Self>() {
-            assets_to_load.remove::<Self>();
+        if !assets_to_load.contains::<Self>() {
+            return;
+        }
+
+        let load_states = asset_collection.handles().into_iter().map(|handle| {
+            let path = asset_server
+                .get_path(handle.id())
+                .map(|path| path.to_string())
+                .unwrap_or("unknown_path".to_string());
+
+            (asset_server.get_load_state(handle.id()), path)
+        });
+        let progress = LoadProgress::new(load_states);
+
+        let previous_failures = assets_to_load
+            .progress
+            .get(&TypeId::of::<Self>())
+            .map(|previous| previous.failed.as_slice())
+            .unwrap_or_default();
+        for path in &progress.failed {
+            if !previous_failures.contains(path) {
+                error!("Failed to load {path}");
+            }
+        }
+
+        // Avoid triggering change detection every frame
+        let unchanged = assets_to_load.progress.get(&TypeId::of::<Self>()) == Some(&progress);
+        let skipping = assets_to_load.skip_failures && progress.is_stuck();
+        if !unchanged || skipping {
+            assets_to_load.update_progress::<Self>(progress);
         }
     }
 }
@@ -181,3 +305,32 @@ impl AssetCollectionExt for App {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_assets_leave_loading_stuck() {
+        let progress = LoadProgress::new([
+            (Some(LoadState::Loaded), "a.png".to_string()),
+            (Some(LoadState::Loading), "b.png".to_string()),
+            (Some(LoadState::Failed), "c.png".to_string()),
+        ]);
+        assert_eq!(progress.loaded, 1);
+        assert_eq!(progress.total, 3);
+        assert_eq!(progress.failed, vec!["c.png".to_string()]);
+        assert!(!progress.is_complete());
+        assert!(!progress.is_stuck());
+
+        let progress = LoadProgress::new([
+            (Some(LoadState::Loaded), "a.png".to_string()),
+            (Some(LoadState::Failed), "c.png".to_string()),
+        ]);
+        assert!(progress.is_stuck());
+
+        let progress = LoadProgress::new([(Some(LoadState::Loaded), "a.png".to_string())]);
+        assert!(progress.is_complete());
+        assert!(!progress.is_stuck());
+    }
+}
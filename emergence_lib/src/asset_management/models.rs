@@ -0,0 +1,183 @@
+//! Describes how the gltF models of game objects are chosen and varied.
+//!
+//! By default, an object named `leuco` in the `structures` folder is drawn using `structures/leuco.gltf#Scene0`.
+//! Entries in the [`ModelManifest`] can point to a different scene instead,
+//! and refer to the named nodes within that scene to show growth stages, damage states and faction colors.
+//!
+//! Models are keyed by their folder and name, such as `structures/leuco` or `terrain/loam`.
+
+use bevy::{
+    asset::Asset,
+    reflect::{Reflect, TypePath, TypeUuid},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use super::manifest::{loader::IsRawManifest, Id, Manifest};
+
+/// The marker type for [`Id<Model>`](super::manifest::Id).
+#[derive(Reflect, Clone, Copy, PartialEq, Eq)]
+pub struct Model;
+/// Stores the read-only definitions for all models that differ from the defaults.
+pub type ModelManifest = Manifest<Model, ModelData>;
+
+impl ModelManifest {
+    /// The [`Id<Model>`] of the object called `name` whose assets are stored in `folder`.
+    pub fn model_id(folder: &str, name: &str) -> Id<Model> {
+        Id::from_name(format!("{folder}/{name}"))
+    }
+
+    /// The data for the model of the object called `name` in `folder`, if it differs from the default.
+    pub fn model(&self, folder: &str, name: &str) -> Option<&ModelData> {
+        self.data_map().get(&Self::model_id(folder, name))
+    }
+
+    /// The path to the scene used to draw the object called `name` in `folder`.
+    pub fn scene_path(&self, folder: &str, name: &str) -> String {
+        match self.model(folder, name) {
+            Some(model_data) => model_data.scene.clone(),
+            None => format!("{folder}/{name}.gltf#Scene0"),
+        }
+    }
+}
+
+/// Data stored in a [`ModelManifest`] for each [`Id<Model>`](super::manifest::Id).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelData {
+    /// The gltF scene to spawn, relative to the assets folder.
+    pub scene: String,
+    /// Nodes in the scene, by name, that are only shown in some states.
+    #[serde(default)]
+    pub conditional_nodes: HashMap<String, NodeCondition>,
+    /// Nodes in the scene, by name, whose materials are tinted with the color of the faction that owns the object.
+    #[serde(default)]
+    pub tinted_nodes: Vec<String>,
+    /// Nodes in the scene, by name, whose materials are replaced when the object is hovered or selected.
+    ///
+    /// If this is empty, only the usual outline is drawn.
+    #[serde(default)]
+    pub highlighted_nodes: Vec<String>,
+}
+
+impl ModelData {
+    /// Does this model refer to any named nodes?
+    pub fn has_named_nodes(&self) -> bool {
+        !self.conditional_nodes.is_empty()
+            || !self.tinted_nodes.is_empty()
+            || !self.highlighted_nodes.is_empty()
+    }
+
+    /// Iterates over the names of every node that this model refers to.
+    pub fn node_names(&self) -> impl Iterator<Item = &str> {
+        self.conditional_nodes
+            .keys()
+            .chain(self.tinted_nodes.iter())
+            .chain(self.highlighted_nodes.iter())
+            .map(String::as_str)
+    }
+}
+
+/// Controls when a node listed in [`ModelData::conditional_nodes`] is visible.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NodeCondition {
+    /// The state to check.
+    pub state: ModelState,
+    /// Is the node visible while the object is in this state?
+    ///
+    /// If `false`, the node is only visible while the object is not in this state.
+    pub visible: bool,
+}
+
+impl NodeCondition {
+    /// Should the node be visible, given whether or not the object is in [`NodeCondition::state`]?
+    pub fn is_visible(&self, in_state: bool) -> bool {
+        in_state == self.visible
+    }
+}
+
+/// A state of a game object that can change how its model is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ModelState {
+    /// The organism is dormant.
+    Dormant,
+    /// The structure has been switched off.
+    Disabled,
+    /// The organism has less than half of its maximum energy, and is visibly suffering.
+    Damaged,
+    /// The organism has made at least this fraction of its progress towards its next life stage.
+    Grown(f32),
+}
+
+/// The [`ModelManifest`] as seen in the manifest file.
+#[derive(Asset, Debug, Clone, Serialize, Deserialize, TypeUuid, TypePath, PartialEq)]
+#[uuid = "4b1d7a53-6c2e-4f89-9a3e-81c5d20f6e47"]
+pub struct RawModelManifest {
+    /// The data for each model, keyed by folder and name.
+    pub models: HashMap<String, ModelData>,
+}
+
+impl IsRawManifest for RawModelManifest {
+    const EXTENSION: &'static str = "model_manifest.json";
+
+    type Marker = Model;
+    type Data = ModelData;
+
+    fn process(&self) -> Manifest<Self::Marker, Self::Data> {
+        let mut manifest = Manifest::new();
+
+        for (raw_id, raw_data) in self.models.clone() {
+            manifest.insert(raw_id, raw_data)
+        }
+
+        manifest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn models_default_to_the_first_scene_in_their_folder() {
+        let mut manifest = ModelManifest::new();
+        manifest.insert(
+            "structures/leuco".to_string(),
+            ModelData {
+                scene: "structures/leuco_grown.gltf#Scene1".to_string(),
+                conditional_nodes: HashMap::default(),
+                tinted_nodes: vec!["Cap".to_string()],
+                highlighted_nodes: Vec::new(),
+            },
+        );
+
+        assert_eq!(
+            manifest.scene_path("structures", "leuco"),
+            "structures/leuco_grown.gltf#Scene1"
+        );
+        assert_eq!(
+            manifest.scene_path("structures", "acacia"),
+            "structures/acacia.gltf#Scene0"
+        );
+        assert_eq!(
+            manifest.scene_path("terrain", "leuco"),
+            "terrain/leuco.gltf#Scene0"
+        );
+    }
+
+    #[test]
+    fn conditional_nodes_can_be_shown_or_hidden() {
+        let shown_while_dormant = NodeCondition {
+            state: ModelState::Dormant,
+            visible: true,
+        };
+        assert!(shown_while_dormant.is_visible(true));
+        assert!(!shown_while_dormant.is_visible(false));
+
+        let hidden_while_dormant = NodeCondition {
+            state: ModelState::Dormant,
+            visible: false,
+        };
+        assert!(!hidden_while_dormant.is_visible(true));
+        assert!(hidden_while_dormant.is_visible(false));
+    }
+}
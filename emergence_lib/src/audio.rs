@@ -0,0 +1,84 @@
+//! Sound effects that acknowledge the player's actions.
+//!
+//! Like the [`GraphicsPlugin`](crate::graphics::GraphicsPlugin), this contains no gameplay logic,
+//! and only reacts to [`InteractionEvent`]s.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::player_interaction::feedback::InteractionEvent;
+
+/// Plays a sound for each [`InteractionEvent`].
+pub struct AudioFeedbackPlugin;
+
+impl Plugin for AudioFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InteractionSounds>()
+            .add_systems(Update, play_interaction_sounds);
+    }
+}
+
+/// The sounds played in response to the player's actions.
+///
+/// Events without a sound are silent.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct InteractionSounds {
+    /// Played when the selection changes.
+    pub selection_changed: Option<Handle<AudioSource>>,
+    /// Played when a tool is picked up.
+    pub tool_armed: Option<Handle<AudioSource>>,
+    /// Played when a structure is placed.
+    pub placement_succeeded: Option<Handle<AudioSource>>,
+    /// Played when a structure could not be placed.
+    pub placement_failed: Option<Handle<AudioSource>>,
+    /// Played when an action could not be carried out.
+    pub invalid_action: Option<Handle<AudioSource>>,
+}
+
+impl InteractionSounds {
+    /// The sound to play in response to `interaction_event`, if any.
+    fn get(&self, interaction_event: InteractionEvent) -> Option<&Handle<AudioSource>> {
+        match interaction_event {
+            InteractionEvent::SelectionChanged => self.selection_changed.as_ref(),
+            InteractionEvent::ToolArmed => self.tool_armed.as_ref(),
+            InteractionEvent::PlacementSucceeded(_) => self.placement_succeeded.as_ref(),
+            InteractionEvent::PlacementFailed(_) => self.placement_failed.as_ref(),
+            InteractionEvent::InvalidAction => self.invalid_action.as_ref(),
+        }
+    }
+}
+
+/// The minimum time between two plays of the same sound.
+///
+/// Dragging a selection or holding down the placement button can send many events in quick succession.
+const MIN_REPEAT_INTERVAL: Duration = Duration::from_millis(120);
+
+/// Plays the sound for each [`InteractionEvent`], skipping rapid repeats of the same sound.
+fn play_interaction_sounds(
+    mut interaction_events: EventReader<InteractionEvent>,
+    interaction_sounds: Res<InteractionSounds>,
+    time: Res<Time>,
+    mut last_played: Local<HashMap<AssetId<AudioSource>, Duration>>,
+    mut commands: Commands,
+) {
+    let now = time.elapsed();
+
+    for &interaction_event in interaction_events.read() {
+        let Some(sound) = interaction_sounds.get(interaction_event) else {
+            continue;
+        };
+
+        if let Some(&previous) = last_played.get(&sound.id()) {
+            if now.saturating_sub(previous) < MIN_REPEAT_INTERVAL {
+                continue;
+            }
+        }
+
+        last_played.insert(sound.id(), now);
+        commands.spawn(AudioBundle {
+            source: sound.clone(),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
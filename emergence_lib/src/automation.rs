@@ -0,0 +1,373 @@
+//! Simple rules that change how structures behave in response to the state of the colony.
+//!
+//! Each [`AutomationRule`] pairs a [`Condition`] with actions to take when the condition becomes satisfied,
+//! and when it stops being satisfied.
+//! For example, "if there are fewer than 20 leuco chunks, prioritize all leuco farms, otherwise return them to normal".
+//!
+//! Rules are only acted on when their condition changes, rather than on every tick,
+//! so the player remains free to override their effects by hand in the meantime.
+//! Structures that the player has marked as a [`Favorite`] are never touched by automation rules.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    crafting::{
+        inventories::{CraftingState, InputInventory, OutputInventory},
+        recipe::{ActiveRecipe, Recipe, RecipeManifest},
+    },
+    geometry::VoxelPos,
+    items::{item_manifest::ItemManifest, totals::ItemTotals},
+    litter::LitterCommandsExt,
+    milestones::conditions::{Condition, ConditionContext},
+    player_interaction::bulk_commands::{Disabled, Favorite, Forbidden, Prioritized},
+    simulation::{reports::DailyReports, time::InGameTime, weather::CurrentWeather, SimulationSet},
+    structures::structure_manifest::Structure,
+    units::census::Census,
+};
+
+/// Evaluates the player's [`AutomationRules`].
+pub(crate) struct AutomationPlugin;
+
+impl Plugin for AutomationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutomationRules>()
+            .add_systems(FixedUpdate, evaluate_automation_rules.in_set(SimulationSet));
+    }
+}
+
+/// A change to every structure of a given type, made by an [`AutomationRule`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleAction {
+    /// Switch every structure of this type on or off.
+    SetEnabled {
+        /// The type of structure to change.
+        structure_id: Id<Structure>,
+        /// Should these structures be switched on?
+        enabled: bool,
+    },
+    /// Forbid or allow hauling to and from every structure of this type.
+    SetForbidden {
+        /// The type of structure to change.
+        structure_id: Id<Structure>,
+        /// Should hauling be forbidden?
+        forbidden: bool,
+    },
+    /// Prioritize every structure of this type, or return them to normal priority.
+    SetPrioritized {
+        /// The type of structure to change.
+        structure_id: Id<Structure>,
+        /// Should these structures be prioritized?
+        prioritized: bool,
+    },
+    /// Switch every structure of this type to a different recipe, or clear their recipe.
+    ///
+    /// Any items held for the previous recipe are dropped on the ground.
+    SetRecipe {
+        /// The type of structure to change.
+        structure_id: Id<Structure>,
+        /// The recipe to craft from now on.
+        recipe_id: Option<Id<Recipe>>,
+    },
+}
+
+impl RuleAction {
+    /// The type of structure that this action changes.
+    pub fn structure_id(&self) -> Id<Structure> {
+        match self {
+            RuleAction::SetEnabled { structure_id, .. }
+            | RuleAction::SetForbidden { structure_id, .. }
+            | RuleAction::SetPrioritized { structure_id, .. }
+            | RuleAction::SetRecipe { structure_id, .. } => *structure_id,
+        }
+    }
+}
+
+/// A rule authored by the player: when `condition` changes, the matching actions are taken.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutomationRule {
+    /// A name for this rule, shown to the player.
+    pub name: String,
+    /// Is this rule currently being evaluated?
+    pub enabled: bool,
+    /// The condition that this rule watches.
+    pub condition: Condition,
+    /// The actions taken when the condition becomes satisfied.
+    pub when_satisfied: Vec<RuleAction>,
+    /// The actions taken when the condition stops being satisfied.
+    pub otherwise: Vec<RuleAction>,
+    /// Was the condition satisfied when this rule was last evaluated?
+    ///
+    /// This is `None` until the rule is first evaluated.
+    last_satisfied: Option<bool>,
+}
+
+impl AutomationRule {
+    /// Creates a new, enabled rule that has not yet been evaluated.
+    pub fn new(
+        name: impl Into<String>,
+        condition: Condition,
+        when_satisfied: Vec<RuleAction>,
+        otherwise: Vec<RuleAction>,
+    ) -> Self {
+        AutomationRule {
+            name: name.into(),
+            enabled: true,
+            condition,
+            when_satisfied,
+            otherwise,
+            last_satisfied: None,
+        }
+    }
+
+    /// Was the condition satisfied when this rule was last evaluated?
+    pub fn last_satisfied(&self) -> Option<bool> {
+        self.last_satisfied
+    }
+
+    /// Checks the condition, and returns the actions that should be taken as a result, if any.
+    fn evaluate(&mut self, context: &ConditionContext) -> &[RuleAction] {
+        if !self.enabled {
+            self.last_satisfied = None;
+            return &[];
+        }
+
+        let satisfied = self.condition.is_satisfied(context);
+        if self.last_satisfied == Some(satisfied) {
+            return &[];
+        }
+
+        self.last_satisfied = Some(satisfied);
+        match satisfied {
+            true => &self.when_satisfied,
+            false => &self.otherwise,
+        }
+    }
+}
+
+/// The player's automation rules, evaluated in order every tick.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct AutomationRules {
+    /// The rules, in the order in which they are evaluated.
+    rules: Vec<AutomationRule>,
+}
+
+impl AutomationRules {
+    /// Adds a new rule, to be evaluated after all existing rules.
+    pub fn push(&mut self, rule: AutomationRule) {
+        self.rules.push(rule);
+    }
+
+    /// Removes and returns the rule at `index`, if it exists.
+    pub fn remove(&mut self, index: usize) -> Option<AutomationRule> {
+        (index < self.rules.len()).then(|| self.rules.remove(index))
+    }
+
+    /// Swaps the rule at `index` with the one before it, so that it is evaluated earlier.
+    pub fn move_up(&mut self, index: usize) {
+        if index > 0 && index < self.rules.len() {
+            self.rules.swap(index - 1, index);
+        }
+    }
+
+    /// The rule at `index`, if it exists.
+    pub fn get(&self, index: usize) -> Option<&AutomationRule> {
+        self.rules.get(index)
+    }
+
+    /// A mutable reference to the rule at `index`, if it exists.
+    ///
+    /// Editing a rule causes it to be re-evaluated from scratch.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut AutomationRule> {
+        let rule = self.rules.get_mut(index)?;
+        rule.last_satisfied = None;
+        Some(rule)
+    }
+
+    /// Iterates over all rules, in the order in which they are evaluated.
+    pub fn iter(&self) -> impl Iterator<Item = &AutomationRule> {
+        self.rules.iter()
+    }
+
+    /// Evaluates every rule, returning the actions that should be taken, in order.
+    fn evaluate(&mut self, context: &ConditionContext) -> Vec<RuleAction> {
+        self.rules
+            .iter_mut()
+            .flat_map(|rule| rule.evaluate(context).to_vec())
+            .collect()
+    }
+}
+
+/// Evaluates each automation rule, and applies the actions of any whose condition has changed.
+fn evaluate_automation_rules(
+    mut automation_rules: ResMut<AutomationRules>,
+    structure_query: Query<
+        (
+            Entity,
+            &Id<Structure>,
+            Has<Disabled>,
+            Has<Forbidden>,
+            Has<Prioritized>,
+        ),
+        (Without<Ghost>, Without<Preview>, Without<Favorite>),
+    >,
+    mut crafter_query: Query<
+        (
+            &Id<Structure>,
+            &VoxelPos,
+            &mut ActiveRecipe,
+            &mut CraftingState,
+            &mut InputInventory,
+            &mut OutputInventory,
+        ),
+        (Without<Ghost>, Without<Preview>, Without<Favorite>),
+    >,
+    census: Res<Census>,
+    item_totals: Res<ItemTotals>,
+    daily_reports: Res<DailyReports>,
+    current_weather: Res<CurrentWeather>,
+    in_game_time: Res<InGameTime>,
+    recipe_manifest: Res<RecipeManifest>,
+    item_manifest: Res<ItemManifest>,
+    mut commands: Commands,
+) {
+    let context = ConditionContext {
+        census: &census,
+        item_totals: &item_totals,
+        daily_reports: &daily_reports,
+        current_weather: &current_weather,
+        elapsed_days: in_game_time.rounded_elapsed_days(),
+        season: in_game_time.season(),
+    };
+
+    // Avoid triggering change detection unless a rule actually fires
+    let actions = automation_rules
+        .bypass_change_detection()
+        .evaluate(&context);
+
+    for action in actions {
+        let structure_id = action.structure_id();
+        let matching_structures = structure_query
+            .iter()
+            .filter(|(_, &id, ..)| id == structure_id);
+
+        match action {
+            RuleAction::SetEnabled { enabled, .. } => {
+                for (entity, _, is_disabled, ..) in matching_structures {
+                    match (enabled, is_disabled) {
+                        (true, true) => commands.entity(entity).remove::<Disabled>(),
+                        (false, false) => commands.entity(entity).insert(Disabled),
+                        _ => continue,
+                    };
+                }
+            }
+            RuleAction::SetForbidden { forbidden, .. } => {
+                for (entity, _, _, is_forbidden, _) in matching_structures {
+                    match (forbidden, is_forbidden) {
+                        (true, false) => commands.entity(entity).insert(Forbidden),
+                        (false, true) => commands.entity(entity).remove::<Forbidden>(),
+                        _ => continue,
+                    };
+                }
+            }
+            RuleAction::SetPrioritized { prioritized, .. } => {
+                for (entity, .., is_prioritized) in matching_structures {
+                    match (prioritized, is_prioritized) {
+                        (true, false) => commands.entity(entity).insert(Prioritized),
+                        (false, true) => commands.entity(entity).remove::<Prioritized>(),
+                        _ => continue,
+                    };
+                }
+            }
+            RuleAction::SetRecipe { recipe_id, .. } => {
+                for (
+                    &id,
+                    &voxel_pos,
+                    mut active_recipe,
+                    mut crafting_state,
+                    mut input_inventory,
+                    mut output_inventory,
+                ) in crafter_query.iter_mut()
+                {
+                    if id != structure_id || *active_recipe.recipe_id() == recipe_id {
+                        continue;
+                    }
+
+                    // Items gathered for the old recipe are no longer needed here
+                    for item_slot in input_inventory.iter().chain(output_inventory.iter()) {
+                        for _ in 0..item_slot.count() {
+                            commands.spawn_litter(voxel_pos, item_slot.item_id());
+                        }
+                    }
+
+                    match recipe_id {
+                        Some(recipe_id) => {
+                            let recipe = recipe_manifest.get(recipe_id);
+                            *active_recipe = ActiveRecipe::new(recipe_id);
+                            *input_inventory = recipe.input_inventory(&item_manifest);
+                            *output_inventory = recipe.output_inventory(&item_manifest);
+                            *crafting_state = CraftingState::NeedsInput;
+                        }
+                        None => {
+                            *active_recipe = ActiveRecipe::NONE;
+                            *input_inventory = InputInventory::NULL;
+                            *output_inventory = OutputInventory::default();
+                            *crafting_state = CraftingState::NoRecipe;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::time::Season;
+
+    use super::*;
+
+    #[test]
+    fn rules_only_fire_when_their_condition_changes() {
+        let structure_id = Id::from_name("leuco".to_string());
+        let prioritize = RuleAction::SetPrioritized {
+            structure_id,
+            prioritized: true,
+        };
+        let deprioritize = RuleAction::SetPrioritized {
+            structure_id,
+            prioritized: false,
+        };
+
+        let mut rules = AutomationRules::default();
+        rules.push(AutomationRule::new(
+            "Winter preparations",
+            Condition::Season(Season::Autumn),
+            vec![prioritize.clone()],
+            vec![deprioritize.clone()],
+        ));
+
+        let census = Census::default();
+        let item_totals = ItemTotals::default();
+        let daily_reports = DailyReports::default();
+        let current_weather = CurrentWeather::default();
+        let context = |season| ConditionContext {
+            census: &census,
+            item_totals: &item_totals,
+            daily_reports: &daily_reports,
+            current_weather: &current_weather,
+            elapsed_days: 0,
+            season,
+        };
+
+        assert_eq!(rules.evaluate(&context(Season::Summer)), vec![deprioritize]);
+        assert!(rules.evaluate(&context(Season::Summer)).is_empty());
+        assert_eq!(rules.evaluate(&context(Season::Autumn)), vec![prioritize]);
+        assert!(rules.evaluate(&context(Season::Autumn)).is_empty());
+
+        rules.get_mut(0).unwrap().enabled = false;
+        assert!(rules.evaluate(&context(Season::Summer)).is_empty());
+        assert_eq!(rules.get(0).unwrap().last_satisfied(), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Command line tools that run the game without a window.
+//!
+//! ```text
+//! cargo run --release --features cli --bin emergence_cli -- <command>
+//! ```
+//!
+//! The available commands are:
+//!
+//! - `worldgen --seed N [--radius N] --out map.scenario`: generates a world and saves it as a [`Scenario`],
+//!   which can be played from the new game menu or opened in the map editor.
+//! - `simulate [--scenario map.scenario] [--seed N] --ticks N [--metrics out.csv]`: runs the simulation for a fixed number of ticks,
+//!   optionally writing the [`ColonyMetrics`] of each completed in-game day to a CSV file.
+//! - `save inspect file.emsave`: checks that a save file can be read, and prints its header.
+//!
+//! Time is advanced by exactly one simulation tick per frame, so results do not depend on the speed of the machine.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use emergence_lib::{
+    save_files::SaveFile,
+    simulation::metrics::{ColonyMetrics, DailyMetrics},
+    testing::regression::generate_world,
+    world_gen::{scenario::Scenario, GenerationConfig},
+};
+
+/// How to use this tool, printed when the arguments can't be understood.
+const USAGE: &str = "Usage:
+    emergence_cli worldgen --seed N [--radius N] --out map.scenario
+    emergence_cli simulate [--scenario map.scenario] [--seed N] --ticks N [--metrics out.csv]
+    emergence_cli save inspect file.emsave";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("worldgen") => Flags::parse(&args[1..]).and_then(worldgen),
+        Some("simulate") => Flags::parse(&args[1..]).and_then(simulate),
+        Some("save") => match (args.get(1).map(String::as_str), args.get(2)) {
+            (Some("inspect"), Some(path)) if args.len() == 3 => inspect_save(path),
+            _ => Err(USAGE.to_string()),
+        },
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The `--flag value` pairs passed to a command.
+#[derive(Debug, Default)]
+struct Flags {
+    /// The world generation seed.
+    seed: Option<u64>,
+    /// The radius of the generated map.
+    radius: Option<u32>,
+    /// The scenario to load, rather than generating a world.
+    scenario: Option<PathBuf>,
+    /// The number of simulation ticks to run for.
+    ticks: Option<u32>,
+    /// Where to write daily metrics to.
+    metrics: Option<PathBuf>,
+    /// Where to write the generated world to.
+    out: Option<PathBuf>,
+}
+
+impl Flags {
+    /// Parses a list of `--flag value` pairs.
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut flags = Flags::default();
+
+        for pair in args.chunks(2) {
+            let [flag, value] = pair else {
+                return Err(format!("{} is missing a value\n\n{USAGE}", pair[0]));
+            };
+
+            match flag.as_str() {
+                "--seed" => flags.seed = Some(parse_number(flag, value)?),
+                "--radius" => flags.radius = Some(parse_number(flag, value)?),
+                "--ticks" => flags.ticks = Some(parse_number(flag, value)?),
+                "--scenario" => flags.scenario = Some(PathBuf::from(value)),
+                "--metrics" => flags.metrics = Some(PathBuf::from(value)),
+                "--out" => flags.out = Some(PathBuf::from(value)),
+                _ => return Err(format!("Unrecognized flag {flag}\n\n{USAGE}")),
+            }
+        }
+
+        Ok(flags)
+    }
+
+    /// The world generation settings described by these flags.
+    fn gen_config(&self) -> Result<GenerationConfig, String> {
+        let mut gen_config = GenerationConfig::standard();
+
+        if let Some(seed) = self.seed {
+            gen_config.seed = seed;
+        }
+
+        if let Some(radius) = self.radius {
+            gen_config.map_radius = radius;
+        }
+
+        if let Some(path) = &self.scenario {
+            let scenario = Scenario::load(path)
+                .map_err(|error| format!("Could not load {}: {error}", path.display()))?;
+            gen_config.scenario = Some(scenario);
+        }
+
+        Ok(gen_config)
+    }
+}
+
+/// Parses the `value` passed to `flag` as a number.
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{flag} expects a number, but was given {value}"))
+}
+
+/// Generates a world and saves it as a scenario.
+fn worldgen(flags: Flags) -> Result<(), String> {
+    let Some(out) = flags.out.clone() else {
+        return Err(format!("worldgen requires --out\n\n{USAGE}"));
+    };
+
+    let mut app = generate_world(flags.gen_config()?);
+    let name = out
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .replace('_', " ");
+    let scenario = Scenario::capture(&mut app.world, name);
+
+    scenario
+        .save(&out)
+        .map_err(|error| format!("Could not save {}: {error}", out.display()))?;
+
+    println!(
+        "Saved a world with {} tiles and {} structures to {}",
+        scenario.tiles.len(),
+        scenario.structures.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+/// Runs the simulation for a fixed number of ticks, recording metrics for each completed day.
+fn simulate(flags: Flags) -> Result<(), String> {
+    let Some(ticks) = flags.ticks else {
+        return Err(format!("simulate requires --ticks\n\n{USAGE}"));
+    };
+
+    let mut metrics_writer = match &flags.metrics {
+        Some(path) => {
+            let file = File::create(path)
+                .map_err(|error| format!("Could not create {}: {error}", path.display()))?;
+            let mut writer = BufWriter::new(file);
+            writeln!(writer, "{}", DailyMetrics::CSV_HEADER).map_err(|error| error.to_string())?;
+            Some(writer)
+        }
+        None => None,
+    };
+
+    let mut app = generate_world(flags.gen_config()?);
+    let mut last_recorded_day = None;
+
+    for _ in 0..ticks {
+        app.update();
+
+        let Some(writer) = &mut metrics_writer else {
+            continue;
+        };
+
+        // Days are recorded as soon as they are completed, as only a few days of history are retained
+        let colony_metrics = app.world.resource::<ColonyMetrics>();
+        if let Some(latest) = colony_metrics.latest() {
+            if last_recorded_day != Some(latest.day) {
+                writeln!(writer, "{}", latest.to_csv_row()).map_err(|error| error.to_string())?;
+                last_recorded_day = Some(latest.day);
+            }
+        }
+    }
+
+    if let Some(mut writer) = metrics_writer {
+        writer.flush().map_err(|error| error.to_string())?;
+    }
+
+    let colony_metrics = app.world.resource::<ColonyMetrics>();
+    println!(
+        "Simulated {ticks} ticks.\n\nToday so far:\n{}",
+        colony_metrics.today()
+    );
+    Ok(())
+}
+
+/// Reads the save file at `path`, and prints its header.
+fn inspect_save(path: &str) -> Result<(), String> {
+    let save_file =
+        SaveFile::load(path.as_ref()).map_err(|error| format!("Could not read {path}: {error}"))?;
+    let summary = &save_file.summary;
+    let metadata = &summary.metadata;
+
+    println!("{path}");
+    println!("Game version: {}", metadata.game_version);
+    println!(
+        "Mods: {}",
+        if metadata.mods.is_empty() {
+            "none".to_string()
+        } else {
+            metadata.mods.join(", ")
+        }
+    );
+    println!("Play time: {:.0} s", metadata.play_time.as_secs_f32());
+    println!("Elapsed days: {:.1}", metadata.elapsed_days);
+    println!("Game rules: {:?}", metadata.game_rules);
+    println!(
+        "Thumbnail: {}",
+        summary
+            .thumbnail
+            .as_ref()
+            .map_or("none".to_string(), |thumbnail| format!(
+                "{} bytes",
+                thumbnail.len()
+            ))
+    );
+    println!("World: {} bytes uncompressed", save_file.world.len());
+    Ok(())
+}
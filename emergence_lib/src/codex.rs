@@ -0,0 +1,737 @@
+//! An in-game encyclopedia, generated from the manifests.
+//!
+//! The [`Codex`] has one [`CodexEntry`] for every item, recipe, structure, terrain type and unit type.
+//! Entries are cross-linked in both directions, so that players can follow the production graph:
+//! an item lists the recipes it is used in and produced by, and each of those recipes links back to it.
+//!
+//! This module only stores the data: it is up to the UI to decide how to show it.
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    asset_management::{manifest::Id, AssetState},
+    construction::ConstructionStrategy,
+    crafting::{
+        inventories::InputInventory,
+        item_tags::ItemKind,
+        recipe::{Recipe, RecipeInput, RecipeManifest},
+    },
+    items::item_manifest::{Item, ItemManifest},
+    organisms::OrganismId,
+    structures::structure_manifest::{Structure, StructureManifest},
+    terrain::terrain_manifest::{Terrain, TerrainManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+};
+
+/// Builds the [`Codex`] once the manifests have loaded.
+pub struct CodexPlugin;
+
+impl Plugin for CodexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Codex>()
+            .add_systems(OnEnter(AssetState::FullyLoaded), build_codex);
+    }
+}
+
+/// The thing that a [`CodexEntry`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodexSubject {
+    /// A variety of item.
+    Item(Id<Item>),
+    /// A recipe.
+    Recipe(Id<Recipe>),
+    /// A variety of structure, including plants and fungi.
+    Structure(Id<Structure>),
+    /// A terrain type.
+    Terrain(Id<Terrain>),
+    /// A variety of unit.
+    Unit(Id<Unit>),
+}
+
+impl From<OrganismId> for CodexSubject {
+    fn from(organism_id: OrganismId) -> Self {
+        match organism_id {
+            OrganismId::Structure(structure_id) => CodexSubject::Structure(structure_id),
+            OrganismId::Unit(unit_id) => CodexSubject::Unit(unit_id),
+        }
+    }
+}
+
+/// How one [`CodexSubject`] relates to another.
+///
+/// Every relation has an [`inverse`](CodexRelation::inverse), which is used for the link back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodexRelation {
+    /// This item is consumed by the linked recipe.
+    UsedIn,
+    /// This recipe consumes the linked item.
+    Requires,
+    /// This recipe produces the linked item.
+    Produces,
+    /// This item is produced by the linked recipe.
+    ProducedBy,
+    /// This recipe hatches the linked unit.
+    Hatches,
+    /// This unit is hatched by the linked recipe.
+    HatchedBy,
+    /// This structure crafts the linked recipe when it is first built.
+    Crafts,
+    /// This recipe is crafted by the linked structure.
+    CraftedAt,
+    /// This structure is built out of the linked item.
+    BuiltFrom,
+    /// This item is used to build the linked structure.
+    UsedToBuild,
+    /// This structure can be harvested for the linked item.
+    Yields,
+    /// This item can be harvested from the linked structure.
+    HarvestedFrom,
+    /// This unit eats the linked item.
+    Eats,
+    /// This item is eaten by the linked unit.
+    EatenBy,
+    /// This organism, or seed, can grow into the linked organism.
+    GrowsInto,
+    /// This organism can grow from the linked organism or seed.
+    GrowsFrom,
+    /// This organism leaves the linked item behind when it dies.
+    LeavesBehind,
+    /// This item is left behind by the linked organism when it dies.
+    RemainsOf,
+}
+
+impl CodexRelation {
+    /// The relation seen from the other end of the link.
+    pub fn inverse(&self) -> CodexRelation {
+        match self {
+            CodexRelation::UsedIn => CodexRelation::Requires,
+            CodexRelation::Requires => CodexRelation::UsedIn,
+            CodexRelation::Produces => CodexRelation::ProducedBy,
+            CodexRelation::ProducedBy => CodexRelation::Produces,
+            CodexRelation::Hatches => CodexRelation::HatchedBy,
+            CodexRelation::HatchedBy => CodexRelation::Hatches,
+            CodexRelation::Crafts => CodexRelation::CraftedAt,
+            CodexRelation::CraftedAt => CodexRelation::Crafts,
+            CodexRelation::BuiltFrom => CodexRelation::UsedToBuild,
+            CodexRelation::UsedToBuild => CodexRelation::BuiltFrom,
+            CodexRelation::Yields => CodexRelation::HarvestedFrom,
+            CodexRelation::HarvestedFrom => CodexRelation::Yields,
+            CodexRelation::Eats => CodexRelation::EatenBy,
+            CodexRelation::EatenBy => CodexRelation::Eats,
+            CodexRelation::GrowsInto => CodexRelation::GrowsFrom,
+            CodexRelation::GrowsFrom => CodexRelation::GrowsInto,
+            CodexRelation::LeavesBehind => CodexRelation::RemainsOf,
+            CodexRelation::RemainsOf => CodexRelation::LeavesBehind,
+        }
+    }
+}
+
+impl Display for CodexRelation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            CodexRelation::UsedIn => "Used in",
+            CodexRelation::Requires => "Requires",
+            CodexRelation::Produces => "Produces",
+            CodexRelation::ProducedBy => "Produced by",
+            CodexRelation::Hatches => "Hatches",
+            CodexRelation::HatchedBy => "Hatched by",
+            CodexRelation::Crafts => "Crafts",
+            CodexRelation::CraftedAt => "Crafted at",
+            CodexRelation::BuiltFrom => "Built from",
+            CodexRelation::UsedToBuild => "Used to build",
+            CodexRelation::Yields => "Yields",
+            CodexRelation::HarvestedFrom => "Harvested from",
+            CodexRelation::Eats => "Eats",
+            CodexRelation::EatenBy => "Eaten by",
+            CodexRelation::GrowsInto => "Grows into",
+            CodexRelation::GrowsFrom => "Grows from",
+            CodexRelation::LeavesBehind => "Leaves behind",
+            CodexRelation::RemainsOf => "Remains of",
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// A link from one [`CodexEntry`] to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CodexLink {
+    /// How the linked subject relates to the entry that this link belongs to.
+    pub relation: CodexRelation,
+    /// The linked subject.
+    pub target: CodexSubject,
+}
+
+/// A single page of the [`Codex`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodexEntry {
+    /// The subject of this entry.
+    pub subject: CodexSubject,
+    /// The name of the subject.
+    pub name: String,
+    /// The descriptive text for the subject from its manifest, if any.
+    pub flavor_text: Option<String>,
+    /// Short, human-readable facts about the subject, taken from its manifest.
+    pub facts: Vec<String>,
+    /// Links to related entries, sorted by relation.
+    pub links: Vec<CodexLink>,
+}
+
+impl CodexEntry {
+    /// Creates a new entry with no links.
+    fn new(
+        subject: CodexSubject,
+        name: &str,
+        flavor_text: Option<&str>,
+        facts: Vec<String>,
+    ) -> Self {
+        CodexEntry {
+            subject,
+            name: name.to_string(),
+            flavor_text: flavor_text.map(str::to_string),
+            facts,
+            links: Vec::new(),
+        }
+    }
+
+    /// The subjects linked to this entry by the given `relation`.
+    pub fn linked(&self, relation: CodexRelation) -> impl Iterator<Item = CodexSubject> + '_ {
+        self.links
+            .iter()
+            .filter(move |link| link.relation == relation)
+            .map(|link| link.target)
+    }
+}
+
+/// The in-game encyclopedia, with one [`CodexEntry`] for each entry in the manifests.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct Codex {
+    /// The entry for each subject.
+    entries: HashMap<CodexSubject, CodexEntry>,
+}
+
+impl Codex {
+    /// Generates the codex from the contents of the manifests.
+    pub fn new(
+        item_manifest: &ItemManifest,
+        recipe_manifest: &RecipeManifest,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+        unit_manifest: &UnitManifest,
+    ) -> Self {
+        let mut codex = Codex::default();
+
+        for (&item_id, item_data) in item_manifest.data_map() {
+            let mut facts = vec![format!("Stack size: {}", item_data.stack_size)];
+            let tags = item_manifest.tags(item_id);
+            if !tags.is_empty() {
+                facts.push(format!(
+                    "Tags: {}",
+                    tags.iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+
+            codex.add_entry(CodexEntry::new(
+                CodexSubject::Item(item_id),
+                item_manifest.name(item_id),
+                item_manifest.flavor_text(item_id),
+                facts,
+            ));
+        }
+
+        for (&recipe_id, recipe_data) in recipe_manifest.data_map() {
+            let mut facts = vec![format!(
+                "Craft time: {:.1} s",
+                recipe_data.craft_time.as_secs_f32()
+            )];
+            if let Some(energy) = recipe_data.energy {
+                facts.push(format!("Energy: {:.0}", energy.0));
+            }
+            if recipe_data.conditions != Default::default() {
+                facts.push(format!("Requires {}", recipe_data.conditions));
+            }
+
+            codex.add_entry(CodexEntry::new(
+                CodexSubject::Recipe(recipe_id),
+                recipe_manifest.name(recipe_id),
+                recipe_manifest.flavor_text(recipe_id),
+                facts,
+            ));
+        }
+
+        for (&structure_id, structure_data) in structure_manifest.data_map() {
+            let mut facts = Vec::new();
+            if structure_data.max_workers > 0 {
+                facts.push(format!("Workers: {}", structure_data.max_workers));
+            }
+            if structure_data.can_walk_through {
+                facts.push("Can be walked through".to_string());
+            }
+
+            codex.add_entry(CodexEntry::new(
+                CodexSubject::Structure(structure_id),
+                structure_manifest.name(structure_id),
+                structure_manifest.flavor_text(structure_id),
+                facts,
+            ));
+        }
+
+        for (&terrain_id, terrain_data) in terrain_manifest.data_map() {
+            let mut facts = vec![
+                format!("Walking speed: {:.0}%", terrain_data.walking_speed * 100.),
+                format!("Fertility: {:.0}", terrain_data.base_fertility),
+                format!("Water capacity: {:.2}", terrain_data.soil_water_capacity.0),
+            ];
+            if !terrain_data.buildable {
+                facts.push("Cannot be built on".to_string());
+            }
+
+            codex.add_entry(CodexEntry::new(
+                CodexSubject::Terrain(terrain_id),
+                terrain_manifest.name(terrain_id),
+                terrain_manifest.flavor_text(terrain_id),
+                facts,
+            ));
+        }
+
+        for (&unit_id, unit_data) in unit_manifest.data_map() {
+            let facts = vec![
+                format!("Speed: {:.0}%", unit_data.speed * 100.),
+                format!("Carry capacity: {}", unit_data.carry_capacity),
+                format!("Lifespan: {:.0} days", unit_data.max_age.0),
+            ];
+
+            codex.add_entry(CodexEntry::new(
+                CodexSubject::Unit(unit_id),
+                unit_manifest.name(unit_id),
+                unit_manifest.flavor_text(unit_id),
+                facts,
+            ));
+        }
+
+        codex.link_recipes(item_manifest, recipe_manifest);
+        codex.link_structures(item_manifest, structure_manifest);
+        codex.link_units(item_manifest, unit_manifest);
+        codex.link_seeds(item_manifest);
+        codex.sort_links();
+
+        codex
+    }
+
+    /// The entry for `subject`, if it exists.
+    pub fn get(&self, subject: CodexSubject) -> Option<&CodexEntry> {
+        self.entries.get(&subject)
+    }
+
+    /// Every entry in the codex, sorted by name.
+    pub fn entries(&self) -> Vec<&CodexEntry> {
+        let mut entries: Vec<&CodexEntry> = self.entries.values().collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+    }
+
+    /// The entries whose name contains `query`, sorted by name.
+    ///
+    /// Like [`Manifest::search`](crate::asset_management::manifest::Manifest::search),
+    /// matching ignores case and treats spaces as underscores.
+    pub fn search(&self, query: &str) -> Vec<&CodexEntry> {
+        let query = query.trim().to_lowercase().replace(' ', "_");
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        self.entries()
+            .into_iter()
+            .filter(|entry| entry.name.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Adds `entry` to the codex.
+    fn add_entry(&mut self, entry: CodexEntry) {
+        self.entries.insert(entry.subject, entry);
+    }
+
+    /// Links `from` to `to` with the given `relation`, and links `to` back to `from` with its inverse.
+    ///
+    /// Links to subjects that are missing from the manifests are skipped, as are duplicate links.
+    fn link(&mut self, from: CodexSubject, relation: CodexRelation, to: CodexSubject) {
+        if !self.entries.contains_key(&from) || !self.entries.contains_key(&to) {
+            return;
+        }
+
+        for (subject, link) in [
+            (
+                from,
+                CodexLink {
+                    relation,
+                    target: to,
+                },
+            ),
+            (
+                to,
+                CodexLink {
+                    relation: relation.inverse(),
+                    target: from,
+                },
+            ),
+        ] {
+            let links = &mut self.entries.get_mut(&subject).unwrap().links;
+            if !links.contains(&link) {
+                links.push(link);
+            }
+        }
+    }
+
+    /// Links each recipe to its inputs, outputs and hatchlings.
+    fn link_recipes(&mut self, item_manifest: &ItemManifest, recipe_manifest: &RecipeManifest) {
+        for (&recipe_id, recipe_data) in recipe_manifest.data_map() {
+            let recipe = CodexSubject::Recipe(recipe_id);
+
+            let input_ids: Vec<Id<Item>> = match &recipe_data.inputs {
+                RecipeInput::Exact(inputs) => inputs.iter().map(|input| input.item_id).collect(),
+                RecipeInput::Flexible { tag, .. } => item_manifest
+                    .variants()
+                    .into_iter()
+                    .filter(|&item_id| item_manifest.has_tag(item_id, *tag))
+                    .collect(),
+            };
+            for item_id in input_ids {
+                self.link(recipe, CodexRelation::Requires, CodexSubject::Item(item_id));
+            }
+
+            for item_id in recipe_data.outputs.item_ids() {
+                self.link(recipe, CodexRelation::Produces, CodexSubject::Item(item_id));
+            }
+
+            if let Some(unit_id) = recipe_data.hatches {
+                self.link(recipe, CodexRelation::Hatches, CodexSubject::Unit(unit_id));
+            }
+        }
+    }
+
+    /// Links each structure to its recipe, building materials, harvests, growth and remains.
+    fn link_structures(
+        &mut self,
+        item_manifest: &ItemManifest,
+        structure_manifest: &StructureManifest,
+    ) {
+        for (&structure_id, structure_data) in structure_manifest.data_map() {
+            let structure = CodexSubject::Structure(structure_id);
+
+            if let Some(recipe_id) = structure_data.starting_recipe().recipe_id() {
+                self.link(
+                    structure,
+                    CodexRelation::Crafts,
+                    CodexSubject::Recipe(*recipe_id),
+                );
+            }
+
+            // Seedlings are built from the materials of the structure they grow into
+            if let ConstructionStrategy::Direct(construction_data) =
+                &structure_data.construction_strategy
+            {
+                let material_ids: Vec<Id<Item>> = match &construction_data.materials {
+                    InputInventory::Exact { inventory } => {
+                        inventory.iter().map(|slot| slot.item_id()).collect()
+                    }
+                    InputInventory::Tagged { tag, .. } => item_manifest
+                        .variants()
+                        .into_iter()
+                        .filter(|&item_id| item_manifest.has_tag(item_id, *tag))
+                        .collect(),
+                };
+
+                for item_id in material_ids {
+                    self.link(
+                        structure,
+                        CodexRelation::BuiltFrom,
+                        CodexSubject::Item(item_id),
+                    );
+                }
+            }
+
+            if let Some(resource_node) = &structure_data.resource_node {
+                self.link(
+                    structure,
+                    CodexRelation::Yields,
+                    CodexSubject::Item(resource_node.item),
+                );
+            }
+
+            if let Some(organism_variety) = &structure_data.organism_variety {
+                for life_path in organism_variety.lifecycle.life_paths() {
+                    self.link(
+                        structure,
+                        CodexRelation::GrowsInto,
+                        life_path.new_form.into(),
+                    );
+                }
+
+                if let Some(corpse_id) = organism_variety.corpse {
+                    self.link(
+                        structure,
+                        CodexRelation::LeavesBehind,
+                        CodexSubject::Item(corpse_id),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Links each unit to its food, growth and remains.
+    fn link_units(&mut self, item_manifest: &ItemManifest, unit_manifest: &UnitManifest) {
+        for (&unit_id, unit_data) in unit_manifest.data_map() {
+            let unit = CodexSubject::Unit(unit_id);
+
+            let food_ids: Vec<Id<Item>> = match unit_data.diet.item_kind() {
+                ItemKind::Single(item_id) => vec![item_id],
+                ItemKind::Tag(tag) => item_manifest
+                    .variants()
+                    .into_iter()
+                    .filter(|&item_id| item_manifest.has_tag(item_id, tag))
+                    .collect(),
+            };
+            for item_id in food_ids {
+                self.link(unit, CodexRelation::Eats, CodexSubject::Item(item_id));
+            }
+
+            let organism_variety = &unit_data.organism_variety;
+            for life_path in organism_variety.lifecycle.life_paths() {
+                self.link(unit, CodexRelation::GrowsInto, life_path.new_form.into());
+            }
+
+            if let Some(corpse_id) = organism_variety.corpse {
+                self.link(
+                    unit,
+                    CodexRelation::LeavesBehind,
+                    CodexSubject::Item(corpse_id),
+                );
+            }
+        }
+    }
+
+    /// Links each seed to the organism that it grows into.
+    fn link_seeds(&mut self, item_manifest: &ItemManifest) {
+        for (&item_id, item_data) in item_manifest.data_map() {
+            if let Some(organism_id) = item_data.seed {
+                self.link(
+                    CodexSubject::Item(item_id),
+                    CodexRelation::GrowsInto,
+                    organism_id.into(),
+                );
+            }
+        }
+    }
+
+    /// Sorts the links of every entry by relation, and then by the name of their target.
+    fn sort_links(&mut self) {
+        let names: HashMap<CodexSubject, String> = self
+            .entries
+            .iter()
+            .map(|(&subject, entry)| (subject, entry.name.clone()))
+            .collect();
+
+        for entry in self.entries.values_mut() {
+            entry
+                .links
+                .sort_by_key(|link| (link.relation as u8, names[&link.target].clone()));
+        }
+    }
+}
+
+/// Generates the [`Codex`] from the loaded manifests.
+fn build_codex(
+    item_manifest: Res<ItemManifest>,
+    recipe_manifest: Res<RecipeManifest>,
+    structure_manifest: Res<StructureManifest>,
+    terrain_manifest: Res<TerrainManifest>,
+    unit_manifest: Res<UnitManifest>,
+    mut codex: ResMut<Codex>,
+) {
+    *codex = Codex::new(
+        &item_manifest,
+        &recipe_manifest,
+        &structure_manifest,
+        &terrain_manifest,
+        &unit_manifest,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        crafting::recipe::{RecipeConditions, RecipeData, RecipeOutput},
+        items::{item_manifest::ItemData, ItemCount},
+        structures::structure_manifest::StructureData,
+        terrain::terrain_manifest::TerrainData,
+        units::{basic_needs::Diet, unit_manifest::UnitData},
+    };
+
+    use super::*;
+
+    fn item(stack_size: u32) -> ItemData {
+        ItemData {
+            stack_size,
+            compostable: false,
+            fluid: false,
+            buoyant: false,
+            seed: None,
+            corpse: false,
+        }
+    }
+
+    fn codex() -> Codex {
+        let mut item_manifest = ItemManifest::new();
+        item_manifest.insert_with_flavor_text(
+            "food".to_string(),
+            item(5),
+            Some("Tasty.".to_string()),
+        );
+        item_manifest.insert("water".to_string(), item(10));
+
+        let mut recipe_manifest = RecipeManifest::new();
+        recipe_manifest.insert(
+            "cooking".to_string(),
+            RecipeData {
+                inputs: RecipeInput::Exact(vec![ItemCount::new(
+                    Id::from_name("water".to_string()),
+                    1,
+                )]),
+                outputs: RecipeOutput::Deterministic(vec![ItemCount::new(
+                    Id::from_name("food".to_string()),
+                    1,
+                )]),
+                bulk_inputs: Vec::new(),
+                bulk_outputs: Vec::new(),
+                craft_time: std::time::Duration::from_secs(1),
+                conditions: RecipeConditions::default(),
+                energy: None,
+                hatches: None,
+            },
+        );
+
+        let mut structure_manifest = StructureManifest::new();
+        structure_manifest.insert("kitchen".to_string(), StructureData::passable());
+
+        let mut terrain_manifest = TerrainManifest::new();
+        terrain_manifest.insert("grassy".to_string(), TerrainData::default());
+
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert(
+            "simple_unit".to_string(),
+            UnitData::simple("simple_unit", Diet::simple("food")),
+        );
+
+        Codex::new(
+            &item_manifest,
+            &recipe_manifest,
+            &structure_manifest,
+            &terrain_manifest,
+            &unit_manifest,
+        )
+    }
+
+    #[test]
+    fn every_manifest_entry_has_a_codex_entry() {
+        let codex = codex();
+
+        assert_eq!(codex.entries().len(), 6);
+        assert!(codex
+            .get(CodexSubject::Terrain(Id::from_name("grassy".to_string())))
+            .is_some());
+        assert!(codex
+            .get(CodexSubject::Structure(Id::from_name(
+                "kitchen".to_string()
+            )))
+            .is_some());
+    }
+
+    #[test]
+    fn flavor_text_is_copied_from_the_manifest() {
+        let codex = codex();
+        let food = codex
+            .get(CodexSubject::Item(Id::from_name("food".to_string())))
+            .unwrap();
+        let water = codex
+            .get(CodexSubject::Item(Id::from_name("water".to_string())))
+            .unwrap();
+
+        assert_eq!(food.flavor_text.as_deref(), Some("Tasty."));
+        assert_eq!(water.flavor_text, None);
+    }
+
+    #[test]
+    fn recipes_are_linked_in_both_directions() {
+        let codex = codex();
+        let recipe = CodexSubject::Recipe(Id::from_name("cooking".to_string()));
+        let food = CodexSubject::Item(Id::from_name("food".to_string()));
+        let water = CodexSubject::Item(Id::from_name("water".to_string()));
+
+        let water_entry = codex.get(water).unwrap();
+        assert_eq!(
+            water_entry
+                .linked(CodexRelation::UsedIn)
+                .collect::<Vec<_>>(),
+            vec![recipe]
+        );
+
+        let food_entry = codex.get(food).unwrap();
+        assert_eq!(
+            food_entry
+                .linked(CodexRelation::ProducedBy)
+                .collect::<Vec<_>>(),
+            vec![recipe]
+        );
+
+        let recipe_entry = codex.get(recipe).unwrap();
+        assert_eq!(
+            recipe_entry
+                .linked(CodexRelation::Requires)
+                .collect::<Vec<_>>(),
+            vec![water]
+        );
+        assert_eq!(
+            recipe_entry
+                .linked(CodexRelation::Produces)
+                .collect::<Vec<_>>(),
+            vec![food]
+        );
+    }
+
+    #[test]
+    fn units_link_to_their_food() {
+        let codex = codex();
+        let unit = CodexSubject::Unit(Id::from_name("simple_unit".to_string()));
+        let food = CodexSubject::Item(Id::from_name("food".to_string()));
+
+        let food_entry = codex.get(food).unwrap();
+        assert_eq!(
+            food_entry
+                .linked(CodexRelation::EatenBy)
+                .collect::<Vec<_>>(),
+            vec![unit]
+        );
+    }
+
+    #[test]
+    fn every_relation_is_the_inverse_of_its_inverse() {
+        let codex = codex();
+
+        for entry in codex.entries() {
+            for link in &entry.links {
+                assert_eq!(link.relation.inverse().inverse(), link.relation);
+
+                let target = codex.get(link.target).unwrap();
+                assert!(target.links.contains(&CodexLink {
+                    relation: link.relation.inverse(),
+                    target: entry.subject,
+                }));
+            }
+        }
+    }
+}
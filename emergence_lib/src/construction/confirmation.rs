@@ -0,0 +1,330 @@
+//! Destructive actions are planned before they are carried out, so large ones can be previewed and confirmed.
+//!
+//! A [`DestructivePlan`] is a dry run of clearing zoning, demolishing or terraforming:
+//! it records exactly what would change without touching the world.
+//! Small plans are applied immediately, while larger ones wait in [`PendingConfirmation`]
+//! until the player presses [`PlayerAction::ConfirmAction`].
+
+use bevy::{prelude::*, utils::HashSet};
+use hexx::Hex;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    multiplayer::actions::{SharedAction, SharedActions},
+    player_interaction::{
+        selection::{CurrentSelection, SelectedVoxels},
+        InteractionSystem, PlayerAction, PlayerModifiesWorld,
+    },
+    structures::{structure_manifest::Structure, Landmark},
+};
+
+use super::{demolition::MarkedForDemolition, terraform::TerraformingAction};
+
+/// Holds large destructive actions until the player confirms or cancels them.
+pub(super) struct ConfirmationPlugin;
+
+impl Plugin for ConfirmationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingConfirmation>().add_systems(
+            Update,
+            confirm_destructive_actions
+                .in_set(PlayerModifiesWorld)
+                .before(InteractionSystem::SelectTiles),
+        );
+    }
+}
+
+/// Plans that affect more than this many objects must be confirmed before they are applied.
+pub(crate) const CONFIRMATION_THRESHOLD: usize = 10;
+
+/// The positions of structures that can be marked for demolition.
+///
+/// Landmarks can't be demolished, and structures that are already marked are not counted again.
+pub(crate) type DemolishableQuery<'w, 's> = Query<
+    'w,
+    's,
+    &'static VoxelPos,
+    (
+        With<Id<Structure>>,
+        Without<Landmark>,
+        Without<MarkedForDemolition>,
+    ),
+>;
+
+/// Everything that a destructive action would change, computed without changing anything.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct DestructivePlan {
+    /// Structures that will be marked for demolition, and their positions.
+    demolished_structures: Vec<(Entity, VoxelPos)>,
+    /// Ghost structures that will be removed, and a position that they occupy.
+    removed_ghosts: Vec<(Entity, VoxelPos)>,
+    /// Tiles whose terraforming orders will be cancelled.
+    cancelled_terraforming: Vec<Hex>,
+    /// Tiles that will be terraformed, and how.
+    terraforming: Vec<(Hex, TerraformingAction)>,
+}
+
+impl DestructivePlan {
+    /// Plans clearing the zoning of the `relevant_tiles`, removing ghosts and cancelling terraforming there.
+    ///
+    /// If `demolish` is true, the structures in the `current_selection` are also marked for demolition.
+    pub(crate) fn clear(
+        relevant_tiles: &SelectedVoxels,
+        current_selection: &CurrentSelection,
+        demolish: bool,
+        map_geometry: &MapGeometry,
+        terraforming_query: &Query<&TerraformingAction>,
+        demolition_query: &DemolishableQuery,
+    ) -> Self {
+        let mut plan = DestructivePlan::default();
+
+        if demolish {
+            for structure_entity in current_selection.structures(map_geometry) {
+                if let Ok(&voxel_pos) = demolition_query.get(structure_entity) {
+                    plan.demolished_structures
+                        .push((structure_entity, voxel_pos));
+                }
+            }
+        }
+
+        let mut seen_ghosts = HashSet::new();
+        for &voxel_pos in relevant_tiles.iter() {
+            // Ghosts are placed on top of the selected terrain
+            for ghost_pos in [voxel_pos, voxel_pos.above()] {
+                if let Some(ghost_entity) = map_geometry.get_ghost_structure(ghost_pos) {
+                    if seen_ghosts.insert(ghost_entity) {
+                        plan.removed_ghosts.push((ghost_entity, ghost_pos));
+                    }
+                }
+            }
+
+            if current_terraforming(voxel_pos.hex, map_geometry, terraforming_query)
+                != TerraformingAction::None
+            {
+                plan.cancelled_terraforming.push(voxel_pos.hex);
+            }
+        }
+
+        plan
+    }
+
+    /// Plans applying the terraforming `action` to the `relevant_tiles`.
+    ///
+    /// Tiles that are already being terraformed in this way are left out.
+    pub(crate) fn terraform(
+        relevant_tiles: &SelectedVoxels,
+        action: TerraformingAction,
+        map_geometry: &MapGeometry,
+        terraforming_query: &Query<&TerraformingAction>,
+    ) -> Self {
+        let terraforming = relevant_tiles
+            .iter()
+            .map(|voxel_pos| voxel_pos.hex)
+            .filter(|&hex| current_terraforming(hex, map_geometry, terraforming_query) != action)
+            .map(|hex| (hex, action))
+            .collect();
+
+        DestructivePlan {
+            terraforming,
+            ..Default::default()
+        }
+    }
+
+    /// The number of objects that this plan affects.
+    pub(crate) fn n_affected(&self) -> usize {
+        self.demolished_structures.len()
+            + self.removed_ghosts.len()
+            + self.cancelled_terraforming.len()
+            + self.terraforming.len()
+    }
+
+    /// The hexes of every object affected by this plan, for highlighting.
+    pub(crate) fn affected_hexes(&self) -> HashSet<Hex> {
+        self.demolished_structures
+            .iter()
+            .chain(self.removed_ghosts.iter())
+            .map(|(_, voxel_pos)| voxel_pos.hex)
+            .chain(self.cancelled_terraforming.iter().copied())
+            .chain(self.terraforming.iter().map(|(hex, _)| *hex))
+            .collect()
+    }
+
+    /// A short description of what this plan will do, such as "Demolish 3 structures, Remove 12 ghosts".
+    pub(crate) fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            (self.demolished_structures.len(), "Demolish", "structure"),
+            (self.removed_ghosts.len(), "Remove", "ghost"),
+            (
+                self.cancelled_terraforming.len(),
+                "Cancel",
+                "terraforming order",
+            ),
+            (self.terraforming.len(), "Terraform", "tile"),
+        ]
+        .into_iter()
+        .filter(|(count, ..)| *count > 0)
+        .map(|(count, verb, noun)| {
+            let plural = if count == 1 { "" } else { "s" };
+            format!("{verb} {count} {noun}{plural}")
+        })
+        .collect();
+
+        parts.join(", ")
+    }
+
+    /// The actions that carry out this plan.
+    fn actions(&self) -> Vec<SharedAction> {
+        let demolish = self
+            .demolished_structures
+            .iter()
+            .map(|&(_, voxel_pos)| SharedAction::Demolish { voxel_pos });
+        let clear_zoning = self
+            .removed_ghosts
+            .iter()
+            .map(|&(_, voxel_pos)| SharedAction::ClearZoning { voxel_pos });
+        let cancel_terraforming = self
+            .cancelled_terraforming
+            .iter()
+            .map(|&hex| SharedAction::CancelTerraform { hex });
+        let terraform = self
+            .terraforming
+            .iter()
+            .map(|&(hex, action)| SharedAction::Terraform { hex, action });
+
+        demolish
+            .chain(clear_zoning)
+            .chain(cancel_terraforming)
+            .chain(terraform)
+            .collect()
+    }
+}
+
+/// The terraforming that is currently ordered at `hex`.
+fn current_terraforming(
+    hex: Hex,
+    map_geometry: &MapGeometry,
+    terraforming_query: &Query<&TerraformingAction>,
+) -> TerraformingAction {
+    map_geometry
+        .get_terrain(hex)
+        .ok()
+        .and_then(|terrain_entity| terraforming_query.get(terrain_entity).ok())
+        .copied()
+        .unwrap_or_default()
+}
+
+/// A large [`DestructivePlan`] that is waiting for the player to confirm it.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PendingConfirmation {
+    /// The plan and the selection that it was made for, if any.
+    pending: Option<(DestructivePlan, CurrentSelection)>,
+}
+
+impl PendingConfirmation {
+    /// The plan waiting to be confirmed, if any.
+    pub(crate) fn plan(&self) -> Option<&DestructivePlan> {
+        self.pending.as_ref().map(|(plan, _)| plan)
+    }
+
+    /// Is a plan waiting to be confirmed?
+    ///
+    /// No new destructive actions are planned until it is confirmed or cancelled.
+    pub(crate) fn is_waiting(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Returns the actions that carry out the `plan` if it is small, or holds it for confirmation otherwise.
+    #[must_use]
+    pub(crate) fn request(
+        &mut self,
+        plan: DestructivePlan,
+        current_selection: &CurrentSelection,
+    ) -> Vec<SharedAction> {
+        if plan.n_affected() > CONFIRMATION_THRESHOLD {
+            self.pending = Some((plan, current_selection.clone()));
+            return Vec::new();
+        }
+
+        plan.actions()
+    }
+}
+
+/// Applies the pending plan when the player confirms it.
+///
+/// The plan is cancelled if the player deselects, or if the selection that it was made for changes.
+fn confirm_destructive_actions(
+    mut actions: ResMut<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    mut pending_confirmation: ResMut<PendingConfirmation>,
+    mut shared_actions: SharedActions,
+) {
+    let Some((plan, selection)) = &pending_confirmation.pending else {
+        return;
+    };
+
+    if actions.just_pressed(PlayerAction::ConfirmAction) {
+        for action in plan.actions() {
+            shared_actions.take(action);
+        }
+        actions.consume(PlayerAction::ConfirmAction);
+        pending_confirmation.pending = None;
+    } else if actions.just_pressed(PlayerAction::Deselect) {
+        // Cancelling should not also deselect anything
+        actions.consume(PlayerAction::Deselect);
+        pending_confirmation.pending = None;
+    } else if *selection != *current_selection {
+        pending_confirmation.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_only_mention_affected_objects() {
+        let plan = DestructivePlan {
+            removed_ghosts: vec![(Entity::PLACEHOLDER, VoxelPos::default())],
+            terraforming: (0..3)
+                .map(|x| (Hex::new(x, 0), TerraformingAction::Raise))
+                .collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(plan.n_affected(), 4);
+        assert_eq!(plan.summary(), "Remove 1 ghost, Terraform 3 tiles");
+    }
+
+    #[test]
+    fn small_plans_are_applied_immediately() {
+        let mut pending_confirmation = PendingConfirmation::default();
+        let small_plan = DestructivePlan {
+            cancelled_terraforming: vec![Hex::ZERO],
+            ..Default::default()
+        };
+
+        let actions = pending_confirmation.request(small_plan, &CurrentSelection::None);
+        assert_eq!(
+            actions,
+            vec![SharedAction::CancelTerraform { hex: Hex::ZERO }]
+        );
+        assert!(!pending_confirmation.is_waiting());
+    }
+
+    #[test]
+    fn large_plans_wait_for_confirmation() {
+        let mut pending_confirmation = PendingConfirmation::default();
+        let large_plan = DestructivePlan {
+            cancelled_terraforming: (0..=CONFIRMATION_THRESHOLD as i32)
+                .map(|x| Hex::new(x, 0))
+                .collect(),
+            ..Default::default()
+        };
+
+        let actions = pending_confirmation.request(large_plan.clone(), &CurrentSelection::None);
+        assert!(actions.is_empty());
+        assert_eq!(pending_confirmation.plan(), Some(&large_plan));
+    }
+}
@@ -4,13 +4,15 @@ use bevy::{ecs::system::SystemParam, prelude::*};
 
 use crate::{
     asset_management::manifest::Id,
-    geometry::{Height, MapGeometry, VoxelPos},
+    geometry::{MapGeometry, VoxelPos},
     signals::{Emitter, SignalStrength, SignalType},
     structures::structure_manifest::Structure,
+    units::movement::MovementMode,
 };
 
 /// Marker component for structures that are intended to be deconstructed
 #[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
 pub(crate) struct MarkedForDemolition;
 
 /// A query for the structures that need to be demolished.
@@ -29,10 +31,11 @@ impl<'w, 's> DemolitionQuery<'w, 's> {
         current: VoxelPos,
         target: VoxelPos,
         structure_id: Id<Structure>,
+        movement_mode: MovementMode,
         map_geometry: &MapGeometry,
     ) -> Option<Entity> {
         // This is only a viable target if the unit can reach it!
-        if current.abs_height_diff(target) > Height::MAX_STEP {
+        if !movement_mode.can_reach(current, target) {
             return None;
         }
 
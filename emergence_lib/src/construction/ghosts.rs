@@ -5,9 +5,11 @@
 //! Previews are simply hovered, and used as a visual aid to show placement.
 
 use crate::crafting::item_tags::ItemKind;
+use crate::crafting::pending_work::PendingWork;
 use crate::crafting::recipe::ActiveRecipe;
 use crate::crafting::workers::WorkersPresent;
 use crate::enum_iter::IterableEnum;
+use crate::factions::Faction;
 use crate::geometry::MapGeometry;
 use crate::organisms::energy::StartingEnergy;
 use crate::player_interaction::picking::PickableVoxel;
@@ -367,6 +369,8 @@ pub(super) fn ghost_structure_signals(
 
             match *crafting_state {
                 CraftingState::NeedsInput => {
+                    emitter.signals.push(PendingWork::Construction.signal());
+
                     match input_inventory {
                         InputInventory::Exact { inventory } => {
                             // Emit signals to cause workers to bring the correct item to this ghost
@@ -394,7 +398,8 @@ pub(super) fn ghost_structure_signals(
 
                         let signal_type = SignalType::Work(workplace_id);
                         let signal_strength = SignalStrength::new(GHOST_SIGNAL_STRENGTH);
-                        emitter.signals.push((signal_type, signal_strength))
+                        emitter.signals.push((signal_type, signal_strength));
+                        emitter.signals.push(PendingWork::Construction.signal());
                     }
                 }
                 _ => (),
@@ -468,6 +473,7 @@ pub(super) fn ghost_structure_lifecycle(
             &Facing,
             &ActiveRecipe,
             &WorkersPresent,
+            Option<&Faction>,
         ),
         With<Ghost>,
     >,
@@ -483,6 +489,7 @@ pub(super) fn ghost_structure_lifecycle(
         &facing,
         active_recipe,
         workers_present,
+        maybe_owner,
     ) in ghost_query.iter_mut()
     {
         let construction_data = structure_manifest.construction_data(structure_id);
@@ -531,6 +538,7 @@ pub(super) fn ghost_structure_lifecycle(
                             active_recipe: active_recipe.clone(),
                         },
                         StartingEnergy::Full,
+                        maybe_owner.copied(),
                     );
                 } else {
                     commands.spawn_structure(
@@ -541,6 +549,7 @@ pub(super) fn ghost_structure_lifecycle(
                             active_recipe: active_recipe.clone(),
                         },
                         StartingEnergy::NotAnOrganism,
+                        maybe_owner.copied(),
                     );
                 }
             }
@@ -13,9 +13,11 @@ use crate::{asset_management::manifest::Id, structures::structure_manifest::Stru
 use self::demolition::set_emitter_for_structures_to_be_demolished;
 use self::terraform::{terraforming_lifecycle, terraforming_signals};
 
+pub(crate) mod confirmation;
 pub(crate) mod demolition;
 pub(crate) mod ghosts;
 pub(crate) mod terraform;
+pub mod work_orders;
 pub(crate) mod zoning;
 
 /// Systems and resources for constructing structures and terraforming the world.
@@ -25,6 +27,8 @@ impl Plugin for ConstructionPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ghosts::GhostPlugin)
             .add_plugins(zoning::ZoningPlugin)
+            .add_plugins(confirmation::ConfirmationPlugin)
+            .add_plugins(work_orders::WorkOrderPlugin)
             // Must run after crafting emitters in order to wipe out their signals
             .add_systems(
                 FixedUpdate,
@@ -5,6 +5,7 @@ use bevy::{
     prelude::*,
 };
 use hexx::Hex;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::Id,
@@ -18,6 +19,7 @@ use crate::{
     player_interaction::selection::ObjectInteraction,
     signals::{Emitter, SignalStrength, SignalType},
     terrain::{
+        history::{TileEvent, TileEventKind},
         terrain_assets::TerrainHandles,
         terrain_manifest::{Terrain, TerrainManifest},
     },
@@ -42,7 +44,20 @@ pub(crate) enum TerraformingTool {
 /// Added as a component to terrain tiles, tracking the work needed to terraform them.
 ///
 /// When set to a non-null value, units will take action to manipulate them.
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Default,
+    Serialize,
+    Deserialize,
+)]
 pub enum TerraformingAction {
     /// No terraforming action is being performed.
     #[default]
@@ -366,8 +381,16 @@ impl Command for ApplyTerraformingCommand {
                 .clone_weak();
         }
 
+        let completed_action = *terraforming_action;
         *terraforming_action = TerraformingAction::None;
 
         map_geometry.update_height(voxel_pos.hex, voxel_pos.height);
+
+        if completed_action != TerraformingAction::None {
+            world.send_event(TileEvent {
+                hex: self.hex,
+                kind: TileEventKind::Terraformed(completed_action),
+            });
+        }
     }
 }
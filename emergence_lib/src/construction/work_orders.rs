@@ -0,0 +1,393 @@
+//! Work orders unify all of the jobs that the player has asked the colony to perform.
+//!
+//! Construction, demolition, terraforming and harvesting are each designated differently,
+//! but the player needs a single place to see what has been asked for, how it is going and to cancel it.
+//! Each designated entity gets a [`WorkOrder`] component, which tracks the kind of job and its current state.
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashSet};
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::{
+        inventories::{CraftingState, InputInventory},
+        workers::WorkersPresent,
+    },
+    geometry::{MapGeometry, VoxelPos},
+    multiplayer::actions::{SharedAction, SharedActions},
+    player_interaction::{
+        selection::CurrentSelection, InteractionSystem, PlayerAction, PlayerModifiesWorld,
+    },
+    simulation::SimulationSet,
+    structures::{
+        commands::StructureCommandsExt,
+        resource_nodes::MarkedForHarvest,
+        structure_manifest::{Structure, StructureManifest},
+    },
+    terrain::terrain_manifest::{Terrain, TerrainManifest},
+    units::actions::CurrentAction,
+};
+
+use super::{
+    demolition::MarkedForDemolition,
+    ghosts::Ghost,
+    terraform::{TerraformingAction, TerraformingCommandsExt},
+};
+
+/// Systems that create, update and cancel work orders.
+pub(super) struct WorkOrderPlugin;
+
+impl Plugin for WorkOrderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                register_work_orders,
+                update_work_order_states.after(register_work_orders),
+            )
+                .in_set(SimulationSet),
+        )
+        .add_systems(
+            Update,
+            cancel_selected_work_orders
+                .in_set(InteractionSystem::ApplyZoning)
+                .in_set(PlayerModifiesWorld)
+                .after(InteractionSystem::SelectTiles),
+        );
+    }
+}
+
+/// The kind of job that a [`WorkOrder`] asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkOrderKind {
+    /// Build a new structure from a ghost.
+    Construct(Id<Structure>),
+    /// Tear down an existing structure.
+    Demolish(Id<Structure>),
+    /// Reshape the terrain.
+    Terraform(TerraformingAction),
+    /// Gather wild resources from a structure.
+    Harvest(Id<Structure>),
+}
+
+/// How far along a [`WorkOrder`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WorkOrderState {
+    /// Nobody has started on this work order yet.
+    #[default]
+    Pending,
+    /// A unit is fetching or delivering items for this work order.
+    Assigned,
+    /// Units are actively working on this work order.
+    InProgress,
+    /// No unit can reach this work order.
+    Blocked,
+}
+
+impl WorkOrderState {
+    /// Determines the state of a work order based on what the colony is currently doing.
+    ///
+    /// Active work takes priority over assignment, which takes priority over reachability:
+    /// a unit that is already on site is clearly able to reach it.
+    fn determine(in_progress: bool, assigned: bool, reachable: bool) -> Self {
+        if in_progress {
+            WorkOrderState::InProgress
+        } else if assigned {
+            WorkOrderState::Assigned
+        } else if !reachable {
+            WorkOrderState::Blocked
+        } else {
+            WorkOrderState::Pending
+        }
+    }
+}
+
+impl Display for WorkOrderState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            WorkOrderState::Pending => "Pending",
+            WorkOrderState::Assigned => "Assigned",
+            WorkOrderState::InProgress => "In progress",
+            WorkOrderState::Blocked => "Blocked",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// A job that the player has asked the colony to perform.
+///
+/// This is added to the entity that units interact with in order to complete the job:
+/// ghost structures, structures marked for demolition or harvest, or terrain that is being terraformed.
+#[derive(Component, Debug, Clone, PartialEq)]
+#[component(storage = "SparseSet")]
+pub struct WorkOrder {
+    /// What needs to be done.
+    kind: WorkOrderKind,
+    /// How far along the job is.
+    state: WorkOrderState,
+    /// Where the job needs to be done.
+    voxel_pos: VoxelPos,
+}
+
+impl WorkOrder {
+    /// Creates a new [`WorkOrder`] in the [`WorkOrderState::Pending`] state.
+    pub fn new(kind: WorkOrderKind, voxel_pos: VoxelPos) -> Self {
+        WorkOrder {
+            kind,
+            state: WorkOrderState::Pending,
+            voxel_pos,
+        }
+    }
+
+    /// What needs to be done.
+    pub fn kind(&self) -> WorkOrderKind {
+        self.kind
+    }
+
+    /// How far along the job is.
+    pub fn state(&self) -> WorkOrderState {
+        self.state
+    }
+
+    /// Where the job needs to be done.
+    pub fn voxel_pos(&self) -> VoxelPos {
+        self.voxel_pos
+    }
+
+    /// The voxel that units need to be next to in order to work on this order.
+    ///
+    /// Terraforming is done from the surface, rather than inside of the terrain.
+    fn access_voxel(&self) -> VoxelPos {
+        match self.kind {
+            WorkOrderKind::Terraform(..) => self.voxel_pos.above(),
+            WorkOrderKind::Construct(..)
+            | WorkOrderKind::Demolish(..)
+            | WorkOrderKind::Harvest(..) => self.voxel_pos,
+        }
+    }
+
+    /// Cancels this work order, undoing the designation that created it.
+    ///
+    /// `entity` must be the entity that this work order is attached to.
+    pub(crate) fn cancel(&self, entity: Entity, commands: &mut Commands) {
+        match self.kind {
+            WorkOrderKind::Construct(..) => commands.despawn_ghost_structure(self.voxel_pos),
+            WorkOrderKind::Demolish(..) => {
+                commands
+                    .entity(entity)
+                    .remove::<(MarkedForDemolition, WorkOrder)>();
+            }
+            WorkOrderKind::Terraform(..) => commands.cancel_terraform(self.voxel_pos.hex),
+            WorkOrderKind::Harvest(..) => {
+                commands
+                    .entity(entity)
+                    .remove::<(MarkedForHarvest, WorkOrder)>();
+            }
+        }
+    }
+
+    /// Pretty formatting for this type.
+    pub(crate) fn display(
+        &self,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+    ) -> String {
+        let job = match self.kind {
+            WorkOrderKind::Construct(structure_id) => {
+                format!("Build {}", structure_manifest.name(structure_id))
+            }
+            WorkOrderKind::Demolish(structure_id) => {
+                format!("Demolish {}", structure_manifest.name(structure_id))
+            }
+            WorkOrderKind::Terraform(terraforming_action) => {
+                format!(
+                    "Terraform: {}",
+                    terraforming_action.display(terrain_manifest)
+                )
+            }
+            WorkOrderKind::Harvest(structure_id) => {
+                format!("Harvest {}", structure_manifest.name(structure_id))
+            }
+        };
+
+        format!("{job} at {}: {}", self.voxel_pos, self.state)
+    }
+}
+
+/// Attaches [`WorkOrder`]s to newly designated jobs, and removes them from terrain that no longer needs terraforming.
+///
+/// Ghosts and demolished structures are despawned once their job is complete, taking their work order with them.
+fn register_work_orders(
+    ghost_query: Query<(Entity, &Id<Structure>, &VoxelPos), Added<Ghost>>,
+    demolition_query: Query<(Entity, &Id<Structure>, &VoxelPos), Added<MarkedForDemolition>>,
+    harvest_query: Query<(Entity, &Id<Structure>, &VoxelPos), Added<MarkedForHarvest>>,
+    terraform_query: Query<
+        (Entity, &TerraformingAction, &VoxelPos),
+        (Changed<TerraformingAction>, With<Id<Terrain>>),
+    >,
+    mut commands: Commands,
+) {
+    for (entity, &structure_id, &voxel_pos) in ghost_query.iter() {
+        commands.entity(entity).insert(WorkOrder::new(
+            WorkOrderKind::Construct(structure_id),
+            voxel_pos,
+        ));
+    }
+
+    for (entity, &structure_id, &voxel_pos) in demolition_query.iter() {
+        commands.entity(entity).insert(WorkOrder::new(
+            WorkOrderKind::Demolish(structure_id),
+            voxel_pos,
+        ));
+    }
+
+    for (entity, &structure_id, &voxel_pos) in harvest_query.iter() {
+        commands.entity(entity).insert(WorkOrder::new(
+            WorkOrderKind::Harvest(structure_id),
+            voxel_pos,
+        ));
+    }
+
+    for (entity, &terraforming_action, &voxel_pos) in terraform_query.iter() {
+        match terraforming_action {
+            TerraformingAction::None => {
+                commands.entity(entity).remove::<WorkOrder>();
+            }
+            _ => {
+                commands.entity(entity).insert(WorkOrder::new(
+                    WorkOrderKind::Terraform(terraforming_action),
+                    voxel_pos,
+                ));
+            }
+        }
+    }
+}
+
+/// Updates the [`WorkOrderState`] of each work order based on what units are doing.
+fn update_work_order_states(
+    mut work_order_query: Query<(
+        Entity,
+        &mut WorkOrder,
+        Option<&WorkersPresent>,
+        Option<&CraftingState>,
+        Option<&InputInventory>,
+    )>,
+    unit_query: Query<&CurrentAction>,
+    map_geometry: Res<MapGeometry>,
+) {
+    let mut laboring = HashSet::new();
+    let mut hauling = HashSet::new();
+
+    for current_action in unit_query.iter() {
+        if let Some(entity) = current_action.target_entity() {
+            match current_action.is_labor() {
+                true => laboring.insert(entity),
+                false => hauling.insert(entity),
+            };
+        }
+    }
+
+    for (entity, mut work_order, maybe_workers_present, maybe_crafting_state, maybe_input) in
+        work_order_query.iter_mut()
+    {
+        let workers_present = maybe_workers_present.map_or(0, WorkersPresent::current);
+        let work_started = matches!(maybe_crafting_state, Some(CraftingState::InProgress { .. }));
+        // Terraforming has no separate work phase: delivering the first item is progress
+        let items_delivered = matches!(work_order.kind, WorkOrderKind::Terraform(..))
+            && maybe_input.map_or(false, |input| !input.is_empty());
+
+        let in_progress =
+            laboring.contains(&entity) || workers_present > 0 || work_started || items_delivered;
+        let assigned = hauling.contains(&entity);
+        let reachable = map_geometry
+            .walkable_neighbors(work_order.access_voxel())
+            .next()
+            .is_some();
+
+        let state = WorkOrderState::determine(in_progress, assigned, reachable);
+        // Avoid triggering change detection needlessly
+        if work_order.state != state {
+            work_order.state = state;
+        }
+    }
+}
+
+/// Cancels all work orders on the selected tiles.
+fn cancel_selected_work_orders(
+    actions: Res<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    work_order_query: Query<&WorkOrder>,
+    map_geometry: Res<MapGeometry>,
+    mut shared_actions: SharedActions,
+) {
+    if !actions.just_pressed(PlayerAction::CancelWorkOrders) {
+        return;
+    }
+
+    let entities: Vec<Entity> = match *current_selection {
+        CurrentSelection::Voxels(ref selected_voxels) => selected_voxels
+            .voxel_objects(&map_geometry)
+            .into_iter()
+            .map(|voxel_object| voxel_object.entity)
+            .collect(),
+        CurrentSelection::Structure(structure_entity) => vec![structure_entity],
+        CurrentSelection::Unit(_) | CurrentSelection::None => return,
+    };
+
+    let voxels: Vec<VoxelPos> = work_order_query
+        .iter_many(entities)
+        .map(|work_order| work_order.voxel_pos)
+        .collect();
+
+    if !voxels.is_empty() {
+        shared_actions.take(SharedAction::CancelWorkOrders { voxels });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn work_in_progress_takes_priority() {
+        assert_eq!(
+            WorkOrderState::determine(true, true, false),
+            WorkOrderState::InProgress
+        );
+        assert_eq!(
+            WorkOrderState::determine(false, true, false),
+            WorkOrderState::Assigned
+        );
+    }
+
+    #[test]
+    fn unreachable_work_orders_are_blocked() {
+        assert_eq!(
+            WorkOrderState::determine(false, false, false),
+            WorkOrderState::Blocked
+        );
+        assert_eq!(
+            WorkOrderState::determine(false, false, true),
+            WorkOrderState::Pending
+        );
+    }
+
+    #[test]
+    fn terraforming_is_done_from_the_surface() {
+        let voxel_pos = VoxelPos::ZERO;
+        let terraform = WorkOrder::new(
+            WorkOrderKind::Terraform(TerraformingAction::Raise),
+            voxel_pos,
+        );
+        let demolish = WorkOrder::new(
+            WorkOrderKind::Demolish(Id::from_name("test".to_string())),
+            voxel_pos,
+        );
+
+        assert_eq!(terraform.access_voxel(), voxel_pos.above());
+        assert_eq!(demolish.access_voxel(), voxel_pos);
+    }
+}
@@ -4,17 +4,20 @@ use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
 
 use crate::{
-    asset_management::manifest::Id,
-    construction::{demolition::MarkedForDemolition, ghosts::Preview},
+    construction::ghosts::Preview,
     geometry::MapGeometry,
+    multiplayer::actions::{SharedAction, SharedActions},
     player_interaction::{
         clipboard::Tool, picking::CursorPos, selection::CurrentSelection, InteractionSystem,
         PlayerAction, PlayerModifiesWorld,
     },
-    structures::{commands::StructureCommandsExt, structure_manifest::Structure, Landmark},
+    structures::commands::StructureCommandsExt,
 };
 
-use super::terraform::TerraformingCommandsExt;
+use super::{
+    confirmation::{DemolishableQuery, DestructivePlan, PendingConfirmation},
+    terraform::{TerraformingAction, TerraformingCommandsExt},
+};
 
 /// Code and data for setting zoning of areas for construction.
 pub(super) struct ZoningPlugin;
@@ -23,7 +26,7 @@ impl Plugin for ZoningPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (mark_for_demolition, set_zoning)
+            set_zoning
                 .in_set(InteractionSystem::ApplyZoning)
                 .in_set(PlayerModifiesWorld)
                 .after(InteractionSystem::SelectTiles)
@@ -56,21 +59,38 @@ struct CleanMeUp;
 
 /// Applies zoning to an area, causing structures to be created (or removed) there.
 ///
+/// Clearing zoning also marks the selected structures for demolition.
+/// Destructive actions are planned first, and large ones wait in [`PendingConfirmation`] until the player confirms them.
+///
 /// This system also displays previews in order to ensure perfect consistency.
 fn set_zoning(
     cursor_pos: Res<CursorPos>,
     actions: Res<ActionState<PlayerAction>>,
     tool: Res<Tool>,
     current_selection: Res<CurrentSelection>,
+    map_geometry: Res<MapGeometry>,
+    terraforming_query: Query<&TerraformingAction>,
+    demolition_query: DemolishableQuery,
+    mut pending_confirmation: ResMut<PendingConfirmation>,
+    mut shared_actions: SharedActions,
     mut commands: Commands,
 ) {
     let relevant_tiles = current_selection.relevant_tiles(&cursor_pos);
 
     // Explicitly clear the selection
     if actions.pressed(PlayerAction::ClearZoning) {
-        for &voxel_pos in relevant_tiles.iter() {
-            commands.despawn_ghost_structure(voxel_pos);
-            commands.cancel_terraform(voxel_pos.hex);
+        if !pending_confirmation.is_waiting() {
+            let plan = DestructivePlan::clear(
+                &relevant_tiles,
+                &current_selection,
+                actions.just_pressed(PlayerAction::ClearZoning),
+                &map_geometry,
+                &terraforming_query,
+                &demolition_query,
+            );
+            for action in pending_confirmation.request(plan, &current_selection) {
+                shared_actions.take(action);
+            }
         }
 
         // Don't try to clear and zone in the same frame
@@ -80,13 +100,23 @@ fn set_zoning(
     // If this is true, we'll build ghosts that will be acted on.
     // Otherwise, we'll just preview.
     let actually_build = actions.pressed(PlayerAction::Paste)
-        || actions.pressed(PlayerAction::UseTool) && !tool.is_empty();
+        || actions.pressed(PlayerAction::UseTool)
+            && !tool.is_empty()
+            && !actions.pressed(PlayerAction::Measure);
 
     match &*tool {
         Tool::Terraform(terraform_tool) => match actually_build {
             true => {
-                for voxel_pos in relevant_tiles.iter() {
-                    commands.start_terraform(voxel_pos.hex, (*terraform_tool).into());
+                if !pending_confirmation.is_waiting() {
+                    let plan = DestructivePlan::terraform(
+                        &relevant_tiles,
+                        (*terraform_tool).into(),
+                        &map_geometry,
+                        &terraforming_query,
+                    );
+                    for action in pending_confirmation.request(plan, &current_selection) {
+                        shared_actions.take(action);
+                    }
                 }
             }
             false => {
@@ -106,10 +136,10 @@ fn set_zoning(
                             for voxel_pos in relevant_tiles.iter() {
                                 // We need to build on top of the selected tile,
                                 // not inside the terrain
-                                commands.spawn_ghost_structure(
+                                shared_actions.take(SharedAction::zone(
                                     voxel_pos.above(),
                                     clipboard_item.clone(),
-                                );
+                                ));
                             }
                         }
                         false => {
@@ -132,10 +162,10 @@ fn set_zoning(
                     for (voxel_pos, clipboard_item) in tool.offset_positions(cursor_tile_pos) {
                         match actually_build {
                             true => {
-                                commands.spawn_ghost_structure(
+                                shared_actions.take(SharedAction::zone(
                                     voxel_pos.above(),
                                     clipboard_item.clone(),
-                                );
+                                ));
                             }
                             false => {
                                 commands.spawn_preview_structure(
@@ -151,25 +181,3 @@ fn set_zoning(
         Tool::None => (),
     }
 }
-
-/// Mark the selected structure for deletion.
-fn mark_for_demolition(
-    player_actions: Res<ActionState<PlayerAction>>,
-    current_selection: Res<CurrentSelection>,
-    // Landmarks can't be demolished
-    structure_query: Query<&Id<Structure>, Without<Landmark>>,
-    map_geometry: Res<MapGeometry>,
-    mut commands: Commands,
-) {
-    if player_actions.just_pressed(PlayerAction::ClearZoning) {
-        if let CurrentSelection::Voxels(ref selected_voxels) = *current_selection {
-            for voxel_object in selected_voxels.voxel_objects(&map_geometry) {
-                if structure_query.contains(voxel_object.entity) {
-                    commands
-                        .entity(voxel_object.entity)
-                        .insert(MarkedForDemolition);
-                }
-            }
-        }
-    }
-}
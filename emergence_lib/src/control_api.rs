@@ -0,0 +1,573 @@
+//! Lets an external process observe and play the game.
+//!
+//! The control API is opt-in: add a [`ControlApiPlugin`] with a [`ControlApiConfig`] to enable it.
+//! This is intended for automated playtesting and for training agents against the real simulation,
+//! and works in headless apps that only add the [`SimulationPlugin`](crate::simulation::SimulationPlugin).
+//!
+//! A single controller connects over TCP, and sends one [`ControlRequest`] per line as JSON.
+//! Each request is answered with a single [`ControlResponse`] line.
+//! In lockstep mode, the simulation only advances when the controller asks it to,
+//! so the controller can observe the world and act on every tick.
+//! Requested ticks are simulated directly, so the controller can step the simulation even while the game is paused.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+};
+
+use bevy::prelude::*;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::{demolition::MarkedForDemolition, ghosts::Ghost},
+    factions::Faction,
+    geometry::{MapGeometry, VoxelPos},
+    items::{item_manifest::ItemManifest, totals::ItemTotals},
+    multiplayer::split_messages,
+    player_interaction::clipboard::ClipboardData,
+    simulation::{
+        stepping::{self, run_requested_tick},
+        time::InGameTime,
+        SimulationSet,
+    },
+    structures::{
+        commands::StructureCommandsExt,
+        structure_manifest::{Structure, StructureManifest},
+        Landmark,
+    },
+    units::{
+        goals::{Goal, GoalKind},
+        unit_manifest::{Unit, UnitManifest},
+    },
+};
+
+/// Serves the control API to an external controller.
+pub struct ControlApiPlugin {
+    /// Where to listen for a controller, and how the simulation is paced.
+    ///
+    /// If this is [`None`], the control API is disabled.
+    pub config: Option<ControlApiConfig>,
+}
+
+impl ControlApiPlugin {
+    /// Configures the control API using [`ControlApiConfig::from_env`].
+    pub fn from_env() -> Self {
+        ControlApiPlugin {
+            config: ControlApiConfig::from_env(),
+        }
+    }
+}
+
+impl Plugin for ControlApiPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(config) = &self.config else {
+            return;
+        };
+
+        let listener = match TcpListener::bind(config.address)
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+        {
+            Ok(listener) => listener,
+            Err(error) => {
+                error!("Could not start the control API: {error}");
+                return;
+            }
+        };
+
+        info!("Serving the control API on {}", config.address);
+        app.insert_resource(ControlServer::new(config.clone(), listener))
+            // Requests are handled even while the simulation is paused, so that it can be stepped
+            .add_systems(Update, (serve_controller, run_controller_steps).chain())
+            .add_systems(FixedUpdate, count_ticks.in_set(SimulationSet))
+            .configure_sets(FixedUpdate, SimulationSet.run_if(controller_allows_tick));
+    }
+}
+
+/// Settings for the [`ControlApiPlugin`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlApiConfig {
+    /// The address that the controller connects to.
+    pub address: SocketAddr,
+    /// Should the simulation wait for [`ControlRequest::Step`] before each tick?
+    ///
+    /// If this is `false`, the simulation runs in real time, and the controller observes it as it goes.
+    pub lockstep: bool,
+}
+
+impl ControlApiConfig {
+    /// The environment variable that enables the control API when set to an address.
+    pub const ADDRESS_VAR: &'static str = "EMERGENCE_CONTROL_API";
+
+    /// The environment variable that lets the simulation run in real time when set to `0`.
+    pub const LOCKSTEP_VAR: &'static str = "EMERGENCE_CONTROL_LOCKSTEP";
+
+    /// Reads the control API settings from the environment.
+    ///
+    /// Returns [`None`] if the control API has not been requested, or if the address is invalid.
+    pub fn from_env() -> Option<Self> {
+        let address = std::env::var(Self::ADDRESS_VAR).ok()?;
+        let address = match address.parse() {
+            Ok(address) => address,
+            Err(error) => {
+                error!("Invalid control API address {address}: {error}");
+                return None;
+            }
+        };
+
+        let lockstep = std::env::var(Self::LOCKSTEP_VAR).as_deref() != Ok("0");
+
+        Some(ControlApiConfig { address, lockstep })
+    }
+}
+
+/// A request sent by the controller.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ControlRequest {
+    /// Describe the current state of the world.
+    Observe,
+    /// Take an action on behalf of the player.
+    Act(ControlAction),
+    /// Advance the simulation by this many ticks.
+    ///
+    /// The response is only sent once the ticks have been simulated.
+    Step(u32),
+}
+
+/// An action that the controller can take on behalf of the player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ControlAction {
+    /// Zone the tile at `hex` for the named structure.
+    Zone {
+        /// The name of the structure to build.
+        structure: String,
+        /// The tile to build on.
+        hex: Hex,
+    },
+    /// Remove any zoning from the tile at `hex`.
+    ClearZoning {
+        /// The tile to clear.
+        hex: Hex,
+    },
+    /// Mark the structure on the tile at `hex` for demolition.
+    Demolish {
+        /// The tile that the structure is on.
+        hex: Hex,
+    },
+}
+
+/// The answer to a single [`ControlRequest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ControlResponse {
+    /// The current state of the world.
+    Observation(WorldObservation),
+    /// The action was accepted, and will take effect on the next tick.
+    Accepted,
+    /// The requested ticks have been simulated.
+    Stepped {
+        /// The number of ticks simulated since the controller connected.
+        tick: u64,
+    },
+    /// The request could not be carried out.
+    Error(String),
+}
+
+/// A structured description of the world, as seen by the controller.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldObservation {
+    /// The number of ticks simulated since the controller connected.
+    pub tick: u64,
+    /// The number of in-game days that have elapsed.
+    pub day: f32,
+    /// Every unit in the world.
+    pub units: Vec<UnitObservation>,
+    /// Every structure in the world, including those that have been zoned but not yet built.
+    pub structures: Vec<StructureObservation>,
+    /// The total number of each item, by name.
+    pub items: BTreeMap<String, u32>,
+}
+
+/// The state of a single unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitObservation {
+    /// The type of unit.
+    pub unit: String,
+    /// Where the unit is.
+    pub position: VoxelPos,
+    /// The colony that the unit belongs to.
+    pub faction: Faction,
+    /// What the unit is trying to do.
+    pub goal: GoalKind,
+}
+
+/// The state of a single structure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructureObservation {
+    /// The type of structure.
+    pub structure: String,
+    /// The center of the structure.
+    pub position: VoxelPos,
+    /// The colony that owns the structure, if any.
+    pub owner: Option<Faction>,
+    /// Has this structure been zoned, but not yet built?
+    pub ghost: bool,
+}
+
+/// The connection to the controller, and the work it has asked for.
+#[derive(Resource, Debug)]
+struct ControlServer {
+    /// How the control API was started.
+    config: ControlApiConfig,
+    /// Listens for a controller.
+    listener: TcpListener,
+    /// The connected controller, along with any partial request received from it.
+    ///
+    /// Only one controller may be connected at once: new connections replace the old one.
+    controller: Option<(TcpStream, Vec<u8>)>,
+    /// Requests that have been received, but not yet handled.
+    queued: VecDeque<ControlRequest>,
+    /// The number of ticks that the controller has asked for, but that have not yet been simulated.
+    pending_ticks: u32,
+    /// The number of ticks simulated since the controller connected.
+    tick: u64,
+}
+
+impl ControlServer {
+    /// Starts waiting for a controller.
+    fn new(config: ControlApiConfig, listener: TcpListener) -> Self {
+        ControlServer {
+            config,
+            listener,
+            controller: None,
+            queued: VecDeque::new(),
+            pending_ticks: 0,
+            tick: 0,
+        }
+    }
+
+    /// Accepts a new controller, if one is trying to connect.
+    fn accept(&mut self) {
+        match self.listener.accept() {
+            Ok((stream, address)) => {
+                if let Err(error) = stream.set_nonblocking(true) {
+                    warn!("Could not accept controller from {address}: {error}");
+                    return;
+                }
+
+                info!("Controller connected from {address}");
+                self.controller = Some((stream, Vec::new()));
+                self.queued.clear();
+                self.pending_ticks = 0;
+                self.tick = 0;
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => (),
+            Err(error) => warn!("Could not accept controller: {error}"),
+        }
+    }
+
+    /// Queues up any requests that have arrived from the controller.
+    fn receive(&mut self) {
+        let Some((stream, buffer)) = &mut self.controller else {
+            return;
+        };
+
+        let mut chunk = [0; 4096];
+        let open = loop {
+            match stream.read(&mut chunk) {
+                Ok(0) => break false,
+                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break true,
+                Err(error) => {
+                    warn!("Lost connection to controller: {error}");
+                    break false;
+                }
+            }
+        };
+
+        self.queued.extend(split_messages::<ControlRequest>(buffer));
+        if !open {
+            info!("Controller disconnected");
+            self.controller = None;
+        }
+    }
+
+    /// Sends the `response` to the controller.
+    fn respond(&mut self, response: &ControlResponse) {
+        let Some((stream, _)) = &mut self.controller else {
+            return;
+        };
+
+        let mut line = serde_json::to_vec(response).expect("Responses can always be serialized");
+        line.push(b'\n');
+
+        // Observations can be large, so block until the whole response has been sent
+        let result = stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.write_all(&line))
+            .and_then(|_| stream.set_nonblocking(true));
+
+        if let Err(error) = result {
+            warn!("Lost connection to controller: {error}");
+            self.controller = None;
+        }
+    }
+
+    /// Should the simulation advance this tick?
+    ///
+    /// In lockstep mode, only requested ticks are simulated.
+    fn allows_tick(&self, requested_tick: bool) -> bool {
+        !self.config.lockstep || requested_tick
+    }
+}
+
+/// In lockstep mode, the simulation only runs when the controller has asked it to.
+fn controller_allows_tick(
+    control_server: Res<ControlServer>,
+    requested_tick: Option<Res<stepping::RequestedTick>>,
+) -> bool {
+    control_server.allows_tick(requested_tick.is_some())
+}
+
+/// Records that a tick has been simulated.
+fn count_ticks(mut control_server: ResMut<ControlServer>) {
+    control_server.tick += 1;
+}
+
+/// Simulates the ticks that the controller has asked for, then tells it that they are done.
+///
+/// Like debug steps, these ticks are run directly, so they are taken even while the game is paused.
+fn run_controller_steps(world: &mut World) {
+    let pending_ticks = std::mem::take(&mut world.resource_mut::<ControlServer>().pending_ticks);
+    if pending_ticks == 0 {
+        return;
+    }
+
+    for _ in 0..pending_ticks {
+        run_requested_tick(world);
+    }
+
+    let mut control_server = world.resource_mut::<ControlServer>();
+    let tick = control_server.tick;
+    control_server.respond(&ControlResponse::Stepped { tick });
+}
+
+/// Accepts a controller, and handles its requests.
+fn serve_controller(
+    mut control_server: ResMut<ControlServer>,
+    unit_query: Query<(&Id<Unit>, &VoxelPos, &Faction, &Goal)>,
+    structure_query: Query<(&Id<Structure>, &VoxelPos, Option<&Faction>, Has<Ghost>)>,
+    // Landmarks can't be demolished
+    demolishable_query: Query<(), (With<Id<Structure>>, Without<Landmark>)>,
+    unit_manifest: Res<UnitManifest>,
+    structure_manifest: Res<StructureManifest>,
+    item_manifest: Res<ItemManifest>,
+    item_totals: Res<ItemTotals>,
+    in_game_time: Res<InGameTime>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    control_server.accept();
+    control_server.receive();
+
+    while let Some(request) = control_server.queued.pop_front() {
+        let response = match request {
+            ControlRequest::Observe => ControlResponse::Observation(WorldObservation {
+                tick: control_server.tick,
+                day: in_game_time.elapsed_days(),
+                units: unit_query
+                    .iter()
+                    .map(|(&unit_id, &position, &faction, goal)| UnitObservation {
+                        unit: unit_manifest.name(unit_id).to_string(),
+                        position,
+                        faction,
+                        goal: GoalKind::from(goal),
+                    })
+                    .collect(),
+                structures: structure_query
+                    .iter()
+                    .map(
+                        |(&structure_id, &position, maybe_owner, ghost)| StructureObservation {
+                            structure: structure_manifest.name(structure_id).to_string(),
+                            position,
+                            owner: maybe_owner.copied(),
+                            ghost,
+                        },
+                    )
+                    .collect(),
+                items: item_totals
+                    .counts()
+                    .iter()
+                    .map(|(&item_id, &count)| (item_manifest.name(item_id).to_string(), count))
+                    .collect(),
+            }),
+            ControlRequest::Act(action) => match take_action(
+                action,
+                &structure_manifest,
+                &map_geometry,
+                &demolishable_query,
+                &mut commands,
+            ) {
+                Ok(()) => ControlResponse::Accepted,
+                Err(reason) => ControlResponse::Error(reason),
+            },
+            ControlRequest::Step(ticks) => {
+                if !control_server.config.lockstep {
+                    ControlResponse::Error(
+                        "The simulation is running in real time, and cannot be stepped".to_string(),
+                    )
+                } else if ticks == 0 {
+                    let tick = control_server.tick;
+                    ControlResponse::Stepped { tick }
+                } else {
+                    control_server.pending_ticks = ticks;
+                    // The response is sent once the ticks have been simulated,
+                    // and later requests wait until then
+                    return;
+                }
+            }
+        };
+
+        control_server.respond(&response);
+    }
+}
+
+/// Carries out the `action` on behalf of the player.
+fn take_action(
+    action: ControlAction,
+    structure_manifest: &StructureManifest,
+    map_geometry: &MapGeometry,
+    demolishable_query: &Query<(), (With<Id<Structure>>, Without<Landmark>)>,
+    commands: &mut Commands,
+) -> Result<(), String> {
+    /// The position just above the terrain at `hex`, where structures are built.
+    fn surface(hex: Hex, map_geometry: &MapGeometry) -> Result<VoxelPos, String> {
+        let height = map_geometry
+            .get_height(hex)
+            .map_err(|_| format!("({}, {}) is not on the map", hex.x, hex.y))?;
+
+        Ok(VoxelPos { hex, height }.above())
+    }
+
+    match action {
+        ControlAction::Zone { structure, hex } => {
+            let structure_id = structure_manifest
+                .variants()
+                .into_iter()
+                .find(|&structure_id| structure_manifest.name(structure_id) == structure)
+                .ok_or_else(|| format!("Unknown structure {structure}"))?;
+
+            commands.spawn_ghost_structure(
+                surface(hex, map_geometry)?,
+                ClipboardData::generate_from_id(structure_id, structure_manifest),
+                Faction::PLAYER,
+            );
+        }
+        ControlAction::ClearZoning { hex } => {
+            commands.despawn_ghost_structure(surface(hex, map_geometry)?);
+        }
+        ControlAction::Demolish { hex } => {
+            let structure_entity = map_geometry
+                .get_structure(surface(hex, map_geometry)?)
+                .filter(|&entity| demolishable_query.contains(entity))
+                .ok_or_else(|| {
+                    format!(
+                        "There is no structure to demolish at ({}, {})",
+                        hex.x, hex.y
+                    )
+                })?;
+
+            commands
+                .entity(structure_entity)
+                .insert(MarkedForDemolition);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_round_trip_through_json() {
+        let requests = [
+            ControlRequest::Observe,
+            ControlRequest::Step(10),
+            ControlRequest::Act(ControlAction::Zone {
+                structure: "ant_hive".to_string(),
+                hex: Hex::new(1, -2),
+            }),
+        ];
+
+        for request in requests {
+            let json = serde_json::to_string(&request).unwrap();
+            assert_eq!(
+                serde_json::from_str::<ControlRequest>(&json).unwrap(),
+                request
+            );
+        }
+    }
+
+    #[test]
+    fn lockstep_waits_for_the_controller() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = ControlApiConfig {
+            address: listener.local_addr().unwrap(),
+            lockstep: true,
+        };
+        let mut control_server = ControlServer::new(config, listener);
+        assert!(!control_server.allows_tick(false));
+        assert!(control_server.allows_tick(true));
+
+        control_server.config.lockstep = false;
+        assert!(control_server.allows_tick(false));
+    }
+
+    /// Stands in for the pause state, which only lets requested ticks through.
+    fn playing() -> bool {
+        false
+    }
+
+    #[test]
+    fn steps_run_while_paused() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = ControlApiConfig {
+            address: listener.local_addr().unwrap(),
+            lockstep: true,
+        };
+
+        let mut world = World::new();
+        world.insert_resource(ControlServer::new(config, listener));
+        world.insert_resource(Time::<Fixed>::from_hz(10.));
+        let mut virtual_time = Time::<Virtual>::default();
+        virtual_time.pause();
+        world.insert_resource(virtual_time);
+        world.init_resource::<Time>();
+
+        let mut fixed_update = Schedule::new(FixedUpdate);
+        fixed_update
+            .add_systems(count_ticks.in_set(SimulationSet))
+            .configure_sets(
+                SimulationSet
+                    .run_if(playing.or_else(stepping::is_stepping))
+                    .run_if(controller_allows_tick),
+            );
+        world.add_schedule(fixed_update);
+
+        let mut update = Schedule::default();
+        update.add_systems(run_controller_steps);
+
+        // Ordinary ticks don't advance the simulation while paused
+        world.run_schedule(FixedUpdate);
+        assert_eq!(world.resource::<ControlServer>().tick, 0);
+
+        world.resource_mut::<ControlServer>().pending_ticks = 3;
+        update.run(&mut world);
+        let control_server = world.resource::<ControlServer>();
+        assert_eq!(control_server.tick, 3);
+        assert_eq!(control_server.pending_ticks, 0);
+    }
+}
@@ -0,0 +1,219 @@
+//! Recipes that hatch new units, and the nurseries that speed them up.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    factions::Faction,
+    geometry::{Facing, MapGeometry, VoxelPos},
+    structures::{
+        structure_manifest::{Structure, StructureManifest},
+        Footprint,
+    },
+    units::{
+        census::Newborn, occupancy::TileOccupancy, unit_assets::UnitHandles,
+        unit_manifest::UnitManifest, UnitBundle,
+    },
+};
+
+use super::{
+    inventories::CraftingState,
+    recipe::{ActiveRecipe, RecipeManifest},
+    status::CraftingStatus,
+};
+
+/// A structure that speeds up hatching at nearby crafters.
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Nursery {
+    /// How far away (in tiles) a crafter can be and still benefit from this nursery.
+    pub radius: u32,
+    /// How much faster hatching recipes progress within range, as a fraction of their normal rate.
+    ///
+    /// The bonuses of overlapping nurseries add together.
+    pub hatching_speedup: f32,
+}
+
+/// The factor that hatching progress at `crafter_pos` is multiplied by, given the `nurseries` on the map.
+pub(super) fn hatching_multiplier<'a>(
+    crafter_pos: VoxelPos,
+    nurseries: impl IntoIterator<Item = (&'a VoxelPos, &'a Nursery)>,
+) -> f32 {
+    let speedup: f32 = nurseries
+        .into_iter()
+        .filter(|(nursery_pos, nursery)| {
+            crafter_pos.hex.unsigned_distance_to(nursery_pos.hex) <= nursery.radius
+        })
+        .map(|(_, nursery)| nursery.hatching_speedup)
+        .sum();
+
+    1.0 + speedup
+}
+
+/// Finds an empty spot just outside of a structure's footprint for a newly hatched unit to stand on.
+///
+/// Returns [`None`] if every walkable voxel around the structure is full.
+fn hatching_site(
+    center: VoxelPos,
+    facing: Facing,
+    footprint: &Footprint,
+    map_geometry: &MapGeometry,
+    tile_occupancy: &TileOccupancy,
+) -> Option<VoxelPos> {
+    let occupied = footprint.normalized(facing, center);
+
+    occupied
+        .iter()
+        .flat_map(|voxel_pos| voxel_pos.all_neighbors())
+        .filter(|neighbor| !occupied.contains(neighbor))
+        // Allow for uneven terrain around the structure
+        .flat_map(|neighbor| [neighbor, neighbor.above(), neighbor.below()])
+        .find(|&candidate| {
+            map_geometry.is_walkable(candidate) && !tile_occupancy.is_full(candidate)
+        })
+}
+
+/// Spawns the units hatched by completed recipes next to their crafters.
+///
+/// If there's no room for the new unit, the recipe waits until space frees up.
+pub(super) fn hatch_units(
+    mut crafter_query: Query<(
+        &Id<Structure>,
+        &VoxelPos,
+        &Facing,
+        &ActiveRecipe,
+        &mut CraftingState,
+        &mut CraftingStatus,
+        Option<&Faction>,
+    )>,
+    recipe_manifest: Res<RecipeManifest>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    unit_handles: Res<UnitHandles>,
+    map_geometry: Res<MapGeometry>,
+    mut tile_occupancy: ResMut<TileOccupancy>,
+    mut commands: Commands,
+) {
+    for (
+        &structure_id,
+        &voxel_pos,
+        &facing,
+        active_recipe,
+        mut crafting_state,
+        mut crafting_status,
+        maybe_faction,
+    ) in crafter_query.iter_mut()
+    {
+        if !matches!(*crafting_state, CraftingState::RecipeComplete) {
+            continue;
+        }
+
+        let Some(recipe_id) = active_recipe.recipe_id() else {
+            continue;
+        };
+
+        let recipe = recipe_manifest.get(*recipe_id);
+        let Some(unit_id) = recipe.hatches else {
+            continue;
+        };
+
+        let footprint = &structure_manifest.get(structure_id).footprint;
+        match hatching_site(voxel_pos, facing, footprint, &map_geometry, &tile_occupancy) {
+            Some(hatch_pos) => {
+                let unit_data = unit_manifest.get(unit_id).clone();
+                commands.spawn((
+                    UnitBundle::newborn(
+                        unit_id,
+                        hatch_pos,
+                        unit_data,
+                        // Units hatched by wild crafters join the player's colony
+                        maybe_faction.copied().unwrap_or_default(),
+                        &unit_handles,
+                    ),
+                    Newborn,
+                ));
+                // Record the newborn right away, so crafters hatching on the same tick don't overfill the tile
+                tile_occupancy.add(hatch_pos);
+            }
+            None => {
+                // Hold the finished recipe until there's room, retrying each tick
+                *crafting_state = CraftingState::InProgress {
+                    progress: recipe.craft_time,
+                    required: recipe.craft_time,
+                };
+                crafting_status.set_if_neq(CraftingStatus::OutputFull);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nurseries_only_speed_up_nearby_crafters() {
+        let nursery = Nursery {
+            radius: 2,
+            hatching_speedup: 0.5,
+        };
+        let near = VoxelPos::from_xy(1, 0);
+        let far = VoxelPos::from_xy(5, 0);
+        let nurseries = [(&near, &nursery), (&far, &nursery)];
+
+        assert_eq!(hatching_multiplier(VoxelPos::ZERO, nurseries), 1.5);
+        assert_eq!(
+            hatching_multiplier(VoxelPos::from_xy(-5, 0), nurseries),
+            1.0
+        );
+        // Overlapping nurseries stack
+        assert_eq!(hatching_multiplier(VoxelPos::from_xy(3, 0), nurseries), 2.0);
+    }
+
+    #[test]
+    fn hatching_site_is_outside_the_footprint() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let tile_occupancy = TileOccupancy::default();
+        let center = VoxelPos::ZERO.above();
+        let footprint = Footprint::hexagon(1);
+
+        let hatch_pos = hatching_site(
+            center,
+            Facing::default(),
+            &footprint,
+            &map_geometry,
+            &tile_occupancy,
+        )
+        .unwrap();
+
+        assert_eq!(center.hex.unsigned_distance_to(hatch_pos.hex), 2);
+    }
+
+    #[test]
+    fn hatching_is_blocked_when_all_tiles_are_full() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let mut tile_occupancy = TileOccupancy {
+            max_units_per_tile: Some(1),
+            ..default()
+        };
+        let center = VoxelPos::ZERO.above();
+        let footprint = Footprint::single();
+
+        for neighbor in center.all_neighbors() {
+            tile_occupancy.add(neighbor);
+        }
+
+        assert_eq!(
+            hatching_site(
+                center,
+                Facing::default(),
+                &footprint,
+                &map_geometry,
+                &tile_occupancy
+            ),
+            None
+        );
+    }
+}
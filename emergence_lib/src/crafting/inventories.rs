@@ -8,7 +8,7 @@ use super::{
 use crate::{
     asset_management::manifest::Id,
     items::{
-        errors::{AddManyItemsError, AddOneItemError},
+        errors::{AddManyItemsError, AddOneItemError, TransferError},
         inventory::Inventory,
         item_manifest::{Item, ItemManifest},
         slot::ItemSlot,
@@ -19,7 +19,7 @@ use crate::{
 use std::{fmt::Display, time::Duration};
 
 use bevy::prelude::*;
-use rand::{distributions::Uniform, prelude::Distribution, rngs::ThreadRng, Rng};
+use rand::{distributions::Uniform, prelude::Distribution, Rng};
 use serde::{Deserialize, Serialize};
 
 /// The current state in the crafting progress.
@@ -161,43 +161,40 @@ impl InputInventory {
     }
 
     /// Try to add items to this inventory.
+    ///
+    /// Either all of the items are added, or none of them are.
     pub fn fill_with_items(
         &mut self,
         item_count: &ItemCount,
         item_manifest: &ItemManifest,
-    ) -> Result<(), AddToInputError> {
+    ) -> Result<(), TransferError> {
         if let InputInventory::Tagged { tag, .. } = self {
             if !item_manifest.has_tag(item_count.item_id, *tag) {
-                return Err(AddToInputError::IncorrectItemTags);
+                return Err(TransferError::WrongItemKind {
+                    item_id: item_count.item_id,
+                });
             }
         };
 
-        match self
-            .inventory_mut()
-            .add_item_all_or_nothing(item_count, item_manifest)
-        {
-            Ok(()) => Ok(()),
-            Err(AddOneItemError { excess_count }) => {
-                Err(AddToInputError::NotEnoughSpace { excess_count })
-            }
-        }
+        self.inventory_mut()
+            .give_items(&[item_count.clone()], item_manifest)
     }
 
     /// Try to remove the items specified by `recipe` from the inventory.
+    ///
+    /// On success, returns the items that were removed.
     pub fn consume_items(
         &mut self,
         recipe_input: &RecipeInput,
         item_manifest: &ItemManifest,
-    ) -> Result<(), ConsumeInputError> {
+    ) -> Result<Vec<ItemCount>, ConsumeInputError> {
         let inventory = self.inventory_mut();
 
         match recipe_input {
-            RecipeInput::Exact(item_counts) => {
-                match inventory.remove_items_all_or_nothing(item_counts) {
-                    Ok(()) => Ok(()),
-                    Err(_) => Err(ConsumeInputError::NotEnoughItems),
-                }
-            }
+            RecipeInput::Exact(item_counts) => match inventory.take_items(item_counts) {
+                Ok(()) => Ok(item_counts.clone()),
+                Err(_) => Err(ConsumeInputError::NotEnoughItems),
+            },
             RecipeInput::Flexible { tag, count } => {
                 let mut remaining_to_remove = *count;
                 let mut proposed_removal: Vec<ItemCount> = Vec::new();
@@ -226,8 +223,8 @@ impl InputInventory {
                     return Err(ConsumeInputError::NotEnoughItems);
                 }
 
-                match inventory.remove_items_all_or_nothing(&proposed_removal) {
-                    Ok(()) => Ok(()),
+                match inventory.take_items(&proposed_removal) {
+                    Ok(()) => Ok(proposed_removal),
                     Err(_) => panic!("Inventory should have had enough items to remove"),
                 }
             }
@@ -270,18 +267,6 @@ pub enum ConsumeInputError {
     IncorrectItemTags,
 }
 
-/// An error that can occur when trying to add items to an [`InputInventory`].
-#[derive(Clone, Debug)]
-pub enum AddToInputError {
-    /// Not enough space in the inventory.
-    NotEnoughSpace {
-        /// The items that could not be added.
-        excess_count: ItemCount,
-    },
-    /// The items in the inventory did not match the provided recipe.
-    IncorrectItemTags,
-}
-
 /// The output inventory for a structure.
 #[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
 pub(crate) struct OutputInventory {
@@ -307,7 +292,7 @@ impl OutputInventory {
         &mut self,
         recipe: &RecipeData,
         item_manifest: &ItemManifest,
-        rng: &mut ThreadRng,
+        rng: &mut impl Rng,
     ) -> Result<(), AddManyItemsError> {
         let mut overflow: Vec<ItemCount> = Vec::new();
 
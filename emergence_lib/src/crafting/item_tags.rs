@@ -21,6 +21,8 @@ pub enum ItemTag {
     Fluid,
     /// Items that float
     Buoyant,
+    /// The remains of dead organisms.
+    Corpse,
 }
 
 impl ItemTag {
@@ -31,6 +33,7 @@ impl ItemTag {
             ItemTag::Seed => "Seed",
             ItemTag::Fluid => "Fluid",
             ItemTag::Buoyant => "Buoyant",
+            ItemTag::Corpse => "Corpse",
         }
     }
 }
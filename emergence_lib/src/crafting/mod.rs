@@ -6,17 +6,28 @@ use recipe::{RawRecipeManifest, RecipeManifest};
 use crate::{
     asset_management::manifest::{plugin::ManifestPlugin, Id},
     construction::{demolition::MarkedForDemolition, ghosts::WorkplaceId},
-    geometry::{MapGeometry, VoxelPos},
+    geometry::{MapGeometry, MapLayer, VoxelPos},
     items::{
+        bulk::Tanks,
         inventory::Inventory,
         item_manifest::{ItemManifest, RawItemManifest},
+        ledger::{ItemLedger, ItemSink, ItemSource},
     },
     light::shade::ReceivedLight,
-    organisms::{energy::EnergyPool, lifecycle::Lifecycle, Organism},
-    player_interaction::InteractionSystem,
+    logistics::OutputRouting,
+    organisms::{
+        dormancy::Dormant,
+        energy::{ColonyEnergy, EnergyPool},
+        lifecycle::Lifecycle,
+        Organism,
+    },
+    player_interaction::{bulk_commands::Disabled, InteractionSystem},
     signals::{Emitter, SignalStrength, SignalType},
-    simulation::SimulationSet,
+    simulation::{rng::SystemRng, SimulationSet},
     structures::structure_manifest::{Structure, StructureManifest},
+    temperature::Temperature,
+    terrain::fertility::SoilFertility,
+    units::census::PopulationTargets,
 };
 
 use std::time::Duration;
@@ -24,15 +35,29 @@ use std::time::Duration;
 use bevy::{ecs::query::WorldQuery, prelude::*};
 
 use self::{
+    hatching::{hatch_units, hatching_multiplier, Nursery},
     inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
     item_tags::{ItemKind, ItemTag},
+    orders::CraftOrderPlugin,
+    pending_work::PendingWork,
+    queue::RecipeQueuePlugin,
     recipe::{ActiveRecipe, RecipeInput},
+    recipe_graph::RecipeGraphPlugin,
+    status::CraftingStatus,
+    throughput::CraftingHistory,
     workers::WorkersPresent,
 };
 
+pub mod hatching;
 pub mod inventories;
 pub mod item_tags;
+pub mod orders;
+pub mod pending_work;
+pub mod queue;
 pub mod recipe;
+pub mod recipe_graph;
+pub mod status;
+pub mod throughput;
 pub mod workers;
 
 /// Add crafting capabilities to structures.
@@ -42,11 +67,18 @@ impl Plugin for CraftingPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ManifestPlugin::<RawItemManifest>::new())
             .add_plugins(ManifestPlugin::<RawRecipeManifest>::new())
+            .add_plugins(RecipeGraphPlugin)
+            .add_plugins(CraftOrderPlugin)
+            .add_plugins(RecipeQueuePlugin)
             .add_systems(
                 FixedUpdate,
                 (
+                    update_tanks.before(progress_crafting),
                     progress_crafting,
-                    gain_energy_when_crafting_completes.after(progress_crafting),
+                    hatch_units
+                        .after(progress_crafting)
+                        .before(apply_recipe_energy),
+                    apply_recipe_energy.after(progress_crafting),
                     set_crafting_emitter
                         .after(progress_crafting)
                         // This must run before zoning, to avoid wiping out the destruction signal
@@ -74,6 +106,12 @@ pub(crate) struct CraftingBundle {
     /// The current state for the crafting process.
     craft_state: CraftingState,
 
+    /// Why crafting is or isn't progressing.
+    craft_status: CraftingStatus,
+
+    /// The most recently completed crafts.
+    craft_history: CraftingHistory,
+
     /// Emits signals, drawing units towards this structure to ensure crafting flows smoothly
     emitter: Emitter,
 
@@ -100,6 +138,8 @@ impl CraftingBundle {
                 output_inventory: recipe.output_inventory(item_manifest),
                 active_recipe: ActiveRecipe(Some(recipe_id)),
                 craft_state: CraftingState::NeedsInput,
+                craft_status: CraftingStatus::default(),
+                craft_history: CraftingHistory::default(),
                 emitter: Emitter::default(),
                 workers_present: WorkersPresent::new(max_workers),
             }
@@ -113,6 +153,8 @@ impl CraftingBundle {
                 },
                 active_recipe: ActiveRecipe(None),
                 craft_state: CraftingState::NeedsInput,
+                craft_status: CraftingStatus::default(),
+                craft_history: CraftingHistory::default(),
                 emitter: Emitter::default(),
                 workers_present: WorkersPresent::new(max_workers),
             }
@@ -128,6 +170,10 @@ struct CraftingQuery {
     active_recipe: &'static ActiveRecipe,
     /// The status of crafting
     state: &'static mut CraftingState,
+    /// Why crafting is or isn't progressing
+    status: &'static mut CraftingStatus,
+    /// The recently completed crafts, if they are being tracked
+    maybe_history: Option<&'static mut CraftingHistory>,
     /// The inputs
     input: &'static mut InputInventory,
     /// The outputs
@@ -138,44 +184,146 @@ struct CraftingQuery {
     voxel_pos: &'static VoxelPos,
     /// Is the structure an organism?
     maybe_organism: Option<&'static Organism>,
+    /// The energy available to the crafter, if it is an organism.
+    maybe_energy_pool: Option<&'static mut EnergyPool>,
+    /// The bulk resources stored by the crafter, if its recipe uses any.
+    maybe_tanks: Option<&'static mut Tanks>,
+    /// Is the crafter dormant?
+    maybe_dormant: Option<&'static Dormant>,
+    /// Has the crafter been switched off by the player?
+    disabled: Has<Disabled>,
 }
 
+/// The amount of soil fertility consumed by organisms for each second that they spend crafting.
+const FERTILITY_CONSUMPTION_PER_SECOND: f32 = 0.1;
+
 /// Progress the state of recipes that are being crafted.
 fn progress_crafting(
     time: Res<Time>,
     recipe_manifest: Res<RecipeManifest>,
     item_manifest: Res<ItemManifest>,
-    terrain_query: Query<&ReceivedLight>,
+    mut terrain_query: Query<(&ReceivedLight, &Temperature, &mut SoilFertility)>,
     mut crafting_query: Query<CraftingQuery>,
+    nursery_query: Query<(&VoxelPos, &Nursery)>,
     map_geometry: Res<MapGeometry>,
+    population_targets: Res<PopulationTargets>,
+    mut item_ledger: ResMut<ItemLedger>,
+    mut colony_energy: ResMut<ColonyEnergy>,
+    mut system_rng: SystemRng,
 ) {
-    let rng = &mut rand::thread_rng();
+    let rng = system_rng.get("progress_crafting");
+    let now = time.elapsed();
 
     for mut crafter in crafting_query.iter_mut() {
+        // Disabled structures hold their progress until they are switched back on
+        if crafter.disabled {
+            crafter.status.set_if_neq(CraftingStatus::Disabled);
+            continue;
+        }
+
+        // Dormant organisms wait for conditions to improve before continuing
+        if crafter
+            .maybe_dormant
+            .is_some_and(|dormant| dormant.blocks_crafting())
+        {
+            crafter.status.set_if_neq(CraftingStatus::MissingConditions);
+            continue;
+        }
+
+        let mut status = CraftingStatus::Working;
+
         *crafter.state = match *crafter.state {
             CraftingState::NoRecipe => match crafter.active_recipe.recipe_id() {
                 Some(_) => CraftingState::NeedsInput,
-                None => CraftingState::NoRecipe,
+                None => {
+                    status = CraftingStatus::Idle;
+                    CraftingState::NoRecipe
+                }
             },
             CraftingState::NeedsInput | CraftingState::Overproduction => {
                 if let Some(recipe_id) = crafter.active_recipe.recipe_id() {
+                    // Don't start producing more offspring if the population is already at its target
+                    if population_targets.is_throttled(*recipe_id) {
+                        crafter.status.set_if_neq(CraftingStatus::MissingConditions);
+                        continue;
+                    }
+
                     let recipe = recipe_manifest.get(*recipe_id);
+
+                    // Organisms cannot start recipes whose energy cost they cannot pay
+                    if let (Some(cost), Some(energy_pool)) =
+                        (recipe.energy_cost(), crafter.maybe_energy_pool.as_deref())
+                    {
+                        if !energy_pool.can_afford(cost) {
+                            crafter.status.set_if_neq(CraftingStatus::MissingConditions);
+                            continue;
+                        }
+                    }
+
+                    // Bulk resources must be on hand too, but are only drained once the items are
+                    if !recipe.bulk_inputs.is_empty() {
+                        let missing = match crafter.maybe_tanks.as_deref() {
+                            Some(tanks) => tanks.missing(&recipe.bulk_inputs),
+                            None => recipe.bulk_inputs.first().copied(),
+                        };
+
+                        if let Some(missing) = missing {
+                            crafter
+                                .status
+                                .set_if_neq(CraftingStatus::MissingBulkInputs { missing });
+                            continue;
+                        }
+                    }
+
                     // Check if we have enough items, and if so, start crafting
                     match crafter.input.consume_items(&recipe.inputs, &item_manifest) {
-                        Ok(()) => {
+                        Ok(consumed) => {
+                            if let Some(tanks) = crafter.maybe_tanks.as_mut() {
+                                // We checked above that everything needed is stored
+                                tanks.drain_all(&recipe.bulk_inputs).unwrap();
+                            }
+
+                            // The energy cost is paid up front, alongside the inputs
+                            if let (Some(cost), Some(energy_pool)) =
+                                (recipe.energy_cost(), crafter.maybe_energy_pool.as_mut())
+                            {
+                                let spent = energy_pool.debit(cost);
+                                colony_energy.record_spending(spent);
+                            }
+
                             // If this is crafting with flexible inputs, clear the input slots
                             if matches!(recipe.inputs, RecipeInput::Flexible { .. }) {
                                 crafter.input.clear_empty_slots();
                             }
 
+                            for item_count in &consumed {
+                                item_ledger.record_consumed(
+                                    item_count.item_id,
+                                    item_count.count,
+                                    ItemSink::Crafting,
+                                );
+                            }
+
+                            if let Some(history) = crafter.maybe_history.as_mut() {
+                                history.record_start(now, consumed);
+                            }
+
                             CraftingState::InProgress {
                                 progress: Duration::ZERO,
                                 required: recipe.craft_time,
                             }
                         }
-                        Err(_) => CraftingState::NeedsInput,
+                        Err(_) => {
+                            status = CraftingStatus::missing_inputs(
+                                &crafter.input,
+                                &recipe.inputs,
+                                &item_manifest,
+                            );
+                            CraftingState::NeedsInput
+                        }
                     }
                 } else {
+                    status = CraftingStatus::Idle;
                     CraftingState::NoRecipe
                 }
             }
@@ -185,21 +333,47 @@ fn progress_crafting(
                     let recipe = recipe_manifest.get(*recipe_id);
                     let terrain_entity = map_geometry.get_terrain(crafter.voxel_pos.hex).unwrap();
 
-                    let received_light = terrain_query.get(terrain_entity).unwrap();
+                    let (surface_light, &temperature, mut soil_fertility) =
+                        terrain_query.get_mut(terrain_entity).unwrap();
+                    let received_light = match map_geometry.layer_of(*crafter.voxel_pos) {
+                        MapLayer::Surface => surface_light,
+                        MapLayer::Underground => &ReceivedLight::UNDERGROUND,
+                    };
 
                     // Check if we can make progress
-                    if recipe.satisfied(crafter.workers_present.current(), received_light) {
+                    if recipe.satisfied(
+                        crafter.workers_present.current(),
+                        received_light,
+                        temperature,
+                    ) {
                         // Many hands make light work!
-                        if recipe.workers_required() > 0 {
-                            updated_progress += Duration::from_secs_f32(
+                        let mut delta = if recipe.workers_required() > 0 {
+                            Duration::from_secs_f32(
                                 time.delta().as_secs_f32()
                                     * crafter.workers_present.effective_workers()
                                     / recipe.workers_required() as f32,
-                            );
+                            )
                         } else {
-                            updated_progress += time.delta();
+                            time.delta()
+                        };
+
+                        // Organisms grow faster in fertile soil, drawing down its nutrients as they do
+                        if crafter.maybe_organism.is_some() {
+                            delta = delta.mul_f32(soil_fertility.growth_multiplier());
+                            soil_fertility
+                                .drain(FERTILITY_CONSUMPTION_PER_SECOND * delta.as_secs_f32());
                         }
 
+                        // Nurseries speed up hatching nearby
+                        if recipe.hatches.is_some() {
+                            delta = delta.mul_f32(hatching_multiplier(
+                                *crafter.voxel_pos,
+                                nursery_query.iter(),
+                            ));
+                        }
+
+                        updated_progress += delta;
+
                         if updated_progress >= required {
                             CraftingState::RecipeComplete
                         } else {
@@ -209,46 +383,110 @@ fn progress_crafting(
                             }
                         }
                     } else {
+                        status = CraftingStatus::MissingConditions;
                         CraftingState::InProgress { progress, required }
                     }
                 } else {
+                    status = CraftingStatus::Idle;
                     CraftingState::NoRecipe
                 }
             }
             CraftingState::RecipeComplete => {
                 if let Some(recipe_id) = crafter.active_recipe.recipe_id() {
                     let recipe = recipe_manifest.get(*recipe_id);
+                    let before = throughput::contents(&crafter.output);
                     // Actually produce the items
-                    match crafter.maybe_organism {
-                        Some(_) => {
-                            match crafter.output.craft(recipe, &item_manifest, rng) {
-                                Ok(_) => CraftingState::NeedsInput,
-                                // TODO: handle the waste products somehow
-                                Err(_) => CraftingState::Overproduction,
-                            }
+                    let result = crafter.output.craft(recipe, &item_manifest, rng);
+                    let produced = throughput::gained(&before, &crafter.output);
+
+                    // Bulk outputs that don't fit in the tanks are spilled
+                    if let Some(tanks) = crafter.maybe_tanks.as_mut() {
+                        tanks.fill_all(&recipe.bulk_outputs);
+                    }
+
+                    for item_count in &produced {
+                        item_ledger.record_produced(
+                            item_count.item_id,
+                            item_count.count,
+                            ItemSource::Crafting,
+                        );
+                    }
+
+                    if let Some(history) = crafter.maybe_history.as_mut() {
+                        history.record_finish(now, produced);
+                    }
+
+                    match (crafter.maybe_organism, result) {
+                        (_, Ok(())) => CraftingState::NeedsInput,
+                        // TODO: handle the waste products somehow
+                        (Some(_), Err(_)) => CraftingState::Overproduction,
+                        (None, Err(_)) => {
+                            status = CraftingStatus::OutputFull;
+                            CraftingState::FullAndBlocked
                         }
-                        None => match crafter.output.craft(recipe, &item_manifest, rng) {
-                            Ok(()) => CraftingState::NeedsInput,
-                            Err(_) => CraftingState::FullAndBlocked,
-                        },
                     }
                 } else {
+                    status = CraftingStatus::Idle;
                     CraftingState::NoRecipe
                 }
             }
             CraftingState::FullAndBlocked => {
                 let mut item_slots = crafter.output.iter();
                 match item_slots.any(|slot| slot.is_full()) {
-                    true => CraftingState::FullAndBlocked,
+                    true => {
+                        status = CraftingStatus::OutputFull;
+                        CraftingState::FullAndBlocked
+                    }
                     false => CraftingState::NeedsInput,
                 }
             }
         };
+
+        // Once the outputs have been stored, the craft is complete
+        if matches!(
+            *crafter.state,
+            CraftingState::NeedsInput | CraftingState::Overproduction
+        ) {
+            if let Some(history) = crafter.maybe_history.as_mut() {
+                history.record_delivery(now);
+            }
+        }
+
+        crafter.status.set_if_neq(status);
+    }
+}
+
+/// Gives crafters the tanks needed to store the bulk resources of their current recipe.
+///
+/// Whatever is already stored is kept, as long as the new tanks have room for it.
+fn update_tanks(
+    mut commands: Commands,
+    mut crafter_query: Query<(Entity, &ActiveRecipe, Option<&mut Tanks>), Changed<ActiveRecipe>>,
+    recipe_manifest: Res<RecipeManifest>,
+) {
+    for (entity, active_recipe, maybe_tanks) in crafter_query.iter_mut() {
+        let new_tanks = match active_recipe.recipe_id() {
+            Some(recipe_id) => recipe_manifest.get(*recipe_id).tanks(),
+            None => Tanks::default(),
+        };
+
+        match (maybe_tanks, new_tanks.is_empty()) {
+            (Some(_), true) => {
+                commands.entity(entity).remove::<Tanks>();
+            }
+            (Some(mut tanks), false) => tanks.replace_with(new_tanks),
+            (None, false) => {
+                commands.entity(entity).insert(new_tanks);
+            }
+            (None, true) => (),
+        }
     }
 }
 
 /// Sessile organisms gain energy when they finish crafting recipes.
-fn gain_energy_when_crafting_completes(
+///
+/// Energy costs are paid by [`progress_crafting`] when the recipe starts instead.
+fn apply_recipe_energy(
     mut sessile_query: Query<(
         &mut EnergyPool,
         &mut Lifecycle,
@@ -256,16 +494,19 @@ fn gain_energy_when_crafting_completes(
         &ActiveRecipe,
     )>,
     recipe_manifest: Res<RecipeManifest>,
+    mut colony_energy: ResMut<ColonyEnergy>,
 ) {
     for (mut energy_pool, mut lifecycle, crafting_state, active_recipe) in sessile_query.iter_mut()
     {
         if matches!(crafting_state, CraftingState::RecipeComplete) {
             if let Some(recipe_id) = active_recipe.recipe_id() {
                 let recipe = recipe_manifest.get(*recipe_id);
-                if let Some(energy) = recipe.energy {
-                    let proposed = energy_pool.current() + energy;
-                    energy_pool.set_current(proposed);
+
+                if let Some(energy) = recipe.energy_produced() {
+                    let gained = energy_pool.credit(energy);
+                    // Energy gained counts towards lifecycles even if it overflows the pool
                     lifecycle.record_energy_gained(energy);
+                    colony_energy.record_gain(gained);
                 }
             }
         }
@@ -283,6 +524,9 @@ pub(crate) fn set_crafting_emitter(
             &Id<Structure>,
             &WorkersPresent,
             &ActiveRecipe,
+            &CraftingStatus,
+            Has<OutputRouting>,
+            Has<Disabled>,
         ),
         Without<MarkedForDemolition>,
     >,
@@ -297,6 +541,9 @@ pub(crate) fn set_crafting_emitter(
         &structure_id,
         workers_present,
         active_recipe,
+        crafting_status,
+        routed,
+        disabled,
     ) in crafting_query.iter_mut()
     {
         // Reset and recompute all signals
@@ -329,30 +576,46 @@ pub(crate) fn set_crafting_emitter(
         }
 
         // Output signals
-        for item_slot in output_inventory.iter() {
-            if item_slot.is_full() {
-                let signal_type = SignalType::Push(ItemKind::Single(item_slot.item_id()));
-                let signal_strength = SignalStrength::new(10.);
-                emitter.signals.push((signal_type, signal_strength));
-            } else if !item_slot.is_empty() {
-                let signal_type = SignalType::Contains(ItemKind::Single(item_slot.item_id()));
-                let signal_strength = SignalStrength::new(10.);
-                emitter.signals.push((signal_type, signal_strength));
+        // Routed outputs are only moved by hauling jobs, so units should not be drawn to them
+        if !routed {
+            for item_slot in output_inventory.iter() {
+                if item_slot.is_full() {
+                    let signal_type = SignalType::Push(ItemKind::Single(item_slot.item_id()));
+                    let signal_strength = SignalStrength::new(10.);
+                    emitter.signals.push((signal_type, signal_strength));
+                } else if !item_slot.is_empty() {
+                    let signal_type = SignalType::Contains(ItemKind::Single(item_slot.item_id()));
+                    let signal_strength = SignalStrength::new(10.);
+                    emitter.signals.push((signal_type, signal_strength));
+                }
             }
         }
 
         // Work signals
-        if let CraftingState::InProgress { .. } = crafting_state {
-            if let Some(recipe_id) = active_recipe.recipe_id() {
+        // Disabled structures hold their progress, so there is no point in working on them
+        let needs_workers = match (crafting_state, active_recipe.recipe_id()) {
+            (CraftingState::InProgress { .. }, Some(recipe_id)) if !disabled => {
                 let recipe = recipe_manifest.get(*recipe_id);
-                if workers_present.needs_more() && recipe.needs_workers() {
-                    let signal_strength = SignalStrength::new(100.);
-                    emitter.signals.push((
-                        SignalType::Work(WorkplaceId::structure(structure_id)),
-                        signal_strength,
-                    ));
-                }
+                workers_present.needs_more() && recipe.needs_workers()
             }
+            _ => false,
+        };
+
+        if needs_workers {
+            let signal_strength = SignalStrength::new(100.);
+            emitter.signals.push((
+                SignalType::Work(WorkplaceId::structure(structure_id)),
+                signal_strength,
+            ));
+        }
+
+        // Disabled structures have no pending work, even before their status catches up
+        if disabled {
+            continue;
+        }
+
+        if let Some(pending_work) = PendingWork::for_crafting(crafting_status, needs_workers) {
+            emitter.signals.push(pending_work.signal());
         }
     }
 }
@@ -0,0 +1,604 @@
+//! Player orders to craft a specific number of an item, such as "make 10 leuco chunks".
+//!
+//! Each [`CraftOrder`] is its own entity.
+//! While an order is open, it:
+//! 1. Assigns crafters that are already running its recipe, or that could run it but are idle, with a [`CraftOrderAssignment`].
+//! 2. [Reserves](crate::items::inventory::Reservation) the inputs that its remaining crafts will need
+//!     in the colony's output and storage inventories, oldest order first.
+//! 3. Counts the products of its assigned crafters, and closes itself once enough have been made.
+//!
+//! Deliveries to assigned crafters are given a high priority by the [logistics network](crate::logistics),
+//! and reserved inputs are only offered to the crafters working on the order that reserved them.
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::{
+        demolition::MarkedForDemolition,
+        ghosts::{Ghost, Preview},
+    },
+    items::{
+        inventory::Inventory,
+        item_manifest::{Item, ItemManifest},
+        ItemCount,
+    },
+    logistics::HaulingJob,
+    player_interaction::bulk_commands::Forbidden,
+    simulation::SimulationSet,
+    structures::structure_manifest::Structure,
+};
+
+use super::{
+    inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
+    progress_crafting,
+    recipe::{ActiveRecipe, Recipe, RecipeData, RecipeInput, RecipeManifest},
+    recipe_graph::{ProductionTarget, RecipeGraph},
+};
+
+/// Opens, assigns and tracks [`CraftOrder`]s.
+pub(super) struct CraftOrderPlugin;
+
+impl Plugin for CraftOrderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaceCraftOrder>()
+            .add_event::<CancelCraftOrder>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    place_craft_orders,
+                    cancel_craft_orders,
+                    assign_craft_orders,
+                    reserve_craft_order_inputs,
+                )
+                    .chain()
+                    .before(progress_crafting)
+                    .in_set(SimulationSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                track_craft_order_progress
+                    .after(progress_crafting)
+                    .in_set(SimulationSet),
+            );
+    }
+}
+
+/// Asks for `count` of `item_id` to be crafted.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct PlaceCraftOrder {
+    /// The item to make.
+    pub item_id: Id<Item>,
+    /// How many to make.
+    pub count: u32,
+}
+
+/// Cancels the [`CraftOrder`] stored on this entity.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelCraftOrder(pub Entity);
+
+/// An open order to craft a specific number of an item.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct CraftOrder {
+    /// The order in which this was placed, starting from 1.
+    pub number: u32,
+    /// The item to make.
+    pub item_id: Id<Item>,
+    /// The recipe used to make the item.
+    pub recipe_id: Id<Recipe>,
+    /// How many items were asked for.
+    pub requested: u32,
+    /// How many items have been made so far.
+    ///
+    /// This is fractional, as recipes with random outputs are counted by their expected output.
+    produced: f32,
+    /// The inputs set aside for the remaining crafts.
+    reserved: Vec<ItemCount>,
+    /// The number of crafters working on this order.
+    crafters: usize,
+}
+
+impl CraftOrder {
+    /// Creates a new order for `requested` of `item_id`, made with `recipe_id`.
+    pub fn new(number: u32, item_id: Id<Item>, recipe_id: Id<Recipe>, requested: u32) -> Self {
+        CraftOrder {
+            number,
+            item_id,
+            recipe_id,
+            requested,
+            produced: 0.,
+            reserved: Vec::new(),
+            crafters: 0,
+        }
+    }
+
+    /// How many items have been made so far, rounded down.
+    pub fn produced(&self) -> u32 {
+        self.produced.floor() as u32
+    }
+
+    /// The fraction of the order that has been made, between 0 and 1.
+    pub fn progress(&self) -> f32 {
+        match self.requested {
+            0 => 1.,
+            requested => (self.produced / requested as f32).min(1.),
+        }
+    }
+
+    /// Have enough items been made?
+    pub fn is_complete(&self) -> bool {
+        self.produced >= self.requested as f32
+    }
+
+    /// The number of crafts of `recipe` still needed to finish this order.
+    pub fn crafts_remaining(&self, recipe: &RecipeData) -> u32 {
+        let per_craft = recipe.outputs.expected_count(self.item_id);
+        if per_craft <= 0. || self.is_complete() {
+            return 0;
+        }
+
+        ((self.requested as f32 - self.produced) / per_craft).ceil() as u32
+    }
+
+    /// The inputs needed for the remaining crafts.
+    ///
+    /// Recipes that accept any item with a tag have no specific inputs to set aside.
+    pub fn inputs_needed(&self, recipe: &RecipeData) -> Vec<ItemCount> {
+        let crafts = self.crafts_remaining(recipe);
+        match &recipe.inputs {
+            RecipeInput::Exact(item_counts) => item_counts
+                .iter()
+                .map(|item_count| ItemCount::new(item_count.item_id, item_count.count * crafts))
+                .collect(),
+            RecipeInput::Flexible { .. } => Vec::new(),
+        }
+    }
+
+    /// The inputs set aside for the remaining crafts.
+    pub fn reserved(&self) -> &[ItemCount] {
+        &self.reserved
+    }
+
+    /// Records that a craft of `recipe` has finished.
+    fn record_craft(&mut self, recipe: &RecipeData) {
+        self.produced += recipe.outputs.expected_count(self.item_id);
+    }
+
+    /// The pretty formatting for this type.
+    pub fn display(
+        &self,
+        item_manifest: &ItemManifest,
+        recipe_manifest: &RecipeManifest,
+    ) -> String {
+        let recipe = recipe_manifest.get(self.recipe_id);
+        let needed = self.inputs_needed(recipe);
+
+        let mut string = format!(
+            "#{} {}: {}/{} ({} crafters)",
+            self.number,
+            item_manifest.name(self.item_id),
+            self.produced(),
+            self.requested,
+            self.crafters
+        );
+
+        for item_count in needed {
+            let reserved = self
+                .reserved
+                .iter()
+                .find(|reserved| reserved.item_id == item_count.item_id)
+                .map_or(0, |reserved| reserved.count);
+
+            string += &format!(
+                "\n  {}: {reserved}/{} reserved",
+                item_manifest.name(item_count.item_id),
+                item_count.count
+            );
+        }
+
+        string
+    }
+}
+
+/// Marks a crafter as working on the [`CraftOrder`] stored on `order`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CraftOrderAssignment {
+    /// The entity with the [`CraftOrder`].
+    pub order: Entity,
+}
+
+/// Opens a new [`CraftOrder`] for each [`PlaceCraftOrder`] event.
+fn place_craft_orders(
+    mut events: EventReader<PlaceCraftOrder>,
+    recipe_graph: Res<RecipeGraph>,
+    item_manifest: Res<ItemManifest>,
+    mut next_number: Local<u32>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Some(recipe_id) =
+            recipe_graph.preferred_producer(ProductionTarget::Item(event.item_id))
+        else {
+            warn!(
+                "Cannot order {}, as no recipe makes it",
+                item_manifest.name(event.item_id)
+            );
+            continue;
+        };
+
+        *next_number += 1;
+        commands.spawn(CraftOrder::new(
+            *next_number,
+            event.item_id,
+            recipe_id,
+            event.count,
+        ));
+    }
+}
+
+/// Closes the orders named by [`CancelCraftOrder`] events.
+fn cancel_craft_orders(
+    mut events: EventReader<CancelCraftOrder>,
+    order_query: Query<(), With<CraftOrder>>,
+    mut commands: Commands,
+) {
+    for &CancelCraftOrder(order_entity) in events.read() {
+        if order_query.contains(order_entity) {
+            commands.entity(order_entity).despawn();
+        }
+    }
+}
+
+/// Hands out crafters to open orders, oldest order first, and releases crafters whose order has closed.
+///
+/// Each order gets at most one crafter per remaining craft.
+/// Crafters already running the order's recipe are preferred;
+/// after that, idle crafters of a type that can craft the recipe are switched over to it.
+fn assign_craft_orders(
+    mut order_query: Query<(Entity, &mut CraftOrder)>,
+    mut crafter_query: Query<
+        (
+            Entity,
+            &Id<Structure>,
+            &mut ActiveRecipe,
+            &mut CraftingState,
+            &mut InputInventory,
+            &mut OutputInventory,
+            Option<&CraftOrderAssignment>,
+        ),
+        (
+            Without<Ghost>,
+            Without<Preview>,
+            Without<MarkedForDemolition>,
+        ),
+    >,
+    recipe_graph: Res<RecipeGraph>,
+    recipe_manifest: Res<RecipeManifest>,
+    item_manifest: Res<ItemManifest>,
+    mut commands: Commands,
+) {
+    // The number of crafters working on each order
+    let mut assigned: HashMap<Entity, usize> = HashMap::default();
+    // Crafters assigned this tick are only marked once commands are applied
+    let mut newly_assigned: HashSet<Entity> = HashSet::default();
+
+    // Release crafters whose order is gone, or who have been switched to something else
+    for (crafter_entity, _, active_recipe, .., maybe_assignment) in crafter_query.iter() {
+        let Some(assignment) = maybe_assignment else {
+            continue;
+        };
+
+        let still_valid = order_query
+            .get(assignment.order)
+            .is_ok_and(|(_, order)| *active_recipe.recipe_id() == Some(order.recipe_id));
+
+        if still_valid {
+            *assigned.entry(assignment.order).or_default() += 1;
+        } else {
+            commands
+                .entity(crafter_entity)
+                .remove::<CraftOrderAssignment>();
+        }
+    }
+
+    let mut orders: Vec<(Entity, Mut<CraftOrder>)> = order_query.iter_mut().collect();
+    orders.sort_by_key(|(_, order)| order.number);
+
+    for (order_entity, mut order) in orders {
+        let recipe = recipe_manifest.get(order.recipe_id);
+        let wanted = order.crafts_remaining(recipe) as usize;
+        let mut current = assigned.get(&order_entity).copied().unwrap_or_default();
+
+        for (
+            crafter_entity,
+            structure_id,
+            mut active_recipe,
+            mut crafting_state,
+            mut input_inventory,
+            mut output_inventory,
+            maybe_assignment,
+        ) in crafter_query.iter_mut()
+        {
+            if current >= wanted {
+                break;
+            }
+
+            if maybe_assignment.is_some() || newly_assigned.contains(&crafter_entity) {
+                continue;
+            }
+
+            let running_recipe = *active_recipe.recipe_id() == Some(order.recipe_id);
+            let idle_and_capable = active_recipe.recipe_id().is_none()
+                && recipe_graph
+                    .crafters(order.recipe_id)
+                    .contains(structure_id);
+            if !running_recipe && !idle_and_capable {
+                continue;
+            }
+
+            if active_recipe.recipe_id().is_none() {
+                *active_recipe = ActiveRecipe::new(order.recipe_id);
+                *input_inventory = recipe.input_inventory(&item_manifest);
+                *output_inventory = recipe.output_inventory(&item_manifest);
+                *crafting_state = CraftingState::NeedsInput;
+            }
+
+            commands
+                .entity(crafter_entity)
+                .insert(CraftOrderAssignment {
+                    order: order_entity,
+                });
+            newly_assigned.insert(crafter_entity);
+            current += 1;
+        }
+
+        order.crafters = current;
+    }
+}
+
+/// How long a craft order's claim on its inputs lasts.
+///
+/// Orders renew their reservations every tick, so this only comes into play once an order is gone.
+const ORDER_RESERVATION_DURATION: Duration = Duration::from_secs(5);
+
+/// Reserves the inputs needed by each open order in the colony's output and storage inventories, oldest order first.
+///
+/// Inputs that are already in, or on their way to, the order's crafters count towards its reservation.
+/// Reservations are made afresh each tick, so that newer orders pick up whatever older ones no longer need.
+fn reserve_craft_order_inputs(
+    mut order_query: Query<(Entity, &mut CraftOrder)>,
+    crafter_query: Query<(Entity, &CraftOrderAssignment, &InputInventory)>,
+    job_query: Query<&HaulingJob>,
+    mut source_query: Query<
+        AnyOf<(&mut OutputInventory, &mut StorageInventory)>,
+        Without<Forbidden>,
+    >,
+    recipe_manifest: Res<RecipeManifest>,
+    time: Res<Time>,
+) {
+    let expires_at = time.elapsed() + ORDER_RESERVATION_DURATION;
+
+    let mut orders: Vec<(Entity, Mut<CraftOrder>)> = order_query.iter_mut().collect();
+    orders.sort_by_key(|(_, order)| order.number);
+
+    let mut crafter_orders: HashMap<Entity, Entity> = HashMap::default();
+    let mut delivered: HashMap<(Entity, Id<Item>), u32> = HashMap::default();
+    for (crafter_entity, assignment, input_inventory) in crafter_query.iter() {
+        crafter_orders.insert(crafter_entity, assignment.order);
+        for slot in input_inventory.inventory().iter() {
+            *delivered
+                .entry((assignment.order, slot.item_id()))
+                .or_default() += slot.count();
+        }
+    }
+
+    for job in job_query.iter() {
+        if let Some(&order_entity) = crafter_orders.get(&job.destination) {
+            *delivered.entry((order_entity, job.item_id)).or_default() += 1;
+        }
+    }
+
+    let order_entities: HashSet<Entity> = orders.iter().map(|(entity, _)| *entity).collect();
+    let mut inventories: Vec<Mut<Inventory>> = source_query
+        .iter_mut()
+        .filter_map(
+            |(maybe_output, maybe_storage)| match (maybe_output, maybe_storage) {
+                (Some(output), _) => Some(output.map_unchanged(|output| &mut output.inventory)),
+                (None, Some(storage)) => {
+                    Some(storage.map_unchanged(|storage| &mut storage.inventory))
+                }
+                (None, None) => None,
+            },
+        )
+        .collect();
+
+    // Start from scratch, releasing the claims made last tick
+    for inventory in inventories.iter_mut() {
+        if inventory
+            .reservations()
+            .any(|reservation| order_entities.contains(&reservation.holder))
+        {
+            for &order_entity in &order_entities {
+                inventory.release_all(order_entity);
+            }
+        }
+    }
+
+    let mut available: HashMap<Id<Item>, u32> = HashMap::default();
+    for (order_entity, mut order) in orders {
+        let mut on_hand = Vec::new();
+        let mut needed = Vec::new();
+        for item_count in order.inputs_needed(recipe_manifest.get(order.recipe_id)) {
+            let delivered_count = delivered
+                .get(&(order_entity, item_count.item_id))
+                .copied()
+                .unwrap_or_default()
+                .min(item_count.count);
+
+            on_hand.push(ItemCount::new(item_count.item_id, delivered_count));
+            needed.push(ItemCount::new(
+                item_count.item_id,
+                item_count.count - delivered_count,
+            ));
+        }
+
+        for item_count in &needed {
+            available.entry(item_count.item_id).or_insert_with(|| {
+                inventories
+                    .iter()
+                    .map(|inventory| inventory.unreserved_item_count(item_count.item_id))
+                    .sum()
+            });
+        }
+
+        let share = reserve(&needed, &mut available);
+        for item_count in &share {
+            let mut remaining = item_count.count;
+            for inventory in inventories.iter_mut() {
+                if remaining == 0 {
+                    break;
+                }
+
+                let count = remaining.min(inventory.unreserved_item_count(item_count.item_id));
+                if count > 0 {
+                    let partial = ItemCount::new(item_count.item_id, count);
+                    // Cannot fail: we just checked that enough items are unreserved
+                    inventory
+                        .reserve(order_entity, &partial, expires_at)
+                        .unwrap();
+                    remaining -= count;
+                }
+            }
+        }
+
+        let reserved: Vec<ItemCount> = share
+            .into_iter()
+            .zip(on_hand)
+            .map(|(share, on_hand)| ItemCount::new(share.item_id, share.count + on_hand.count))
+            .collect();
+
+        if order.reserved != reserved {
+            order.reserved = reserved;
+        }
+    }
+}
+
+/// Takes as much of `needed` as possible out of `available`, returning what was taken.
+fn reserve(needed: &[ItemCount], available: &mut HashMap<Id<Item>, u32>) -> Vec<ItemCount> {
+    needed
+        .iter()
+        .map(|item_count| {
+            let available = available.entry(item_count.item_id).or_default();
+            let taken = item_count.count.min(*available);
+            *available -= taken;
+            ItemCount::new(item_count.item_id, taken)
+        })
+        .collect()
+}
+
+/// Counts the crafts finished by assigned crafters, and closes orders once they are complete.
+fn track_craft_order_progress(
+    crafter_query: Query<(&CraftOrderAssignment, &CraftingState, &ActiveRecipe)>,
+    mut order_query: Query<&mut CraftOrder>,
+    recipe_manifest: Res<RecipeManifest>,
+    mut commands: Commands,
+) {
+    for (assignment, crafting_state, active_recipe) in crafter_query.iter() {
+        if !matches!(crafting_state, CraftingState::RecipeComplete) {
+            continue;
+        }
+
+        let Ok(mut order) = order_query.get_mut(assignment.order) else {
+            continue;
+        };
+
+        if *active_recipe.recipe_id() != Some(order.recipe_id) || order.is_complete() {
+            continue;
+        }
+
+        order.record_craft(recipe_manifest.get(order.recipe_id));
+        if order.is_complete() {
+            commands.entity(assignment.order).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crafting::recipe::{RecipeConditions, RecipeOutput};
+
+    fn leaf() -> Id<Item> {
+        Id::from_name("leaf".to_string())
+    }
+
+    fn mushroom() -> Id<Item> {
+        Id::from_name("mushroom".to_string())
+    }
+
+    /// A recipe that turns 2 leaves into 3 mushrooms.
+    fn recipe() -> RecipeData {
+        RecipeData {
+            inputs: RecipeInput::Exact(vec![ItemCount::new(leaf(), 2)]),
+            outputs: RecipeOutput::Deterministic(vec![ItemCount::new(mushroom(), 3)]),
+            bulk_inputs: Vec::new(),
+            bulk_outputs: Vec::new(),
+            hatches: None,
+            craft_time: Duration::from_secs(1),
+            conditions: RecipeConditions::NONE,
+            energy: None,
+        }
+    }
+
+    fn order(requested: u32) -> CraftOrder {
+        CraftOrder::new(
+            1,
+            mushroom(),
+            Id::from_name("mushroom_farming".to_string()),
+            requested,
+        )
+    }
+
+    #[test]
+    fn crafts_are_rounded_up() {
+        let recipe = recipe();
+        let order = order(10);
+
+        assert_eq!(order.crafts_remaining(&recipe), 4);
+        assert_eq!(
+            order.inputs_needed(&recipe),
+            vec![ItemCount::new(leaf(), 8)]
+        );
+    }
+
+    #[test]
+    fn orders_complete_once_enough_is_made() {
+        let recipe = recipe();
+        let mut order = order(5);
+
+        order.record_craft(&recipe);
+        assert!(!order.is_complete());
+        assert_eq!(order.produced(), 3);
+        assert_eq!(order.crafts_remaining(&recipe), 1);
+
+        order.record_craft(&recipe);
+        assert!(order.is_complete());
+        assert_eq!(order.progress(), 1.);
+        assert_eq!(order.crafts_remaining(&recipe), 0);
+    }
+
+    #[test]
+    fn earlier_orders_reserve_first() {
+        let mut available = HashMap::from_iter([(leaf(), 10)]);
+        let first = reserve(&[ItemCount::new(leaf(), 8)], &mut available);
+        let second = reserve(&[ItemCount::new(leaf(), 8)], &mut available);
+
+        assert_eq!(first, vec![ItemCount::new(leaf(), 8)]);
+        assert_eq!(second, vec![ItemCount::new(leaf(), 2)]);
+    }
+}
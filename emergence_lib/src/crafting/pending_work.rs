@@ -0,0 +1,100 @@
+//! Structures that need labor advertise it with a [`SignalType::WorkNeeded`] signal.
+//!
+//! Unlike the specific signals for each item or workplace, this signal is shared by every kind of work.
+//! Idle units drift up its gradient while wandering, so they end up near work before they know what it is,
+//! and then pick up the specific signals of the structure that needs them.
+//! Like any other signal, it is amplified for [`Prioritized`](crate::player_interaction::bulk_commands::Prioritized) structures.
+
+use crate::signals::{SignalStrength, SignalType};
+
+use super::status::CraftingStatus;
+
+/// A reason that a structure needs units to come and help.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PendingWork {
+    /// A ghost needs its materials delivered, or needs to be built.
+    Construction,
+    /// A recipe needs more workers to make progress.
+    Labor,
+    /// A recipe cannot start until items are delivered.
+    MissingInputs,
+    /// A recipe is blocked until its products are carried away.
+    FullOutput,
+}
+
+impl PendingWork {
+    /// Determines the work that a crafting structure needs, if any.
+    ///
+    /// Missing bulk inputs are ignored, as they cannot be delivered by units.
+    pub(crate) fn for_crafting(
+        crafting_status: &CraftingStatus,
+        needs_workers: bool,
+    ) -> Option<Self> {
+        if needs_workers {
+            return Some(PendingWork::Labor);
+        }
+
+        match crafting_status {
+            CraftingStatus::MissingInputs { items } if !items.is_empty() => {
+                Some(PendingWork::MissingInputs)
+            }
+            CraftingStatus::OutputFull => Some(PendingWork::FullOutput),
+            _ => None,
+        }
+    }
+
+    /// The strength of the signal advertising this work, before it is amplified by priority.
+    const fn strength(&self) -> f32 {
+        match self {
+            PendingWork::Construction => 20.,
+            PendingWork::Labor => 20.,
+            PendingWork::MissingInputs => 10.,
+            PendingWork::FullOutput => 10.,
+        }
+    }
+
+    /// The signal that advertises this work.
+    pub(crate) fn signal(&self) -> (SignalType, SignalStrength) {
+        (SignalType::WorkNeeded, SignalStrength::new(self.strength()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{asset_management::manifest::Id, items::bulk::BulkQuantity};
+
+    use super::*;
+
+    #[test]
+    fn only_structures_that_units_can_help_need_work() {
+        assert_eq!(
+            PendingWork::for_crafting(&CraftingStatus::Working, true),
+            Some(PendingWork::Labor)
+        );
+        assert_eq!(
+            PendingWork::for_crafting(&CraftingStatus::OutputFull, false),
+            Some(PendingWork::FullOutput)
+        );
+        assert_eq!(
+            PendingWork::for_crafting(&CraftingStatus::MissingInputs { items: Vec::new() }, false),
+            None
+        );
+        assert_eq!(
+            PendingWork::for_crafting(
+                &CraftingStatus::MissingBulkInputs {
+                    missing: BulkQuantity::new(Id::from_name("water".to_string()), 1.)
+                },
+                false
+            ),
+            None
+        );
+        assert_eq!(
+            PendingWork::for_crafting(&CraftingStatus::Working, false),
+            None
+        );
+        assert_eq!(
+            PendingWork::for_crafting(&CraftingStatus::Disabled, false),
+            None
+        );
+    }
+}
@@ -0,0 +1,489 @@
+//! Short queues of recipes that a single crafter works through in order.
+//!
+//! A [`RecipeQueue`] lets a structure cycle between recipes without the player stepping in,
+//! such as laying eggs and then hatching them.
+//! Each time a craft finishes, the crafter switches to the next recipe in its queue, wrapping around at the end.
+//!
+//! Items left over from the previous recipe are moved into the inventories of the next recipe where they fit,
+//! so the products of one step can feed the next.
+//! Anything else is kept in extra output slots until it has been collected.
+
+use bevy::{ecs::query::WorldQuery, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    items::{
+        errors::AddOneItemError,
+        item_manifest::{Item, ItemManifest},
+        ItemCount,
+    },
+    simulation::SimulationSet,
+    structures::structure_manifest::Structure,
+};
+
+use super::{
+    inventories::{CraftingState, InputInventory, OutputInventory},
+    progress_crafting,
+    recipe::{ActiveRecipe, Recipe, RecipeData, RecipeManifest},
+    recipe_graph::RecipeGraph,
+};
+
+/// Edits and advances each crafter's [`RecipeQueue`].
+pub(super) struct RecipeQueuePlugin;
+
+impl Plugin for RecipeQueuePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EditRecipeQueue>().add_systems(
+            FixedUpdate,
+            (
+                edit_recipe_queues.before(progress_crafting),
+                advance_recipe_queues.after(progress_crafting),
+            )
+                .in_set(SimulationSet),
+        );
+    }
+}
+
+/// The recipes that a crafter cycles through, in order.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecipeQueue {
+    /// The queued recipes.
+    recipes: Vec<Id<Recipe>>,
+    /// The index of the recipe that is being crafted, or will be crafted once the current craft finishes.
+    current: usize,
+    /// Has the current craft finished, with its products yet to be stored?
+    finishing: bool,
+}
+
+impl RecipeQueue {
+    /// The maximum number of recipes that can be queued at once.
+    pub const MAX_LENGTH: usize = 4;
+
+    /// The queued recipes, in order.
+    pub fn recipes(&self) -> &[Id<Recipe>] {
+        &self.recipes
+    }
+
+    /// The recipe that is being crafted, or will be crafted once the current craft finishes.
+    pub fn current(&self) -> Option<Id<Recipe>> {
+        self.recipes.get(self.current).copied()
+    }
+
+    /// Are there no recipes in the queue?
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    /// Adds `recipe_id` to the end of the queue.
+    ///
+    /// Returns `false` if the queue was already full.
+    fn push(&mut self, recipe_id: Id<Recipe>) -> bool {
+        if self.recipes.len() >= Self::MAX_LENGTH {
+            return false;
+        }
+
+        self.recipes.push(recipe_id);
+        true
+    }
+
+    /// Removes the recipe at `index` from the queue, if there is one.
+    fn remove(&mut self, index: usize) {
+        if index >= self.recipes.len() {
+            return;
+        }
+
+        self.recipes.remove(index);
+        if index < self.current {
+            self.current -= 1;
+        }
+        if self.current >= self.recipes.len() {
+            self.current = 0;
+        }
+    }
+
+    /// Picks the recipe to craft once the `finished` recipe is complete.
+    ///
+    /// If the crafter was working on the current recipe, the queue moves on to the next one.
+    /// Otherwise, the crafter was interrupted, and resumes the queue where it left off.
+    fn advance(&mut self, finished: Option<Id<Recipe>>) -> Option<Id<Recipe>> {
+        if self.recipes.is_empty() {
+            return None;
+        }
+
+        if self.current() == finished {
+            self.current = (self.current + 1) % self.recipes.len();
+        }
+
+        self.current()
+    }
+
+    /// The pretty formatting for this type.
+    pub(crate) fn display(&self, recipe_manifest: &RecipeManifest) -> String {
+        if self.recipes.is_empty() {
+            return "Empty".to_string();
+        }
+
+        self.recipes
+            .iter()
+            .enumerate()
+            .map(|(index, &recipe_id)| {
+                let name = recipe_manifest.name(recipe_id);
+                match index == self.current {
+                    true => format!("[{name}]"),
+                    false => name.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+/// A change to the [`RecipeQueue`] of a single crafter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecipeQueueEdit {
+    /// Adds a recipe to the end of the queue.
+    Push(Id<Recipe>),
+    /// Removes the recipe at this index from the queue.
+    Remove(usize),
+    /// Removes every recipe from the queue.
+    Clear,
+}
+
+/// Changes the [`RecipeQueue`] of the `structure` entity.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditRecipeQueue {
+    /// The crafter whose queue should change.
+    pub structure: Entity,
+    /// The change to make.
+    pub edit: RecipeQueueEdit,
+}
+
+/// The components of a crafter that change when it switches to a new recipe.
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+struct RecipeSwitchQuery {
+    /// The recipe being crafted.
+    active_recipe: &'static mut ActiveRecipe,
+    /// The progress of the current craft.
+    crafting_state: &'static mut CraftingState,
+    /// The inputs for the current recipe.
+    input_inventory: &'static mut InputInventory,
+    /// The outputs of the current recipe, along with any leftovers from earlier recipes.
+    output_inventory: &'static mut OutputInventory,
+}
+
+impl RecipeSwitchQueryItem<'_> {
+    /// Switches this crafter over to `recipe_id`, carrying over any items that are still useful.
+    ///
+    /// Leftovers that the new recipe cannot use are kept in extra output slots, so that they can be collected.
+    fn switch_to(
+        &mut self,
+        recipe_id: Id<Recipe>,
+        recipe: &RecipeData,
+        item_manifest: &ItemManifest,
+    ) {
+        let leftovers: Vec<ItemCount> = self
+            .input_inventory
+            .iter()
+            .chain(self.output_inventory.iter())
+            .filter(|item_slot| !item_slot.is_empty())
+            .map(|item_slot| item_slot.item_count())
+            .collect();
+
+        *self.active_recipe = ActiveRecipe::new(recipe_id);
+        *self.input_inventory = recipe.input_inventory(item_manifest);
+        *self.output_inventory = recipe.output_inventory(item_manifest);
+        *self.crafting_state = CraftingState::NeedsInput;
+
+        for mut item_count in leftovers {
+            if self
+                .input_inventory
+                .currently_accepts(item_count.item_id, item_manifest)
+            {
+                match self
+                    .input_inventory
+                    .inventory_mut()
+                    .try_add_item(&item_count, item_manifest)
+                {
+                    Ok(()) => continue,
+                    Err(AddOneItemError { excess_count }) => item_count = excess_count,
+                }
+            }
+
+            // Each new slot holds a full stack, so this always finishes
+            while let Err(AddOneItemError { excess_count }) = self
+                .output_inventory
+                .try_add_item(&item_count, item_manifest)
+            {
+                self.output_inventory
+                    .add_empty_slot(excess_count.item_id, item_manifest);
+                item_count = excess_count;
+            }
+        }
+    }
+
+    /// Removes the extra output slots that held leftovers from an earlier recipe, once they have been emptied.
+    fn clear_collected_leftovers(&mut self, recipe: &RecipeData, item_manifest: &ItemManifest) {
+        let outputs: Vec<Id<Item>> = recipe.outputs.item_ids();
+        let has_empty_leftovers = self
+            .output_inventory
+            .iter()
+            .any(|item_slot| item_slot.is_empty() && !outputs.contains(&item_slot.item_id()));
+        if !has_empty_leftovers {
+            return;
+        }
+
+        // Crafting relies on the slots for its own outputs, so put those back
+        self.output_inventory.clear_empty_slots();
+        for item_id in outputs {
+            if !self.output_inventory.contains(item_id) {
+                self.output_inventory.add_empty_slot(item_id, item_manifest);
+            }
+        }
+    }
+}
+
+/// Applies each [`EditRecipeQueue`] event.
+///
+/// Recipes that the crafter cannot make are ignored.
+/// Idle crafters start on their queue straight away; busy crafters finish their current craft first.
+fn edit_recipe_queues(
+    mut events: EventReader<EditRecipeQueue>,
+    mut crafter_query: Query<
+        (&Id<Structure>, Option<&mut RecipeQueue>, RecipeSwitchQuery),
+        (Without<Ghost>, Without<Preview>),
+    >,
+    recipe_graph: Res<RecipeGraph>,
+    recipe_manifest: Res<RecipeManifest>,
+    item_manifest: Res<ItemManifest>,
+    mut commands: Commands,
+) {
+    for &EditRecipeQueue { structure, edit } in events.read() {
+        let Ok((structure_id, maybe_queue, mut crafter)) = crafter_query.get_mut(structure) else {
+            continue;
+        };
+
+        let had_queue = maybe_queue.is_some();
+        let mut new_queue = RecipeQueue::default();
+        let queue = match maybe_queue {
+            Some(queue) => queue.into_inner(),
+            None => &mut new_queue,
+        };
+
+        match edit {
+            RecipeQueueEdit::Push(recipe_id) => {
+                if !recipe_graph.crafters(recipe_id).contains(structure_id) {
+                    continue;
+                }
+
+                queue.push(recipe_id);
+            }
+            RecipeQueueEdit::Remove(index) => queue.remove(index),
+            RecipeQueueEdit::Clear => *queue = RecipeQueue::default(),
+        }
+
+        if let (None, Some(recipe_id)) = (*crafter.active_recipe.recipe_id(), queue.current()) {
+            crafter.switch_to(recipe_id, recipe_manifest.get(recipe_id), &item_manifest);
+        }
+
+        if queue.is_empty() {
+            commands.entity(structure).remove::<RecipeQueue>();
+        } else if !had_queue {
+            commands.entity(structure).insert(new_queue);
+        }
+    }
+}
+
+/// Moves crafters on to the next recipe in their queue once their current craft is complete.
+///
+/// Crafters only switch once the products of the craft have been stored,
+/// so that they can be carried over to the next recipe.
+fn advance_recipe_queues(
+    mut crafter_query: Query<(&mut RecipeQueue, RecipeSwitchQuery)>,
+    recipe_manifest: Res<RecipeManifest>,
+    item_manifest: Res<ItemManifest>,
+) {
+    for (mut queue, mut crafter) in crafter_query.iter_mut() {
+        if let Some(recipe_id) = *crafter.active_recipe.recipe_id() {
+            crafter.clear_collected_leftovers(recipe_manifest.get(recipe_id), &item_manifest);
+        }
+
+        match *crafter.crafting_state {
+            CraftingState::RecipeComplete => {
+                if !queue.finishing {
+                    queue.finishing = true;
+                }
+            }
+            CraftingState::NeedsInput
+            | CraftingState::Overproduction
+            | CraftingState::FullAndBlocked
+                if queue.finishing =>
+            {
+                queue.finishing = false;
+
+                let finished = *crafter.active_recipe.recipe_id();
+                if let Some(next) = queue.advance(finished) {
+                    if Some(next) != finished {
+                        crafter.switch_to(next, recipe_manifest.get(next), &item_manifest);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crafting::recipe::{RecipeConditions, RecipeInput, RecipeOutput},
+        testing::item_manifest,
+    };
+    use bevy::utils::Duration;
+
+    fn recipe(name: &str) -> Id<Recipe> {
+        Id::from_name(name.to_string())
+    }
+
+    fn item(name: &str) -> Id<Item> {
+        Id::from_name(name.to_string())
+    }
+
+    fn recipe_data(input: &str, output: &str) -> RecipeData {
+        RecipeData {
+            inputs: RecipeInput::Exact(vec![ItemCount::new(item(input), 1)]),
+            outputs: RecipeOutput::Deterministic(vec![ItemCount::new(item(output), 1)]),
+            bulk_inputs: Vec::new(),
+            bulk_outputs: Vec::new(),
+            craft_time: Duration::from_secs(1),
+            conditions: RecipeConditions::default(),
+            energy: None,
+            hatches: None,
+        }
+    }
+
+    #[test]
+    fn queues_cycle_through_their_recipes() {
+        let mut queue = RecipeQueue::default();
+        assert_eq!(queue.advance(None), None);
+
+        queue.push(recipe("lay_eggs"));
+        queue.push(recipe("hatch_eggs"));
+
+        // An interrupted crafter starts from the current recipe
+        assert_eq!(
+            queue.advance(Some(recipe("other"))),
+            Some(recipe("lay_eggs"))
+        );
+        assert_eq!(
+            queue.advance(Some(recipe("lay_eggs"))),
+            Some(recipe("hatch_eggs"))
+        );
+        assert_eq!(
+            queue.advance(Some(recipe("hatch_eggs"))),
+            Some(recipe("lay_eggs"))
+        );
+    }
+
+    #[test]
+    fn queues_have_a_maximum_length() {
+        let mut queue = RecipeQueue::default();
+        for i in 0..RecipeQueue::MAX_LENGTH {
+            assert!(queue.push(recipe(&format!("recipe_{i}"))));
+        }
+
+        assert!(!queue.push(recipe("one_too_many")));
+        assert_eq!(queue.recipes().len(), RecipeQueue::MAX_LENGTH);
+    }
+
+    #[test]
+    fn removing_recipes_keeps_the_current_recipe() {
+        let mut queue = RecipeQueue::default();
+        queue.push(recipe("a"));
+        queue.push(recipe("b"));
+        queue.push(recipe("c"));
+        queue.advance(Some(recipe("a")));
+        queue.advance(Some(recipe("b")));
+        assert_eq!(queue.current(), Some(recipe("c")));
+
+        queue.remove(0);
+        assert_eq!(queue.current(), Some(recipe("c")));
+
+        // Removing the current recipe moves on to the next one
+        queue.remove(1);
+        assert_eq!(queue.current(), Some(recipe("b")));
+
+        queue.remove(0);
+        assert!(queue.is_empty());
+        assert_eq!(queue.current(), None);
+    }
+
+    #[test]
+    fn products_of_unrelated_recipes_are_kept_until_collected() {
+        let item_manifest = item_manifest();
+
+        let mut recipe_manifest = RecipeManifest::new();
+        recipe_manifest.insert("grow_leaves".to_string(), recipe_data("water", "leaf"));
+        recipe_manifest.insert(
+            "grow_mushrooms".to_string(),
+            recipe_data("soil", "mushroom"),
+        );
+        let grow_leaves = recipe("grow_leaves");
+        let grow_mushrooms = recipe("grow_mushrooms");
+
+        let leaf_recipe = recipe_manifest.get(grow_leaves);
+        let mut output_inventory = leaf_recipe.output_inventory(&item_manifest);
+        output_inventory
+            .try_add_item(&ItemCount::new(item("leaf"), 1), &item_manifest)
+            .unwrap();
+        let input_inventory = leaf_recipe.input_inventory(&item_manifest);
+
+        let mut app = App::new();
+        app.insert_resource(item_manifest)
+            .insert_resource(recipe_manifest)
+            .add_systems(Update, advance_recipe_queues);
+
+        // The leaves have just been crafted
+        let crafter = app
+            .world
+            .spawn((
+                RecipeQueue {
+                    recipes: vec![grow_leaves, grow_mushrooms],
+                    current: 0,
+                    finishing: true,
+                },
+                ActiveRecipe::new(grow_leaves),
+                CraftingState::NeedsInput,
+                input_inventory,
+                output_inventory,
+            ))
+            .id();
+
+        app.update();
+        let output = app.world.get::<OutputInventory>(crafter).unwrap();
+        assert_eq!(
+            app.world.get::<ActiveRecipe>(crafter).unwrap(),
+            &ActiveRecipe::new(grow_mushrooms)
+        );
+        assert_eq!(output.item_count(item("leaf")), 1);
+        assert!(output
+            .iter()
+            .any(|item_slot| item_slot.item_id() == item("mushroom")));
+
+        // Once a hauler collects the leaf, its slot is no longer needed
+        app.world
+            .get_mut::<OutputInventory>(crafter)
+            .unwrap()
+            .try_remove_item(&ItemCount::new(item("leaf"), 1))
+            .unwrap();
+        app.update();
+        let output = app.world.get::<OutputInventory>(crafter).unwrap();
+        assert!(!output.contains(item("leaf")));
+        assert!(output
+            .iter()
+            .any(|item_slot| item_slot.item_id() == item("mushroom")));
+    }
+}
@@ -3,9 +3,15 @@
 use crate::asset_management::manifest::loader::IsRawManifest;
 use crate::asset_management::manifest::{Id, Manifest};
 use crate::items::item_manifest::{Item, ItemManifest};
-use crate::items::{inventory::Inventory, ItemCount};
+use crate::items::{
+    bulk::{BulkQuantity, Tank, Tanks},
+    inventory::Inventory,
+    ItemCount,
+};
 use crate::light::shade::ReceivedLight;
 use crate::light::Illuminance;
+use crate::temperature::Temperature;
+use crate::units::unit_manifest::{Unit, UnitManifest};
 use crate::{
     crafting::inventories::{InputInventory, OutputInventory},
     organisms::energy::Energy,
@@ -35,6 +41,12 @@ pub struct RecipeData {
     /// The outputs generated by crafting.
     pub outputs: RecipeOutput,
 
+    /// The bulk resources, such as water, drained from the crafter's [`Tanks`] when crafting begins.
+    pub bulk_inputs: Vec<BulkQuantity>,
+
+    /// The bulk resources added to the crafter's [`Tanks`] when crafting completes.
+    pub bulk_outputs: Vec<BulkQuantity>,
+
     /// The time needed to craft the recipe.
     pub craft_time: Duration,
 
@@ -43,8 +55,12 @@ pub struct RecipeData {
 
     /// The amount of [`Energy`] produced by making this recipe, if any.
     ///
+    /// Negative values represent an energy cost, which must be paid before crafting can begin.
     /// This is only relevant to living structures.
     pub energy: Option<Energy>,
+
+    /// The unit that hatches next to the crafter when the recipe completes, if any.
+    pub hatches: Option<Id<Unit>>,
 }
 
 /// The items needed to craft a recipe.
@@ -186,6 +202,22 @@ impl RecipeOutput {
             Self::Stochastic(outputs) => outputs.iter().map(|(item_id, _)| *item_id).collect(),
         }
     }
+
+    /// The average number of `item_id` produced each time this recipe is crafted.
+    pub fn expected_count(&self, item_id: Id<Item>) -> f32 {
+        match self {
+            Self::Deterministic(outputs) => outputs
+                .iter()
+                .filter(|output| output.item_id == item_id)
+                .map(|output| output.count as f32)
+                .sum(),
+            Self::Stochastic(outputs) => outputs
+                .iter()
+                .filter(|(output_id, _)| *output_id == item_id)
+                .map(|(_, count)| count)
+                .sum(),
+        }
+    }
 }
 
 /// The unprocessed equivalent of [`RecipeData`].
@@ -197,6 +229,14 @@ pub struct RawRecipeData {
     /// The outputs generated by crafting.
     pub outputs: HashMap<String, f32>,
 
+    /// The bulk resources consumed by crafting, by name.
+    #[serde(default)]
+    pub bulk_inputs: HashMap<String, f32>,
+
+    /// The bulk resources produced by crafting, by name.
+    #[serde(default)]
+    pub bulk_outputs: HashMap<String, f32>,
+
     /// The time needed to craft the recipe.
     pub craft_time: f32,
 
@@ -205,8 +245,25 @@ pub struct RawRecipeData {
 
     /// The amount of [`Energy`] produced by making this recipe, if any.
     ///
+    /// Negative values represent an energy cost, which must be paid before crafting can begin.
     /// This is only relevant to living structures.
     pub energy: Option<Energy>,
+
+    /// The name of the unit that hatches next to the crafter when the recipe completes, if any.
+    pub hatches: Option<String>,
+
+    /// Descriptive text shown in the codex, if any.
+    #[serde(default)]
+    pub flavor_text: Option<String>,
+}
+
+/// Converts the raw bulk quantities of a recipe, sorted by name so that the order is stable.
+fn bulk_from_raw(raw_data: HashMap<String, f32>) -> Vec<BulkQuantity> {
+    raw_data
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(item_name, amount)| BulkQuantity::new(Id::from_name(item_name), amount))
+        .collect()
 }
 
 impl From<RawRecipeData> for RecipeData {
@@ -214,17 +271,29 @@ impl From<RawRecipeData> for RecipeData {
         Self {
             inputs: raw.inputs.into(),
             outputs: RecipeOutput::from_raw(raw.outputs),
+            bulk_inputs: bulk_from_raw(raw.bulk_inputs),
+            bulk_outputs: bulk_from_raw(raw.bulk_outputs),
             craft_time: Duration::from_secs_f32(raw.craft_time),
             conditions: raw.conditions.unwrap_or_default(),
             energy: raw.energy,
+            hatches: raw.hatches.map(Id::from_name),
         }
     }
 }
 
 impl RecipeData {
+    /// The number of crafts' worth of each bulk resource that a crafter's tanks can hold.
+    pub(crate) const TANK_CAPACITY_IN_CRAFTS: f32 = 3.;
+
     /// Are the conditions to craft this recipe met?
-    pub(crate) fn satisfied(&self, workers: u8, received_light: &ReceivedLight) -> bool {
-        self.conditions.satisfied(workers, received_light)
+    pub(crate) fn satisfied(
+        &self,
+        workers: u8,
+        received_light: &ReceivedLight,
+        temperature: Temperature,
+    ) -> bool {
+        self.conditions
+            .satisfied(workers, received_light, temperature)
     }
 
     /// An inventory with empty slots for all of the inputs of this recipe.
@@ -255,6 +324,24 @@ impl RecipeData {
         OutputInventory { inventory }
     }
 
+    /// Tanks for all of the bulk inputs and outputs of this recipe.
+    ///
+    /// Each tank holds enough for [`Self::TANK_CAPACITY_IN_CRAFTS`] crafts,
+    /// so that crafting can continue while the tanks are topped up or emptied.
+    pub(crate) fn tanks(&self) -> Tanks {
+        Tanks::new(
+            self.bulk_inputs
+                .iter()
+                .chain(self.bulk_outputs.iter())
+                .map(|quantity| {
+                    Tank::new(
+                        quantity.item_id,
+                        quantity.amount * Self::TANK_CAPACITY_IN_CRAFTS,
+                    )
+                }),
+        )
+    }
+
     /// The number of workers this recipe needs to be crafted at all.
     pub(crate) fn workers_required(&self) -> u8 {
         self.conditions.workers_required
@@ -265,8 +352,24 @@ impl RecipeData {
         self.conditions.workers_required > 0
     }
 
+    /// The amount of [`Energy`] credited to the crafter when this recipe completes, if any.
+    pub(crate) fn energy_produced(&self) -> Option<Energy> {
+        self.energy.filter(|energy| *energy > Energy(0.))
+    }
+
+    /// The amount of [`Energy`] debited from the crafter when this recipe starts, if any.
+    pub(crate) fn energy_cost(&self) -> Option<Energy> {
+        self.energy
+            .filter(|energy| *energy < Energy(0.))
+            .map(|energy| Energy(-energy.0))
+    }
+
     /// The pretty formatting of this type
-    pub(crate) fn display(&self, item_manifest: &ItemManifest) -> String {
+    pub(crate) fn display(
+        &self,
+        item_manifest: &ItemManifest,
+        unit_manifest: &UnitManifest,
+    ) -> String {
         let input_str: String = match self.inputs {
             RecipeInput::Exact(ref inputs) => inputs
                 .iter()
@@ -274,13 +377,29 @@ impl RecipeData {
                 .join(", "),
             RecipeInput::Flexible { tag, count } => format!("{count}x {tag}"),
         };
+        let input_str = self
+            .bulk_inputs
+            .iter()
+            .map(|quantity| quantity.display(item_manifest))
+            .fold(input_str, |acc, bulk| match acc.is_empty() {
+                true => bulk,
+                false => format!("{acc}, {bulk}"),
+            });
 
-        let output_strings: Vec<String> = self
+        let mut output_strings: Vec<String> = self
             .outputs
             .item_ids()
             .iter()
             .map(|output_id| item_manifest.name(*output_id).to_string())
             .collect();
+        output_strings.extend(
+            self.bulk_outputs
+                .iter()
+                .map(|quantity| quantity.display(item_manifest)),
+        );
+        if let Some(unit_id) = self.hatches {
+            output_strings.push(unit_manifest.name(unit_id).to_string());
+        }
         let output_str = output_strings.join(", ");
 
         let duration_str = format!("{:.2}", self.craft_time.as_secs_f32());
@@ -302,6 +421,8 @@ pub struct RecipeConditions {
     pub workers_required: u8,
     /// The range of light levels that are acceptable for this recipe.
     pub allowable_light_range: Option<Threshold<Illuminance>>,
+    /// The range of temperatures that are acceptable for this recipe.
+    pub allowable_temperature_range: Option<Threshold<Temperature>>,
 }
 
 impl Display for RecipeConditions {
@@ -312,6 +433,9 @@ impl Display for RecipeConditions {
         if let Some(range) = &self.allowable_light_range {
             write!(f, "Light: {}", *range)?;
         }
+        if let Some(range) = &self.allowable_temperature_range {
+            write!(f, "Temperature: {}", *range)?;
+        }
         Ok(())
     }
 }
@@ -321,6 +445,7 @@ impl RecipeConditions {
     pub const NONE: RecipeConditions = RecipeConditions {
         workers_required: 0,
         allowable_light_range: None,
+        allowable_temperature_range: None,
     };
 
     /// Creates a new [`RecipeConditions`].
@@ -328,18 +453,28 @@ impl RecipeConditions {
         Self {
             workers_required,
             allowable_light_range: Some(allowable_light_range),
+            allowable_temperature_range: None,
         }
     }
 
     /// Are the conditions to craft this recipe met?
-    fn satisfied(&self, workers: u8, received_light: &ReceivedLight) -> bool {
+    fn satisfied(
+        &self,
+        workers: u8,
+        received_light: &ReceivedLight,
+        temperature: Temperature,
+    ) -> bool {
         let work_satisfied = self.workers_required == 0 || workers >= self.workers_required;
         let light_satisfied = self
             .allowable_light_range
             .as_ref()
             .map_or(true, |range| range.contains(received_light.0));
+        let temperature_satisfied = self
+            .allowable_temperature_range
+            .as_ref()
+            .map_or(true, |range| range.contains(temperature));
 
-        work_satisfied && light_satisfied
+        work_satisfied && light_satisfied && temperature_satisfied
     }
 }
 
@@ -390,9 +525,10 @@ impl IsRawManifest for RawRecipeManifest {
         let mut manifest = Manifest::new();
 
         for (raw_id, raw_data) in self.recipes.clone() {
+            let flavor_text = raw_data.flavor_text.clone();
             let data = raw_data.into();
 
-            manifest.insert(raw_id, data)
+            manifest.insert_with_flavor_text(raw_id, data, flavor_text)
         }
 
         manifest
@@ -0,0 +1,462 @@
+//! The dependency graph between recipes, used to plan how many producers are needed to hit a target output rate.
+//!
+//! Each item (or hatched unit) is produced by zero or more recipes, and each recipe is crafted by zero or more structures.
+//! A [`ProductionPlan`] walks this graph backwards from a [`ProductionTarget`],
+//! working out how often each recipe must be crafted, and how many crafters that takes.
+//!
+//! Rates are measured per minute of in-game time.
+//! Recipes are assumed to run back-to-back, so the plan is a lower bound on what is needed in practice.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    asset_management::{manifest::Id, AssetState},
+    items::item_manifest::{Item, ItemManifest},
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+};
+
+use super::{
+    item_tags::ItemKind,
+    recipe::{Recipe, RecipeInput, RecipeManifest},
+};
+
+/// Builds the [`RecipeGraph`] once the manifests have loaded.
+pub(super) struct RecipeGraphPlugin;
+
+impl Plugin for RecipeGraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecipeGraph>()
+            .add_systems(OnEnter(AssetState::FullyLoaded), build_recipe_graph);
+    }
+}
+
+/// Something that recipes can produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProductionTarget {
+    /// An item, placed in the output inventory of the crafter.
+    Item(Id<Item>),
+    /// A unit, hatched next to the crafter.
+    Unit(Id<Unit>),
+}
+
+impl ProductionTarget {
+    /// The human-readable name of this target.
+    pub fn name<'a>(
+        &self,
+        item_manifest: &'a ItemManifest,
+        unit_manifest: &'a UnitManifest,
+    ) -> &'a str {
+        match self {
+            ProductionTarget::Item(item_id) => item_manifest.name(*item_id),
+            ProductionTarget::Unit(unit_id) => unit_manifest.name(*unit_id),
+        }
+    }
+}
+
+/// Which recipes produce each [`ProductionTarget`], and which structures craft each recipe.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct RecipeGraph {
+    /// The recipes that produce each target, sorted by name.
+    producers: HashMap<ProductionTarget, Vec<Id<Recipe>>>,
+    /// The structures that craft each recipe when built, sorted by name.
+    crafters: HashMap<Id<Recipe>, Vec<Id<Structure>>>,
+}
+
+impl RecipeGraph {
+    /// Builds the graph from the contents of the manifests.
+    pub fn new(recipe_manifest: &RecipeManifest, structure_manifest: &StructureManifest) -> Self {
+        let mut graph = RecipeGraph::default();
+
+        for (&recipe_id, recipe_data) in recipe_manifest.data_map() {
+            let targets = recipe_data
+                .outputs
+                .item_ids()
+                .into_iter()
+                .map(ProductionTarget::Item)
+                .chain(recipe_data.hatches.map(ProductionTarget::Unit));
+
+            for target in targets {
+                graph.producers.entry(target).or_default().push(recipe_id);
+            }
+        }
+
+        for (&structure_id, structure_data) in structure_manifest.data_map() {
+            if let Some(recipe_id) = structure_data.starting_recipe().recipe_id() {
+                graph
+                    .crafters
+                    .entry(*recipe_id)
+                    .or_default()
+                    .push(structure_id);
+            }
+        }
+
+        for recipes in graph.producers.values_mut() {
+            recipes.sort_by_key(|&recipe_id| recipe_manifest.name(recipe_id));
+            recipes.dedup();
+        }
+
+        for structures in graph.crafters.values_mut() {
+            structures.sort_by_key(|&structure_id| structure_manifest.name(structure_id));
+        }
+
+        graph
+    }
+
+    /// The recipes that produce `target`, sorted by name.
+    pub fn producers(&self, target: ProductionTarget) -> &[Id<Recipe>] {
+        self.producers.get(&target).map_or(&[], Vec::as_slice)
+    }
+
+    /// The structures that craft `recipe_id`, sorted by name.
+    pub fn crafters(&self, recipe_id: Id<Recipe>) -> &[Id<Structure>] {
+        self.crafters.get(&recipe_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Works out what is needed to produce `per_minute` of `target` each minute.
+    ///
+    /// When several recipes produce the same thing, recipes that some structure crafts are preferred,
+    /// and ties are broken by name.
+    /// Items that no recipe produces, inputs that accept any item with a tag,
+    /// and items that would need a recipe to (indirectly) consume its own output are treated as raw inputs.
+    pub fn plan(
+        &self,
+        target: ProductionTarget,
+        per_minute: f32,
+        recipe_manifest: &RecipeManifest,
+    ) -> ProductionPlan {
+        let mut plan = ProductionPlan::default();
+        let mut stack = Vec::new();
+        self.plan_recursive(target, per_minute, recipe_manifest, &mut plan, &mut stack);
+        plan
+    }
+
+    /// The recipe used to produce `target`, if any.
+    ///
+    /// Recipes that some structure crafts are preferred, and ties are broken by name.
+    pub fn preferred_producer(&self, target: ProductionTarget) -> Option<Id<Recipe>> {
+        let producers = self.producers(target);
+
+        producers
+            .iter()
+            .find(|&&recipe_id| !self.crafters(recipe_id).is_empty())
+            .or(producers.first())
+            .copied()
+    }
+
+    /// Adds the steps needed to produce `per_minute` of `target` to `plan`.
+    ///
+    /// `stack` holds the recipes currently being planned, and is used to detect cycles.
+    fn plan_recursive(
+        &self,
+        target: ProductionTarget,
+        per_minute: f32,
+        recipe_manifest: &RecipeManifest,
+        plan: &mut ProductionPlan,
+        stack: &mut Vec<Id<Recipe>>,
+    ) {
+        let recipe_id = match self.preferred_producer(target) {
+            Some(recipe_id) if !stack.contains(&recipe_id) => recipe_id,
+            _ => {
+                if let ProductionTarget::Item(item_id) = target {
+                    plan.add_raw_input(ItemKind::Single(item_id), per_minute);
+                }
+                return;
+            }
+        };
+
+        let recipe_data = recipe_manifest.get(recipe_id);
+        let output_per_craft = match target {
+            ProductionTarget::Item(item_id) => recipe_data.outputs.expected_count(item_id),
+            ProductionTarget::Unit(_) => 1.,
+        };
+        if output_per_craft <= 0. {
+            return;
+        }
+
+        let crafts_per_minute = per_minute / output_per_craft;
+        let crafters_needed = crafts_per_minute * recipe_data.craft_time.as_secs_f32() / 60.;
+        plan.add_step(ProductionStep {
+            recipe_id,
+            crafter: self.crafters(recipe_id).first().copied(),
+            crafts_per_minute,
+            crafters_needed,
+        });
+
+        stack.push(recipe_id);
+        match &recipe_data.inputs {
+            RecipeInput::Exact(inputs) => {
+                for input in inputs {
+                    self.plan_recursive(
+                        ProductionTarget::Item(input.item_id),
+                        crafts_per_minute * input.count as f32,
+                        recipe_manifest,
+                        plan,
+                        stack,
+                    );
+                }
+            }
+            RecipeInput::Flexible { tag, count } => {
+                plan.add_raw_input(ItemKind::Tag(*tag), crafts_per_minute * *count as f32);
+            }
+        }
+        // Bulk resources are gathered into tanks rather than crafted
+        for bulk_input in &recipe_data.bulk_inputs {
+            plan.add_raw_input(
+                ItemKind::Single(bulk_input.item_id),
+                crafts_per_minute * bulk_input.amount,
+            );
+        }
+        stack.pop();
+    }
+}
+
+/// A single recipe that must be crafted as part of a [`ProductionPlan`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductionStep {
+    /// The recipe to craft.
+    pub recipe_id: Id<Recipe>,
+    /// The structure that crafts this recipe, if any.
+    pub crafter: Option<Id<Structure>>,
+    /// How many times the recipe must be completed each minute.
+    pub crafts_per_minute: f32,
+    /// How many crafters must be working on this recipe full-time.
+    pub crafters_needed: f32,
+}
+
+/// Everything needed to produce a [`ProductionTarget`] at a fixed rate, computed by [`RecipeGraph::plan`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProductionPlan {
+    /// The recipes to craft, starting with the one that makes the target.
+    ///
+    /// Each recipe appears at most once, even if it feeds several other steps.
+    pub steps: Vec<ProductionStep>,
+    /// The items that must be gathered each minute, rather than crafted.
+    pub raw_inputs: Vec<(ItemKind, f32)>,
+}
+
+impl ProductionPlan {
+    /// Adds `step` to the plan, merging it with any existing step for the same recipe.
+    fn add_step(&mut self, step: ProductionStep) {
+        match self
+            .steps
+            .iter_mut()
+            .find(|existing| existing.recipe_id == step.recipe_id)
+        {
+            Some(existing) => {
+                existing.crafts_per_minute += step.crafts_per_minute;
+                existing.crafters_needed += step.crafters_needed;
+            }
+            None => self.steps.push(step),
+        }
+    }
+
+    /// Records that `per_minute` of `item_kind` must be gathered each minute.
+    fn add_raw_input(&mut self, item_kind: ItemKind, per_minute: f32) {
+        match self
+            .raw_inputs
+            .iter_mut()
+            .find(|(existing, _)| *existing == item_kind)
+        {
+            Some((_, existing_rate)) => *existing_rate += per_minute,
+            None => self.raw_inputs.push((item_kind, per_minute)),
+        }
+    }
+
+    /// The number of crafters needed for `recipe_id`, or 0 if it is not part of the plan.
+    pub fn crafters_needed(&self, recipe_id: Id<Recipe>) -> f32 {
+        self.steps
+            .iter()
+            .find(|step| step.recipe_id == recipe_id)
+            .map_or(0., |step| step.crafters_needed)
+    }
+
+    /// Pretty formatting for this type.
+    pub fn display(
+        &self,
+        item_manifest: &ItemManifest,
+        recipe_manifest: &RecipeManifest,
+        structure_manifest: &StructureManifest,
+    ) -> String {
+        let mut lines: Vec<String> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let recipe_name = recipe_manifest.name(step.recipe_id);
+                match step.crafter {
+                    Some(structure_id) => format!(
+                        "{:.1}x {} ({recipe_name})",
+                        step.crafters_needed,
+                        structure_manifest.name(structure_id)
+                    ),
+                    None => format!("{:.1}x {recipe_name}", step.crafters_needed),
+                }
+            })
+            .collect();
+
+        lines.extend(self.raw_inputs.iter().map(|(item_kind, per_minute)| {
+            format!(
+                "{per_minute:.1} {}/min gathered",
+                item_manifest.name_of_kind(*item_kind)
+            )
+        }));
+
+        lines.join("\n")
+    }
+}
+
+/// Builds the [`RecipeGraph`] from the loaded manifests.
+fn build_recipe_graph(
+    recipe_manifest: Res<RecipeManifest>,
+    structure_manifest: Res<StructureManifest>,
+    mut recipe_graph: ResMut<RecipeGraph>,
+) {
+    *recipe_graph = RecipeGraph::new(&recipe_manifest, &structure_manifest);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        crafting::{
+            item_tags::ItemTag,
+            recipe::{ActiveRecipe, RecipeConditions, RecipeData, RecipeOutput},
+        },
+        items::ItemCount,
+        structures::structure_manifest::{StructureData, StructureKind},
+    };
+
+    use super::*;
+
+    fn id<T>(name: &str) -> Id<T> {
+        Id::from_name(name.to_string())
+    }
+
+    fn recipe(inputs: RecipeInput, outputs: RecipeOutput, craft_time: u64) -> RecipeData {
+        RecipeData {
+            inputs,
+            outputs,
+            bulk_inputs: Vec::new(),
+            bulk_outputs: Vec::new(),
+            craft_time: Duration::from_secs(craft_time),
+            conditions: RecipeConditions::default(),
+            energy: None,
+            hatches: None,
+        }
+    }
+
+    fn crafter(recipe_name: &str) -> StructureData {
+        let mut structure_data = StructureData::passable();
+        structure_data.kind = StructureKind::Crafting {
+            starting_recipe: ActiveRecipe::new(id(recipe_name)),
+        };
+        structure_data
+    }
+
+    /// Leaves are grown from water, mushrooms are grown from leaves, and ants hatch from mushrooms.
+    fn manifests() -> (RecipeManifest, StructureManifest) {
+        let mut recipe_manifest = RecipeManifest::new();
+        recipe_manifest.insert(
+            "leaf_production".to_string(),
+            recipe(
+                RecipeInput::Exact(vec![ItemCount::new(id("water"), 1)]),
+                RecipeOutput::Deterministic(vec![ItemCount::new(id("leaf"), 1)]),
+                60,
+            ),
+        );
+        recipe_manifest.insert(
+            "mushroom_production".to_string(),
+            recipe(
+                RecipeInput::Exact(vec![ItemCount::new(id("leaf"), 2)]),
+                RecipeOutput::Deterministic(vec![ItemCount::new(id("mushroom"), 1)]),
+                30,
+            ),
+        );
+        let mut hatching = recipe(
+            RecipeInput::Exact(vec![ItemCount::new(id("mushroom"), 1)]),
+            RecipeOutput::EMPTY,
+            60,
+        );
+        hatching.hatches = Some(id("ant"));
+        recipe_manifest.insert("ant_hatching".to_string(), hatching);
+        recipe_manifest.insert(
+            "composting".to_string(),
+            recipe(
+                RecipeInput::Flexible {
+                    tag: ItemTag::Compostable,
+                    count: 2,
+                },
+                RecipeOutput::Stochastic(vec![(id("soil"), 0.5)]),
+                10,
+            ),
+        );
+
+        let mut structure_manifest = StructureManifest::new();
+        structure_manifest.insert("acacia".to_string(), crafter("leaf_production"));
+        structure_manifest.insert("leuco".to_string(), crafter("mushroom_production"));
+        structure_manifest.insert("hive".to_string(), crafter("ant_hatching"));
+
+        (recipe_manifest, structure_manifest)
+    }
+
+    #[test]
+    fn graph_links_targets_to_recipes_and_crafters() {
+        let (recipe_manifest, structure_manifest) = manifests();
+        let graph = RecipeGraph::new(&recipe_manifest, &structure_manifest);
+
+        assert_eq!(
+            graph.producers(ProductionTarget::Unit(id("ant"))),
+            &[id::<Recipe>("ant_hatching")]
+        );
+        assert_eq!(
+            graph.crafters(id("mushroom_production")),
+            &[id::<Structure>("leuco")]
+        );
+        assert!(graph
+            .producers(ProductionTarget::Item(id("water")))
+            .is_empty());
+    }
+
+    #[test]
+    fn plan_computes_crafter_ratios() {
+        let (recipe_manifest, structure_manifest) = manifests();
+        let graph = RecipeGraph::new(&recipe_manifest, &structure_manifest);
+
+        let plan = graph.plan(ProductionTarget::Unit(id("ant")), 1., &recipe_manifest);
+
+        // One ant per minute takes one mushroom per minute, which takes two leaves per minute
+        assert_eq!(plan.crafters_needed(id("ant_hatching")), 1.);
+        assert_eq!(plan.crafters_needed(id("mushroom_production")), 0.5);
+        assert_eq!(plan.crafters_needed(id("leaf_production")), 2.);
+        assert_eq!(plan.raw_inputs, vec![(ItemKind::Single(id("water")), 2.)]);
+    }
+
+    #[test]
+    fn plan_scales_with_target_rate() {
+        let (recipe_manifest, structure_manifest) = manifests();
+        let graph = RecipeGraph::new(&recipe_manifest, &structure_manifest);
+
+        let plan = graph.plan(ProductionTarget::Item(id("leaf")), 3., &recipe_manifest);
+
+        assert_eq!(plan.steps.len(), 1);
+        assert_eq!(plan.steps[0].crafter, Some(id("acacia")));
+        assert_eq!(plan.steps[0].crafts_per_minute, 3.);
+        assert_eq!(plan.crafters_needed(id("leaf_production")), 3.);
+    }
+
+    #[test]
+    fn stochastic_outputs_use_their_expected_count() {
+        let (recipe_manifest, structure_manifest) = manifests();
+        let graph = RecipeGraph::new(&recipe_manifest, &structure_manifest);
+
+        let plan = graph.plan(ProductionTarget::Item(id("soil")), 1., &recipe_manifest);
+
+        assert_eq!(plan.steps[0].crafts_per_minute, 2.);
+        assert_eq!(plan.steps[0].crafter, None);
+        assert_eq!(
+            plan.raw_inputs,
+            vec![(ItemKind::Tag(ItemTag::Compostable), 4.)]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Explains why a crafting structure is or isn't producing anything.
+//!
+//! [`CraftingState`](super::inventories::CraftingState) tracks where a structure is in the crafting process,
+//! while [`CraftingStatus`] records the reason it is stuck there, so that it can be shown to the player.
+
+use bevy::prelude::*;
+
+use crate::items::{bulk::BulkQuantity, item_manifest::ItemManifest};
+
+use super::{inventories::InputInventory, item_tags::ItemKind, recipe::RecipeInput};
+
+/// Why a crafting structure is or isn't making progress.
+///
+/// This is updated each tick by the crafting system.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub enum CraftingStatus {
+    /// No recipe is set, so there is nothing to do.
+    #[default]
+    Idle,
+    /// The recipe is progressing normally.
+    Working,
+    /// The recipe cannot start until more items are delivered.
+    MissingInputs {
+        /// The kinds of items that are missing, and how many more of each are needed.
+        items: Vec<(ItemKind, u32)>,
+    },
+    /// The recipe cannot start until its tanks hold enough of a bulk resource, such as water.
+    MissingBulkInputs {
+        /// The first resource that is short, and how much more is needed.
+        missing: BulkQuantity,
+    },
+    /// The recipe is complete, but its products have nowhere to go.
+    OutputFull,
+    /// The recipe's environmental requirements are not met.
+    ///
+    /// This covers light, temperature, workers, energy, dormancy and population limits.
+    MissingConditions,
+    /// The structure has been switched off by the player.
+    Disabled,
+}
+
+impl CraftingStatus {
+    /// Computes which items are needed to start a batch of `recipe_input`, given what is already in `input_inventory`.
+    ///
+    /// The list of items may be empty, if everything needed is stored but could not be consumed.
+    pub(crate) fn missing_inputs(
+        input_inventory: &InputInventory,
+        recipe_input: &RecipeInput,
+        item_manifest: &ItemManifest,
+    ) -> Self {
+        let items = match recipe_input {
+            RecipeInput::Exact(item_counts) => item_counts
+                .iter()
+                .filter_map(|item_count| {
+                    let available = input_inventory.inventory().item_count(item_count.item_id);
+                    let missing = item_count.count.saturating_sub(available);
+                    (missing > 0).then_some((ItemKind::Single(item_count.item_id), missing))
+                })
+                .collect(),
+            RecipeInput::Flexible { tag, count } => {
+                let available: u32 = input_inventory
+                    .iter()
+                    .filter(|slot| item_manifest.has_tag(slot.item_id(), *tag))
+                    .map(|slot| slot.count())
+                    .sum();
+                let missing = count.saturating_sub(available);
+
+                if missing > 0 {
+                    vec![(ItemKind::Tag(*tag), missing)]
+                } else {
+                    Vec::new()
+                }
+            }
+        };
+
+        CraftingStatus::MissingInputs { items }
+    }
+
+    /// Is this structure unable to make progress?
+    pub fn is_stalled(&self) -> bool {
+        !matches!(
+            self,
+            CraftingStatus::Idle | CraftingStatus::Working | CraftingStatus::Disabled
+        )
+    }
+
+    /// The pretty formatting for this type.
+    pub fn display(&self, item_manifest: &ItemManifest) -> String {
+        match self {
+            CraftingStatus::Idle => "Idle".to_string(),
+            CraftingStatus::Working => "Working".to_string(),
+            // Everything needed may already be stored, even though it could not be consumed
+            CraftingStatus::MissingInputs { items } if items.is_empty() => {
+                "Missing inputs".to_string()
+            }
+            CraftingStatus::MissingInputs { items } => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|(item_kind, count)| {
+                        let name = match item_kind {
+                            ItemKind::Single(item_id) => item_manifest.name(*item_id).to_string(),
+                            ItemKind::Tag(tag) => tag.to_string(),
+                        };
+                        format!("{name} ({count})")
+                    })
+                    .collect();
+
+                format!("Missing inputs: {}", items.join(", "))
+            }
+            CraftingStatus::MissingBulkInputs { missing } => {
+                format!("Missing inputs: {}", missing.display(item_manifest))
+            }
+            CraftingStatus::OutputFull => "Output full".to_string(),
+            CraftingStatus::MissingConditions => "Conditions not met".to_string(),
+            CraftingStatus::Disabled => "Disabled".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset_management::manifest::Id,
+        crafting::item_tags::ItemTag,
+        items::{inventory::Inventory, ItemCount},
+        testing::item_manifest,
+    };
+
+    #[test]
+    fn exact_inputs_report_each_missing_item() {
+        let item_manifest = item_manifest();
+        let leaf = Id::from_name("leaf".to_string());
+        let mushroom = Id::from_name("mushroom".to_string());
+
+        let mut inventory = Inventory::new(2, None);
+        inventory
+            .add_item_all_or_nothing(&ItemCount::new(leaf, 3), &item_manifest)
+            .unwrap();
+        let input_inventory = InputInventory::Exact { inventory };
+        let recipe_input =
+            RecipeInput::Exact(vec![ItemCount::new(leaf, 2), ItemCount::new(mushroom, 4)]);
+
+        assert_eq!(
+            CraftingStatus::missing_inputs(&input_inventory, &recipe_input, &item_manifest),
+            CraftingStatus::MissingInputs {
+                items: vec![(ItemKind::Single(mushroom), 4)]
+            }
+        );
+    }
+
+    #[test]
+    fn flexible_inputs_report_the_missing_tag() {
+        let item_manifest = item_manifest();
+        let leaf = Id::from_name("leaf".to_string());
+
+        let mut inventory = Inventory::new(1, None);
+        inventory
+            .add_item_all_or_nothing(&ItemCount::new(leaf, 3), &item_manifest)
+            .unwrap();
+        let input_inventory = InputInventory::Tagged {
+            tag: ItemTag::Compostable,
+            inventory,
+        };
+        let recipe_input = RecipeInput::Flexible {
+            tag: ItemTag::Compostable,
+            count: 5,
+        };
+
+        let status =
+            CraftingStatus::missing_inputs(&input_inventory, &recipe_input, &item_manifest);
+        assert_eq!(
+            status,
+            CraftingStatus::MissingInputs {
+                items: vec![(ItemKind::Tag(ItemTag::Compostable), 2)]
+            }
+        );
+        assert!(status.is_stalled());
+        assert_eq!(
+            status.display(&item_manifest),
+            "Missing inputs: Compostable (2)"
+        );
+    }
+
+    #[test]
+    fn nothing_missing_is_still_described() {
+        let item_manifest = item_manifest();
+        let leaf = Id::from_name("leaf".to_string());
+
+        let mut inventory = Inventory::new(1, None);
+        inventory
+            .add_item_all_or_nothing(&ItemCount::new(leaf, 3), &item_manifest)
+            .unwrap();
+        let input_inventory = InputInventory::Exact { inventory };
+        let recipe_input = RecipeInput::Exact(vec![ItemCount::new(leaf, 2)]);
+
+        let status =
+            CraftingStatus::missing_inputs(&input_inventory, &recipe_input, &item_manifest);
+        assert_eq!(status, CraftingStatus::MissingInputs { items: Vec::new() });
+        assert_eq!(status.display(&item_manifest), "Missing inputs");
+    }
+}
@@ -0,0 +1,358 @@
+//! Records the recent crafts of each structure, so that players can see what is holding it back.
+//!
+//! Each completed craft is split into three phases:
+//! waiting for inputs, crafting, and waiting for the outputs to be stored.
+//! A structure that spends most of its time between crafts waiting for deliveries is input-bound,
+//! while one whose products pile up is output-bound.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::Id,
+    items::{
+        inventory::Inventory,
+        item_manifest::{Item, ItemManifest},
+        ItemCount,
+    },
+};
+
+/// A single completed craft.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CraftRecord {
+    /// When the inputs were consumed and crafting began.
+    pub started: Duration,
+    /// When crafting finished and the outputs were produced.
+    pub finished: Duration,
+    /// When the outputs fit into the output inventory, freeing the structure to start again.
+    pub delivered: Duration,
+    /// The items consumed.
+    pub inputs: Vec<ItemCount>,
+    /// The items produced.
+    pub outputs: Vec<ItemCount>,
+}
+
+/// The craft that is currently underway.
+#[derive(Debug, Clone, PartialEq)]
+struct OngoingCraft {
+    /// When the inputs were consumed and crafting began.
+    started: Duration,
+    /// When crafting finished, if it has.
+    finished: Option<Duration>,
+    /// The items consumed.
+    inputs: Vec<ItemCount>,
+    /// The items produced, once crafting has finished.
+    outputs: Vec<ItemCount>,
+}
+
+/// What is limiting the throughput of a crafting structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bottleneck {
+    /// The structure spends its time waiting for inputs to be delivered.
+    InputBound,
+    /// The structure spends its time waiting for its outputs to be hauled away.
+    OutputBound,
+    /// The structure spends nearly all of its time crafting.
+    Balanced,
+}
+
+impl Display for Bottleneck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Bottleneck::InputBound => "Waiting on inputs",
+            Bottleneck::OutputBound => "Waiting on outputs",
+            Bottleneck::Balanced => "Balanced",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// The last few crafts completed by a crafting structure.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct CraftingHistory {
+    /// The most recent completed crafts, oldest first.
+    records: VecDeque<CraftRecord>,
+    /// The craft that is currently underway, if any.
+    ongoing: Option<OngoingCraft>,
+}
+
+impl CraftingHistory {
+    /// The number of completed crafts that are remembered.
+    pub const CAPACITY: usize = 10;
+
+    /// The fraction of time that must be spent waiting before a structure is considered bottlenecked.
+    const BOTTLENECK_THRESHOLD: f32 = 0.1;
+
+    /// The most recent completed crafts, oldest first.
+    pub fn records(&self) -> impl Iterator<Item = &CraftRecord> {
+        self.records.iter()
+    }
+
+    /// Records that `inputs` were consumed to begin crafting at `now`.
+    pub(super) fn record_start(&mut self, now: Duration, inputs: Vec<ItemCount>) {
+        self.ongoing = Some(OngoingCraft {
+            started: now,
+            finished: None,
+            inputs,
+            outputs: Vec::new(),
+        });
+    }
+
+    /// Records that crafting finished at `now`, producing `outputs`.
+    pub(super) fn record_finish(&mut self, now: Duration, outputs: Vec<ItemCount>) {
+        if let Some(ongoing) = &mut self.ongoing {
+            ongoing.finished = Some(now);
+            ongoing.outputs = outputs;
+        }
+    }
+
+    /// Records that the outputs of the ongoing craft were stored at `now`, completing it.
+    ///
+    /// Crafts that were started before this history was created are ignored.
+    pub(super) fn record_delivery(&mut self, now: Duration) {
+        let Some(ongoing) = self.ongoing.take() else {
+            return;
+        };
+
+        if self.records.len() == Self::CAPACITY {
+            self.records.pop_front();
+        }
+
+        self.records.push_back(CraftRecord {
+            started: ongoing.started,
+            finished: ongoing.finished.unwrap_or(now),
+            delivered: now,
+            inputs: ongoing.inputs,
+            outputs: ongoing.outputs,
+        });
+    }
+
+    /// The time covered by the recorded crafts.
+    fn window(&self) -> Duration {
+        match (self.records.front(), self.records.back()) {
+            (Some(first), Some(last)) => last.delivered.saturating_sub(first.started),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// The number of each item consumed or produced per minute over the recorded crafts.
+    fn rates(&self, items: impl Fn(&CraftRecord) -> &[ItemCount]) -> BTreeMap<Id<Item>, f32> {
+        let minutes = self.window().as_secs_f32() / 60.;
+        let mut rates = BTreeMap::new();
+        if minutes <= 0. {
+            return rates;
+        }
+
+        for item_count in self.records.iter().flat_map(&items) {
+            *rates.entry(item_count.item_id).or_default() += item_count.count as f32 / minutes;
+        }
+
+        rates
+    }
+
+    /// The number of each item consumed per minute over the recorded crafts.
+    pub fn input_rates(&self) -> BTreeMap<Id<Item>, f32> {
+        self.rates(|record| &record.inputs)
+    }
+
+    /// The number of each item produced per minute over the recorded crafts.
+    pub fn output_rates(&self) -> BTreeMap<Id<Item>, f32> {
+        self.rates(|record| &record.outputs)
+    }
+
+    /// The number of crafts completed per minute over the recorded crafts.
+    pub fn crafts_per_minute(&self) -> f32 {
+        let minutes = self.window().as_secs_f32() / 60.;
+        match minutes > 0. {
+            true => self.records.len() as f32 / minutes,
+            false => 0.,
+        }
+    }
+
+    /// Works out what is limiting this structure, if enough crafts have been recorded to tell.
+    ///
+    /// The time spent waiting for inputs is measured from each delivery to the start of the next craft,
+    /// while the time spent waiting on outputs is measured from the end of each craft to its delivery.
+    pub fn bottleneck(&self) -> Option<Bottleneck> {
+        if self.records.len() < 2 {
+            return None;
+        }
+
+        let window = self.window().as_secs_f32();
+        if window <= 0. {
+            return None;
+        }
+
+        let input_wait: Duration = self
+            .records
+            .iter()
+            .zip(self.records.iter().skip(1))
+            .map(|(previous, next)| next.started.saturating_sub(previous.delivered))
+            .sum();
+
+        let output_wait: Duration = self
+            .records
+            .iter()
+            .map(|record| record.delivered.saturating_sub(record.finished))
+            .sum();
+
+        let input_fraction = input_wait.as_secs_f32() / window;
+        let output_fraction = output_wait.as_secs_f32() / window;
+
+        Some(
+            if input_fraction.max(output_fraction) < Self::BOTTLENECK_THRESHOLD {
+                Bottleneck::Balanced
+            } else if input_fraction >= output_fraction {
+                Bottleneck::InputBound
+            } else {
+                Bottleneck::OutputBound
+            },
+        )
+    }
+
+    /// The pretty formatting for this type.
+    pub fn display(&self, item_manifest: &ItemManifest) -> String {
+        if self.records.is_empty() {
+            return "No crafts completed yet".to_string();
+        }
+
+        let format_rates = |rates: BTreeMap<Id<Item>, f32>| -> String {
+            if rates.is_empty() {
+                return "nothing".to_string();
+            }
+
+            rates
+                .iter()
+                .map(|(&item_id, rate)| format!("{} {rate:.1}/min", item_manifest.name(item_id)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut string = format!(
+            "Last {} crafts: {:.1}/min\nConsuming: {}\nProducing: {}",
+            self.records.len(),
+            self.crafts_per_minute(),
+            format_rates(self.input_rates()),
+            format_rates(self.output_rates()),
+        );
+
+        if let Some(bottleneck) = self.bottleneck() {
+            string += &format!("\nBottleneck: {bottleneck}");
+        }
+
+        string
+    }
+}
+
+/// The total number of each item in `inventory`.
+pub(super) fn contents(inventory: &Inventory) -> BTreeMap<Id<Item>, u32> {
+    let mut contents = BTreeMap::new();
+    for slot in inventory.iter() {
+        *contents.entry(slot.item_id()).or_default() += slot.count();
+    }
+    contents
+}
+
+/// The items that `inventory` has gained, compared to its earlier `contents`.
+pub(super) fn gained(before: &BTreeMap<Id<Item>, u32>, inventory: &Inventory) -> Vec<ItemCount> {
+    contents(inventory)
+        .into_iter()
+        .filter_map(|(item_id, count)| {
+            let previous = before.get(&item_id).copied().unwrap_or_default();
+            (count > previous).then(|| ItemCount::new(item_id, count - previous))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    fn leaf() -> Id<Item> {
+        Id::from_name("leaf".to_string())
+    }
+
+    fn mushroom() -> Id<Item> {
+        Id::from_name("mushroom".to_string())
+    }
+
+    /// Records a craft that starts at `start`, takes `craft` seconds, then waits `output_wait` seconds to be delivered.
+    fn record(history: &mut CraftingHistory, start: u64, craft: u64, output_wait: u64) {
+        history.record_start(secs(start), vec![ItemCount::new(leaf(), 2)]);
+        history.record_finish(secs(start + craft), vec![ItemCount::new(mushroom(), 1)]);
+        history.record_delivery(secs(start + craft + output_wait));
+    }
+
+    #[test]
+    fn only_the_most_recent_crafts_are_kept() {
+        let mut history = CraftingHistory::default();
+        for i in 0..CraftingHistory::CAPACITY as u64 + 5 {
+            record(&mut history, i * 10, 10, 0);
+        }
+
+        assert_eq!(history.records().count(), CraftingHistory::CAPACITY);
+        assert_eq!(history.records().next().unwrap().started, secs(50));
+    }
+
+    #[test]
+    fn deliveries_without_a_start_are_ignored() {
+        let mut history = CraftingHistory::default();
+        history.record_finish(secs(5), vec![ItemCount::new(mushroom(), 1)]);
+        history.record_delivery(secs(10));
+
+        assert_eq!(history.records().count(), 0);
+        assert_eq!(history.bottleneck(), None);
+    }
+
+    #[test]
+    fn rates_are_per_minute() {
+        let mut history = CraftingHistory::default();
+        for i in 0..6 {
+            record(&mut history, i * 10, 10, 0);
+        }
+
+        assert_eq!(history.crafts_per_minute(), 6.);
+        assert_eq!(history.input_rates().get(&leaf()), Some(&12.));
+        assert_eq!(history.output_rates().get(&mushroom()), Some(&6.));
+    }
+
+    #[test]
+    fn constant_crafting_is_balanced() {
+        let mut history = CraftingHistory::default();
+        for i in 0..5 {
+            record(&mut history, i * 10, 10, 0);
+        }
+
+        assert_eq!(history.bottleneck(), Some(Bottleneck::Balanced));
+    }
+
+    #[test]
+    fn gaps_between_crafts_are_input_bound() {
+        let mut history = CraftingHistory::default();
+        for i in 0..5 {
+            record(&mut history, i * 30, 10, 0);
+        }
+
+        assert_eq!(history.bottleneck(), Some(Bottleneck::InputBound));
+    }
+
+    #[test]
+    fn slow_deliveries_are_output_bound() {
+        let mut history = CraftingHistory::default();
+        for i in 0..5 {
+            record(&mut history, i * 30, 10, 20);
+        }
+
+        assert_eq!(history.bottleneck(), Some(Bottleneck::OutputBound));
+    }
+}
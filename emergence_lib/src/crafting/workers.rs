@@ -1,14 +1,14 @@
 //! Code for allowing workers to help with crafting.
 
-use bevy::{prelude::*, utils::HashSet};
+use bevy::{prelude::*, utils::HashMap};
 
 use std::fmt::Display;
 
 /// The number of workers present / allowed at this structure.
 #[derive(Component, Debug, Clone, PartialEq)]
 pub(crate) struct WorkersPresent {
-    /// The list of workers present
-    workers: HashSet<Entity>,
+    /// The list of workers present, and how effectively each of them is working
+    workers: HashMap<Entity, f32>,
 
     /// The maximum number of workers allowed
     allowed: u8,
@@ -18,7 +18,7 @@ impl WorkersPresent {
     /// Create a new [`WorkersPresent`] with the provided maximum number of workers allowed.
     pub(crate) fn new(allowed: u8) -> Self {
         Self {
-            workers: HashSet::new(),
+            workers: HashMap::new(),
             allowed,
         }
     }
@@ -34,14 +34,18 @@ impl WorkersPresent {
     }
 
     /// The current number of effective workers present.
+    ///
+    /// Workers that are slowed down (for example, by fatigue) count as a fraction of a worker.
     pub(crate) fn effective_workers(&self) -> f32 {
-        self.workers.len() as f32
+        self.workers.values().sum()
     }
 
     /// Adds a worker to this structure if there is room.
-    pub(crate) fn add_worker(&mut self, worker_entity: Entity) -> Result<(), ()> {
+    ///
+    /// The `efficiency` is the fraction of a full worker that this worker counts as.
+    pub(crate) fn add_worker(&mut self, worker_entity: Entity, efficiency: f32) -> Result<(), ()> {
         if self.needs_more() {
-            self.workers.insert(worker_entity);
+            self.workers.insert(worker_entity, efficiency);
             Ok(())
         } else {
             Err(())
@@ -58,7 +62,7 @@ impl Display for WorkersPresent {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{present} ({effective_workers}) / {allowed}",
+            "{present} ({effective_workers:.1}) / {allowed}",
             present = self.current(),
             effective_workers = self.effective_workers(),
             allowed = self.allowed
@@ -0,0 +1,361 @@
+//! An in-game entity inspector for developers, enabled by the `dev-tools` feature.
+//!
+//! Press F10 to open the inspector for the current selection.
+//! Components that implement [`DomainFormat`] are shown the way a player would read them:
+//! inventories as lists of items, [`Id`]s as names, and timers as progress bars.
+//! Everything else that is registered for reflection is shown in the raw component view below.
+//!
+//! Register additional components with [`RegisterDomainFormatExt::register_domain_format`].
+
+use bevy::{prelude::*, utils::get_short_name, window::PrimaryWindow};
+use bevy_inspector_egui::{
+    bevy_egui::{egui, EguiContext, EguiPlugin},
+    bevy_inspector::ui_for_entity,
+    DefaultInspectorConfigPlugin,
+};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::work_orders::WorkOrder,
+    crafting::{
+        inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
+        recipe::{ActiveRecipe, RecipeManifest},
+        throughput::CraftingHistory,
+    },
+    factions::Faction,
+    geometry::{MapGeometry, VoxelPos},
+    items::{bulk::Tanks, item_manifest::ItemManifest},
+    organisms::{energy::EnergyPool, lifecycle::Lifecycle},
+    player_interaction::{nicknames::Nickname, selection::CurrentSelection},
+    structures::structure_manifest::{Structure, StructureManifest},
+    terrain::terrain_manifest::{Terrain, TerrainManifest},
+    units::{
+        actions::CurrentAction,
+        goals::Goal,
+        item_interaction::UnitInventory,
+        unit_manifest::{Unit, UnitManifest},
+    },
+};
+
+/// Adds the developer inspector.
+pub struct DevToolsPlugin;
+
+impl Plugin for DevToolsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+
+        app.add_plugins(DefaultInspectorConfigPlugin)
+            .init_resource::<DomainFormatters>()
+            .init_resource::<InspectorOpen>()
+            .register_domain_format::<Id<Structure>>()
+            .register_domain_format::<Id<Unit>>()
+            .register_domain_format::<Id<Terrain>>()
+            .register_domain_format::<Faction>()
+            .register_domain_format::<Nickname>()
+            .register_domain_format::<VoxelPos>()
+            .register_domain_format::<Goal>()
+            .register_domain_format::<CurrentAction>()
+            .register_domain_format::<UnitInventory>()
+            .register_domain_format::<InputInventory>()
+            .register_domain_format::<OutputInventory>()
+            .register_domain_format::<StorageInventory>()
+            .register_domain_format::<Tanks>()
+            .register_domain_format::<ActiveRecipe>()
+            .register_domain_format::<CraftingState>()
+            .register_domain_format::<CraftingHistory>()
+            .register_domain_format::<EnergyPool>()
+            .register_domain_format::<Lifecycle>()
+            .register_domain_format::<WorkOrder>()
+            .add_systems(
+                Update,
+                (
+                    toggle_inspector,
+                    inspector_window.run_if(|open: Res<InspectorOpen>| open.0),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// A component that can be described in the terms used by the game, rather than as a raw struct.
+pub trait DomainFormat: Component {
+    /// Describes this component, using the manifests and other resources in `world` as needed.
+    fn format(&self, world: &World) -> String;
+}
+
+/// Registers [`DomainFormat`] components with the inspector.
+pub trait RegisterDomainFormatExt {
+    /// Shows `T` in the inspector using its [`DomainFormat`] implementation.
+    fn register_domain_format<T: DomainFormat>(&mut self) -> &mut Self;
+}
+
+impl RegisterDomainFormatExt for App {
+    fn register_domain_format<T: DomainFormat>(&mut self) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(DomainFormatters::default)
+            .formatters
+            .push((
+                get_short_name(std::any::type_name::<T>()),
+                format_component::<T>,
+            ));
+        self
+    }
+}
+
+/// Formats the `T` component of `entity`, if it has one.
+fn format_component<T: DomainFormat>(world: &World, entity: Entity) -> Option<String> {
+    world
+        .get::<T>(entity)
+        .map(|component| component.format(world))
+}
+
+/// A function that formats a single component type of an entity, if the entity has that component.
+type Formatter = fn(&World, Entity) -> Option<String>;
+
+/// The name and [`Formatter`] of each component registered with [`RegisterDomainFormatExt`], in registration order.
+#[derive(Resource, Debug, Default, Clone)]
+struct DomainFormatters {
+    /// The registered formatters.
+    formatters: Vec<(String, Formatter)>,
+}
+
+/// Is the inspector window currently open?
+#[derive(Resource, Debug, Default)]
+struct InspectorOpen(bool);
+
+/// Opens and closes the inspector when F10 is pressed.
+fn toggle_inspector(keyboard: Res<Input<KeyCode>>, mut inspector_open: ResMut<InspectorOpen>) {
+    if keyboard.just_pressed(KeyCode::F10) {
+        inspector_open.0 = !inspector_open.0;
+    }
+}
+
+/// Draws a text progress bar that is `fraction` full.
+fn progress_bar(fraction: f32) -> String {
+    /// The number of characters in the bar.
+    const WIDTH: usize = 20;
+
+    let fraction = fraction.clamp(0., 1.);
+    let filled = (fraction * WIDTH as f32).round() as usize;
+
+    format!(
+        "[{}{}] {:.0}%",
+        "#".repeat(filled),
+        "-".repeat(WIDTH - filled),
+        fraction * 100.
+    )
+}
+
+/// The entities that are part of the current selection.
+///
+/// At most a handful of tiles are inspected at once, to keep the window readable.
+fn selected_entities(world: &World) -> Vec<Entity> {
+    /// The largest number of selected tiles that are inspected.
+    const MAX_TILES: usize = 4;
+
+    match world.resource::<CurrentSelection>() {
+        CurrentSelection::Structure(entity) | CurrentSelection::Unit(entity) => vec![*entity],
+        CurrentSelection::Voxels(selected_voxels) => {
+            let map_geometry = world.resource::<MapGeometry>();
+
+            selected_voxels
+                .iter()
+                .take(MAX_TILES)
+                .flat_map(|&voxel_pos| {
+                    let terrain = map_geometry.get_terrain(voxel_pos.hex).ok();
+                    let structure = map_geometry.get_structure(voxel_pos);
+                    terrain.into_iter().chain(structure)
+                })
+                .collect()
+        }
+        CurrentSelection::None => Vec::new(),
+    }
+}
+
+/// Shows the selected entities in the inspector window.
+fn inspector_window(world: &mut World) {
+    let Ok(egui_context) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+        .get_single(world)
+    else {
+        return;
+    };
+    let mut egui_context = egui_context.clone();
+
+    let entities = selected_entities(world);
+    let formatters = world.resource::<DomainFormatters>().clone();
+
+    egui::Window::new("Inspector").show(egui_context.get_mut(), |ui| {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if entities.is_empty() {
+                ui.label("Nothing is selected");
+            }
+
+            for entity in entities {
+                ui.heading(format!("{entity:?}"));
+
+                for (name, formatter) in &formatters.formatters {
+                    if let Some(text) = formatter(world, entity) {
+                        ui.label(egui::RichText::new(name).strong());
+                        ui.label(text);
+                    }
+                }
+
+                ui.collapsing(format!("Raw components of {entity:?}"), |ui| {
+                    ui_for_entity(world, entity, ui);
+                });
+                ui.separator();
+            }
+        });
+    });
+}
+
+impl DomainFormat for Id<Structure> {
+    fn format(&self, world: &World) -> String {
+        world
+            .resource::<StructureManifest>()
+            .name(*self)
+            .to_string()
+    }
+}
+
+impl DomainFormat for Id<Unit> {
+    fn format(&self, world: &World) -> String {
+        world.resource::<UnitManifest>().name(*self).to_string()
+    }
+}
+
+impl DomainFormat for Id<Terrain> {
+    fn format(&self, world: &World) -> String {
+        world.resource::<TerrainManifest>().name(*self).to_string()
+    }
+}
+
+impl DomainFormat for Faction {
+    fn format(&self, _world: &World) -> String {
+        self.to_string()
+    }
+}
+
+impl DomainFormat for Nickname {
+    fn format(&self, _world: &World) -> String {
+        self.to_string()
+    }
+}
+
+impl DomainFormat for VoxelPos {
+    fn format(&self, _world: &World) -> String {
+        self.to_string()
+    }
+}
+
+impl DomainFormat for Goal {
+    fn format(&self, world: &World) -> String {
+        self.display(
+            world.resource::<ItemManifest>(),
+            world.resource::<StructureManifest>(),
+            world.resource::<TerrainManifest>(),
+            world.resource::<UnitManifest>(),
+        )
+    }
+}
+
+impl DomainFormat for CurrentAction {
+    fn format(&self, world: &World) -> String {
+        self.display(world.resource::<ItemManifest>())
+    }
+}
+
+impl DomainFormat for UnitInventory {
+    fn format(&self, world: &World) -> String {
+        self.display(world.resource::<ItemManifest>())
+    }
+}
+
+impl DomainFormat for InputInventory {
+    fn format(&self, world: &World) -> String {
+        self.display(world.resource::<ItemManifest>())
+    }
+}
+
+impl DomainFormat for OutputInventory {
+    fn format(&self, world: &World) -> String {
+        self.inventory.display(world.resource::<ItemManifest>())
+    }
+}
+
+impl DomainFormat for StorageInventory {
+    fn format(&self, world: &World) -> String {
+        self.inventory.display(world.resource::<ItemManifest>())
+    }
+}
+
+impl DomainFormat for Tanks {
+    fn format(&self, world: &World) -> String {
+        self.display(world.resource::<ItemManifest>())
+    }
+}
+
+impl DomainFormat for ActiveRecipe {
+    fn format(&self, world: &World) -> String {
+        self.display(world.resource::<RecipeManifest>())
+    }
+}
+
+impl DomainFormat for CraftingState {
+    fn format(&self, _world: &World) -> String {
+        match self {
+            CraftingState::InProgress { progress, required } => {
+                let fraction = match required.is_zero() {
+                    true => 1.,
+                    false => progress.as_secs_f32() / required.as_secs_f32(),
+                };
+                format!("{self}\n{}", progress_bar(fraction))
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl DomainFormat for CraftingHistory {
+    fn format(&self, world: &World) -> String {
+        self.display(world.resource::<ItemManifest>())
+    }
+}
+
+impl DomainFormat for EnergyPool {
+    fn format(&self, _world: &World) -> String {
+        use leafwing_abilities::prelude::Pool;
+
+        let current = self.current().0;
+        let max = self.max().0;
+        let fraction = match max > 0. {
+            true => current / max,
+            false => 0.,
+        };
+
+        format!("{current:.1} / {max:.1}\n{}", progress_bar(fraction))
+    }
+}
+
+impl DomainFormat for Lifecycle {
+    fn format(&self, world: &World) -> String {
+        let description = self.display(
+            world.resource::<StructureManifest>(),
+            world.resource::<UnitManifest>(),
+        );
+
+        format!("{description}\n{}", progress_bar(self.progress()))
+    }
+}
+
+impl DomainFormat for WorkOrder {
+    fn format(&self, world: &World) -> String {
+        self.display(
+            world.resource::<StructureManifest>(),
+            world.resource::<TerrainManifest>(),
+        )
+    }
+}
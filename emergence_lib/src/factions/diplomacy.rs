@@ -0,0 +1,178 @@
+//! Factions can form relationships with each other, changing how their units behave.
+//!
+//! Relationships are directed: they describe how one faction treats another,
+//! and need not be reciprocated.
+//! A faction's units can sense the signals of the factions that it has a relationship with,
+//! so an aphid-like colony that offers honeydew will have its excess collected by its mutualistic partners,
+//! while parasites will help themselves to whatever their hosts are storing.
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::signals::SignalKind;
+
+use super::Faction;
+
+/// How one faction treats another.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Relationship {
+    /// The factions ignore each other, and only compete for wild resources.
+    #[default]
+    Neutral,
+    /// This faction cooperates with the other, responding to all of its requests at reduced strength.
+    Mutualism,
+    /// This faction exploits the other, taking the items it offers or stores without giving anything back.
+    Parasitism,
+}
+
+impl Relationship {
+    /// The fraction of signal strength that a mutualistic partner senses.
+    ///
+    /// This is less than 1, so units prefer to tend to their own colony first.
+    const MUTUALISM_SENSITIVITY: f32 = 0.5;
+
+    /// The fraction of another faction's signals of `signal_kind` that are sensed under this relationship.
+    pub(crate) fn sensitivity(&self, signal_kind: SignalKind) -> f32 {
+        match self {
+            Relationship::Neutral => 0.,
+            // Units should never try to reproduce with members of another faction
+            Relationship::Mutualism => match signal_kind {
+                SignalKind::Unit => 0.,
+                _ => Self::MUTUALISM_SENSITIVITY,
+            },
+            Relationship::Parasitism => match signal_kind {
+                SignalKind::Push | SignalKind::Contains => 1.,
+                _ => 0.,
+            },
+        }
+    }
+}
+
+/// The directed relationships between every pair of factions.
+///
+/// Pairs that are not listed are [`Relationship::Neutral`].
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct Relationships {
+    /// How the first faction treats the second.
+    matrix: HashMap<(Faction, Faction), Relationship>,
+}
+
+impl Relationships {
+    /// How `faction` treats `other`.
+    pub fn get(&self, faction: Faction, other: Faction) -> Relationship {
+        self.matrix
+            .get(&(faction, other))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sets how `faction` treats `other`.
+    ///
+    /// Factions always cooperate with themselves, so attempts to change that are ignored.
+    pub fn set(&mut self, faction: Faction, other: Faction, relationship: Relationship) {
+        if faction == other {
+            return;
+        }
+
+        match relationship {
+            Relationship::Neutral => self.matrix.remove(&(faction, other)),
+            _ => self.matrix.insert((faction, other), relationship),
+        };
+    }
+
+    /// Sets how `faction` and `other` treat each other.
+    pub fn set_mutual(&mut self, faction: Faction, other: Faction, relationship: Relationship) {
+        self.set(faction, other, relationship);
+        self.set(other, faction, relationship);
+    }
+
+    /// The fraction of the signals of `signal_kind` emitted by `emitter` that members of `faction` can sense.
+    pub(crate) fn sensitivity(
+        &self,
+        faction: Faction,
+        emitter: Faction,
+        signal_kind: SignalKind,
+    ) -> f32 {
+        if faction == emitter {
+            1.
+        } else {
+            self.get(faction, emitter).sensitivity(signal_kind)
+        }
+    }
+
+    /// Can members of `faction` sense any of the signals emitted by `emitter`?
+    pub(crate) fn can_sense(&self, faction: Faction, emitter: Faction) -> bool {
+        faction == emitter || self.get(faction, emitter) != Relationship::Neutral
+    }
+
+    /// Does `faction` welcome members of `other` into its territory?
+    pub(crate) fn welcomes(&self, faction: Faction, other: Faction) -> bool {
+        faction == other || self.get(faction, other) == Relationship::Mutualism
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enum_iter::IterableEnum;
+
+    use super::*;
+
+    #[test]
+    fn relationships_are_directed() {
+        let mut relationships = Relationships::default();
+        let aphids = Faction(1);
+        let ants = Faction::PLAYER;
+
+        assert_eq!(relationships.get(ants, aphids), Relationship::Neutral);
+
+        relationships.set(ants, aphids, Relationship::Parasitism);
+        assert_eq!(relationships.get(ants, aphids), Relationship::Parasitism);
+        assert_eq!(relationships.get(aphids, ants), Relationship::Neutral);
+        assert!(relationships.can_sense(ants, aphids));
+        assert!(!relationships.can_sense(aphids, ants));
+
+        relationships.set_mutual(ants, aphids, Relationship::Mutualism);
+        assert!(relationships.welcomes(aphids, ants));
+
+        relationships.set(ants, aphids, Relationship::Neutral);
+        assert_eq!(relationships, {
+            let mut expected = Relationships::default();
+            expected.set(aphids, ants, Relationship::Mutualism);
+            expected
+        });
+    }
+
+    #[test]
+    fn factions_fully_sense_their_own_signals() {
+        let mut relationships = Relationships::default();
+        relationships.set(Faction(1), Faction(1), Relationship::Parasitism);
+
+        for signal_kind in SignalKind::variants() {
+            assert_eq!(
+                relationships.sensitivity(Faction(1), Faction(1), signal_kind),
+                1.
+            );
+        }
+    }
+
+    #[test]
+    fn parasites_only_sense_available_items() {
+        let host = Faction::PLAYER;
+        let parasite = Faction(1);
+        let mut relationships = Relationships::default();
+        relationships.set(parasite, host, Relationship::Parasitism);
+
+        assert_eq!(
+            relationships.sensitivity(parasite, host, SignalKind::Contains),
+            1.
+        );
+        assert_eq!(
+            relationships.sensitivity(parasite, host, SignalKind::Pull),
+            0.
+        );
+        assert_eq!(
+            relationships.sensitivity(host, parasite, SignalKind::Push),
+            0.
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Factions are independent colonies that share the same map.
+//!
+//! Every unit belongs to a [`Faction`], as do the structures that a faction builds.
+//! Each faction has its own channel of [`Signals`](crate::signals::Signals),
+//! so units only respond to the needs of their own colony.
+//! Wild organisms belong to no faction, and their signals can be sensed by everyone,
+//! which leaves rival colonies competing for the same resources,
+//! unless they have formed a [`Relationship`](diplomacy::Relationship) with each other.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod diplomacy;
+pub mod territory;
+
+/// The colony that a unit or structure belongs to.
+///
+/// Structures without this component are wild, and are not owned by anyone.
+#[derive(
+    Component,
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub struct Faction(pub u8);
+
+impl Faction {
+    /// The faction controlled by the player.
+    pub const PLAYER: Faction = Faction(0);
+}
+
+impl Display for Faction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Faction::PLAYER => write!(f, "Player"),
+            Faction(index) => write!(f, "Rival {index}"),
+        }
+    }
+}
+
+/// Who makes the decisions for a [`Faction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FactionController {
+    /// A human player, through the user interface.
+    Player,
+    /// The game itself.
+    ///
+    /// AI factions are run entirely by the emergent behavior of their units.
+    Ai,
+}
+
+/// The set of factions that exist in the current game.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Factions {
+    /// Who controls each faction.
+    controllers: BTreeMap<Faction, FactionController>,
+}
+
+impl Default for Factions {
+    fn default() -> Self {
+        Factions::new(0)
+    }
+}
+
+impl Factions {
+    /// Creates the player's faction, along with `n_ai_factions` rivals.
+    pub fn new(n_ai_factions: u8) -> Self {
+        let mut controllers = BTreeMap::new();
+        controllers.insert(Faction::PLAYER, FactionController::Player);
+        for index in 1..=n_ai_factions {
+            controllers.insert(Faction(index), FactionController::Ai);
+        }
+
+        Factions { controllers }
+    }
+
+    /// Iterates over all factions, starting with the player's.
+    pub fn iter(&self) -> impl Iterator<Item = Faction> + '_ {
+        self.controllers.keys().copied()
+    }
+
+    /// The number of factions, including the player's.
+    pub fn len(&self) -> usize {
+        self.controllers.len()
+    }
+
+    /// Are there no factions at all?
+    pub fn is_empty(&self) -> bool {
+        self.controllers.is_empty()
+    }
+
+    /// Who controls the provided `faction`, if it exists.
+    pub fn controller(&self, faction: Faction) -> Option<FactionController> {
+        self.controllers.get(&faction).copied()
+    }
+
+    /// Picks the faction that claims the provided `hex` at the start of the game.
+    ///
+    /// The map is divided into equal wedges around its center, one per faction.
+    pub(crate) fn starting_territory(&self, hex: hexx::Hex) -> Faction {
+        let n_factions = self.len().max(1);
+        let angle = (hex.y as f32 * 3f32.sqrt() / 2.)
+            .atan2(hex.x as f32 + hex.y as f32 / 2.)
+            .rem_euclid(std::f32::consts::TAU);
+        let wedge = (angle / std::f32::consts::TAU * n_factions as f32) as usize;
+
+        self.iter()
+            .nth(wedge.min(n_factions - 1))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::Hex;
+
+    use super::*;
+
+    #[test]
+    fn the_player_always_has_a_faction() {
+        let factions = Factions::default();
+        assert_eq!(factions.len(), 1);
+        assert_eq!(
+            factions.controller(Faction::PLAYER),
+            Some(FactionController::Player)
+        );
+
+        let factions = Factions::new(2);
+        assert_eq!(factions.len(), 3);
+        assert_eq!(factions.controller(Faction(2)), Some(FactionController::Ai));
+        assert_eq!(factions.controller(Faction(3)), None);
+    }
+
+    #[test]
+    fn starting_territory_is_split_between_factions() {
+        let solo = Factions::default();
+        assert_eq!(solo.starting_territory(Hex::new(3, -1)), Faction::PLAYER);
+        assert_eq!(solo.starting_territory(Hex::new(-3, 1)), Faction::PLAYER);
+
+        let rivals = Factions::new(1);
+        let east = rivals.starting_territory(Hex::new(3, 1));
+        let west = rivals.starting_territory(Hex::new(-3, -1));
+        assert_ne!(east, west);
+    }
+}
@@ -0,0 +1,279 @@
+//! Colonies claim the land around them.
+//!
+//! Each faction claims the tiles near the structures it owns,
+//! and scent-marks the tiles that its units walk across.
+//! Whichever faction has the strongest claim to a tile controls it.
+//!
+//! Wandering units are reluctant to stray into territory controlled by a rival,
+//! although mutualistic partners are welcome.
+//! Units that do trespass, usually while chasing a resource, leave their own scent behind and contest the claim.
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::{shapes::hexagon, Hex};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::Ghost,
+    geometry::{MapGeometry, VoxelPos},
+    simulation::SimulationSet,
+    structures::structure_manifest::Structure,
+    units::unit_manifest::Unit,
+};
+
+use super::{diplomacy::Relationships, Faction};
+
+/// Tracks and updates the [`Territory`] of each faction.
+pub(crate) struct TerritoryPlugin;
+
+impl Plugin for TerritoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Territory>().add_systems(
+            FixedUpdate,
+            (mark_territory, update_claims)
+                .chain()
+                .in_set(SimulationSet),
+        );
+    }
+}
+
+/// The tiles claimed by each faction.
+#[derive(Resource, Debug, Default)]
+pub struct Territory {
+    /// The scent left by each faction on each tile, which fades over time.
+    scent: HashMap<Hex, HashMap<Faction, f32>>,
+    /// The faction that controls each claimed tile.
+    ///
+    /// Tiles that are unclaimed, or whose claim is contested, are not present.
+    claims: HashMap<Hex, Faction>,
+}
+
+impl Territory {
+    /// The distance in tiles around each owned structure that its faction claims.
+    const STRUCTURE_CLAIM_RADIUS: u32 = 3;
+
+    /// The strength of the claim that a structure makes on each tile around it.
+    const STRUCTURE_CLAIM_STRENGTH: f32 = 10.;
+
+    /// The scent left per second by a unit standing on a tile.
+    const SCENT_RATE: f32 = 1.;
+
+    /// The fraction of scent that fades each second.
+    const SCENT_DECAY_RATE: f32 = 0.05;
+
+    /// The most scent that a single faction can build up on a tile.
+    ///
+    /// This is stronger than a structure's claim, so a persistent rival presence can take over territory.
+    const MAX_SCENT: f32 = 20.;
+
+    /// The weakest claim that can give a faction control of a tile.
+    const MIN_CLAIM_STRENGTH: f32 = 1.;
+
+    /// How likely wandering units are to step into a rival's territory, relative to unclaimed land.
+    pub(crate) const TRESPASS_WEIGHT: f32 = 0.25;
+
+    /// The faction that controls the provided `hex`, if any.
+    pub fn owner(&self, hex: Hex) -> Option<Faction> {
+        self.claims.get(&hex).copied()
+    }
+
+    /// Is `hex` controlled by a faction that does not welcome members of `faction`?
+    pub fn is_trespassing(
+        &self,
+        hex: Hex,
+        faction: Faction,
+        relationships: &Relationships,
+    ) -> bool {
+        matches!(self.owner(hex), Some(owner) if !relationships.welcomes(owner, faction))
+    }
+
+    /// Iterates over every claimed tile, along with the faction that controls it.
+    pub(crate) fn claims(&self) -> impl Iterator<Item = (Hex, Faction)> + '_ {
+        self.claims.iter().map(|(&hex, &faction)| (hex, faction))
+    }
+
+    /// The number of tiles controlled by `faction`.
+    pub fn n_claimed(&self, faction: Faction) -> usize {
+        self.claims
+            .values()
+            .filter(|&&owner| owner == faction)
+            .count()
+    }
+
+    /// Leaves `amount` of `faction`'s scent on `hex`.
+    fn mark(&mut self, hex: Hex, faction: Faction, amount: f32) {
+        let scent = self
+            .scent
+            .entry(hex)
+            .or_default()
+            .entry(faction)
+            .or_default();
+        *scent = (*scent + amount).min(Self::MAX_SCENT);
+    }
+
+    /// Fades all scent marks over `delta_secs`, forgetting any that have become too faint to matter.
+    fn fade(&mut self, delta_secs: f32) {
+        let retained = (1. - Self::SCENT_DECAY_RATE * delta_secs).max(0.);
+
+        self.scent.retain(|_, scents| {
+            scents.retain(|_, scent| {
+                *scent *= retained;
+                *scent >= f32::EPSILON
+            });
+            !scents.is_empty()
+        });
+    }
+
+    /// Recomputes who controls each tile, based on the current scent and the `structure_claims`.
+    fn recompute_claims(&mut self, structure_claims: HashMap<Hex, HashMap<Faction, f32>>) {
+        let mut strengths = structure_claims;
+        for (&hex, scents) in &self.scent {
+            let strengths_here = strengths.entry(hex).or_default();
+            for (&faction, &scent) in scents {
+                *strengths_here.entry(faction).or_default() += scent;
+            }
+        }
+
+        self.claims = strengths
+            .iter()
+            .filter_map(|(&hex, strengths_here)| {
+                strongest_claim(strengths_here).map(|faction| (hex, faction))
+            })
+            .collect();
+    }
+}
+
+/// The faction with the strongest claim, out of the provided `strengths`.
+///
+/// Returns [`None`] if no claim is strong enough, or if the strongest claims are tied.
+fn strongest_claim(strengths: &HashMap<Faction, f32>) -> Option<Faction> {
+    let mut strongest: Option<(Faction, f32)> = None;
+    let mut tied = false;
+
+    for (&faction, &strength) in strengths {
+        match strongest {
+            Some((_, best)) if strength < best => (),
+            Some((_, best)) if strength == best => tied = true,
+            _ => {
+                strongest = Some((faction, strength));
+                tied = false;
+            }
+        }
+    }
+
+    match strongest {
+        Some((faction, strength)) if !tied && strength >= Territory::MIN_CLAIM_STRENGTH => {
+            Some(faction)
+        }
+        _ => None,
+    }
+}
+
+/// Units scent-mark the tiles they stand on, while old marks fade away.
+fn mark_territory(
+    unit_query: Query<(&VoxelPos, &Faction), With<Id<Unit>>>,
+    mut territory: ResMut<Territory>,
+    time: Res<Time>,
+) {
+    let delta_secs = time.delta_seconds();
+
+    territory.fade(delta_secs);
+    for (voxel_pos, &faction) in unit_query.iter() {
+        territory.mark(voxel_pos.hex, faction, Territory::SCENT_RATE * delta_secs);
+    }
+}
+
+/// Determines which faction controls each tile.
+fn update_claims(
+    // Ghosts haven't been built yet, so they don't claim any land
+    structure_query: Query<(&VoxelPos, &Faction), (With<Id<Structure>>, Without<Ghost>)>,
+    map_geometry: Res<MapGeometry>,
+    mut territory: ResMut<Territory>,
+) {
+    let mut structure_claims: HashMap<Hex, HashMap<Faction, f32>> = HashMap::new();
+
+    for (voxel_pos, &faction) in structure_query.iter() {
+        for hex in hexagon(voxel_pos.hex, Territory::STRUCTURE_CLAIM_RADIUS) {
+            if !map_geometry.is_valid(hex) {
+                continue;
+            }
+
+            // Overlapping structures of the same faction don't strengthen each other's claims
+            let strength = structure_claims
+                .entry(hex)
+                .or_default()
+                .entry(faction)
+                .or_default();
+            *strength = strength.max(Territory::STRUCTURE_CLAIM_STRENGTH);
+        }
+    }
+
+    territory.recompute_claims(structure_claims);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::factions::diplomacy::Relationship;
+
+    use super::*;
+
+    #[test]
+    fn the_strongest_claim_wins() {
+        let player = Faction::PLAYER;
+        let rival = Faction(1);
+
+        assert_eq!(strongest_claim(&HashMap::new()), None);
+
+        let strengths = HashMap::from_iter([(player, 5.), (rival, 2.)]);
+        assert_eq!(strongest_claim(&strengths), Some(player));
+
+        let contested = HashMap::from_iter([(player, 5.), (rival, 5.)]);
+        assert_eq!(strongest_claim(&contested), None);
+
+        let faint = HashMap::from_iter([(rival, Territory::MIN_CLAIM_STRENGTH / 2.)]);
+        assert_eq!(strongest_claim(&faint), None);
+    }
+
+    #[test]
+    fn persistent_scent_can_contest_structure_claims() {
+        let mut territory = Territory::default();
+        let player = Faction::PLAYER;
+        let rival = Faction(1);
+
+        let structure_claims = || {
+            HashMap::from_iter([(
+                Hex::ZERO,
+                HashMap::from_iter([(player, Territory::STRUCTURE_CLAIM_STRENGTH)]),
+            )])
+        };
+
+        territory.recompute_claims(structure_claims());
+        assert_eq!(territory.owner(Hex::ZERO), Some(player));
+        let relationships = Relationships::default();
+        assert!(territory.is_trespassing(Hex::ZERO, rival, &relationships));
+        assert!(!territory.is_trespassing(Hex::ZERO, player, &relationships));
+
+        let mut relationships = Relationships::default();
+        relationships.set(player, rival, Relationship::Mutualism);
+        assert!(!territory.is_trespassing(Hex::ZERO, rival, &relationships));
+
+        territory.mark(Hex::ZERO, rival, Territory::MAX_SCENT);
+        territory.recompute_claims(structure_claims());
+        assert_eq!(territory.owner(Hex::ZERO), Some(rival));
+        assert_eq!(territory.n_claimed(rival), 1);
+    }
+
+    #[test]
+    fn scent_fades_over_time() {
+        let mut territory = Territory::default();
+        let rival = Faction(1);
+
+        territory.mark(Hex::ZERO, rival, 2. * Territory::MAX_SCENT);
+        assert_eq!(territory.scent[&Hex::ZERO][&rival], Territory::MAX_SCENT);
+
+        territory.fade(1.);
+        assert!(territory.scent[&Hex::ZERO][&rival] < Territory::MAX_SCENT);
+
+        territory.fade(1. / Territory::SCENT_DECAY_RATE);
+        assert!(territory.scent.is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+//! Limits what the player can see to the surroundings of their own colony.
+//!
+//! Fog of war is opt-in: add a [`FogOfWarPlugin`] with a [`FogOfWarConfig`] to enable it.
+//! Each organism and structure that belongs to the player reveals the tiles around it, unless a ridge blocks the view.
+//! Tiles that have been seen before, but are not currently in view, are drawn dimmed and without any units on them,
+//! while tiles that have never been seen are not drawn at all.
+//! Only visible tiles can be hovered, selected or inspected.
+//!
+//! This is purely a matter of presentation: the simulation, including rival colonies, is unaffected.
+
+use bevy::{prelude::*, utils::HashSet};
+use hexx::Hex;
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    factions::Faction,
+    geometry::{has_line_of_sight, spiral, DiscreteHeight, MapGeometry, VoxelPos},
+    organisms::Organism,
+    structures::structure_manifest::Structure,
+    world_gen::WorldGenState,
+};
+
+/// Tracks which tiles the player can see.
+pub struct FogOfWarPlugin {
+    /// How far the player's colony can see.
+    ///
+    /// If this is [`None`], fog of war is disabled and the whole map is visible.
+    pub config: Option<FogOfWarConfig>,
+}
+
+impl FogOfWarPlugin {
+    /// Configures fog of war using [`FogOfWarConfig::from_env`].
+    pub fn from_env() -> Self {
+        FogOfWarPlugin {
+            config: FogOfWarConfig::from_env(),
+        }
+    }
+}
+
+impl Plugin for FogOfWarPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(config) = &self.config else {
+            return;
+        };
+
+        info!(
+            "Fog of war is enabled, with a sight radius of {}",
+            config.sight_radius
+        );
+        app.insert_resource(FogOfWar::new(config.clone()))
+            .add_systems(
+                Update,
+                update_fog_of_war.run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// Settings for the [`FogOfWarPlugin`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FogOfWarConfig {
+    /// The number of tiles that each of the player's organisms and structures can see in every direction.
+    pub sight_radius: u32,
+}
+
+impl Default for FogOfWarConfig {
+    fn default() -> Self {
+        FogOfWarConfig {
+            sight_radius: Self::DEFAULT_SIGHT_RADIUS,
+        }
+    }
+}
+
+impl FogOfWarConfig {
+    /// The environment variable that enables fog of war.
+    ///
+    /// This can either be set to the sight radius, or to `on` to use the default radius.
+    pub const VAR: &'static str = "EMERGENCE_FOG_OF_WAR";
+
+    /// The default number of tiles that can be seen in every direction.
+    const DEFAULT_SIGHT_RADIUS: u32 = 6;
+
+    /// Reads the fog of war settings from the environment.
+    ///
+    /// Returns [`None`] if fog of war has not been requested, or if the setting is invalid.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(Self::VAR).ok()?;
+
+        match value.trim() {
+            "on" | "true" | "1" => Some(FogOfWarConfig::default()),
+            value => match value.parse() {
+                Ok(sight_radius) => Some(FogOfWarConfig { sight_radius }),
+                Err(error) => {
+                    error!("Invalid fog of war setting {value}: {error}");
+                    None
+                }
+            },
+        }
+    }
+}
+
+/// How much the player knows about a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TileVisibility {
+    /// The tile is currently in view.
+    Visible,
+    /// The tile has been seen before, but is not currently in view.
+    Explored,
+    /// The tile has never been seen.
+    Unexplored,
+}
+
+/// The tiles that the player can currently see, and those that they have seen before.
+///
+/// This resource only exists when fog of war is enabled.
+#[derive(Resource, Debug)]
+pub(crate) struct FogOfWar {
+    /// The settings used to compute visibility.
+    config: FogOfWarConfig,
+    /// The tiles that are currently in view.
+    visible: HashSet<Hex>,
+    /// The tiles that have ever been in view, including those that are in view now.
+    explored: HashSet<Hex>,
+}
+
+impl FogOfWar {
+    /// Creates a fog of war that covers the whole map.
+    fn new(config: FogOfWarConfig) -> Self {
+        FogOfWar {
+            config,
+            visible: HashSet::default(),
+            explored: HashSet::default(),
+        }
+    }
+
+    /// How much the player knows about the tile at `hex`.
+    pub(crate) fn visibility(&self, hex: Hex) -> TileVisibility {
+        if self.visible.contains(&hex) {
+            TileVisibility::Visible
+        } else if self.explored.contains(&hex) {
+            TileVisibility::Explored
+        } else {
+            TileVisibility::Unexplored
+        }
+    }
+
+    /// Can the player currently see the tile at `hex`?
+    ///
+    /// Every tile is visible when fog of war is disabled.
+    pub(crate) fn is_visible(maybe_fog_of_war: Option<&FogOfWar>, hex: Hex) -> bool {
+        maybe_fog_of_war.map_or(true, |fog_of_war| fog_of_war.visible.contains(&hex))
+    }
+}
+
+/// The number of height steps that a tile must rise above the viewer to block its view.
+const SIGHT_CLEARANCE: u8 = 2;
+
+/// Computes the set of tiles that can be seen from any of the `viewers`.
+///
+/// `height` returns the height of the terrain at each hex, or [`None`] if the hex is off the map.
+fn visible_hexes(
+    viewers: impl IntoIterator<Item = Hex>,
+    sight_radius: u32,
+    height: impl Fn(Hex) -> Option<DiscreteHeight>,
+) -> HashSet<Hex> {
+    let mut visible = HashSet::default();
+
+    for viewer in viewers {
+        let Some(viewer_height) = height(viewer) else {
+            continue;
+        };
+
+        let is_blocked = |hex: Hex| {
+            height(hex).map_or(false, |hex_height| {
+                hex_height.0 > viewer_height.0.saturating_add(SIGHT_CLEARANCE)
+            })
+        };
+
+        for hex in spiral(viewer, sight_radius) {
+            if height(hex).is_some()
+                && !visible.contains(&hex)
+                && has_line_of_sight(viewer, hex, is_blocked)
+            {
+                visible.insert(hex);
+            }
+        }
+    }
+
+    visible
+}
+
+/// Recomputes which tiles the player can see whenever their colony moves or the terrain changes.
+fn update_fog_of_war(
+    viewer_query: Query<
+        (Ref<VoxelPos>, &Faction),
+        (
+            Or<(With<Organism>, With<Id<Structure>>)>,
+            Without<Ghost>,
+            Without<Preview>,
+        ),
+    >,
+    mut removed_viewers: RemovedComponents<VoxelPos>,
+    map_geometry: Res<MapGeometry>,
+    mut fog_of_war: ResMut<FogOfWar>,
+) {
+    let viewers_moved = viewer_query
+        .iter()
+        .any(|(voxel_pos, _)| voxel_pos.is_changed());
+    let viewers_removed = removed_viewers.read().count() > 0;
+    if !viewers_moved && !viewers_removed && !map_geometry.is_changed() {
+        return;
+    }
+
+    let viewers = viewer_query
+        .iter()
+        .filter(|(_, &faction)| faction == Faction::PLAYER)
+        .map(|(voxel_pos, _)| voxel_pos.hex);
+
+    let visible = visible_hexes(viewers, fog_of_war.config.sight_radius, |hex| {
+        map_geometry.get_height(hex).ok()
+    });
+
+    // Avoid triggering change detection, as graphics and picking are updated whenever this changes
+    if visible != fog_of_war.visible {
+        let fog_of_war = &mut *fog_of_war;
+        fog_of_war.explored.extend(visible.iter().copied());
+        fog_of_war.visible = visible;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn viewers_see_within_their_sight_radius() {
+        let origin = Hex::ZERO;
+        let map: HashMap<Hex, DiscreteHeight> = spiral(origin, 5)
+            .map(|hex| (hex, DiscreteHeight::ZERO))
+            .collect();
+
+        let visible = visible_hexes([origin], 2, |hex| map.get(&hex).copied());
+
+        assert_eq!(visible.len(), Hex::range_count(2) as usize);
+        assert!(visible.contains(&Hex::new(2, 0)));
+        assert!(!visible.contains(&Hex::new(3, 0)));
+    }
+
+    #[test]
+    fn ridges_block_sight() {
+        let origin = Hex::ZERO;
+        let ridge = Hex::new(1, 0);
+        let mut map: HashMap<Hex, DiscreteHeight> = spiral(origin, 5)
+            .map(|hex| (hex, DiscreteHeight::ZERO))
+            .collect();
+        map.insert(ridge, DiscreteHeight(SIGHT_CLEARANCE + 1));
+
+        let visible = visible_hexes([origin], 3, |hex| map.get(&hex).copied());
+
+        // The ridge itself can be seen, but not what lies behind it
+        assert!(visible.contains(&ridge));
+        assert!(!visible.contains(&Hex::new(2, 0)));
+        assert!(visible.contains(&Hex::new(-2, 0)));
+
+        // Viewers on high ground can see over lower ridges
+        map.insert(origin, DiscreteHeight(2));
+        let visible = visible_hexes([origin], 3, |hex| map.get(&hex).copied());
+        assert!(visible.contains(&Hex::new(2, 0)));
+    }
+
+    #[test]
+    fn explored_tiles_are_remembered() {
+        let mut fog_of_war = FogOfWar::new(FogOfWarConfig::default());
+        let hex = Hex::new(4, -1);
+        assert_eq!(fog_of_war.visibility(hex), TileVisibility::Unexplored);
+        assert!(!FogOfWar::is_visible(Some(&fog_of_war), hex));
+        assert!(FogOfWar::is_visible(None, hex));
+
+        fog_of_war.visible.insert(hex);
+        fog_of_war.explored.insert(hex);
+        assert_eq!(fog_of_war.visibility(hex), TileVisibility::Visible);
+
+        fog_of_war.visible.clear();
+        assert_eq!(fog_of_war.visibility(hex), TileVisibility::Explored);
+    }
+}
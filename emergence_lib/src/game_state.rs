@@ -0,0 +1,181 @@
+//! Tracks which screen of the application the player is on, from the main menu to the game itself.
+//!
+//! The simulation and all player interaction with the world only run in [`GameState::Playing`].
+//! In [`GameState::MapEditor`], the camera and selection still work, but the world is only changed by the editor's brushes.
+//! Menus are opened on top of each other, and [`MenuHistory`] remembers the way back.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    enum_iter::IterableEnum,
+    map_editor::MapEditorPlugin,
+    player_interaction::{
+        photo_mode::PhotoMode, InteractionSystem, PlayerAction, PlayerModifiesWorld,
+    },
+    simulation::{rng::GlobalRng, stepping, SimulationSet},
+    world_gen::GenerationConfig,
+};
+
+/// Starts the application in the main menu, and moves between menus and the game.
+///
+/// Without this plugin, the game starts immediately.
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .init_resource::<MenuHistory>()
+            .configure_sets(
+                FixedUpdate,
+                // Requested steps still run behind menus, so the simulation can be stepped while paused
+                SimulationSet.run_if(in_state(GameState::Playing).or_else(stepping::is_stepping)),
+            )
+            .configure_sets(
+                Update,
+                PlayerModifiesWorld.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, toggle_menu)
+            .add_systems(OnExit(GameState::NewGame), reseed_world_gen)
+            .add_plugins(MapEditorPlugin);
+
+        for variant in InteractionSystem::variants() {
+            match variant {
+                // The map editor paints with the selection brush, so the camera and selection are needed there too
+                InteractionSystem::MoveCamera
+                | InteractionSystem::ComputeCursorPos
+                | InteractionSystem::SelectTiles => {
+                    app.configure_sets(
+                        Update,
+                        variant.run_if(|game_state: Res<State<GameState>>| {
+                            game_state.get().is_in_world()
+                        }),
+                    );
+                }
+                _ => {
+                    app.configure_sets(Update, variant.run_if(in_state(GameState::Playing)));
+                }
+            }
+        }
+    }
+}
+
+/// The screen that the player is currently on.
+#[derive(States, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameState {
+    /// The first screen shown when the game is launched.
+    #[default]
+    MainMenu,
+    /// Choosing the settings used to generate a new world.
+    NewGame,
+    /// Choosing a saved game to continue.
+    LoadGame,
+    /// Changing the settings of the application.
+    Settings,
+    /// The world is being generated or played.
+    Playing,
+    /// The game is paused behind the in-game menu.
+    PauseMenu,
+    /// The world is being painted by hand, to be saved as a [`Scenario`](crate::world_gen::scenario::Scenario).
+    MapEditor,
+}
+
+impl GameState {
+    /// Is this a menu screen, rather than the game itself?
+    pub fn is_menu(&self) -> bool {
+        !self.is_in_world()
+    }
+
+    /// Is the player looking at the world, either to play in it or to edit it?
+    pub fn is_in_world(&self) -> bool {
+        matches!(self, GameState::Playing | GameState::MapEditor)
+    }
+}
+
+/// The screens that the player has passed through to reach the current one.
+#[derive(Resource, Debug, Default)]
+pub struct MenuHistory {
+    /// The previous screens, with the most recent last.
+    previous: Vec<GameState>,
+}
+
+impl MenuHistory {
+    /// Records that the player is moving from `current` to `next`, returning `next`.
+    pub fn open(&mut self, current: GameState, next: GameState) -> GameState {
+        self.previous.push(current);
+        next
+    }
+
+    /// Returns to the previous screen, if there is one.
+    pub fn back(&mut self) -> Option<GameState> {
+        self.previous.pop()
+    }
+
+    /// Forgets the previous screens, so that going back is no longer possible.
+    ///
+    /// This should be called when a game is started, so that leaving the pause menu returns to the game.
+    pub fn clear(&mut self) {
+        self.previous.clear();
+    }
+}
+
+/// Opens the pause menu during the game, or goes back a screen in the menus.
+fn toggle_menu(
+    actions: Res<ActionState<PlayerAction>>,
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut menu_history: ResMut<MenuHistory>,
+    photo_mode: Res<PhotoMode>,
+) {
+    // Photo mode uses the same key to close itself
+    if !actions.just_pressed(PlayerAction::OpenMenu) || photo_mode.is_active() {
+        return;
+    }
+
+    let current = *game_state.get();
+    let next = match current {
+        GameState::Playing | GameState::MapEditor => {
+            Some(menu_history.open(current, GameState::PauseMenu))
+        }
+        _ => menu_history.back(),
+    };
+
+    if let Some(next) = next {
+        next_game_state.set(next);
+    }
+}
+
+/// Reseeds the random number generator from the [`GenerationConfig`] chosen in the new game menu.
+fn reseed_world_gen(generation_config: Res<GenerationConfig>, mut commands: Commands) {
+    commands.insert_resource(GlobalRng::new(generation_config.seed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menus_return_to_where_they_were_opened() {
+        let mut history = MenuHistory::default();
+
+        let pause_menu = history.open(GameState::Playing, GameState::PauseMenu);
+        let settings = history.open(pause_menu, GameState::Settings);
+        assert_eq!(settings, GameState::Settings);
+
+        assert_eq!(history.back(), Some(GameState::PauseMenu));
+        assert_eq!(history.back(), Some(GameState::Playing));
+        assert_eq!(history.back(), None);
+    }
+
+    #[test]
+    fn starting_a_game_forgets_the_menus() {
+        let mut history = MenuHistory::default();
+
+        history.open(GameState::MainMenu, GameState::NewGame);
+        history.clear();
+        let pause_menu = history.open(GameState::Playing, GameState::PauseMenu);
+
+        assert!(pause_menu.is_menu());
+        assert_eq!(history.back(), Some(GameState::Playing));
+    }
+}
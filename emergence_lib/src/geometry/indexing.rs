@@ -11,7 +11,7 @@ use crate::{
     items::inventory::InventoryState, structures::Footprint, units::actions::DeliveryMode,
 };
 
-use super::{DiscreteHeight, Facing, VoxelKind, VoxelObject, VoxelPos};
+use super::{DiscreteHeight, Facing, MapLayer, VoxelKind, VoxelObject, VoxelPos};
 use core::fmt::Display;
 
 /// The overall size and arrangement of the map.
@@ -39,6 +39,11 @@ pub struct MapGeometry {
     ///
     /// The set of keys is the set of all [`VoxelPos`] that units could be found.
     walkable_neighbors: HashMap<VoxelPos, Neighbors>,
+    /// The on-map neighbors of each hex, indexed in the order of [`hexx::Direction::ALL_DIRECTIONS`].
+    ///
+    /// The shape of the map never changes, so this is computed once when the map is created.
+    /// The set of keys is the set of all valid [`Hex`] positions on the map, plus the ring of ocean tiles around it.
+    adjacent_hex_index: HashMap<Hex, [Option<Hex>; 6]>,
 }
 
 /// The six neighbors of a voxel position.
@@ -162,6 +167,11 @@ impl MapGeometry {
             );
         }
 
+        // Ocean tiles need to know their neighbors too, so water can flow back onto the map
+        let adjacent_hex_index = hexagon(Hex::ZERO, radius + 1)
+            .map(|hex| (hex, compute_adjacent_hexes(radius, hex)))
+            .collect();
+
         let mut map_geometry = MapGeometry {
             radius,
             terrain_index,
@@ -169,6 +179,7 @@ impl MapGeometry {
             height_index,
             voxel_index,
             walkable_neighbors: HashMap::default(),
+            adjacent_hex_index,
         };
 
         map_geometry.recompute_walkable_neighbors();
@@ -205,6 +216,21 @@ impl MapGeometry {
         self.voxel_index.get(&voxel_pos)
     }
 
+    /// Returns an iterator over the objects stacked in the column at `hex`, starting with the terrain and moving upwards.
+    ///
+    /// Iteration stops at the first empty voxel.
+    pub(crate) fn objects_in_column(&self, hex: Hex) -> impl Iterator<Item = &VoxelObject> + '_ {
+        let terrain_voxel = self
+            .get_height(hex)
+            .ok()
+            .map(|height| VoxelPos { hex, height });
+
+        std::iter::successors(terrain_voxel, |voxel_pos| {
+            (voxel_pos.height < DiscreteHeight::MAX).then(|| voxel_pos.above())
+        })
+        .map_while(|voxel_pos| self.get_voxel(voxel_pos))
+    }
+
     /// Are all of the tiles in the `footprint` centered around `center` valid?
     #[inline]
     #[must_use]
@@ -323,6 +349,19 @@ impl MapGeometry {
         }
     }
 
+    /// Returns the [`MapLayer`] that `voxel_pos` belongs to.
+    ///
+    /// Voxels below the top of their terrain column are underground.
+    /// Voxels off the edge of the map are treated as part of the surface.
+    #[inline]
+    #[must_use]
+    pub(crate) fn layer_of(&self, voxel_pos: VoxelPos) -> MapLayer {
+        match self.get_height(voxel_pos.hex) {
+            Ok(terrain_height) if voxel_pos.height < terrain_height => MapLayer::Underground,
+            _ => MapLayer::Surface,
+        }
+    }
+
     /// Returns the average height (in world units) of tiles around `voxel_pos` within `radius`
     #[inline]
     #[must_use]
@@ -694,18 +733,22 @@ impl MapGeometry {
     }
 
     /// The set of tiles adjacent to `hex` that are on the map.
+    ///
+    /// These are indexed in the order of [`hexx::Direction::ALL_DIRECTIONS`].
+    /// Results are cached for every tile on the map and the ring of ocean tiles around it.
     #[inline]
     #[must_use]
     pub(crate) fn adjacent_hexes(&self, hex: Hex) -> [Option<Hex>; 6] {
-        let mut adjacent_hexes = [None; 6];
-
-        for (i, neighbor) in hex.ring(1).enumerate() {
-            if self.is_valid(neighbor) {
-                adjacent_hexes[i] = Some(neighbor);
-            }
+        match self.adjacent_hex_index.get(&hex) {
+            Some(adjacent_hexes) => *adjacent_hexes,
+            None => compute_adjacent_hexes(self.radius, hex),
         }
+    }
 
-        adjacent_hexes
+    /// Returns an iterator over the tiles adjacent to `hex` that are on the map.
+    #[inline]
+    pub(crate) fn valid_neighbors(&self, hex: Hex) -> impl Iterator<Item = Hex> {
+        self.adjacent_hexes(hex).into_iter().flatten()
     }
 
     /// The set of tiles that can be walked to by a basket crab from `voxel_pos`.
@@ -741,6 +784,29 @@ impl MapGeometry {
         neighbors.in_direction(direction)
     }
 
+    /// Returns the voxel that a flying unit would reach by moving from `voxel_pos` in the provided direction, if any.
+    ///
+    /// Flying ignores obstacles and height differences: the unit simply hovers above the tallest object in the next column.
+    #[inline]
+    #[must_use]
+    pub(crate) fn flight_neighbor_in_direction(
+        &self,
+        voxel_pos: VoxelPos,
+        direction: hexx::Direction,
+    ) -> Option<VoxelPos> {
+        let hex = voxel_pos.hex.neighbor(direction);
+        if !self.is_valid(hex) {
+            return None;
+        }
+
+        let terrain_height = self.get_height(hex).ok()?;
+        // The column always contains the terrain, so this is the first empty voxel above it
+        let column_height = self.objects_in_column(hex).count() as u8;
+        let height = DiscreteHeight(terrain_height.0.saturating_add(column_height));
+
+        Some(VoxelPos { hex, height })
+    }
+
     /// Returns an iterator over the set of empty voxels that are walkalbe from `voxel_pos`.
     pub(crate) fn empty_neighbors(
         &self,
@@ -750,6 +816,22 @@ impl MapGeometry {
             .filter(|neighbor| self.is_voxel_clear(*neighbor).is_ok())
     }
 
+    /// Can a basket crab stand in `voxel_pos`?
+    ///
+    /// This requires something to stand on below, and nothing blocking the voxel itself.
+    pub(crate) fn is_walkable(&self, voxel_pos: VoxelPos) -> bool {
+        let has_floor = self
+            .get_voxel(voxel_pos.below())
+            .is_some_and(|voxel_data| voxel_data.object_kind.can_walk_on_roof());
+
+        let can_walk_through = match self.get_voxel(voxel_pos) {
+            Some(voxel_data) => voxel_data.object_kind.can_walk_through(),
+            None => true,
+        };
+
+        has_floor && can_walk_through
+    }
+
     /// Computes the set of tiles across the entire map that can be walked on by a basket crab.
     pub(crate) fn walkable_voxels(&self) -> HashSet<VoxelPos> {
         let mut walkable_voxels = HashSet::new();
@@ -796,8 +878,14 @@ impl MapGeometry {
         for origin_voxel in &self.origin_voxels() {
             let mut local_neighbors = Neighbors::NONE;
 
-            for (i, &direction) in hexx::Direction::ALL_DIRECTIONS.iter().enumerate() {
-                let neighbor_hex = origin_voxel.hex.neighbor(direction);
+            let adjacent_hexes = self.adjacent_hexes(origin_voxel.hex);
+
+            for (i, maybe_neighbor_hex) in adjacent_hexes.into_iter().enumerate() {
+                // Tiles off the edge of the map can never be walked on
+                let Some(neighbor_hex) = maybe_neighbor_hex else {
+                    continue;
+                };
+
                 let neighbor_flat = VoxelPos {
                     hex: neighbor_hex,
                     height: origin_voxel.height,
@@ -827,6 +915,16 @@ impl MapGeometry {
     }
 }
 
+/// Computes the tiles adjacent to `hex` that lie within a map of the given `radius`.
+///
+/// These are indexed in the order of [`hexx::Direction::ALL_DIRECTIONS`].
+fn compute_adjacent_hexes(radius: u32, hex: Hex) -> [Option<Hex>; 6] {
+    hexx::Direction::ALL_DIRECTIONS.map(|direction| {
+        let neighbor = hex.neighbor(direction);
+        (Hex::ZERO.unsigned_distance_to(neighbor) <= radius).then_some(neighbor)
+    })
+}
+
 #[cfg(test)]
 impl MapGeometry {
     /// Runs all of the validation checks on the map.
@@ -938,6 +1036,64 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn adjacent_hexes_are_cached_for_the_map_and_ocean() {
+        let radius = 3;
+
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, radius);
+
+        // Interior tiles have all six neighbors
+        assert_eq!(map_geometry.valid_neighbors(Hex::ZERO).count(), 6);
+
+        for hex in hexagon(Hex::ZERO, radius + 1) {
+            let cached = map_geometry.adjacent_hexes(hex);
+            assert_eq!(cached, compute_adjacent_hexes(radius, hex));
+
+            for (direction, maybe_neighbor) in hexx::Direction::ALL_DIRECTIONS.iter().zip(cached) {
+                let neighbor = hex.neighbor(*direction);
+                assert_eq!(maybe_neighbor.is_some(), map_geometry.is_valid(neighbor));
+            }
+        }
+
+        // Ocean tiles are only adjacent to the edge of the map
+        for hex in map_geometry.ocean_tiles() {
+            for neighbor in map_geometry.valid_neighbors(hex) {
+                assert_eq!(Hex::ZERO.unsigned_distance_to(neighbor), radius);
+            }
+        }
+    }
+
+    #[test]
+    fn columns_start_with_terrain() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+
+        let column: Vec<&VoxelObject> = map_geometry.objects_in_column(Hex::ZERO).collect();
+        assert_eq!(column.len(), 1);
+        assert_eq!(column[0].object_kind, VoxelKind::Terrain);
+
+        // Hexes off the map have nothing in them
+        assert_eq!(map_geometry.objects_in_column(Hex::new(10, 0)).count(), 0);
+    }
+
+    #[test]
+    fn voxels_below_the_terrain_are_underground() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 3);
+        map_geometry.update_height(Hex::ZERO, DiscreteHeight(2));
+
+        let voxel_at = |height| VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(height),
+        };
+
+        assert_eq!(map_geometry.layer_of(voxel_at(1)), MapLayer::Underground);
+        // The terrain itself forms the floor of the surface layer
+        assert_eq!(map_geometry.layer_of(voxel_at(2)), MapLayer::Surface);
+        assert_eq!(map_geometry.layer_of(voxel_at(3)), MapLayer::Surface);
+    }
+
     #[test]
     fn map_geometry_is_initialized_successfully() {
         let radius = 10;
@@ -1087,6 +1243,32 @@ mod tests {
         assert_eq!(map_geometry.walkable_voxels(), can_walk_at_height_two);
     }
 
+    #[test]
+    fn is_walkable_agrees_with_walkable_voxels() {
+        let mut map_geometry = MapGeometry::new(&mut World::new(), 0);
+        let ground = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(1),
+        };
+
+        assert!(map_geometry.is_walkable(ground));
+        assert!(!map_geometry.is_walkable(ground.below()));
+        assert!(!map_geometry.is_walkable(ground.above()));
+
+        map_geometry
+            .add_structure(
+                ground,
+                Facing::default(),
+                &Footprint::default(),
+                false,
+                false,
+                Entity::from_bits(42),
+            )
+            .unwrap();
+
+        assert!(!map_geometry.is_walkable(ground));
+    }
+
     #[test]
     fn adding_ghost_structures_does_not_change_walkable_neighbors() {
         let mut world = World::new();
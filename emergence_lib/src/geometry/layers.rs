@@ -0,0 +1,35 @@
+//! The map is split into a surface layer and an underground layer.
+//!
+//! Voxels below the top of a terrain column are underground: they receive no sunlight,
+//! and can be hidden or revealed independently of the surface when rendering.
+
+use core::fmt::Display;
+
+/// A horizontal slice of the map.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MapLayer {
+    /// The top of the terrain and everything above it.
+    #[default]
+    Surface,
+    /// Everything below the top of the terrain.
+    Underground,
+}
+
+impl MapLayer {
+    /// Switches to the other layer.
+    pub fn toggle(self) -> Self {
+        match self {
+            MapLayer::Surface => MapLayer::Underground,
+            MapLayer::Underground => MapLayer::Surface,
+        }
+    }
+}
+
+impl Display for MapLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapLayer::Surface => write!(f, "Surface"),
+            MapLayer::Underground => write!(f, "Underground"),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Conversions between hex coordinates and world space.
+//!
+//! All code that needs to know how hexes are laid out in the world should go through these functions,
+//! rather than using the underlying [`HexLayout`] directly.
+
+use bevy::prelude::*;
+use hexx::{Direction, Hex, HexLayout};
+
+/// The layout of the hexagonal grid used to define the world map.
+pub(super) const MAP_LAYOUT: HexLayout = HexLayout {
+    orientation: hexx::HexOrientation::Flat,
+    origin: hexx::Vec2::ZERO,
+    hex_size: hexx::Vec2::ONE,
+    invert_x: false,
+    invert_y: false,
+};
+
+/// The world-space position of the center of `hex`, in the XZ plane.
+#[inline]
+#[must_use]
+pub fn hex_to_xz(hex: Hex) -> Vec2 {
+    MAP_LAYOUT.hex_to_world_pos(hex)
+}
+
+/// The hex that contains the world-space position `xz`, given in the XZ plane.
+#[inline]
+#[must_use]
+pub fn xz_to_hex(xz: Vec2) -> Hex {
+    MAP_LAYOUT.world_pos_to_hex(xz)
+}
+
+/// The world-space offset from the center of `from` to the center of `to`, in the XZ plane.
+#[inline]
+#[must_use]
+pub fn xz_offset(from: Hex, to: Hex) -> Vec2 {
+    hex_to_xz(to) - hex_to_xz(from)
+}
+
+/// The angle in radians that `direction` points in, as used for rotating objects on the map.
+#[inline]
+#[must_use]
+pub fn direction_angle(direction: Direction) -> f32 {
+    direction.angle(MAP_LAYOUT.orientation)
+}
+
+/// The [`Direction`] that most closely matches the provided `angle` in radians.
+///
+/// This is the inverse of [`direction_angle`].
+#[inline]
+#[must_use]
+pub fn direction_from_angle(angle: f32) -> Direction {
+    Direction::from_angle(angle, MAP_LAYOUT.orientation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_to_xz_is_invertable() {
+        for x in -10..=10 {
+            for y in -10..=10 {
+                let hex = Hex::new(x, y);
+                assert_eq!(xz_to_hex(hex_to_xz(hex)), hex);
+            }
+        }
+    }
+
+    #[test]
+    fn direction_angle_is_invertable() {
+        for direction in Direction::ALL_DIRECTIONS {
+            assert_eq!(direction_from_angle(direction_angle(direction)), direction);
+        }
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn hex_to_xz_round_trips(x in -1000..=1000, y in -1000..=1000) {
+                let hex = Hex::new(x, y);
+                prop_assert_eq!(xz_to_hex(hex_to_xz(hex)), hex);
+            }
+
+            #[test]
+            fn neighbors_are_one_unit_apart(x in -1000..=1000, y in -1000..=1000) {
+                let hex = Hex::new(x, y);
+                for neighbor in hex.all_neighbors() {
+                    let distance = xz_offset(hex, neighbor).length();
+                    prop_assert!((distance - 3f32.sqrt()).abs() < 1e-3);
+                }
+            }
+        }
+    }
+}
@@ -4,9 +4,9 @@ use bevy::{
     prelude::Mesh,
     render::{mesh::Indices, render_resource::PrimitiveTopology},
 };
-use hexx::ColumnMeshBuilder;
+use hexx::{ColumnMeshBuilder, Hex};
 
-use super::MAP_LAYOUT;
+use super::layout::MAP_LAYOUT;
 
 /// Constructs the mesh for a single hexagonal column with the specified height.
 #[must_use]
@@ -20,3 +20,30 @@ pub(crate) fn hexagonal_column(hex_height: f32) -> Mesh {
     mesh.set_indices(Some(Indices::U16(mesh_info.indices)));
     mesh
 }
+
+/// Constructs a flat hexagonal ring, lying in the XZ plane at `y = 0`.
+///
+/// The outer edge matches the edge of a tile,
+/// while the inner edge is scaled down by `inner_scale`, which should be between 0.0 and 1.0.
+#[must_use]
+pub(crate) fn hexagonal_outline(inner_scale: f32) -> Mesh {
+    let corners = MAP_LAYOUT.hex_corners(Hex::ZERO);
+
+    let outer_vertices = corners.map(|corner| [corner.x, 0., corner.y]);
+    let inner_vertices = corners.map(|corner| [corner.x * inner_scale, 0., corner.y * inner_scale]);
+    let vertices: Vec<[f32; 3]> = outer_vertices.into_iter().chain(inner_vertices).collect();
+
+    let mut indices = Vec::with_capacity(6 * 6);
+    for i in 0..6 {
+        let next = (i + 1) % 6;
+        // Each side of the ring is a quad, made of two triangles
+        indices.extend([i, next, 6 + i, next, 6 + next, 6 + i]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 1., 0.]; 12]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; 12]);
+    mesh.set_indices(Some(Indices::U16(indices)));
+    mesh
+}
@@ -1,26 +1,29 @@
 //! Manages the game world's grid and data tied to that grid
 
 mod indexing;
-use hexx::HexLayout;
 pub use indexing::MapGeometry;
 
+mod layers;
+pub use layers::MapLayer;
+
+mod layout;
+pub use layout::{direction_angle, direction_from_angle, hex_to_xz, xz_offset, xz_to_hex};
+
 mod meshes;
-pub(crate) use meshes::hexagonal_column;
+pub(crate) use meshes::{hexagonal_column, hexagonal_outline};
 
 mod position;
 pub use position::{DiscreteHeight, Height, Volume, VoxelPos};
 
 mod rotation;
-pub(crate) use rotation::{sync_rotation_to_facing, Facing, RotationDirection};
+pub(crate) use rotation::{
+    sync_rotation_to_facing, weighted_random_direction, Facing, RotationDirection,
+};
+
+mod shapes;
+pub use shapes::{
+    arc, cone, has_line_of_sight, line, reflect_across, rotate_around, spiral, HexAxis,
+};
 
 mod voxels;
 pub(crate) use voxels::{VoxelKind, VoxelObject};
-
-/// The layout of the hexagonal grid used to define the world map.
-pub(crate) const MAP_LAYOUT: HexLayout = HexLayout {
-    orientation: hexx::HexOrientation::Flat,
-    origin: hexx::Vec2::ZERO,
-    hex_size: hexx::Vec2::ONE,
-    invert_x: false,
-    invert_y: false,
-};
@@ -10,7 +10,7 @@ use std::{
     ops::{Add, AddAssign, Div, Mul, Sub, SubAssign},
 };
 
-use super::{Facing, MAP_LAYOUT};
+use super::{hex_to_xz, xz_to_hex, Facing};
 
 /// The discretized height of this tile
 ///
@@ -341,13 +341,11 @@ impl VoxelPos {
     ///
     /// This is a 3-high column of a one-radius hexagon extending above and below `tile_pos`, with the center voxel being `self`.
     pub(crate) fn reachable_neighbors(&self) -> [VoxelPos; 21] {
-        let hexagon: Vec<Hex> = hexagon(self.hex, 1).collect();
-
         let mut reachable_neighbors = [Self::ZERO; 21];
 
         for layer in 0..=2 {
             let height_offset = layer as i8 - 1;
-            for (i, &hex) in hexagon.iter().enumerate() {
+            for (i, hex) in hexagon(self.hex, 1).enumerate() {
                 let index = layer * 7 + i;
 
                 reachable_neighbors[index] = VoxelPos {
@@ -362,7 +360,7 @@ impl VoxelPos {
 
     /// Returns the transform-space position of the top-center of this voxel.
     pub fn into_world_pos(&self) -> Vec3 {
-        let xz = MAP_LAYOUT.hex_to_world_pos(self.hex);
+        let xz = hex_to_xz(self.hex);
         let y = self.height().into_world_pos();
 
         Vec3 {
@@ -374,7 +372,7 @@ impl VoxelPos {
 
     /// Returns the transform-space position of the terrain topper on top of this voxel.
     pub fn top_of_tile(&self) -> Vec3 {
-        let xz = MAP_LAYOUT.hex_to_world_pos(self.hex);
+        let xz = hex_to_xz(self.hex);
         let y = self.height().into_world_pos() + Height::TOPPER_THICKNESS;
 
         Vec3 {
@@ -386,7 +384,7 @@ impl VoxelPos {
 
     /// Returns the transform-space position of the terrain topper below this voxel.
     pub fn inside_voxel(&self) -> Vec3 {
-        let xz = MAP_LAYOUT.hex_to_world_pos(self.hex);
+        let xz = hex_to_xz(self.hex);
         let y = self.height.below().into_world_pos() + Height::TOPPER_THICKNESS;
 
         Vec3 {
@@ -402,7 +400,7 @@ impl VoxelPos {
     #[inline]
     #[must_use]
     pub(crate) fn from_world_pos(world_pos: Vec3) -> Self {
-        let hex = MAP_LAYOUT.world_pos_to_hex(Vec2 {
+        let hex = xz_to_hex(Vec2 {
             x: world_pos.x,
             y: world_pos.z,
         });
@@ -4,9 +4,12 @@ use bevy::prelude::*;
 use core::fmt::Display;
 use derive_more::Display;
 use hexx::Direction;
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
 
-use super::MAP_LAYOUT;
+use super::direction_angle;
 
 /// The hex direction that this entity is facing.
 ///
@@ -24,9 +27,9 @@ impl Facing {
     #[inline]
     #[must_use]
     pub(crate) fn random(rng: &mut impl Rng) -> Self {
-        let direction = *Direction::ALL_DIRECTIONS.choose(rng).unwrap();
-
-        Self { direction }
+        Self {
+            direction: random_direction(rng),
+        }
     }
 
     /// Rotates this facing one 60 degree step counterclockwise.
@@ -80,6 +83,29 @@ impl Display for Facing {
     }
 }
 
+/// Picks one of the six hex directions uniformly at random.
+#[inline]
+#[must_use]
+pub(crate) fn random_direction(rng: &mut impl Rng) -> Direction {
+    Direction::ALL_DIRECTIONS[rng.gen_range(0..Direction::ALL_DIRECTIONS.len())]
+}
+
+/// Picks a hex direction at random, with each direction chosen in proportion to its `weight`.
+///
+/// Negative weights are treated as zero.
+/// Returns [`None`] if no direction has a positive weight.
+#[must_use]
+pub(crate) fn weighted_random_direction(
+    rng: &mut impl Rng,
+    weight: impl Fn(Direction) -> f32,
+) -> Option<Direction> {
+    let weights = Direction::ALL_DIRECTIONS.map(|direction| weight(direction).max(0.));
+    // This fails if every weight is zero, or if any weight is not finite
+    let distribution = WeightedIndex::new(weights).ok()?;
+
+    Some(Direction::ALL_DIRECTIONS[distribution.sample(rng)])
+}
+
 /// The direction of a [`Facing`] rotation
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Display)]
 pub(crate) enum RotationDirection {
@@ -93,7 +119,7 @@ impl RotationDirection {
     /// Picks a direction to rotate in at random
     #[inline]
     #[must_use]
-    pub(crate) fn random(rng: &mut ThreadRng) -> Self {
+    pub(crate) fn random(rng: &mut impl Rng) -> Self {
         match rng.gen::<bool>() {
             true => RotationDirection::Left,
             false => RotationDirection::Right,
@@ -110,8 +136,48 @@ pub(crate) fn sync_rotation_to_facing(
 ) {
     for (mut transform, &facing) in query.iter_mut() {
         // Rotate the object in the correct direction
-        let angle = facing.direction.angle(MAP_LAYOUT.orientation);
+        let angle = direction_angle(facing.direction);
         let target = Quat::from_axis_angle(Vec3::Y, angle);
         transform.rotation = target;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_directions_cover_every_direction() {
+        let rng = &mut rand::thread_rng();
+        let mut seen = [false; 6];
+
+        for _ in 0..1000 {
+            let direction = random_direction(rng);
+            let index = Direction::ALL_DIRECTIONS
+                .iter()
+                .position(|&d| d == direction)
+                .unwrap();
+            seen[index] = true;
+        }
+
+        assert_eq!(seen, [true; 6]);
+    }
+
+    #[test]
+    fn weighted_directions_respect_their_weights() {
+        let rng = &mut rand::thread_rng();
+
+        for _ in 0..100 {
+            let direction = weighted_random_direction(rng, |direction| match direction {
+                Direction::Top => 1.,
+                Direction::Bottom => 3.,
+                _ => 0.,
+            })
+            .unwrap();
+            assert!(matches!(direction, Direction::Top | Direction::Bottom));
+        }
+
+        assert_eq!(weighted_random_direction(rng, |_| 0.), None);
+        assert_eq!(weighted_random_direction(rng, |_| -1.), None);
+    }
+}
@@ -0,0 +1,354 @@
+//! Reusable operations on groups of hexes: rotations, reflections, spirals, cones and lines of sight.
+//!
+//! These are expressed in terms of [`Hex`] rather than [`VoxelPos`](super::VoxelPos),
+//! as they only care about the horizontal layout of the map.
+
+use bevy::prelude::*;
+use hexx::{Direction, Hex};
+
+use super::xz_offset;
+
+/// Rotates `hex` around `center` by `clockwise_steps` 60 degree steps.
+///
+/// Negative values rotate counterclockwise.
+#[inline]
+#[must_use]
+pub fn rotate_around(hex: Hex, center: Hex, clockwise_steps: i32) -> Hex {
+    (hex - center).rotate_cw(clockwise_steps.rem_euclid(6) as u32) + center
+}
+
+/// One of the three axes of a hexagonal grid, which hexes can be reflected across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexAxis {
+    /// The `x` (or `q`) axis.
+    X,
+    /// The `y` (or `r`) axis.
+    Y,
+    /// The `z` (or `s`) axis, where `z = -x - y`.
+    Z,
+}
+
+/// Reflects `hex` across the line through `center` that runs parallel to `axis`.
+///
+/// Reflecting twice across the same axis returns the original hex.
+#[inline]
+#[must_use]
+pub fn reflect_across(hex: Hex, center: Hex, axis: HexAxis) -> Hex {
+    let offset = hex - center;
+    let (x, y) = (offset.x, offset.y);
+    let z = -x - y;
+
+    // Reflecting across an axis keeps that cube coordinate fixed and swaps the other two
+    let reflected = match axis {
+        HexAxis::X => Hex::new(x, z),
+        HexAxis::Y => Hex::new(z, y),
+        HexAxis::Z => Hex::new(y, x),
+    };
+
+    reflected + center
+}
+
+/// Iterates over all hexes within `radius` of `center`, starting at the center and spiraling outwards ring by ring.
+///
+/// Hexes closer to the center are always returned before those further away.
+pub fn spiral(center: Hex, radius: u32) -> impl Iterator<Item = Hex> {
+    std::iter::once(center)
+        .chain((1..=radius).flat_map(move |ring_radius| center.ring(ring_radius)))
+}
+
+/// The angle in radians between the `direction` from `origin` and the line from `origin` to `hex`.
+///
+/// Returns 0 if `hex` is the `origin`.
+fn angle_from(origin: Hex, direction: Direction, hex: Hex) -> f32 {
+    let forward = xz_offset(origin, origin.neighbor(direction));
+    let offset = xz_offset(origin, hex);
+
+    if offset == Vec2::ZERO {
+        return 0.;
+    }
+
+    // More precise than `Vec2::angle_between` for nearly parallel vectors
+    forward.perp_dot(offset).atan2(forward.dot(offset)).abs()
+}
+
+/// A small tolerance used when comparing angles, so that hexes lying exactly on the edge of a cone are included.
+const ANGLE_EPSILON: f32 = 1e-4;
+
+/// Iterates over all hexes within `radius` of `origin` that lie within `half_angle` radians of `direction`.
+///
+/// The `origin` itself is not included.
+/// A `half_angle` of [`PI`](std::f32::consts::PI) includes every hex in range.
+pub fn cone(
+    origin: Hex,
+    direction: Direction,
+    radius: u32,
+    half_angle: f32,
+) -> impl Iterator<Item = Hex> {
+    spiral(origin, radius)
+        .skip(1)
+        .filter(move |&hex| angle_from(origin, direction, hex) <= half_angle + ANGLE_EPSILON)
+}
+
+/// Iterates over the hexes exactly `radius` away from `origin` that lie within `half_angle` radians of `direction`.
+pub fn arc(
+    origin: Hex,
+    direction: Direction,
+    radius: u32,
+    half_angle: f32,
+) -> impl Iterator<Item = Hex> {
+    origin
+        .ring(radius)
+        .filter(move |&hex| angle_from(origin, direction, hex) <= half_angle + ANGLE_EPSILON)
+}
+
+/// Rounds fractional cube coordinates to the nearest hex.
+fn round_cube(x: f32, y: f32, z: f32) -> Hex {
+    let mut rounded_x = x.round();
+    let mut rounded_y = y.round();
+    let rounded_z = z.round();
+
+    let x_error = (rounded_x - x).abs();
+    let y_error = (rounded_y - y).abs();
+    let z_error = (rounded_z - z).abs();
+
+    // Fix up whichever coordinate had the largest rounding error, so that x + y + z == 0
+    if x_error > y_error && x_error > z_error {
+        rounded_x = -rounded_y - rounded_z;
+    } else if y_error > z_error {
+        rounded_y = -rounded_x - rounded_z;
+    }
+
+    Hex::new(rounded_x as i32, rounded_y as i32)
+}
+
+/// The hexes on the straight line from `from` to `to`, including both ends.
+///
+/// Each step along the line moves to an adjacent hex.
+pub fn line(from: Hex, to: Hex) -> Vec<Hex> {
+    let n_steps = from.unsigned_distance_to(to);
+    if n_steps == 0 {
+        return vec![from];
+    }
+
+    // Nudge the line slightly, so that it never runs exactly along the boundary between two hexes
+    let start = (
+        from.x as f32 + 1e-4,
+        from.y as f32 + 1e-4,
+        (-from.x - from.y) as f32 - 2e-4,
+    );
+    let end = (to.x as f32, to.y as f32, (-to.x - to.y) as f32);
+
+    (0..=n_steps)
+        .map(|step| {
+            let t = step as f32 / n_steps as f32;
+            round_cube(
+                start.0 + (end.0 - start.0) * t,
+                start.1 + (end.1 - start.1) * t,
+                start.2 + (end.2 - start.2) * t,
+            )
+        })
+        .collect()
+}
+
+/// Can `to` be seen from `from`?
+///
+/// The view is blocked if any hex strictly between the two is blocked, as determined by `is_blocked`.
+/// The endpoints themselves never block the view, so a wall can always see and be seen by its neighbors.
+pub fn has_line_of_sight(from: Hex, to: Hex, is_blocked: impl Fn(Hex) -> bool) -> bool {
+    let line = line(from, to);
+    let n = line.len();
+
+    line.into_iter()
+        .skip(1)
+        .take(n.saturating_sub(2))
+        .all(|hex| !is_blocked(hex))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use bevy::utils::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn rotating_six_times_is_identity() {
+        let center = Hex::new(2, -1);
+        let hex = Hex::new(4, 1);
+
+        assert_eq!(rotate_around(hex, center, 0), hex);
+        assert_eq!(rotate_around(hex, center, 6), hex);
+        assert_eq!(
+            rotate_around(hex, center, -1),
+            rotate_around(hex, center, 5)
+        );
+
+        let mut rotated: HashSet<Hex> = HashSet::default();
+        for steps in 1..=6 {
+            let rotated_hex = rotate_around(hex, center, steps);
+            // Rotation preserves the distance to the center
+            assert_eq!(
+                rotated_hex.unsigned_distance_to(center),
+                hex.unsigned_distance_to(center)
+            );
+            rotated.insert(rotated_hex);
+        }
+
+        assert_eq!(rotated.len(), 6);
+        assert!(rotated.contains(&hex));
+    }
+
+    #[test]
+    fn reflection_is_an_involution() {
+        let center = Hex::new(-1, 3);
+        for axis in [HexAxis::X, HexAxis::Y, HexAxis::Z] {
+            for hex in spiral(center, 3) {
+                let reflected = reflect_across(hex, center, axis);
+                assert_eq!(
+                    reflected.unsigned_distance_to(center),
+                    hex.unsigned_distance_to(center)
+                );
+                assert_eq!(reflect_across(reflected, center, axis), hex);
+            }
+        }
+
+        // The center is fixed
+        assert_eq!(reflect_across(center, center, HexAxis::X), center);
+    }
+
+    #[test]
+    fn spiral_is_ordered_by_distance() {
+        let center = Hex::new(3, -2);
+        let hexes: Vec<Hex> = spiral(center, 3).collect();
+
+        // 1 + 6 + 12 + 18
+        assert_eq!(hexes.len(), 37);
+        assert_eq!(hexes[0], center);
+        assert_eq!(hexes.iter().collect::<HashSet<_>>().len(), 37);
+
+        for window in hexes.windows(2) {
+            assert!(
+                window[0].unsigned_distance_to(center) <= window[1].unsigned_distance_to(center)
+            );
+        }
+    }
+
+    #[test]
+    fn cones_are_bounded_by_their_angle() {
+        let origin = Hex::ZERO;
+        let direction = Direction::Top;
+
+        let narrow: Vec<Hex> = cone(origin, direction, 3, 0.).collect();
+        assert_eq!(narrow.len(), 3);
+        assert!(narrow.contains(&origin.neighbor(direction)));
+
+        // A full circle includes every hex except the origin
+        assert_eq!(cone(origin, direction, 3, PI).count(), 36);
+
+        let wide_arc: Vec<Hex> = arc(origin, direction, 2, PI / 3.).collect();
+        assert!(wide_arc
+            .iter()
+            .all(|hex| hex.unsigned_distance_to(origin) == 2));
+        assert!(wide_arc.len() < 12);
+    }
+
+    #[test]
+    fn lines_are_contiguous() {
+        let from = Hex::new(-3, 1);
+        let to = Hex::new(4, -2);
+        let line = line(from, to);
+
+        assert_eq!(line.first(), Some(&from));
+        assert_eq!(line.last(), Some(&to));
+        assert_eq!(line.len() as u32, from.unsigned_distance_to(to) + 1);
+
+        for window in line.windows(2) {
+            assert_eq!(window[0].unsigned_distance_to(window[1]), 1);
+        }
+    }
+
+    #[test]
+    fn blockers_obstruct_line_of_sight() {
+        let from = Hex::ZERO;
+        let to = Hex::new(3, 0);
+        let wall = Hex::new(2, 0);
+
+        assert!(has_line_of_sight(from, to, |_| false));
+        assert!(!has_line_of_sight(from, to, |hex| hex == wall));
+        // The endpoints never block
+        assert!(has_line_of_sight(from, to, |hex| hex == from || hex == to));
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Generates a hex near the origin.
+        fn hex() -> impl Strategy<Value = Hex> {
+            (-50..=50, -50..=50).prop_map(|(x, y)| Hex::new(x, y))
+        }
+
+        /// Generates one of the three hex axes.
+        fn axis() -> impl Strategy<Value = HexAxis> {
+            prop_oneof![Just(HexAxis::X), Just(HexAxis::Y), Just(HexAxis::Z)]
+        }
+
+        proptest! {
+            #[test]
+            fn distance_is_symmetric(a in hex(), b in hex()) {
+                prop_assert_eq!(a.unsigned_distance_to(b), b.unsigned_distance_to(a));
+                prop_assert_eq!(a.unsigned_distance_to(a), 0);
+            }
+
+            #[test]
+            fn rings_have_six_hexes_per_step(center in hex(), radius in 1..20u32) {
+                let ring: HashSet<Hex> = center.ring(radius).collect();
+                prop_assert_eq!(ring.len() as u32, 6 * radius);
+                prop_assert!(ring.iter().all(|hex| hex.unsigned_distance_to(center) == radius));
+            }
+
+            #[test]
+            fn spirals_contain_every_hex_in_range(center in hex(), radius in 0..10u32) {
+                let hexes: HashSet<Hex> = spiral(center, radius).collect();
+                prop_assert_eq!(hexes.len() as u32, 3 * radius * (radius + 1) + 1);
+            }
+
+            #[test]
+            fn rotation_wraps_every_six_steps(
+                hex in hex(),
+                center in hex(),
+                steps in -12..12i32,
+            ) {
+                let rotated = rotate_around(hex, center, steps);
+                prop_assert_eq!(rotate_around(hex, center, steps + 6), rotated);
+                prop_assert_eq!(rotate_around(rotated, center, -steps), hex);
+                prop_assert_eq!(
+                    rotated.unsigned_distance_to(center),
+                    hex.unsigned_distance_to(center)
+                );
+            }
+
+            #[test]
+            fn reflection_is_idempotent_in_pairs(hex in hex(), center in hex(), axis in axis()) {
+                let reflected = reflect_across(hex, center, axis);
+                prop_assert_eq!(reflect_across(reflected, center, axis), hex);
+                prop_assert_eq!(
+                    reflected.unsigned_distance_to(center),
+                    hex.unsigned_distance_to(center)
+                );
+            }
+
+            #[test]
+            fn lines_are_shortest_paths(from in hex(), to in hex()) {
+                let line = line(from, to);
+                prop_assert_eq!(line.first(), Some(&from));
+                prop_assert_eq!(line.last(), Some(&to));
+                prop_assert_eq!(line.len() as u32, from.unsigned_distance_to(to) + 1);
+                for window in line.windows(2) {
+                    prop_assert_eq!(window[0].unsigned_distance_to(window[1]), 1);
+                }
+            }
+        }
+    }
+}
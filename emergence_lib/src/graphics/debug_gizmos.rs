@@ -0,0 +1,340 @@
+//! Draws the internal state of the simulation over the map, to help debug unit AI and logistics.
+//!
+//! Gizmos are grouped into [`GizmoCategory`]s, which can be toggled independently through the [`DebugGizmos`] resource.
+//! [`PlayerAction::CycleDebugGizmos`] steps through each category on its own, then all of them at once, then none.
+
+use bevy::{prelude::*, utils::HashSet};
+use emergence_macros::IterableEnum;
+use hexx::Direction;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    self as emergence_lib,
+    enum_iter::IterableEnum,
+    factions::territory::Territory,
+    geometry::{hex_to_xz, spiral, Facing, MapGeometry, VoxelPos},
+    graphics::palette::infovis::{
+        FACTION_COLORS, HAULING_ROUTE_COLOR, NEUTRAL_INFOVIS_COLOR, PATH_PREVIEW_COLOR,
+        PATH_SEARCH_COLOR,
+    },
+    logistics::HaulingJob,
+    player_interaction::{
+        path_preview::PathPreview, picking::CursorPos, InteractionSystem, PlayerAction,
+    },
+    signals::{SignalChannels, SignalKind},
+    units::{
+        actions::CurrentAction,
+        goals::Goal,
+        item_interaction::UnitInventory,
+        scheduling::{LastThought, ThinkingQueue},
+    },
+};
+
+use super::GraphicsSet;
+
+/// Draws debugging gizmos for the enabled [`GizmoCategory`]s.
+pub(super) struct DebugGizmosPlugin;
+
+impl Plugin for DebugGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugGizmos>().add_systems(
+            Update,
+            (
+                cycle_debug_gizmos,
+                (
+                    draw_unit_goals.run_if(gizmo_enabled(GizmoCategory::UnitGoals)),
+                    draw_hauling_routes.run_if(gizmo_enabled(GizmoCategory::HaulingRoutes)),
+                    draw_signal_gradients.run_if(gizmo_enabled(GizmoCategory::SignalGradients)),
+                    draw_territory_borders.run_if(gizmo_enabled(GizmoCategory::TerritoryBorders)),
+                    draw_path_search.run_if(gizmo_enabled(GizmoCategory::PathSearch)),
+                    draw_recent_thoughts.run_if(gizmo_enabled(GizmoCategory::RecentThoughts)),
+                ),
+            )
+                .chain()
+                .after(InteractionSystem::ComputeCursorPos)
+                .in_set(GraphicsSet),
+        );
+    }
+}
+
+/// A group of debugging gizmos that can be toggled together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IterableEnum)]
+pub(crate) enum GizmoCategory {
+    /// An arrow from each unit to whatever it is currently acting on, colored by its goal.
+    UnitGoals,
+    /// An arrow from the source to the destination of each hauling job.
+    HaulingRoutes,
+    /// Arrows pointing up the strongest goal signal, for the tiles around the cursor.
+    SignalGradients,
+    /// The edges of each faction's territory.
+    TerritoryBorders,
+    /// The voxels explored while searching for the previewed path.
+    PathSearch,
+    /// A ring around each unit that made a decision during the latest tick.
+    ///
+    /// This is most useful while stepping through the simulation one tick at a time.
+    RecentThoughts,
+}
+
+/// The [`GizmoCategory`]s that are currently being drawn.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct DebugGizmos {
+    /// The enabled categories.
+    pub(crate) enabled: HashSet<GizmoCategory>,
+}
+
+impl DebugGizmos {
+    /// Steps to the next combination of categories.
+    ///
+    /// This moves from no categories, to each category on its own, to every category at once, and then back to none.
+    fn cycle(&mut self) {
+        let variants: Vec<GizmoCategory> = GizmoCategory::variants().collect();
+
+        let next = match self.enabled.len() {
+            0 => Some(variants[0]),
+            1 => {
+                let current = *self.enabled.iter().next().unwrap();
+                let index = variants
+                    .iter()
+                    .position(|&variant| variant == current)
+                    .unwrap();
+                match variants.get(index + 1) {
+                    Some(&next) => Some(next),
+                    // Turn everything on after the last category
+                    None => {
+                        self.enabled = variants.into_iter().collect();
+                        return;
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        self.enabled = next.into_iter().collect();
+    }
+}
+
+/// A run condition that is true when `category` is enabled.
+fn gizmo_enabled(category: GizmoCategory) -> impl Fn(Res<DebugGizmos>) -> bool {
+    move |debug_gizmos: Res<DebugGizmos>| debug_gizmos.enabled.contains(&category)
+}
+
+/// Steps through the gizmo categories when [`PlayerAction::CycleDebugGizmos`] is pressed.
+fn cycle_debug_gizmos(
+    actions: Res<ActionState<PlayerAction>>,
+    mut debug_gizmos: ResMut<DebugGizmos>,
+) {
+    if actions.just_pressed(PlayerAction::CycleDebugGizmos) {
+        debug_gizmos.cycle();
+        info!("Showing debug gizmos: {:?}", debug_gizmos.enabled);
+    }
+}
+
+/// The height above the ground at which gizmos are drawn, so they aren't hidden inside of objects.
+const GIZMO_LIFT: f32 = 0.5;
+
+/// Draws an arrow from `start` to `end`, with its head lying flat.
+fn arrow(gizmos: &mut Gizmos, start: Vec3, end: Vec3, color: Color) {
+    /// The length of each side of the arrowhead.
+    const HEAD_LENGTH: f32 = 0.2;
+
+    gizmos.line(start, end, color);
+
+    let Some(back) = (start - end).try_normalize() else {
+        return;
+    };
+
+    for angle in [-0.5, 0.5] {
+        let side = Quat::from_rotation_y(angle) * back * HEAD_LENGTH;
+        gizmos.line(end, end + side, color);
+    }
+}
+
+/// The world position used to draw gizmos for `voxel_pos`.
+fn gizmo_pos(voxel_pos: VoxelPos) -> Vec3 {
+    voxel_pos.into_world_pos() + Vec3::Y * GIZMO_LIFT
+}
+
+/// The color used to show units pursuing `goal`, based on the kind of signal that they follow.
+fn goal_color(goal: &Goal) -> Color {
+    match goal {
+        Goal::Wander { .. } | Goal::Breathe => NEUTRAL_INFOVIS_COLOR,
+        Goal::Remove(_) | Goal::Fetch(_) | Goal::Eat(_) => SignalKind::Push.color(),
+        Goal::Deliver(_) => SignalKind::Pull.color(),
+        Goal::Store(_) => SignalKind::Stores.color(),
+        Goal::Work(_) => SignalKind::Work.color(),
+        Goal::Demolish(_) => SignalKind::Demolish.color(),
+        Goal::Avoid(_) => SignalKind::Unit.color(),
+        Goal::Rest => SignalKind::Shelter.color(),
+    }
+}
+
+/// Draws an arrow from each unit to the object it is acting on, or in the direction it is facing.
+fn draw_unit_goals(
+    unit_query: Query<(&VoxelPos, &Goal, &CurrentAction, &Facing)>,
+    target_query: Query<&VoxelPos>,
+    mut gizmos: Gizmos,
+) {
+    for (&voxel_pos, goal, current_action, facing) in unit_query.iter() {
+        let start = gizmo_pos(voxel_pos);
+        let end = match current_action
+            .target_entity()
+            .and_then(|target| target_query.get(target).ok())
+        {
+            Some(&target_pos) => gizmo_pos(target_pos),
+            // Show which way the unit is heading, without suggesting that it knows where it is going
+            None => start.lerp(gizmo_pos(voxel_pos.neighbor(facing.direction)), 0.5),
+        };
+
+        arrow(&mut gizmos, start, end, goal_color(goal));
+    }
+}
+
+/// Draws the route of each hauling job, and links each hauler to the end it is heading to.
+fn draw_hauling_routes(
+    hauler_query: Query<(&VoxelPos, &HaulingJob, &UnitInventory)>,
+    mut gizmos: Gizmos,
+) {
+    for (&voxel_pos, hauling_job, unit_inventory) in hauler_query.iter() {
+        arrow(
+            &mut gizmos,
+            gizmo_pos(hauling_job.source_pos),
+            gizmo_pos(hauling_job.destination_pos),
+            HAULING_ROUTE_COLOR,
+        );
+
+        let (_, target_pos) = hauling_job.target(unit_inventory);
+        gizmos.line(
+            gizmo_pos(voxel_pos),
+            gizmo_pos(target_pos),
+            HAULING_ROUTE_COLOR.with_a(0.4),
+        );
+    }
+}
+
+/// Draws an arrow from each tile near the cursor towards the neighbor with the strongest signal of the same type.
+///
+/// Only the player's signals are shown, and only the strongest goal signal on each tile.
+fn draw_signal_gradients(
+    cursor_pos: Res<CursorPos>,
+    signal_channels: Res<SignalChannels>,
+    map_geometry: Res<MapGeometry>,
+    mut gizmos: Gizmos,
+) {
+    /// The distance in tiles around the cursor for which gradients are drawn.
+    const RADIUS: u32 = 6;
+
+    let Some(cursor_voxel) = cursor_pos.maybe_voxel_pos() else {
+        return;
+    };
+
+    let signals = signal_channels.player();
+    // Signals are sensed by units walking on top of the terrain
+    let walkable_voxel = |hex| {
+        map_geometry
+            .get_height(hex)
+            .ok()
+            .map(|height| VoxelPos { hex, height }.above())
+    };
+
+    for hex in spiral(cursor_voxel.hex, RADIUS) {
+        let Some(voxel_pos) = walkable_voxel(hex) else {
+            continue;
+        };
+        let Some((signal_type, strength)) = signals.strongest_goal_signal_at_position(voxel_pos)
+        else {
+            continue;
+        };
+
+        let strongest_neighbor = Direction::ALL_DIRECTIONS
+            .into_iter()
+            .filter_map(|direction| walkable_voxel(hex.neighbor(direction)))
+            .map(|neighbor| (neighbor, signals.get(signal_type, neighbor)))
+            .filter(|&(_, neighbor_strength)| neighbor_strength > strength)
+            .max_by(|(_, a), (_, b)| a.value().total_cmp(&b.value()));
+
+        if let Some((neighbor, _)) = strongest_neighbor {
+            let start = gizmo_pos(voxel_pos.below());
+            let end = start.lerp(gizmo_pos(neighbor.below()), 0.6);
+            arrow(
+                &mut gizmos,
+                start,
+                end,
+                SignalKind::from(signal_type).color(),
+            );
+        }
+    }
+}
+
+/// Outlines the edges of each faction's territory.
+fn draw_territory_borders(
+    territory: Res<Territory>,
+    map_geometry: Res<MapGeometry>,
+    mut gizmos: Gizmos,
+) {
+    for (hex, faction) in territory.claims() {
+        let Ok(height) = map_geometry.get_height(hex) else {
+            continue;
+        };
+        let y = VoxelPos { hex, height }.top_of_tile().y + 0.05;
+        let color = FACTION_COLORS[faction.0 as usize % FACTION_COLORS.len()].with_a(1.);
+
+        for direction in Direction::ALL_DIRECTIONS {
+            let neighbor = hex.neighbor(direction);
+            if territory.owner(neighbor) == Some(faction) {
+                continue;
+            }
+
+            // The shared edge is perpendicular to the line between the two centers, and as long as the hex's radius
+            let center = hex_to_xz(hex);
+            let offset = hex_to_xz(neighbor) - center;
+            let midpoint = center + offset / 2.;
+            let half_edge = offset.perp() / (2. * 3f32.sqrt());
+
+            let a = midpoint + half_edge;
+            let b = midpoint - half_edge;
+            gizmos.line(Vec3::new(a.x, y, a.y), Vec3::new(b.x, y, b.y), color);
+        }
+    }
+}
+
+/// Marks each voxel explored while searching for the previewed path, and traces the path itself.
+fn draw_path_search(path_preview: Res<PathPreview>, mut gizmos: Gizmos) {
+    /// The radius of the circle drawn on each explored voxel.
+    const EXPLORED_RADIUS: f32 = 0.15;
+
+    for &voxel_pos in path_preview.explored() {
+        gizmos.circle(
+            voxel_pos.into_world_pos() + Vec3::Y * 0.05,
+            Vec3::Y,
+            EXPLORED_RADIUS,
+            PATH_SEARCH_COLOR,
+        );
+    }
+
+    gizmos.linestrip(
+        path_preview.voxels().iter().copied().map(gizmo_pos),
+        PATH_PREVIEW_COLOR.with_a(1.),
+    );
+}
+
+/// Rings each unit that made a decision during the latest tick, colored by the goal it chose.
+fn draw_recent_thoughts(
+    unit_query: Query<(&VoxelPos, &Goal, &LastThought)>,
+    thinking_queue: Res<ThinkingQueue>,
+    mut gizmos: Gizmos,
+) {
+    /// The radius of the ring drawn around each unit.
+    const THOUGHT_RADIUS: f32 = 0.4;
+
+    for (&voxel_pos, goal, last_thought) in unit_query.iter() {
+        if last_thought.is_latest(&thinking_queue) {
+            gizmos.circle(
+                gizmo_pos(voxel_pos),
+                Vec3::Y,
+                THOUGHT_RADIUS,
+                goal_color(goal),
+            );
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Draws a shroud over explored tiles that are hidden by the [`FogOfWar`].
+//!
+//! Unexplored tiles are hidden outright by [`hide_other_layers`](super::layers::hide_other_layers).
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::Id,
+    fog_of_war::{FogOfWar, TileVisibility},
+    geometry::VoxelPos,
+    graphics::palette::environment::FOG_SHROUD,
+    terrain::{terrain_assets::TerrainHandles, terrain_manifest::Terrain},
+};
+
+use super::GraphicsSet;
+
+/// Spawns and updates the shrouds drawn over tiles hidden by the fog of war.
+pub(super) struct FogShroudPlugin;
+
+impl Plugin for FogShroudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FogShroudMaterial>().add_systems(
+            Update,
+            (spawn_fog_shrouds, apply_deferred, display_fog_shrouds)
+                .chain()
+                .run_if(resource_exists::<FogOfWar>())
+                .in_set(GraphicsSet),
+        );
+    }
+}
+
+/// Marks the child entity of each terrain tile used to dim it while it is out of sight.
+#[derive(Component, Debug)]
+struct FogShroud;
+
+/// The material shared by all [`FogShroud`]s.
+#[derive(Resource, Debug, Deref)]
+struct FogShroudMaterial(Handle<StandardMaterial>);
+
+impl FromWorld for FogShroudMaterial {
+    fn from_world(world: &mut World) -> Self {
+        let mut material_assets = world.resource_mut::<Assets<StandardMaterial>>();
+
+        FogShroudMaterial(material_assets.add(StandardMaterial {
+            base_color: FOG_SHROUD,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..Default::default()
+        }))
+    }
+}
+
+/// Adds a shroud to each new terrain tile.
+///
+/// The shroud is a child of the tile, so it follows the tile as its height changes.
+fn spawn_fog_shrouds(
+    new_terrain_query: Query<Entity, Added<Id<Terrain>>>,
+    terrain_handles: Res<TerrainHandles>,
+    shroud_material: Res<FogShroudMaterial>,
+    mut commands: Commands,
+) {
+    /// Controls how much larger the shroud is relative to the terrain tiles.
+    ///
+    /// This must be greater than 0 to avoid z-fighting.
+    const EPSILON: f32 = 0.02;
+
+    for terrain_entity in new_terrain_query.iter() {
+        let shroud_entity = commands
+            .spawn((
+                PbrBundle {
+                    mesh: terrain_handles.topper_mesh.clone_weak(),
+                    material: shroud_material.clone_weak(),
+                    transform: Transform::from_scale(Vec3::splat(1. + EPSILON)),
+                    visibility: Visibility::Hidden,
+                    ..Default::default()
+                },
+                FogShroud,
+            ))
+            .id();
+
+        commands.entity(terrain_entity).add_child(shroud_entity);
+    }
+}
+
+/// Shows the shroud of each tile that has been explored, but is not currently in view.
+fn display_fog_shrouds(
+    mut shroud_query: Query<(&Parent, &mut Visibility), With<FogShroud>>,
+    terrain_query: Query<&VoxelPos, With<Id<Terrain>>>,
+    fog_of_war: Res<FogOfWar>,
+) {
+    for (parent, mut visibility) in shroud_query.iter_mut() {
+        let Ok(terrain_pos) = terrain_query.get(parent.get()) else {
+            continue;
+        };
+
+        let desired_visibility = match fog_of_war.visibility(terrain_pos.hex) {
+            TileVisibility::Explored => Visibility::Inherited,
+            TileVisibility::Visible | TileVisibility::Unexplored => Visibility::Hidden,
+        };
+
+        visibility.set_if_neq(desired_visibility);
+    }
+}
@@ -0,0 +1,152 @@
+//! Brief visual pulses that acknowledge the player's actions.
+//!
+//! Selection changes and newly armed tools are already visible through tinting,
+//! so only the outcome of placements and invalid actions are shown here.
+//! Everything affected by a destructive action that is waiting to be confirmed is also marked.
+
+use bevy::prelude::*;
+
+use crate::{
+    construction::confirmation::PendingConfirmation,
+    geometry::{MapGeometry, VoxelPos},
+    player_interaction::{feedback::InteractionEvent, picking::CursorPos},
+};
+
+use super::{
+    palette::infovis::{ACTION_FAILED_COLOR, DESTRUCTIVE_PREVIEW_COLOR, PLACEMENT_SUCCEEDED_COLOR},
+    GraphicsSet,
+};
+
+/// Shows a pulse for each [`InteractionEvent`] that happens somewhere on the map.
+pub(super) struct InteractionFeedbackVfxPlugin;
+
+impl Plugin for InteractionFeedbackVfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FeedbackPulses>().add_systems(
+            Update,
+            (
+                (spawn_feedback_pulses, draw_feedback_pulses).chain(),
+                draw_pending_confirmation,
+            )
+                .in_set(GraphicsSet),
+        );
+    }
+}
+
+/// How long each pulse lasts, in seconds.
+const PULSE_DURATION: f32 = 0.4;
+
+/// The radius of each pulse at the start and end of its life, in world units.
+const PULSE_RADIUS: (f32, f32) = (0.3, 0.8);
+
+/// A single expanding ring drawn on top of a terrain voxel.
+#[derive(Debug, Clone)]
+struct FeedbackPulse {
+    /// The terrain voxel on which the pulse is drawn.
+    voxel_pos: VoxelPos,
+    /// The color of the ring.
+    color: Color,
+    /// The time remaining before the pulse disappears.
+    lifetime: Timer,
+}
+
+/// The pulses that are currently visible.
+#[derive(Resource, Debug, Default)]
+struct FeedbackPulses {
+    /// Each visible pulse.
+    pulses: Vec<FeedbackPulse>,
+}
+
+impl FeedbackPulses {
+    /// Starts a pulse at `voxel_pos`.
+    ///
+    /// Matching pulses that are already visible are restarted, so held inputs do not pile them up.
+    fn start(&mut self, voxel_pos: VoxelPos, color: Color) {
+        self.pulses
+            .retain(|pulse| pulse.voxel_pos != voxel_pos || pulse.color != color);
+
+        self.pulses.push(FeedbackPulse {
+            voxel_pos,
+            color,
+            lifetime: Timer::from_seconds(PULSE_DURATION, TimerMode::Once),
+        });
+    }
+}
+
+/// Starts a pulse for each [`InteractionEvent`] that happens somewhere on the map.
+fn spawn_feedback_pulses(
+    mut interaction_events: EventReader<InteractionEvent>,
+    cursor_pos: Res<CursorPos>,
+    mut feedback_pulses: ResMut<FeedbackPulses>,
+) {
+    for interaction_event in interaction_events.read() {
+        // Structures are placed on top of the terrain voxel
+        match *interaction_event {
+            InteractionEvent::PlacementSucceeded(voxel_pos) => {
+                feedback_pulses.start(voxel_pos.below(), PLACEMENT_SUCCEEDED_COLOR);
+            }
+            InteractionEvent::PlacementFailed(voxel_pos) => {
+                feedback_pulses.start(voxel_pos.below(), ACTION_FAILED_COLOR);
+            }
+            InteractionEvent::InvalidAction => {
+                if let Some(voxel_pos) = cursor_pos.maybe_voxel_pos() {
+                    feedback_pulses.start(voxel_pos, ACTION_FAILED_COLOR);
+                }
+            }
+            InteractionEvent::SelectionChanged | InteractionEvent::ToolArmed => (),
+        }
+    }
+}
+
+/// Draws each pulse as an expanding, fading ring, and removes those that have finished.
+fn draw_feedback_pulses(
+    mut feedback_pulses: ResMut<FeedbackPulses>,
+    time: Res<Time>,
+    mut gizmos: Gizmos,
+) {
+    let delta = time.delta();
+
+    feedback_pulses.pulses.retain_mut(|pulse| {
+        pulse.lifetime.tick(delta);
+        if pulse.lifetime.finished() {
+            return false;
+        }
+
+        let progress = pulse.lifetime.percent();
+        let radius = PULSE_RADIUS.0 + (PULSE_RADIUS.1 - PULSE_RADIUS.0) * progress;
+        let color = pulse.color.with_a(1. - progress);
+
+        gizmos.circle(
+            pulse.voxel_pos.top_of_tile() + Vec3::Y * 0.05,
+            Vec3::Y,
+            radius,
+            color,
+        );
+
+        true
+    });
+}
+
+/// Marks every tile affected by the destructive action that is waiting to be confirmed.
+fn draw_pending_confirmation(
+    pending_confirmation: Res<PendingConfirmation>,
+    map_geometry: Res<MapGeometry>,
+    mut gizmos: Gizmos,
+) {
+    let Some(plan) = pending_confirmation.plan() else {
+        return;
+    };
+
+    for hex in plan.affected_hexes() {
+        let Ok(height) = map_geometry.get_height(hex) else {
+            continue;
+        };
+
+        gizmos.circle(
+            VoxelPos { hex, height }.top_of_tile() + Vec3::Y * 0.05,
+            Vec3::Y,
+            PULSE_RADIUS.1,
+            DESTRUCTIVE_PREVIEW_COLOR,
+        );
+    }
+}
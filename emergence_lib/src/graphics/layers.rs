@@ -0,0 +1,81 @@
+//! Switches between rendering the surface and the underground.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::Id,
+    fog_of_war::{FogOfWar, TileVisibility},
+    geometry::{MapGeometry, MapLayer, VoxelPos},
+    player_interaction::PlayerAction,
+    terrain::terrain_manifest::Terrain,
+    units::unit_manifest::Unit,
+};
+
+/// The map layer that is currently being shown.
+///
+/// Objects on other layers are hidden.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct VisibleLayer(pub(crate) MapLayer);
+
+/// Swaps the [`VisibleLayer`] when the player asks for it.
+pub(super) fn toggle_visible_layer(
+    player_actions: Res<ActionState<PlayerAction>>,
+    mut visible_layer: ResMut<VisibleLayer>,
+) {
+    if player_actions.just_pressed(PlayerAction::ToggleUndergroundView) {
+        visible_layer.0 = visible_layer.0.toggle();
+    }
+}
+
+/// Hides every object that is not on the [`VisibleLayer`], or that is hidden by the [`FogOfWar`].
+///
+/// Terrain columns are shown on both layers.
+/// The voxel index only stores the top of each column, so while looking underground
+/// the columns are left standing as a cut-away, with everything on top of them stripped off.
+///
+/// Tiles that have been explored remain visible, along with any structures on them,
+/// but units are only shown while the player can see them.
+pub(super) fn hide_other_layers(
+    mut query: Query<(
+        Ref<VoxelPos>,
+        &mut Visibility,
+        Has<Id<Unit>>,
+        Has<Id<Terrain>>,
+    )>,
+    visible_layer: Res<VisibleLayer>,
+    map_geometry: Res<MapGeometry>,
+    maybe_fog_of_war: Option<Res<FogOfWar>>,
+) {
+    let fog_changed = maybe_fog_of_war
+        .as_ref()
+        .is_some_and(|fog_of_war| fog_of_war.is_changed());
+
+    for (voxel_pos, mut visibility, is_unit, is_terrain) in query.iter_mut() {
+        if !visible_layer.is_changed()
+            && !voxel_pos.is_changed()
+            && !map_geometry.is_changed()
+            && !fog_changed
+        {
+            continue;
+        }
+
+        let revealed = match maybe_fog_of_war.as_ref() {
+            Some(fog_of_war) => match fog_of_war.visibility(voxel_pos.hex) {
+                TileVisibility::Visible => true,
+                TileVisibility::Explored => !is_unit,
+                TileVisibility::Unexplored => false,
+            },
+            None => true,
+        };
+
+        let on_visible_layer = is_terrain || map_geometry.layer_of(*voxel_pos) == visible_layer.0;
+        let desired_visibility = if revealed && on_visible_layer {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        visibility.set_if_neq(desired_visibility);
+    }
+}
@@ -2,12 +2,35 @@
 
 use crate::{
     geometry::{MapGeometry, VoxelPos},
+    items::inventory::InventoryState,
+    litter::Litter,
+    terrain::terrain_assets::TerrainHandles,
     water::WaterDepth,
 };
 use bevy::prelude::*;
 
-/// Spawn and despawn litter scenes based on the items stored as litter on each tile.
-pub(super) fn render_litter_piles() {}
+/// Swaps the scene used for each litter pile based on how close it is to a full stack.
+pub(super) fn render_litter_piles(
+    mut query: Query<(&Litter, &mut Handle<Scene>), Changed<Litter>>,
+    terrain_handles: Res<TerrainHandles>,
+) {
+    for (litter, mut scene) in query.iter_mut() {
+        let inventory_state = match litter.contents.state() {
+            // Empty litter is about to be despawned
+            InventoryState::Empty => continue,
+            InventoryState::Partial => InventoryState::Partial,
+            InventoryState::Full => InventoryState::Full,
+        };
+
+        let Some(new_scene) = terrain_handles.litter_models.get(&inventory_state) else {
+            continue;
+        };
+
+        if *scene != *new_scene {
+            *scene = new_scene.clone_weak();
+        }
+    }
+}
 
 /// Computes the [`Transform`] for a floating litter entity.
 #[allow(dead_code)]
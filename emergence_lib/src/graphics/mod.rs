@@ -2,21 +2,48 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_management::AssetState, world_gen::WorldGenState};
+use crate::{
+    asset_management::AssetState, player_interaction::InteractionSystem, world_gen::WorldGenState,
+};
 
 use self::{
-    atmosphere::AtmospherePlugin, lighting::LightingPlugin, litter::render_litter_piles,
-    overlay::OverlayPlugin, structures::remove_ghostly_shadows, water::WaterRenderingPlugin,
+    atmosphere::AtmospherePlugin,
+    debug_gizmos::DebugGizmosPlugin,
+    fog::FogShroudPlugin,
+    interaction_feedback::InteractionFeedbackVfxPlugin,
+    layers::{hide_other_layers, toggle_visible_layer, VisibleLayer},
+    lighting::LightingPlugin,
+    litter::render_litter_piles,
+    models::{
+        index_structure_models, index_terrain_models, set_conditional_node_visibility,
+        style_model_materials,
+    },
+    organisms::shrink_dormant_organisms,
+    overlay::OverlayPlugin,
+    selection_highlights::{SelectionHighlight, SelectionHighlightPlugin},
+    structures::remove_ghostly_shadows,
+    variation::vary_models,
+    water::WaterRenderingPlugin,
+    wind::WindStreakPlugin,
 };
 
 mod atmosphere;
+mod debug_gizmos;
+mod fog;
+mod interaction_feedback;
+mod layers;
 pub(crate) mod lighting;
 mod litter;
+mod models;
+mod organisms;
 pub(crate) mod overlay;
 pub(crate) mod palette;
+mod selection_highlights;
 mod structures;
 mod units;
+mod variation;
 mod water;
+mod wind;
 
 /// Adds all logic required to render the game.
 ///
@@ -33,7 +60,30 @@ impl Plugin for GraphicsPlugin {
             .add_plugins(AtmospherePlugin)
             .add_plugins(WaterRenderingPlugin)
             .add_plugins(OverlayPlugin)
-            .add_systems(Update, render_litter_piles.in_set(GraphicsSet))
+            .add_plugins(SelectionHighlightPlugin)
+            .add_plugins(FogShroudPlugin)
+            .add_plugins(DebugGizmosPlugin)
+            .add_plugins(WindStreakPlugin)
+            .add_plugins(InteractionFeedbackVfxPlugin)
+            .init_resource::<VisibleLayer>()
+            .add_systems(
+                Update,
+                (
+                    render_litter_piles,
+                    shrink_dormant_organisms,
+                    (
+                        (index_structure_models, index_terrain_models),
+                        apply_deferred,
+                        vary_models,
+                        set_conditional_node_visibility,
+                        style_model_materials.after(InteractionSystem::SelectTiles),
+                    )
+                        .chain(),
+                    toggle_visible_layer,
+                    hide_other_layers.after(toggle_visible_layer),
+                )
+                    .in_set(GraphicsSet),
+            )
             // Run these after Update to avoid panics due to despawned entities
             .add_systems(PostUpdate, (inherit_materials, remove_ghostly_shadows))
             .configure_sets(
@@ -53,7 +103,8 @@ pub(crate) struct InheritedMaterial(pub(crate) Handle<StandardMaterial>);
 pub(super) fn inherit_materials(
     root_structure_query: Query<(Entity, &InheritedMaterial)>,
     children: Query<&Children>,
-    mut material_query: Query<&mut Handle<StandardMaterial>>,
+    // Selection highlights keep their own material, so that ghosts and previews can still be outlined
+    mut material_query: Query<&mut Handle<StandardMaterial>, Without<SelectionHighlight>>,
 ) {
     for (root_entity, inherited_material) in root_structure_query.iter() {
         for child in children.iter_descendants(root_entity) {
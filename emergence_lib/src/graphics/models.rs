@@ -0,0 +1,377 @@
+//! Applies the per-model variations described in the [`ModelManifest`] to spawned gltF scenes.
+//!
+//! Once the scene of a structure or terrain tile has spawned, its named nodes are indexed in a [`ModelNodes`] component.
+//! These nodes can then be shown or hidden as the object changes state,
+//! tinted with the color of the faction that owns the object,
+//! and have their materials swapped out while the object is hovered or selected.
+
+use bevy::{
+    prelude::*,
+    scene::{InstanceId, SceneInstance},
+    utils::{HashMap, HashSet},
+};
+use leafwing_abilities::prelude::Pool;
+
+use crate::{
+    asset_management::{
+        manifest::Id,
+        models::{Model, ModelData, ModelManifest, ModelState},
+    },
+    construction::ghosts::{Ghost, Preview},
+    factions::Faction,
+    graphics::palette::infovis::FACTION_COLORS,
+    organisms::{dormancy::Dormant, energy::EnergyPool, lifecycle::Lifecycle},
+    player_interaction::{bulk_commands::Disabled, selection::ObjectInteraction},
+    structures::structure_manifest::{Structure, StructureManifest},
+    terrain::terrain_manifest::{Terrain, TerrainManifest},
+};
+
+use super::selection_highlights::SelectionHighlight;
+
+/// The named nodes of a spawned model that are referred to by its [`ModelData`].
+///
+/// This is added to the root entity of each structure and terrain tile once its scene has spawned,
+/// and is empty for models that don't refer to any named nodes.
+/// If the scene is swapped out, such as when terrain is changed, the new scene is indexed in turn.
+#[derive(Component, Debug)]
+pub(super) struct ModelNodes {
+    /// The spawned scene that these nodes were found in.
+    instance: InstanceId,
+    /// The model that these nodes belong to, if it has an entry in the [`ModelManifest`].
+    model_id: Option<Id<Model>>,
+    /// The entity of each named node.
+    nodes: HashMap<String, Entity>,
+}
+
+impl ModelNodes {
+    /// A model without any named nodes.
+    fn empty(instance: InstanceId) -> Self {
+        ModelNodes {
+            instance,
+            model_id: None,
+            nodes: HashMap::default(),
+        }
+    }
+
+    /// Finds the named nodes of `model_data` among the descendants of `root_entity`.
+    fn index(
+        instance: InstanceId,
+        model_id: Id<Model>,
+        model_data: &ModelData,
+        root_entity: Entity,
+        children: &Query<&Children>,
+        name_query: &Query<&Name>,
+    ) -> Self {
+        let mut nodes = HashMap::default();
+        for descendant in children.iter_descendants(root_entity) {
+            let Ok(name) = name_query.get(descendant) else {
+                continue;
+            };
+
+            if model_data
+                .node_names()
+                .any(|node_name| node_name == name.as_str())
+            {
+                nodes.insert(name.to_string(), descendant);
+            }
+        }
+
+        for node_name in model_data.node_names() {
+            if !nodes.contains_key(node_name) {
+                warn!(
+                    "Node {node_name} was not found in the scene {}",
+                    model_data.scene
+                );
+            }
+        }
+
+        ModelNodes {
+            instance,
+            model_id: Some(model_id),
+            nodes,
+        }
+    }
+
+    /// The entities of the nodes with the given names that were found in the scene.
+    fn get<'a>(&'a self, names: &'a [String]) -> impl Iterator<Item = Entity> + 'a {
+        names
+            .iter()
+            .filter_map(|name| self.nodes.get(name).copied())
+    }
+}
+
+/// Indexes the named nodes of each structure's model, once its scene has spawned.
+///
+/// Ghosts and previews are drawn with a single inherited material, so their models are left alone.
+pub(super) fn index_structure_models(
+    root_query: Query<
+        (Entity, &Id<Structure>, &SceneInstance, Option<&ModelNodes>),
+        (Without<Ghost>, Without<Preview>),
+    >,
+    scene_spawner: Res<SceneSpawner>,
+    children: Query<&Children>,
+    name_query: Query<&Name>,
+    model_manifest: Res<ModelManifest>,
+    structure_manifest: Res<StructureManifest>,
+    mut commands: Commands,
+) {
+    for (root_entity, &structure_id, scene_instance, maybe_model_nodes) in root_query.iter() {
+        let instance = **scene_instance;
+        let already_indexed =
+            maybe_model_nodes.is_some_and(|model_nodes| model_nodes.instance == instance);
+        if already_indexed || !scene_spawner.instance_is_ready(instance) {
+            continue;
+        }
+
+        let name = structure_manifest.name(structure_id);
+        let model_nodes = match model_manifest.model("structures", name) {
+            Some(model_data) if model_data.has_named_nodes() => ModelNodes::index(
+                instance,
+                ModelManifest::model_id("structures", name),
+                model_data,
+                root_entity,
+                &children,
+                &name_query,
+            ),
+            _ => ModelNodes::empty(instance),
+        };
+
+        commands.entity(root_entity).insert(model_nodes);
+    }
+}
+
+/// Indexes the named nodes of each terrain tile's model, once its scene has spawned.
+pub(super) fn index_terrain_models(
+    root_query: Query<(Entity, &Id<Terrain>, &SceneInstance, Option<&ModelNodes>)>,
+    scene_spawner: Res<SceneSpawner>,
+    children: Query<&Children>,
+    name_query: Query<&Name>,
+    model_manifest: Res<ModelManifest>,
+    terrain_manifest: Res<TerrainManifest>,
+    mut commands: Commands,
+) {
+    for (root_entity, &terrain_id, scene_instance, maybe_model_nodes) in root_query.iter() {
+        let instance = **scene_instance;
+        let already_indexed =
+            maybe_model_nodes.is_some_and(|model_nodes| model_nodes.instance == instance);
+        if already_indexed || !scene_spawner.instance_is_ready(instance) {
+            continue;
+        }
+
+        let name = terrain_manifest.name(terrain_id);
+        let model_nodes = match model_manifest.model("terrain", name) {
+            Some(model_data) if model_data.has_named_nodes() => ModelNodes::index(
+                instance,
+                ModelManifest::model_id("terrain", name),
+                model_data,
+                root_entity,
+                &children,
+                &name_query,
+            ),
+            _ => ModelNodes::empty(instance),
+        };
+
+        commands.entity(root_entity).insert(model_nodes);
+    }
+}
+
+/// Shows and hides the conditional nodes of each model to match the state of the object.
+pub(super) fn set_conditional_node_visibility(
+    root_query: Query<(
+        &ModelNodes,
+        Has<Dormant>,
+        Has<Disabled>,
+        Option<&EnergyPool>,
+        Option<&Lifecycle>,
+    )>,
+    mut visibility_query: Query<&mut Visibility>,
+    model_manifest: Res<ModelManifest>,
+) {
+    for (model_nodes, is_dormant, is_disabled, maybe_energy_pool, maybe_lifecycle) in
+        root_query.iter()
+    {
+        let Some(model_id) = model_nodes.model_id else {
+            continue;
+        };
+
+        for (node_name, condition) in &model_manifest.get(model_id).conditional_nodes {
+            let Some(&node_entity) = model_nodes.nodes.get(node_name) else {
+                continue;
+            };
+
+            let in_state = match condition.state {
+                ModelState::Dormant => is_dormant,
+                ModelState::Disabled => is_disabled,
+                ModelState::Damaged => maybe_energy_pool
+                    .is_some_and(|energy_pool| energy_pool.current().0 < energy_pool.max().0 / 2.),
+                ModelState::Grown(fraction) => {
+                    maybe_lifecycle.is_some_and(|lifecycle| lifecycle.progress() >= fraction)
+                }
+            };
+
+            if let Ok(mut visibility) = visibility_query.get_mut(node_entity) {
+                visibility.set_if_neq(match condition.is_visible(in_state) {
+                    true => Visibility::Inherited,
+                    false => Visibility::Hidden,
+                });
+            }
+        }
+    }
+}
+
+/// The fraction of its usual brightness that a disabled structure is drawn with.
+const DISABLED_BRIGHTNESS: f32 = 0.4;
+
+/// The material that a mesh in a model was drawn with before it was restyled.
+///
+/// Every restyled material is derived from this, so tints, highlights and dimming never stack on top of each other.
+#[derive(Component, Debug)]
+pub(super) struct BaseMaterial(Handle<StandardMaterial>);
+
+/// The ways in which a mesh's [`BaseMaterial`] is restyled, based on the state of the model that it belongs to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+struct MaterialStyle {
+    /// The faction whose color the mesh is tinted with.
+    faction: Option<Faction>,
+    /// Whether the mesh is dimmed, because its structure is disabled.
+    dimmed: bool,
+    /// The hover and selection state that the mesh is highlighted with.
+    interaction: ObjectInteraction,
+}
+
+impl MaterialStyle {
+    /// Applies this style to the `base` material.
+    ///
+    /// Highlights take priority, so hovered and selected objects always stand out.
+    fn apply(&self, base: &StandardMaterial) -> StandardMaterial {
+        if let Some(highlight_material) = self.interaction.material() {
+            return highlight_material;
+        }
+
+        let mut material = base.clone();
+        if let Some(faction) = self.faction {
+            let faction_color = FACTION_COLORS[faction.0 as usize % FACTION_COLORS.len()];
+            material.base_color = faction_color.with_a(material.base_color.a());
+        }
+
+        if self.dimmed {
+            material.base_color = material.base_color * DISABLED_BRIGHTNESS;
+        }
+
+        material
+    }
+}
+
+/// Restyles the meshes of each model to match the state of the object.
+///
+/// - the meshes in tinted nodes are tinted with the color of the faction that owns the object
+/// - the meshes in highlighted nodes are swapped to the hover or selection material while the object is interacted with
+/// - every mesh of a disabled structure is darkened
+///
+/// Models without any highlighted nodes are only outlined by their [`SelectionHighlight`].
+/// Styled copies of each material are cached, so that objects sharing a material and style also share its styled copy.
+pub(super) fn style_model_materials(
+    root_query: Query<(
+        &ModelNodes,
+        Option<&Faction>,
+        Option<&ObjectInteraction>,
+        Has<Disabled>,
+    )>,
+    changed_query: Query<
+        Entity,
+        (
+            With<ModelNodes>,
+            Or<(
+                Changed<ModelNodes>,
+                Changed<Faction>,
+                Changed<ObjectInteraction>,
+                Changed<Disabled>,
+            )>,
+        ),
+    >,
+    mut removed_disabled: RemovedComponents<Disabled>,
+    children: Query<&Children>,
+    mut mesh_query: Query<
+        (Entity, &mut Handle<StandardMaterial>, Option<&BaseMaterial>),
+        Without<SelectionHighlight>,
+    >,
+    model_manifest: Res<ModelManifest>,
+    mut material_assets: ResMut<Assets<StandardMaterial>>,
+    mut styled_materials: Local<
+        HashMap<(AssetId<StandardMaterial>, MaterialStyle), Handle<StandardMaterial>>,
+    >,
+    mut commands: Commands,
+) {
+    let changed_roots: HashSet<Entity> = changed_query
+        .iter()
+        .chain(removed_disabled.read())
+        .collect();
+
+    for root_entity in changed_roots {
+        let Ok((model_nodes, maybe_faction, maybe_interaction, is_disabled)) =
+            root_query.get(root_entity)
+        else {
+            continue;
+        };
+
+        let mut tinted_meshes = HashSet::new();
+        let mut highlighted_meshes = HashSet::new();
+        if let Some(model_id) = model_nodes.model_id {
+            let model_data = model_manifest.get(model_id);
+            for (node_names, meshes) in [
+                (&model_data.tinted_nodes, &mut tinted_meshes),
+                (&model_data.highlighted_nodes, &mut highlighted_meshes),
+            ] {
+                for node_entity in model_nodes.get(node_names) {
+                    meshes.insert(node_entity);
+                    meshes.extend(children.iter_descendants(node_entity));
+                }
+            }
+        }
+
+        for mesh_entity in children.iter_descendants(root_entity) {
+            let Ok((mesh_entity, mut material, maybe_base)) = mesh_query.get_mut(mesh_entity)
+            else {
+                continue;
+            };
+
+            let style = MaterialStyle {
+                faction: maybe_faction
+                    .copied()
+                    .filter(|_| tinted_meshes.contains(&mesh_entity)),
+                dimmed: is_disabled,
+                interaction: maybe_interaction
+                    .filter(|_| highlighted_meshes.contains(&mesh_entity))
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+
+            let base = match maybe_base {
+                Some(base) => base.0.clone(),
+                // This mesh has never been restyled, and doesn't need to be
+                None if style == MaterialStyle::default() => continue,
+                None => {
+                    commands
+                        .entity(mesh_entity)
+                        .insert(BaseMaterial(material.clone()));
+                    material.clone()
+                }
+            };
+
+            if style == MaterialStyle::default() {
+                material.set_if_neq(base);
+                continue;
+            }
+
+            let styled = styled_materials
+                .entry((base.id(), style))
+                .or_insert_with_key(|(_, style)| {
+                    let base_material = material_assets.get(&base).cloned().unwrap_or_default();
+                    material_assets.add(style.apply(&base_material))
+                })
+                .clone();
+
+            material.set_if_neq(styled);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! Graphics and animation code that applies to all organisms.
+
+use bevy::prelude::*;
+
+use crate::organisms::{dormancy::Dormant, Organism};
+
+/// The scale applied to dormant organisms, so they visibly hunker down.
+const DORMANT_SCALE: f32 = 0.75;
+
+/// Shrinks dormant organisms, and restores them to their usual size once they wake up.
+pub(super) fn shrink_dormant_organisms(
+    mut organism_query: Query<(&mut Transform, Option<&Dormant>), With<Organism>>,
+) {
+    for (mut transform, maybe_dormant) in organism_query.iter_mut() {
+        let scale = match maybe_dormant {
+            Some(_) => Vec3::splat(DORMANT_SCALE),
+            None => Vec3::ONE,
+        };
+
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+    }
+}
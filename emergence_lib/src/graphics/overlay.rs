@@ -20,11 +20,18 @@ use emergence_macros::IterableEnum;
 use crate::{
     asset_management::manifest::Id,
     enum_iter::IterableEnum,
+    factions::{territory::Territory, Faction},
+    fog_of_war::FogOfWar,
     geometry::{Height, MapGeometry, VoxelPos},
-    graphics::palette::infovis::{WATER_TABLE_COLOR_HIGH, WATER_TABLE_COLOR_LOW},
-    player_interaction::{selection::ObjectInteraction, InteractionSystem},
-    signals::{SignalKind, SignalStrength, SignalType, Signals},
+    graphics::palette::infovis::{
+        FACTION_COLORS, PATH_PREVIEW_COLOR, TEMPERATURE_COLOR_COLD, TEMPERATURE_COLOR_HOT,
+        TRAFFIC_COLOR_HIGH, TRAFFIC_COLOR_LOW, WATER_TABLE_COLOR_HIGH, WATER_TABLE_COLOR_LOW,
+    },
+    player_interaction::path_preview::PathPreview,
+    signals::{SignalChannels, SignalKind, SignalStrength, SignalType},
+    temperature::Temperature,
     terrain::{terrain_assets::TerrainHandles, terrain_manifest::Terrain},
+    units::traffic::TrafficHeatmap,
     water::{PreviousWaterVolume, WaterDepth, WaterVolume},
 };
 
@@ -38,14 +45,7 @@ impl Plugin for OverlayPlugin {
         app.init_resource::<TileOverlay>()
             .add_systems(
                 Update,
-                (
-                    set_overlay_material,
-                    set_overlay_height,
-                    display_player_selection
-                        .after(InteractionSystem::SelectTiles)
-                        .after(set_overlay_material),
-                )
-                    .in_set(GraphicsSet),
+                (set_overlay_material, set_overlay_height).in_set(GraphicsSet),
             )
             .add_systems(
                 Update,
@@ -70,14 +70,26 @@ pub(crate) struct TileOverlay {
     light_level_color_ramp: HashMap<Illuminance, Handle<StandardMaterial>>,
     /// The materials used to visualize the net change in water volume.
     flux_color_ramp: Vec<Handle<StandardMaterial>>,
+    /// The materials used to visualize temperature.
+    temperature_color_ramp: Vec<Handle<StandardMaterial>>,
     /// The materials used to visualize vector fields.
     vector_field_materials: HashMap<DiscretizedVector, Handle<StandardMaterial>>,
+    /// The materials used to visualize unit traffic.
+    traffic_color_ramp: Vec<Handle<StandardMaterial>>,
+    /// The materials used to visualize the territory of each faction.
+    territory_materials: Vec<Handle<StandardMaterial>>,
+    /// The material used to highlight a previewed path.
+    path_preview_material: Handle<StandardMaterial>,
     /// The images to be used to display the gradient in order to create a legend.
     signal_legends: HashMap<SignalKind, Handle<Image>>,
     /// The image used to display the gradient for the water table.
     water_table_legend: Handle<Image>,
     /// The image used to display the gradient for the net change in water volume.
     flux_legend: Handle<Image>,
+    /// The image used to display the gradient for temperature.
+    temperature_legend: Handle<Image>,
+    /// The image used to display the gradient for unit traffic.
+    traffic_legend: Handle<Image>,
 }
 
 /// The type of information that is being visualized by the overlay.
@@ -100,6 +112,14 @@ pub(crate) enum OverlayType {
     NetWater,
     /// Shows the current light level of each tile.
     LightLevel,
+    /// Shows the current temperature of each tile.
+    Temperature,
+    /// Shows which faction controls each tile.
+    Territory,
+    /// Shows how many units have recently walked through each tile.
+    Traffic,
+    /// Highlights the path that the selected unit would take to reach the cursor.
+    PathPreview,
 }
 
 impl OverlayType {
@@ -166,6 +186,29 @@ impl FromWorld for TileOverlay {
         let mut image_assets = world.resource_mut::<Assets<Image>>();
         let flux_legend = image_assets.add(flux_legend_image);
 
+        // Temperature
+        let temperature_colors = generate_color_bigradient(
+            TEMPERATURE_COLOR_COLD,
+            TEMPERATURE_COLOR_HOT,
+            Self::N_COLORS,
+        );
+        let material_assets: &mut Assets<StandardMaterial> =
+            &mut world.resource_mut::<Assets<StandardMaterial>>();
+        let temperature_color_ramp = generate_color_ramp(&temperature_colors, material_assets);
+        let temperature_legend_image = generate_legend(&temperature_colors, Self::LEGEND_WIDTH);
+        let mut image_assets = world.resource_mut::<Assets<Image>>();
+        let temperature_legend = image_assets.add(temperature_legend_image);
+
+        // Traffic
+        let traffic_colors =
+            generate_color_gradient(TRAFFIC_COLOR_LOW, TRAFFIC_COLOR_HIGH, Self::N_COLORS);
+        let material_assets: &mut Assets<StandardMaterial> =
+            &mut world.resource_mut::<Assets<StandardMaterial>>();
+        let traffic_color_ramp = generate_color_ramp(&traffic_colors, material_assets);
+        let traffic_legend_image = generate_legend(&traffic_colors, Self::LEGEND_WIDTH);
+        let mut image_assets = world.resource_mut::<Assets<Image>>();
+        let traffic_legend = image_assets.add(traffic_legend_image);
+
         let material_assets: &mut Assets<StandardMaterial> =
             &mut world.resource_mut::<Assets<StandardMaterial>>();
 
@@ -187,16 +230,33 @@ impl FromWorld for TileOverlay {
             &mut world.resource_mut::<Assets<StandardMaterial>>();
         let vector_field_materials = generate_vector_field_materials(material_assets);
 
+        // Territory
+        let territory_materials = generate_color_ramp(&FACTION_COLORS.to_vec(), material_assets);
+
+        // Path preview
+        let path_preview_material = material_assets.add(StandardMaterial {
+            base_color: PATH_PREVIEW_COLOR,
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            ..Default::default()
+        });
+
         Self {
             overlay_type: OverlayType::None,
             signal_color_ramps: color_ramps,
             water_table_color_ramp,
             flux_color_ramp,
+            temperature_color_ramp,
             light_level_color_ramp,
             vector_field_materials,
+            traffic_color_ramp,
+            territory_materials,
+            path_preview_material,
             signal_legends: legends,
             water_table_legend,
             flux_legend,
+            temperature_legend,
+            traffic_legend,
         }
     }
 }
@@ -362,6 +422,21 @@ impl TileOverlay {
     /// Above this volume, the water flux is considered to be equally large.
     const MAX_FLUX: Volume = Volume(1e-2);
 
+    /// The coldest temperature to be displayed.
+    ///
+    /// Below this temperature, tiles are considered to be equally cold.
+    const MIN_TEMPERATURE: Temperature = Temperature(-10.);
+
+    /// The hottest temperature to be displayed.
+    ///
+    /// Above this temperature, tiles are considered to be equally hot.
+    const MAX_TEMPERATURE: Temperature = Temperature(40.);
+
+    /// The maximum displayed traffic.
+    ///
+    /// Above this level, tiles are considered to be equally busy.
+    const MAX_TRAFFIC: f32 = 100.;
+
     /// The width of the legend image.
     pub(crate) const LEGEND_WIDTH: u32 = 32;
 
@@ -429,6 +504,31 @@ impl TileOverlay {
         self.flux_color_ramp[color_index.min(Self::N_COLORS - 1)].clone_weak()
     }
 
+    /// Gets the material that should be used to visualize the provided `temperature`.
+    fn get_temperature_material(&self, temperature: Temperature) -> Handle<StandardMaterial> {
+        let normalized_temperature = (temperature.0 - Self::MIN_TEMPERATURE.0)
+            / (Self::MAX_TEMPERATURE.0 - Self::MIN_TEMPERATURE.0);
+        let clamped_temperature = normalized_temperature.clamp(0., 1.);
+
+        // Avoid indexing out of bounds by clamping to the maximum value in the case of extreme temperatures
+        let color_index: usize = (clamped_temperature * Self::N_COLORS as f32) as usize;
+        self.temperature_color_ramp[color_index.min(Self::N_COLORS - 1)].clone_weak()
+    }
+
+    /// Gets the material that should be used to visualize the provided `traffic`.
+    ///
+    /// If this is `None`, then no units have walked here recently.
+    fn get_traffic_material(&self, traffic: f32) -> Option<Handle<StandardMaterial>> {
+        if traffic < f32::EPSILON {
+            return None;
+        }
+
+        // Like signals, traffic is shown on a logarithmic scale so that faint trails are still visible
+        let normalized_traffic = traffic.ln_1p() / Self::MAX_TRAFFIC.ln_1p();
+        let color_index: usize = (normalized_traffic * Self::N_COLORS as f32) as usize;
+        Some(self.traffic_color_ramp[color_index.min(Self::N_COLORS - 1)].clone_weak())
+    }
+
     /// Gets the material that should be used to visualize the flow of water with the provided `flow_velocity`.
     pub(crate) fn get_flow_velocity_material(
         &self,
@@ -463,6 +563,12 @@ impl TileOverlay {
             .map(|material| material.clone_weak())
     }
 
+    /// Gets the material that should be used to visualize territory controlled by `faction`.
+    pub(crate) fn get_territory_material(&self, faction: Faction) -> Handle<StandardMaterial> {
+        let color_index = faction.0 as usize % self.territory_materials.len();
+        self.territory_materials[color_index].clone_weak()
+    }
+
     /// Gets the handle to the image that should be used to display the legend.
     pub(crate) fn signal_legend_image_handle(&self, signal_kind: SignalKind) -> Handle<Image> {
         self.signal_legends[&signal_kind].clone_weak()
@@ -477,6 +583,16 @@ impl TileOverlay {
     pub(crate) fn flux_legend_image_handle(&self) -> Handle<Image> {
         self.flux_legend.clone_weak()
     }
+
+    /// Gets the handle to the material that should be used to display the legend for temperature.
+    pub(crate) fn temperature_legend_image_handle(&self) -> Handle<Image> {
+        self.temperature_legend.clone_weak()
+    }
+
+    /// Gets the handle to the material that should be used to display the legend for unit traffic.
+    pub(crate) fn traffic_legend_image_handle(&self) -> Handle<Image> {
+        self.traffic_legend.clone_weak()
+    }
 }
 
 /// Sets the material for the currently visualized map overlay.
@@ -490,16 +606,30 @@ fn set_overlay_material(
     water_volume_query: Query<(&WaterVolume, &PreviousWaterVolume)>,
     terrain_pos_query: Query<&VoxelPos, With<Id<Terrain>>>,
     flow_velocity_query: Query<&FlowVelocity>,
-    signals: Res<Signals>,
+    temperature_query: Query<&Temperature>,
+    signal_channels: Res<SignalChannels>,
+    territory: Res<Territory>,
+    traffic_heatmap: Res<TrafficHeatmap>,
+    path_preview: Res<PathPreview>,
     map_geometry: Res<MapGeometry>,
     tile_overlay: Res<TileOverlay>,
+    maybe_fog_of_war: Option<Res<FogOfWar>>,
     time: Res<Time>,
 ) {
     if tile_overlay.overlay_type == OverlayType::None {
         return;
     }
 
+    // Only the player's own signals are shown
+    let signals = signal_channels.player();
+
     for (&voxel_pos, mut overlay_material, mut overlay_visibility) in overlay_query.iter_mut() {
+        // Don't reveal information about tiles hidden by the fog of war
+        if !FogOfWar::is_visible(maybe_fog_of_war.as_deref(), voxel_pos.hex) {
+            *overlay_visibility = Visibility::Hidden;
+            continue;
+        }
+
         let maybe_material = match tile_overlay.overlay_type {
             OverlayType::None => None,
             OverlayType::Single(signal_type) => {
@@ -557,6 +687,22 @@ fn set_overlay_material(
 
                 tile_overlay.get_light_level_material(received_light)
             }
+            OverlayType::Temperature => {
+                let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
+                let temperature = *temperature_query.get(terrain_entity).unwrap();
+
+                Some(tile_overlay.get_temperature_material(temperature))
+            }
+            OverlayType::Territory => territory
+                .owner(voxel_pos.hex)
+                .map(|faction| tile_overlay.get_territory_material(faction)),
+            // Like signals, traffic is recorded in the voxels above the terrain, where units walk
+            OverlayType::Traffic => {
+                tile_overlay.get_traffic_material(traffic_heatmap.get(voxel_pos.above()))
+            }
+            OverlayType::PathPreview => path_preview
+                .contains(voxel_pos.hex)
+                .then(|| tile_overlay.path_preview_material.clone_weak()),
         };
 
         match maybe_material {
@@ -571,41 +717,6 @@ fn set_overlay_material(
     }
 }
 
-/// Sets the overlay of the tile based on the player's selection.
-fn display_player_selection(
-    terrain_query: Query<&ObjectInteraction, With<Id<Terrain>>>,
-    mut overlay_query: Query<
-        (&VoxelPos, &mut Handle<StandardMaterial>, &mut Visibility),
-        With<Overlay>,
-    >,
-    terrain_handles: Res<TerrainHandles>,
-    tile_overlay: Res<TileOverlay>,
-    map_geometry: Res<MapGeometry>,
-) {
-    for (&voxel_pos, mut overlay_material, mut overlay_visibility) in overlay_query.iter_mut() {
-        let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
-        let object_interaction = terrain_query.get(terrain_entity).unwrap();
-
-        match object_interaction {
-            ObjectInteraction::None => {
-                if tile_overlay.overlay_type.is_none() {
-                    *overlay_visibility = Visibility::Hidden;
-                }
-            }
-            _ => {
-                *overlay_visibility = Visibility::Visible;
-                let new_material = terrain_handles
-                    .interaction_materials
-                    .get(object_interaction)
-                    .unwrap()
-                    .clone_weak();
-
-                *overlay_material = new_material;
-            }
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 /// A discretized 2D vector for visualization purposes.
 struct DiscretizedVector {
@@ -112,6 +112,14 @@ pub(crate) mod infovis {
                 SignalKind::Demolish => 0.,
                 // Blue
                 SignalKind::Unit => 220.,
+                // Indigo
+                SignalKind::Shelter => 260.,
+                // Gold
+                SignalKind::WorkNeeded => 45.,
+                // Cyan
+                SignalKind::Crowding => 195.,
+                // Pink
+                SignalKind::Custom => 330.,
             }
         }
 
@@ -149,6 +157,59 @@ pub(crate) mod infovis {
     /// The color used to indicate that water is near the surface.
     pub(crate) const WATER_TABLE_COLOR_LOW: Color = Color::hsla(195., 0.7, 0.2, OVERLAY_ALPHA);
 
+    /// The color used to indicate that a tile is cold.
+    pub(crate) const TEMPERATURE_COLOR_COLD: Color = Color::hsla(220., 0.8, 0.4, OVERLAY_ALPHA);
+    /// The color used to indicate that a tile is hot.
+    pub(crate) const TEMPERATURE_COLOR_HOT: Color = Color::hsla(10., 0.8, 0.5, OVERLAY_ALPHA);
+
+    /// The color used to indicate that few units have walked over a tile recently.
+    pub(crate) const TRAFFIC_COLOR_LOW: Color = Color::hsla(40., 0.3, 0.3, OVERLAY_ALPHA);
+    /// The color used to indicate that many units have walked over a tile recently.
+    pub(crate) const TRAFFIC_COLOR_HIGH: Color = Color::hsla(40., 0.9, 0.7, OVERLAY_ALPHA);
+
+    /// The color used to highlight the tiles along a previewed path.
+    pub(crate) const PATH_PREVIEW_COLOR: Color = Color::hsla(60., 0.9, 0.6, DISCRETE_OVERLAY_ALPHA);
+
+    /// The color of the voxels explored while searching for a previewed path, as drawn by debug gizmos.
+    pub(crate) const PATH_SEARCH_COLOR: Color = Color::hsla(60., 0.4, 0.4, 1.0);
+
+    /// The color of the routes between the ends of each hauling job, as drawn by debug gizmos.
+    pub(crate) const HAULING_ROUTE_COLOR: Color = Color::hsla(30., 0.9, 0.6, 1.0);
+
+    /// The color of the pulse shown where a structure was successfully placed.
+    pub(crate) const PLACEMENT_SUCCEEDED_COLOR: Color = Color::hsla(
+        SELECTION_HUE,
+        SELECTION_SATURATION,
+        SELECTION_LIGHTNESS,
+        1.0,
+    );
+
+    /// The color of the pulse shown where an action could not be carried out.
+    pub(crate) const ACTION_FAILED_COLOR: Color = Color::hsla(
+        FORBIDDEN_HUE,
+        SELECTION_SATURATION,
+        SELECTION_LIGHTNESS,
+        1.0,
+    );
+
+    /// The color of the rings marking everything that a pending destructive action will affect.
+    pub(crate) const DESTRUCTIVE_PREVIEW_COLOR: Color = Color::hsla(
+        FORBIDDEN_HUE,
+        SELECTION_SATURATION,
+        SELECTION_LIGHTNESS,
+        DISCRETE_OVERLAY_ALPHA,
+    );
+
+    /// The colors used to show the territory of each faction, starting with the player's.
+    ///
+    /// These are reused if there are more factions than colors.
+    pub(crate) const FACTION_COLORS: [Color; 4] = [
+        Color::hsla(200., 0.8, 0.5, DISCRETE_OVERLAY_ALPHA),
+        Color::hsla(0., 0.8, 0.5, DISCRETE_OVERLAY_ALPHA),
+        Color::hsla(45., 0.8, 0.5, DISCRETE_OVERLAY_ALPHA),
+        Color::hsla(290., 0.8, 0.5, DISCRETE_OVERLAY_ALPHA),
+    ];
+
     impl Illuminance {
         /// The color used to describe the illuminance of a tile.
         pub(crate) fn info_vis_color(&self) -> Color {
@@ -189,6 +250,22 @@ pub(crate) mod environment {
         lightness: 0.5,
         alpha: 0.5,
     };
+
+    /// The color of the streaks that show the wind.
+    pub(crate) const WIND_STREAK: Color = Color::Hsla {
+        hue: 0.,
+        saturation: 0.,
+        lightness: 0.95,
+        alpha: 0.35,
+    };
+
+    /// The shroud drawn over tiles that have been explored, but are hidden by the fog of war.
+    pub(crate) const FOG_SHROUD: Color = Color::Hsla {
+        hue: 230.,
+        saturation: 0.2,
+        lightness: 0.1,
+        alpha: 0.6,
+    };
 }
 
 /// Colors used for lighting
@@ -234,4 +311,20 @@ pub(crate) mod ui {
         lightness: 0.7,
         alpha: 1.0,
     };
+
+    /// The tint of the cursor icon when clicking will succeed.
+    pub(crate) const CURSOR_VALID_COLOR: Color = Color::Hsla {
+        hue: 100.,
+        saturation: 0.5,
+        lightness: 0.6,
+        alpha: 1.0,
+    };
+
+    /// The tint of the cursor icon when clicking will fail.
+    pub(crate) const CURSOR_INVALID_COLOR: Color = Color::Hsla {
+        hue: 0.,
+        saturation: 0.5,
+        lightness: 0.6,
+        alpha: 1.0,
+    };
 }
@@ -0,0 +1,157 @@
+//! Draws outlines around hovered and selected objects.
+//!
+//! Highlights are drawn as a separate mesh that is a child of the object being interacted with,
+//! rather than by swapping out the object's own material.
+//! This keeps them independent of the terrain overlay and works for any object with an [`ObjectInteraction`].
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    enum_iter::IterableEnum,
+    geometry::{hexagonal_outline, Height},
+    player_interaction::{selection::ObjectInteraction, InteractionSystem},
+    water::WaterDepth,
+};
+
+use super::GraphicsSet;
+
+/// Spawns and updates the outlines drawn around hovered and selected objects.
+pub(super) struct SelectionHighlightPlugin;
+
+impl Plugin for SelectionHighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionHighlightHandles>()
+            .add_systems(
+                Update,
+                (
+                    spawn_selection_highlights,
+                    display_selection_highlights,
+                    set_terrain_highlight_height,
+                )
+                    .chain()
+                    .after(InteractionSystem::SelectTiles)
+                    .in_set(GraphicsSet),
+            );
+    }
+}
+
+/// Marks the child entity used to draw the outline of an object with an [`ObjectInteraction`].
+#[derive(Component, Debug)]
+pub(crate) struct SelectionHighlight;
+
+/// The assets used to draw [`SelectionHighlight`]s.
+#[derive(Resource, Debug)]
+struct SelectionHighlightHandles {
+    /// The hexagonal ring drawn around each highlighted object.
+    outline_mesh: Handle<Mesh>,
+    /// The material used for each kind of interaction.
+    ///
+    /// [`ObjectInteraction::None`] has no entry, as these outlines are hidden.
+    materials: HashMap<ObjectInteraction, Handle<StandardMaterial>>,
+}
+
+impl FromWorld for SelectionHighlightHandles {
+    fn from_world(world: &mut World) -> Self {
+        /// The fraction of the tile's radius that is left empty in the middle of the outline.
+        const INNER_SCALE: f32 = 0.85;
+
+        let mut mesh_assets = world.resource_mut::<Assets<Mesh>>();
+        let outline_mesh = mesh_assets.add(hexagonal_outline(INNER_SCALE));
+
+        let mut material_assets = world.resource_mut::<Assets<StandardMaterial>>();
+        let mut materials = HashMap::new();
+        for variant in ObjectInteraction::variants() {
+            if let Some(material) = variant.material() {
+                let material_handle = material_assets.add(StandardMaterial {
+                    unlit: true,
+                    double_sided: true,
+                    cull_mode: None,
+                    ..material
+                });
+                materials.insert(variant, material_handle);
+            }
+        }
+
+        SelectionHighlightHandles {
+            outline_mesh,
+            materials,
+        }
+    }
+}
+
+/// How far above the surface of an object its outline is drawn, to avoid z-fighting.
+const HIGHLIGHT_EPSILON: f32 = 0.01;
+
+/// Adds a hidden [`SelectionHighlight`] to each new object that can be interacted with.
+fn spawn_selection_highlights(
+    new_objects: Query<Entity, Added<ObjectInteraction>>,
+    handles: Res<SelectionHighlightHandles>,
+    mut commands: Commands,
+) {
+    for entity in new_objects.iter() {
+        let highlight = commands
+            .spawn((
+                SelectionHighlight,
+                PbrBundle {
+                    mesh: handles.outline_mesh.clone_weak(),
+                    // The material is set in `display_selection_highlights`.
+                    material: Handle::default(),
+                    transform: Transform::from_xyz(0., HIGHLIGHT_EPSILON, 0.),
+                    visibility: Visibility::Hidden,
+                    ..Default::default()
+                },
+            ))
+            .id();
+
+        commands.entity(entity).add_child(highlight);
+    }
+}
+
+/// Shows, hides and recolors each [`SelectionHighlight`] to match its parent's [`ObjectInteraction`].
+fn display_selection_highlights(
+    object_query: Query<(&ObjectInteraction, &Children), Changed<ObjectInteraction>>,
+    mut highlight_query: Query<
+        (&mut Handle<StandardMaterial>, &mut Visibility),
+        With<SelectionHighlight>,
+    >,
+    handles: Res<SelectionHighlightHandles>,
+) {
+    for (object_interaction, children) in object_query.iter() {
+        for &child in children.iter() {
+            let Ok((mut material, mut visibility)) = highlight_query.get_mut(child) else {
+                continue;
+            };
+
+            match handles.materials.get(object_interaction) {
+                Some(new_material) => {
+                    *material = new_material.clone_weak();
+                    *visibility = Visibility::Visible;
+                }
+                None => *visibility = Visibility::Hidden,
+            }
+        }
+    }
+}
+
+/// Raises the [`SelectionHighlight`] of each terrain tile to sit on top of its topper, or on the water's surface when flooded.
+///
+/// Other objects are outlined at their base.
+fn set_terrain_highlight_height(
+    mut highlight_query: Query<(&Parent, &mut Transform), With<SelectionHighlight>>,
+    terrain_query: Query<&WaterDepth>,
+) {
+    for (parent, mut transform) in highlight_query.iter_mut() {
+        let Ok(water_depth) = terrain_query.get(parent.get()) else {
+            continue;
+        };
+
+        let surface_height = match water_depth {
+            WaterDepth::Dry | WaterDepth::Underground(_) => Height::TOPPER_THICKNESS,
+            WaterDepth::Flooded(surface_water_depth) => surface_water_depth
+                .into_world_pos()
+                .max(Height::TOPPER_THICKNESS),
+        };
+
+        transform.translation.y = surface_height + HIGHLIGHT_EPSILON;
+    }
+}
@@ -0,0 +1,157 @@
+//! Small, deterministic variations in how terrain and plants are drawn, so that fields of identical objects don't look tiled.
+//!
+//! Each variation is derived from a hash of the tile that the object sits on,
+//! so the same map always looks the same, without drawing on any of the random number generators used by the simulation.
+//! Variations are applied to the spawned scene, rather than the root entity,
+//! so the transforms used for simulation and picking are untouched.
+
+use bevy::{prelude::*, scene::SceneInstance, utils::HashMap};
+use hexx::Hex;
+
+use crate::{
+    asset_management::manifest::Id, geometry::VoxelPos, organisms::Organism,
+    terrain::terrain_manifest::Terrain,
+};
+
+use super::{models::ModelNodes, selection_highlights::SelectionHighlight};
+
+/// The largest angle, in radians, that a plant can be turned away from its facing.
+const MAX_PLANT_YAW: f32 = std::f32::consts::PI / 9.;
+
+/// The largest fraction by which a plant can be larger or smaller than usual.
+const MAX_PLANT_SCALE_JITTER: f32 = 0.1;
+
+/// The number of palette shifts in each direction, towards warmer or cooler colors.
+///
+/// Shifts are bucketed so that only a handful of extra materials are created.
+const PALETTE_STEPS: i8 = 2;
+
+/// The change in warmth made by each palette step.
+const PALETTE_STEP_SIZE: f32 = 0.04;
+
+/// The way that the object on a particular tile is drawn differently from its neighbors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Variation {
+    /// The rotation around the vertical axis, in radians.
+    yaw: f32,
+    /// The uniform scale factor.
+    scale: f32,
+    /// The number of palette steps towards warmer (positive) or cooler (negative) colors.
+    palette_shift: i8,
+}
+
+impl Variation {
+    /// The variation of the terrain at `hex`.
+    ///
+    /// Terrain must still tile seamlessly, so it is only turned in 60 degree steps and is never scaled.
+    fn terrain(hex: Hex) -> Self {
+        let turns = (tile_hash(hex, 0) % 6) as f32;
+
+        Variation {
+            yaw: turns * std::f32::consts::FRAC_PI_3,
+            scale: 1.,
+            palette_shift: Self::palette_shift(hex),
+        }
+    }
+
+    /// The variation of the plant at `hex`.
+    fn plant(hex: Hex) -> Self {
+        Variation {
+            yaw: (unit_interval(hex, 1) * 2. - 1.) * MAX_PLANT_YAW,
+            scale: 1. + (unit_interval(hex, 2) * 2. - 1.) * MAX_PLANT_SCALE_JITTER,
+            palette_shift: Self::palette_shift(hex),
+        }
+    }
+
+    /// The palette shift of any object at `hex`.
+    fn palette_shift(hex: Hex) -> i8 {
+        let n_buckets = (2 * PALETTE_STEPS + 1) as u32;
+        (tile_hash(hex, 3) % n_buckets) as i8 - PALETTE_STEPS
+    }
+
+    /// Shifts `color` towards warmer or cooler tones.
+    fn shift_color(&self, color: Color) -> Color {
+        let warmth = self.palette_shift as f32 * PALETTE_STEP_SIZE;
+        let [r, g, b, a] = color.as_rgba_f32();
+
+        Color::rgba(r * (1. + warmth), g, b * (1. - warmth), a)
+    }
+}
+
+/// A well-mixed hash of the tile position and `salt`.
+///
+/// Different salts are used for each property, so that they don't vary in lockstep.
+fn tile_hash(hex: Hex, salt: u32) -> u32 {
+    let mut hash = (hex.x as u32).wrapping_mul(0x9e37_79b1)
+        ^ (hex.y as u32).wrapping_mul(0x85eb_ca77)
+        ^ salt.wrapping_mul(0xc2b2_ae3d);
+
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7feb_352d);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846c_a68b);
+    hash ^= hash >> 16;
+    hash
+}
+
+/// A value between 0 and 1 derived from the tile position and `salt`.
+fn unit_interval(hex: Hex, salt: u32) -> f32 {
+    tile_hash(hex, salt) as f32 / u32::MAX as f32
+}
+
+/// Varies the scenes of terrain and plants once they have spawned.
+///
+/// [`ModelNodes`] is replaced whenever a new scene has spawned, so each scene is only varied once.
+pub(super) fn vary_models(
+    root_query: Query<
+        (Entity, &VoxelPos, &SceneInstance, Has<Id<Terrain>>),
+        (Changed<ModelNodes>, Or<(With<Id<Terrain>>, With<Organism>)>),
+    >,
+    scene_spawner: Res<SceneSpawner>,
+    parent_query: Query<&Parent>,
+    mut transform_query: Query<&mut Transform>,
+    mut mesh_query: Query<&mut Handle<StandardMaterial>, Without<SelectionHighlight>>,
+    mut material_assets: ResMut<Assets<StandardMaterial>>,
+    mut shifted_materials: Local<
+        HashMap<(AssetId<StandardMaterial>, i8), Handle<StandardMaterial>>,
+    >,
+) {
+    for (root_entity, voxel_pos, scene_instance, is_terrain) in root_query.iter() {
+        let variation = match is_terrain {
+            true => Variation::terrain(voxel_pos.hex),
+            false => Variation::plant(voxel_pos.hex),
+        };
+
+        for entity in scene_spawner.iter_instance_entities(**scene_instance) {
+            // Only the top-level nodes of the scene are transformed, as their children follow along
+            let is_top_level = parent_query
+                .get(entity)
+                .is_ok_and(|parent| parent.get() == root_entity);
+            if is_top_level {
+                if let Ok(mut transform) = transform_query.get_mut(entity) {
+                    transform.rotation = Quat::from_rotation_y(variation.yaw) * transform.rotation;
+                    transform.scale *= variation.scale;
+                }
+            }
+
+            if variation.palette_shift == 0 {
+                continue;
+            }
+
+            if let Ok(mut material) = mesh_query.get_mut(entity) {
+                let shifted = shifted_materials
+                    .entry((material.id(), variation.palette_shift))
+                    .or_insert_with(|| {
+                        let mut shifted_material =
+                            material_assets.get(&*material).cloned().unwrap_or_default();
+                        shifted_material.base_color =
+                            variation.shift_color(shifted_material.base_color);
+                        material_assets.add(shifted_material)
+                    })
+                    .clone();
+
+                *material = shifted;
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Streaks that drift across the map to show the direction and strength of the wind.
+
+use bevy::prelude::*;
+use rand::{seq::IteratorRandom, Rng};
+
+use crate::{
+    geometry::{MapGeometry, VoxelPos},
+    simulation::weather::Wind,
+};
+
+use super::{palette::environment::WIND_STREAK, GraphicsSet};
+
+/// A plugin that displays the wind.
+pub(super) struct WindStreakPlugin;
+
+impl Plugin for WindStreakPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, init_wind_streak_handles)
+            .add_systems(
+                Update,
+                (spawn_wind_streaks, move_wind_streaks).in_set(GraphicsSet),
+            );
+    }
+}
+
+/// Stores handles used for wind streak rendering.
+#[derive(Resource)]
+struct WindStreakHandles {
+    /// The handle for the wind streak material.
+    material: Handle<StandardMaterial>,
+    /// The handle for the wind streak mesh.
+    mesh: Handle<Mesh>,
+}
+
+/// Initializes handles used for wind streak rendering.
+fn init_wind_streak_handles(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color: WIND_STREAK,
+        unlit: true,
+        alpha_mode: AlphaMode::Blend,
+        ..Default::default()
+    });
+    // Streaks are long and thin, pointing along the x axis
+    let mesh = meshes.add(Mesh::from(shape::Box::new(0.8, 0.02, 0.02)));
+
+    commands.insert_resource(WindStreakHandles { material, mesh });
+}
+
+/// A single streak of wind, which drifts downwind until its lifetime runs out.
+#[derive(Component)]
+struct WindStreak {
+    /// The time remaining before this streak disappears.
+    lifetime: Timer,
+}
+
+/// The number of streaks shown at once in the strongest winds.
+const MAX_WIND_STREAKS: usize = 200;
+
+/// The speed at which streaks move in the strongest winds, in world units per second.
+const MAX_STREAK_SPEED: f32 = 4.;
+
+/// Spawns new wind streaks at random positions, so that their number matches the strength of the wind.
+fn spawn_wind_streaks(
+    streak_query: Query<(), With<WindStreak>>,
+    wind: Res<Wind>,
+    handles: Res<WindStreakHandles>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    let desired_streaks = (MAX_WIND_STREAKS as f32 * wind.speed()) as usize;
+    let n_streaks = streak_query.iter().len();
+    if n_streaks >= desired_streaks {
+        return;
+    }
+
+    let rng = &mut rand::thread_rng();
+    // Aligns the long axis of the streak with the wind
+    let rotation = Quat::from_rotation_y(-wind.direction());
+
+    for _ in n_streaks..desired_streaks {
+        let Some(&hex) = map_geometry.all_hexes().choose(rng) else {
+            return;
+        };
+        let Ok(height) = map_geometry.get_height(hex) else {
+            continue;
+        };
+
+        let translation =
+            VoxelPos { hex, height }.top_of_tile() + Vec3::Y * rng.gen_range(0.5..2.0);
+
+        commands.spawn((
+            WindStreak {
+                lifetime: Timer::from_seconds(rng.gen_range(1.0..3.0), TimerMode::Once),
+            },
+            PbrBundle {
+                mesh: handles.mesh.clone_weak(),
+                material: handles.material.clone_weak(),
+                transform: Transform {
+                    translation,
+                    rotation,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ));
+    }
+}
+
+/// Carries wind streaks along with the wind, despawning them once they expire.
+fn move_wind_streaks(
+    mut streak_query: Query<(Entity, &mut WindStreak, &mut Transform)>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let velocity = wind.velocity() * MAX_STREAK_SPEED;
+    let displacement = Vec3::new(velocity.x, 0., velocity.y) * time.delta_seconds();
+
+    for (entity, mut wind_streak, mut transform) in streak_query.iter_mut() {
+        wind_streak.lifetime.tick(time.delta());
+
+        if wind_streak.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            transform.translation += displacement;
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Fluids and other bulk resources, which are measured in continuous amounts rather than counted.
+//!
+//! Bulk resources share their identity with items: each is an [`Item`] flagged as a fluid in the item manifest.
+//! However, they are never held in an [`Inventory`](super::inventory::Inventory) or carried by units.
+//! Instead, they are stored in the [`Tanks`] of structures, and recipes consume and produce them as [`BulkQuantity`]s.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asset_management::manifest::Id;
+
+use super::{
+    errors::DrainBulkError,
+    item_manifest::{Item, ItemManifest},
+};
+
+/// A continuous amount of a bulk resource.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BulkQuantity {
+    /// The resource being measured.
+    pub item_id: Id<Item>,
+    /// How much of the resource there is.
+    pub amount: f32,
+}
+
+impl BulkQuantity {
+    /// Creates a new [`BulkQuantity`] of `amount` of `item_id`.
+    pub fn new(item_id: Id<Item>, amount: f32) -> Self {
+        Self { item_id, amount }
+    }
+
+    /// The pretty formatting for this type.
+    pub fn display(&self, item_manifest: &ItemManifest) -> String {
+        format!("{} ({:.1})", item_manifest.name(self.item_id), self.amount)
+    }
+}
+
+/// A container for a single bulk resource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tank {
+    /// The resource stored in this tank.
+    item_id: Id<Item>,
+    /// How much is currently stored.
+    amount: f32,
+    /// How much can be stored.
+    capacity: f32,
+}
+
+impl Tank {
+    /// Creates an empty tank that can store up to `capacity` of `item_id`.
+    pub fn new(item_id: Id<Item>, capacity: f32) -> Self {
+        Self {
+            item_id,
+            amount: 0.,
+            capacity: capacity.max(0.),
+        }
+    }
+
+    /// The resource stored in this tank.
+    pub fn item_id(&self) -> Id<Item> {
+        self.item_id
+    }
+
+    /// How much is currently stored.
+    pub fn amount(&self) -> f32 {
+        self.amount
+    }
+
+    /// How much can be stored.
+    pub fn capacity(&self) -> f32 {
+        self.capacity
+    }
+
+    /// How much more can be stored.
+    pub fn remaining_space(&self) -> f32 {
+        self.capacity - self.amount
+    }
+
+    /// How full this tank is, between 0 and 1.
+    pub fn fraction_full(&self) -> f32 {
+        match self.capacity > 0. {
+            true => self.amount / self.capacity,
+            false => 1.,
+        }
+    }
+
+    /// Adds up to `amount` to this tank, returning the amount that did not fit.
+    pub fn fill(&mut self, amount: f32) -> f32 {
+        let added = amount.max(0.).min(self.remaining_space());
+        self.amount += added;
+        amount.max(0.) - added
+    }
+
+    /// Removes exactly `amount` from this tank.
+    ///
+    /// If not enough is stored, nothing is removed and `Err` is returned.
+    pub fn drain(&mut self, amount: f32) -> Result<(), DrainBulkError> {
+        if self.amount < amount {
+            return Err(DrainBulkError {
+                missing: BulkQuantity::new(self.item_id, amount - self.amount),
+            });
+        }
+
+        self.amount -= amount;
+        Ok(())
+    }
+
+    /// The pretty formatting for this type.
+    pub fn display(&self, item_manifest: &ItemManifest) -> String {
+        format!(
+            "{}: {:.1}/{:.1}",
+            item_manifest.name(self.item_id),
+            self.amount,
+            self.capacity
+        )
+    }
+}
+
+/// The tanks of a structure, which store the bulk resources used and made by its recipe.
+#[derive(Component, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tanks {
+    /// The tanks, at most one per resource.
+    tanks: Vec<Tank>,
+}
+
+impl Tanks {
+    /// Creates a new set of tanks.
+    ///
+    /// Tanks for a resource that already has one are merged into it.
+    pub fn new(tanks: impl IntoIterator<Item = Tank>) -> Self {
+        let mut merged: Vec<Tank> = Vec::new();
+        for tank in tanks {
+            match merged
+                .iter_mut()
+                .find(|existing| existing.item_id == tank.item_id)
+            {
+                Some(existing) => {
+                    existing.capacity += tank.capacity;
+                    existing.amount += tank.amount;
+                }
+                None => merged.push(tank),
+            }
+        }
+
+        Self { tanks: merged }
+    }
+
+    /// Are there no tanks at all?
+    pub fn is_empty(&self) -> bool {
+        self.tanks.is_empty()
+    }
+
+    /// Returns an iterator over the tanks.
+    pub fn iter(&self) -> impl Iterator<Item = &Tank> {
+        self.tanks.iter()
+    }
+
+    /// The tank for `item_id`, if there is one.
+    pub fn get(&self, item_id: Id<Item>) -> Option<&Tank> {
+        self.tanks.iter().find(|tank| tank.item_id == item_id)
+    }
+
+    /// The tank for `item_id`, if there is one.
+    pub fn get_mut(&mut self, item_id: Id<Item>) -> Option<&mut Tank> {
+        self.tanks.iter_mut().find(|tank| tank.item_id == item_id)
+    }
+
+    /// How much of `item_id` is stored.
+    pub fn amount(&self, item_id: Id<Item>) -> f32 {
+        self.get(item_id).map_or(0., Tank::amount)
+    }
+
+    /// How much more of `item_id` can be stored.
+    pub fn remaining_space(&self, item_id: Id<Item>) -> f32 {
+        self.get(item_id).map_or(0., Tank::remaining_space)
+    }
+
+    /// The first of `quantities` that is not fully stored here, and how much of it is missing.
+    pub fn missing(&self, quantities: &[BulkQuantity]) -> Option<BulkQuantity> {
+        quantities
+            .iter()
+            .find(|quantity| self.amount(quantity.item_id) < quantity.amount)
+            .map(|quantity| {
+                BulkQuantity::new(
+                    quantity.item_id,
+                    quantity.amount - self.amount(quantity.item_id),
+                )
+            })
+    }
+
+    /// Removes all of `quantities` from these tanks, or nothing at all.
+    pub fn drain_all(&mut self, quantities: &[BulkQuantity]) -> Result<(), DrainBulkError> {
+        if let Some(missing) = self.missing(quantities) {
+            return Err(DrainBulkError { missing });
+        }
+
+        for quantity in quantities {
+            // The tank must exist and hold enough, since nothing is missing
+            self.get_mut(quantity.item_id)
+                .unwrap()
+                .drain(quantity.amount)
+                .unwrap();
+        }
+
+        Ok(())
+    }
+
+    /// Adds as much of `quantities` to these tanks as will fit, returning whatever did not.
+    pub fn fill_all(&mut self, quantities: &[BulkQuantity]) -> Vec<BulkQuantity> {
+        quantities
+            .iter()
+            .filter_map(|quantity| {
+                let overflow = match self.get_mut(quantity.item_id) {
+                    Some(tank) => tank.fill(quantity.amount),
+                    None => quantity.amount,
+                };
+
+                (overflow > 0.).then(|| BulkQuantity::new(quantity.item_id, overflow))
+            })
+            .collect()
+    }
+
+    /// Replaces these tanks with `new`, keeping as much of each stored resource as still fits.
+    pub fn replace_with(&mut self, mut new: Tanks) {
+        for tank in new.tanks.iter_mut() {
+            tank.fill(self.amount(tank.item_id));
+        }
+
+        *self = new;
+    }
+
+    /// The pretty formatting for this type.
+    pub fn display(&self, item_manifest: &ItemManifest) -> String {
+        let tank_strings: Vec<String> = self
+            .tanks
+            .iter()
+            .map(|tank| tank.display(item_manifest))
+            .collect();
+
+        format!("[{}]", tank_strings.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn water() -> Id<Item> {
+        Id::from_name("water".to_string())
+    }
+
+    fn nectar() -> Id<Item> {
+        Id::from_name("nectar".to_string())
+    }
+
+    #[test]
+    fn tanks_overflow_when_full() {
+        let mut tank = Tank::new(water(), 5.);
+
+        assert_eq!(tank.fill(3.5), 0.);
+        assert_eq!(tank.fill(3.5), 2.);
+        assert_eq!(tank.fraction_full(), 1.);
+    }
+
+    #[test]
+    fn draining_is_all_or_nothing() {
+        let mut tanks = Tanks::new([Tank::new(water(), 10.), Tank::new(nectar(), 10.)]);
+        tanks.fill_all(&[
+            BulkQuantity::new(water(), 4.),
+            BulkQuantity::new(nectar(), 1.),
+        ]);
+
+        let result = tanks.drain_all(&[
+            BulkQuantity::new(water(), 2.),
+            BulkQuantity::new(nectar(), 1.5),
+        ]);
+
+        assert_eq!(
+            result,
+            Err(DrainBulkError {
+                missing: BulkQuantity::new(nectar(), 0.5)
+            })
+        );
+        assert_eq!(tanks.amount(water()), 4.);
+        assert_eq!(tanks.amount(nectar()), 1.);
+    }
+
+    #[test]
+    fn resources_without_a_tank_are_spilled() {
+        let mut tanks = Tanks::new([Tank::new(water(), 10.)]);
+
+        let overflow = tanks.fill_all(&[BulkQuantity::new(nectar(), 2.)]);
+
+        assert_eq!(overflow, vec![BulkQuantity::new(nectar(), 2.)]);
+        assert_eq!(tanks.remaining_space(nectar()), 0.);
+    }
+
+    #[test]
+    fn replacing_tanks_keeps_what_fits() {
+        let mut tanks = Tanks::new([Tank::new(water(), 10.), Tank::new(nectar(), 10.)]);
+        tanks.fill_all(&[
+            BulkQuantity::new(water(), 8.),
+            BulkQuantity::new(nectar(), 8.),
+        ]);
+
+        tanks.replace_with(Tanks::new([Tank::new(water(), 5.)]));
+
+        assert_eq!(tanks.amount(water()), 5.);
+        assert_eq!(tanks.get(nectar()), None);
+    }
+}
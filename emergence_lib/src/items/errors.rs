@@ -1,6 +1,8 @@
 //! Errors related to items and inventories.
 
-use super::ItemCount;
+use crate::asset_management::manifest::Id;
+
+use super::{bulk::BulkQuantity, item_manifest::Item, ItemCount};
 
 /// Failed to add items to an inventory.
 #[derive(Debug, PartialEq, Eq)]
@@ -40,3 +42,39 @@ pub struct ItemTransferError {
     /// Did this fail because the output inventory of the source was empty?
     pub empty_source: bool,
 }
+
+/// Failed to reserve items in an inventory.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReserveItemError {
+    /// The number of items that were missing or already reserved by someone else.
+    pub missing_count: ItemCount,
+}
+
+/// Failed to move a set of items from one inventory to another.
+///
+/// When this is returned, neither inventory has been changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferError {
+    /// The source did not hold enough items.
+    InsufficientItems {
+        /// The number of items that were missing from the source.
+        missing_counts: Vec<ItemCount>,
+    },
+    /// The destination did not have room for all of the items.
+    NoCapacity {
+        /// The number of items that would not have fit.
+        excess_counts: Vec<ItemCount>,
+    },
+    /// The destination only accepts a different kind of item.
+    WrongItemKind {
+        /// The item that was refused.
+        item_id: Id<Item>,
+    },
+}
+
+/// Failed to remove a bulk resource from a tank.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrainBulkError {
+    /// How much of the resource was missing.
+    pub missing: BulkQuantity,
+}
@@ -1,6 +1,8 @@
 //! Storage of multiple items with a capacity.
 
-use bevy::log::warn;
+use std::time::Duration;
+
+use bevy::{ecs::entity::Entity, log::warn};
 use itertools::rev;
 use serde::{Deserialize, Serialize};
 
@@ -9,7 +11,7 @@ use crate::{asset_management::manifest::Id, crafting::item_tags::ItemKind};
 use super::{
     errors::{
         AddManyItemsError, AddOneItemError, ItemTransferError, RemoveManyItemsError,
-        RemoveOneItemError,
+        RemoveOneItemError, ReserveItemError, TransferError,
     },
     item_manifest::{Item, ItemManifest},
     slot::ItemSlot,
@@ -31,6 +33,26 @@ pub struct Inventory {
 
     /// The maximum number of item slots this inventory can hold.
     max_slot_count: usize,
+
+    /// Items that have been set aside for a particular unit or order.
+    ///
+    /// Reserved items are still stored in `slots`, but are not offered to anyone else.
+    /// Reservations refer to entities, so they are not saved: holders reserve their items again after loading.
+    #[serde(skip)]
+    reservations: Vec<Reservation>,
+}
+
+/// A claim on some of the items in an [`Inventory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservation {
+    /// The entity that the items are set aside for, such as a hauling unit or a craft order.
+    pub holder: Entity,
+    /// The items that are set aside.
+    pub item_count: ItemCount,
+    /// When this reservation lapses, if it has not been released before then.
+    ///
+    /// This is measured against the elapsed simulation time.
+    pub expires_at: Duration,
 }
 
 impl Default for Inventory {
@@ -72,6 +94,7 @@ impl Inventory {
     /// An inventory with no slots.
     pub const NULL: Inventory = Inventory {
         reserved_for: None,
+        reservations: Vec::new(),
         slots: Vec::new(),
         max_slot_count: 0,
     };
@@ -80,6 +103,7 @@ impl Inventory {
     pub fn new(max_slot_count: usize, reserved_for: Option<Id<Item>>) -> Self {
         Self {
             reserved_for,
+            reservations: Vec::new(),
             slots: Vec::new(),
             max_slot_count,
         }
@@ -89,6 +113,7 @@ impl Inventory {
     pub fn new_from_item(item_id: Id<Item>, max: u32) -> Self {
         Self {
             reserved_for: Some(item_id),
+            reservations: Vec::new(),
             slots: vec![ItemSlot::empty(item_id, max)],
             max_slot_count: 1,
         }
@@ -98,6 +123,7 @@ impl Inventory {
     pub fn full_from_item(item_id: Id<Item>, max: u32) -> Self {
         Self {
             reserved_for: Some(item_id),
+            reservations: Vec::new(),
             slots: vec![ItemSlot::full(item_id, max)],
             max_slot_count: 1,
         }
@@ -107,6 +133,7 @@ impl Inventory {
     pub fn empty_from_item(item_id: Id<Item>, max: u32) -> Self {
         Self {
             reserved_for: Some(item_id),
+            reservations: Vec::new(),
             slots: vec![ItemSlot::empty(item_id, max)],
             max_slot_count: 1,
         }
@@ -308,7 +335,7 @@ impl Inventory {
         // This is always equal to the length, as the 0-indexing and "1 past the end" behavior cancel out.
         let first_unallocated_slot = self.slots.len();
         if first_unallocated_slot >= self.max_slot_count {
-            self.max_slot_count = first_unallocated_slot;
+            self.max_slot_count = first_unallocated_slot + 1;
         }
 
         // By definition we're at the end of the slots, so we need to add a new one.
@@ -526,6 +553,8 @@ impl Inventory {
             }
         }
 
+        self.trim_reservations(item_count.item_id);
+
         if items_to_remove > 0 {
             Err(RemoveOneItemError {
                 missing_count: ItemCount::new(item_count.item_id, items_to_remove),
@@ -564,7 +593,21 @@ impl Inventory {
         &mut self,
         item_counts: &[ItemCount],
     ) -> Result<(), RemoveManyItemsError> {
-        let missing_counts: Vec<ItemCount> = item_counts
+        let missing_counts = self.missing_counts(item_counts);
+
+        if missing_counts.is_empty() {
+            item_counts
+                .iter()
+                .for_each(|item_count| self.remove_item_all_or_nothing(item_count).unwrap());
+            Ok(())
+        } else {
+            Err(RemoveManyItemsError { missing_counts })
+        }
+    }
+
+    /// The number of each item in `item_counts` that this inventory is short of.
+    fn missing_counts(&self, item_counts: &[ItemCount]) -> Vec<ItemCount> {
+        item_counts
             .iter()
             .filter_map(|item_count| {
                 let missing = item_count
@@ -577,16 +620,7 @@ impl Inventory {
                     None
                 }
             })
-            .collect();
-
-        if missing_counts.is_empty() {
-            item_counts
-                .iter()
-                .for_each(|item_count| self.remove_item_all_or_nothing(item_count).unwrap());
-            Ok(())
-        } else {
-            Err(RemoveManyItemsError { missing_counts })
-        }
+            .collect()
     }
 
     /// Transfers item of the type given by `item_count` from the inventory of `self` to `other`.
@@ -669,6 +703,254 @@ impl Inventory {
         result
     }
 
+    /// Removes all of `item_counts` from this inventory, or nothing at all.
+    ///
+    /// Counts of the same item are combined.
+    /// If any items are missing, an error is returned and nothing is removed.
+    pub fn take_items(&mut self, item_counts: &[ItemCount]) -> Result<(), TransferError> {
+        let combined = combine_item_counts(item_counts);
+
+        match self.remove_items_all_or_nothing(&combined) {
+            Ok(()) => Ok(()),
+            Err(RemoveManyItemsError { missing_counts }) => {
+                Err(TransferError::InsufficientItems { missing_counts })
+            }
+        }
+    }
+
+    /// Removes all of `item_counts` from this inventory on behalf of `holder`, or nothing at all.
+    ///
+    /// Any of these items that `holder` had reserved are released first,
+    /// so that the items are taken out of its own reservation rather than one held by someone else.
+    pub fn take_items_for(
+        &mut self,
+        holder: Entity,
+        item_counts: &[ItemCount],
+    ) -> Result<(), TransferError> {
+        let combined = combine_item_counts(item_counts);
+
+        let missing_counts = self.missing_counts(&combined);
+        if !missing_counts.is_empty() {
+            return Err(TransferError::InsufficientItems { missing_counts });
+        }
+
+        for item_count in combined.iter() {
+            self.release(holder, item_count);
+        }
+
+        // If this unwrap panics, the check above is broken
+        self.take_items(&combined).unwrap();
+        Ok(())
+    }
+
+    /// Adds all of `item_counts` to this inventory, or nothing at all.
+    ///
+    /// Counts of the same item are combined.
+    /// If this inventory refuses any of the items or cannot fit them all, an error is returned and nothing is added.
+    pub fn give_items(
+        &mut self,
+        item_counts: &[ItemCount],
+        item_manifest: &ItemManifest,
+    ) -> Result<(), TransferError> {
+        let combined = combine_item_counts(item_counts);
+
+        if let Some(refused) = combined
+            .iter()
+            .find(|item_count| !self.permits(item_count.item_id))
+        {
+            return Err(TransferError::WrongItemKind {
+                item_id: refused.item_id,
+            });
+        }
+
+        match self.add_items_all_or_nothing(&combined, item_manifest) {
+            Ok(()) => Ok(()),
+            Err(AddManyItemsError { excess_counts }) => {
+                Err(TransferError::NoCapacity { excess_counts })
+            }
+        }
+    }
+
+    /// Moves all of `item_counts` from `self` to `destination`, or nothing at all.
+    ///
+    /// Counts of the same item are combined.
+    /// If the source is missing any items, the destination refuses any of them or cannot fit them all,
+    /// an error describing the first problem found is returned and neither inventory is changed.
+    pub fn transfer_items(
+        &mut self,
+        item_counts: &[ItemCount],
+        destination: &mut Inventory,
+        item_manifest: &ItemManifest,
+    ) -> Result<(), TransferError> {
+        let combined = combine_item_counts(item_counts);
+
+        // Check the source before touching the destination, so that a failure changes nothing
+        let missing_counts = self.missing_counts(&combined);
+        if !missing_counts.is_empty() {
+            return Err(TransferError::InsufficientItems { missing_counts });
+        }
+
+        destination.give_items(&combined, item_manifest)?;
+        // If this unwrap panics, the check above is broken
+        self.take_items(&combined).unwrap();
+        Ok(())
+    }
+
+    /// The current reservations on this inventory, oldest first.
+    pub fn reservations(&self) -> impl Iterator<Item = &Reservation> {
+        self.reservations.iter()
+    }
+
+    /// The number of items of the type `item_id` that have been reserved by anyone.
+    pub fn reserved_item_count(&self, item_id: Id<Item>) -> u32 {
+        self.reservations
+            .iter()
+            .filter(|reservation| reservation.item_count.item_id == item_id)
+            .map(|reservation| reservation.item_count.count)
+            .sum()
+    }
+
+    /// The number of items of the type `item_id` that have been reserved by `holder`.
+    pub fn reserved_by(&self, holder: Entity, item_id: Id<Item>) -> u32 {
+        self.reservations
+            .iter()
+            .find(|reservation| {
+                reservation.holder == holder && reservation.item_count.item_id == item_id
+            })
+            .map_or(0, |reservation| reservation.item_count.count)
+    }
+
+    /// The number of items of the type `item_id` that are stored here and not reserved by anyone.
+    pub fn unreserved_item_count(&self, item_id: Id<Item>) -> u32 {
+        self.item_count(item_id)
+            .saturating_sub(self.reserved_item_count(item_id))
+    }
+
+    /// The number of items of the type `item_id` that `holder` may take.
+    ///
+    /// This is every item that is not reserved by anyone, along with any that are reserved by `holder` itself.
+    pub fn item_count_available_to(&self, holder: Entity, item_id: Id<Item>) -> u32 {
+        self.unreserved_item_count(item_id) + self.reserved_by(holder, item_id)
+    }
+
+    /// Returns the first [`Id<Item>`] that matches the given [`ItemKind`] and that `holder` may take any of, if any.
+    pub fn available_item_id(
+        &self,
+        item_kind: ItemKind,
+        holder: Entity,
+        item_manifest: &ItemManifest,
+    ) -> Option<Id<Item>> {
+        match item_kind {
+            ItemKind::Single(item_id) => {
+                (self.item_count_available_to(holder, item_id) > 0).then_some(item_id)
+            }
+            ItemKind::Tag(tag) => {
+                self.iter()
+                    .map(|item_slot| item_slot.item_id())
+                    .find(|&item_id| {
+                        item_manifest.has_tag(item_id, tag)
+                            && self.item_count_available_to(holder, item_id) > 0
+                    })
+            }
+        }
+    }
+
+    /// Sets aside the items in `item_count` for `holder`, until `expires_at`.
+    ///
+    /// If `holder` already has a reservation for this item, it is extended and its expiry is updated.
+    /// - If enough unreserved items are stored here, they are all reserved and `Ok` is returned.
+    /// - Otherwise, _no_ items are reserved and `Err` is returned.
+    pub fn reserve(
+        &mut self,
+        holder: Entity,
+        item_count: &ItemCount,
+        expires_at: Duration,
+    ) -> Result<(), ReserveItemError> {
+        let available = self.unreserved_item_count(item_count.item_id);
+        if available < item_count.count {
+            return Err(ReserveItemError {
+                missing_count: ItemCount::new(item_count.item_id, item_count.count - available),
+            });
+        }
+
+        match self.reservations.iter_mut().find(|reservation| {
+            reservation.holder == holder && reservation.item_count.item_id == item_count.item_id
+        }) {
+            Some(reservation) => {
+                reservation.item_count.count += item_count.count;
+                reservation.expires_at = expires_at;
+            }
+            None => self.reservations.push(Reservation {
+                holder,
+                item_count: item_count.clone(),
+                expires_at,
+            }),
+        }
+
+        Ok(())
+    }
+
+    /// Releases up to `item_count` of the items reserved by `holder`.
+    ///
+    /// Returns the number of items that were released.
+    pub fn release(&mut self, holder: Entity, item_count: &ItemCount) -> u32 {
+        let Some(index) = self.reservations.iter().position(|reservation| {
+            reservation.holder == holder && reservation.item_count.item_id == item_count.item_id
+        }) else {
+            return 0;
+        };
+
+        let reservation = &mut self.reservations[index];
+        let released = item_count.count.min(reservation.item_count.count);
+        reservation.item_count.count -= released;
+        if reservation.item_count.count == 0 {
+            self.reservations.remove(index);
+        }
+
+        released
+    }
+
+    /// Releases every reservation held by `holder`.
+    pub fn release_all(&mut self, holder: Entity) {
+        self.reservations
+            .retain(|reservation| reservation.holder != holder);
+    }
+
+    /// Drops reservations that have expired by `now`, or whose holder is no longer valid.
+    ///
+    /// This ensures that items are never locked away by a unit that died or a job that was cancelled.
+    pub fn expire_reservations(&mut self, now: Duration, mut is_valid: impl FnMut(Entity) -> bool) {
+        self.reservations
+            .retain(|reservation| reservation.expires_at > now && is_valid(reservation.holder));
+    }
+
+    /// Shrinks the reservations for `item_id` until they no longer exceed the items stored here.
+    ///
+    /// The newest reservations are cut back first.
+    fn trim_reservations(&mut self, item_id: Id<Item>) {
+        let mut excess = self
+            .reserved_item_count(item_id)
+            .saturating_sub(self.item_count(item_id));
+
+        for reservation in self
+            .reservations
+            .iter_mut()
+            .rev()
+            .filter(|reservation| reservation.item_count.item_id == item_id)
+        {
+            if excess == 0 {
+                break;
+            }
+
+            let cut = excess.min(reservation.item_count.count);
+            reservation.item_count.count -= cut;
+            excess -= cut;
+        }
+
+        self.reservations
+            .retain(|reservation| reservation.item_count.count > 0);
+    }
+
     /// The pretty formatting for this type
     pub fn display(&self, item_manifest: &ItemManifest) -> String {
         let slot_strings: Vec<String> = self
@@ -688,6 +970,7 @@ impl FromIterator<ItemSlot> for Inventory {
     fn from_iter<I: IntoIterator<Item = ItemSlot>>(iter: I) -> Self {
         let mut inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             slots: iter.into_iter().collect(),
             max_slot_count: 0,
         };
@@ -697,6 +980,22 @@ impl FromIterator<ItemSlot> for Inventory {
     }
 }
 
+/// Merges any counts of the same item in `item_counts`, preserving the order in which items first appear.
+fn combine_item_counts(item_counts: &[ItemCount]) -> Vec<ItemCount> {
+    let mut combined: Vec<ItemCount> = Vec::new();
+    for item_count in item_counts {
+        match combined
+            .iter_mut()
+            .find(|existing| existing.item_id == item_count.item_id)
+        {
+            Some(existing) => existing.count += item_count.count,
+            None => combined.push(item_count.clone()),
+        }
+    }
+
+    combined
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -713,6 +1012,7 @@ mod tests {
                 fluid: false,
                 buoyant: true,
                 seed: None,
+                corpse: false,
             },
         );
         manifest.insert(
@@ -723,6 +1023,7 @@ mod tests {
                 fluid: false,
                 buoyant: true,
                 seed: None,
+                corpse: false,
             },
         );
         manifest
@@ -731,6 +1032,7 @@ mod tests {
     fn full_inventory() -> Inventory {
         Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 1,
             slots: vec![ItemSlot::new_with_count(
                 Id::from_name("mushroom".to_string()),
@@ -743,6 +1045,7 @@ mod tests {
     fn partial_inventory() -> Inventory {
         Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 1,
             slots: vec![ItemSlot::new_with_count(
                 Id::from_name("mushroom".to_string()),
@@ -755,6 +1058,7 @@ mod tests {
     fn empty_inventory() -> Inventory {
         Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 1,
             slots: vec![],
         }
@@ -771,6 +1075,7 @@ mod tests {
     fn should_count_item() {
         let inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 4,
             slots: vec![
                 ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -786,6 +1091,7 @@ mod tests {
     fn should_determine_that_item_count_is_available() {
         let inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 4,
             slots: vec![
                 ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -801,6 +1107,7 @@ mod tests {
     fn should_determine_that_item_count_is_not_available() {
         let inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 4,
             slots: vec![
                 ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -825,6 +1132,7 @@ mod tests {
     fn should_determine_that_inventory_is_not_empty() {
         let inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 4,
             slots: vec![
                 ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -840,6 +1148,7 @@ mod tests {
     fn should_determine_that_inventory_is_full() {
         let inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 4,
             slots: vec![
                 ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -856,6 +1165,7 @@ mod tests {
     fn should_determine_that_inventory_is_not_full() {
         let inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 4,
             slots: vec![
                 ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -871,6 +1181,7 @@ mod tests {
     fn should_calculate_number_of_free_slots() {
         let inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 4,
             slots: vec![
                 ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -886,6 +1197,7 @@ mod tests {
     fn should_calculate_remaining_space_for_item() {
         let inventory = Inventory {
             reserved_for: None,
+            reservations: Vec::new(),
             max_slot_count: 4,
             slots: vec![
                 ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -910,6 +1222,7 @@ mod tests {
             fn should_be_ok_when_all_fit() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -936,6 +1249,7 @@ mod tests {
             fn should_fill_up_when_not_all_fit() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -972,6 +1286,7 @@ mod tests {
             fn should_be_ok_when_all_fit() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1038,6 +1353,7 @@ mod tests {
             fn should_not_add_anything_if_not_enough_space() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1071,6 +1387,7 @@ mod tests {
             fn should_be_ok_when_all_fit() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1100,6 +1417,7 @@ mod tests {
             fn should_not_add_anything_if_not_enough_space() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1140,6 +1458,7 @@ mod tests {
             fn should_be_ok_when_all_exist() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1164,6 +1483,7 @@ mod tests {
             fn should_empty_when_not_all_exist() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1194,6 +1514,7 @@ mod tests {
             fn should_be_ok_when_all_exist() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1220,6 +1541,7 @@ mod tests {
             fn should_not_remove_anything_if_not_enough_exist() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1252,6 +1574,7 @@ mod tests {
             fn should_be_ok_when_all_exist() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1278,6 +1601,7 @@ mod tests {
             fn should_not_remove_anything_if_not_enough_exist() {
                 let mut inventory = Inventory {
                     reserved_for: None,
+                    reservations: Vec::new(),
                     max_slot_count: 4,
                     slots: vec![
                         ItemSlot::new_with_count(Id::from_name("leaf".to_string()), 10, 10),
@@ -1391,4 +1715,348 @@ mod tests {
             );
         }
     }
+
+    mod transfer_items {
+        use super::*;
+
+        fn mushrooms(count: u32) -> ItemCount {
+            ItemCount::new(Id::from_name("mushroom".to_string()), count)
+        }
+
+        #[test]
+        fn items_are_moved_together() {
+            let mut source = full_inventory();
+            let mut destination = empty_inventory();
+
+            // Counts of the same item are combined
+            let result = source.transfer_items(
+                &[mushrooms(4), mushrooms(3)],
+                &mut destination,
+                &item_manifest(),
+            );
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(source.item_count(mushrooms(0).item_id), 3);
+            assert_eq!(destination.item_count(mushrooms(0).item_id), 7);
+        }
+
+        #[test]
+        fn missing_items_move_nothing() {
+            let mut source = partial_inventory();
+            let mut destination = empty_inventory();
+
+            let result =
+                source.transfer_items(&[transfer_count()], &mut destination, &item_manifest());
+
+            assert_eq!(
+                result,
+                Err(TransferError::InsufficientItems {
+                    missing_counts: vec![mushrooms(3)]
+                })
+            );
+            assert_eq!(source, partial_inventory());
+            assert_eq!(destination, empty_inventory());
+        }
+
+        #[test]
+        fn items_that_do_not_fit_move_nothing() {
+            let mut source = full_inventory();
+            let mut destination = partial_inventory();
+
+            let result = source.transfer_items(&[mushrooms(5)], &mut destination, &item_manifest());
+
+            assert_eq!(
+                result,
+                Err(TransferError::NoCapacity {
+                    excess_counts: vec![mushrooms(2)]
+                })
+            );
+            assert_eq!(source, full_inventory());
+            assert_eq!(destination, partial_inventory());
+        }
+
+        #[test]
+        fn items_can_be_taken_and_given_without_a_second_inventory() {
+            let mut inventory = partial_inventory();
+
+            assert_eq!(
+                inventory.take_items(&[mushrooms(4), mushrooms(4)]),
+                Err(TransferError::InsufficientItems {
+                    missing_counts: vec![mushrooms(1)]
+                })
+            );
+            assert_eq!(inventory, partial_inventory());
+
+            assert_eq!(inventory.take_items(&[mushrooms(7)]), Ok(()));
+            assert_eq!(inventory.item_count(mushrooms(0).item_id), 0);
+
+            assert_eq!(
+                inventory.give_items(&[mushrooms(7)], &item_manifest()),
+                Ok(())
+            );
+            assert_eq!(inventory.item_count(mushrooms(0).item_id), 7);
+        }
+
+        #[test]
+        fn destinations_refuse_the_wrong_kind_of_item() {
+            let leaf = Id::from_name("leaf".to_string());
+            let mut source = full_inventory();
+            let mut destination = Inventory::new_from_item(leaf, 10);
+
+            let result = source.transfer_items(&[mushrooms(1)], &mut destination, &item_manifest());
+
+            assert_eq!(
+                result,
+                Err(TransferError::WrongItemKind {
+                    item_id: mushrooms(0).item_id
+                })
+            );
+            assert_eq!(source, full_inventory());
+        }
+    }
+
+    mod reservations {
+        use super::*;
+
+        fn mushrooms(count: u32) -> ItemCount {
+            ItemCount::new(Id::from_name("mushroom".to_string()), count)
+        }
+
+        fn secs(seconds: u64) -> Duration {
+            Duration::from_secs(seconds)
+        }
+
+        #[test]
+        fn reserved_items_cannot_be_reserved_twice() {
+            let mut inventory = partial_inventory();
+            let first = Entity::from_raw(0);
+            let second = Entity::from_raw(1);
+
+            assert!(inventory.reserve(first, &mushrooms(5), secs(10)).is_ok());
+            assert_eq!(
+                inventory.reserve(second, &mushrooms(5), secs(10)),
+                Err(ReserveItemError {
+                    missing_count: mushrooms(3)
+                })
+            );
+            assert_eq!(inventory.reserved_by(second, mushrooms(0).item_id), 0);
+            assert_eq!(inventory.unreserved_item_count(mushrooms(0).item_id), 2);
+        }
+
+        #[test]
+        fn released_items_can_be_reserved_again() {
+            let mut inventory = partial_inventory();
+            let first = Entity::from_raw(0);
+            let second = Entity::from_raw(1);
+
+            inventory.reserve(first, &mushrooms(7), secs(10)).unwrap();
+            assert_eq!(inventory.release(first, &mushrooms(10)), 7);
+            assert!(inventory.reserve(second, &mushrooms(7), secs(10)).is_ok());
+        }
+
+        #[test]
+        fn reservations_lapse_when_expired_or_abandoned() {
+            let mut inventory = full_inventory();
+            let expired = Entity::from_raw(0);
+            let dead = Entity::from_raw(1);
+            let alive = Entity::from_raw(2);
+
+            inventory.reserve(expired, &mushrooms(1), secs(5)).unwrap();
+            inventory.reserve(dead, &mushrooms(1), secs(20)).unwrap();
+            inventory.reserve(alive, &mushrooms(1), secs(20)).unwrap();
+
+            inventory.expire_reservations(secs(10), |holder| holder != dead);
+            let holders: Vec<Entity> = inventory
+                .reservations()
+                .map(|reservation| reservation.holder)
+                .collect();
+            assert_eq!(holders, vec![alive]);
+        }
+
+        #[test]
+        fn removing_items_trims_the_newest_reservations() {
+            let mut inventory = full_inventory();
+            let older = Entity::from_raw(0);
+            let newer = Entity::from_raw(1);
+
+            inventory.reserve(older, &mushrooms(4), secs(10)).unwrap();
+            inventory.reserve(newer, &mushrooms(4), secs(10)).unwrap();
+            inventory.try_remove_item(&mushrooms(5)).unwrap();
+
+            let item_id = mushrooms(0).item_id;
+            assert_eq!(inventory.reserved_by(older, item_id), 4);
+            assert_eq!(inventory.reserved_by(newer, item_id), 1);
+            assert_eq!(inventory.unreserved_item_count(item_id), 0);
+        }
+
+        #[test]
+        fn taking_items_uses_up_the_takers_own_reservation() {
+            let mut inventory = partial_inventory();
+            let first_hauler = Entity::from_raw(0);
+            let second_hauler = Entity::from_raw(1);
+            let item_id = mushrooms(0).item_id;
+
+            inventory
+                .reserve(first_hauler, &mushrooms(3), secs(10))
+                .unwrap();
+            inventory
+                .reserve(second_hauler, &mushrooms(4), secs(10))
+                .unwrap();
+
+            inventory
+                .take_items_for(first_hauler, &[mushrooms(3)])
+                .unwrap();
+            assert_eq!(inventory.item_count(item_id), 4);
+            assert_eq!(inventory.reserved_by(first_hauler, item_id), 0);
+            assert_eq!(inventory.reserved_by(second_hauler, item_id), 4);
+
+            // The remaining items are all spoken for
+            assert_eq!(inventory.item_count_available_to(first_hauler, item_id), 0);
+            assert_eq!(inventory.item_count_available_to(second_hauler, item_id), 4);
+        }
+
+        #[test]
+        fn failed_takes_keep_the_takers_reservation() {
+            let mut inventory = partial_inventory();
+            let hauler = Entity::from_raw(0);
+            let item_id = mushrooms(0).item_id;
+
+            inventory.reserve(hauler, &mushrooms(3), secs(10)).unwrap();
+            assert!(inventory.take_items_for(hauler, &[mushrooms(8)]).is_err());
+            assert_eq!(inventory.item_count(item_id), 7);
+            assert_eq!(inventory.reserved_by(hauler, item_id), 3);
+        }
+
+        #[test]
+        fn items_reserved_by_others_are_not_available() {
+            let mut inventory = partial_inventory();
+            let holder = Entity::from_raw(0);
+            let other = Entity::from_raw(1);
+            let item_id = mushrooms(0).item_id;
+            let mushroom_kind = ItemKind::Single(item_id);
+
+            inventory.reserve(other, &mushrooms(7), secs(10)).unwrap();
+            assert_eq!(inventory.item_count_available_to(holder, item_id), 0);
+            assert_eq!(
+                inventory.available_item_id(mushroom_kind, holder, &item_manifest()),
+                None
+            );
+            assert_eq!(inventory.item_count_available_to(other, item_id), 7);
+            assert_eq!(
+                inventory.available_item_id(mushroom_kind, other, &item_manifest()),
+                Some(item_id)
+            );
+        }
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// The names of the items that can appear in generated inventories.
+        const ITEM_NAMES: [&str; 2] = ["leaf", "mushroom"];
+
+        /// Generates an arbitrary count of one of the test items.
+        fn item_count() -> impl Strategy<Value = ItemCount> {
+            (0..ITEM_NAMES.len(), 0..40u32).prop_map(|(index, count)| {
+                ItemCount::new(Id::from_name(ITEM_NAMES[index].to_string()), count)
+            })
+        }
+
+        /// Generates an inventory with up to four slots, filled by adding arbitrary items until they no longer fit.
+        fn inventory() -> impl Strategy<Value = Inventory> {
+            (1..=4usize, prop::collection::vec(item_count(), 0..8)).prop_map(
+                |(max_slot_count, item_counts)| {
+                    let item_manifest = item_manifest();
+                    let mut inventory = Inventory::new(max_slot_count, None);
+                    for item_count in item_counts {
+                        let _ = inventory.try_add_item(&item_count, &item_manifest);
+                    }
+                    inventory
+                },
+            )
+        }
+
+        /// The total number of each test item held across `inventories`.
+        fn totals(inventories: &[&Inventory]) -> Vec<u32> {
+            ITEM_NAMES
+                .iter()
+                .map(|name| {
+                    let item_id = Id::from_name(name.to_string());
+                    inventories
+                        .iter()
+                        .map(|inventory| inventory.item_count(item_id))
+                        .sum()
+                })
+                .collect()
+        }
+
+        /// Checks that `inventory` respects both its slot limit and the stack size of each slot.
+        fn assert_within_capacity(inventory: &Inventory) {
+            assert!(inventory.slots.len() <= inventory.max_slot_count);
+            for slot in inventory.iter() {
+                assert!(slot.count() <= slot.max_item_count());
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn adding_never_exceeds_capacity(inventory in inventory()) {
+                assert_within_capacity(&inventory);
+            }
+
+            #[test]
+            fn adding_reports_items_that_did_not_fit(
+                mut inventory in inventory(),
+                item_count in item_count(),
+            ) {
+                let before = inventory.item_count(item_count.item_id);
+                let result = inventory.try_add_item(&item_count, &item_manifest());
+                let added = inventory.item_count(item_count.item_id) - before;
+
+                match result {
+                    Ok(()) => prop_assert_eq!(added, item_count.count),
+                    Err(error) => prop_assert_eq!(added + error.excess_count.count, item_count.count),
+                }
+                assert_within_capacity(&inventory);
+            }
+
+            #[test]
+            fn transferring_an_item_conserves_items(
+                mut source in inventory(),
+                mut destination in inventory(),
+                item_count in item_count(),
+            ) {
+                let before = totals(&[&source, &destination]);
+                let source_count = source.item_count(item_count.item_id);
+
+                let result = source.transfer_item(&item_count, &mut destination, &item_manifest());
+
+                prop_assert_eq!(totals(&[&source, &destination]), before);
+                assert_within_capacity(&source);
+                assert_within_capacity(&destination);
+
+                let moved = source_count - source.item_count(item_count.item_id);
+                prop_assert!(moved <= item_count.count);
+                prop_assert_eq!(result.is_ok(), moved == item_count.count);
+            }
+
+            #[test]
+            fn transferring_everything_conserves_items(
+                mut source in inventory(),
+                mut destination in inventory(),
+            ) {
+                let before = totals(&[&source, &destination]);
+
+                let result = source.transfer_all(&mut destination, &item_manifest());
+
+                prop_assert_eq!(totals(&[&source, &destination]), before);
+                assert_within_capacity(&source);
+                assert_within_capacity(&destination);
+                if result.is_ok() {
+                    prop_assert_eq!(totals(&[&source]), vec![0, 0]);
+                }
+            }
+        }
+    }
 }
@@ -29,6 +29,7 @@ impl ItemManifest {
             ItemTag::Seed => data.seed.is_some(),
             ItemTag::Fluid => data.fluid,
             ItemTag::Buoyant => data.buoyant,
+            ItemTag::Corpse => data.corpse,
         }
     }
 
@@ -100,6 +101,8 @@ pub struct ItemData {
     ///
     /// If so, what does it grow into when left as litter?
     pub seed: Option<OrganismId>,
+    /// Is this item the remains of a dead organism?
+    pub corpse: bool,
 }
 
 /// The unprocessed [`ItemData`] as seen in the manifest file.
@@ -117,6 +120,12 @@ pub struct RawItemData {
     ///
     /// If so, what does it grow into when left as litter?
     pub seed: Option<RawOrganismId>,
+    /// Is this item the remains of a dead organism?
+    #[serde(default)]
+    pub corpse: bool,
+    /// Descriptive text shown in the codex, if any.
+    #[serde(default)]
+    pub flavor_text: Option<String>,
 }
 
 impl From<RawItemData> for ItemData {
@@ -127,6 +136,7 @@ impl From<RawItemData> for ItemData {
             fluid: raw.fluid,
             buoyant: raw.buoyant,
             seed: raw.seed.map(OrganismId::from),
+            corpse: raw.corpse,
         }
     }
 }
@@ -149,9 +159,10 @@ impl IsRawManifest for RawItemManifest {
         let mut manifest = Manifest::new();
 
         for (raw_id, raw_data) in self.items.clone() {
+            let flavor_text = raw_data.flavor_text.clone();
             let data = ItemData::from(raw_data);
 
-            manifest.insert(raw_id, data)
+            manifest.insert_with_flavor_text(raw_id, data, flavor_text)
         }
 
         manifest
@@ -0,0 +1,461 @@
+//! A ledger of every item in the world, broken down by where each item is and where it came from.
+//!
+//! The ledger is recounted from the inventories of every entity once per frame.
+//! Systems that create or destroy items record those flows with [`ItemLedger::record_produced`]
+//! and [`ItemLedger::record_consumed`], so that the player can see where their items are coming from.
+//!
+//! In debug builds, each recount is reconciled against the previous count and the flows recorded since then.
+//! Items that appear without a recorded source are reported as errors, as they are almost always duplication bugs.
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::inventories::{InputInventory, OutputInventory, StorageInventory},
+    litter::Litter,
+    units::{goals::Goal, item_interaction::UnitInventory},
+    world_gen::WorldGenState,
+};
+
+use super::{
+    item_manifest::{Item, ItemManifest},
+    ItemCount,
+};
+
+/// Maintains the [`ItemLedger`].
+pub(crate) struct ItemLedgerPlugin;
+
+impl Plugin for ItemLedgerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemLedger>()
+            .add_systems(OnEnter(WorldGenState::Complete), reset_item_ledger)
+            .add_systems(
+                // Run after all commands from Update and FixedUpdate have been applied,
+                // so that items spawned by commands are counted in the same frame that they are recorded
+                PostUpdate,
+                update_item_ledger.run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// The broad categories of places that items can be found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum ItemLocation {
+    /// Stored in the inventory of a structure or ghost.
+    Structures,
+    /// Held by a unit that isn't currently hauling it anywhere.
+    Units,
+    /// Littered on the ground.
+    Ground,
+    /// Held by a unit that is hauling it to a structure.
+    InTransit,
+}
+
+impl ItemLocation {
+    /// All of the possible locations, in display order.
+    pub(crate) const ALL: [ItemLocation; 4] = [
+        ItemLocation::Structures,
+        ItemLocation::Units,
+        ItemLocation::Ground,
+        ItemLocation::InTransit,
+    ];
+}
+
+impl Display for ItemLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ItemLocation::Structures => "Structures",
+            ItemLocation::Units => "Units",
+            ItemLocation::Ground => "Ground",
+            ItemLocation::InTransit => "In transit",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// Where new items come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum ItemSource {
+    /// Produced by a recipe.
+    Crafting,
+    /// Gathered from a wild resource node.
+    Harvesting,
+    /// Bought from a trading caravan.
+    Trading,
+    /// Left behind when an organism died.
+    Corpse,
+    /// Dropped by a random event.
+    Event,
+}
+
+impl ItemSource {
+    /// All of the possible sources, in display order.
+    pub(crate) const ALL: [ItemSource; 5] = [
+        ItemSource::Crafting,
+        ItemSource::Harvesting,
+        ItemSource::Trading,
+        ItemSource::Corpse,
+        ItemSource::Event,
+    ];
+}
+
+impl Display for ItemSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ItemSource::Crafting => "Crafted",
+            ItemSource::Harvesting => "Harvested",
+            ItemSource::Trading => "Bought",
+            ItemSource::Corpse => "Corpses",
+            ItemSource::Event => "Events",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// Where items go when they leave the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum ItemSink {
+    /// Consumed as the input of a recipe.
+    Crafting,
+    /// Eaten by a unit.
+    Eaten,
+    /// Paid to a trading caravan.
+    Trading,
+}
+
+impl ItemSink {
+    /// All of the possible sinks, in display order.
+    pub(crate) const ALL: [ItemSink; 3] = [ItemSink::Crafting, ItemSink::Eaten, ItemSink::Trading];
+}
+
+impl Display for ItemSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ItemSink::Crafting => "Used in recipes",
+            ItemSink::Eaten => "Eaten",
+            ItemSink::Trading => "Sold",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// The number of items of each type in each [`ItemLocation`].
+pub(crate) type LocationCounts = HashMap<(ItemLocation, Id<Item>), u32>;
+
+/// Every item in the world, by location, along with where items have come from and gone to.
+#[derive(Resource, Debug, Default, Clone)]
+pub(crate) struct ItemLedger {
+    /// The number of items of each type in each location, as of the last recount.
+    counts: LocationCounts,
+    /// The total number of items of each type produced by each source since the world was generated.
+    produced: HashMap<(ItemSource, Id<Item>), u32>,
+    /// The total number of items of each type consumed by each sink since the world was generated.
+    consumed: HashMap<(ItemSink, Id<Item>), u32>,
+    /// The net number of items of each type created since the last recount.
+    pending: HashMap<Id<Item>, i64>,
+    /// Has the ledger been counted since it was last reset?
+    counted: bool,
+    /// The total number of items of each type that have appeared without a recorded source.
+    unexplained: HashMap<Id<Item>, u32>,
+}
+
+impl ItemLedger {
+    /// Records that `count` items of type `item_id` were created by `source`.
+    pub(crate) fn record_produced(&mut self, item_id: Id<Item>, count: u32, source: ItemSource) {
+        if count == 0 {
+            return;
+        }
+
+        *self.produced.entry((source, item_id)).or_default() += count;
+        *self.pending.entry(item_id).or_default() += count as i64;
+    }
+
+    /// Records that `count` items of type `item_id` were destroyed by `sink`.
+    pub(crate) fn record_consumed(&mut self, item_id: Id<Item>, count: u32, sink: ItemSink) {
+        if count == 0 {
+            return;
+        }
+
+        *self.consumed.entry((sink, item_id)).or_default() += count;
+        *self.pending.entry(item_id).or_default() -= count as i64;
+    }
+
+    /// The number of items of type `item_id` found in `location`.
+    pub(crate) fn count(&self, location: ItemLocation, item_id: Id<Item>) -> u32 {
+        self.counts
+            .get(&(location, item_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The number of items of type `item_id` across all locations.
+    pub(crate) fn total(&self, item_id: Id<Item>) -> u32 {
+        ItemLocation::ALL
+            .into_iter()
+            .map(|location| self.count(location, item_id))
+            .sum()
+    }
+
+    /// The total number of items of each type, across all locations.
+    fn totals(counts: &LocationCounts) -> HashMap<Id<Item>, u32> {
+        let mut totals = HashMap::default();
+        for (&(_, item_id), &count) in counts.iter() {
+            *totals.entry(item_id).or_default() += count;
+        }
+        totals
+    }
+
+    /// The total number of items of type `item_id` that have been produced by `source`.
+    pub(crate) fn produced(&self, source: ItemSource, item_id: Id<Item>) -> u32 {
+        self.produced
+            .get(&(source, item_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The total number of items of type `item_id` that have been consumed by `sink`.
+    pub(crate) fn consumed(&self, sink: ItemSink, item_id: Id<Item>) -> u32 {
+        self.consumed
+            .get(&(sink, item_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The total number of items of type `item_id` that have appeared without a recorded source.
+    ///
+    /// This is only tracked in debug builds.
+    pub(crate) fn unexplained(&self, item_id: Id<Item>) -> u32 {
+        self.unexplained.get(&item_id).copied().unwrap_or_default()
+    }
+
+    /// Forgets the previous count, so that the next recount is taken as the new baseline.
+    fn reset(&mut self) {
+        *self = ItemLedger::default();
+    }
+
+    /// Replaces the counts in this ledger with a fresh recount of the world.
+    ///
+    /// If `reconcile` is true, returns the items that appeared since the last recount without a recorded source.
+    fn recount(&mut self, counts: LocationCounts, reconcile: bool) -> Vec<ItemCount> {
+        let mut surplus = Vec::new();
+
+        if reconcile && self.counted {
+            let previous = Self::totals(&self.counts);
+            let current = Self::totals(&counts);
+
+            for (&item_id, &current_count) in current.iter() {
+                let previous_count = previous.get(&item_id).copied().unwrap_or_default() as i64;
+                let expected =
+                    previous_count + self.pending.get(&item_id).copied().unwrap_or_default();
+                let unexplained = current_count as i64 - expected;
+
+                // Items can vanish when the entity that holds them is despawned, so only surpluses are suspicious
+                if unexplained > 0 {
+                    let unexplained = unexplained as u32;
+                    *self.unexplained.entry(item_id).or_default() += unexplained;
+                    surplus.push(ItemCount::new(item_id, unexplained));
+                }
+            }
+        }
+
+        self.counts = counts;
+        self.pending.clear();
+        self.counted = true;
+        surplus
+    }
+
+    /// Describes where each type of item is, and where it came from.
+    pub(crate) fn display(&self, item_manifest: &ItemManifest) -> String {
+        let totals = Self::totals(&self.counts);
+        let mut item_ids: Vec<Id<Item>> = totals.keys().copied().collect();
+        item_ids.sort_by_key(|&item_id| item_manifest.name(item_id).to_string());
+
+        let mut string = String::new();
+        for item_id in item_ids {
+            let name = item_manifest.name(item_id);
+            let total = self.total(item_id);
+            string += &format!("{name}: {total}\n");
+
+            let describe = |counts: Vec<(String, u32)>| -> String {
+                counts
+                    .into_iter()
+                    .filter(|(_, count)| *count > 0)
+                    .map(|(label, count)| format!("{label} {count}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            let locations = describe(
+                ItemLocation::ALL
+                    .into_iter()
+                    .map(|location| (location.to_string(), self.count(location, item_id)))
+                    .collect(),
+            );
+            string += &format!("  {locations}\n");
+
+            let sources = describe(
+                ItemSource::ALL
+                    .into_iter()
+                    .map(|source| (source.to_string(), self.produced(source, item_id)))
+                    .collect(),
+            );
+            if !sources.is_empty() {
+                string += &format!("  In: {sources}\n");
+            }
+
+            let sinks = describe(
+                ItemSink::ALL
+                    .into_iter()
+                    .map(|sink| (sink.to_string(), self.consumed(sink, item_id)))
+                    .collect(),
+            );
+            if !sinks.is_empty() {
+                string += &format!("  Out: {sinks}\n");
+            }
+
+            let unexplained = self.unexplained(item_id);
+            if unexplained > 0 {
+                string += &format!("  Unexplained: {unexplained}\n");
+            }
+        }
+
+        string
+    }
+}
+
+/// Starts a fresh ledger once world generation is complete, ignoring anything that happened while burning in.
+fn reset_item_ledger(mut item_ledger: ResMut<ItemLedger>) {
+    item_ledger.reset();
+}
+
+/// Recounts every item in the world, and in debug builds, reports any that appeared from nowhere.
+fn update_item_ledger(
+    mut item_ledger: ResMut<ItemLedger>,
+    input_inventory_query: Query<&InputInventory>,
+    output_inventory_query: Query<&OutputInventory>,
+    storage_inventory_query: Query<&StorageInventory>,
+    unit_inventory_query: Query<(&UnitInventory, &Goal)>,
+    litter_query: Query<&Litter>,
+    item_manifest: Res<ItemManifest>,
+) {
+    let mut counts = LocationCounts::default();
+
+    let structure_slots = input_inventory_query
+        .iter()
+        .flat_map(|inventory| inventory.iter())
+        .chain(
+            output_inventory_query
+                .iter()
+                .flat_map(|inventory| inventory.iter()),
+        )
+        .chain(
+            storage_inventory_query
+                .iter()
+                .flat_map(|inventory| inventory.iter()),
+        );
+    for item_slot in structure_slots {
+        *counts
+            .entry((ItemLocation::Structures, item_slot.item_id()))
+            .or_default() += item_slot.count();
+    }
+
+    for (unit_inventory, goal) in unit_inventory_query.iter() {
+        let Some(item_id) = unit_inventory.held_item() else {
+            continue;
+        };
+
+        let location = match goal {
+            Goal::Deliver(_) | Goal::Store(_) => ItemLocation::InTransit,
+            _ => ItemLocation::Units,
+        };
+        *counts.entry((location, item_id)).or_default() += unit_inventory.count();
+    }
+
+    for item_slot in litter_query
+        .iter()
+        .flat_map(|litter| litter.contents.iter())
+    {
+        *counts
+            .entry((ItemLocation::Ground, item_slot.item_id()))
+            .or_default() += item_slot.count();
+    }
+
+    let surplus = item_ledger.recount(counts, cfg!(debug_assertions));
+    for item_count in surplus {
+        error!(
+            "{} appeared without a recorded source: this is probably an item duplication bug",
+            item_count.display(&item_manifest)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf() -> Id<Item> {
+        Id::from_name("leaf".to_string())
+    }
+
+    fn counts(location: ItemLocation, count: u32) -> LocationCounts {
+        LocationCounts::from_iter([((location, leaf()), count)])
+    }
+
+    #[test]
+    fn the_first_count_is_the_baseline() {
+        let mut item_ledger = ItemLedger::default();
+        let surplus = item_ledger.recount(counts(ItemLocation::Structures, 10), true);
+
+        assert!(surplus.is_empty());
+        assert_eq!(item_ledger.total(leaf()), 10);
+    }
+
+    #[test]
+    fn moving_items_is_not_suspicious() {
+        let mut item_ledger = ItemLedger::default();
+        item_ledger.recount(counts(ItemLocation::Structures, 10), true);
+        let surplus = item_ledger.recount(counts(ItemLocation::InTransit, 10), true);
+
+        assert!(surplus.is_empty());
+        assert_eq!(item_ledger.count(ItemLocation::InTransit, leaf()), 10);
+        assert_eq!(item_ledger.count(ItemLocation::Structures, leaf()), 0);
+    }
+
+    #[test]
+    fn recorded_items_are_not_suspicious() {
+        let mut item_ledger = ItemLedger::default();
+        item_ledger.recount(counts(ItemLocation::Structures, 10), true);
+        item_ledger.record_produced(leaf(), 5, ItemSource::Crafting);
+        item_ledger.record_consumed(leaf(), 2, ItemSink::Eaten);
+        let surplus = item_ledger.recount(counts(ItemLocation::Structures, 13), true);
+
+        assert!(surplus.is_empty());
+        assert_eq!(item_ledger.produced(ItemSource::Crafting, leaf()), 5);
+        assert_eq!(item_ledger.consumed(ItemSink::Eaten, leaf()), 2);
+    }
+
+    #[test]
+    fn duplicated_items_are_reported() {
+        let mut item_ledger = ItemLedger::default();
+        item_ledger.recount(counts(ItemLocation::Structures, 10), true);
+        item_ledger.record_produced(leaf(), 1, ItemSource::Harvesting);
+        let surplus = item_ledger.recount(counts(ItemLocation::Ground, 14), true);
+
+        assert_eq!(surplus, vec![ItemCount::new(leaf(), 3)]);
+        assert_eq!(item_ledger.unexplained(leaf()), 3);
+    }
+
+    #[test]
+    fn vanishing_items_are_not_reported() {
+        let mut item_ledger = ItemLedger::default();
+        item_ledger.recount(counts(ItemLocation::Structures, 10), true);
+        let surplus = item_ledger.recount(counts(ItemLocation::Structures, 4), true);
+
+        assert!(surplus.is_empty());
+    }
+}
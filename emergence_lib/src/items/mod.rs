@@ -6,10 +6,13 @@ use crate::asset_management::manifest::Id;
 
 use self::item_manifest::{Item, ItemManifest};
 
+pub mod bulk;
 pub mod errors;
 pub mod inventory;
 pub mod item_manifest;
+pub(crate) mod ledger;
 pub mod slot;
+pub(crate) mod totals;
 
 /// A specific amount of a given item.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -0,0 +1,81 @@
+//! Counts the total number of items of each type across the whole world.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::inventories::{InputInventory, OutputInventory, StorageInventory},
+    litter::Litter,
+    units::item_interaction::UnitInventory,
+};
+
+use super::item_manifest::{Item, ItemManifest};
+
+/// Counts the total number of items across all inventories of each type.
+#[derive(Debug, Resource, Default)]
+pub(crate) struct ItemTotals {
+    /// The number of items of each type
+    map: HashMap<Id<Item>, u32>,
+}
+
+impl ItemTotals {
+    /// The number of items of each type.
+    pub(crate) fn counts(&self) -> &HashMap<Id<Item>, u32> {
+        &self.map
+    }
+
+    /// Returns a human-readable string representation of the item count
+    pub(crate) fn display(&self, item_manifest: &ItemManifest) -> String {
+        let mut string = String::new();
+
+        for (item_id, count) in self.map.iter() {
+            let name = item_manifest.name(*item_id);
+            string.push_str(&format!("{name}: {count}\n"));
+        }
+
+        string
+    }
+}
+
+/// Count the total number of items across all inventories of each type.
+pub(crate) fn update_item_totals(
+    mut item_totals: ResMut<ItemTotals>,
+    input_inventory_query: Query<&InputInventory>,
+    output_inventory_query: Query<&OutputInventory>,
+    storage_inventory_query: Query<&StorageInventory>,
+    unit_inventory_query: Query<&UnitInventory>,
+    litter_query: Query<&Litter>,
+) {
+    // Reset the item count
+    item_totals.map.clear();
+
+    for inventory in input_inventory_query.iter() {
+        for item_slot in inventory.iter() {
+            *item_totals.map.entry(item_slot.item_id()).or_default() += item_slot.count();
+        }
+    }
+
+    for inventory in output_inventory_query.iter() {
+        for item_slot in inventory.iter() {
+            *item_totals.map.entry(item_slot.item_id()).or_default() += item_slot.count();
+        }
+    }
+
+    for inventory in storage_inventory_query.iter() {
+        for item_slot in inventory.iter() {
+            *item_totals.map.entry(item_slot.item_id()).or_default() += item_slot.count();
+        }
+    }
+
+    for unit_inventory in unit_inventory_query.iter() {
+        if let Some(item_id) = unit_inventory.held_item() {
+            *item_totals.map.entry(item_id).or_default() += unit_inventory.count();
+        }
+    }
+
+    for litter in litter_query.iter() {
+        for item_slot in litter.contents.iter() {
+            *item_totals.map.entry(item_slot.item_id()).or_default() += item_slot.count();
+        }
+    }
+}
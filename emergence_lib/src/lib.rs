@@ -9,24 +9,42 @@
 #![allow(clippy::too_many_arguments)]
 
 pub mod asset_management;
+pub mod audio;
+pub mod automation;
+pub mod codex;
 pub mod construction;
+pub mod control_api;
 pub mod crafting;
+#[cfg(feature = "dev-tools")]
+pub mod dev_tools;
 pub mod enum_iter;
+pub mod factions;
 pub mod filtered_array_iter;
+pub mod fog_of_war;
+pub mod game_state;
 pub mod geometry;
 pub mod graphics;
 pub mod items;
 pub mod light;
 pub mod litter;
+pub mod logistics;
+pub mod map_editor;
+pub mod milestones;
+pub mod multiplayer;
 pub mod organisms;
 pub mod player_interaction;
+pub mod random_events;
+pub mod save_files;
 pub mod signals;
 pub mod simulation;
 pub mod structures;
+pub mod temperature;
 pub mod terrain;
+pub mod trading;
 pub mod ui;
 pub mod units;
 pub mod utils;
+pub mod viewer_events;
 pub mod water;
 pub mod world_gen;
 
@@ -38,6 +56,55 @@ pub mod testing {
     use crate::{simulation::SimulationPlugin, world_gen::GenerationConfig};
     use bevy::prelude::*;
 
+    pub mod regression;
+
+    /// Creates a new, empty directory for a test to write files into, named after `name`.
+    ///
+    /// Each call returns a different directory, so tests that run in parallel never share files.
+    #[cfg(test)]
+    pub(crate) fn unique_temp_dir(name: &str) -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// The number of directories created so far by this process.
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let directory = std::env::temp_dir().join(format!(
+            "{name}_{}_{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        // Clear out anything left behind by an earlier process with the same id
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    /// A small [`ItemManifest`](crate::items::item_manifest::ItemManifest) for unit tests.
+    ///
+    /// Contains `leaf`, `mushroom`, `soil` and `water`, each of which stacks up to 10.
+    /// Only `leaf` is compostable.
+    #[cfg(test)]
+    pub(crate) fn item_manifest() -> crate::items::item_manifest::ItemManifest {
+        use crate::{asset_management::manifest::Manifest, items::item_manifest::ItemData};
+
+        let mut manifest = Manifest::new();
+        for name in ["leaf", "mushroom", "soil", "water"] {
+            manifest.insert(
+                name.to_string(),
+                ItemData {
+                    stack_size: 10,
+                    compostable: name == "leaf",
+                    fluid: false,
+                    buoyant: true,
+                    seed: None,
+                    corpse: false,
+                },
+            );
+        }
+        manifest
+    }
+
     /// Just [`MinimalPlugins`].
     pub fn minimal_app() -> App {
         let mut app = App::new();
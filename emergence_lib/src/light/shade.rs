@@ -62,6 +62,11 @@ impl Display for Shade {
 #[derive(Component, Clone, Debug, Default)]
 pub(crate) struct ReceivedLight(pub(crate) Illuminance);
 
+impl ReceivedLight {
+    /// The light received underground, where the sun never reaches.
+    pub(crate) const UNDERGROUND: ReceivedLight = ReceivedLight(Illuminance::Dark);
+}
+
 impl Display for ReceivedLight {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
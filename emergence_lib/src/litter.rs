@@ -2,22 +2,26 @@
 
 use std::f32::consts::TAU;
 
-use bevy::utils::Duration;
+use bevy::utils::{Duration, HashMap};
 use bevy::{ecs::system::Command, prelude::*};
-use hexx::Direction;
-use rand::thread_rng;
+use hexx::{Direction, Hex};
 use rand_distr::{Distribution, Normal};
 
 use crate::asset_management::manifest::Id;
-use crate::geometry::MAP_LAYOUT;
 use crate::items::inventory::InventoryState;
 use crate::items::item_manifest::Item;
 use crate::items::ItemCount;
+use crate::simulation::rng::SystemRng;
+use crate::terrain::fertility::Decomposition;
 use crate::terrain::terrain_assets::TerrainHandles;
 use crate::{
-    crafting::{inventories::StorageInventory, item_tags::ItemKind},
-    geometry::{DiscreteHeight, Height, MapGeometry, VoxelPos},
+    crafting::{
+        inventories::StorageInventory,
+        item_tags::{ItemKind, ItemTag},
+    },
+    geometry::{direction_from_angle, DiscreteHeight, Height, MapGeometry, VoxelKind, VoxelPos},
     items::item_manifest::ItemManifest,
+    organisms::corpses::{CorpseHandling, CorpsePolicy},
     signals::{Emitter, SignalStrength, SignalType},
     structures::{logistic_buildings::AbsorbsItems, Footprint},
     water::{FlowVelocity, WaterDepth},
@@ -36,6 +40,8 @@ struct LitterBundle {
     scene_bundle: SceneBundle,
     /// Is this litter currently floating?
     floating: Floating,
+    /// Tracks the decomposition of any compostable items.
+    decomposition: Decomposition,
 }
 
 /// Items that are littered without a container.
@@ -81,6 +87,26 @@ impl Litter {
     }
 }
 
+impl Litter {
+    /// Moves as many items as possible from `other` into `self`.
+    ///
+    /// Litter only holds a single stack, so this only has an effect when both piles contain the same item,
+    /// and stops once `self` reaches the manifest's stack size.
+    pub(crate) fn merge_from(&mut self, other: &mut Litter, item_manifest: &ItemManifest) {
+        // Partial transfers are expected: anything that does not fit stays in `other`
+        let _ = other
+            .contents
+            .transfer_all(&mut self.contents, item_manifest);
+    }
+
+    /// Can some of the items in `other` be merged into this pile?
+    pub(crate) fn can_merge(&self, other: &Litter) -> bool {
+        let item_id = |litter: &Litter| litter.contents.iter().next().map(|slot| slot.item_id());
+
+        !self.contents.is_full() && item_id(other).is_some() && item_id(self) == item_id(other)
+    }
+}
+
 impl Default for Litter {
     fn default() -> Self {
         Litter {
@@ -90,16 +116,29 @@ impl Default for Litter {
 }
 
 /// Updates the signals produced by litter.
-pub(super) fn set_litter_emitters(mut query: Query<(&mut Emitter, Ref<Litter>)>) {
+pub(super) fn set_litter_emitters(
+    mut query: Query<(&mut Emitter, Ref<Litter>)>,
+    item_manifest: Res<ItemManifest>,
+    corpse_policy: Res<CorpsePolicy>,
+) {
     for (mut emitter, litter) in query.iter_mut() {
-        if litter.is_changed() {
+        if litter.is_changed() || corpse_policy.is_changed() {
             emitter.signals.clear();
             for item_slot in litter.contents.iter() {
-                let item_kind = ItemKind::Single(item_slot.item_id());
-
-                let signal_type = match litter.contents.is_full() {
-                    true => SignalType::Push(item_kind),
-                    false => SignalType::Contains(item_kind),
+                let item_id = item_slot.item_id();
+                let item_kind = ItemKind::Single(item_id);
+
+                let signal_type = if item_manifest.has_tag(item_id, ItemTag::Corpse) {
+                    match corpse_policy.get(item_id) {
+                        // Corpses that are left to decay shouldn't attract haulers
+                        CorpseHandling::LeaveToDecay => continue,
+                        CorpseHandling::HaulAway => SignalType::Push(item_kind),
+                    }
+                } else {
+                    match litter.contents.is_full() {
+                        true => SignalType::Push(item_kind),
+                        false => SignalType::Contains(item_kind),
+                    }
                 };
                 let signal_strength = SignalStrength::new(10.);
 
@@ -114,14 +153,62 @@ pub(super) fn set_litter_emitters(mut query: Query<(&mut Emitter, Ref<Litter>)>)
 pub(crate) struct LitterEmitters;
 
 /// Litter entities with empty content should be despawned.
-pub(super) fn clear_empty_litter(query: Query<(Entity, &Litter)>, mut commands: Commands) {
-    for (entity, litter) in query.iter() {
+pub(super) fn clear_empty_litter(
+    query: Query<(Entity, &Litter, &VoxelPos)>,
+    mut map_geometry: ResMut<MapGeometry>,
+    mut commands: Commands,
+) {
+    for (entity, litter, &voxel_pos) in query.iter() {
         if litter.contents.is_empty() {
+            // Only clear the index if it still points to this entity
+            if map_geometry
+                .get_voxel(voxel_pos)
+                .is_some_and(|voxel_object| voxel_object.entity == entity)
+            {
+                map_geometry.remove_litter(voxel_pos);
+            }
+
             commands.entity(entity).despawn_recursive();
         }
     }
 }
 
+/// Combines piles of the same item that are stacked in the same hex, up to the item's stack size.
+///
+/// Without this, every dropped item would remain its own entity.
+pub(super) fn merge_litter_piles(
+    mut query: Query<(Entity, &VoxelPos, &mut Litter)>,
+    item_manifest: Res<ItemManifest>,
+) {
+    let mut piles_by_hex: HashMap<Hex, Vec<Entity>> = HashMap::new();
+    for (entity, voxel_pos, litter) in query.iter() {
+        if !litter.contents.is_empty() {
+            piles_by_hex.entry(voxel_pos.hex).or_default().push(entity);
+        }
+    }
+
+    for piles in piles_by_hex.values() {
+        if piles.len() < 2 {
+            continue;
+        }
+
+        for (i, &target_entity) in piles.iter().enumerate() {
+            for &source_entity in &piles[i + 1..] {
+                let Ok([(.., mut target), (.., mut source)]) =
+                    query.get_many_mut([target_entity, source_entity])
+                else {
+                    continue;
+                };
+
+                // Check before merging to avoid spurious change detection
+                if target.can_merge(&source) {
+                    target.merge_from(&mut source, &item_manifest);
+                }
+            }
+        }
+    }
+}
+
 /// Make litter in tiles submerged by water float (and stop it from floating when there's no water).
 pub(super) fn make_litter_float(
     mut query: Query<(&mut Floating, &mut VoxelPos), With<Litter>>,
@@ -195,6 +282,7 @@ pub(super) fn carry_floating_litter_with_current(
     net_query: Query<&Footprint, With<AbsorbsItems>>,
     time: Res<Time>,
     mut map_geometry: ResMut<MapGeometry>,
+    mut system_rng: SystemRng,
 ) {
     /// Controls how fast litter drifts with the current
     ///
@@ -215,7 +303,7 @@ pub(super) fn carry_floating_litter_with_current(
     const MAX_DRIFT_TIME: f32 = 10.0;
 
     let delta_time = time.delta();
-    let rng = &mut thread_rng();
+    let rng = system_rng.get("carry_floating_litter_with_current");
     let normal_distribution = Normal::new(0.0, DRIFT_DEVIATION).unwrap();
 
     for (voxel_pos, mut litter_drift, water_depth, flow_velocity, floating) in
@@ -251,7 +339,7 @@ pub(super) fn carry_floating_litter_with_current(
 
             // If the litter is not already drifting, start it drifting
             if litter_drift.direction.is_none() {
-                let direction = Direction::from_angle(flow_direction, MAP_LAYOUT.orientation);
+                let direction = direction_from_angle(flow_direction);
                 let time_to_drift = (1. / (ITEM_DRIFT_RATE * water_speed)).min(MAX_DRIFT_TIME);
 
                 litter_drift.start(direction, Duration::from_secs_f32(time_to_drift));
@@ -317,9 +405,32 @@ struct SpawnLitterCommand {
 
 impl Command for SpawnLitterCommand {
     fn apply(self, world: &mut World) {
-        let item_manifest = world.resource::<ItemManifest>();
+        let litter_in_column: Vec<Entity> = world
+            .resource::<MapGeometry>()
+            .objects_in_column(self.voxel_pos.hex)
+            .filter(|voxel_object| matches!(voxel_object.object_kind, VoxelKind::Litter { .. }))
+            .map(|voxel_object| voxel_object.entity)
+            .collect();
+
+        let mut litter = Litter::new(self.item, world.resource::<ItemManifest>());
+
+        // Top up existing piles of the same item rather than spawning a new entity
+        world.resource_scope(|world, item_manifest: Mut<ItemManifest>| {
+            for existing_entity in litter_in_column {
+                let Some(mut existing_litter) = world.get_mut::<Litter>(existing_entity) else {
+                    continue;
+                };
+
+                if existing_litter.can_merge(&litter) {
+                    existing_litter.merge_from(&mut litter, &item_manifest);
+                }
+            }
+        });
 
-        let litter = Litter::new(self.item, item_manifest);
+        // Anything that did not fit is spawned as a new pile, which is placed on a neighbouring tile if this one is full
+        if litter.contents.is_empty() {
+            return;
+        }
 
         let terrain_handles = world.resource::<TerrainHandles>();
         let litter_models = &terrain_handles.litter_models;
@@ -340,6 +451,7 @@ impl Command for SpawnLitterCommand {
                 voxel_pos: self.voxel_pos,
                 scene_bundle,
                 floating: Floating(false),
+                decomposition: Decomposition::default(),
             })
             .id();
 
@@ -352,3 +464,61 @@ impl Command for SpawnLitterCommand {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::item_manifest;
+
+    /// Creates a pile of `count` items of the type `item_id`.
+    fn pile(item_id: Id<Item>, count: u32, item_manifest: &ItemManifest) -> Litter {
+        let mut litter = Litter::default();
+        litter
+            .contents
+            .add_item_all_or_nothing(&ItemCount::new(item_id, count), item_manifest)
+            .unwrap();
+        litter
+    }
+
+    #[test]
+    fn piles_of_the_same_item_merge() {
+        let item_manifest = item_manifest();
+        let leaf = Id::from_name("leaf".to_string());
+
+        let mut target = pile(leaf, 3, &item_manifest);
+        let mut source = pile(leaf, 4, &item_manifest);
+
+        assert!(target.can_merge(&source));
+        target.merge_from(&mut source, &item_manifest);
+
+        assert_eq!(target.contents.item_count(leaf), 7);
+        assert!(source.contents.is_empty());
+    }
+
+    #[test]
+    fn merging_respects_stack_size() {
+        let item_manifest = item_manifest();
+        let leaf = Id::from_name("leaf".to_string());
+
+        let mut target = pile(leaf, 8, &item_manifest);
+        let mut source = pile(leaf, 5, &item_manifest);
+
+        target.merge_from(&mut source, &item_manifest);
+
+        assert_eq!(target.contents.item_count(leaf), 10);
+        assert_eq!(source.contents.item_count(leaf), 3);
+        assert!(!target.can_merge(&source));
+    }
+
+    #[test]
+    fn piles_of_different_items_do_not_merge() {
+        let item_manifest = item_manifest();
+        let leaf = Id::from_name("leaf".to_string());
+        let mushroom = Id::from_name("mushroom".to_string());
+
+        let target = pile(leaf, 3, &item_manifest);
+        let source = pile(mushroom, 4, &item_manifest);
+
+        assert!(!target.can_merge(&source));
+    }
+}
@@ -0,0 +1,1053 @@
+//! Matches structures that need items with those that can provide them, and hands the resulting jobs to idle units.
+//!
+//! Signals alone tell units where items are wanted, but not which pile they should come from.
+//! Left to follow the strongest local gradient, units will happily carry items from one storage to the next and back again.
+//!
+//! Instead, each tick:
+//! 1. Input inventories publish an [`ItemRequest`] for each item they are missing,
+//!     while output and storage inventories publish an [`ItemOffer`] for each item they hold.
+//! 2. Requests are paired with offers, highest priority first, then nearest first, producing [`HaulingMatch`]es.
+//! 3. Idle units near the source of a match are given a [`HaulingJob`], which directs them from the source to the destination.
+//!
+//! Only requests generate jobs, so items are never moved from storage to storage.
+//!
+//! Structures can choose where their outputs go with [`OutputRouting`].
+//! Routed outputs are only offered to the chosen storage, which publishes a matching request on their behalf,
+//! so that separate supply chains can be kept apart on the map.
+//!
+//! Each unit given a job [reserves](crate::items::inventory::Reservation) the item it is sent for,
+//! so no two units are ever dispatched for the same item.
+//! Items reserved by a [`CraftOrder`](crate::crafting::orders::CraftOrder) are only offered to the crafters working on that order.
+//! Reservations are released once the item is picked up, and lapse if the job is abandoned or the unit dies.
+//!
+//! The player can tune which requests are served first with [`HaulingPriorities`],
+//! and override these for individual structures with [`HaulingPriorityOverride`].
+//! Crafters working on a [`CraftOrder`](crate::crafting::orders::CraftOrder) have their requests served first,
+//! just like [`Prioritized`] structures.
+
+use std::{fmt::Display, time::Duration};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::demolition::MarkedForDemolition,
+    crafting::{
+        inventories::{InputInventory, OutputInventory, StorageInventory},
+        item_tags::{ItemKind, ItemTag},
+        orders::{CraftOrder, CraftOrderAssignment},
+    },
+    geometry::VoxelPos,
+    items::{
+        inventory::Inventory,
+        item_manifest::{Item, ItemManifest},
+        ItemCount,
+    },
+    player_interaction::bulk_commands::{Disabled, Forbidden, Prioritized},
+    save_files::SaveResourceExt,
+    simulation::SimulationSet,
+    units::{goals::Goal, item_interaction::UnitInventory, unit_manifest::Unit, UnitSystem},
+};
+
+/// Pairs item requests with item offers, and assigns the resulting hauling jobs to units.
+pub(super) struct LogisticsPlugin;
+
+impl Plugin for LogisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LogisticsNetwork>()
+            .init_resource::<HaulingPriorities>()
+            .save_resource::<HaulingPriorities>("hauling_priorities")
+            .add_event::<SetOutputRouting>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    set_output_routing,
+                    update_hauling_jobs,
+                    expire_item_reservations,
+                    publish_requests_and_offers,
+                    match_requests_and_offers,
+                    assign_hauling_jobs,
+                )
+                    .chain()
+                    .after(UnitSystem::Act)
+                    .before(UnitSystem::ChooseGoal)
+                    .in_set(SimulationSet),
+            );
+    }
+}
+
+/// How urgently an item should be moved.
+///
+/// Higher priorities are served first.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum LogisticsPriority {
+    /// Items that can wait, such as those sitting in storage.
+    Low,
+    /// The default priority.
+    #[default]
+    Normal,
+    /// Items that should be moved before anything else, such as deliveries to [`Prioritized`] structures.
+    High,
+}
+
+impl LogisticsPriority {
+    /// The next priority, wrapping around from [`LogisticsPriority::High`] to [`LogisticsPriority::Low`].
+    pub fn cycle(self) -> Self {
+        match self {
+            LogisticsPriority::Low => LogisticsPriority::Normal,
+            LogisticsPriority::Normal => LogisticsPriority::High,
+            LogisticsPriority::High => LogisticsPriority::Low,
+        }
+    }
+}
+
+impl Display for LogisticsPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            LogisticsPriority::Low => "Low",
+            LogisticsPriority::Normal => "Normal",
+            LogisticsPriority::High => "High",
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// The player's chosen priority for delivering each kind of item.
+///
+/// Items without an entry use [`LogisticsPriority::Normal`].
+/// This is part of the game state, and is stored in saves.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HaulingPriorities {
+    /// The priorities that differ from the default.
+    #[serde(with = "crate::save_files::map_as_pairs")]
+    priorities: HashMap<Id<Item>, LogisticsPriority>,
+}
+
+impl HaulingPriorities {
+    /// The priority of delivering `item_id`.
+    pub fn get(&self, item_id: Id<Item>) -> LogisticsPriority {
+        self.priorities.get(&item_id).copied().unwrap_or_default()
+    }
+
+    /// Sets the priority of delivering `item_id`.
+    pub fn set(&mut self, item_id: Id<Item>, priority: LogisticsPriority) {
+        if priority == LogisticsPriority::default() {
+            self.priorities.remove(&item_id);
+        } else {
+            self.priorities.insert(item_id, priority);
+        }
+    }
+
+    /// Moves `item_id` to the next priority, returning the new priority.
+    pub fn cycle(&mut self, item_id: Id<Item>) -> LogisticsPriority {
+        let priority = self.get(item_id).cycle();
+        self.set(item_id, priority);
+        priority
+    }
+}
+
+/// Sets the priority of all deliveries to this structure, ignoring [`HaulingPriorities`].
+///
+/// Structures that are [`Prioritized`] without an override use [`LogisticsPriority::High`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HaulingPriorityOverride(pub LogisticsPriority);
+
+/// Where the outputs of this structure should be taken.
+///
+/// Structures without this component offer their outputs to any structure that requests them.
+/// Items reserved for a [`CraftOrder`] are always offered to the crafters working on that order.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputRouting {
+    /// Keep the outputs here, until the player changes their mind.
+    Hold,
+    /// Only take the outputs to this storage structure.
+    ///
+    /// If the storage is full, forbidden or demolished, the outputs are held instead.
+    Storage(Entity),
+    /// Only take the outputs to the nearest storage structure with room for them.
+    NearestStorage,
+}
+
+impl Display for OutputRouting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            OutputRouting::Hold => "Hold",
+            OutputRouting::Storage(_) => "Chosen storage",
+            OutputRouting::NearestStorage => "Nearest storage",
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// Changes the [`OutputRouting`] of the `structure` entity.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetOutputRouting {
+    /// The structure whose outputs should be routed.
+    pub structure: Entity,
+    /// The new routing, or `None` to offer the outputs to any structure that requests them.
+    pub routing: Option<OutputRouting>,
+}
+
+/// Applies each [`SetOutputRouting`] event.
+///
+/// Only structures with an [`OutputInventory`] can route their outputs,
+/// and they can only be routed to structures with a [`StorageInventory`].
+fn set_output_routing(
+    mut events: EventReader<SetOutputRouting>,
+    output_query: Query<(), With<OutputInventory>>,
+    storage_query: Query<(), With<StorageInventory>>,
+    mut commands: Commands,
+) {
+    for &SetOutputRouting { structure, routing } in events.read() {
+        if !output_query.contains(structure) {
+            continue;
+        }
+
+        match routing {
+            Some(OutputRouting::Storage(storage)) if !storage_query.contains(storage) => (),
+            Some(routing) => {
+                commands.entity(structure).insert(routing);
+            }
+            None => {
+                commands.entity(structure).remove::<OutputRouting>();
+            }
+        }
+    }
+}
+
+/// A structure that needs items delivered.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ItemRequest {
+    /// The structure that needs the items.
+    pub(crate) entity: Entity,
+    /// Where the structure is.
+    pub(crate) voxel_pos: VoxelPos,
+    /// Which items are accepted.
+    pub(crate) item_kind: ItemKind,
+    /// How many more items are needed, after accounting for those already on their way.
+    pub(crate) count: u32,
+    /// How urgently the items are needed.
+    pub(crate) priority: LogisticsPriority,
+    /// The craft order that the structure is working on, if any.
+    pub(crate) order: Option<Entity>,
+    /// The only structure that may supply these items, if this storage is collecting routed outputs.
+    pub(crate) routed_from: Option<Entity>,
+}
+
+/// A structure that has items available to be picked up.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ItemOffer {
+    /// The structure that holds the items.
+    pub(crate) entity: Entity,
+    /// Where the structure is.
+    pub(crate) voxel_pos: VoxelPos,
+    /// Which item is available.
+    pub(crate) item_id: Id<Item>,
+    /// How many are available, after accounting for those already claimed by units.
+    pub(crate) count: u32,
+    /// How eagerly these items should be used.
+    pub(crate) priority: LogisticsPriority,
+    /// The craft order that these items are reserved for, if any.
+    ///
+    /// Reserved items are only offered to structures working on that order.
+    pub(crate) reserved_for: Option<Entity>,
+    /// The only structure that may receive these items, if their source routes its outputs.
+    pub(crate) routed_to: Option<Entity>,
+}
+
+/// A pairing between an [`ItemOffer`] and an [`ItemRequest`], which still needs units to carry it out.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HaulingMatch {
+    /// The item to move.
+    pub(crate) item_id: Id<Item>,
+    /// The number of items still to be assigned to units.
+    pub(crate) count: u32,
+    /// The structure to pick the items up from.
+    pub(crate) source: Entity,
+    /// Where the source is.
+    pub(crate) source_pos: VoxelPos,
+    /// The structure to drop the items off at.
+    pub(crate) destination: Entity,
+    /// Where the destination is.
+    pub(crate) destination_pos: VoxelPos,
+    /// The craft order that the items are reserved for, if any.
+    pub(crate) reserved_for: Option<Entity>,
+}
+
+/// The current state of supply and demand for items across the colony.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct LogisticsNetwork {
+    /// Items that structures need, which no unit is already bringing.
+    requests: Vec<ItemRequest>,
+    /// Items that structures can give up, which no unit has claimed.
+    offers: Vec<ItemOffer>,
+    /// Pairings of requests and offers that are waiting for units.
+    matches: Vec<HaulingMatch>,
+}
+
+/// A single item that a unit has been asked to carry from one structure to another.
+#[derive(Component, Debug, Clone, PartialEq)]
+#[component(storage = "SparseSet")]
+pub(crate) struct HaulingJob {
+    /// The item to move.
+    pub(crate) item_id: Id<Item>,
+    /// The structure to pick the item up from.
+    pub(crate) source: Entity,
+    /// Where the source is.
+    pub(crate) source_pos: VoxelPos,
+    /// The structure to drop the item off at.
+    pub(crate) destination: Entity,
+    /// Where the destination is.
+    pub(crate) destination_pos: VoxelPos,
+}
+
+impl HaulingJob {
+    /// The structure that a unit with this job should interact with next, and where it is.
+    pub(crate) fn target(&self, unit_inventory: &UnitInventory) -> (Entity, VoxelPos) {
+        match unit_inventory.held_item() {
+            Some(_) => (self.destination, self.destination_pos),
+            None => (self.source, self.source_pos),
+        }
+    }
+}
+
+/// How long a unit's claim on the item it was sent for lasts, if it never picks the item up.
+const HAULING_RESERVATION_DURATION: Duration = Duration::from_secs(60);
+
+/// Structures that units pick items up from.
+type SourceInventoryQuery<'w, 's> =
+    Query<'w, 's, AnyOf<(&'static mut OutputInventory, &'static mut StorageInventory)>>;
+
+/// Applies `f` to the inventory that `entity` offers items from, if it has one.
+fn with_source_inventory(
+    source_query: &mut SourceInventoryQuery,
+    entity: Entity,
+    f: impl FnOnce(&mut Inventory),
+) {
+    match source_query.get_mut(entity) {
+        Ok((Some(mut output_inventory), _)) => f(&mut output_inventory.inventory),
+        Ok((None, Some(mut storage_inventory))) => f(&mut storage_inventory.inventory),
+        _ => (),
+    }
+}
+
+/// Drops jobs that have been completed or abandoned, and keeps units that are carrying out a job on track.
+///
+/// Units release their reservation on the source once they have picked up their item, or when their job is dropped.
+fn update_hauling_jobs(
+    mut unit_query: Query<(Entity, &mut Goal, &UnitInventory, &HaulingJob)>,
+    structure_query: Query<(), Without<MarkedForDemolition>>,
+    mut source_query: SourceInventoryQuery,
+    storage_query: Query<(), With<StorageInventory>>,
+    mut commands: Commands,
+) {
+    for (unit_entity, mut goal, unit_inventory, job) in unit_query.iter_mut() {
+        let endpoints_exist =
+            structure_query.contains(job.source) && structure_query.contains(job.destination);
+
+        let on_track = match (&*goal, unit_inventory.held_item()) {
+            (Goal::Fetch(..), None) => true,
+            (Goal::Deliver(..) | Goal::Store(..), Some(held_item)) => held_item == job.item_id,
+            _ => false,
+        };
+
+        if !endpoints_exist || !on_track || unit_inventory.held_item().is_some() {
+            with_source_inventory(&mut source_query, job.source, |inventory| {
+                if inventory.reserved_by(unit_entity, job.item_id) > 0 {
+                    inventory.release_all(unit_entity);
+                }
+            });
+        }
+
+        if !endpoints_exist || !on_track {
+            commands.entity(unit_entity).remove::<HaulingJob>();
+            continue;
+        }
+
+        // Once the item has been picked up, take it to the structure that asked for it,
+        // rather than wherever the local signals suggest
+        if unit_inventory.held_item().is_some() {
+            let item_kind = ItemKind::Single(job.item_id);
+            // Units only drop items off in storage when storing them
+            goal.set_if_neq(match storage_query.contains(job.destination) {
+                true => Goal::Store(item_kind),
+                false => Goal::Deliver(item_kind),
+            });
+        }
+    }
+}
+
+/// Drops reservations whose time has run out, or whose holder no longer needs them.
+///
+/// Units that die or abandon their job without picking up their item would otherwise lock it away forever.
+fn expire_item_reservations(
+    mut source_query: SourceInventoryQuery,
+    job_query: Query<&HaulingJob>,
+    order_query: Query<(), With<CraftOrder>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed();
+    for (maybe_output, maybe_storage) in source_query.iter_mut() {
+        // Only flag the inventory as changed if there is something to expire
+        let mut inventory = match (maybe_output, maybe_storage) {
+            (Some(output), _) => output.map_unchanged(|output| &mut output.inventory),
+            (None, Some(storage)) => storage.map_unchanged(|storage| &mut storage.inventory),
+            (None, None) => continue,
+        };
+
+        if inventory.reservations().next().is_none() {
+            continue;
+        }
+
+        inventory.expire_reservations(now, |holder| {
+            order_query.contains(holder) || job_query.contains(holder)
+        });
+    }
+}
+
+/// Records which items each structure needs and which it can spare.
+///
+/// Items that units are already carrying as part of a [`HaulingJob`] are subtracted from requests,
+/// so that a single request does not attract a crowd of haulers.
+/// Reserved items are left out of offers, unless they are reserved for a craft order.
+///
+/// Outputs routed to storage are only offered to that storage,
+/// which requests as many of them as it has room for.
+fn publish_requests_and_offers(
+    input_query: Query<
+        (
+            Entity,
+            &VoxelPos,
+            &InputInventory,
+            Option<&HaulingPriorityOverride>,
+            Has<Prioritized>,
+            Option<&CraftOrderAssignment>,
+        ),
+        (
+            Without<Forbidden>,
+            Without<Disabled>,
+            Without<MarkedForDemolition>,
+        ),
+    >,
+    output_query: Query<
+        (
+            Entity,
+            &VoxelPos,
+            &OutputInventory,
+            Has<Prioritized>,
+            Option<&OutputRouting>,
+        ),
+        Without<Forbidden>,
+    >,
+    storage_query: Query<
+        (Entity, &VoxelPos, &StorageInventory, Has<Prioritized>),
+        Without<Forbidden>,
+    >,
+    routed_storage_query: Query<
+        (Entity, &VoxelPos, &StorageInventory),
+        (
+            Without<Forbidden>,
+            Without<Disabled>,
+            Without<MarkedForDemolition>,
+        ),
+    >,
+    job_query: Query<&HaulingJob>,
+    order_query: Query<(), With<CraftOrder>>,
+    item_manifest: Res<ItemManifest>,
+    hauling_priorities: Res<HaulingPriorities>,
+    mut logistics_network: ResMut<LogisticsNetwork>,
+) {
+    let mut in_flight_to: HashMap<(Entity, Id<Item>), u32> = HashMap::default();
+    for job in job_query.iter() {
+        *in_flight_to
+            .entry((job.destination, job.item_id))
+            .or_default() += 1;
+    }
+
+    let priority = |prioritized: bool, default: LogisticsPriority| match prioritized {
+        true => LogisticsPriority::High,
+        false => default,
+    };
+
+    let mut requests = Vec::new();
+    for (entity, &voxel_pos, input_inventory, maybe_override, prioritized, maybe_assignment) in
+        input_query.iter()
+    {
+        let order = maybe_assignment.map(|assignment| assignment.order);
+        // Inputs for craft orders jump the queue
+        let prioritized = prioritized || order.is_some();
+        let inventory = input_inventory.inventory();
+        let mut push_request = |item_kind: ItemKind, missing: u32| {
+            let in_flight = match item_kind {
+                ItemKind::Single(item_id) => {
+                    in_flight_to.get(&(entity, item_id)).copied().unwrap_or(0)
+                }
+                ItemKind::Tag(_) => in_flight_to
+                    .iter()
+                    .filter(|((destination, _), _)| *destination == entity)
+                    .map(|(_, count)| *count)
+                    .sum(),
+            };
+
+            let count = missing.saturating_sub(in_flight);
+            if count > 0 {
+                requests.push(ItemRequest {
+                    entity,
+                    voxel_pos,
+                    item_kind,
+                    count,
+                    priority: request_priority(
+                        item_kind,
+                        maybe_override,
+                        prioritized,
+                        &hauling_priorities,
+                    ),
+                    order,
+                    routed_from: None,
+                });
+            }
+        };
+
+        match input_inventory {
+            InputInventory::Exact { .. } => {
+                for slot in inventory.iter() {
+                    // Fluids cannot be carried by units
+                    if item_manifest.has_tag(slot.item_id(), ItemTag::Fluid) {
+                        continue;
+                    }
+
+                    push_request(ItemKind::Single(slot.item_id()), slot.remaining_space());
+                }
+            }
+            InputInventory::Tagged { tag, .. } => match inventory.iter().next() {
+                // Once an item has been chosen, only more of the same item will fit
+                Some(slot) => {
+                    push_request(ItemKind::Single(slot.item_id()), slot.remaining_space())
+                }
+                None if !inventory.is_full() => push_request(ItemKind::Tag(*tag), 1),
+                None => (),
+            },
+        }
+    }
+
+    // Room in storage that has already been promised to routed outputs this tick
+    let mut promised_to: HashMap<(Entity, Id<Item>), u32> = HashMap::default();
+    let free_space = |storage_entity: Entity,
+                      storage: &StorageInventory,
+                      item_id: Id<Item>,
+                      promised_to: &HashMap<(Entity, Id<Item>), u32>| {
+        let key = (storage_entity, item_id);
+        storage
+            .remaining_space_for_item(item_id, &item_manifest)
+            .saturating_sub(in_flight_to.get(&key).copied().unwrap_or(0))
+            .saturating_sub(promised_to.get(&key).copied().unwrap_or(0))
+    };
+
+    let mut offers = Vec::new();
+    let inventories = output_query
+        .iter()
+        .map(|(entity, voxel_pos, output, prioritized, maybe_routing)| {
+            let priority = priority(prioritized, LogisticsPriority::Normal);
+            (
+                entity,
+                voxel_pos,
+                &output.inventory,
+                priority,
+                maybe_routing.copied(),
+            )
+        })
+        .chain(
+            storage_query
+                .iter()
+                .map(|(entity, voxel_pos, storage, prioritized)| {
+                    let priority = priority(prioritized, LogisticsPriority::Low);
+                    (entity, voxel_pos, &storage.inventory, priority, None)
+                }),
+        );
+
+    for (entity, &voxel_pos, inventory, priority, maybe_routing) in inventories {
+        let mut item_ids: Vec<Id<Item>> = inventory.iter().map(|slot| slot.item_id()).collect();
+        item_ids.sort();
+        item_ids.dedup();
+
+        for item_id in item_ids {
+            if item_manifest.has_tag(item_id, ItemTag::Fluid) {
+                continue;
+            }
+
+            let mut push_offer =
+                |count: u32, reserved_for: Option<Entity>, routed_to: Option<Entity>| {
+                    if count > 0 {
+                        offers.push(ItemOffer {
+                            entity,
+                            voxel_pos,
+                            item_id,
+                            count,
+                            priority,
+                            reserved_for,
+                            routed_to,
+                        });
+                    }
+                };
+
+            let unreserved = inventory.unreserved_item_count(item_id);
+            let destination = match maybe_routing {
+                None => {
+                    push_offer(unreserved, None, None);
+                    None
+                }
+                Some(OutputRouting::Hold) => None,
+                Some(OutputRouting::Storage(storage_entity)) => routed_storage_query
+                    .get(storage_entity)
+                    .ok()
+                    .filter(|(_, _, storage)| storage.currently_accepts(item_id, &item_manifest)),
+                Some(OutputRouting::NearestStorage) => routed_storage_query
+                    .iter()
+                    .filter(|&(storage_entity, _, storage)| {
+                        storage.currently_accepts(item_id, &item_manifest)
+                            && free_space(storage_entity, storage, item_id, &promised_to) > 0
+                    })
+                    .min_by_key(|(_, storage_pos, _)| {
+                        storage_pos.hex.unsigned_distance_to(voxel_pos.hex)
+                    }),
+            };
+
+            if let Some((storage_entity, &storage_pos, storage)) = destination {
+                let count =
+                    unreserved.min(free_space(storage_entity, storage, item_id, &promised_to));
+                if count > 0 {
+                    push_offer(count, None, Some(storage_entity));
+                    *promised_to.entry((storage_entity, item_id)).or_default() += count;
+                    requests.push(ItemRequest {
+                        entity: storage_entity,
+                        voxel_pos: storage_pos,
+                        item_kind: ItemKind::Single(item_id),
+                        count,
+                        priority: LogisticsPriority::Low,
+                        order: None,
+                        routed_from: Some(entity),
+                    });
+                }
+            }
+
+            for reservation in inventory.reservations() {
+                if reservation.item_count.item_id == item_id
+                    && order_query.contains(reservation.holder)
+                {
+                    push_offer(reservation.item_count.count, Some(reservation.holder), None);
+                }
+            }
+        }
+    }
+
+    logistics_network.requests = requests;
+    logistics_network.offers = offers;
+}
+
+/// The priority of a request for `item_kind`.
+///
+/// Per-structure settings take precedence over the player's per-item [`HaulingPriorities`].
+fn request_priority(
+    item_kind: ItemKind,
+    maybe_override: Option<&HaulingPriorityOverride>,
+    prioritized: bool,
+    hauling_priorities: &HaulingPriorities,
+) -> LogisticsPriority {
+    if let Some(&HaulingPriorityOverride(priority)) = maybe_override {
+        return priority;
+    }
+
+    if prioritized {
+        return LogisticsPriority::High;
+    }
+
+    match item_kind {
+        ItemKind::Single(item_id) => hauling_priorities.get(item_id),
+        ItemKind::Tag(_) => LogisticsPriority::default(),
+    }
+}
+
+/// Pairs the published requests and offers into [`HaulingMatch`]es.
+fn match_requests_and_offers(
+    mut logistics_network: ResMut<LogisticsNetwork>,
+    item_manifest: Res<ItemManifest>,
+) {
+    let logistics_network = &mut *logistics_network;
+    logistics_network.matches = match_requests(
+        &logistics_network.requests,
+        &logistics_network.offers,
+        &item_manifest,
+    );
+}
+
+/// Greedily pairs `requests` with `offers`.
+///
+/// Requests are served in order of decreasing priority.
+/// Each request draws from the highest priority offers first, breaking ties by distance.
+/// No offer is ever promised more items than it holds, and no request is sent more items than it needs.
+/// Offers reserved for a craft order are only paired with requests from that order,
+/// and routed offers are only paired with requests from the storage they are routed to.
+pub(crate) fn match_requests(
+    requests: &[ItemRequest],
+    offers: &[ItemOffer],
+    item_manifest: &ItemManifest,
+) -> Vec<HaulingMatch> {
+    let mut remaining_offers: Vec<u32> = offers.iter().map(|offer| offer.count).collect();
+
+    let mut sorted_requests: Vec<&ItemRequest> = requests.iter().collect();
+    // Stable, so requests of the same priority are served in a consistent order
+    sorted_requests.sort_by(|a, b| b.priority.cmp(&a.priority));
+
+    let mut matches = Vec::new();
+
+    for request in sorted_requests {
+        let mut still_needed = request.count;
+
+        while still_needed > 0 {
+            let best_offer = offers
+                .iter()
+                .enumerate()
+                .filter(|&(i, offer)| {
+                    remaining_offers[i] > 0
+                        && offer.entity != request.entity
+                        && offer
+                            .reserved_for
+                            .map_or(true, |order| request.order == Some(order))
+                        && offer
+                            .routed_to
+                            .map_or(true, |destination| destination == request.entity)
+                        && request
+                            .routed_from
+                            .map_or(true, |source| source == offer.entity)
+                        && request.item_kind.matches(offer.item_id, item_manifest)
+                })
+                .min_by_key(|(_, offer)| {
+                    (
+                        std::cmp::Reverse(offer.priority),
+                        offer
+                            .voxel_pos
+                            .hex
+                            .unsigned_distance_to(request.voxel_pos.hex),
+                    )
+                });
+
+            let Some((i, offer)) = best_offer else {
+                break;
+            };
+
+            let count = still_needed.min(remaining_offers[i]);
+            remaining_offers[i] -= count;
+            still_needed -= count;
+
+            matches.push(HaulingMatch {
+                item_id: offer.item_id,
+                count,
+                source: offer.entity,
+                source_pos: offer.voxel_pos,
+                destination: request.entity,
+                destination_pos: request.voxel_pos,
+                reserved_for: offer.reserved_for,
+            });
+        }
+    }
+
+    matches
+}
+
+/// The furthest that a unit will be sent to start a [`HaulingJob`], in tiles.
+///
+/// Jobs that are further away from every idle unit are left for units to discover by following signals.
+const MAX_ASSIGNMENT_DISTANCE: u32 = 15;
+
+/// Hands out [`HaulingJob`]s to the nearest idle unit, one item at a time.
+///
+/// Each unit reserves the item it is sent for, taking it over from the craft order that held it if needed.
+fn assign_hauling_jobs(
+    mut unit_query: Query<
+        (Entity, &VoxelPos, &mut Goal, &UnitInventory),
+        (With<Id<Unit>>, Without<HaulingJob>),
+    >,
+    mut source_query: SourceInventoryQuery,
+    mut logistics_network: ResMut<LogisticsNetwork>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let expires_at = time.elapsed() + HAULING_RESERVATION_DURATION;
+
+    let mut idle_units: Vec<(Entity, VoxelPos, Mut<Goal>)> = unit_query
+        .iter_mut()
+        .filter(|(_, _, goal, unit_inventory)| {
+            matches!(**goal, Goal::Wander { .. }) && unit_inventory.held_item().is_none()
+        })
+        .map(|(entity, &voxel_pos, goal, _)| (entity, voxel_pos, goal))
+        .collect();
+
+    for hauling_match in logistics_network.matches.iter_mut() {
+        while hauling_match.count > 0 {
+            let nearest_unit = idle_units
+                .iter()
+                .enumerate()
+                .map(|(i, (_, voxel_pos, _))| {
+                    (
+                        i,
+                        voxel_pos
+                            .hex
+                            .unsigned_distance_to(hauling_match.source_pos.hex),
+                    )
+                })
+                .filter(|&(_, distance)| distance <= MAX_ASSIGNMENT_DISTANCE)
+                .min_by_key(|&(_, distance)| distance);
+
+            let Some((i, _)) = nearest_unit else {
+                break;
+            };
+
+            let one_item = ItemCount::new(hauling_match.item_id, 1);
+            let mut reserved = false;
+            with_source_inventory(&mut source_query, hauling_match.source, |inventory| {
+                if let Some(order) = hauling_match.reserved_for {
+                    inventory.release(order, &one_item);
+                }
+                reserved = inventory
+                    .reserve(idle_units[i].0, &one_item, expires_at)
+                    .is_ok();
+            });
+
+            // The items were taken by something outside of the logistics network
+            if !reserved {
+                break;
+            }
+
+            let (unit_entity, _, mut goal) = idle_units.swap_remove(i);
+            *goal = Goal::Fetch(ItemKind::Single(hauling_match.item_id));
+            commands.entity(unit_entity).insert(HaulingJob {
+                item_id: hauling_match.item_id,
+                source: hauling_match.source,
+                source_pos: hauling_match.source_pos,
+                destination: hauling_match.destination,
+                destination_pos: hauling_match.destination_pos,
+            });
+
+            hauling_match.count -= 1;
+        }
+    }
+
+    logistics_network
+        .matches
+        .retain(|hauling_match| hauling_match.count > 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{geometry::DiscreteHeight, testing::item_manifest};
+    use hexx::Hex;
+
+    fn leaf() -> Id<Item> {
+        Id::from_name("leaf".to_string())
+    }
+
+    fn voxel_pos(x: i32) -> VoxelPos {
+        VoxelPos {
+            hex: Hex::new(x, 0),
+            height: DiscreteHeight::ZERO,
+        }
+    }
+
+    fn request(index: u32, x: i32, count: u32, priority: LogisticsPriority) -> ItemRequest {
+        ItemRequest {
+            entity: Entity::from_raw(index),
+            voxel_pos: voxel_pos(x),
+            item_kind: ItemKind::Single(leaf()),
+            count,
+            priority,
+            order: None,
+            routed_from: None,
+        }
+    }
+
+    fn offer(index: u32, x: i32, count: u32, priority: LogisticsPriority) -> ItemOffer {
+        ItemOffer {
+            entity: Entity::from_raw(index),
+            voxel_pos: voxel_pos(x),
+            item_id: leaf(),
+            count,
+            priority,
+            reserved_for: None,
+            routed_to: None,
+        }
+    }
+
+    #[test]
+    fn nearest_offer_is_used_first() {
+        let requests = [request(0, 0, 3, LogisticsPriority::Normal)];
+        let offers = [
+            offer(1, 10, 5, LogisticsPriority::Normal),
+            offer(2, 2, 5, LogisticsPriority::Normal),
+        ];
+
+        let matches = match_requests(&requests, &offers, &item_manifest());
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].source, Entity::from_raw(2));
+        assert_eq!(matches[0].count, 3);
+    }
+
+    #[test]
+    fn storage_is_only_used_when_nothing_else_is_available() {
+        let requests = [request(0, 0, 4, LogisticsPriority::Normal)];
+        let offers = [
+            offer(1, 1, 5, LogisticsPriority::Low),
+            offer(2, 8, 3, LogisticsPriority::Normal),
+        ];
+
+        let matches = match_requests(&requests, &offers, &item_manifest());
+        assert_eq!(matches.len(), 2);
+        assert_eq!(
+            (matches[0].source, matches[0].count),
+            (Entity::from_raw(2), 3)
+        );
+        assert_eq!(
+            (matches[1].source, matches[1].count),
+            (Entity::from_raw(1), 1)
+        );
+    }
+
+    #[test]
+    fn high_priority_requests_are_served_first() {
+        let requests = [
+            request(0, 1, 4, LogisticsPriority::Normal),
+            request(1, 9, 4, LogisticsPriority::High),
+        ];
+        let offers = [offer(2, 0, 5, LogisticsPriority::Normal)];
+
+        let matches = match_requests(&requests, &offers, &item_manifest());
+        assert_eq!(matches.len(), 2);
+        assert_eq!(
+            (matches[0].destination, matches[0].count),
+            (Entity::from_raw(1), 4)
+        );
+        assert_eq!(
+            (matches[1].destination, matches[1].count),
+            (Entity::from_raw(0), 1)
+        );
+    }
+
+    #[test]
+    fn structure_overrides_beat_item_priorities() {
+        let mut hauling_priorities = HaulingPriorities::default();
+        assert_eq!(hauling_priorities.cycle(leaf()), LogisticsPriority::High);
+
+        let leaves = ItemKind::Single(leaf());
+        assert_eq!(
+            request_priority(leaves, None, false, &hauling_priorities),
+            LogisticsPriority::High
+        );
+        assert_eq!(
+            request_priority(
+                leaves,
+                Some(&HaulingPriorityOverride(LogisticsPriority::Low)),
+                true,
+                &hauling_priorities
+            ),
+            LogisticsPriority::Low
+        );
+        assert_eq!(
+            request_priority(
+                ItemKind::Tag(ItemTag::Seed),
+                None,
+                true,
+                &hauling_priorities
+            ),
+            LogisticsPriority::High
+        );
+
+        // Cycling back to the default forgets the setting
+        hauling_priorities.cycle(leaf());
+        hauling_priorities.cycle(leaf());
+        assert_eq!(hauling_priorities, HaulingPriorities::default());
+    }
+
+    #[test]
+    fn hauling_priorities_survive_saving_and_loading() {
+        use crate::save_files::{load_world, save_world};
+
+        let mut app = App::new();
+        app.init_resource::<HaulingPriorities>()
+            .save_resource::<HaulingPriorities>("hauling_priorities");
+        app.world.resource_mut::<HaulingPriorities>().cycle(leaf());
+        let saved = save_world(&app.world).unwrap();
+
+        let mut loaded_app = App::new();
+        loaded_app
+            .init_resource::<HaulingPriorities>()
+            .save_resource::<HaulingPriorities>("hauling_priorities");
+        load_world(&mut loaded_app.world, &saved).unwrap();
+
+        assert_eq!(
+            loaded_app.world.resource::<HaulingPriorities>().get(leaf()),
+            LogisticsPriority::High
+        );
+        assert_eq!(
+            *loaded_app.world.resource::<HaulingPriorities>(),
+            *app.world.resource::<HaulingPriorities>()
+        );
+    }
+
+    #[test]
+    fn items_are_not_delivered_to_their_source_or_to_the_wrong_kind() {
+        let mut wrong_kind = request(1, 1, 2, LogisticsPriority::Normal);
+        wrong_kind.item_kind = ItemKind::Tag(ItemTag::Seed);
+        let requests = [request(0, 0, 2, LogisticsPriority::Normal), wrong_kind];
+        let offers = [offer(0, 0, 5, LogisticsPriority::Normal)];
+
+        assert!(match_requests(&requests, &offers, &item_manifest()).is_empty());
+    }
+
+    #[test]
+    fn reserved_items_are_only_offered_to_their_order() {
+        let order = Entity::from_raw(10);
+        let mut on_order = request(1, 9, 2, LogisticsPriority::Normal);
+        on_order.order = Some(order);
+        let requests = [request(0, 1, 2, LogisticsPriority::High), on_order];
+
+        let mut reserved = offer(2, 0, 2, LogisticsPriority::Normal);
+        reserved.reserved_for = Some(order);
+        let offers = [reserved];
+
+        let matches = match_requests(&requests, &offers, &item_manifest());
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].destination, Entity::from_raw(1));
+        assert_eq!(matches[0].reserved_for, Some(order));
+    }
+
+    #[test]
+    fn routed_outputs_only_go_to_their_storage() {
+        let storage = Entity::from_raw(3);
+        let mut storage_request = request(3, 9, 5, LogisticsPriority::Low);
+        storage_request.routed_from = Some(Entity::from_raw(2));
+        let requests = [
+            request(0, 1, 2, LogisticsPriority::High),
+            storage_request.clone(),
+        ];
+
+        let mut routed = offer(2, 0, 2, LogisticsPriority::Normal);
+        routed.routed_to = Some(storage);
+        let offers = [routed, offer(1, 5, 5, LogisticsPriority::Low)];
+
+        let matches = match_requests(&requests, &offers, &item_manifest());
+        assert_eq!(matches.len(), 2);
+        assert_eq!(
+            (matches[0].source, matches[0].destination),
+            (Entity::from_raw(1), Entity::from_raw(0))
+        );
+        assert_eq!(
+            (matches[1].source, matches[1].destination, matches[1].count),
+            (Entity::from_raw(2), storage, 2)
+        );
+    }
+}
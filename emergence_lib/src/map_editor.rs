@@ -0,0 +1,271 @@
+//! Paints hand-made maps, which can be saved as a [`Scenario`] and played from the new game menu.
+//!
+//! The editor runs in [`GameState::MapEditor`], where the simulation is stopped and nothing costs anything.
+//! Tiles are painted by selecting them with the ordinary selection brush, using the current [`EditorBrush`].
+//! Each tile is only painted once per stroke, so dragging a brush over the map raises it by a single step.
+
+use bevy::{prelude::*, utils::HashSet};
+use hexx::Hex;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::Id,
+    game_state::GameState,
+    geometry::{MapGeometry, Volume, VoxelPos},
+    milestones::Profile,
+    organisms::energy::StartingEnergy,
+    player_interaction::{
+        clipboard::ClipboardData, picking::CursorPos, selection::CurrentSelection,
+        InteractionSystem, PlayerAction,
+    },
+    structures::{
+        commands::StructureCommandsExt,
+        structure_manifest::{Structure, StructureKind, StructureManifest},
+    },
+    terrain::{
+        fertility::SoilFertility,
+        respond_to_height_changes,
+        terrain_assets::TerrainHandles,
+        terrain_manifest::{Terrain, TerrainManifest},
+    },
+    water::{
+        water_dynamics::{SoilWaterEvaporationRate, SoilWaterFlowRate},
+        SoilWaterCapacity, WaterVolume,
+    },
+    world_gen::scenario::Scenario,
+};
+
+/// Paints the world by hand in [`GameState::MapEditor`], and saves it as a [`Scenario`].
+pub(crate) struct MapEditorPlugin;
+
+impl Plugin for MapEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapEditor>()
+            .add_event::<SaveScenario>()
+            .add_systems(
+                Update,
+                (
+                    paint_with_brush.after(InteractionSystem::SelectTiles),
+                    // Height changes are normally handled by the simulation, which doesn't run in the editor
+                    respond_to_height_changes.after(paint_with_brush),
+                    save_scenario,
+                )
+                    .run_if(in_state(GameState::MapEditor)),
+            );
+    }
+}
+
+/// The state of the map editor.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub(crate) struct MapEditor {
+    /// The brush that selected tiles are painted with.
+    pub(crate) brush: EditorBrush,
+}
+
+/// How the map editor changes each tile that it paints.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum EditorBrush {
+    /// Raises the terrain by one step.
+    #[default]
+    Raise,
+    /// Lowers the terrain by one step.
+    Lower,
+    /// Replaces the terrain with the given type.
+    Terrain(Id<Terrain>),
+    /// Adds a tile's worth of water.
+    Flood,
+    /// Removes all water.
+    Drain,
+    /// Places a structure of the given type on top of the terrain, such as a plant or a landmark.
+    Structure(Id<Structure>),
+    /// Removes any structure on top of the terrain.
+    Clear,
+}
+
+impl EditorBrush {
+    /// A short description of this brush, for use in the UI.
+    pub(crate) fn display(
+        &self,
+        terrain_manifest: &TerrainManifest,
+        structure_manifest: &StructureManifest,
+    ) -> String {
+        match self {
+            EditorBrush::Raise => "Raise".to_string(),
+            EditorBrush::Lower => "Lower".to_string(),
+            EditorBrush::Terrain(terrain_id) => {
+                format!("Paint {}", terrain_manifest.name(*terrain_id))
+            }
+            EditorBrush::Flood => "Flood".to_string(),
+            EditorBrush::Drain => "Drain".to_string(),
+            EditorBrush::Structure(structure_id) => {
+                format!("Place {}", structure_manifest.name(*structure_id))
+            }
+            EditorBrush::Clear => "Clear".to_string(),
+        }
+    }
+
+    /// Every brush that can be used with the loaded manifests.
+    ///
+    /// Only organisms and landmarks can be placed, as other structures are built by the player.
+    pub(crate) fn all(
+        terrain_manifest: &TerrainManifest,
+        structure_manifest: &StructureManifest,
+    ) -> Vec<EditorBrush> {
+        let mut terrain_ids: Vec<Id<Terrain>> = terrain_manifest.variants().into_iter().collect();
+        terrain_ids.sort_by_key(|&terrain_id| terrain_manifest.name(terrain_id));
+
+        let mut structure_ids: Vec<Id<Structure>> = structure_manifest
+            .variants()
+            .into_iter()
+            .filter(|&structure_id| {
+                let structure_data = structure_manifest.get(structure_id);
+                structure_data.organism_variety.is_some()
+                    || matches!(structure_data.kind, StructureKind::Landmark)
+            })
+            .collect();
+        structure_ids.sort_by_key(|&structure_id| structure_manifest.name(structure_id));
+
+        [EditorBrush::Raise, EditorBrush::Lower]
+            .into_iter()
+            .chain(terrain_ids.into_iter().map(EditorBrush::Terrain))
+            .chain([EditorBrush::Flood, EditorBrush::Drain])
+            .chain(structure_ids.into_iter().map(EditorBrush::Structure))
+            .chain([EditorBrush::Clear])
+            .collect()
+    }
+}
+
+/// Saves the map being edited as a new [`Scenario`] in the player's [`Profile`].
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SaveScenario;
+
+/// Paints each newly selected tile with the current [`EditorBrush`].
+fn paint_with_brush(
+    actions: Res<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    cursor_pos: Res<CursorPos>,
+    map_editor: Res<MapEditor>,
+    mut painted: Local<HashSet<Hex>>,
+    mut terrain_query: Query<(
+        &mut Id<Terrain>,
+        &mut VoxelPos,
+        &mut Handle<Scene>,
+        &mut WaterVolume,
+        &mut SoilWaterCapacity,
+        &mut SoilWaterFlowRate,
+        &mut SoilWaterEvaporationRate,
+        &mut SoilFertility,
+    )>,
+    mut map_geometry: ResMut<MapGeometry>,
+    terrain_manifest: Res<TerrainManifest>,
+    structure_manifest: Res<StructureManifest>,
+    maybe_terrain_handles: Option<Res<TerrainHandles>>,
+    mut commands: Commands,
+) {
+    // Each stroke paints every tile at most once
+    if actions.just_pressed(PlayerAction::UseTool) {
+        painted.clear();
+    }
+
+    if !actions.pressed(PlayerAction::UseTool) && !actions.just_released(PlayerAction::UseTool) {
+        return;
+    }
+
+    // Clicking on a structure selects it, but the brush should still paint its tile
+    let selected_voxels = current_selection.relevant_tiles(&cursor_pos);
+
+    let hexes: Vec<Hex> = selected_voxels
+        .iter()
+        .map(|voxel_pos| voxel_pos.hex)
+        .filter(|&hex| painted.insert(hex))
+        .collect();
+
+    for hex in hexes {
+        let Ok(terrain_entity) = map_geometry.get_terrain(hex) else {
+            continue;
+        };
+        let Ok((
+            mut terrain_id,
+            mut voxel_pos,
+            mut scene_handle,
+            mut water_volume,
+            mut soil_water_capacity,
+            mut soil_water_flow_rate,
+            mut soil_water_evaporation_rate,
+            mut soil_fertility,
+        )) = terrain_query.get_mut(terrain_entity)
+        else {
+            continue;
+        };
+
+        let surface = voxel_pos.above();
+
+        match map_editor.brush {
+            EditorBrush::Raise | EditorBrush::Lower => {
+                // Structures would be left floating or buried, so they are removed along with the old surface
+                commands.despawn_structure(surface);
+
+                voxel_pos.height = match map_editor.brush {
+                    EditorBrush::Raise => voxel_pos.height.above(),
+                    _ => voxel_pos.height.below(),
+                };
+                map_geometry.update_height(hex, voxel_pos.height);
+            }
+            EditorBrush::Terrain(new_terrain_id) => {
+                let Some(terrain_handles) = &maybe_terrain_handles else {
+                    continue;
+                };
+
+                let terrain_data = terrain_manifest.get(new_terrain_id);
+                *terrain_id = new_terrain_id;
+                *scene_handle = terrain_handles
+                    .scenes
+                    .get(&new_terrain_id)
+                    .unwrap()
+                    .clone_weak();
+                *soil_water_capacity = terrain_data.soil_water_capacity;
+                *soil_water_flow_rate = terrain_data.soil_water_flow_rate;
+                *soil_water_evaporation_rate = terrain_data.soil_water_evaporation_rate;
+                *soil_fertility = SoilFertility::new(terrain_data.base_fertility);
+            }
+            EditorBrush::Flood => water_volume.add(Volume::ONE),
+            EditorBrush::Drain => *water_volume = WaterVolume::ZERO,
+            EditorBrush::Structure(structure_id) => {
+                let starting_energy = match structure_manifest.get(structure_id).organism_variety {
+                    Some(_) => StartingEnergy::Full,
+                    None => StartingEnergy::NotAnOrganism,
+                };
+
+                // Space is checked when the structure is spawned
+                commands.spawn_structure(
+                    surface,
+                    ClipboardData::generate_from_id(structure_id, &structure_manifest),
+                    starting_energy,
+                    None,
+                );
+            }
+            EditorBrush::Clear => commands.despawn_structure(surface),
+        }
+    }
+}
+
+/// Saves the map being edited when a [`SaveScenario`] event is sent.
+fn save_scenario(world: &mut World) {
+    if world.resource_mut::<Events<SaveScenario>>().drain().count() == 0 {
+        return;
+    }
+
+    let path = Scenario::unused_path(world.resource::<Profile>());
+    let name = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .replace('_', " ");
+
+    let scenario = Scenario::capture(world, name);
+
+    match scenario.save(&path) {
+        Ok(()) => info!("Saved the {} scenario to {}", scenario.name, path.display()),
+        Err(error) => error!("Could not save scenario to {}: {error}", path.display()),
+    }
+}
@@ -0,0 +1,237 @@
+//! Data-driven conditions that are evaluated against the state of the simulation.
+//!
+//! Conditions are written in manifests as a [`RawCondition`], using string names,
+//! and are converted to a [`Condition`] that uses [`Id`]s when the manifest is processed.
+//! Simple conditions can be combined using [`Condition::All`] and [`Condition::Any`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    items::{item_manifest::Item, totals::ItemTotals},
+    simulation::{reports::DailyReports, time::Season, weather::CurrentWeather},
+    units::{census::Census, unit_manifest::Unit},
+};
+
+/// The state of the simulation that a [`Condition`] is checked against.
+pub(crate) struct ConditionContext<'a> {
+    /// The population of each species.
+    pub(crate) census: &'a Census,
+    /// The total number of each item.
+    pub(crate) item_totals: &'a ItemTotals,
+    /// Summaries of the most recent days.
+    pub(crate) daily_reports: &'a DailyReports,
+    /// Today's weather.
+    pub(crate) current_weather: &'a CurrentWeather,
+    /// The number of complete in-game days that have passed.
+    pub(crate) elapsed_days: u64,
+    /// The current season.
+    pub(crate) season: Season,
+}
+
+/// A condition that can be satisfied by the state of the simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// At least `count` units of the given species are alive.
+    Population {
+        /// The species to count.
+        unit_id: Id<Unit>,
+        /// The number of living units required.
+        count: usize,
+    },
+    /// At least `count` of the given item exist, across all inventories.
+    ItemStockpiled {
+        /// The item to count.
+        item_id: Id<Item>,
+        /// The number of items required.
+        count: u32,
+    },
+    /// The total number of the given item rose on each of the last `days` days.
+    ItemProducedDaily {
+        /// The item that must be produced.
+        item_id: Id<Item>,
+        /// The number of consecutive days on which the item must be produced.
+        days: usize,
+    },
+    /// It has not rained for at least this many consecutive days.
+    DaysWithoutRain(u32),
+    /// The colony has existed for at least this many days.
+    DaysSurvived(u64),
+    /// It is currently this season.
+    Season(Season),
+    /// Every one of these conditions is satisfied.
+    All(Vec<Condition>),
+    /// At least one of these conditions is satisfied.
+    Any(Vec<Condition>),
+    /// This condition is not satisfied.
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Is this condition satisfied by the current state of the simulation?
+    pub(crate) fn is_satisfied(&self, context: &ConditionContext) -> bool {
+        match self {
+            Condition::Population { unit_id, count } => {
+                context.census.population(*unit_id) >= *count
+            }
+            Condition::ItemStockpiled { item_id, count } => {
+                let total = context
+                    .item_totals
+                    .counts()
+                    .get(item_id)
+                    .copied()
+                    .unwrap_or_default();
+                total >= *count
+            }
+            Condition::ItemProducedDaily { item_id, days } => {
+                let consecutive_days = context
+                    .daily_reports
+                    .iter()
+                    .rev()
+                    .take_while(|report| report.item_change(*item_id) > 0)
+                    .count();
+                consecutive_days >= *days
+            }
+            Condition::DaysWithoutRain(days) => {
+                context.current_weather.days_without_rain() >= *days
+            }
+            Condition::DaysSurvived(days) => context.elapsed_days >= *days,
+            Condition::Season(season) => context.season == *season,
+            Condition::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.is_satisfied(context)),
+            Condition::Any(conditions) => conditions
+                .iter()
+                .any(|condition| condition.is_satisfied(context)),
+            Condition::Not(condition) => !condition.is_satisfied(context),
+        }
+    }
+}
+
+/// The unprocessed equivalent of [`Condition`], as written in manifest files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RawCondition {
+    /// At least `count` units of the given species are alive.
+    Population {
+        /// The name of the species to count.
+        unit: String,
+        /// The number of living units required.
+        count: usize,
+    },
+    /// At least `count` of the given item exist, across all inventories.
+    ItemStockpiled {
+        /// The name of the item to count.
+        item: String,
+        /// The number of items required.
+        count: u32,
+    },
+    /// The total number of the given item rose on each of the last `days` days.
+    ItemProducedDaily {
+        /// The name of the item that must be produced.
+        item: String,
+        /// The number of consecutive days on which the item must be produced.
+        days: usize,
+    },
+    /// It has not rained for at least this many consecutive days.
+    DaysWithoutRain(u32),
+    /// The colony has existed for at least this many days.
+    DaysSurvived(u64),
+    /// It is currently this season.
+    Season(Season),
+    /// Every one of these conditions is satisfied.
+    All(Vec<RawCondition>),
+    /// At least one of these conditions is satisfied.
+    Any(Vec<RawCondition>),
+    /// This condition is not satisfied.
+    Not(Box<RawCondition>),
+}
+
+impl From<RawCondition> for Condition {
+    fn from(raw_condition: RawCondition) -> Self {
+        match raw_condition {
+            RawCondition::Population { unit, count } => Condition::Population {
+                unit_id: Id::from_name(unit),
+                count,
+            },
+            RawCondition::ItemStockpiled { item, count } => Condition::ItemStockpiled {
+                item_id: Id::from_name(item),
+                count,
+            },
+            RawCondition::ItemProducedDaily { item, days } => Condition::ItemProducedDaily {
+                item_id: Id::from_name(item),
+                days,
+            },
+            RawCondition::DaysWithoutRain(days) => Condition::DaysWithoutRain(days),
+            RawCondition::DaysSurvived(days) => Condition::DaysSurvived(days),
+            RawCondition::Season(season) => Condition::Season(season),
+            RawCondition::All(raw_conditions) => {
+                Condition::All(raw_conditions.into_iter().map(Condition::from).collect())
+            }
+            RawCondition::Any(raw_conditions) => {
+                Condition::Any(raw_conditions.into_iter().map(Condition::from).collect())
+            }
+            RawCondition::Not(raw_condition) => Condition::Not(Box::new((*raw_condition).into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conditions_combine() {
+        let census = Census::default();
+        let item_totals = ItemTotals::default();
+        let daily_reports = DailyReports::default();
+        let current_weather = CurrentWeather::default();
+
+        let context = ConditionContext {
+            census: &census,
+            item_totals: &item_totals,
+            daily_reports: &daily_reports,
+            current_weather: &current_weather,
+            elapsed_days: 10,
+            season: Season::Spring,
+        };
+
+        let survived = Condition::DaysSurvived(5);
+        let populous = Condition::Population {
+            unit_id: Id::from_name("basket_crab".to_string()),
+            count: 100,
+        };
+
+        assert!(survived.is_satisfied(&context));
+        assert!(!populous.is_satisfied(&context));
+        assert!(!Condition::All(vec![survived.clone(), populous.clone()]).is_satisfied(&context));
+        assert!(Condition::Any(vec![survived, populous]).is_satisfied(&context));
+        // Vacuously true
+        assert!(Condition::All(Vec::new()).is_satisfied(&context));
+        assert!(!Condition::Not(Box::new(Condition::All(Vec::new()))).is_satisfied(&context));
+        assert!(Condition::Season(Season::Spring).is_satisfied(&context));
+        assert!(!Condition::Season(Season::Winter).is_satisfied(&context));
+    }
+
+    #[test]
+    fn raw_conditions_are_processed_recursively() {
+        let raw_condition = RawCondition::Any(vec![
+            RawCondition::ItemStockpiled {
+                item: "crab_egg".to_string(),
+                count: 10,
+            },
+            RawCondition::DaysWithoutRain(3),
+        ]);
+
+        let condition = Condition::from(raw_condition);
+        assert_eq!(
+            condition,
+            Condition::Any(vec![
+                Condition::ItemStockpiled {
+                    item_id: Id::from_name("crab_egg".to_string()),
+                    count: 10,
+                },
+                Condition::DaysWithoutRain(3),
+            ])
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Defines write-only data for each milestone.
+
+use bevy::{
+    asset::Asset,
+    reflect::{Reflect, TypePath, TypeUuid},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::asset_management::manifest::{loader::IsRawManifest, Manifest};
+
+use super::conditions::{Condition, RawCondition};
+
+/// The marker type for [`Id<Milestone>`](crate::asset_management::manifest::Id).
+#[derive(Reflect, Clone, Copy, PartialEq, Eq)]
+pub struct Milestone;
+/// Stores the read-only definitions for all milestones.
+pub type MilestoneManifest = Manifest<Milestone, MilestoneData>;
+
+/// Data stored in a [`MilestoneManifest`] for each [`Id<Milestone>`](crate::asset_management::manifest::Id).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MilestoneData {
+    /// A short description of the milestone, shown to the player.
+    pub description: String,
+    /// The condition that must be met for the milestone to be achieved.
+    pub condition: Condition,
+}
+
+/// The unprocessed equivalent of [`MilestoneData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawMilestoneData {
+    /// A short description of the milestone, shown to the player.
+    pub description: String,
+    /// The condition that must be met for the milestone to be achieved.
+    pub condition: RawCondition,
+}
+
+impl From<RawMilestoneData> for MilestoneData {
+    fn from(raw_data: RawMilestoneData) -> Self {
+        Self {
+            description: raw_data.description,
+            condition: raw_data.condition.into(),
+        }
+    }
+}
+
+/// The [`MilestoneManifest`] as seen in the manifest file.
+#[derive(Asset, Debug, Clone, Serialize, Deserialize, TypeUuid, TypePath, PartialEq)]
+#[uuid = "4b1d7c2e-93a6-4f0b-8e52-6d0c1f7a9b38"]
+pub struct RawMilestoneManifest {
+    /// The data for each milestone.
+    pub milestones: HashMap<String, RawMilestoneData>,
+}
+
+impl IsRawManifest for RawMilestoneManifest {
+    const EXTENSION: &'static str = "milestone_manifest.json";
+
+    type Marker = Milestone;
+    type Data = MilestoneData;
+
+    fn process(&self) -> Manifest<Self::Marker, Self::Data> {
+        let mut manifest = Manifest::new();
+
+        for (raw_id, raw_data) in self.milestones.clone() {
+            manifest.insert(raw_id, raw_data.into())
+        }
+
+        manifest
+    }
+}
@@ -0,0 +1,223 @@
+//! Milestones are goals that the colony can achieve, like raising its first hundred units or surviving a drought.
+//!
+//! Each milestone is defined in the [`MilestoneManifest`] by a data-driven [`Condition`](conditions::Condition),
+//! which is checked against the state of the simulation.
+//! Achieved milestones are saved to disk as part of the player's [`Profile`], so they carry over between games.
+
+use std::path::PathBuf;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::plugin::ManifestPlugin,
+    items::totals::{update_item_totals, ItemTotals},
+    simulation::{reports::DailyReports, time::InGameTime, weather::CurrentWeather},
+    units::census::Census,
+    world_gen::WorldGenState,
+};
+
+use self::{
+    conditions::ConditionContext,
+    milestone_manifest::{MilestoneManifest, RawMilestoneManifest},
+};
+
+pub mod conditions;
+pub mod milestone_manifest;
+
+/// Loads, checks and saves milestones.
+pub(crate) struct MilestonesPlugin;
+
+impl Plugin for MilestonesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ManifestPlugin::<RawMilestoneManifest>::new())
+            .init_resource::<Profile>()
+            .init_resource::<MilestoneProgress>()
+            .add_event::<MilestoneAchieved>()
+            .add_systems(Startup, load_milestone_progress)
+            .add_systems(
+                Update,
+                check_milestones
+                    .after(update_item_totals)
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// The player profile that progress is saved to.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    /// The name of the profile, which is also the name of its directory.
+    pub name: String,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            name: "default".to_string(),
+        }
+    }
+}
+
+impl Profile {
+    /// The directory that all profiles are stored in, relative to the working directory.
+    const PROFILES_DIRECTORY: &'static str = "profiles";
+
+    /// The directory where this profile's data is stored.
+    pub fn directory(&self) -> PathBuf {
+        PathBuf::from(Self::PROFILES_DIRECTORY).join(&self.name)
+    }
+
+    /// The file where this profile's achieved milestones are stored.
+    fn milestones_path(&self) -> PathBuf {
+        self.directory().join("milestones.json")
+    }
+
+    /// The directory where this profile's saved games are stored.
+    pub fn saves_directory(&self) -> PathBuf {
+        self.directory().join("saves")
+    }
+
+    /// The directory where this profile's hand-made maps are stored.
+    pub fn scenarios_directory(&self) -> PathBuf {
+        self.directory().join("scenarios")
+    }
+}
+
+/// The milestones that have been achieved by this [`Profile`].
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MilestoneProgress {
+    /// The in-game day on which each milestone was first achieved, keyed by the milestone's name.
+    ///
+    /// Names are used rather than [`Id`](crate::asset_management::manifest::Id)s,
+    /// so that progress survives changes to the manifest.
+    achieved: HashMap<String, u64>,
+}
+
+impl MilestoneProgress {
+    /// Has the milestone with the given name been achieved?
+    pub fn is_achieved(&self, name: &str) -> bool {
+        self.achieved.contains_key(name)
+    }
+
+    /// The in-game day on which the milestone with the given name was achieved, if any.
+    pub fn achieved_on(&self, name: &str) -> Option<u64> {
+        self.achieved.get(name).copied()
+    }
+
+    /// Records that the milestone with the given name was achieved on `day`.
+    ///
+    /// If it had already been achieved, the original day is kept.
+    fn achieve(&mut self, name: &str, day: u64) {
+        self.achieved.entry(name.to_string()).or_insert(day);
+    }
+}
+
+/// An event sent when a milestone is achieved for the first time.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MilestoneAchieved {
+    /// The name of the milestone.
+    pub name: String,
+}
+
+/// Loads the [`MilestoneProgress`] of the current [`Profile`] from disk, if it has been saved before.
+fn load_milestone_progress(
+    profile: Res<Profile>,
+    mut milestone_progress: ResMut<MilestoneProgress>,
+) {
+    let path = profile.milestones_path();
+
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        // Nothing has been achieved yet
+        return;
+    };
+
+    match serde_json::from_str(&contents) {
+        Ok(progress) => *milestone_progress = progress,
+        Err(error) => error!("Could not read milestones from {}: {error}", path.display()),
+    }
+}
+
+/// Saves the [`MilestoneProgress`] of the current [`Profile`] to disk.
+fn save_milestone_progress(profile: &Profile, milestone_progress: &MilestoneProgress) {
+    let path = profile.milestones_path();
+
+    let result = std::fs::create_dir_all(profile.directory()).and_then(|_| {
+        let contents = serde_json::to_string_pretty(milestone_progress)?;
+        std::fs::write(&path, contents)
+    });
+
+    if let Err(error) = result {
+        error!("Could not save milestones to {}: {error}", path.display());
+    }
+}
+
+/// Checks whether any milestones that have not yet been achieved have now been met.
+fn check_milestones(
+    milestone_manifest: Res<MilestoneManifest>,
+    mut milestone_progress: ResMut<MilestoneProgress>,
+    mut achieved_events: EventWriter<MilestoneAchieved>,
+    profile: Res<Profile>,
+    census: Res<Census>,
+    item_totals: Res<ItemTotals>,
+    daily_reports: Res<DailyReports>,
+    current_weather: Res<CurrentWeather>,
+    in_game_time: Res<InGameTime>,
+) {
+    let context = ConditionContext {
+        census: &census,
+        item_totals: &item_totals,
+        daily_reports: &daily_reports,
+        current_weather: &current_weather,
+        elapsed_days: in_game_time.rounded_elapsed_days(),
+        season: in_game_time.season(),
+    };
+
+    let mut newly_achieved = false;
+
+    for (milestone_id, milestone_data) in milestone_manifest.data_map() {
+        let name = milestone_manifest.name(*milestone_id);
+        if milestone_progress.is_achieved(name) {
+            continue;
+        }
+
+        if milestone_data.condition.is_satisfied(&context) {
+            milestone_progress.achieve(name, context.elapsed_days);
+            info!("Milestone achieved: {}", milestone_data.description);
+            achieved_events.send(MilestoneAchieved {
+                name: name.to_string(),
+            });
+            newly_achieved = true;
+        }
+    }
+
+    if newly_achieved {
+        save_milestone_progress(&profile, &milestone_progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn milestones_are_only_achieved_once() {
+        let mut milestone_progress = MilestoneProgress::default();
+
+        milestone_progress.achieve("first_steps", 3);
+        milestone_progress.achieve("first_steps", 5);
+        assert_eq!(milestone_progress.achieved_on("first_steps"), Some(3));
+        assert!(!milestone_progress.is_achieved("drought_survivor"));
+    }
+
+    #[test]
+    fn milestone_progress_round_trips_through_json() {
+        let mut milestone_progress = MilestoneProgress::default();
+        milestone_progress.achieve("first_steps", 3);
+
+        let serialized = serde_json::to_string(&milestone_progress).unwrap();
+        let deserialized: MilestoneProgress = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(milestone_progress, deserialized);
+    }
+}
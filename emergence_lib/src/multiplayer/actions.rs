@@ -0,0 +1,456 @@
+//! Changes that players make to the world, which must happen in the same way for every player.
+//!
+//! Systems that change the world on the player's behalf hand a [`SharedAction`] to [`SharedActions`],
+//! rather than changing the world themselves.
+//! In single player games, the action is applied straight away as a [`Command`].
+//! In networked games, it is passed to the [`NetworkSession`], which applies it on the same tick for every player.
+//!
+//! Entities are not numbered in the same way on every machine, so actions refer to objects by their position.
+//! Nicknames and favorite units are only labels, so they are kept by each player rather than being shared.
+
+use bevy::{
+    core::FrameCount,
+    ecs::system::{Command, CommandQueue, SystemParam},
+    prelude::*,
+};
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::{
+        demolition::MarkedForDemolition,
+        terraform::{TerraformingAction, TerraformingCommandsExt},
+        work_orders::WorkOrder,
+    },
+    crafting::{
+        orders::{CancelCraftOrder, CraftOrder, PlaceCraftOrder},
+        queue::{EditRecipeQueue, RecipeQueueEdit},
+        recipe::ActiveRecipe,
+    },
+    factions::Faction,
+    geometry::{Facing, MapGeometry, VoxelPos},
+    items::item_manifest::Item,
+    logistics::{HaulingPriorities, LogisticsPriority, OutputRouting, SetOutputRouting},
+    organisms::corpses::{CorpseHandling, CorpsePolicy},
+    player_interaction::{bulk_commands::BulkCommand, clipboard::ClipboardData},
+    structures::{commands::StructureCommandsExt, structure_manifest::Structure, Landmark},
+    trading::AcceptTradeOffer,
+    units::{census::PopulationTargets, unit_manifest::Unit},
+};
+
+use super::NetworkSession;
+
+/// A change to the world made by a player, which must be made in every player's game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SharedAction {
+    /// A structure was zoned.
+    Zone {
+        /// The center of the new ghost.
+        voxel_pos: VoxelPos,
+        /// The variety of structure.
+        structure_id: Id<Structure>,
+        /// The direction the structure faces.
+        direction: hexx::Direction,
+        /// The recipe that the structure will craft.
+        active_recipe: ActiveRecipe,
+    },
+    /// The ghost at this position was removed.
+    ClearZoning {
+        /// The center of the removed ghost.
+        voxel_pos: VoxelPos,
+    },
+    /// The structure at this position was marked for demolition.
+    Demolish {
+        /// The center of the structure.
+        voxel_pos: VoxelPos,
+    },
+    /// A tile was marked for terraforming.
+    Terraform {
+        /// The tile to terraform.
+        hex: Hex,
+        /// How the tile should change.
+        action: TerraformingAction,
+    },
+    /// The terraforming of a tile was cancelled.
+    CancelTerraform {
+        /// The tile that should be left alone.
+        hex: Hex,
+    },
+    /// The work orders of the objects at these positions were cancelled.
+    CancelWorkOrders {
+        /// The positions of the objects whose work orders should be cancelled.
+        voxels: Vec<VoxelPos>,
+    },
+    /// A [`BulkCommand`] was applied to the objects at these positions.
+    ApplyBulkCommand {
+        /// The command that was issued.
+        command: BulkCommand,
+        /// The positions of the objects that the command applies to.
+        voxels: Vec<VoxelPos>,
+        /// Should the command's marker be added, rather than removed?
+        enable: bool,
+    },
+    /// The outputs of a structure were routed somewhere else.
+    RouteOutputs {
+        /// The center of the structure whose outputs are routed.
+        structure: VoxelPos,
+        /// The new routing, or `None` to offer the outputs to any structure that requests them.
+        routing: Option<SharedRouting>,
+    },
+    /// The recipe queue of a crafter was changed.
+    EditRecipeQueue {
+        /// The center of the crafter.
+        structure: VoxelPos,
+        /// The change to make.
+        edit: RecipeQueueEdit,
+    },
+    /// A [`CraftOrder`] was placed.
+    PlaceCraftOrder {
+        /// The item to make.
+        item_id: Id<Item>,
+        /// How many to make.
+        count: u32,
+    },
+    /// A [`CraftOrder`] was cancelled.
+    CancelCraftOrder {
+        /// The [`CraftOrder::number`] of the cancelled order.
+        number: u32,
+    },
+    /// A trade offer from the visiting caravan was accepted.
+    AcceptTradeOffer {
+        /// The id of the offer.
+        offer_id: u64,
+        /// The center of the trading post where the trade is carried out.
+        trading_post: VoxelPos,
+    },
+    /// The hauling priority of an item was changed.
+    SetHaulingPriority {
+        /// The item whose priority changed.
+        item_id: Id<Item>,
+        /// The new priority.
+        priority: LogisticsPriority,
+    },
+    /// The default handling of corpses was changed.
+    SetCorpseHandling {
+        /// How corpses should be handled from now on.
+        handling: CorpseHandling,
+    },
+    /// The population target of a species was changed.
+    SetPopulationTarget {
+        /// The species whose target changed.
+        unit_id: Id<Unit>,
+        /// The new target, or `None` to let the population grow freely.
+        target: Option<usize>,
+    },
+}
+
+impl SharedAction {
+    /// Zones the structure described by `data` at `voxel_pos`.
+    pub(crate) fn zone(voxel_pos: VoxelPos, data: ClipboardData) -> Self {
+        SharedAction::Zone {
+            voxel_pos,
+            structure_id: data.structure_id,
+            direction: data.facing.direction,
+            active_recipe: data.active_recipe,
+        }
+    }
+
+    /// Does taking this action twice have the same effect as taking it once?
+    fn is_idempotent(&self) -> bool {
+        !matches!(
+            self,
+            SharedAction::EditRecipeQueue {
+                edit: RecipeQueueEdit::Push(..) | RecipeQueueEdit::Remove(..),
+                ..
+            } | SharedAction::PlaceCraftOrder { .. }
+        )
+    }
+}
+
+/// An [`OutputRouting`], with the chosen storage identified by its position rather than its entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SharedRouting {
+    /// See [`OutputRouting::Hold`].
+    Hold,
+    /// See [`OutputRouting::Storage`].
+    Storage(VoxelPos),
+    /// See [`OutputRouting::NearestStorage`].
+    NearestStorage,
+}
+
+impl SharedRouting {
+    /// Describes the `routing` by position, using the `position_query` to find the chosen storage.
+    ///
+    /// Returns [`None`] if the chosen storage no longer exists.
+    pub(crate) fn from_routing(
+        routing: OutputRouting,
+        position_query: &Query<&VoxelPos>,
+    ) -> Option<Self> {
+        match routing {
+            OutputRouting::Hold => Some(SharedRouting::Hold),
+            OutputRouting::Storage(storage) => position_query
+                .get(storage)
+                .ok()
+                .map(|&voxel_pos| SharedRouting::Storage(voxel_pos)),
+            OutputRouting::NearestStorage => Some(SharedRouting::NearestStorage),
+        }
+    }
+
+    /// Finds the chosen storage in this world.
+    ///
+    /// Returns [`None`] if there is no structure at the chosen position.
+    fn to_routing(self, map_geometry: &MapGeometry) -> Option<OutputRouting> {
+        match self {
+            SharedRouting::Hold => Some(OutputRouting::Hold),
+            SharedRouting::Storage(voxel_pos) => map_geometry
+                .get_structure(voxel_pos)
+                .map(OutputRouting::Storage),
+            SharedRouting::NearestStorage => Some(OutputRouting::NearestStorage),
+        }
+    }
+}
+
+impl Command for SharedAction {
+    fn apply(self, world: &mut World) {
+        // Actions reuse the existing commands, which are queued up here and then applied straight away
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        let map_geometry = world.resource::<MapGeometry>();
+
+        match self {
+            SharedAction::Zone {
+                voxel_pos,
+                structure_id,
+                direction,
+                active_recipe,
+            } => commands.spawn_ghost_structure(
+                voxel_pos,
+                ClipboardData {
+                    structure_id,
+                    facing: Facing { direction },
+                    active_recipe,
+                },
+                Faction::PLAYER,
+            ),
+            SharedAction::ClearZoning { voxel_pos } => {
+                commands.despawn_ghost_structure(voxel_pos);
+            }
+            SharedAction::Demolish { voxel_pos } => {
+                // Landmarks can't be demolished
+                if let Some(structure_entity) = map_geometry
+                    .get_structure(voxel_pos)
+                    .filter(|&entity| world.get::<Landmark>(entity).is_none())
+                {
+                    commands
+                        .entity(structure_entity)
+                        .insert(MarkedForDemolition);
+                }
+            }
+            SharedAction::Terraform { hex, action } => commands.start_terraform(hex, action),
+            SharedAction::CancelTerraform { hex } => commands.cancel_terraform(hex),
+            SharedAction::CancelWorkOrders { voxels } => {
+                for voxel_pos in voxels {
+                    let Some(voxel_object) = map_geometry.get_voxel(voxel_pos) else {
+                        continue;
+                    };
+
+                    if let Some(work_order) = world.get::<WorkOrder>(voxel_object.entity) {
+                        work_order.cancel(voxel_object.entity, &mut commands);
+                    }
+                }
+            }
+            SharedAction::ApplyBulkCommand {
+                command,
+                voxels,
+                enable,
+            } => {
+                for voxel_pos in voxels {
+                    let Some(voxel_object) = map_geometry.get_voxel(voxel_pos) else {
+                        continue;
+                    };
+
+                    // The object may have changed since the command was issued
+                    if command.applies_to(world.entity(voxel_object.entity)) {
+                        command.set_marker(&mut commands.entity(voxel_object.entity), enable);
+                    }
+                }
+            }
+            SharedAction::RouteOutputs { structure, routing } => {
+                let Some(structure) = map_geometry.get_structure(structure) else {
+                    return;
+                };
+
+                let routing = match routing {
+                    Some(routing) => match routing.to_routing(map_geometry) {
+                        Some(routing) => Some(routing),
+                        None => return,
+                    },
+                    None => None,
+                };
+
+                commands.add(move |world: &mut World| {
+                    world.send_event(SetOutputRouting { structure, routing });
+                });
+            }
+            SharedAction::EditRecipeQueue { structure, edit } => {
+                let Some(structure) = map_geometry.get_structure(structure) else {
+                    return;
+                };
+
+                commands.add(move |world: &mut World| {
+                    world.send_event(EditRecipeQueue { structure, edit });
+                });
+            }
+            SharedAction::PlaceCraftOrder { item_id, count } => {
+                commands.add(move |world: &mut World| {
+                    world.send_event(PlaceCraftOrder { item_id, count });
+                });
+            }
+            SharedAction::CancelCraftOrder { number } => {
+                commands.add(move |world: &mut World| {
+                    let order_entity = world
+                        .query::<(Entity, &CraftOrder)>()
+                        .iter(world)
+                        .find(|(_, order)| order.number == number)
+                        .map(|(order_entity, _)| order_entity);
+
+                    if let Some(order_entity) = order_entity {
+                        world.send_event(CancelCraftOrder(order_entity));
+                    }
+                });
+            }
+            SharedAction::AcceptTradeOffer {
+                offer_id,
+                trading_post,
+            } => {
+                let Some(trading_post) = map_geometry.get_structure(trading_post) else {
+                    return;
+                };
+
+                commands.add(move |world: &mut World| {
+                    world.send_event(AcceptTradeOffer {
+                        offer_id,
+                        trading_post,
+                    });
+                });
+            }
+            SharedAction::SetHaulingPriority { item_id, priority } => {
+                commands.add(move |world: &mut World| {
+                    world
+                        .resource_mut::<HaulingPriorities>()
+                        .set(item_id, priority);
+                });
+            }
+            SharedAction::SetCorpseHandling { handling } => {
+                commands.add(move |world: &mut World| {
+                    world.resource_mut::<CorpsePolicy>().default_handling = handling;
+                });
+            }
+            SharedAction::SetPopulationTarget { unit_id, target } => {
+                commands.add(move |world: &mut World| {
+                    let mut population_targets = world.resource_mut::<PopulationTargets>();
+                    match target {
+                        Some(target) => population_targets.set_target(unit_id, target),
+                        None => population_targets.clear_target(unit_id),
+                    }
+                });
+            }
+        }
+
+        queue.apply(world);
+    }
+}
+
+/// The actions taken by a single system, used to skip those that are repeated every frame.
+#[derive(Debug, Default)]
+pub(crate) struct RecentActions {
+    /// The frame on which [`RecentActions::current`] were taken.
+    frame: u32,
+    /// The actions taken on the frame before [`RecentActions::frame`].
+    previous: Vec<SharedAction>,
+    /// The actions taken on [`RecentActions::frame`].
+    current: Vec<SharedAction>,
+}
+
+impl RecentActions {
+    /// Records that `action` is being taken on `frame`.
+    ///
+    /// Returns `true` if the same action was already taken on this frame or the one before,
+    /// and taking it again would change nothing.
+    fn is_repeat(&mut self, action: &SharedAction, frame: u32) -> bool {
+        if frame != self.frame {
+            self.previous = match frame == self.frame.wrapping_add(1) {
+                true => std::mem::take(&mut self.current),
+                false => Vec::new(),
+            };
+            self.current.clear();
+            self.frame = frame;
+        }
+
+        let is_repeat = action.is_idempotent()
+            && (self.previous.contains(action) || self.current.contains(action));
+        self.current.push(action.clone());
+        is_repeat
+    }
+}
+
+/// Takes [`SharedAction`]s on behalf of the local player.
+///
+/// Tools keep taking the same action every frame while their button is held.
+/// Once an action has been taken, it is not taken again until a frame passes without it,
+/// so that networked games are not flooded with copies of it while it waits for its tick.
+#[derive(SystemParam)]
+pub(crate) struct SharedActions<'w, 's> {
+    /// The networked game, if any.
+    session: Option<ResMut<'w, NetworkSession>>,
+    /// The current frame.
+    frame_count: Res<'w, FrameCount>,
+    /// The actions recently taken by this system.
+    recent_actions: Local<'s, RecentActions>,
+    /// Applies actions in single player games.
+    commands: Commands<'w, 's>,
+}
+
+impl SharedActions<'_, '_> {
+    /// Takes the `action`, applying it now in single player games, or on an agreed tick in networked games.
+    pub(crate) fn take(&mut self, action: SharedAction) {
+        if self.recent_actions.is_repeat(&action, self.frame_count.0) {
+            return;
+        }
+
+        match &mut self.session {
+            Some(session) => session.submit(action),
+            None => self.commands.add(action),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn held_actions_are_only_taken_once() {
+        let mut recent_actions = RecentActions::default();
+        let clear = SharedAction::ClearZoning {
+            voxel_pos: VoxelPos::ZERO,
+        };
+        let order = SharedAction::PlaceCraftOrder {
+            item_id: Id::from_name("leaf".to_string()),
+            count: 5,
+        };
+
+        assert!(!recent_actions.is_repeat(&clear, 1));
+        assert!(recent_actions.is_repeat(&clear, 1));
+        assert!(recent_actions.is_repeat(&clear, 2));
+        assert!(recent_actions.is_repeat(&clear, 3));
+        // Once the action stops for a frame, it can be taken again
+        assert!(!recent_actions.is_repeat(&order, 4));
+        assert!(!recent_actions.is_repeat(&clear, 5));
+
+        // Placing the same order twice asks for twice as many items
+        assert!(!recent_actions.is_repeat(&order, 5));
+        assert!(!recent_actions.is_repeat(&order, 5));
+    }
+}
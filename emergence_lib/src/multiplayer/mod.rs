@@ -0,0 +1,1153 @@
+//! Lets several players connect to the same game.
+//!
+//! Multiplayer is opt-in: add a [`MultiplayerPlugin`] with a [`MultiplayerConfig`] to host or join a game.
+//! One player hosts, and the others connect to them over TCP.
+//! Messages are sent as JSON Lines, one [`NetMessage`] per line.
+//!
+//! Every change that a player makes to the world is a [`SharedAction`](actions::SharedAction).
+//! Clients send their actions to the host, which schedules each one for a tick a little way in the future
+//! and passes the full, ordered list of actions on to every client.
+//! Peers run in lockstep: clients never simulate past the tick that the host has allowed,
+//! and the host waits for any client that falls too far behind.
+//! Since the simulation only draws from seeded random number generators,
+//! peers that start from the same seed and apply the same actions on the same ticks stay in sync.
+//!
+//! Players that join a game in progress are sent every action taken so far,
+//! and fast-forward through the game from the start until they have caught up.
+//! Each peer periodically shares a checksum of its simulation state,
+//! so that games that have drifted apart are detected rather than silently diverging.
+//!
+//! Pausing the game on any machine soon pauses it for everyone, as the other players wait for it to catch up.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io::{ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    time::{Duration, Instant},
+};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::VoxelPos,
+    simulation::{self, stepping::run_requested_tick, SimulationSet},
+    structures::structure_manifest::Structure,
+    units::{scheduling::AiBudget, unit_manifest::Unit},
+    world_gen::GenerationConfig,
+};
+
+use self::actions::SharedAction;
+
+pub mod actions;
+
+/// Hosts or joins a networked game.
+pub struct MultiplayerPlugin {
+    /// How to connect to other players.
+    ///
+    /// If this is [`None`], the game is single player.
+    pub config: Option<MultiplayerConfig>,
+}
+
+impl MultiplayerPlugin {
+    /// Configures multiplayer using [`MultiplayerConfig::from_env`].
+    pub fn from_env() -> Self {
+        MultiplayerPlugin {
+            config: MultiplayerConfig::from_env(),
+        }
+    }
+}
+
+impl Plugin for MultiplayerPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(config) = &self.config else {
+            return;
+        };
+
+        let session = match NetworkSession::new(config.clone()) {
+            Ok(session) => session,
+            Err(error) => {
+                error!("Could not start multiplayer session: {error}");
+                return;
+            }
+        };
+
+        app.insert_resource(session)
+            // Frame times differ between machines, so they must not decide which units get to think
+            .insert_resource(AiBudget {
+                max_time_per_frame: Duration::MAX,
+                ..default()
+            })
+            // Keep talking to other players while the simulation is paused or loading
+            .add_systems(
+                PreUpdate,
+                (accept_connections, exchange_messages, catch_up_with_host).chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (apply_shared_actions, apply_deferred)
+                        .chain()
+                        .before(SimulationSet)
+                        .run_if(simulation::is_unpaused)
+                        .run_if(lockstep_allows_tick),
+                    count_ticks.in_set(SimulationSet),
+                    share_checksum.after(SimulationSet),
+                ),
+            )
+            .configure_sets(FixedUpdate, SimulationSet.run_if(lockstep_allows_tick))
+            // Runs after the local player's actions have been taken in `Update`
+            .add_systems(PostUpdate, sync_peers);
+    }
+}
+
+/// Whether this game is hosting, or connecting to a host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NetworkRole {
+    /// Listen for other players on this address.
+    Host(SocketAddr),
+    /// Join the game hosted at this address.
+    Client(SocketAddr),
+}
+
+/// Settings for the [`MultiplayerPlugin`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiplayerConfig {
+    /// Whether this game is hosting, or connecting to a host.
+    pub role: NetworkRole,
+    /// The name shown to other players.
+    pub player_name: String,
+}
+
+impl MultiplayerConfig {
+    /// The environment variable that hosts a game on the given address when set.
+    pub const HOST_VAR: &'static str = "EMERGENCE_HOST";
+
+    /// The environment variable that joins the game at the given address when set.
+    pub const CONNECT_VAR: &'static str = "EMERGENCE_CONNECT";
+
+    /// The environment variable that sets the name shown to other players.
+    pub const NAME_VAR: &'static str = "EMERGENCE_PLAYER_NAME";
+
+    /// Reads the multiplayer settings from the environment.
+    ///
+    /// Returns [`None`] if no networked game has been requested, or if the address is invalid.
+    pub fn from_env() -> Option<Self> {
+        let role = if let Ok(address) = std::env::var(Self::HOST_VAR) {
+            NetworkRole::Host(parse_address(&address)?)
+        } else if let Ok(address) = std::env::var(Self::CONNECT_VAR) {
+            NetworkRole::Client(parse_address(&address)?)
+        } else {
+            return None;
+        };
+
+        let player_name = std::env::var(Self::NAME_VAR).unwrap_or_else(|_| "Player".to_string());
+
+        Some(MultiplayerConfig { role, player_name })
+    }
+}
+
+/// Parses a socket address, logging an error if it is invalid.
+fn parse_address(address: &str) -> Option<SocketAddr> {
+    match address.parse() {
+        Ok(address) => Some(address),
+        Err(error) => {
+            error!("Invalid multiplayer address {address}: {error}");
+            None
+        }
+    }
+}
+
+/// A message sent between peers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NetMessage {
+    /// Sent by a client when it first connects.
+    Hello {
+        /// The version of the protocol used by the client.
+        protocol_version: u32,
+        /// The name of the connecting player.
+        player_name: String,
+        /// The seed that the client's world was generated from.
+        seed: u64,
+    },
+    /// Sent by the host when it accepts a new client.
+    Welcome {
+        /// The name of the hosting player.
+        player_name: String,
+    },
+    /// Sent by the host when it refuses a new client.
+    Rejected {
+        /// Why the client was refused.
+        reason: String,
+    },
+    /// A summary of the simulation state at the given tick.
+    Checksum {
+        /// The simulation tick that this checksum was computed on.
+        tick: u64,
+        /// The checksum of the simulation state.
+        checksum: u64,
+    },
+    /// Sent by a client to ask the host to schedule an action.
+    Request {
+        /// What the player did.
+        action: SharedAction,
+    },
+    /// Sent by the host to schedule an action.
+    ///
+    /// Actions are sent in the order that they must be applied.
+    Action {
+        /// The simulation tick on which the action is applied, before the simulation advances.
+        tick: u64,
+        /// What the player did.
+        action: SharedAction,
+    },
+    /// Sent by the host to let a client simulate up to, but not including, the given tick.
+    Advance {
+        /// The first tick that the client may not simulate yet.
+        horizon: u64,
+    },
+    /// Sent by a client to tell the host how far it has simulated.
+    Progress {
+        /// The number of ticks that the client has simulated.
+        tick: u64,
+    },
+}
+
+impl NetMessage {
+    /// The version of the protocol described by this type.
+    ///
+    /// This should be incremented whenever the messages change.
+    pub const PROTOCOL_VERSION: u32 = 3;
+}
+
+/// The most bytes that can be waiting to be sent to or parsed from a single peer.
+///
+/// A peer that falls this far behind, or sends a message this large, is disconnected.
+const MAX_BUFFERED_BYTES: usize = 1024 * 1024;
+
+/// A connection to another player.
+#[derive(Debug)]
+struct Peer {
+    /// The connection to this peer.
+    stream: TcpStream,
+    /// The name of this player, once they have introduced themselves.
+    player_name: Option<String>,
+    /// Bytes that have been received, but do not yet form a complete message.
+    buffer: Vec<u8>,
+    /// Bytes that are waiting for the connection to accept them.
+    outbox: Vec<u8>,
+    /// Have we introduced ourselves to this peer?
+    introduced: bool,
+    /// Has this connection been closed?
+    disconnected: bool,
+    /// The number of ticks that this client has simulated, as last reported.
+    progress: u64,
+    /// Has this client caught up with the host since joining?
+    ///
+    /// The host only waits for clients that have caught up.
+    caught_up: bool,
+    /// The number of [`NetworkSession::actions`] that have been sent to this client.
+    actions_sent: usize,
+    /// The horizon that this client was last sent.
+    horizon_sent: u64,
+}
+
+impl Peer {
+    /// Wraps a newly opened `stream`.
+    fn new(stream: TcpStream) -> std::io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Peer {
+            stream,
+            player_name: None,
+            buffer: Vec::new(),
+            outbox: Vec::new(),
+            introduced: false,
+            disconnected: false,
+            progress: 0,
+            caught_up: false,
+            actions_sent: 0,
+            horizon_sent: 0,
+        })
+    }
+
+    /// The name used to refer to this peer in logs.
+    fn name(&self) -> String {
+        match &self.player_name {
+            Some(name) => name.clone(),
+            None => self
+                .stream
+                .peer_addr()
+                .map_or("unknown peer".to_string(), |address| address.to_string()),
+        }
+    }
+
+    /// Sends the `message` to this peer.
+    ///
+    /// If sending fails, or too many messages are waiting to be sent, the peer is marked as disconnected.
+    fn send(&mut self, message: &NetMessage) {
+        if self.disconnected {
+            return;
+        }
+
+        let mut line = serde_json::to_vec(message).expect("Messages can always be serialized");
+        line.push(b'\n');
+
+        if self.outbox.len() + line.len() > MAX_BUFFERED_BYTES {
+            warn!("Disconnecting {}: too many unsent messages", self.name());
+            self.disconnected = true;
+            return;
+        }
+
+        self.outbox.extend(line);
+        self.flush();
+    }
+
+    /// Sends as much of the outbox as the connection will accept right now.
+    ///
+    /// The stream is non-blocking, so whatever is left over is sent on a later frame.
+    fn flush(&mut self) {
+        while !self.outbox.is_empty() && !self.disconnected {
+            match self.stream.write(&self.outbox) {
+                Ok(0) => {
+                    info!("{} disconnected", self.name());
+                    self.disconnected = true;
+                }
+                Ok(n) => {
+                    self.outbox.drain(..n);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    warn!("Lost connection to {}: {error}", self.name());
+                    self.disconnected = true;
+                }
+            }
+        }
+    }
+
+    /// Reads all messages that have arrived from this peer.
+    ///
+    /// If a single message is too large to buffer, the peer is marked as disconnected.
+    fn receive(&mut self) -> Vec<NetMessage> {
+        let mut messages = Vec::new();
+        let mut chunk = [0; 4096];
+        while !self.disconnected {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    info!("{} disconnected", self.name());
+                    self.disconnected = true;
+                }
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&chunk[..n]);
+                    messages.extend(split_messages(&mut self.buffer));
+
+                    if self.buffer.len() > MAX_BUFFERED_BYTES {
+                        warn!("Disconnecting {}: message too large", self.name());
+                        self.disconnected = true;
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    warn!("Lost connection to {}: {error}", self.name());
+                    self.disconnected = true;
+                }
+            }
+        }
+
+        messages
+    }
+}
+
+/// Removes and parses every complete line in the `buffer`.
+///
+/// Any trailing partial line is left in place, to be completed by the next read.
+/// Lines that are not valid messages are skipped.
+pub(crate) fn split_messages<M: DeserializeOwned>(buffer: &mut Vec<u8>) -> Vec<M> {
+    let Some(last_newline) = buffer.iter().rposition(|&byte| byte == b'\n') else {
+        return Vec::new();
+    };
+
+    let complete: Vec<u8> = buffer.drain(..=last_newline).collect();
+    complete
+        .split(|&byte| byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| match serde_json::from_slice(line) {
+            Ok(message) => Some(message),
+            Err(error) => {
+                warn!("Ignoring malformed network message: {error}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// The state of the current networked game.
+#[derive(Resource, Debug)]
+pub struct NetworkSession {
+    /// How this session was started.
+    config: MultiplayerConfig,
+    /// Listens for new players, if this game is the host.
+    listener: Option<TcpListener>,
+    /// The other players connected to this game.
+    ///
+    /// Clients are only connected to the host.
+    peers: Vec<Peer>,
+    /// The number of simulation ticks that have elapsed.
+    tick: u64,
+    /// Every action scheduled in this game so far, and the tick on which it is applied, in order.
+    ///
+    /// The host adds to this as actions are taken, and clients are sent a copy.
+    actions: Vec<(u64, SharedAction)>,
+    /// The number of [`NetworkSession::actions`] that have been applied.
+    applied: usize,
+    /// The first tick that the host has not yet allowed this client to simulate.
+    horizon: u64,
+    /// The tick that this client last told the host it had reached.
+    reported_tick: u64,
+    /// The checksums of recent ticks, so they can be compared with those of other peers.
+    recent_checksums: HashMap<u64, u64>,
+    /// The first tick on which a peer's state differed from ours, if any.
+    desynced_at: Option<u64>,
+}
+
+impl NetworkSession {
+    /// The number of ticks between each checksum.
+    const CHECKSUM_INTERVAL: u64 = 60;
+
+    /// The number of checksums that are remembered.
+    const CHECKSUM_HISTORY: u64 = 10;
+
+    /// The number of ticks between an action being scheduled by the host and it being applied.
+    ///
+    /// Clients may simulate this far ahead of the host, and the action must reach them before they get there.
+    const INPUT_DELAY: u64 = 6;
+
+    /// The furthest that the host runs ahead of a client that has caught up, in ticks.
+    ///
+    /// Clients that are further behind than this fast-forward to catch up.
+    const MAX_LEAD: u64 = 30;
+
+    /// The longest that a client spends fast-forwarding each frame while it catches up.
+    const CATCH_UP_BUDGET: Duration = Duration::from_millis(20);
+
+    /// Creates a session that is not connected to anyone yet.
+    fn offline(config: MultiplayerConfig) -> Self {
+        NetworkSession {
+            config,
+            listener: None,
+            peers: Vec::new(),
+            tick: 0,
+            actions: Vec::new(),
+            applied: 0,
+            horizon: 0,
+            reported_tick: 0,
+            recent_checksums: HashMap::new(),
+            desynced_at: None,
+        }
+    }
+
+    /// Starts hosting, or connects to the host, as set by the `config`.
+    fn new(config: MultiplayerConfig) -> std::io::Result<Self> {
+        let mut session = NetworkSession::offline(config);
+
+        match session.config.role {
+            NetworkRole::Host(address) => {
+                let listener = TcpListener::bind(address)?;
+                listener.set_nonblocking(true)?;
+                info!("Hosting multiplayer game on {address}");
+                session.listener = Some(listener);
+            }
+            NetworkRole::Client(address) => {
+                let peer = Peer::new(TcpStream::connect(address)?)?;
+                info!("Connected to multiplayer game at {address}");
+                session.peers.push(peer);
+            }
+        }
+
+        Ok(session)
+    }
+
+    /// Is this game the host?
+    pub fn is_host(&self) -> bool {
+        matches!(self.config.role, NetworkRole::Host(_))
+    }
+
+    /// The names of the other players that have joined this game.
+    pub fn player_names(&self) -> impl Iterator<Item = &str> {
+        self.peers
+            .iter()
+            .filter_map(|peer| peer.player_name.as_deref())
+    }
+
+    /// The first tick on which another player's game differed from ours, if any.
+    pub fn desynced_at(&self) -> Option<u64> {
+        self.desynced_at
+    }
+
+    /// Sends the `message` to every player that has joined this game.
+    fn broadcast(&mut self, message: &NetMessage) {
+        for peer in self.peers.iter_mut() {
+            // Peers that have not finished joining don't share our world yet
+            if peer.player_name.is_some() {
+                peer.send(message);
+            }
+        }
+    }
+
+    /// Can the simulation advance another tick?
+    ///
+    /// Clients may not pass the horizon set by the host,
+    /// and the host waits for any client that has fallen more than [`NetworkSession::MAX_LEAD`] ticks behind.
+    fn allows_tick(&self) -> bool {
+        match self.is_host() {
+            true => self
+                .peers
+                .iter()
+                .filter(|peer| peer.caught_up)
+                .all(|peer| peer.progress + Self::MAX_LEAD > self.tick),
+            false => self.tick < self.horizon,
+        }
+    }
+
+    /// Takes an `action` on behalf of the local player.
+    ///
+    /// The host schedules it straight away, while clients ask the host to schedule it.
+    pub(crate) fn submit(&mut self, action: SharedAction) {
+        if self.is_host() {
+            self.schedule(action);
+            return;
+        }
+
+        match self
+            .peers
+            .first_mut()
+            .filter(|host| host.player_name.is_some())
+        {
+            Some(host) => host.send(&NetMessage::Request { action }),
+            None => warn!("Dropping {action:?}: not connected to the host"),
+        }
+    }
+
+    /// Schedules the `action` for a tick that no client can have reached yet.
+    fn schedule(&mut self, action: SharedAction) {
+        self.actions.push((self.tick + Self::INPUT_DELAY, action));
+    }
+
+    /// Returns the actions that are due on or before the current tick, in order, and marks them as applied.
+    fn take_due_actions(&mut self) -> Vec<(u64, SharedAction)> {
+        let due: Vec<(u64, SharedAction)> = self.actions[self.applied..]
+            .iter()
+            .take_while(|(tick, _)| *tick <= self.tick)
+            .cloned()
+            .collect();
+
+        self.applied += due.len();
+        due
+    }
+
+    /// Sends each client the actions that it has not seen yet, then lets it simulate up to the new horizon.
+    ///
+    /// Clients that have just joined are sent every action so far,
+    /// only as quickly as their connection accepts them.
+    fn sync_clients(&mut self) {
+        let horizon = self.tick + Self::INPUT_DELAY;
+
+        for peer in self.peers.iter_mut() {
+            if peer.player_name.is_none() {
+                continue;
+            }
+
+            while let Some((tick, action)) = self.actions.get(peer.actions_sent) {
+                if peer.disconnected || peer.outbox.len() > MAX_BUFFERED_BYTES / 2 {
+                    break;
+                }
+
+                peer.send(&NetMessage::Action {
+                    tick: *tick,
+                    action: action.clone(),
+                });
+                peer.actions_sent += 1;
+            }
+
+            // Clients must not reach the tick of an action that they haven't received yet
+            if peer.actions_sent == self.actions.len() && peer.horizon_sent != horizon {
+                peer.send(&NetMessage::Advance { horizon });
+                peer.horizon_sent = horizon;
+            }
+        }
+    }
+
+    /// Records the local `checksum` for the current tick, forgetting any that are too old to be useful.
+    fn record_checksum(&mut self, checksum: u64) {
+        self.recent_checksums.insert(self.tick, checksum);
+
+        let oldest = self
+            .tick
+            .saturating_sub(Self::CHECKSUM_INTERVAL * Self::CHECKSUM_HISTORY);
+        self.recent_checksums.retain(|&tick, _| tick >= oldest);
+    }
+
+    /// Compares a `checksum` received from a peer to our own for the same `tick`.
+    ///
+    /// Returns `true` if a new desync was detected.
+    fn compare_checksum(&mut self, tick: u64, checksum: u64) -> bool {
+        match self.recent_checksums.get(&tick) {
+            Some(&local) if local != checksum && self.desynced_at.is_none() => {
+                self.desynced_at = Some(tick);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Computes an order-independent checksum of the simulation state.
+///
+/// This covers the type and position of every unit and structure,
+/// which is enough to spot games that have diverged without hashing the entire world.
+fn simulation_checksum<'a>(
+    units: impl Iterator<Item = (&'a Id<Unit>, &'a VoxelPos)>,
+    structures: impl Iterator<Item = (&'a Id<Structure>, &'a VoxelPos)>,
+) -> u64 {
+    /// Hashes a single value with a fixed key, so that results are the same on every machine.
+    fn hash_one(value: impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Entities are not processed in a consistent order, so combine their hashes commutatively
+    let unit_checksum = units.fold(0u64, |checksum, unit| checksum.wrapping_add(hash_one(unit)));
+    let structure_checksum = structures.fold(0u64, |checksum, structure| {
+        checksum.wrapping_add(hash_one(structure))
+    });
+
+    hash_one((unit_checksum, structure_checksum))
+}
+
+/// Lets new players join, if this game is the host.
+fn accept_connections(mut session: ResMut<NetworkSession>) {
+    let Some(listener) = &session.listener else {
+        return;
+    };
+
+    let mut new_peers = Vec::new();
+    loop {
+        match listener.accept() {
+            Ok((stream, address)) => match Peer::new(stream) {
+                Ok(peer) => {
+                    info!("New connection from {address}");
+                    new_peers.push(peer);
+                }
+                Err(error) => warn!("Could not accept connection from {address}: {error}"),
+            },
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) => {
+                warn!("Could not accept connection: {error}");
+                break;
+            }
+        }
+    }
+
+    session.peers.extend(new_peers);
+}
+
+/// Handles messages from other players, and forgets about those that have disconnected.
+fn exchange_messages(mut session: ResMut<NetworkSession>, gen_config: Res<GenerationConfig>) {
+    let session = &mut *session;
+    let is_host = session.is_host();
+    let mut new_desyncs = Vec::new();
+    let mut requested_actions = Vec::new();
+
+    for peer in session.peers.iter_mut() {
+        peer.flush();
+
+        for message in peer.receive() {
+            match message {
+                NetMessage::Hello {
+                    protocol_version,
+                    player_name,
+                    seed,
+                } if is_host => {
+                    let rejection = if protocol_version != NetMessage::PROTOCOL_VERSION {
+                        Some(format!(
+                            "Protocol version {protocol_version} does not match the host's version {}",
+                            NetMessage::PROTOCOL_VERSION
+                        ))
+                    } else if seed != gen_config.seed {
+                        Some(format!("World seed {seed} does not match the host's world"))
+                    } else {
+                        None
+                    };
+
+                    match rejection {
+                        Some(reason) => {
+                            info!("Rejected {player_name}: {reason}");
+                            peer.send(&NetMessage::Rejected { reason });
+                            peer.disconnected = true;
+                        }
+                        None => {
+                            // The actions taken so far are sent by `sync_clients`
+                            info!("{player_name} joined the game");
+                            peer.player_name = Some(player_name);
+                            peer.send(&NetMessage::Welcome {
+                                player_name: session.config.player_name.clone(),
+                            });
+                        }
+                    }
+                }
+                NetMessage::Welcome { player_name } if !is_host => {
+                    info!("Joined {player_name}'s game");
+                    peer.player_name = Some(player_name);
+                }
+                NetMessage::Rejected { reason } if !is_host => {
+                    error!("Could not join multiplayer game: {reason}");
+                    peer.disconnected = true;
+                }
+                NetMessage::Checksum { tick, checksum } => new_desyncs.push((tick, checksum)),
+                NetMessage::Request { action } if is_host && peer.player_name.is_some() => {
+                    requested_actions.push(action);
+                }
+                NetMessage::Action { tick, action } if !is_host => {
+                    session.actions.push((tick, action));
+                }
+                NetMessage::Advance { horizon } if !is_host => {
+                    session.horizon = session.horizon.max(horizon);
+                }
+                NetMessage::Progress { tick } if is_host && peer.player_name.is_some() => {
+                    peer.progress = tick;
+
+                    if !peer.caught_up && tick + NetworkSession::MAX_LEAD > session.tick {
+                        info!("{} has caught up", peer.name());
+                        peer.caught_up = true;
+                    }
+                }
+                message => warn!(
+                    "Ignoring unexpected message from {}: {message:?}",
+                    peer.name()
+                ),
+            }
+        }
+
+        // Clients introduce themselves as soon as they connect
+        if !is_host && !peer.introduced {
+            peer.introduced = true;
+            peer.send(&NetMessage::Hello {
+                protocol_version: NetMessage::PROTOCOL_VERSION,
+                player_name: session.config.player_name.clone(),
+                seed: gen_config.seed,
+            });
+        }
+    }
+
+    for action in requested_actions {
+        session.schedule(action);
+    }
+
+    session.peers.retain(|peer| !peer.disconnected);
+
+    for (tick, checksum) in new_desyncs {
+        if session.compare_checksum(tick, checksum) {
+            error!("Multiplayer desync detected at tick {tick}");
+        }
+    }
+}
+
+/// Fast-forwards a client that is far behind the host, such as one that has just joined a game in progress.
+///
+/// Ticks are run directly, as many as fit in [`NetworkSession::CATCH_UP_BUDGET`] each frame.
+fn catch_up_with_host(world: &mut World) {
+    let start = Instant::now();
+
+    while start.elapsed() < NetworkSession::CATCH_UP_BUDGET {
+        let session = world.resource::<NetworkSession>();
+        let tick = session.tick;
+        // Clients that are close behind keep up using the usual fixed timestep
+        if session.is_host() || tick + NetworkSession::MAX_LEAD >= session.horizon {
+            return;
+        }
+
+        run_requested_tick(world);
+
+        // The simulation can't run yet, such as while the world is being generated
+        if world.resource::<NetworkSession>().tick == tick {
+            return;
+        }
+    }
+}
+
+/// Is the simulation allowed to advance by the other players?
+fn lockstep_allows_tick(session: Res<NetworkSession>) -> bool {
+    session.allows_tick()
+}
+
+/// Applies the actions that are due on the current tick, in the order that they were scheduled.
+fn apply_shared_actions(mut session: ResMut<NetworkSession>, mut commands: Commands) {
+    let current_tick = session.tick;
+
+    for (tick, action) in session.take_due_actions() {
+        // Lockstep should make this impossible, so the games have probably diverged
+        if tick < current_tick {
+            warn!("Applying an action from tick {tick} late, on tick {current_tick}");
+        }
+
+        commands.add(action);
+    }
+}
+
+/// Counts the simulation ticks that have elapsed.
+fn count_ticks(mut session: ResMut<NetworkSession>) {
+    session.tick += 1;
+}
+
+/// Periodically shares a checksum of the simulation state with other players.
+fn share_checksum(
+    mut session: ResMut<NetworkSession>,
+    unit_query: Query<(&Id<Unit>, &VoxelPos)>,
+    structure_query: Query<(&Id<Structure>, &VoxelPos)>,
+) {
+    // While the simulation is paused, the tick does not advance and has already been checked
+    if session.tick % NetworkSession::CHECKSUM_INTERVAL != 0
+        || session.recent_checksums.contains_key(&session.tick)
+    {
+        return;
+    }
+
+    let checksum = simulation_checksum(unit_query.iter(), structure_query.iter());
+    session.record_checksum(checksum);
+
+    let message = NetMessage::Checksum {
+        tick: session.tick,
+        checksum,
+    };
+    session.broadcast(&message);
+}
+
+/// Tells the other players how far the simulation has got.
+///
+/// The host sends out the actions taken this frame and the new horizon,
+/// while clients report their progress to the host.
+fn sync_peers(mut session: ResMut<NetworkSession>) {
+    if session.is_host() {
+        session.sync_clients();
+    } else if session.reported_tick != session.tick {
+        let tick = session.tick;
+        session.reported_tick = tick;
+        session.broadcast(&NetMessage::Progress { tick });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::Hex;
+
+    use crate::{
+        crafting::recipe::ActiveRecipe,
+        geometry::MapGeometry,
+        organisms::corpses::{CorpseHandling, CorpsePolicy},
+        player_interaction::bulk_commands::BulkCommand,
+    };
+
+    use super::{actions::SharedRouting, *};
+
+    #[test]
+    fn messages_are_split_on_newlines() {
+        let hello = NetMessage::Welcome {
+            player_name: "Host".to_string(),
+        };
+        let checksum = NetMessage::Checksum {
+            tick: 60,
+            checksum: 42,
+        };
+
+        let mut buffer = serde_json::to_vec(&hello).unwrap();
+        buffer.push(b'\n');
+        buffer.extend(b"not a message\n");
+        let checksum_line = serde_json::to_vec(&checksum).unwrap();
+        let (first_half, second_half) = checksum_line.split_at(checksum_line.len() / 2);
+        buffer.extend(first_half);
+
+        assert_eq!(split_messages::<NetMessage>(&mut buffer), vec![hello]);
+        assert_eq!(buffer, first_half);
+
+        buffer.extend(second_half);
+        buffer.push(b'\n');
+        assert_eq!(split_messages::<NetMessage>(&mut buffer), vec![checksum]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn checksums_ignore_entity_order() {
+        let acacia = Id::<Structure>::from_name("acacia".to_string());
+        let ant = Id::<Unit>::from_name("ant".to_string());
+        let here = VoxelPos::ZERO;
+        let there = VoxelPos::from_xy(1, 0);
+
+        let units = [(ant, here), (ant, there)];
+        let reversed_units = [(ant, there), (ant, here)];
+        let structures = [(acacia, here)];
+
+        let checksum = |units: &[(Id<Unit>, VoxelPos)]| {
+            simulation_checksum(
+                units.iter().map(|(id, pos)| (id, pos)),
+                structures.iter().map(|(id, pos)| (id, pos)),
+            )
+        };
+
+        assert_eq!(checksum(&units), checksum(&reversed_units));
+        assert_ne!(checksum(&units), checksum(&units[..1]));
+    }
+
+    /// The settings of a host that is not listening for anyone.
+    fn host_config() -> MultiplayerConfig {
+        MultiplayerConfig {
+            role: NetworkRole::Host("127.0.0.1:0".parse().unwrap()),
+            player_name: "Host".to_string(),
+        }
+    }
+
+    /// The settings of a client that is not connected to anyone.
+    fn client_config() -> MultiplayerConfig {
+        MultiplayerConfig {
+            role: NetworkRole::Client("127.0.0.1:0".parse().unwrap()),
+            player_name: "Client".to_string(),
+        }
+    }
+
+    #[test]
+    fn mismatched_checksums_are_desyncs() {
+        let mut session = NetworkSession::offline(host_config());
+        session.tick = 60;
+
+        session.record_checksum(7);
+        // We don't know our own state at this tick, so we can't compare
+        assert!(!session.compare_checksum(120, 8));
+        assert!(!session.compare_checksum(60, 7));
+        assert_eq!(session.desynced_at(), None);
+
+        assert!(session.compare_checksum(60, 8));
+        assert_eq!(session.desynced_at(), Some(60));
+        // Only the first desync is reported
+        assert!(!session.compare_checksum(60, 9));
+    }
+
+    /// Opens a connection on the loopback interface, returning our end as a [`Peer`] and the raw remote end.
+    fn connected_peer() -> (Peer, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let local = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (remote, _) = listener.accept().unwrap();
+
+        (Peer::new(local).unwrap(), remote)
+    }
+
+    #[test]
+    fn full_connections_keep_unsent_messages() {
+        let (mut peer, mut remote) = connected_peer();
+        let message = NetMessage::Rejected {
+            reason: "x".repeat(10_000),
+        };
+
+        // Nobody is reading, so the connection eventually stops accepting data
+        for _ in 0..10_000 {
+            peer.send(&message);
+            if !peer.outbox.is_empty() {
+                break;
+            }
+        }
+        assert!(!peer.outbox.is_empty());
+        assert!(!peer.disconnected);
+
+        remote.set_nonblocking(true).unwrap();
+        let mut chunk = [0; 4096];
+        for _ in 0..100_000 {
+            while remote.read(&mut chunk).is_ok() {}
+            peer.flush();
+            if peer.outbox.is_empty() {
+                break;
+            }
+        }
+        assert!(peer.outbox.is_empty());
+        assert!(!peer.disconnected);
+    }
+
+    #[test]
+    fn oversized_messages_disconnect() {
+        let (mut peer, mut remote) = connected_peer();
+        let writer = std::thread::spawn(move || {
+            // The write fails once the peer hangs up, which is expected
+            let _ = remote.write_all(&vec![b'x'; MAX_BUFFERED_BYTES + 1]);
+        });
+
+        for _ in 0..100_000 {
+            assert!(peer.receive().is_empty());
+            if peer.disconnected {
+                break;
+            }
+            std::thread::yield_now();
+        }
+        assert!(peer.disconnected);
+        assert!(peer.buffer.len() <= MAX_BUFFERED_BYTES + 4096);
+
+        drop(peer);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn actions_round_trip_through_json() {
+        let messages = vec![
+            NetMessage::Request {
+                action: SharedAction::ApplyBulkCommand {
+                    command: BulkCommand::TogglePriority,
+                    voxels: vec![VoxelPos::ZERO, VoxelPos::from_xy(1, 0)],
+                    enable: true,
+                },
+            },
+            NetMessage::Action {
+                tick: 12,
+                action: SharedAction::Zone {
+                    voxel_pos: VoxelPos::from_xy(3, -1),
+                    structure_id: Id::from_name("acacia".to_string()),
+                    direction: hexx::Direction::Top,
+                    active_recipe: ActiveRecipe::default(),
+                },
+            },
+            NetMessage::Action {
+                tick: 13,
+                action: SharedAction::RouteOutputs {
+                    structure: VoxelPos::ZERO,
+                    routing: Some(SharedRouting::Storage(VoxelPos::from_xy(2, 2))),
+                },
+            },
+        ];
+
+        let mut buffer = Vec::new();
+        for message in &messages {
+            buffer.extend(serde_json::to_vec(message).unwrap());
+            buffer.push(b'\n');
+        }
+        assert_eq!(split_messages::<NetMessage>(&mut buffer), messages);
+    }
+
+    #[test]
+    fn clients_stop_at_the_horizon() {
+        let mut session = NetworkSession::offline(client_config());
+        assert!(!session.allows_tick());
+
+        session.horizon = 6;
+        assert!(session.allows_tick());
+
+        session.tick = 6;
+        assert!(!session.allows_tick());
+    }
+
+    #[test]
+    fn hosts_only_wait_for_clients_that_have_caught_up() {
+        let mut session = NetworkSession::offline(host_config());
+        let (mut joining, _joining_remote) = connected_peer();
+        let (mut playing, _playing_remote) = connected_peer();
+        joining.player_name = Some("Joining".to_string());
+        playing.player_name = Some("Playing".to_string());
+        playing.caught_up = true;
+        session.peers = vec![joining, playing];
+
+        session.tick = NetworkSession::MAX_LEAD - 1;
+        assert!(session.allows_tick());
+
+        session.tick = NetworkSession::MAX_LEAD;
+        assert!(!session.allows_tick());
+
+        session.peers[1].progress = 1;
+        assert!(session.allows_tick());
+    }
+
+    #[test]
+    fn actions_are_applied_in_order_after_the_input_delay() {
+        let mut session = NetworkSession::offline(host_config());
+        let first = SharedAction::ClearZoning {
+            voxel_pos: VoxelPos::ZERO,
+        };
+        let second = SharedAction::Demolish {
+            voxel_pos: VoxelPos::ZERO,
+        };
+
+        session.schedule(first.clone());
+        session.tick = 1;
+        session.schedule(second.clone());
+        assert_eq!(session.actions[0].0, NetworkSession::INPUT_DELAY);
+
+        session.tick = NetworkSession::INPUT_DELAY - 1;
+        assert!(session.take_due_actions().is_empty());
+
+        session.tick = NetworkSession::INPUT_DELAY + 1;
+        assert_eq!(
+            session.take_due_actions(),
+            vec![
+                (NetworkSession::INPUT_DELAY, first),
+                (NetworkSession::INPUT_DELAY + 1, second)
+            ]
+        );
+        // Each action is only applied once
+        assert!(session.take_due_actions().is_empty());
+    }
+
+    #[test]
+    fn due_actions_change_the_world() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        world.insert_resource(map_geometry);
+        world.init_resource::<CorpsePolicy>();
+        let mut session = NetworkSession::offline(host_config());
+        session.schedule(SharedAction::SetCorpseHandling {
+            handling: CorpseHandling::HaulAway,
+        });
+        session.tick = NetworkSession::INPUT_DELAY;
+        world.insert_resource(session);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((apply_shared_actions, apply_deferred).chain());
+        schedule.run(&mut world);
+
+        assert_eq!(
+            world.resource::<CorpsePolicy>().default_handling,
+            CorpseHandling::HaulAway
+        );
+    }
+
+    #[test]
+    fn clients_are_sent_every_action_before_advancing() {
+        let mut session = NetworkSession::offline(host_config());
+        let (mut client, mut remote) = connected_peer();
+        client.player_name = Some("Client".to_string());
+        session.peers.push(client);
+
+        let action = SharedAction::CancelTerraform { hex: Hex::ZERO };
+        session.schedule(action.clone());
+        session.tick = 2;
+        session.sync_clients();
+        // Nothing new has happened, so nothing more is sent
+        session.sync_clients();
+
+        let expected = vec![
+            NetMessage::Action {
+                tick: NetworkSession::INPUT_DELAY,
+                action,
+            },
+            NetMessage::Advance {
+                horizon: 2 + NetworkSession::INPUT_DELAY,
+            },
+        ];
+
+        let mut buffer = Vec::new();
+        let mut chunk = [0; 4096];
+        while buffer.iter().filter(|&&byte| byte == b'\n').count() < expected.len() {
+            let n = remote.read(&mut chunk).unwrap();
+            buffer.extend_from_slice(&chunk[..n]);
+        }
+        assert_eq!(split_messages::<NetMessage>(&mut buffer), expected);
+    }
+}
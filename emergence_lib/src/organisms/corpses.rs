@@ -0,0 +1,175 @@
+//! Dead organisms leave their remains behind.
+//!
+//! Corpses are dropped as litter where the organism died.
+//! Depending on the player's [`CorpsePolicy`], they are either left to rot into the soil,
+//! or hauled away to be composted or stored in a graveyard.
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    items::{
+        item_manifest::Item,
+        ledger::{ItemLedger, ItemSource},
+    },
+    litter::LitterCommandsExt,
+    structures::structure_manifest::StructureManifest,
+    terrain::history::{TileEvent, TileEventKind},
+    units::unit_manifest::UnitManifest,
+};
+
+use super::OrganismId;
+
+/// What should be done with corpses once they're dropped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CorpseHandling {
+    /// Leave corpses where they fell, where they will decompose and fertilize the soil.
+    #[default]
+    LeaveToDecay,
+    /// Haul corpses away, to be composted or stored.
+    HaulAway,
+}
+
+impl CorpseHandling {
+    /// The other way of handling corpses.
+    pub fn toggle(self) -> Self {
+        match self {
+            CorpseHandling::LeaveToDecay => CorpseHandling::HaulAway,
+            CorpseHandling::HaulAway => CorpseHandling::LeaveToDecay,
+        }
+    }
+}
+
+impl Display for CorpseHandling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            CorpseHandling::LeaveToDecay => "Leave to decay",
+            CorpseHandling::HaulAway => "Haul away",
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// The player's chosen way of dealing with each kind of corpse.
+///
+/// Corpses without an entry use the `default_handling`.
+/// This is part of the game state, and is stored in saves.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorpsePolicy {
+    /// How corpses are handled unless overridden.
+    pub default_handling: CorpseHandling,
+    /// The handling of specific kinds of corpses that differ from the default.
+    #[serde(with = "crate::save_files::map_as_pairs")]
+    overrides: HashMap<Id<Item>, CorpseHandling>,
+}
+
+impl CorpsePolicy {
+    /// How corpses of type `item_id` should be handled.
+    pub fn get(&self, item_id: Id<Item>) -> CorpseHandling {
+        self.overrides
+            .get(&item_id)
+            .copied()
+            .unwrap_or(self.default_handling)
+    }
+
+    /// Sets how corpses of type `item_id` should be handled.
+    pub fn set(&mut self, item_id: Id<Item>, handling: CorpseHandling) {
+        if handling == self.default_handling {
+            self.overrides.remove(&item_id);
+        } else {
+            self.overrides.insert(item_id, handling);
+        }
+    }
+}
+
+/// Drops a corpse wherever an organism that leaves one behind has died.
+pub(super) fn spawn_corpses(
+    mut tile_events: EventReader<TileEvent>,
+    unit_manifest: Res<UnitManifest>,
+    structure_manifest: Res<StructureManifest>,
+    map_geometry: Res<MapGeometry>,
+    mut item_ledger: ResMut<ItemLedger>,
+    mut commands: Commands,
+) {
+    for tile_event in tile_events.read() {
+        let TileEventKind::OrganismDied { organism_id, .. } = tile_event.kind else {
+            continue;
+        };
+
+        let maybe_corpse = match organism_id {
+            OrganismId::Unit(unit_id) => unit_manifest.get(unit_id).organism_variety.corpse,
+            OrganismId::Structure(structure_id) => structure_manifest
+                .get(structure_id)
+                .organism_variety
+                .as_ref()
+                .and_then(|organism_variety| organism_variety.corpse),
+        };
+
+        let Some(corpse) = maybe_corpse else {
+            continue;
+        };
+
+        let Ok(terrain_height) = map_geometry.get_height(tile_event.hex) else {
+            continue;
+        };
+
+        let voxel_pos = VoxelPos {
+            hex: tile_event.hex,
+            height: terrain_height.above(),
+        };
+
+        commands.spawn_litter(voxel_pos, corpse);
+        item_ledger.record_produced(corpse, 1, ItemSource::Corpse);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_take_precedence_over_the_default() {
+        let corpse = Id::from_name("corpse".to_string());
+        let other_corpse = Id::from_name("other_corpse".to_string());
+        let mut corpse_policy = CorpsePolicy::default();
+
+        corpse_policy.set(corpse, CorpseHandling::HaulAway);
+        assert_eq!(corpse_policy.get(corpse), CorpseHandling::HaulAway);
+        assert_eq!(
+            corpse_policy.get(other_corpse),
+            CorpseHandling::LeaveToDecay
+        );
+
+        corpse_policy.default_handling = CorpseHandling::HaulAway;
+        assert_eq!(corpse_policy.get(other_corpse), CorpseHandling::HaulAway);
+    }
+
+    #[test]
+    fn corpse_policy_survives_saving_and_loading() {
+        use crate::save_files::{load_world, save_world, SaveResourceExt};
+
+        let corpse = Id::from_name("corpse".to_string());
+        let mut app = App::new();
+        app.init_resource::<CorpsePolicy>()
+            .save_resource::<CorpsePolicy>("corpse_policy");
+        let mut corpse_policy = app.world.resource_mut::<CorpsePolicy>();
+        corpse_policy.default_handling = CorpseHandling::HaulAway;
+        corpse_policy.set(corpse, CorpseHandling::LeaveToDecay);
+        let saved = save_world(&app.world).unwrap();
+
+        let mut loaded_app = App::new();
+        loaded_app
+            .init_resource::<CorpsePolicy>()
+            .save_resource::<CorpsePolicy>("corpse_policy");
+        load_world(&mut loaded_app.world, &saved).unwrap();
+
+        let loaded_policy = loaded_app.world.resource::<CorpsePolicy>();
+        assert_eq!(loaded_policy.get(corpse), CorpseHandling::LeaveToDecay);
+        assert_eq!(*loaded_policy, *app.world.resource::<CorpsePolicy>());
+    }
+}
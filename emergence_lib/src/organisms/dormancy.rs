@@ -0,0 +1,339 @@
+//! Organisms can become dormant to wait out adverse conditions.
+//!
+//! Dormant organisms pay only a fraction of their usual energy upkeep,
+//! but cannot act or craft until conditions improve.
+
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use leafwing_abilities::prelude::Pool;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{Height, MapGeometry, VoxelPos},
+    structures::structure_manifest::{Structure, StructureManifest},
+    temperature::Temperature,
+    units::unit_manifest::{Unit, UnitManifest},
+    water::WaterDepth,
+};
+
+use super::{
+    energy::{Energy, EnergyPool},
+    Organism, OrganismVariety,
+};
+
+/// The conditions under which an organism will enter dormancy.
+///
+/// These are set on a per-variety basis in the manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DormancyConditions {
+    /// Enter dormancy when stored energy falls below this amount.
+    pub starvation_threshold: Option<Energy>,
+    /// Enter dormancy when the water table is deeper than this below the surface.
+    pub drought_depth: Option<Height>,
+    /// Enter dormancy when the temperature falls below this.
+    pub frost_temperature: Option<Temperature>,
+    /// Enter dormancy when the temperature rises above this.
+    pub heat_temperature: Option<Temperature>,
+    /// The fraction of the organism's usual energy upkeep that is paid while dormant.
+    ///
+    /// This should be between 0.0 and 1.0.
+    pub upkeep_fraction: f32,
+}
+
+impl DormancyConditions {
+    /// Determines why an organism should be dormant, if at all.
+    ///
+    /// If several conditions are met, starvation is reported first, then drought, then temperature.
+    fn cause(
+        &self,
+        energy_pool: &EnergyPool,
+        water_depth: &WaterDepth,
+        temperature: Temperature,
+    ) -> Option<DormancyCause> {
+        if let Some(starvation_threshold) = self.starvation_threshold {
+            if energy_pool.current() < starvation_threshold {
+                return Some(DormancyCause::Starvation);
+            }
+        }
+
+        if let Some(drought_depth) = self.drought_depth {
+            let parched = match water_depth {
+                WaterDepth::Dry => true,
+                WaterDepth::Underground(depth) => *depth > drought_depth,
+                WaterDepth::Flooded(..) => false,
+            };
+
+            if parched {
+                return Some(DormancyCause::Drought);
+            }
+        }
+
+        if let Some(frost_temperature) = self.frost_temperature {
+            if temperature < frost_temperature {
+                return Some(DormancyCause::Frost);
+            }
+        }
+
+        if let Some(heat_temperature) = self.heat_temperature {
+            if temperature > heat_temperature {
+                return Some(DormancyCause::Heat);
+            }
+        }
+
+        None
+    }
+}
+
+/// Why an organism is dormant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DormancyCause {
+    /// The organism is low on energy.
+    Starvation,
+    /// There is not enough water nearby.
+    Drought,
+    /// It is too cold.
+    Frost,
+    /// It is too hot.
+    Heat,
+}
+
+impl Display for DormancyCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DormancyCause::Starvation => write!(f, "starvation"),
+            DormancyCause::Drought => write!(f, "drought"),
+            DormancyCause::Frost => write!(f, "frost"),
+            DormancyCause::Heat => write!(f, "heat"),
+        }
+    }
+}
+
+/// An organism that is currently dormant.
+#[derive(Component, Debug, Clone, PartialEq)]
+#[component(storage = "SparseSet")]
+pub struct Dormant {
+    /// Why this organism went dormant.
+    cause: DormancyCause,
+    /// The fraction of the organism's usual energy upkeep that is paid while dormant.
+    upkeep_fraction: f32,
+}
+
+impl Dormant {
+    #[cfg(test)]
+    /// Creates a new [`Dormant`] component with the given `cause`.
+    pub(crate) fn new(cause: DormancyCause, upkeep_fraction: f32) -> Self {
+        Dormant {
+            cause,
+            upkeep_fraction,
+        }
+    }
+
+    /// Why this organism went dormant.
+    pub fn cause(&self) -> DormancyCause {
+        self.cause
+    }
+
+    /// The fraction of the organism's usual energy upkeep that is paid while dormant.
+    pub fn upkeep_fraction(&self) -> f32 {
+        self.upkeep_fraction
+    }
+
+    /// Can this organism craft while dormant?
+    ///
+    /// Starving organisms keep crafting, as this is typically how they regain energy.
+    pub(crate) fn blocks_crafting(&self) -> bool {
+        self.cause != DormancyCause::Starvation
+    }
+
+    /// Must this unit stop what it is doing while dormant?
+    ///
+    /// Starving units keep acting, as eating is how they regain energy.
+    pub(crate) fn blocks_actions(&self) -> bool {
+        self.cause != DormancyCause::Starvation
+    }
+}
+
+impl Display for Dormant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Dormant ({})", self.cause)
+    }
+}
+
+/// Causes organisms to enter dormancy when conditions are poor, and wake once they improve.
+pub(super) fn enter_and_exit_dormancy(
+    organism_query: Query<
+        (
+            Entity,
+            &EnergyPool,
+            &VoxelPos,
+            Option<&Id<Unit>>,
+            Option<&Id<Structure>>,
+            Option<&Dormant>,
+        ),
+        With<Organism>,
+    >,
+    terrain_query: Query<(&WaterDepth, &Temperature)>,
+    unit_manifest: Res<UnitManifest>,
+    structure_manifest: Res<StructureManifest>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    for (entity, energy_pool, voxel_pos, maybe_unit_id, maybe_structure_id, maybe_dormant) in
+        organism_query.iter()
+    {
+        let maybe_variety: Option<&OrganismVariety> = match (maybe_unit_id, maybe_structure_id) {
+            (Some(&unit_id), _) => Some(&unit_manifest.get(unit_id).organism_variety),
+            (None, Some(&structure_id)) => structure_manifest
+                .get(structure_id)
+                .organism_variety
+                .as_ref(),
+            (None, None) => None,
+        };
+
+        let Some(conditions) = maybe_variety.and_then(|variety| variety.dormancy.as_ref()) else {
+            continue;
+        };
+
+        let Ok(terrain_entity) = map_geometry.get_terrain(voxel_pos.hex) else {
+            continue;
+        };
+        let Ok((water_depth, &temperature)) = terrain_query.get(terrain_entity) else {
+            continue;
+        };
+
+        match (
+            conditions.cause(energy_pool, water_depth, temperature),
+            maybe_dormant,
+        ) {
+            (Some(cause), maybe_dormant) => {
+                if maybe_dormant.map(Dormant::cause) != Some(cause) {
+                    commands.entity(entity).insert(Dormant {
+                        cause,
+                        upkeep_fraction: conditions.upkeep_fraction,
+                    });
+                }
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Dormant>();
+            }
+            (None, None) => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::organisms::{lifecycle::Lifecycle, OrganismBundle};
+
+    use super::*;
+
+    #[test]
+    fn starvation_causes_dormancy() {
+        let conditions = DormancyConditions {
+            starvation_threshold: Some(Energy(10.)),
+            drought_depth: None,
+            frost_temperature: None,
+            heat_temperature: None,
+            upkeep_fraction: 0.1,
+        };
+
+        let mut energy_pool = EnergyPool::simple(100.);
+        assert_eq!(
+            conditions.cause(&energy_pool, &WaterDepth::Dry, Temperature::MILD),
+            Some(DormancyCause::Starvation)
+        );
+
+        energy_pool.set_current(Energy(50.));
+        assert_eq!(
+            conditions.cause(&energy_pool, &WaterDepth::Dry, Temperature::MILD),
+            None
+        );
+    }
+
+    #[test]
+    fn drought_causes_dormancy() {
+        let conditions = DormancyConditions {
+            starvation_threshold: None,
+            drought_depth: Some(Height(2.)),
+            frost_temperature: None,
+            heat_temperature: None,
+            upkeep_fraction: 0.1,
+        };
+
+        let energy_pool = EnergyPool::simple(100.);
+        assert_eq!(
+            conditions.cause(&energy_pool, &WaterDepth::Dry, Temperature::MILD),
+            Some(DormancyCause::Drought)
+        );
+        assert_eq!(
+            conditions.cause(
+                &energy_pool,
+                &WaterDepth::Underground(Height(3.)),
+                Temperature::MILD
+            ),
+            Some(DormancyCause::Drought)
+        );
+        assert_eq!(
+            conditions.cause(
+                &energy_pool,
+                &WaterDepth::Underground(Height(1.)),
+                Temperature::MILD
+            ),
+            None
+        );
+        assert_eq!(
+            conditions.cause(
+                &energy_pool,
+                &WaterDepth::Flooded(Height(1.)),
+                Temperature::MILD
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn extreme_temperatures_cause_dormancy() {
+        let conditions = DormancyConditions {
+            starvation_threshold: None,
+            drought_depth: None,
+            frost_temperature: Some(Temperature(0.)),
+            heat_temperature: Some(Temperature(35.)),
+            upkeep_fraction: 0.1,
+        };
+
+        let energy_pool = EnergyPool::simple(100.);
+        let water_depth = WaterDepth::Flooded(Height(1.));
+        assert_eq!(
+            conditions.cause(&energy_pool, &water_depth, Temperature(-5.)),
+            Some(DormancyCause::Frost)
+        );
+        assert_eq!(
+            conditions.cause(&energy_pool, &water_depth, Temperature(40.)),
+            Some(DormancyCause::Heat)
+        );
+        assert_eq!(
+            conditions.cause(&energy_pool, &water_depth, Temperature::MILD),
+            None
+        );
+    }
+
+    #[test]
+    fn going_dormant_does_not_move_organisms_between_tables() {
+        let mut world = World::new();
+        let entity = world
+            .spawn(OrganismBundle::new(
+                EnergyPool::simple(100.),
+                Lifecycle::default(),
+            ))
+            .id();
+        let table_id = world.entity(entity).location().table_id;
+
+        world.entity_mut(entity).insert(Dormant {
+            cause: DormancyCause::Starvation,
+            upkeep_fraction: 0.1,
+        });
+        assert_eq!(world.entity(entity).location().table_id, table_id);
+    }
+}
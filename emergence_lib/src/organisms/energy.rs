@@ -9,9 +9,14 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::asset_management::manifest::Id;
+use crate::simulation::time::InGameTime;
 use crate::structures::structure_manifest::Structure;
+use crate::terrain::history::{DeathCause, TileEvent, TileEventKind};
+use crate::units::unit_manifest::Unit;
 use crate::{geometry::VoxelPos, structures::commands::StructureCommandsExt};
 
+use super::{dormancy::Dormant, Organism, OrganismId};
+
 /// The amount of energy available to an organism.
 /// If they run out, they die.
 #[derive(Debug, Clone, PartialEq, Component, Resource, Serialize, Deserialize)]
@@ -62,10 +67,57 @@ impl EnergyPool {
         self.current >= self.satiation_threshold
     }
 
+    /// The energy at which this organism stops trying to gain more energy.
+    pub(crate) fn satiation_threshold(&self) -> Energy {
+        self.satiation_threshold
+    }
+
+    /// The energy stored beyond this organism's satiation threshold.
+    pub(crate) fn surplus(&self) -> Energy {
+        Energy((self.current.0 - self.satiation_threshold.0).max(0.))
+    }
+
+    /// The energy needed to bring this organism up to its satiation threshold.
+    pub(crate) fn deficit(&self) -> Energy {
+        Energy((self.satiation_threshold.0 - self.current.0).max(0.))
+    }
+
+    /// The fraction of the maximum energy currently stored, between 0 and 1.
+    pub(crate) fn fraction_full(&self) -> f32 {
+        match self.max > Energy(0.) {
+            true => self.current.0 / self.max.0,
+            false => 0.,
+        }
+    }
+
     /// Is this pool full?
     pub(crate) fn is_full(&self) -> bool {
         self.current >= self.max
     }
+
+    /// Adds `energy` to this pool, returning the amount that could actually be stored.
+    pub(crate) fn credit(&mut self, energy: Energy) -> Energy {
+        let previous = self.current;
+        self.set_current(previous + energy) - previous
+    }
+
+    /// Removes `energy` from this pool, returning the amount that was actually removed.
+    ///
+    /// The pool cannot go below zero: any shortfall is simply not paid.
+    pub(crate) fn debit(&mut self, energy: Energy) -> Energy {
+        let previous = self.current;
+        previous - self.set_current(previous - energy)
+    }
+
+    /// Does this pool contain at least `energy`?
+    pub(crate) fn can_afford(&self, energy: Energy) -> bool {
+        self.current >= energy
+    }
+
+    /// The amount of energy that this organism must spend each second simply to stay alive.
+    pub(crate) fn upkeep_per_second(&self) -> Energy {
+        Energy((-self.regen_per_second.0).max(0.))
+    }
 }
 
 impl Display for EnergyPool {
@@ -172,25 +224,187 @@ pub(crate) enum StartingEnergy {
 }
 
 /// Steadily depletes [`Energy`] over time.
-pub(super) fn consume_energy(time: Res<Time>, mut energy_query: Query<&mut EnergyPool>) {
+pub(super) fn consume_energy(
+    time: Res<Time>,
+    mut energy_query: Query<(&mut EnergyPool, Option<&Dormant>)>,
+    mut colony_energy: ResMut<ColonyEnergy>,
+) {
     let delta_time = time.delta().as_secs_f32();
 
-    for mut energy_pool in energy_query.iter_mut() {
+    for (mut energy_pool, maybe_dormant) in energy_query.iter_mut() {
         // Note that regen rates are almost always negative.
-        let regen_rate = energy_pool.regen_per_second;
-        let current = energy_pool.current();
+        let regen_rate = match maybe_dormant {
+            // Dormant organisms only pay a fraction of their usual upkeep
+            Some(dormant) if energy_pool.regen_per_second < Energy(0.) => {
+                energy_pool.regen_per_second * dormant.upkeep_fraction()
+            }
+            _ => energy_pool.regen_per_second,
+        };
 
-        energy_pool.set_current(current + regen_rate * delta_time);
+        if regen_rate >= Energy(0.) {
+            let gained = energy_pool.credit(regen_rate * delta_time);
+            colony_energy.record_gain(gained);
+        } else {
+            let spent = energy_pool.debit(-1. * regen_rate * delta_time);
+            colony_energy.record_spending(spent);
+        }
     }
 }
 
+/// An aggregate view of the energy stored and spent by all organisms in the world.
+///
+/// This is intended to be displayed to players, to give them a sense of how healthy their colony is.
+#[derive(Debug, Resource, Default)]
+pub struct ColonyEnergy {
+    /// The total energy stored across all organisms.
+    stored: Energy,
+    /// The total energy that could be stored across all organisms.
+    capacity: Energy,
+    /// The total energy spent each second by all organisms simply to stay alive.
+    upkeep_per_second: Energy,
+    /// The number of living organisms.
+    organisms: usize,
+    /// The number of organisms that are close to running out of energy.
+    hungry_organisms: usize,
+    /// The in-game day that the `_today` fields correspond to.
+    current_day: u64,
+    /// The energy gained so far today.
+    gained_today: Energy,
+    /// The energy spent so far today.
+    spent_today: Energy,
+    /// The energy gained over the course of the previous day.
+    gained_yesterday: Energy,
+    /// The energy spent over the course of the previous day.
+    spent_yesterday: Energy,
+}
+
+impl ColonyEnergy {
+    /// The total energy stored across all organisms.
+    pub fn stored(&self) -> Energy {
+        self.stored
+    }
+
+    /// The total energy that could be stored across all organisms.
+    pub fn capacity(&self) -> Energy {
+        self.capacity
+    }
+
+    /// The total energy spent each second by all organisms simply to stay alive.
+    pub fn upkeep_per_second(&self) -> Energy {
+        self.upkeep_per_second
+    }
+
+    /// The number of organisms that are close to running out of energy.
+    pub fn hungry_organisms(&self) -> usize {
+        self.hungry_organisms
+    }
+
+    /// The net energy gained over the previous in-game day.
+    ///
+    /// Negative values indicate that the colony is consuming more energy than it produces.
+    pub fn net_yesterday(&self) -> Energy {
+        self.gained_yesterday - self.spent_yesterday
+    }
+
+    /// Records that energy was gained by some organism.
+    pub(crate) fn record_gain(&mut self, energy: Energy) {
+        self.gained_today += energy;
+    }
+
+    /// Records that energy was spent by some organism.
+    pub(crate) fn record_spending(&mut self, energy: Energy) {
+        self.spent_today += energy;
+    }
+
+    /// Rolls the daily totals over if a new day has begun.
+    fn advance_to_day(&mut self, day: u64) {
+        if day == self.current_day {
+            return;
+        }
+
+        self.current_day = day;
+        self.gained_yesterday = core::mem::take(&mut self.gained_today);
+        self.spent_yesterday = core::mem::take(&mut self.spent_today);
+    }
+}
+
+impl Display for ColonyEnergy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Energy: {}/{} stored, {} upkeep/s, {} net yesterday, {}/{} organisms hungry",
+            self.stored,
+            self.capacity,
+            self.upkeep_per_second,
+            self.net_yesterday(),
+            self.hungry_organisms,
+            self.organisms
+        )
+    }
+}
+
+/// Aggregates the energy of all organisms into the [`ColonyEnergy`] report.
+pub(super) fn update_colony_energy(
+    energy_query: Query<&EnergyPool, With<Organism>>,
+    in_game_time: Res<InGameTime>,
+    mut colony_energy: ResMut<ColonyEnergy>,
+) {
+    colony_energy.advance_to_day(in_game_time.rounded_elapsed_days());
+
+    let mut stored = Energy(0.);
+    let mut capacity = Energy(0.);
+    let mut upkeep_per_second = Energy(0.);
+    let mut organisms = 0;
+    let mut hungry_organisms = 0;
+
+    for energy_pool in energy_query.iter() {
+        stored += energy_pool.current();
+        capacity += energy_pool.max();
+        upkeep_per_second += energy_pool.upkeep_per_second();
+        organisms += 1;
+
+        if energy_pool.is_hungry() {
+            hungry_organisms += 1;
+        }
+    }
+
+    colony_energy.stored = stored;
+    colony_energy.capacity = capacity;
+    colony_energy.upkeep_per_second = upkeep_per_second;
+    colony_energy.organisms = organisms;
+    colony_energy.hungry_organisms = hungry_organisms;
+}
+
 /// Despawns organisms when they run out of energy
 pub(super) fn kill_organisms_when_out_of_energy(
-    organism_query: Query<(Entity, &EnergyPool, &VoxelPos, Option<&Id<Structure>>)>,
+    organism_query: Query<(
+        Entity,
+        &EnergyPool,
+        &VoxelPos,
+        Option<&Id<Structure>>,
+        Option<&Id<Unit>>,
+    )>,
+    mut tile_events: EventWriter<TileEvent>,
     mut commands: Commands,
 ) {
-    for (entity, energy_pool, voxel_pos, maybe_structure) in organism_query.iter() {
+    for (entity, energy_pool, voxel_pos, maybe_structure, maybe_unit) in organism_query.iter() {
         if energy_pool.is_empty() {
+            let maybe_organism_id = match (maybe_structure, maybe_unit) {
+                (Some(&structure_id), _) => Some(OrganismId::Structure(structure_id)),
+                (None, Some(&unit_id)) => Some(OrganismId::Unit(unit_id)),
+                (None, None) => None,
+            };
+
+            if let Some(organism_id) = maybe_organism_id {
+                tile_events.send(TileEvent {
+                    hex: voxel_pos.hex,
+                    kind: TileEventKind::OrganismDied {
+                        organism_id,
+                        cause: DeathCause::Starvation,
+                    },
+                });
+            }
+
             match maybe_structure {
                 Some(_) => commands.despawn_structure(*voxel_pos),
                 None => commands.entity(entity).despawn_recursive(),
@@ -198,3 +412,55 @@ pub(super) fn kill_organisms_when_out_of_energy(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credit_and_debit_respect_bounds() {
+        let mut energy_pool = EnergyPool::simple(10.);
+
+        assert_eq!(energy_pool.credit(Energy(15.)), Energy(10.));
+        assert!(energy_pool.is_full());
+
+        assert!(energy_pool.can_afford(Energy(10.)));
+        assert!(!energy_pool.can_afford(Energy(11.)));
+
+        assert_eq!(energy_pool.debit(Energy(4.)), Energy(4.));
+        assert_eq!(energy_pool.debit(Energy(100.)), Energy(6.));
+        assert!(energy_pool.is_empty());
+    }
+
+    #[test]
+    fn fraction_full_tracks_current_energy() {
+        let mut energy_pool = EnergyPool::simple(10.);
+        assert_eq!(energy_pool.fraction_full(), 0.);
+
+        energy_pool.credit(Energy(2.5));
+        assert_eq!(energy_pool.fraction_full(), 0.25);
+
+        assert_eq!(EnergyPool::simple(0.).fraction_full(), 0.);
+    }
+
+    #[test]
+    fn upkeep_is_never_negative() {
+        let mut energy_pool = EnergyPool::simple(10.);
+        energy_pool.regen_per_second = Energy(-2.);
+        assert_eq!(energy_pool.upkeep_per_second(), Energy(2.));
+
+        energy_pool.regen_per_second = Energy(2.);
+        assert_eq!(energy_pool.upkeep_per_second(), Energy(0.));
+    }
+
+    #[test]
+    fn colony_energy_rolls_over_daily() {
+        let mut colony_energy = ColonyEnergy::default();
+        colony_energy.record_gain(Energy(5.));
+        colony_energy.record_spending(Energy(2.));
+        assert_eq!(colony_energy.net_yesterday(), Energy(0.));
+
+        colony_energy.advance_to_day(1);
+        assert_eq!(colony_energy.net_yesterday(), Energy(3.));
+    }
+}
@@ -7,13 +7,19 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::Id,
+    factions::Faction,
     geometry::{Facing, MapGeometry, VoxelPos},
     items::item_manifest::ItemManifest,
     litter::Litter,
     player_interaction::clipboard::ClipboardData,
-    simulation::time::{Days, TimePool},
+    simulation::{
+        rng::SystemRng,
+        time::{Days, TickRate, TimePool},
+        weather::Wind,
+    },
     structures::{commands::StructureCommandsExt, structure_manifest::StructureManifest},
     units::{
+        census::Newborn,
         unit_assets::UnitHandles,
         unit_manifest::{Unit, UnitManifest},
         UnitBundle,
@@ -47,6 +53,21 @@ impl Lifecycle {
         Lifecycle { life_paths }
     }
 
+    /// The forms that this organism can turn into, and their triggering conditions.
+    pub(crate) fn life_paths(&self) -> &[LifePath] {
+        &self.life_paths
+    }
+
+    /// How close this organism is to transforming, from 0 to 1.
+    ///
+    /// This is the progress of whichever [`LifePath`] is furthest along.
+    pub(crate) fn progress(&self) -> f32 {
+        self.life_paths
+            .iter()
+            .map(LifePath::progress)
+            .fold(0., f32::max)
+    }
+
     /// Returns the [`OrganismId`] the list of completed [`LifePath`], if any.
     ///
     /// These are prioritized in the order they were added to the lifecycle.
@@ -162,6 +183,33 @@ impl From<RawLifePath> for LifePath {
 }
 
 impl LifePath {
+    /// The fraction of the prerequisites for this transformation that have been met, from 0 to 1.
+    ///
+    /// When there are several conditions, this is the progress of the one that is furthest from being met.
+    pub(crate) fn progress(&self) -> f32 {
+        /// The fraction of `max` that `current` represents.
+        fn fraction(current: f32, max: f32) -> f32 {
+            match max > 0. {
+                true => (current / max).clamp(0., 1.),
+                false => 1.,
+            }
+        }
+
+        let energy_progress = self
+            .energy_required
+            .as_ref()
+            .map(|energy_pool| fraction(energy_pool.current().0, energy_pool.max().0));
+        let time_progress = self
+            .time_required
+            .as_ref()
+            .map(|time_pool| fraction(time_pool.current().0, time_pool.max().0));
+
+        energy_progress
+            .into_iter()
+            .chain(time_progress)
+            .fold(1., f32::min)
+    }
+
     /// Have all of the prerequisites been met to transform?
     pub(crate) fn is_complete(&self) -> bool {
         // All conditions must be true in order for the life path to be complete
@@ -211,6 +259,7 @@ pub(super) fn transform_when_lifecycle_complete(
         &Facing,
         &EnergyPool,
         Option<&Id<Unit>>,
+        Option<&Faction>,
     )>,
     structure_manifest: Res<StructureManifest>,
     unit_manifest: Res<UnitManifest>,
@@ -218,7 +267,9 @@ pub(super) fn transform_when_lifecycle_complete(
     map_geometry: Res<MapGeometry>,
     mut commands: Commands,
 ) {
-    for (entity, lifecycle, &voxel_pos, &facing, energy_pool, maybe_unit) in query.iter() {
+    for (entity, lifecycle, &voxel_pos, &facing, energy_pool, maybe_unit, maybe_faction) in
+        query.iter()
+    {
         for new_form in lifecycle.new_forms() {
             // Make sure that there's a valid place to spawn the new form.
             if let OrganismId::Structure(structure_id) = new_form {
@@ -250,16 +301,26 @@ pub(super) fn transform_when_lifecycle_complete(
                     // Preserve the energy of the parent organism.
                     let starting_energy = StartingEnergy::Specific(energy_pool.current());
 
-                    commands.spawn_structure(voxel_pos, data, starting_energy);
+                    // Ownership carries over to the new form
+                    commands.spawn_structure(
+                        voxel_pos,
+                        data,
+                        starting_energy,
+                        maybe_faction.copied(),
+                    );
                 }
                 OrganismId::Unit(unit_id) => {
                     let unit_data = unit_manifest.get(unit_id).clone();
 
-                    commands.spawn(UnitBundle::newborn(
-                        unit_id,
-                        voxel_pos,
-                        unit_data,
-                        &unit_handles,
+                    commands.spawn((
+                        UnitBundle::newborn(
+                            unit_id,
+                            voxel_pos,
+                            unit_data,
+                            maybe_faction.copied().unwrap_or_default(),
+                            &unit_handles,
+                        ),
+                        Newborn,
                     ));
                 }
             }
@@ -278,17 +339,21 @@ pub(super) fn sprout_seeds(
     unit_manifest: Res<UnitManifest>,
     unit_handles: Res<UnitHandles>,
     map_geometry: Res<MapGeometry>,
+    wind: Res<Wind>,
+    tick_rate: Res<TickRate>,
     mut commands: Commands,
+    mut system_rng: SystemRng,
 ) {
     // TODO: add germination conditions, and vary this based on the seed type.
-    /// The chance that a seed will sprout when dropped on the ground each tick.
+    /// The chance that a seed will sprout when dropped on the ground each tick, at the default [`TickRate`].
     const SEED_SPROUT_CHANCE: f32 = 0.05;
 
-    let rng = &mut rand::thread_rng();
+    let sprout_chance = tick_rate.per_tick_fraction(SEED_SPROUT_CHANCE);
+    let rng = system_rng.get("sprout_seeds");
 
     for (&voxel_pos, mut litter) in litter_query.iter_mut() {
         // Roll to see if any seeds will sprout for this tile this tick.
-        if rng.gen::<f32>() > SEED_SPROUT_CHANCE {
+        if rng.gen::<f32>() > sprout_chance {
             continue;
         }
 
@@ -298,6 +363,14 @@ pub(super) fn sprout_seeds(
                 continue;
             };
 
+            // Plant seeds are carried downwind before they take root
+            let sprout_pos = match organism_id {
+                OrganismId::Structure(..) => {
+                    seed_landing_position(voxel_pos, &wind, &map_geometry, rng)
+                }
+                OrganismId::Unit(..) => voxel_pos,
+            };
+
             // Generate a random facing now, so we can verify that the new organism fits.
             let facing = Facing::random(rng);
 
@@ -306,7 +379,7 @@ pub(super) fn sprout_seeds(
                 let structure_data = structure_manifest.get(structure_id);
 
                 if map_geometry
-                    .is_space_available(voxel_pos, &structure_data.footprint, facing)
+                    .is_space_available(sprout_pos, &structure_data.footprint, facing)
                     .is_ok()
                 {
                     // We can't germinate here
@@ -314,7 +387,7 @@ pub(super) fn sprout_seeds(
                 }
             } else {
                 // For units, just make sure the tile is empty.
-                if map_geometry.is_voxel_clear(voxel_pos).is_err() {
+                if map_geometry.is_voxel_clear(sprout_pos).is_err() {
                     continue;
                 }
             }
@@ -334,19 +407,52 @@ pub(super) fn sprout_seeds(
                             .starting_recipe()
                             .clone(),
                     };
-                    commands.spawn_structure(voxel_pos, data, StartingEnergy::Full);
+                    commands.spawn_structure(sprout_pos, data, StartingEnergy::Full, None);
                 }
                 OrganismId::Unit(unit_id) => {
                     let unit_data = unit_manifest.get(unit_id).clone();
 
-                    commands.spawn(UnitBundle::newborn(
-                        unit_id,
-                        voxel_pos,
-                        unit_data,
-                        &unit_handles,
+                    // FIXME: track who dropped the seed, rather than giving all hatchlings to the player
+                    commands.spawn((
+                        UnitBundle::newborn(
+                            unit_id,
+                            sprout_pos,
+                            unit_data,
+                            Faction::PLAYER,
+                            &unit_handles,
+                        ),
+                        Newborn,
                     ));
                 }
             }
         }
     }
 }
+
+/// The farthest that the strongest winds can carry a seed before it sprouts, in tiles.
+const MAX_SEED_DISPERSAL_DISTANCE: f32 = 4.;
+
+/// Determines where a seed dropped at `voxel_pos` will land after being carried by the `wind`.
+///
+/// Seeds travel a random distance downwind, up to [`MAX_SEED_DISPERSAL_DISTANCE`] tiles in the strongest winds.
+/// Seeds that would be blown off the map stay where they were dropped.
+fn seed_landing_position(
+    voxel_pos: VoxelPos,
+    wind: &Wind,
+    map_geometry: &MapGeometry,
+    rng: &mut impl Rng,
+) -> VoxelPos {
+    let max_distance = (wind.speed() * MAX_SEED_DISPERSAL_DISTANCE).round() as u32;
+    let distance = rng.gen_range(0..=max_distance);
+    let direction = wind.hex_direction();
+
+    let mut hex = voxel_pos.hex;
+    for _ in 0..distance {
+        hex = hex.neighbor(direction);
+    }
+
+    match map_geometry.get_height(hex) {
+        Ok(height) => VoxelPos { hex, height }.above(),
+        Err(..) => voxel_pos,
+    }
+}
@@ -1,24 +1,47 @@
 //! Models organisms, which have two primary types: units (organisms that can move around freely)
 //! and structures (organisms that are fixed in place).
+//!
+//! # Component layout
+//!
+//! Every organism has the components in [`OrganismBundle`], which are read or written every tick.
+//! Optional data that is fixed when an organism is spawned, such as a [`NutrientLink`](nutrient_network::NutrientLink),
+//! is stored as an ordinary component: it splits organisms into a few stable archetypes, which are cheap to iterate.
+//!
+//! State that is toggled at runtime, like [`Dormant`](dormancy::Dormant) or [`Disabled`](crate::player_interaction::bulk_commands::Disabled),
+//! must use `#[component(storage = "SparseSet")]` instead.
+//! Adding or removing a table component moves every other component of the entity to a new table,
+//! and fragments the archetypes that systems iterate over.
+//! Systems should check for these markers with [`Has`] or filters, rather than fetching them.
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::Id,
+    items::item_manifest::Item,
+    save_files::SaveResourceExt,
     simulation::SimulationSet,
     structures::structure_manifest::{Structure, StructureManifest},
     units::unit_manifest::{Unit, UnitManifest},
 };
 
 use self::{
-    energy::{consume_energy, kill_organisms_when_out_of_energy, EnergyPool},
+    corpses::{spawn_corpses, CorpsePolicy},
+    dormancy::{enter_and_exit_dormancy, DormancyConditions},
+    energy::{
+        consume_energy, kill_organisms_when_out_of_energy, update_colony_energy, ColonyEnergy,
+        EnergyPool,
+    },
     lifecycle::{sprout_seeds, transform_when_lifecycle_complete, Lifecycle, RawLifecycle},
+    nutrient_network::{share_nutrients, NutrientNetworks},
     oxygen::{manage_oxygen, Oxygen, OxygenPool},
     vegetative_reproduction::vegetative_spread,
 };
 
+pub mod corpses;
+pub mod dormancy;
 pub mod energy;
 pub mod lifecycle;
+pub mod nutrient_network;
 pub mod oxygen;
 pub mod vegetative_reproduction;
 
@@ -114,6 +137,10 @@ pub struct OrganismVariety {
     pub lifecycle: Lifecycle,
     /// Controls the maximum energy, and the rate at which it drains.
     pub energy_pool: EnergyPool,
+    /// The conditions under which this organism will become dormant, if any.
+    pub dormancy: Option<DormancyConditions>,
+    /// The item left behind when this organism dies, if any.
+    pub corpse: Option<Id<Item>>,
 }
 
 impl OrganismVariety {
@@ -126,6 +153,8 @@ impl OrganismVariety {
             prototypical_form: OrganismId::Unit(Id::from_name(name.to_string())),
             lifecycle: Lifecycle::default(),
             energy_pool: EnergyPool::default(),
+            dormancy: None,
+            corpse: None,
         }
     }
 }
@@ -139,6 +168,10 @@ pub struct RawOrganismVariety {
     pub lifecycle: RawLifecycle,
     /// Controls the maximum energy, and the rate at which it drains.
     pub energy_pool: EnergyPool,
+    /// The conditions under which this organism will become dormant, if any.
+    pub dormancy: Option<DormancyConditions>,
+    /// The name of the item left behind when this organism dies, if any.
+    pub corpse: Option<String>,
 }
 
 impl From<RawOrganismVariety> for OrganismVariety {
@@ -147,6 +180,8 @@ impl From<RawOrganismVariety> for OrganismVariety {
             prototypical_form: raw.prototypical_form.into(),
             lifecycle: raw.lifecycle.into(),
             energy_pool: raw.energy_pool,
+            dormancy: raw.dormancy,
+            corpse: raw.corpse.map(Id::from_name),
         }
     }
 }
@@ -160,17 +195,27 @@ pub struct OrganismPlugin;
 
 impl Plugin for OrganismPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            FixedUpdate,
-            (
-                consume_energy,
-                kill_organisms_when_out_of_energy,
-                transform_when_lifecycle_complete,
-                vegetative_spread,
-                sprout_seeds,
-                manage_oxygen,
-            )
-                .in_set(SimulationSet),
-        );
+        app.init_resource::<ColonyEnergy>()
+            .init_resource::<CorpsePolicy>()
+            .save_resource::<CorpsePolicy>("corpse_policy")
+            .init_resource::<NutrientNetworks>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    consume_energy,
+                    share_nutrients.after(consume_energy),
+                    update_colony_energy.after(share_nutrients),
+                    enter_and_exit_dormancy,
+                    kill_organisms_when_out_of_energy,
+                    transform_when_lifecycle_complete,
+                    vegetative_spread,
+                    sprout_seeds,
+                    manage_oxygen,
+                    spawn_corpses
+                        .after(kill_organisms_when_out_of_energy)
+                        .after(manage_oxygen),
+                )
+                    .in_set(SimulationSet),
+            );
     }
 }
@@ -0,0 +1,416 @@
+//! Living structures connected by roots and fungal threads share their energy through a nutrient network.
+//!
+//! Each [`NutrientLink`] joins every living structure within its reach into a single network,
+//! and links that reach each other (or share a member) merge their networks.
+//! Within a network, energy stored beyond a member's satiation threshold is pooled,
+//! and handed out to the members that are short of energy: [`Prioritized`] structures first,
+//! and then the most deficient.
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::Hex;
+use leafwing_abilities::prelude::Pool;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::VoxelPos,
+    player_interaction::bulk_commands::{Disabled, Prioritized},
+    structures::structure_manifest::Structure,
+};
+
+use super::{
+    energy::{Energy, EnergyPool},
+    Organism,
+};
+
+/// A structure that connects nearby living structures into a shared nutrient network.
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NutrientLink {
+    /// How far away (in tiles) other structures can be and still be connected by this link.
+    pub reach: u32,
+    /// The maximum energy that this link can move through its network each second.
+    ///
+    /// The capacities of all links in a network add together.
+    pub transfer_rate: Energy,
+}
+
+/// A group of living structures that share energy through their [`NutrientLink`]s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NutrientNetwork {
+    /// The living structures in this network.
+    members: Vec<Entity>,
+    /// The links that hold this network together.
+    links: Vec<Entity>,
+    /// The energy moved between members each second, as of the last update.
+    transferred_per_second: Energy,
+}
+
+impl NutrientNetwork {
+    /// The living structures in this network.
+    pub fn members(&self) -> &[Entity] {
+        &self.members
+    }
+
+    /// The links that hold this network together.
+    pub fn links(&self) -> &[Entity] {
+        &self.links
+    }
+
+    /// The energy moved between members each second, as of the last update.
+    pub fn transferred_per_second(&self) -> Energy {
+        self.transferred_per_second
+    }
+}
+
+/// All of the nutrient networks in the world, rebuilt every tick.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct NutrientNetworks {
+    /// The current networks.
+    networks: Vec<NutrientNetwork>,
+}
+
+impl NutrientNetworks {
+    /// Iterates over all networks.
+    pub fn iter(&self) -> impl Iterator<Item = &NutrientNetwork> {
+        self.networks.iter()
+    }
+
+    /// The network that `entity` belongs to, as either a member or a link, if any.
+    pub fn network_of(&self, entity: Entity) -> Option<&NutrientNetwork> {
+        self.networks
+            .iter()
+            .find(|network| network.members.contains(&entity) || network.links.contains(&entity))
+    }
+}
+
+/// A minimal union-find structure, used to group links into networks.
+struct DisjointSets {
+    /// The parent of each element; roots are their own parent.
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    /// Creates `n` singleton sets.
+    fn new(n: usize) -> Self {
+        DisjointSets {
+            parents: (0..n).collect(),
+        }
+    }
+
+    /// The representative element of the set containing `i`.
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // Compress the path so that later lookups are fast
+        let mut current = i;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`.
+    fn union(&mut self, a: usize, b: usize) {
+        let root_a = self.find(a);
+        let root_b = self.find(b);
+        self.parents[root_a] = root_b;
+    }
+}
+
+/// Groups `links` and `organisms` into networks, returning the indexes of the links and organisms in each.
+///
+/// Each link is described by its position and reach.
+/// Networks without any organisms are omitted.
+fn find_networks(links: &[(Hex, u32)], organisms: &[Hex]) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let mut sets = DisjointSets::new(links.len());
+
+    for (i, &(hex_i, reach_i)) in links.iter().enumerate() {
+        for (j, &(hex_j, reach_j)) in links.iter().enumerate().skip(i + 1) {
+            if hex_i.unsigned_distance_to(hex_j) <= reach_i.max(reach_j) {
+                sets.union(i, j);
+            }
+        }
+    }
+
+    // Organisms within reach of several links bridge their networks
+    let mut organism_links = Vec::with_capacity(organisms.len());
+    for &organism_hex in organisms {
+        let mut in_reach = links
+            .iter()
+            .enumerate()
+            .filter(|(_, &(link_hex, reach))| organism_hex.unsigned_distance_to(link_hex) <= reach)
+            .map(|(i, _)| i);
+
+        let first = in_reach.next();
+        if let Some(first) = first {
+            for other in in_reach {
+                sets.union(first, other);
+            }
+        }
+        organism_links.push(first);
+    }
+
+    let mut networks: HashMap<usize, (Vec<usize>, Vec<usize>)> = HashMap::default();
+    for (organism_index, maybe_link) in organism_links.into_iter().enumerate() {
+        if let Some(link_index) = maybe_link {
+            let root = sets.find(link_index);
+            networks.entry(root).or_default().1.push(organism_index);
+        }
+    }
+
+    for link_index in 0..links.len() {
+        let root = sets.find(link_index);
+        if let Some(network) = networks.get_mut(&root) {
+            network.0.push(link_index);
+        }
+    }
+
+    let mut networks: Vec<_> = networks.into_values().collect();
+    // Sort to make the result independent of the iteration order of the map
+    networks.sort_by_key(|(link_indexes, _)| link_indexes[0]);
+    networks
+}
+
+/// A living structure's stake in its nutrient network.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Participant {
+    /// The energy stored beyond this member's satiation threshold.
+    surplus: Energy,
+    /// The energy needed to bring this member up to its satiation threshold.
+    deficit: Energy,
+    /// Should this member be fed before others?
+    prioritized: bool,
+    /// How close this member is to its satiation threshold, between 0 and 1.
+    fullness: f32,
+}
+
+impl Participant {
+    /// Describes the state of an organism with the given `energy_pool`.
+    fn new(energy_pool: &EnergyPool, prioritized: bool) -> Self {
+        let satiation_threshold = energy_pool.satiation_threshold();
+        let fullness = match satiation_threshold > Energy(0.) {
+            true => (energy_pool.current().0 / satiation_threshold.0).min(1.),
+            false => 1.,
+        };
+
+        Participant {
+            surplus: energy_pool.surplus(),
+            deficit: energy_pool.deficit(),
+            prioritized,
+            fullness,
+        }
+    }
+}
+
+/// Decides how much energy each participant gains (or loses, if negative), moving at most `max_transfer`.
+///
+/// Prioritized participants are fed first, then those that are furthest from satiation.
+/// Donors give in proportion to their surplus.
+fn plan_transfers(participants: &[Participant], max_transfer: Energy) -> Vec<Energy> {
+    let mut changes = vec![Energy(0.); participants.len()];
+
+    let total_surplus: Energy = participants
+        .iter()
+        .fold(Energy(0.), |total, participant| total + participant.surplus);
+    if total_surplus <= Energy(0.) {
+        return changes;
+    }
+
+    let mut recipients: Vec<usize> = (0..participants.len())
+        .filter(|&i| participants[i].deficit > Energy(0.))
+        .collect();
+    recipients.sort_by(|&a, &b| {
+        let (a, b) = (&participants[a], &participants[b]);
+        b.prioritized
+            .cmp(&a.prioritized)
+            .then(a.fullness.total_cmp(&b.fullness))
+    });
+
+    let budget = if total_surplus < max_transfer {
+        total_surplus
+    } else {
+        max_transfer
+    };
+    let mut remaining = budget;
+    for i in recipients {
+        if remaining <= Energy(0.) {
+            break;
+        }
+
+        let deficit = participants[i].deficit;
+        let received = if deficit < remaining {
+            deficit
+        } else {
+            remaining
+        };
+        changes[i] = received;
+        remaining -= received;
+    }
+
+    let delivered = budget - remaining;
+    for (change, participant) in changes.iter_mut().zip(participants) {
+        if participant.surplus > Energy(0.) {
+            *change = Energy(-delivered.0 * participant.surplus.0 / total_surplus.0);
+        }
+    }
+
+    changes
+}
+
+/// Rebuilds the [`NutrientNetworks`], and moves surplus energy between the members of each network.
+pub(super) fn share_nutrients(
+    time: Res<Time>,
+    link_query: Query<(Entity, &VoxelPos, &NutrientLink), Without<Disabled>>,
+    mut organism_query: Query<
+        (Entity, &VoxelPos, &mut EnergyPool, Has<Prioritized>),
+        (With<Organism>, With<Id<Structure>>),
+    >,
+    mut nutrient_networks: ResMut<NutrientNetworks>,
+) {
+    let delta_time = time.delta().as_secs_f32();
+
+    let links: Vec<(Entity, Hex, &NutrientLink)> = link_query
+        .iter()
+        .map(|(entity, voxel_pos, link)| (entity, voxel_pos.hex, link))
+        .collect();
+    let organisms: Vec<(Entity, Hex)> = organism_query
+        .iter()
+        .map(|(entity, voxel_pos, ..)| (entity, voxel_pos.hex))
+        .collect();
+
+    let link_reaches: Vec<(Hex, u32)> = links
+        .iter()
+        .map(|(_, hex, link)| (*hex, link.reach))
+        .collect();
+    let organism_hexes: Vec<Hex> = organisms.iter().map(|(_, hex)| *hex).collect();
+
+    let mut networks = Vec::new();
+    for (link_indexes, organism_indexes) in find_networks(&link_reaches, &organism_hexes) {
+        let members: Vec<Entity> = organism_indexes.iter().map(|&i| organisms[i].0).collect();
+        let max_transfer = link_indexes
+            .iter()
+            .fold(Energy(0.), |total, &i| total + links[i].2.transfer_rate)
+            * delta_time;
+
+        let participants: Vec<Participant> = members
+            .iter()
+            .map(|&entity| {
+                let (.., energy_pool, prioritized) = organism_query.get(entity).unwrap();
+                Participant::new(energy_pool, prioritized)
+            })
+            .collect();
+
+        let changes = plan_transfers(&participants, max_transfer);
+        let mut transferred = Energy(0.);
+        for (&entity, &change) in members.iter().zip(changes.iter()) {
+            if change == Energy(0.) {
+                continue;
+            }
+
+            let (_, _, mut energy_pool, _) = organism_query.get_mut(entity).unwrap();
+            if change > Energy(0.) {
+                transferred += energy_pool.credit(change);
+            } else {
+                energy_pool.debit(Energy(-change.0));
+            }
+        }
+
+        networks.push(NutrientNetwork {
+            members,
+            links: link_indexes.iter().map(|&i| links[i].0).collect(),
+            transferred_per_second: match delta_time > 0. {
+                true => transferred / delta_time,
+                false => Energy(0.),
+            },
+        });
+    }
+
+    // Avoid triggering change detection every tick when nothing has changed
+    nutrient_networks.set_if_neq(NutrientNetworks { networks });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn participant(surplus: f32, deficit: f32, prioritized: bool, fullness: f32) -> Participant {
+        Participant {
+            surplus: Energy(surplus),
+            deficit: Energy(deficit),
+            prioritized,
+            fullness,
+        }
+    }
+
+    #[test]
+    fn links_join_organisms_in_reach() {
+        let links = [
+            (Hex::new(0, 0), 2),
+            (Hex::new(2, 0), 1),
+            (Hex::new(10, 0), 1),
+        ];
+        let organisms = [
+            Hex::new(1, 0),
+            Hex::new(3, 0),
+            Hex::new(10, 1),
+            Hex::new(20, 0),
+        ];
+
+        let networks = find_networks(&links, &organisms);
+        assert_eq!(networks, vec![(vec![0, 1], vec![0, 1]), (vec![2], vec![2])]);
+    }
+
+    #[test]
+    fn organisms_bridge_links() {
+        let links = [(Hex::new(0, 0), 2), (Hex::new(4, 0), 2)];
+        let organisms = [Hex::new(2, 0)];
+
+        let networks = find_networks(&links, &organisms);
+        assert_eq!(networks, vec![(vec![0, 1], vec![0])]);
+    }
+
+    #[test]
+    fn prioritized_and_hungry_members_are_fed_first() {
+        let participants = [
+            participant(30., 0., false, 1.),
+            participant(0., 20., false, 0.5),
+            participant(0., 20., false, 0.1),
+            participant(0., 20., true, 0.9),
+        ];
+
+        let changes = plan_transfers(&participants, Energy(30.));
+        assert_eq!(
+            changes,
+            vec![Energy(-30.), Energy(0.), Energy(10.), Energy(20.)]
+        );
+    }
+
+    #[test]
+    fn donors_give_in_proportion_to_their_surplus() {
+        let participants = [
+            participant(30., 0., false, 1.),
+            participant(10., 0., false, 1.),
+            participant(0., 20., false, 0.),
+        ];
+
+        let changes = plan_transfers(&participants, Energy(100.));
+        assert_eq!(changes, vec![Energy(-15.), Energy(-5.), Energy(20.)]);
+    }
+
+    #[test]
+    fn nothing_moves_without_surplus() {
+        let participants = [
+            participant(0., 10., false, 0.),
+            participant(0., 0., false, 1.),
+        ];
+
+        let changes = plan_transfers(&participants, Energy(100.));
+        assert_eq!(changes, vec![Energy(0.), Energy(0.)]);
+    }
+}
@@ -11,12 +11,13 @@ use std::{
 use crate::{
     asset_management::manifest::Id,
     geometry::{Height, MapGeometry, VoxelPos},
-    structures::{commands::StructureCommandsExt, Footprint},
+    structures::{commands::StructureCommandsExt, structure_manifest::Structure, Footprint},
+    terrain::history::{DeathCause, TileEvent, TileEventKind},
     units::unit_manifest::Unit,
     water::WaterDepth,
 };
 
-use super::Organism;
+use super::{Organism, OrganismId};
 
 /// The amount of oxygen available to an organism.
 /// If they run out, they die.
@@ -155,19 +156,20 @@ impl Pool for OxygenPool {
 
 /// Increases and decreases oxygen levels over time, and kills all organisms that run out of oxygen.
 pub(super) fn manage_oxygen(
-    mut unit_query: Query<(Entity, &VoxelPos, &mut OxygenPool), With<Id<Unit>>>,
+    mut unit_query: Query<(Entity, &Id<Unit>, &VoxelPos, &mut OxygenPool)>,
     mut structure_query: Query<
-        (&VoxelPos, &Footprint, &mut OxygenPool),
+        (&Id<Structure>, &VoxelPos, &Footprint, &mut OxygenPool),
         (Without<Id<Unit>>, With<Organism>),
     >,
     water_depth_query: Query<&WaterDepth>,
     time: Res<Time>,
     map_geometry: Res<MapGeometry>,
+    mut tile_events: EventWriter<TileEvent>,
     mut commands: Commands,
 ) {
     let delta_time = time.delta().as_secs_f32();
 
-    for (entity, &voxel_pos, mut oxygen_pool) in unit_query.iter_mut() {
+    for (entity, &unit_id, &voxel_pos, mut oxygen_pool) in unit_query.iter_mut() {
         let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
         let surface_water_depth = water_depth_query
             .get(terrain_entity)
@@ -179,6 +181,13 @@ pub(super) fn manage_oxygen(
             oxygen_pool.set_current(proposed);
 
             if oxygen_pool.is_empty() {
+                tile_events.send(TileEvent {
+                    hex: voxel_pos.hex,
+                    kind: TileEventKind::OrganismDied {
+                        organism_id: OrganismId::Unit(unit_id),
+                        cause: DeathCause::Suffocation,
+                    },
+                });
                 commands.entity(entity).despawn_recursive();
             }
         } else {
@@ -187,7 +196,7 @@ pub(super) fn manage_oxygen(
         }
     }
 
-    for (&voxel_pos, footprint, mut oxygen_pool) in structure_query.iter_mut() {
+    for (&structure_id, &voxel_pos, footprint, mut oxygen_pool) in structure_query.iter_mut() {
         let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
         let surface_water_depth = water_depth_query
             .get(terrain_entity)
@@ -199,6 +208,13 @@ pub(super) fn manage_oxygen(
             oxygen_pool.set_current(proposed);
 
             if oxygen_pool.is_empty() {
+                tile_events.send(TileEvent {
+                    hex: voxel_pos.hex,
+                    kind: TileEventKind::OrganismDied {
+                        organism_id: OrganismId::Structure(structure_id),
+                        cause: DeathCause::Suffocation,
+                    },
+                });
                 commands.despawn_structure(voxel_pos);
             }
         } else {
@@ -9,8 +9,10 @@ use std::fmt::{self, Display, Formatter};
 
 use crate::{
     asset_management::manifest::Id,
+    factions::Faction,
     geometry::{Facing, MapGeometry, VoxelPos},
     player_interaction::clipboard::ClipboardData,
+    simulation::rng::SystemRng,
     structures::{
         commands::StructureCommandsExt,
         structure_manifest::{Structure, StructureManifest},
@@ -69,16 +71,18 @@ pub(super) fn vegetative_spread(
         &Id<Structure>,
         &mut VegetativeReproduction,
         &mut EnergyPool,
+        Option<&Faction>,
     )>,
     map_geometry: Res<MapGeometry>,
     structure_manifest: Res<StructureManifest>,
     time: Res<Time>,
     mut commands: Commands,
+    mut system_rng: SystemRng,
 ) {
-    let mut rng = rand::thread_rng();
+    let rng = system_rng.get("vegetative_spread");
     let delta_time = time.delta();
 
-    for (&voxel_pos, &structure_id, mut vegetative_reproduction, mut energy_pool) in
+    for (&voxel_pos, &structure_id, mut vegetative_reproduction, mut energy_pool, maybe_owner) in
         query.iter_mut()
     {
         vegetative_reproduction.timer.tick(delta_time);
@@ -95,14 +99,14 @@ pub(super) fn vegetative_spread(
         let empty_neighbors = map_geometry.empty_neighbors(voxel_pos);
         let Some(tile_to_spawn_in) = empty_neighbors
             // Just skip this organism if there are no empty neighbors
-            .choose(&mut rng)
+            .choose(rng)
         else {
             continue;
         };
 
         let clipboard_data = ClipboardData {
             structure_id,
-            facing: Facing::random(&mut rng),
+            facing: Facing::random(rng),
             active_recipe: structure_manifest
                 .get(structure_id)
                 .starting_recipe()
@@ -117,6 +121,8 @@ pub(super) fn vegetative_spread(
             tile_to_spawn_in,
             clipboard_data,
             StartingEnergy::Specific(half_current),
+            // Offshoots belong to whoever owns the parent
+            maybe_owner.copied(),
         );
 
         // Reset the timer once we've successfully spawned a new organism
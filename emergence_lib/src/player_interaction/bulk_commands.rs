@@ -0,0 +1,326 @@
+//! Commands that the player can issue to everything in their current selection at once.
+//!
+//! Both keybindings and the context action bar send [`IssueBulkCommand`] events,
+//! which are then dispatched onto each of the selected entities as marker components.
+//! Changes to anything but units are [`SharedAction`]s, so that every player sees them.
+
+use std::fmt::Display;
+
+use bevy::{
+    ecs::{system::EntityCommands, world::EntityRef},
+    prelude::*,
+    utils::HashSet,
+};
+use emergence_macros::IterableEnum;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    self as emergence_lib,
+    asset_management::manifest::Id,
+    enum_iter::IterableEnum,
+    geometry::{MapGeometry, VoxelPos},
+    multiplayer::actions::{SharedAction, SharedActions},
+    structures::{
+        resource_nodes::{MarkedForHarvest, ResourceNode},
+        structure_manifest::Structure,
+    },
+    units::unit_manifest::Unit,
+};
+
+use super::{
+    feedback::InteractionEvent,
+    selection::{CurrentSelection, SelectedVoxels},
+    InteractionSystem, PlayerAction, PlayerModifiesWorld,
+};
+
+/// Dispatches bulk commands onto the current selection.
+pub(super) struct BulkCommandsPlugin;
+
+impl Plugin for BulkCommandsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<IssueBulkCommand>().add_systems(
+            Update,
+            (
+                send_bulk_commands_from_input,
+                apply_bulk_commands.after(send_bulk_commands_from_input),
+            )
+                .in_set(InteractionSystem::ApplyZoning)
+                .in_set(PlayerModifiesWorld)
+                .after(InteractionSystem::SelectTiles),
+        );
+    }
+}
+
+/// A command that can be applied to every selected tile at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IterableEnum, Serialize, Deserialize)]
+pub enum BulkCommand {
+    /// Mark all wild resources for harvest.
+    Harvest,
+    /// Forbid units from hauling items to or from the selection, or allow it again.
+    ToggleForbidden,
+    /// Prioritize the selected structures, or return them to normal priority.
+    TogglePriority,
+    /// Switch the selected structures off, or back on again.
+    ToggleEnabled,
+    /// Mark the selected unit or structures as favorites, or unmark them.
+    ToggleFavorite,
+}
+
+impl BulkCommand {
+    /// The player action that issues this command.
+    fn player_action(&self) -> PlayerAction {
+        match self {
+            BulkCommand::Harvest => PlayerAction::Harvest,
+            BulkCommand::ToggleForbidden => PlayerAction::ToggleForbidden,
+            BulkCommand::TogglePriority => PlayerAction::TogglePriority,
+            BulkCommand::ToggleEnabled => PlayerAction::ToggleEnabled,
+            BulkCommand::ToggleFavorite => PlayerAction::ToggleFavorite,
+        }
+    }
+
+    /// Can this command be applied to the `entity`?
+    pub(crate) fn applies_to(&self, entity: EntityRef) -> bool {
+        match self {
+            BulkCommand::Harvest => {
+                entity.contains::<ResourceNode>() && !entity.contains::<MarkedForHarvest>()
+            }
+            BulkCommand::ToggleForbidden => !entity.contains::<Id<Unit>>(),
+            BulkCommand::TogglePriority | BulkCommand::ToggleEnabled => {
+                entity.contains::<Id<Structure>>()
+            }
+            BulkCommand::ToggleFavorite => {
+                entity.contains::<Id<Structure>>() || entity.contains::<Id<Unit>>()
+            }
+        }
+    }
+
+    /// Adds this command's marker component to an entity if `enable` is `true`, or removes it otherwise.
+    pub(crate) fn set_marker(&self, entity_commands: &mut EntityCommands, enable: bool) {
+        /// Adds or removes a single `marker`.
+        fn set<M: Component>(entity_commands: &mut EntityCommands, marker: M, enable: bool) {
+            match enable {
+                true => entity_commands.insert(marker),
+                false => entity_commands.remove::<M>(),
+            };
+        }
+
+        match self {
+            BulkCommand::Harvest => set(entity_commands, MarkedForHarvest, enable),
+            BulkCommand::ToggleForbidden => set(entity_commands, Forbidden, enable),
+            BulkCommand::TogglePriority => set(entity_commands, Prioritized, enable),
+            BulkCommand::ToggleEnabled => set(entity_commands, Disabled, enable),
+            BulkCommand::ToggleFavorite => set(entity_commands, Favorite, enable),
+        }
+    }
+}
+
+impl Display for BulkCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            BulkCommand::Harvest => "Harvest",
+            BulkCommand::ToggleForbidden => "Forbid / allow",
+            BulkCommand::TogglePriority => "Prioritize",
+            BulkCommand::ToggleEnabled => "Enable / disable",
+            BulkCommand::ToggleFavorite => "Favorite",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// An event that asks for a [`BulkCommand`] to be applied to the current selection.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IssueBulkCommand(pub BulkCommand);
+
+/// Units will not haul items to or from entities with this component, work at them or shelter in them.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[component(storage = "SparseSet")]
+pub struct Forbidden;
+
+/// The signals emitted by structures with this component are amplified, drawing units to them first.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[component(storage = "SparseSet")]
+pub struct Prioritized;
+
+impl Prioritized {
+    /// The factor by which the signals of prioritized structures are multiplied.
+    pub const SIGNAL_MULTIPLIER: f32 = 4.;
+}
+
+/// Structures with this component have been switched off, without being demolished.
+///
+/// Disabled structures do not craft, and do not ask for items to be delivered,
+/// but their outputs can still be collected.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[component(storage = "SparseSet")]
+pub struct Disabled;
+
+/// Organisms and structures that the player is particularly fond of.
+///
+/// Automation rules only act on structures, and leave favorite structures alone.
+/// Favorite units are only labelled as such in the selection panel, and are kept (along with their nicknames) in saved scenarios.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[component(storage = "SparseSet")]
+pub struct Favorite;
+
+/// Turns player input into [`IssueBulkCommand`] events.
+fn send_bulk_commands_from_input(
+    actions: Res<ActionState<PlayerAction>>,
+    mut bulk_command_events: EventWriter<IssueBulkCommand>,
+) {
+    for bulk_command in BulkCommand::variants() {
+        if actions.just_pressed(bulk_command.player_action()) {
+            bulk_command_events.send(IssueBulkCommand(bulk_command));
+        }
+    }
+}
+
+/// Collects the entities in and directly above the selected voxels.
+///
+/// Selecting terrain should also select whatever is sitting on top of it.
+fn selected_entities(
+    selected_voxels: &SelectedVoxels,
+    map_geometry: &MapGeometry,
+) -> HashSet<Entity> {
+    selected_voxels
+        .iter()
+        .flat_map(|voxel_pos| [*voxel_pos, voxel_pos.above()])
+        .filter_map(|voxel_pos| map_geometry.get_voxel(voxel_pos))
+        .map(|voxel_object| voxel_object.entity)
+        .collect()
+}
+
+/// Applies each [`IssueBulkCommand`] to the entities in the current selection.
+///
+/// Toggles are applied uniformly: if any selected entity lacks the marker, it is added to all of them.
+/// Otherwise, it is removed from all of them.
+///
+/// When a unit is selected, only [`BulkCommand::ToggleFavorite`] has any effect.
+/// When a structure is selected, the command only applies to that structure, and not the terrain beneath it.
+/// Commands that affect nothing are reported as an [`InteractionEvent::InvalidAction`].
+fn apply_bulk_commands(
+    mut bulk_command_events: EventReader<IssueBulkCommand>,
+    current_selection: Res<CurrentSelection>,
+    resource_node_query: Query<(), (With<ResourceNode>, Without<MarkedForHarvest>)>,
+    forbidden_query: Query<Option<&Forbidden>, Without<Id<Unit>>>,
+    priority_query: Query<Option<&Prioritized>, With<Id<Structure>>>,
+    disabled_query: Query<Option<&Disabled>, With<Id<Structure>>>,
+    favorite_query: Query<Has<Favorite>, Or<(With<Id<Structure>>, With<Id<Unit>>)>>,
+    position_query: Query<&VoxelPos>,
+    map_geometry: Res<MapGeometry>,
+    mut interaction_events: EventWriter<InteractionEvent>,
+    mut shared_actions: SharedActions,
+    mut commands: Commands,
+) {
+    let entities = match *current_selection {
+        CurrentSelection::Voxels(ref selected_voxels) => {
+            selected_entities(selected_voxels, &map_geometry)
+        }
+        CurrentSelection::Structure(entity) | CurrentSelection::Unit(entity) => {
+            HashSet::from_iter([entity])
+        }
+        CurrentSelection::None => HashSet::new(),
+    };
+
+    for &IssueBulkCommand(bulk_command) in bulk_command_events.read() {
+        let (targets, enable): (Vec<Entity>, bool) = match bulk_command {
+            BulkCommand::Harvest => {
+                let harvestable = entities
+                    .iter()
+                    .copied()
+                    .filter(|&entity| resource_node_query.contains(entity))
+                    .collect();
+
+                (harvestable, true)
+            }
+            BulkCommand::ToggleForbidden => {
+                let entities: Vec<Entity> = entities
+                    .iter()
+                    .copied()
+                    .filter(|&entity| forbidden_query.contains(entity))
+                    .collect();
+
+                let should_forbid = entities
+                    .iter()
+                    .filter_map(|&entity| forbidden_query.get(entity).ok())
+                    .any(|maybe_forbidden| maybe_forbidden.is_none());
+
+                (entities, should_forbid)
+            }
+            BulkCommand::TogglePriority => {
+                let structures: Vec<(Entity, bool)> = entities
+                    .iter()
+                    .filter_map(|&entity| {
+                        priority_query
+                            .get(entity)
+                            .ok()
+                            .map(|maybe_prioritized| (entity, maybe_prioritized.is_some()))
+                    })
+                    .collect();
+
+                let should_prioritize = structures.iter().any(|(_, prioritized)| !prioritized);
+
+                (
+                    structures.into_iter().map(|(entity, _)| entity).collect(),
+                    should_prioritize,
+                )
+            }
+            BulkCommand::ToggleEnabled => {
+                let structures: Vec<(Entity, bool)> = entities
+                    .iter()
+                    .filter_map(|&entity| {
+                        disabled_query
+                            .get(entity)
+                            .ok()
+                            .map(|maybe_disabled| (entity, maybe_disabled.is_some()))
+                    })
+                    .collect();
+
+                let should_disable = structures.iter().any(|(_, disabled)| !disabled);
+
+                (
+                    structures.into_iter().map(|(entity, _)| entity).collect(),
+                    should_disable,
+                )
+            }
+            BulkCommand::ToggleFavorite => {
+                let nameable: Vec<(Entity, bool)> = entities
+                    .iter()
+                    .filter_map(|&entity| {
+                        favorite_query
+                            .get(entity)
+                            .ok()
+                            .map(|is_favorite| (entity, is_favorite))
+                    })
+                    .collect();
+
+                let should_favorite = nameable.iter().any(|(_, is_favorite)| !is_favorite);
+
+                (
+                    nameable.into_iter().map(|(entity, _)| entity).collect(),
+                    should_favorite,
+                )
+            }
+        };
+
+        if targets.is_empty() {
+            interaction_events.send(InteractionEvent::InvalidAction);
+            continue;
+        }
+
+        // Units can't be found by their position on other machines, and favorite units are only a label
+        if let CurrentSelection::Unit(_) = *current_selection {
+            for entity in targets {
+                bulk_command.set_marker(&mut commands.entity(entity), enable);
+            }
+            continue;
+        }
+
+        shared_actions.take(SharedAction::ApplyBulkCommand {
+            command: bulk_command,
+            voxels: position_query.iter_many(targets).copied().collect(),
+            enable,
+        });
+    }
+}
@@ -30,8 +30,9 @@ pub(super) struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(WorldGenState::Complete), setup_camera)
-            .add_systems(Update, mousewheel_zoom.before(zoom))
+        app.add_event::<FocusCamera>()
+            .add_systems(OnEnter(WorldGenState::Complete), setup_camera)
+            .add_systems(Update, mousewheel_adjustments.before(zoom))
             .add_systems(Update, zoom)
             .add_systems(
                 Update,
@@ -48,6 +49,12 @@ impl Plugin for CameraPlugin {
                     // Avoid jittering when the camera is following a unit
                     .after(drag_camera),
             )
+            .add_systems(
+                Update,
+                focus_camera_on_requests
+                    .after(set_camera_focus)
+                    .before(pan_camera),
+            )
             .add_systems(
                 Update,
                 set_camera_inclination.before(InteractionSystem::MoveCamera),
@@ -240,19 +247,27 @@ mod speed {
     }
 }
 
-/// Zoom the camera based on the mouse wheel
+/// Zoom the camera based on the mouse wheel.
+///
+/// While an area or line selection modifier is held, the mouse wheel changes the selection radius instead.
 ///
 /// This is needed to normalize gamepad / keyboard and mouse wheel zoom rates.
-fn mousewheel_zoom(
+pub(super) fn mousewheel_adjustments(
     mut mouse_wheel_events: EventReader<MouseWheel>,
     mut actions: ResMut<ActionState<PlayerAction>>,
 ) {
     if let Some(first_event) = mouse_wheel_events.read().next() {
-        if first_event.y > 0. {
-            actions.press(PlayerAction::ZoomIn);
-        } else {
-            actions.press(PlayerAction::ZoomOut);
-        }
+        let adjusting_selection =
+            actions.pressed(PlayerAction::Area) || actions.pressed(PlayerAction::Line);
+
+        let action = match (adjusting_selection, first_event.y > 0.) {
+            (true, true) => PlayerAction::IncreaseSelectionRadius,
+            (true, false) => PlayerAction::DecreaseSelectionRadius,
+            (false, true) => PlayerAction::ZoomIn,
+            (false, false) => PlayerAction::ZoomOut,
+        };
+
+        actions.press(action);
     }
     mouse_wheel_events.clear();
 }
@@ -334,7 +349,7 @@ fn zoom(
 fn set_camera_focus(
     actions: Res<ActionState<PlayerAction>>,
     selection: Res<CurrentSelection>,
-    unit_query: Query<&Transform>,
+    transform_query: Query<&Transform>,
     mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
 ) {
     let Ok((mut focus, mut settings)) = camera_query.get_single_mut() else {
@@ -350,10 +365,10 @@ fn set_camera_focus(
                 hex: selected_voxels.center(),
                 height: DiscreteHeight::ZERO,
             }),
-            CurrentSelection::Unit(entity) => {
-                let unit_transform = unit_query.get(*entity).unwrap();
-                Some(VoxelPos::from_world_pos(unit_transform.translation))
-            }
+            CurrentSelection::Structure(entity) | CurrentSelection::Unit(entity) => transform_query
+                .get(*entity)
+                .ok()
+                .map(|transform| VoxelPos::from_world_pos(transform.translation)),
             CurrentSelection::None => None,
         };
 
@@ -365,7 +380,7 @@ fn set_camera_focus(
     // Also rotate the camera to match the orientation of the unit we're following
     if settings.camera_mode == CameraMode::FollowUnit {
         if let CurrentSelection::Unit(entity) = &*selection {
-            let unit_transform = unit_query.get(*entity).unwrap();
+            let unit_transform = transform_query.get(*entity).unwrap();
             let quat = unit_transform.rotation;
             let euler = quat.to_euler(EulerRot::YXZ);
             let angle_around_y = euler.0;
@@ -377,6 +392,29 @@ fn set_camera_focus(
     }
 }
 
+/// Asks the camera to jump to the provided voxel.
+///
+/// This breaks the camera out of [`CameraMode::FollowUnit`].
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FocusCamera(pub(crate) VoxelPos);
+
+/// Moves the camera's focus in response to [`FocusCamera`] events.
+fn focus_camera_on_requests(
+    mut focus_events: EventReader<FocusCamera>,
+    mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
+) {
+    let Ok((mut focus, mut settings)) = camera_query.get_single_mut() else {
+        focus_events.clear();
+        return;
+    };
+
+    // Only the most recent request matters
+    if let Some(FocusCamera(voxel_pos)) = focus_events.read().last() {
+        focus.translation = voxel_pos.top_of_tile();
+        settings.camera_mode = CameraMode::Free;
+    }
+}
+
 /// Pan the camera
 fn pan_camera(
     mut camera_query: Query<(&Transform, &mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
@@ -1,15 +1,16 @@
 //! The clipboard stores selected structures, to later be placed via zoning.
 
 use bevy::{ecs::query::WorldQuery, prelude::*, utils::HashMap};
-use hexx::HexIterExt;
+use hexx::{Hex, HexIterExt};
 use leafwing_input_manager::prelude::ActionState;
 
 use crate::{
     asset_management::manifest::Id,
     construction::{ghosts::Preview, terraform::TerraformingTool},
     crafting::recipe::ActiveRecipe,
-    geometry::{DiscreteHeight, Facing, MapGeometry, VoxelPos},
+    geometry::{rotate_around, DiscreteHeight, Facing, MapGeometry, VoxelPos},
     structures::structure_manifest::{Structure, StructureManifest},
+    terrain::terrain_manifest::Terrain,
 };
 
 use super::{picking::CursorPos, selection::CurrentSelection, InteractionSystem, PlayerAction};
@@ -35,9 +36,17 @@ impl Plugin for ClipboardPlugin {
             )
             .add_systems(
                 Update,
-                rotate_selection
+                pipette
                     .in_set(InteractionSystem::SetClipboard)
+                    .after(InteractionSystem::ComputeCursorPos)
                     .after(copy_selection),
+            )
+            .add_systems(
+                Update,
+                rotate_selection
+                    .in_set(InteractionSystem::SetClipboard)
+                    .after(copy_selection)
+                    .after(pipette),
             );
     }
 }
@@ -159,10 +168,10 @@ impl Tool {
             for (&original_pos, item) in map.iter_mut() {
                 let new_pos = if clockwise {
                     item.facing.rotate_clockwise();
-                    original_pos.hex.clockwise()
+                    rotate_around(original_pos.hex, Hex::ZERO, 1)
                 } else {
                     item.facing.rotate_counterclockwise();
-                    original_pos.hex.counter_clockwise()
+                    rotate_around(original_pos.hex, Hex::ZERO, -1)
                 };
 
                 new_map.insert(
@@ -254,6 +263,12 @@ fn copy_selection(
                     tool.normalize_positions();
                 }
             }
+            CurrentSelection::Structure(structure_entity) => {
+                if let Ok(query_item) = structure_query.get(*structure_entity) {
+                    map.insert(VoxelPos::default(), query_item.into());
+                    *tool = Tool::Structures(map);
+                }
+            }
             // Otherwise, just grab whatever's under the cursor
             CurrentSelection::None | CurrentSelection::Unit(_) => {
                 if let Some(cursor_tile_pos) = cursor_pos.maybe_voxel_pos() {
@@ -268,6 +283,41 @@ fn copy_selection(
     }
 }
 
+/// Arms the tool with whatever is under the cursor, so that more of it can be placed.
+///
+/// Structures (and ghosts) are copied along with their facing and recipe,
+/// while bare terrain selects the terraforming tool that produces that terrain.
+fn pipette(
+    actions: Res<ActionState<PlayerAction>>,
+    mut tool: ResMut<Tool>,
+    cursor_pos: Res<CursorPos>,
+    structure_query: Query<ClipboardQuery, Without<Preview>>,
+    terrain_query: Query<&Id<Terrain>>,
+    map_geometry: Res<MapGeometry>,
+) {
+    if !actions.just_pressed(PlayerAction::Pipette) {
+        return;
+    }
+
+    let Some(voxel_pos) = cursor_pos.maybe_voxel_pos() else {
+        return;
+    };
+
+    let maybe_structure = map_geometry
+        .get_ghost_structure(voxel_pos)
+        .or_else(|| map_geometry.get_structure(voxel_pos));
+
+    if let Some(structure_entity) = maybe_structure {
+        if let Ok(structure) = structure_query.get(structure_entity) {
+            tool.set_to_structure(Some(structure.into()));
+        }
+    } else if let Ok(terrain_entity) = map_geometry.get_terrain(voxel_pos.hex) {
+        if let Ok(&terrain_id) = terrain_query.get(terrain_entity) {
+            *tool = Tool::Terraform(TerraformingTool::Change(terrain_id));
+        }
+    }
+}
+
 /// Rotates the contents of the clipboard based on player input
 fn rotate_selection(actions: Res<ActionState<PlayerAction>>, mut clipboard: ResMut<Tool>) {
     if actions.just_pressed(PlayerAction::RotateClipboardLeft)
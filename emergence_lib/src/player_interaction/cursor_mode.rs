@@ -0,0 +1,178 @@
+//! Tracks what clicking will do, so that the cursor can tell the player before they click.
+//!
+//! The [`CursorMode`] is computed here from the player's inputs and the hovered tile,
+//! and is only displayed by the UI.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    construction::terraform::TerraformingTool,
+    geometry::MapGeometry,
+    structures::{structure_manifest::StructureManifest, Landmark},
+    world_gen::WorldGenState,
+};
+
+use super::{
+    clipboard::Tool, picking::CursorPos, selection::CurrentSelection, InteractionSystem,
+    PlayerAction,
+};
+
+/// Computes the [`CursorMode`] each frame.
+pub(super) struct CursorModePlugin;
+
+impl Plugin for CursorModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorMode>().add_systems(
+            Update,
+            set_cursor_mode
+                .after(InteractionSystem::SelectTiles)
+                .after(InteractionSystem::SetClipboard)
+                .before(InteractionSystem::ApplyZoning)
+                .run_if(in_state(WorldGenState::Complete)),
+        );
+    }
+}
+
+/// What will happen when the player clicks, and whether it will work.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CursorMode {
+    /// The kind of action that clicking performs.
+    pub(crate) action: CursorAction,
+    /// Can that action be performed on the hovered target?
+    pub(crate) validity: TargetValidity,
+}
+
+/// The kind of action that clicking performs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CursorAction {
+    /// Clicking selects tiles, units or structures.
+    #[default]
+    Select,
+    /// Clicking measures a region of the map.
+    Measure,
+    /// Clicking zones the brush area for terraforming.
+    Terraform(TerraformingTool),
+    /// Clicking places the ghost structures in the clipboard.
+    Place,
+    /// The selected structures are being marked for demolition.
+    Demolish,
+}
+
+impl CursorAction {
+    /// Determines the kind of action that clicking performs, based on the player's inputs.
+    fn new(tool: &Tool, actions: &ActionState<PlayerAction>) -> Self {
+        if actions.pressed(PlayerAction::ClearZoning) {
+            CursorAction::Demolish
+        } else if actions.pressed(PlayerAction::Measure) {
+            CursorAction::Measure
+        } else {
+            match tool {
+                Tool::Terraform(terraforming_tool) => CursorAction::Terraform(*terraforming_tool),
+                Tool::Structures(map) if !map.is_empty() => CursorAction::Place,
+                Tool::Structures(_) | Tool::None => CursorAction::Select,
+            }
+        }
+    }
+}
+
+/// Can the [`CursorAction`] be performed on the hovered target?
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TargetValidity {
+    /// The action does not depend on its target, or there is no target.
+    #[default]
+    Neutral,
+    /// The action will succeed.
+    Valid,
+    /// The action will fail.
+    Invalid,
+}
+
+impl From<bool> for TargetValidity {
+    fn from(valid: bool) -> Self {
+        match valid {
+            true => TargetValidity::Valid,
+            false => TargetValidity::Invalid,
+        }
+    }
+}
+
+/// Sets the [`CursorMode`] based on the player's inputs and what they are hovering.
+fn set_cursor_mode(
+    tool: Res<Tool>,
+    actions: Res<ActionState<PlayerAction>>,
+    cursor_pos: Res<CursorPos>,
+    current_selection: Res<CurrentSelection>,
+    map_geometry: Res<MapGeometry>,
+    structure_manifest: Res<StructureManifest>,
+    landmark_query: Query<(), With<Landmark>>,
+    mut cursor_mode: ResMut<CursorMode>,
+) {
+    let action = CursorAction::new(&tool, &actions);
+
+    let validity = match action {
+        CursorAction::Select | CursorAction::Measure => TargetValidity::Neutral,
+        CursorAction::Terraform(_) => match cursor_pos.maybe_voxel_pos() {
+            Some(voxel_pos) => map_geometry.is_valid(voxel_pos.hex).into(),
+            None => TargetValidity::Neutral,
+        },
+        CursorAction::Place => match cursor_pos.maybe_voxel_pos() {
+            // Structures are placed on top of the hovered tile
+            Some(voxel_pos) => tool
+                .offset_positions(voxel_pos)
+                .iter()
+                .all(|(voxel_pos, clipboard_data)| {
+                    let footprint = &structure_manifest
+                        .get(clipboard_data.structure_id)
+                        .footprint;
+                    map_geometry
+                        .is_space_available(voxel_pos.above(), footprint, clipboard_data.facing)
+                        .is_ok()
+                })
+                .into(),
+            None => TargetValidity::Neutral,
+        },
+        CursorAction::Demolish => current_selection
+            .structures(&map_geometry)
+            .into_iter()
+            .any(|structure_entity| !landmark_query.contains(structure_entity))
+            .into(),
+    };
+
+    cursor_mode.set_if_neq(CursorMode { action, validity });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_hands_select() {
+        let actions = ActionState::<PlayerAction>::default();
+
+        assert_eq!(
+            CursorAction::new(&Tool::None, &actions),
+            CursorAction::Select
+        );
+        assert_eq!(
+            CursorAction::new(&Tool::Structures(Default::default()), &actions),
+            CursorAction::Select
+        );
+    }
+
+    #[test]
+    fn held_actions_take_priority_over_the_tool() {
+        let tool = Tool::Terraform(TerraformingTool::Raise);
+        let mut actions = ActionState::<PlayerAction>::default();
+        assert_eq!(
+            CursorAction::new(&tool, &actions),
+            CursorAction::Terraform(TerraformingTool::Raise)
+        );
+
+        actions.press(PlayerAction::Measure);
+        assert_eq!(CursorAction::new(&tool, &actions), CursorAction::Measure);
+
+        actions.press(PlayerAction::ClearZoning);
+        assert_eq!(CursorAction::new(&tool, &actions), CursorAction::Demolish);
+    }
+}
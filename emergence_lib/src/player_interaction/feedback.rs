@@ -0,0 +1,115 @@
+//! Events describing the outcome of the player's actions, so that sound and visual effects can respond to them.
+//!
+//! Interaction systems only send [`InteractionEvent`]s: what feedback to give is left entirely to their readers.
+
+use bevy::prelude::*;
+
+use crate::geometry::VoxelPos;
+
+use super::{clipboard::Tool, selection::CurrentSelection, InteractionSystem};
+
+/// Sends [`InteractionEvent`]s as the player interacts with the world.
+pub(super) struct InteractionFeedbackPlugin;
+
+impl Plugin for InteractionFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InteractionEvent>().add_systems(
+            Update,
+            (
+                report_selection_changes.after(InteractionSystem::SelectTiles),
+                report_armed_tools.after(InteractionSystem::SetClipboard),
+            ),
+        );
+    }
+}
+
+/// Something the player did that deserves feedback.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InteractionEvent {
+    /// The [`CurrentSelection`] changed.
+    SelectionChanged,
+    /// A [`Tool`] was picked up while the player's hands were empty.
+    ToolArmed,
+    /// A new ghost structure was placed at this position.
+    PlacementSucceeded(VoxelPos),
+    /// A ghost structure could not be placed at this position.
+    PlacementFailed(VoxelPos),
+    /// The player asked for something that could not be done with the current selection.
+    InvalidAction,
+}
+
+impl InteractionEvent {
+    /// Sends this event from inside a [`Command`].
+    ///
+    /// Nothing happens if the [`InteractionFeedbackPlugin`] was not added, as is the case in headless simulations.
+    pub(crate) fn send(self, world: &mut World) {
+        if let Some(mut events) = world.get_resource_mut::<Events<InteractionEvent>>() {
+            events.send(self);
+        }
+    }
+}
+
+/// Sends [`InteractionEvent::SelectionChanged`] whenever the selection actually changes.
+fn report_selection_changes(
+    current_selection: Res<CurrentSelection>,
+    mut previous_selection: Local<CurrentSelection>,
+    mut interaction_events: EventWriter<InteractionEvent>,
+) {
+    // The selection is often mutably accessed without being changed
+    if !current_selection.is_changed() || *current_selection == *previous_selection {
+        return;
+    }
+
+    *previous_selection = current_selection.clone();
+    interaction_events.send(InteractionEvent::SelectionChanged);
+}
+
+/// Sends [`InteractionEvent::ToolArmed`] when the player picks up a tool.
+fn report_armed_tools(
+    tool: Res<Tool>,
+    mut was_armed: Local<bool>,
+    mut interaction_events: EventWriter<InteractionEvent>,
+) {
+    let is_armed = !tool.is_empty();
+
+    if is_armed && !*was_armed {
+        interaction_events.send(InteractionEvent::ToolArmed);
+    }
+
+    *was_armed = is_armed;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::construction::terraform::TerraformingTool;
+
+    #[test]
+    fn sending_without_the_plugin_does_nothing() {
+        let mut world = World::new();
+        InteractionEvent::InvalidAction.send(&mut world);
+
+        assert!(!world.contains_resource::<Events<InteractionEvent>>());
+    }
+
+    #[test]
+    fn arming_a_tool_is_reported_once() {
+        let mut app = App::new();
+        app.add_event::<InteractionEvent>()
+            .init_resource::<Tool>()
+            .add_systems(Update, report_armed_tools);
+
+        app.update();
+        *app.world.resource_mut::<Tool>() = Tool::Terraform(TerraformingTool::Raise);
+        app.update();
+        app.update();
+
+        let events = app.world.resource::<Events<InteractionEvent>>();
+        let armed = events
+            .get_reader()
+            .read(events)
+            .filter(|&&event| event == InteractionEvent::ToolArmed)
+            .count();
+        assert_eq!(armed, 1);
+    }
+}
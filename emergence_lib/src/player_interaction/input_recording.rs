@@ -0,0 +1,453 @@
+//! Records the player's inputs to a file, and plays them back, for end-to-end tests of interaction flows.
+//!
+//! Each frame, the held [`PlayerAction`]s and the hovered voxel are captured as a [`RecordedFrame`].
+//! Entities are not stable between runs, so hovered units and structures are recorded by position,
+//! and looked up again when the recording is replayed.
+//! This covers the interaction layers, such as selection, zoning and building,
+//! rather than the simulation: replaying input does not make the simulation deterministic.
+//!
+//! Only button states are recorded: the values of analog inputs such as panning are not,
+//! so the camera may end up in a different place when a recording is replayed.
+//! Frames are replayed one per app update, regardless of how long they originally took.
+//!
+//! Recording and playback are opt-in: add an [`InputRecordingPlugin`] with an [`InputRecordingConfig`] to enable them.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{app::AppExit, prelude::*};
+use leafwing_input_manager::{
+    prelude::{ActionState, InputMap},
+    Actionlike,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    geometry::{MapGeometry, VoxelPos},
+    structures::structure_manifest::Structure,
+    units::unit_manifest::Unit,
+};
+
+use super::{
+    picking::{update_cursor_pos, CursorPos},
+    InteractionSystem, PlayerAction,
+};
+
+/// Records the player's inputs, or replays previously recorded ones.
+#[derive(Default)]
+pub struct InputRecordingPlugin {
+    /// Whether inputs are recorded or replayed, and which file is used.
+    ///
+    /// If this is [`None`], inputs are neither recorded nor replayed.
+    pub config: Option<InputRecordingConfig>,
+}
+
+impl InputRecordingPlugin {
+    /// Configures input recording using [`InputRecordingConfig::from_env`].
+    pub fn from_env() -> Self {
+        InputRecordingPlugin {
+            config: InputRecordingConfig::from_env(),
+        }
+    }
+}
+
+impl Plugin for InputRecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                replay_input.run_if(resource_exists::<InputReplayer>()),
+                record_input.run_if(resource_exists::<InputRecorder>()),
+            )
+                .chain()
+                .in_set(InteractionSystem::ComputeCursorPos)
+                .after(update_cursor_pos),
+        )
+        .add_systems(
+            Last,
+            save_recording_on_exit.run_if(resource_exists::<InputRecorder>()),
+        );
+
+        match &self.config {
+            None => (),
+            Some(InputRecordingConfig::Record(path)) => {
+                info!("Recording input to {}", path.display());
+                app.insert_resource(InputRecorder {
+                    path: Some(path.clone()),
+                    ..Default::default()
+                });
+            }
+            Some(InputRecordingConfig::Replay(path)) => match InputRecording::load(path) {
+                Ok(recording) => {
+                    info!(
+                        "Replaying {} frames of input from {}",
+                        recording.frames.len(),
+                        path.display()
+                    );
+                    app.insert_resource(InputReplayer::new(recording))
+                        .add_systems(Startup, disable_player_input);
+                }
+                Err(error) => error!("Could not replay input from {}: {error}", path.display()),
+            },
+        }
+    }
+}
+
+/// Settings for the [`InputRecordingPlugin`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputRecordingConfig {
+    /// Record inputs, writing them to this file when the app exits.
+    ///
+    /// Any existing file at this path is overwritten.
+    Record(PathBuf),
+    /// Replay the inputs stored in this file, ignoring the player's own inputs until it has finished.
+    Replay(PathBuf),
+}
+
+impl InputRecordingConfig {
+    /// The environment variable that enables recording when set to an output path.
+    pub const RECORD_VAR: &'static str = "EMERGENCE_RECORD_INPUT";
+
+    /// The environment variable that enables playback when set to the path of a recording.
+    ///
+    /// This takes priority over [`InputRecordingConfig::RECORD_VAR`].
+    pub const REPLAY_VAR: &'static str = "EMERGENCE_REPLAY_INPUT";
+
+    /// Reads the input recording settings from the environment.
+    ///
+    /// Returns [`None`] if neither recording nor playback has been requested.
+    pub fn from_env() -> Option<Self> {
+        if let Some(path) = std::env::var_os(Self::REPLAY_VAR) {
+            Some(InputRecordingConfig::Replay(PathBuf::from(path)))
+        } else {
+            std::env::var_os(Self::RECORD_VAR)
+                .map(|path| InputRecordingConfig::Record(PathBuf::from(path)))
+        }
+    }
+}
+
+/// The inputs of a single frame.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RecordedFrame {
+    /// The actions that were held down.
+    pub(crate) pressed: Vec<PlayerAction>,
+    /// The voxel that the cursor was hovering over, if any.
+    pub(crate) cursor: Option<VoxelPos>,
+    /// Was the cursor hovering over the structure in the [`RecordedFrame::cursor`] voxel?
+    #[serde(default)]
+    pub(crate) hovering_structure: bool,
+    /// The position of the unit that the cursor was hovering over, if any.
+    #[serde(default)]
+    pub(crate) hovered_unit: Option<VoxelPos>,
+}
+
+impl RecordedFrame {
+    /// Captures the current inputs.
+    ///
+    /// `hovered_unit` is the position of the unit under the cursor, if any.
+    fn capture(
+        actions: &ActionState<PlayerAction>,
+        cursor_pos: &CursorPos,
+        hovered_unit: Option<VoxelPos>,
+    ) -> Self {
+        RecordedFrame {
+            pressed: actions.get_pressed(),
+            cursor: cursor_pos.maybe_voxel_pos(),
+            hovering_structure: cursor_pos.maybe_structure().is_some(),
+            hovered_unit,
+        }
+    }
+
+    /// Overwrites the current inputs with the ones recorded in this frame.
+    ///
+    /// Actions are only pressed or released when their state differs,
+    /// so actions that stay held are not pressed again.
+    ///
+    /// The hovered structure and unit are found again by position, using `find_structure` and `find_unit`.
+    pub(crate) fn apply(
+        &self,
+        actions: &mut ActionState<PlayerAction>,
+        cursor_pos: &mut CursorPos,
+        find_structure: impl FnOnce(VoxelPos) -> Option<Entity>,
+        find_unit: impl FnOnce(VoxelPos) -> Option<Entity>,
+    ) {
+        for action in PlayerAction::variants() {
+            let held = self.pressed.contains(&action);
+
+            if held && !actions.pressed(action.clone()) {
+                actions.press(action);
+            } else if !held && actions.pressed(action.clone()) {
+                actions.release(action);
+            }
+        }
+
+        let mut replayed_cursor_pos = match self.cursor {
+            Some(voxel_pos) => CursorPos::new(voxel_pos),
+            None => CursorPos::default(),
+        };
+
+        if let Some(structure_entity) = self
+            .cursor
+            .filter(|_| self.hovering_structure)
+            .and_then(find_structure)
+        {
+            replayed_cursor_pos = replayed_cursor_pos.hovering_structure(structure_entity);
+        }
+
+        if let Some(unit_entity) = self.hovered_unit.and_then(find_unit) {
+            replayed_cursor_pos = replayed_cursor_pos.hovering_unit(unit_entity);
+        }
+
+        *cursor_pos = replayed_cursor_pos;
+    }
+}
+
+/// A sequence of recorded inputs, one [`RecordedFrame`] per frame.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct InputRecording {
+    /// The recorded frames, in the order that they happened.
+    pub(crate) frames: Vec<RecordedFrame>,
+}
+
+/// An error produced when reading or writing an [`InputRecording`].
+#[derive(Debug, Error)]
+pub(crate) enum InputRecordingError {
+    /// An [IO](std::io) Error
+    #[error("Could not access input recording: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [serde_json](serde_json) Error
+    #[error("Could not parse input recording: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl InputRecording {
+    /// Writes this recording to the file at `path`, replacing it if it already exists.
+    pub(crate) fn save(&self, path: &Path) -> Result<(), InputRecordingError> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        let json = serde_json::to_string(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Loads the recording stored in the file at `path`.
+    pub(crate) fn load(path: &Path) -> Result<Self, InputRecordingError> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Collects the player's inputs into an [`InputRecording`].
+#[derive(Resource, Debug, Default)]
+pub(crate) struct InputRecorder {
+    /// The inputs recorded so far.
+    recording: InputRecording,
+    /// The file that the recording is written to when the app exits, if any.
+    path: Option<PathBuf>,
+}
+
+impl InputRecorder {
+    /// The inputs recorded so far.
+    pub(crate) fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+}
+
+/// Feeds the frames of an [`InputRecording`] back into the app, in place of the player's inputs.
+#[derive(Resource, Debug)]
+struct InputReplayer {
+    /// The recording being replayed.
+    recording: InputRecording,
+    /// The index of the next frame to replay.
+    next_frame: usize,
+    /// The player's keybindings, which are removed while replaying so that they can't interfere.
+    input_map: Option<InputMap<PlayerAction>>,
+}
+
+impl InputReplayer {
+    /// Prepares to replay the `recording` from its first frame.
+    fn new(recording: InputRecording) -> Self {
+        InputReplayer {
+            recording,
+            next_frame: 0,
+            input_map: None,
+        }
+    }
+}
+
+/// Stops the player's own inputs from changing the [`ActionState`] while a recording is replayed.
+fn disable_player_input(
+    input_map: Option<Res<InputMap<PlayerAction>>>,
+    mut input_replayer: ResMut<InputReplayer>,
+    mut commands: Commands,
+) {
+    input_replayer.input_map = input_map.map(|input_map| input_map.clone());
+    commands.remove_resource::<InputMap<PlayerAction>>();
+}
+
+/// Applies the next recorded frame, restoring the player's control once the recording has ended.
+fn replay_input(
+    mut input_replayer: ResMut<InputReplayer>,
+    mut actions: ResMut<ActionState<PlayerAction>>,
+    mut cursor_pos: ResMut<CursorPos>,
+    // Replays can start before the world has been generated
+    maybe_map_geometry: Option<Res<MapGeometry>>,
+    structure_query: Query<(), (With<Id<Structure>>, Without<Ghost>, Without<Preview>)>,
+    unit_query: Query<(Entity, &VoxelPos), With<Id<Unit>>>,
+    mut commands: Commands,
+) {
+    let Some(frame) = input_replayer
+        .recording
+        .frames
+        .get(input_replayer.next_frame)
+    else {
+        info!("Finished replaying input");
+        if let Some(input_map) = input_replayer.input_map.take() {
+            commands.insert_resource(input_map);
+        }
+        commands.remove_resource::<InputReplayer>();
+        return;
+    };
+
+    frame.apply(
+        &mut actions,
+        &mut cursor_pos,
+        |voxel_pos| {
+            maybe_map_geometry
+                .as_ref()?
+                .get_structure(voxel_pos)
+                .filter(|&entity| structure_query.contains(entity))
+        },
+        |voxel_pos| {
+            unit_query
+                .iter()
+                .find(|(_, &unit_pos)| unit_pos == voxel_pos)
+                .map(|(entity, _)| entity)
+        },
+    );
+    input_replayer.next_frame += 1;
+}
+
+/// Captures the inputs of this frame.
+fn record_input(
+    actions: Res<ActionState<PlayerAction>>,
+    cursor_pos: Res<CursorPos>,
+    unit_query: Query<&VoxelPos, With<Id<Unit>>>,
+    mut input_recorder: ResMut<InputRecorder>,
+) {
+    let hovered_unit = cursor_pos
+        .maybe_unit()
+        .and_then(|unit_entity| unit_query.get(unit_entity).ok().copied());
+
+    input_recorder.recording.frames.push(RecordedFrame::capture(
+        &actions,
+        &cursor_pos,
+        hovered_unit,
+    ));
+}
+
+/// Writes the recording to disk when the app exits.
+fn save_recording_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    input_recorder: Res<InputRecorder>,
+) {
+    if exit_events.read().next().is_none() {
+        return;
+    }
+
+    let Some(path) = &input_recorder.path else {
+        return;
+    };
+
+    match input_recorder.recording.save(path) {
+        Ok(()) => info!(
+            "Saved {} frames of input to {}",
+            input_recorder.recording.frames.len(),
+            path.display()
+        ),
+        Err(error) => error!("Could not save input recording: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applying_a_frame_only_changes_what_differs() {
+        let mut actions = ActionState::<PlayerAction>::default();
+        let mut cursor_pos = CursorPos::default();
+        actions.press(PlayerAction::Area);
+        actions.press(PlayerAction::Multiple);
+
+        let frame = RecordedFrame {
+            // Listed in the same order as the variants, as they are when captured
+            pressed: vec![PlayerAction::UseTool, PlayerAction::Area],
+            cursor: Some(VoxelPos::from_xy(3, -1)),
+            ..Default::default()
+        };
+        frame.apply(&mut actions, &mut cursor_pos, |_| None, |_| None);
+
+        assert!(actions.pressed(PlayerAction::Area));
+        assert!(actions.just_pressed(PlayerAction::UseTool));
+        assert!(actions.just_released(PlayerAction::Multiple));
+        assert_eq!(cursor_pos.maybe_voxel_pos(), frame.cursor);
+        assert_eq!(RecordedFrame::capture(&actions, &cursor_pos, None), frame);
+    }
+
+    #[test]
+    fn hovered_entities_are_found_again_by_position() {
+        let mut world = World::new();
+        let structure_entity = world.spawn_empty().id();
+        let unit_entity = world.spawn_empty().id();
+        let structure_pos = VoxelPos::from_xy(3, -1);
+        let unit_pos = VoxelPos::from_xy(2, 0);
+
+        let mut actions = ActionState::<PlayerAction>::default();
+        let mut cursor_pos = CursorPos::default();
+        let frame = RecordedFrame {
+            pressed: Vec::new(),
+            cursor: Some(structure_pos),
+            hovering_structure: true,
+            hovered_unit: Some(unit_pos),
+        };
+
+        frame.apply(
+            &mut actions,
+            &mut cursor_pos,
+            |voxel_pos| (voxel_pos == structure_pos).then_some(structure_entity),
+            |voxel_pos| (voxel_pos == unit_pos).then_some(unit_entity),
+        );
+
+        assert_eq!(cursor_pos.maybe_structure(), Some(structure_entity));
+        assert_eq!(cursor_pos.maybe_unit(), Some(unit_entity));
+        assert_eq!(
+            RecordedFrame::capture(&actions, &cursor_pos, Some(unit_pos)),
+            frame
+        );
+    }
+
+    #[test]
+    fn recordings_survive_a_round_trip_to_disk() {
+        let recording = InputRecording {
+            frames: vec![
+                RecordedFrame {
+                    pressed: vec![PlayerAction::Area],
+                    cursor: Some(VoxelPos::from_xy(1, 2)),
+                    hovering_structure: true,
+                    hovered_unit: Some(VoxelPos::from_xy(1, 3)),
+                },
+                RecordedFrame::default(),
+            ],
+        };
+
+        let path = crate::testing::unique_temp_dir("emergence_input_recording_test")
+            .join("recording.json");
+        recording.save(&path).unwrap();
+        assert_eq!(InputRecording::load(&path).unwrap(), recording);
+    }
+}
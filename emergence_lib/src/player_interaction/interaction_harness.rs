@@ -0,0 +1,498 @@
+//! A headless test harness for player interaction flows.
+//!
+//! The full [`InteractionPlugin`](super::InteractionPlugin) needs a window and raycasting,
+//! so this harness instead drives the selection logic directly.
+//! Synthetic [`PlayerAction`]s and cursor positions are injected frame by frame,
+//! and the resulting selection can then be inspected.
+
+use bevy::prelude::*;
+use bevy::utils::Instant;
+use hexx::Hex;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{Facing, MapGeometry, VoxelPos},
+    structures::{structure_manifest::Structure, Footprint},
+    testing::minimal_app,
+    units::unit_manifest::Unit,
+};
+
+use super::{
+    clipboard::Tool,
+    input_recording::{InputRecorder, InputRecording, InputRecordingPlugin},
+    measure::{MeasurePlugin, Measurement, MeasurementReport},
+    picking::CursorPos,
+    selection::{CurrentSelection, HoveredTiles, ObjectInteraction, SelectionPlugin},
+    PlayerAction,
+};
+
+/// A minimal [`App`] containing just enough to test how player inputs change the selection.
+pub(crate) struct InteractionHarness {
+    /// The app being driven.
+    app: App,
+}
+
+impl InteractionHarness {
+    /// Creates a new harness, with a flat map of the given `radius`.
+    pub(crate) fn new(map_radius: u32) -> Self {
+        let mut app = minimal_app();
+
+        let map_geometry = MapGeometry::new(&mut app.world, map_radius);
+        app.insert_resource(map_geometry)
+            .init_resource::<ActionState<PlayerAction>>()
+            .init_resource::<CursorPos>()
+            .init_resource::<Tool>()
+            .add_plugins(SelectionPlugin)
+            .add_plugins(MeasurePlugin)
+            .add_plugins(InputRecordingPlugin::default());
+
+        // Terrain is only given the components that selection cares about
+        let terrain_entities: Vec<Entity> = app
+            .world
+            .query_filtered::<Entity, With<VoxelPos>>()
+            .iter(&app.world)
+            .collect();
+        for entity in terrain_entities {
+            app.world.entity_mut(entity).insert(ObjectInteraction::None);
+        }
+
+        InteractionHarness { app }
+    }
+
+    /// Moves the cursor over the tile at `hex`.
+    pub(crate) fn hover(&mut self, hex: Hex) -> &mut Self {
+        let height = self
+            .app
+            .world
+            .resource::<MapGeometry>()
+            .get_height(hex)
+            .unwrap();
+        *self.app.world.resource_mut::<CursorPos>() = CursorPos::new(VoxelPos { hex, height });
+        self
+    }
+
+    /// Moves the cursor over the provided `structure_entity`, which must have been spawned by [`InteractionHarness::spawn_structure`].
+    pub(crate) fn hover_structure(&mut self, structure_entity: Entity) -> &mut Self {
+        let voxel_pos = *self.app.world.get::<VoxelPos>(structure_entity).unwrap();
+        *self.app.world.resource_mut::<CursorPos>() =
+            CursorPos::new(voxel_pos).hovering_structure(structure_entity);
+        self
+    }
+
+    /// Moves the cursor over the provided `unit_entity`, which must have been spawned by [`InteractionHarness::spawn_unit`].
+    pub(crate) fn hover_unit(&mut self, unit_entity: Entity) -> &mut Self {
+        let hex = self.app.world.get::<VoxelPos>(unit_entity).unwrap().hex;
+        self.hover(hex);
+        let mut cursor_pos = self.app.world.resource_mut::<CursorPos>();
+        *cursor_pos = cursor_pos.hovering_unit(unit_entity);
+        self
+    }
+
+    /// Spawns a single-tile structure on top of the terrain at `hex`.
+    pub(crate) fn spawn_structure(&mut self, hex: Hex) -> Entity {
+        let voxel_pos = self.surface(hex);
+        let structure_entity = self
+            .app
+            .world
+            .spawn((
+                Id::<Structure>::from_name("test_structure".to_string()),
+                voxel_pos,
+                ObjectInteraction::None,
+            ))
+            .id();
+
+        self.app
+            .world
+            .resource_mut::<MapGeometry>()
+            .add_structure(
+                voxel_pos,
+                Facing::default(),
+                &Footprint::single(),
+                false,
+                false,
+                structure_entity,
+            )
+            .unwrap();
+        structure_entity
+    }
+
+    /// Spawns a unit standing on the terrain at `hex`.
+    pub(crate) fn spawn_unit(&mut self, hex: Hex) -> Entity {
+        let voxel_pos = self.surface(hex);
+        self.app
+            .world
+            .spawn((
+                Id::<Unit>::from_name("test_unit".to_string()),
+                voxel_pos,
+                ObjectInteraction::None,
+            ))
+            .id()
+    }
+
+    /// The voxel directly above the terrain at `hex`.
+    fn surface(&self, hex: Hex) -> VoxelPos {
+        let height = self
+            .app
+            .world
+            .resource::<MapGeometry>()
+            .get_height(hex)
+            .unwrap();
+        VoxelPos { hex, height }.above()
+    }
+
+    /// Starts holding down `action`.
+    pub(crate) fn press(&mut self, action: PlayerAction) -> &mut Self {
+        self.app
+            .world
+            .resource_mut::<ActionState<PlayerAction>>()
+            .press(action);
+        self
+    }
+
+    /// Lets go of `action`.
+    pub(crate) fn release(&mut self, action: PlayerAction) -> &mut Self {
+        self.app
+            .world
+            .resource_mut::<ActionState<PlayerAction>>()
+            .release(action);
+        self
+    }
+
+    /// Advances the app by a single frame.
+    ///
+    /// Actions that were just pressed or released on this frame are held or idle on the next one,
+    /// just like real inputs.
+    pub(crate) fn step(&mut self) -> &mut Self {
+        self.app.update();
+
+        let now = Instant::now();
+        self.app
+            .world
+            .resource_mut::<ActionState<PlayerAction>>()
+            .tick(now, now);
+        self
+    }
+
+    /// Presses and then releases `action` on the hovered tile, taking two frames.
+    pub(crate) fn click(&mut self, action: PlayerAction) -> &mut Self {
+        self.press(action.clone()).step().release(action).step()
+    }
+
+    /// Starts recording the inputs of every following frame.
+    pub(crate) fn record(&mut self) -> &mut Self {
+        self.app.init_resource::<InputRecorder>();
+        self
+    }
+
+    /// The inputs recorded since [`InteractionHarness::record`] was called.
+    pub(crate) fn recording(&self) -> InputRecording {
+        self.app
+            .world
+            .resource::<InputRecorder>()
+            .recording()
+            .clone()
+    }
+
+    /// Plays back each frame of the `recording`, advancing the app by one frame for each.
+    ///
+    /// Hovered structures and units are found again by position, just like when replaying from a file.
+    pub(crate) fn replay(&mut self, recording: &InputRecording) -> &mut Self {
+        let mut unit_query = self
+            .app
+            .world
+            .query_filtered::<(Entity, &VoxelPos), With<Id<Unit>>>();
+
+        for frame in recording.frames.iter() {
+            let world = &mut self.app.world;
+            let mut actions = world
+                .remove_resource::<ActionState<PlayerAction>>()
+                .unwrap();
+            let mut cursor_pos = world.remove_resource::<CursorPos>().unwrap();
+            let map_geometry = world.resource::<MapGeometry>();
+
+            frame.apply(
+                &mut actions,
+                &mut cursor_pos,
+                |voxel_pos| map_geometry.get_structure(voxel_pos),
+                |voxel_pos| {
+                    unit_query
+                        .iter(world)
+                        .find(|(_, &unit_pos)| unit_pos == voxel_pos)
+                        .map(|(entity, _)| entity)
+                },
+            );
+
+            world.insert_resource(actions);
+            world.insert_resource(cursor_pos);
+            self.step();
+        }
+        self
+    }
+
+    /// The currently selected game object(s).
+    pub(crate) fn current_selection(&self) -> CurrentSelection {
+        self.app.world.resource::<CurrentSelection>().clone()
+    }
+
+    /// The hexes of all selected tiles, sorted so they can be compared to an expected snapshot.
+    pub(crate) fn selected_hexes(&self) -> Vec<Hex> {
+        let mut hexes: Vec<Hex> = match self.app.world.resource::<CurrentSelection>() {
+            CurrentSelection::Voxels(selected_voxels) => selected_voxels
+                .iter()
+                .map(|voxel_pos| voxel_pos.hex)
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        hexes.sort_by_key(|hex| (hex.x, hex.y));
+        hexes
+    }
+
+    /// The statistics of the region currently being measured, if any.
+    pub(crate) fn measurement_report(&self) -> Option<MeasurementReport> {
+        self.app.world.resource::<Measurement>().report().cloned()
+    }
+
+    /// The number of tiles currently hovered over.
+    pub(crate) fn n_hovered(&self) -> usize {
+        self.app.world.resource::<HoveredTiles>().len()
+    }
+
+    /// The [`ObjectInteraction`] of the terrain at `hex`.
+    pub(crate) fn terrain_interaction(&self, hex: Hex) -> ObjectInteraction {
+        let terrain_entity = self
+            .app
+            .world
+            .resource::<MapGeometry>()
+            .get_terrain(hex)
+            .unwrap();
+
+        self.app
+            .world
+            .get::<ObjectInteraction>(terrain_entity)
+            .unwrap()
+            .clone()
+    }
+}
+
+/// The hexes within `radius` of `center`, sorted in the same way as [`InteractionHarness::selected_hexes`].
+fn sorted_hexagon(center: Hex, radius: u32) -> Vec<Hex> {
+    let mut hexes: Vec<Hex> = hexx::shapes::hexagon(center, radius).collect();
+    hexes.sort_by_key(|hex| (hex.x, hex.y));
+    hexes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicking_selects_a_single_tile() {
+        let mut harness = InteractionHarness::new(5);
+        let target = Hex::new(1, 2);
+
+        harness.hover(target).step();
+        assert!(harness.selected_hexes().is_empty());
+        assert_eq!(
+            harness.terrain_interaction(target),
+            ObjectInteraction::Hovered
+        );
+
+        harness.click(PlayerAction::UseTool);
+        assert_eq!(harness.selected_hexes(), vec![target]);
+        assert_eq!(
+            harness.terrain_interaction(target),
+            ObjectInteraction::HoveredAndSelected
+        );
+
+        // Moving away leaves the tile selected, but no longer hovered
+        harness.hover(Hex::ZERO).step();
+        assert_eq!(
+            harness.terrain_interaction(target),
+            ObjectInteraction::Selected
+        );
+
+        harness.click(PlayerAction::Deselect);
+        assert!(harness.selected_hexes().is_empty());
+    }
+
+    #[test]
+    fn multiple_selection_accumulates() {
+        let mut harness = InteractionHarness::new(5);
+
+        harness.press(PlayerAction::Multiple);
+        for hex in [Hex::ZERO, Hex::new(1, 0), Hex::new(2, 0)] {
+            harness.hover(hex).click(PlayerAction::UseTool);
+        }
+        harness.release(PlayerAction::Multiple).step();
+
+        assert_eq!(
+            harness.selected_hexes(),
+            vec![Hex::ZERO, Hex::new(1, 0), Hex::new(2, 0)]
+        );
+    }
+
+    #[test]
+    fn dragging_an_area_selects_a_hexagon() {
+        let mut harness = InteractionHarness::new(5);
+        let center = Hex::new(-1, 1);
+
+        harness
+            .hover(center)
+            .press(PlayerAction::Area)
+            .step()
+            .press(PlayerAction::UseTool)
+            .step()
+            .hover(center + Hex::new(2, 0))
+            .step();
+
+        // Nothing is selected until the drag is released, but the outline of the area is previewed
+        assert!(harness.selected_hexes().is_empty());
+        assert_eq!(harness.n_hovered(), 12);
+
+        harness.release(PlayerAction::UseTool).step();
+        assert_eq!(harness.selected_hexes(), sorted_hexagon(center, 2));
+    }
+
+    #[test]
+    fn brush_size_sets_the_minimum_area() {
+        let mut harness = InteractionHarness::new(5);
+
+        harness
+            .click(PlayerAction::IncreaseSelectionRadius)
+            .hover(Hex::ZERO)
+            .press(PlayerAction::Area)
+            .step()
+            .click(PlayerAction::UseTool);
+
+        assert_eq!(harness.selected_hexes(), sorted_hexagon(Hex::ZERO, 1));
+    }
+
+    #[test]
+    fn dragging_a_line_selects_a_contiguous_path() {
+        let mut harness = InteractionHarness::new(5);
+        let start = Hex::new(-3, 0);
+        let end = Hex::new(3, 0);
+
+        harness
+            .hover(start)
+            .press(PlayerAction::Line)
+            .step()
+            .press(PlayerAction::UseTool)
+            .step()
+            .hover(end)
+            .step()
+            .release(PlayerAction::UseTool)
+            .step();
+
+        let selected = harness.selected_hexes();
+        let expected: Vec<Hex> = (-3..=3).map(|x| Hex::new(x, 0)).collect();
+        assert_eq!(selected, expected);
+    }
+
+    #[test]
+    fn measuring_leaves_the_selection_alone() {
+        let mut harness = InteractionHarness::new(5);
+        let center = Hex::new(1, -1);
+
+        harness
+            .hover(center)
+            .press(PlayerAction::Measure)
+            .step()
+            .press(PlayerAction::UseTool)
+            .step()
+            .hover(center + Hex::new(0, 2))
+            .step()
+            .release(PlayerAction::UseTool)
+            .step();
+
+        let report = harness.measurement_report().unwrap();
+        assert_eq!(report.n_tiles, 19);
+        assert_eq!(report.hex_distance, 2);
+        assert!(report.resources.is_empty());
+        assert!(harness.selected_hexes().is_empty());
+
+        // The measurement is discarded once the player stops measuring
+        harness.release(PlayerAction::Measure).step();
+        assert_eq!(harness.measurement_report(), None);
+    }
+
+    #[test]
+    fn replaying_a_recording_reproduces_the_interaction() {
+        let mut harness = InteractionHarness::new(5);
+        let center = Hex::new(-1, 1);
+
+        harness
+            .record()
+            .hover(center)
+            .press(PlayerAction::Area)
+            .step()
+            .press(PlayerAction::UseTool)
+            .step()
+            .hover(center + Hex::new(2, 0))
+            .step()
+            .release(PlayerAction::UseTool)
+            .step();
+        assert_eq!(harness.selected_hexes(), sorted_hexagon(center, 2));
+
+        // Recordings are replayed from disk, so make sure that nothing is lost in serialization
+        let json = serde_json::to_string(&harness.recording()).unwrap();
+        let recording: InputRecording = serde_json::from_str(&json).unwrap();
+        assert_eq!(recording.frames.len(), 4);
+
+        let mut replayed = InteractionHarness::new(5);
+        replayed.replay(&recording);
+        assert_eq!(replayed.selected_hexes(), sorted_hexagon(center, 2));
+    }
+
+    #[test]
+    fn replayed_structures_and_units_are_found_by_position() {
+        let structure_hex = Hex::new(1, 0);
+        let unit_hex = Hex::new(-2, 1);
+
+        let mut harness = InteractionHarness::new(5);
+        let structure_entity = harness.spawn_structure(structure_hex);
+        let unit_entity = harness.spawn_unit(unit_hex);
+
+        harness
+            .record()
+            .hover_structure(structure_entity)
+            .click(PlayerAction::UseTool);
+        assert_eq!(
+            harness.current_selection(),
+            CurrentSelection::Structure(structure_entity)
+        );
+        let structure_frames = harness.recording().frames.len();
+
+        harness.hover_unit(unit_entity).click(PlayerAction::UseTool);
+        assert_eq!(
+            harness.current_selection(),
+            CurrentSelection::Unit(unit_entity)
+        );
+        let recording = harness.recording();
+
+        // Entities are spawned in a different order, so they can only be found again by position
+        let mut replayed = InteractionHarness::new(5);
+        let replayed_unit = replayed.spawn_unit(unit_hex);
+        let replayed_structure = replayed.spawn_structure(structure_hex);
+        assert_ne!(replayed_structure, structure_entity);
+        assert_ne!(replayed_unit, unit_entity);
+
+        replayed.replay(&InputRecording {
+            frames: recording.frames[..structure_frames].to_vec(),
+        });
+        assert_eq!(
+            replayed.current_selection(),
+            CurrentSelection::Structure(replayed_structure)
+        );
+
+        replayed.replay(&InputRecording {
+            frames: recording.frames[structure_frames..].to_vec(),
+        });
+        assert_eq!(
+            replayed.current_selection(),
+            CurrentSelection::Unit(replayed_unit)
+        );
+    }
+}
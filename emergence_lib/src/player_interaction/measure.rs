@@ -0,0 +1,165 @@
+//! Measures regions of the map without changing the selection, to help plan before building.
+//!
+//! While [`PlayerAction::Measure`] is held, dragging with [`PlayerAction::UseTool`] measures a hexagon,
+//! centered where the drag started and reaching the cursor.
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::shapes::hexagon;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    items::item_manifest::{Item, ItemManifest},
+    litter::Litter,
+    structures::resource_nodes::ResourceNode,
+};
+
+use super::{picking::CursorPos, InteractionSystem, PlayerAction};
+
+/// Measures the region dragged out by the player.
+pub(super) struct MeasurePlugin;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Measurement>().add_systems(
+            Update,
+            measure_region
+                .in_set(InteractionSystem::SelectTiles)
+                .after(InteractionSystem::ComputeCursorPos),
+        );
+    }
+}
+
+/// The measurement that the player is currently taking, if any.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub(crate) struct Measurement {
+    /// The tile where the current drag started.
+    anchor: Option<VoxelPos>,
+    /// The statistics of the measured region.
+    report: Option<MeasurementReport>,
+}
+
+impl Measurement {
+    /// The statistics of the measured region, if the player is measuring anything.
+    pub(crate) fn report(&self) -> Option<&MeasurementReport> {
+        self.report.as_ref()
+    }
+}
+
+/// Statistics about a measured region of the map.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MeasurementReport {
+    /// The number of tiles in the region.
+    pub(crate) n_tiles: usize,
+    /// The distance from the start of the drag to its end, in tiles.
+    pub(crate) hex_distance: u32,
+    /// The straight-line distance from the start of the drag to its end, in world units.
+    pub(crate) world_distance: f32,
+    /// The items available in the region, both as litter and in wild resources.
+    ///
+    /// Sorted by item, so the order is stable from frame to frame.
+    pub(crate) resources: Vec<(Id<Item>, u32)>,
+}
+
+impl MeasurementReport {
+    /// Measures the hexagon centered on `anchor` that reaches `end`.
+    ///
+    /// `resources` should contain the position and quantity of every available item on the map.
+    fn new(
+        anchor: VoxelPos,
+        end: VoxelPos,
+        map_geometry: &MapGeometry,
+        resources: impl IntoIterator<Item = (VoxelPos, Id<Item>, u32)>,
+    ) -> Self {
+        let hex_distance = anchor.hex.unsigned_distance_to(end.hex);
+        let n_tiles = hexagon(anchor.hex, hex_distance)
+            .filter(|&hex| map_geometry.is_valid(hex))
+            .count();
+
+        let mut totals: HashMap<Id<Item>, u32> = HashMap::default();
+        for (voxel_pos, item_id, count) in resources {
+            if voxel_pos.hex.unsigned_distance_to(anchor.hex) <= hex_distance {
+                *totals.entry(item_id).or_default() += count;
+            }
+        }
+
+        let mut resources: Vec<(Id<Item>, u32)> = totals.into_iter().collect();
+        resources.sort();
+
+        MeasurementReport {
+            n_tiles,
+            hex_distance,
+            world_distance: anchor.into_world_pos().distance(end.into_world_pos()),
+            resources,
+        }
+    }
+
+    /// Pretty formatting for this type.
+    pub(crate) fn display(&self, item_manifest: &ItemManifest) -> String {
+        let mut string = format!(
+            "Tiles: {}\nDistance: {} tiles ({:.1} m)",
+            self.n_tiles, self.hex_distance, self.world_distance
+        );
+
+        for (item_id, count) in &self.resources {
+            string += &format!("\n{}: {count}", item_manifest.name(*item_id));
+        }
+
+        string
+    }
+}
+
+/// Updates the [`Measurement`] as the player drags out a region to measure.
+fn measure_region(
+    actions: Res<ActionState<PlayerAction>>,
+    cursor_pos: Res<CursorPos>,
+    litter_query: Query<(&VoxelPos, &Litter)>,
+    resource_node_query: Query<(&VoxelPos, &ResourceNode)>,
+    map_geometry: Res<MapGeometry>,
+    mut measurement: ResMut<Measurement>,
+) {
+    if !actions.pressed(PlayerAction::Measure) {
+        if measurement.anchor.is_some() || measurement.report.is_some() {
+            *measurement = Measurement::default();
+        }
+        return;
+    }
+
+    let Some(hovered_tile) = cursor_pos.maybe_voxel_pos() else {
+        return;
+    };
+
+    if actions.just_pressed(PlayerAction::UseTool) {
+        measurement.anchor = Some(hovered_tile);
+    }
+
+    // The last report stays visible after the drag ends, until the player stops measuring
+    if !actions.pressed(PlayerAction::UseTool) {
+        return;
+    }
+
+    let Some(anchor) = measurement.anchor else {
+        return;
+    };
+
+    let litter = litter_query.iter().flat_map(|(&voxel_pos, litter)| {
+        litter
+            .contents
+            .iter()
+            .map(move |item_slot| (voxel_pos, item_slot.item_id(), item_slot.count()))
+    });
+    let wild_resources = resource_node_query
+        .iter()
+        .map(|(&voxel_pos, resource_node)| {
+            (voxel_pos, resource_node.item_id(), resource_node.stock())
+        });
+
+    let report = MeasurementReport::new(
+        anchor,
+        hovered_tile,
+        &map_geometry,
+        litter.chain(wild_resources),
+    );
+    measurement.report = Some(report);
+}
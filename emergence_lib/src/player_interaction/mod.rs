@@ -4,6 +4,7 @@ use crate::enum_iter::IterableEnum;
 use crate::{self as emergence_lib};
 use bevy::prelude::*;
 use emergence_macros::IterableEnum;
+use serde::{Deserialize, Serialize};
 
 use leafwing_input_manager::{
     prelude::{ActionState, DualAxis, InputManagerPlugin, InputMap, VirtualDPad},
@@ -13,10 +14,21 @@ use leafwing_input_manager::{
 
 use crate::world_gen::WorldGenState;
 
+pub(crate) mod bulk_commands;
 pub(crate) mod camera;
 pub(crate) mod clipboard;
+pub(crate) mod cursor_mode;
+pub(crate) mod feedback;
+pub mod input_recording;
+#[cfg(test)]
+pub(crate) mod interaction_harness;
+pub(crate) mod measure;
+pub(crate) mod nicknames;
+pub(crate) mod path_preview;
+pub(crate) mod photo_mode;
 pub(crate) mod picking;
 pub(crate) mod selection;
+pub(crate) mod touch;
 
 /// All of the code needed for users to interact with the simulation.
 pub struct InteractionPlugin;
@@ -30,6 +42,14 @@ impl Plugin for InteractionPlugin {
             .add_plugins(picking::PickingPlugin)
             .add_plugins(selection::SelectionPlugin)
             .add_plugins(clipboard::ClipboardPlugin)
+            .add_plugins(cursor_mode::CursorModePlugin)
+            .add_plugins(feedback::InteractionFeedbackPlugin)
+            .add_plugins(measure::MeasurePlugin)
+            .add_plugins(path_preview::PathPreviewPlugin)
+            .add_plugins(bulk_commands::BulkCommandsPlugin)
+            .add_plugins(nicknames::NicknamesPlugin)
+            .add_plugins(photo_mode::PhotoModePlugin)
+            .add_plugins(touch::TouchPlugin)
             .configure_sets(
                 Update,
                 PlayerModifiesWorld.run_if(in_state(WorldGenState::Complete)),
@@ -65,7 +85,7 @@ pub struct PlayerModifiesWorld;
 /// Actions that the player can take to modify the game world or their view of it.
 ///
 /// This should only store actions that need a dedicated keybinding.
-#[derive(Actionlike, Reflect, Clone, Debug)]
+#[derive(Actionlike, Reflect, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) enum PlayerAction {
     /// Pause or unpause the game.
     TogglePause,
@@ -83,10 +103,16 @@ pub(crate) enum PlayerAction {
     DecreaseSelectionRadius,
     /// Modifies the selection / deselection to be sequential.
     Multiple,
+    /// While held, clicking selects the tile under the cursor, rather than the unit or structure on it.
+    SelectTiles,
+    /// While held, clicking a structure selects every visible structure of the same type nearby.
+    SelectSameType,
     /// Modifies the selection to cover a hexagonal area.
     Area,
     /// Modifies the selection to cover a line between the start and end of the selection.
     Line,
+    /// While held, inverts whether line selections snap to the nearest hex axis or diagonal.
+    ToggleLineSnapping,
     /// Selects a structure from a wheel menu.
     SelectStructure,
     /// Select a terraforming tool from a wheel menu.
@@ -97,10 +123,34 @@ pub(crate) enum PlayerAction {
     ///
     /// If there is no structure there, the player's selection is cleared.
     Copy,
+    /// Arms the placement tool with the structure or terrain under the player's cursor.
+    ///
+    /// Structures keep their facing and recipe.
+    Pipette,
+    /// While held, dragging measures a region of the map instead of selecting it.
+    Measure,
+    /// While held, previews the path that the selected unit would take to reach the cursor.
+    PreviewPath,
     /// Sets the zoning of all currently selected tiles to the currently selected structure.
     Paste,
     /// Cancels any planned actions (ghosts) selected.
     ClearZoning,
+    /// Carries out a large destructive action that is waiting to be confirmed.
+    ConfirmAction,
+    /// Cancels any work orders on the selected tiles, including demolition.
+    CancelWorkOrders,
+    /// Marks any wild resources on the selected tiles for harvest.
+    Harvest,
+    /// Forbids hauling to or from the selected tiles, or allows it again.
+    ToggleForbidden,
+    /// Prioritizes the structures on the selected tiles, or returns them to normal priority.
+    TogglePriority,
+    /// Switches the structures on the selected tiles off, or back on again.
+    ToggleEnabled,
+    /// Marks the selected unit or structures as favorites, or unmarks them.
+    ToggleFavorite,
+    /// Opens a text box to name the selected unit or structure.
+    Rename,
     /// Rotates the contents of the clipboard counterclockwise.
     RotateClipboardLeft,
     /// Rotates the contents of the clipboard clockwise.
@@ -135,6 +185,30 @@ pub(crate) enum PlayerAction {
     ToggleWaterTableOverlay,
     /// Show / hide the light overlay
     ToggleLightOverlay,
+    /// Show / hide the temperature overlay
+    ToggleTemperatureOverlay,
+    /// Show / hide the territory overlay
+    ToggleTerritoryOverlay,
+    /// Show / hide the overlay of where units have been walking
+    ToggleTrafficOverlay,
+    /// Show / hide the overview of where all of the colony's items are
+    ToggleResourcesOverview,
+    /// Steps through the categories of debug gizmos drawn over the map.
+    CycleDebugGizmos,
+    /// Runs the simulation at a tenth of its normal speed, or returns it to normal.
+    ToggleSlowMotion,
+    /// Freezes the simulation so it can be stepped one tick at a time, or lets it run freely again.
+    ToggleFrameStep,
+    /// Advances the simulation by a single tick, freezing it afterwards.
+    StepSimulation,
+    /// Switches the view between the surface and the underground layer.
+    ToggleUndergroundView,
+    /// Opens the search box, to find things by name.
+    Search,
+    /// Enters photo mode, which has a free camera and hides the UI.
+    TogglePhotoMode,
+    /// Opens the pause menu, or goes back a screen when in a menu.
+    OpenMenu,
 }
 
 impl PlayerAction {
@@ -149,14 +223,29 @@ impl PlayerAction {
             IncreaseSelectionRadius => UserInput::modified(Modifier::Control, KeyCode::Equals),
             DecreaseSelectionRadius => UserInput::modified(Modifier::Control, KeyCode::Minus),
             Multiple => Modifier::Shift.into(),
+            SelectTiles => KeyCode::T.into(),
+            SelectSameType => KeyCode::G.into(),
             Area => Modifier::Control.into(),
             Line => Modifier::Alt.into(),
+            ToggleLineSnapping => KeyCode::Tab.into(),
             SelectStructure => KeyCode::Key1.into(),
             SelectTerraform => KeyCode::Key2.into(),
             SelectAbility => KeyCode::Key3.into(),
             Copy => UserInput::modified(Modifier::Control, KeyCode::C),
+            Pipette => KeyCode::I.into(),
+            Measure => KeyCode::M.into(),
+            // K adds a keyframe in photo mode
+            PreviewPath => KeyCode::Y.into(),
             Paste => UserInput::modified(Modifier::Control, KeyCode::V),
             ClearZoning => KeyCode::Back.into(),
+            ConfirmAction => KeyCode::Return.into(),
+            CancelWorkOrders => KeyCode::Delete.into(),
+            Harvest => KeyCode::H.into(),
+            ToggleForbidden => KeyCode::F.into(),
+            TogglePriority => KeyCode::P.into(),
+            ToggleEnabled => KeyCode::O.into(),
+            ToggleFavorite => KeyCode::B.into(),
+            Rename => KeyCode::N.into(),
             RotateClipboardLeft => UserInput::modified(Modifier::Shift, KeyCode::R),
             RotateClipboardRight => KeyCode::R.into(),
             CenterCameraOnSelection => KeyCode::L.into(),
@@ -176,6 +265,18 @@ impl PlayerAction {
             ToggleStrongestSignalOverlay => KeyCode::F3.into(),
             ToggleWaterTableOverlay => KeyCode::F4.into(),
             ToggleLightOverlay => KeyCode::F5.into(),
+            ToggleTemperatureOverlay => KeyCode::F6.into(),
+            ToggleTerritoryOverlay => KeyCode::F7.into(),
+            ToggleTrafficOverlay => KeyCode::J.into(),
+            ToggleResourcesOverview => KeyCode::F8.into(),
+            CycleDebugGizmos => KeyCode::F9.into(),
+            ToggleSlowMotion => KeyCode::F10.into(),
+            ToggleFrameStep => KeyCode::F11.into(),
+            StepSimulation => KeyCode::Period.into(),
+            ToggleUndergroundView => KeyCode::U.into(),
+            Search => UserInput::modified(Modifier::Control, KeyCode::F),
+            TogglePhotoMode => KeyCode::F12.into(),
+            OpenMenu => KeyCode::Escape.into(),
         }
     }
 
@@ -194,19 +295,33 @@ impl PlayerAction {
             PlayerAction::UseTool => South.into(),
             Deselect => East.into(),
             Multiple => RightTrigger.into(),
+            SelectTiles => UserInput::chord([camera_modifier, LeftThumb]),
+            SelectSameType => UserInput::chord([radius_modifier, DPadRight]),
             IncreaseSelectionRadius => UserInput::chord([radius_modifier, DPadUp]),
             DecreaseSelectionRadius => UserInput::chord([radius_modifier, DPadDown]),
             Area => LeftTrigger.into(),
             Line => LeftTrigger2.into(),
+            ToggleLineSnapping => UserInput::chord([camera_modifier, East]),
             Copy => West.into(),
+            Pipette => UserInput::chord([camera_modifier, West]),
+            Measure => UserInput::chord([camera_modifier, North]),
+            PreviewPath => UserInput::chord([selection_modifier, GamepadButtonType::Select]),
             Paste => North.into(),
             ClearZoning => DPadUp.into(),
+            ConfirmAction => UserInput::chord([radius_modifier, RightThumb]),
+            CancelWorkOrders => UserInput::chord([selection_modifier, DPadUp]),
+            Harvest => UserInput::chord([radius_modifier, LeftThumb]),
+            ToggleForbidden => UserInput::chord([radius_modifier, GamepadButtonType::Select]),
+            TogglePriority => UserInput::chord([radius_modifier, Start]),
+            ToggleEnabled => UserInput::chord([selection_modifier, Start]),
+            ToggleFavorite => UserInput::chord([radius_modifier, DPadLeft]),
+            Rename => UserInput::chord([selection_modifier, DPadRight]),
             SelectStructure => UserInput::chord([selection_modifier, West]),
             SelectTerraform => UserInput::chord([selection_modifier, North]),
             SelectAbility => UserInput::chord([selection_modifier, East]),
             RotateClipboardLeft => DPadLeft.into(),
             RotateClipboardRight => DPadRight.into(),
-            CenterCameraOnSelection => GamepadButtonType::LeftThumb.into(),
+            CenterCameraOnSelection => LeftThumb.into(),
             DragCamera => GamepadButtonType::RightThumb.into(),
             Pan => DualAxis::left_stick().into(),
             MoveCursor => DualAxis::right_stick().into(),
@@ -222,6 +337,20 @@ impl PlayerAction {
             ToggleStrongestSignalOverlay => UserInput::chord([infovis_modifier, DPadRight]),
             ToggleWaterTableOverlay => UserInput::chord([infovis_modifier, DPadDown]),
             ToggleLightOverlay => UserInput::chord([infovis_modifier, DPadUp]),
+            ToggleTemperatureOverlay => UserInput::chord([infovis_modifier, North]),
+            ToggleTerritoryOverlay => {
+                UserInput::chord([infovis_modifier, GamepadButtonType::Select])
+            }
+            ToggleTrafficOverlay => UserInput::chord([camera_modifier, RightThumb]),
+            ToggleResourcesOverview => UserInput::chord([selection_modifier, DPadLeft]),
+            CycleDebugGizmos => UserInput::chord([infovis_modifier, RightThumb]),
+            ToggleSlowMotion => UserInput::chord([infovis_modifier, LeftThumb]),
+            ToggleFrameStep => UserInput::chord([selection_modifier, LeftThumb]),
+            StepSimulation => UserInput::chord([selection_modifier, RightThumb]),
+            ToggleUndergroundView => UserInput::chord([infovis_modifier, West]),
+            Search => UserInput::chord([selection_modifier, DPadDown]),
+            TogglePhotoMode => UserInput::chord([camera_modifier, GamepadButtonType::Select]),
+            OpenMenu => Start.into(),
         }
     }
 
@@ -236,3 +365,80 @@ impl PlayerAction {
         input_map
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leafwing_input_manager::user_input::InputKind;
+
+    /// The gamepad buttons that make up the default binding of `action`.
+    fn gamepad_buttons(action: &PlayerAction) -> Vec<GamepadButtonType> {
+        let input_kinds = match action.gamepad_binding() {
+            UserInput::Single(input_kind) => vec![input_kind],
+            UserInput::Chord(input_kinds) => input_kinds.into_iter().collect(),
+            _ => Vec::new(),
+        };
+
+        input_kinds
+            .into_iter()
+            .filter_map(|input_kind| match input_kind {
+                InputKind::GamepadButton(button) => Some(button),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The actions that fire when `pressed` is held, resolved like the default `ClashStrategy::PrioritizeLongest`.
+    fn triggered_actions(pressed: &[GamepadButtonType]) -> Vec<PlayerAction> {
+        let bound: Vec<(PlayerAction, Vec<GamepadButtonType>)> = PlayerAction::variants()
+            .map(|action| {
+                let buttons = gamepad_buttons(&action);
+                (action, buttons)
+            })
+            .filter(|(_, buttons)| {
+                !buttons.is_empty() && buttons.iter().all(|button| pressed.contains(button))
+            })
+            .collect();
+
+        bound
+            .iter()
+            .filter(|(_, buttons)| {
+                !bound.iter().any(|(_, longer)| {
+                    longer.len() > buttons.len() && buttons.iter().all(|b| longer.contains(b))
+                })
+            })
+            .map(|(action, _)| action.clone())
+            .collect()
+    }
+
+    #[test]
+    fn held_modifiers_do_not_clash_with_gamepad_clicks() {
+        use PlayerAction::*;
+
+        let combinations = [
+            (Area, vec![UseTool, Deselect, Copy, Paste]),
+            (Line, vec![UseTool, Deselect]),
+            (Multiple, vec![UseTool]),
+            (SelectTiles, vec![UseTool]),
+            (SelectSameType, vec![UseTool]),
+            (Measure, vec![UseTool]),
+        ];
+
+        for (modifier, tools) in combinations {
+            for tool in tools {
+                let mut pressed = gamepad_buttons(&modifier);
+                pressed.extend(gamepad_buttons(&tool));
+
+                let mut triggered = triggered_actions(&pressed);
+                triggered.sort_by_key(|action| format!("{action:?}"));
+                let mut expected = vec![modifier.clone(), tool.clone()];
+                expected.sort_by_key(|action| format!("{action:?}"));
+
+                assert_eq!(
+                    triggered, expected,
+                    "holding {modifier:?} while pressing {tool:?} on a gamepad"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Names that the player gives to individual organisms and structures.
+//!
+//! Nicknames are purely cosmetic, but make it easy to find a particular unit or structure again with the search box.
+
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id, structures::structure_manifest::Structure,
+    units::unit_manifest::Unit,
+};
+
+use super::PlayerModifiesWorld;
+
+/// Applies the nicknames chosen by the player.
+pub(super) struct NicknamesPlugin;
+
+impl Plugin for NicknamesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SetNickname>()
+            .add_systems(Update, set_nicknames.in_set(PlayerModifiesWorld));
+    }
+}
+
+/// A name given to a single organism or structure by the player.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Nickname(String);
+
+impl Nickname {
+    /// The maximum number of characters in a nickname.
+    pub const MAX_LENGTH: usize = 32;
+
+    /// Creates a new nickname, trimming whitespace and truncating it to [`Nickname::MAX_LENGTH`] characters.
+    ///
+    /// Returns [`None`] if nothing is left.
+    pub fn new(name: &str) -> Option<Self> {
+        let name: String = name.trim().chars().take(Self::MAX_LENGTH).collect();
+        let name = name.trim_end();
+
+        match name.is_empty() {
+            true => None,
+            false => Some(Nickname(name.to_string())),
+        }
+    }
+
+    /// The name itself.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Does this nickname contain `query`, ignoring case?
+    pub fn matches(&self, query: &str) -> bool {
+        !query.is_empty() && self.0.to_lowercase().contains(&query.to_lowercase())
+    }
+}
+
+impl Display for Nickname {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\"", self.0)
+    }
+}
+
+/// An event that names an organism or structure, or clears its nickname.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct SetNickname {
+    /// The entity to name.
+    pub entity: Entity,
+    /// The new nickname, or [`None`] to remove the existing one.
+    pub nickname: Option<Nickname>,
+}
+
+/// Applies each [`SetNickname`] event to its unit or structure.
+fn set_nicknames(
+    mut events: EventReader<SetNickname>,
+    nameable_query: Query<(), Or<(With<Id<Unit>>, With<Id<Structure>>)>>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        // The entity may have died while the player was typing
+        if !nameable_query.contains(event.entity) {
+            continue;
+        }
+
+        match &event.nickname {
+            Some(nickname) => commands.entity(event.entity).insert(nickname.clone()),
+            None => commands.entity(event.entity).remove::<Nickname>(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nicknames_are_trimmed() {
+        assert_eq!(Nickname::new("  Bob "), Some(Nickname("Bob".to_string())));
+        assert_eq!(Nickname::new("   "), None);
+        assert_eq!(Nickname::new(""), None);
+    }
+
+    #[test]
+    fn long_nicknames_are_truncated() {
+        let nickname = Nickname::new(&"a".repeat(100)).unwrap();
+        assert_eq!(nickname.as_str().chars().count(), Nickname::MAX_LENGTH);
+    }
+
+    #[test]
+    fn nicknames_match_case_insensitively() {
+        let nickname = Nickname::new("Old Gnarly").unwrap();
+
+        assert!(nickname.matches("gnar"));
+        assert!(nickname.matches("OLD"));
+        assert!(!nickname.matches("young"));
+        assert!(!nickname.matches(""));
+    }
+}
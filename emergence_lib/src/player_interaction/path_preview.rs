@@ -0,0 +1,156 @@
+//! Previews the path that the selected unit would take to reach the cursor.
+//!
+//! Units can't yet be given direct orders, so for now this is a debugging aid:
+//! while [`PlayerAction::PreviewPath`] is held, the path is highlighted using the [`TileOverlay`],
+//! and an explanation of its cost is logged to the console.
+
+use bevy::{prelude::*, utils::HashSet};
+use hexx::Hex;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    geometry::{MapGeometry, VoxelPos},
+    graphics::overlay::{OverlayType, TileOverlay},
+    units::{
+        movement::MovementMode,
+        pathfinding::{find_path, update_walking_speeds, WalkingSpeeds},
+    },
+};
+
+use super::{picking::CursorPos, selection::CurrentSelection, InteractionSystem, PlayerAction};
+
+/// Computes and displays path previews.
+pub(super) struct PathPreviewPlugin;
+
+impl Plugin for PathPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PathPreview>().add_systems(
+            Update,
+            preview_path
+                .after(InteractionSystem::ComputeCursorPos)
+                .after(InteractionSystem::SelectTiles)
+                .after(update_walking_speeds),
+        );
+    }
+}
+
+/// The path currently being previewed, if any.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PathPreview {
+    /// The unit, its starting position and the goal that the current path was computed for.
+    ///
+    /// Used to avoid recomputing the path every frame.
+    target: Option<(Entity, VoxelPos, Hex)>,
+    /// The tiles covered by the path, including the tile that the unit starts on.
+    hexes: HashSet<Hex>,
+    /// The voxels along the path, in order, including the voxel that the unit starts on.
+    voxels: Vec<VoxelPos>,
+    /// The voxels considered while searching for the path, in the order they were explored.
+    explored: Vec<VoxelPos>,
+    /// The overlay that was displayed before the preview began, restored once it ends.
+    previous_overlay: Option<OverlayType>,
+}
+
+impl PathPreview {
+    /// Is `hex` part of the previewed path?
+    pub(crate) fn contains(&self, hex: Hex) -> bool {
+        self.hexes.contains(&hex)
+    }
+
+    /// The voxels along the path, in order, including the voxel that the unit starts on.
+    pub(crate) fn voxels(&self) -> &[VoxelPos] {
+        &self.voxels
+    }
+
+    /// The voxels considered while searching for the path, in the order they were explored.
+    pub(crate) fn explored(&self) -> &[VoxelPos] {
+        &self.explored
+    }
+
+    /// Forgets the current path.
+    fn clear(&mut self) {
+        self.target = None;
+        self.hexes.clear();
+        self.voxels.clear();
+        self.explored.clear();
+    }
+}
+
+/// Finds the path from the selected unit to the cursor while [`PlayerAction::PreviewPath`] is held.
+fn preview_path(
+    actions: Res<ActionState<PlayerAction>>,
+    cursor_pos: Res<CursorPos>,
+    current_selection: Res<CurrentSelection>,
+    unit_query: Query<(&VoxelPos, &MovementMode)>,
+    map_geometry: Res<MapGeometry>,
+    walking_speeds: Res<WalkingSpeeds>,
+    mut maybe_tile_overlay: Option<ResMut<TileOverlay>>,
+    mut path_preview: ResMut<PathPreview>,
+) {
+    if !actions.pressed(PlayerAction::PreviewPath) {
+        if let Some(previous_overlay) = path_preview.previous_overlay.take() {
+            if let Some(tile_overlay) = maybe_tile_overlay.as_mut() {
+                // Don't clobber an overlay that the player picked while previewing
+                if tile_overlay.overlay_type == OverlayType::PathPreview {
+                    tile_overlay.overlay_type = previous_overlay;
+                }
+            }
+            path_preview.clear();
+        }
+        return;
+    }
+
+    if path_preview.previous_overlay.is_none() {
+        if let Some(tile_overlay) = maybe_tile_overlay.as_mut() {
+            path_preview.previous_overlay = Some(tile_overlay.overlay_type);
+            tile_overlay.overlay_type = OverlayType::PathPreview;
+        }
+    }
+
+    let CurrentSelection::Unit(unit_entity) = *current_selection else {
+        path_preview.clear();
+        return;
+    };
+
+    let (Ok((&start, &movement_mode)), Some(goal)) = (
+        unit_query.get(unit_entity),
+        cursor_pos.maybe_voxel_pos().map(|voxel_pos| voxel_pos.hex),
+    ) else {
+        path_preview.clear();
+        return;
+    };
+
+    // Changes to the map can block the path or make a different one faster
+    let target = Some((unit_entity, start, goal));
+    if path_preview.target == target && !walking_speeds.is_changed() && !map_geometry.is_changed() {
+        return;
+    }
+
+    path_preview.clear();
+    path_preview.target = target;
+
+    match find_path(start, goal, movement_mode, &map_geometry, &walking_speeds) {
+        Some(path) => {
+            info!(
+                "Path from ({}, {}) to ({}, {}): {}",
+                start.hex.x,
+                start.hex.y,
+                goal.x,
+                goal.y,
+                path.explain()
+            );
+
+            path_preview.voxels.push(start);
+            path_preview
+                .voxels
+                .extend(path.steps.iter().map(|step| step.voxel_pos));
+            let hexes: Vec<Hex> = path_preview.voxels.iter().map(|voxel| voxel.hex).collect();
+            path_preview.hexes.extend(hexes);
+            path_preview.explored = path.explored;
+        }
+        None => info!(
+            "No path from ({}, {}) to ({}, {})",
+            start.hex.x, start.hex.y, goal.x, goal.y
+        ),
+    }
+}
@@ -0,0 +1,501 @@
+//! A free-flying camera for taking screenshots and recording flythroughs.
+//!
+//! Photo mode has its own camera rig, which overrides the gameplay camera while it is active.
+//! It ignores the usual zoom and tilt limits, hides the UI and can slow or freeze time.
+//! The gameplay camera is left untouched, and resumes where it left off when photo mode is closed.
+//!
+//! While photo mode is active, keybindings are suspended and the following keys are read directly:
+//! - WASD: fly horizontally
+//! - Space / Left Shift: fly up / down
+//! - arrow keys or middle-mouse drag: look around
+//! - `[` / `]`: narrow / widen the field of view
+//! - T: cycle between normal, slowed and frozen time
+//! - K: add a keyframe at the current camera position
+//! - Enter: play or stop the flythrough between keyframes
+//! - Backspace: clear all keyframes
+//! - Escape or F12: leave photo mode
+
+use std::f32::consts::PI;
+
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use leafwing_input_manager::prelude::{ActionState, ToggleActions};
+
+use crate::{simulation::time::GameSpeed, world_gen::WorldGenState};
+
+use super::{InteractionSystem, PlayerAction};
+
+/// Controls photo mode.
+pub(super) struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhotoMode>().add_systems(
+            Update,
+            (
+                toggle_photo_mode,
+                adjust_photo_settings,
+                fly_photo_camera,
+                play_camera_path,
+                apply_photo_camera,
+            )
+                .chain()
+                // Override the gameplay camera rig
+                .after(InteractionSystem::MoveCamera)
+                .run_if(in_state(WorldGenState::Complete)),
+        );
+    }
+}
+
+/// The position and orientation of the photo mode camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PhotoCamera {
+    /// The position of the camera.
+    translation: Vec3,
+    /// The angle in radians that the camera is turned around the vertical axis.
+    yaw: f32,
+    /// The angle in radians that the camera is tilted up from the horizontal.
+    pitch: f32,
+}
+
+impl PhotoCamera {
+    /// The speed at which the camera flies, in world units per second.
+    const FLY_SPEED: f32 = 15.;
+
+    /// The speed at which the camera turns with the arrow keys, in radians per second.
+    const LOOK_SPEED: f32 = 1.;
+
+    /// How far the camera turns for each pixel that the mouse is dragged, in radians.
+    const DRAG_RATIO: f32 = 0.003;
+
+    /// Starts the camera at the given `transform`.
+    fn from_transform(transform: &Transform) -> Self {
+        let (yaw, pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
+
+        PhotoCamera {
+            translation: transform.translation,
+            yaw,
+            pitch,
+        }
+    }
+
+    /// The [`Transform`] of a camera with this position and orientation.
+    fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation).with_rotation(Quat::from_euler(
+            EulerRot::YXZ,
+            self.yaw,
+            self.pitch,
+            0.,
+        ))
+    }
+
+    /// Turns the camera, without allowing it to flip upside down.
+    fn look(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        // Looking straight up or down causes the camera to flip
+        let max_pitch = PI / 2. - 1e-3;
+
+        self.yaw += delta_yaw;
+        self.pitch = (self.pitch + delta_pitch).clamp(-max_pitch, max_pitch);
+    }
+}
+
+/// How quickly time passes while in photo mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum PhotoTimeScale {
+    /// Time passes as normal.
+    #[default]
+    Normal,
+    /// Time passes at a fraction of the normal rate.
+    Slowed,
+    /// Time does not pass at all.
+    Frozen,
+}
+
+impl PhotoTimeScale {
+    /// The rate at which time passes in [`PhotoTimeScale::Slowed`], relative to normal.
+    const SLOWED_SPEED: f32 = 0.25;
+
+    /// The next option, in the order that they are cycled through.
+    fn next(self) -> Self {
+        match self {
+            PhotoTimeScale::Normal => PhotoTimeScale::Slowed,
+            PhotoTimeScale::Slowed => PhotoTimeScale::Frozen,
+            PhotoTimeScale::Frozen => PhotoTimeScale::Normal,
+        }
+    }
+
+    /// Applies this time scale to the photo mode factor of the [`GameSpeed`].
+    ///
+    /// Other factors, such as slow motion, are left as they were.
+    fn apply(self, game_speed: &mut GameSpeed) {
+        game_speed.photo_mode = match self {
+            PhotoTimeScale::Normal => 1.,
+            PhotoTimeScale::Slowed => Self::SLOWED_SPEED,
+            PhotoTimeScale::Frozen => 0.,
+        };
+    }
+}
+
+/// A sequence of camera keyframes that the camera can smoothly fly through.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct CameraPath {
+    /// The keyframes, in the order that they are visited.
+    keyframes: Vec<PhotoCamera>,
+}
+
+impl CameraPath {
+    /// The time taken to fly from one keyframe to the next, in seconds.
+    const SECONDS_PER_KEYFRAME: f32 = 3.;
+
+    /// The time taken to fly through the whole path, in seconds.
+    fn duration(&self) -> f32 {
+        self.keyframes.len().saturating_sub(1) as f32 * Self::SECONDS_PER_KEYFRAME
+    }
+
+    /// The camera position and orientation `seconds` after the start of the flythrough.
+    ///
+    /// The camera follows a Catmull-Rom spline, which passes through every keyframe without sudden changes in direction.
+    /// Returns `None` if there are no keyframes.
+    fn sample(&self, seconds: f32) -> Option<PhotoCamera> {
+        let n = self.keyframes.len();
+        match n {
+            0 => return None,
+            1 => return Some(self.keyframes[0]),
+            _ => (),
+        }
+
+        let progress = (seconds / Self::SECONDS_PER_KEYFRAME).clamp(0., (n - 1) as f32);
+        let segment = (progress.floor() as usize).min(n - 2);
+        let t = progress - segment as f32;
+
+        let p0 = self.keyframes[segment.saturating_sub(1)];
+        let p1 = self.keyframes[segment];
+        let p2 = self.keyframes[segment + 1];
+        let p3 = self.keyframes[(segment + 2).min(n - 1)];
+
+        let catmull_rom = |a: Vec3, b: Vec3, c: Vec3, d: Vec3| {
+            0.5 * (2. * b
+                + (c - a) * t
+                + (2. * a - 5. * b + 4. * c - d) * t * t
+                + (3. * b - a - 3. * c + d) * t * t * t)
+        };
+
+        Some(PhotoCamera {
+            translation: catmull_rom(
+                p0.translation,
+                p1.translation,
+                p2.translation,
+                p3.translation,
+            ),
+            yaw: p1.yaw + (p2.yaw - p1.yaw) * t,
+            pitch: p1.pitch + (p2.pitch - p1.pitch) * t,
+        })
+    }
+}
+
+/// The state of photo mode.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PhotoMode {
+    /// The photo mode camera, if photo mode is active.
+    camera: Option<PhotoCamera>,
+    /// The vertical field of view of the camera in photo mode, in radians.
+    fov: f32,
+    /// The field of view of the gameplay camera, restored when photo mode is closed.
+    gameplay_fov: f32,
+    /// How quickly time passes.
+    time_scale: PhotoTimeScale,
+    /// The keyframes for the flythrough.
+    path: CameraPath,
+    /// The number of seconds since the flythrough started, if it is playing.
+    playback: Option<f32>,
+    /// The UI nodes that were hidden when photo mode was opened.
+    hidden_ui: Vec<Entity>,
+}
+
+impl PhotoMode {
+    /// The narrowest allowed field of view, in radians.
+    const MIN_FOV: f32 = 0.05;
+
+    /// The widest allowed field of view, in radians.
+    const MAX_FOV: f32 = 1.5;
+
+    /// The rate at which the field of view changes, in radians per second.
+    const FOV_SPEED: f32 = 0.3;
+
+    /// Is photo mode currently active?
+    pub(crate) fn is_active(&self) -> bool {
+        self.camera.is_some()
+    }
+}
+
+/// Opens and closes photo mode.
+///
+/// Opening photo mode hides the UI and suspends keybindings; closing it restores them, along with the flow of time.
+fn toggle_photo_mode(
+    actions: Res<ActionState<PlayerAction>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut photo_mode: ResMut<PhotoMode>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut camera_query: Query<(&Transform, &mut Projection), With<Camera3d>>,
+    mut ui_query: Query<(Entity, &mut Visibility), (With<Node>, Without<Parent>)>,
+) {
+    let Ok((transform, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    if !photo_mode.is_active() {
+        if !actions.just_pressed(PlayerAction::TogglePhotoMode) {
+            return;
+        }
+
+        let fov = match &*projection {
+            Projection::Perspective(perspective) => perspective.fov,
+            Projection::Orthographic(_) => return,
+        };
+
+        photo_mode.camera = Some(PhotoCamera::from_transform(transform));
+        photo_mode.fov = fov;
+        photo_mode.gameplay_fov = fov;
+        photo_mode.time_scale = PhotoTimeScale::Normal;
+        photo_mode.playback = None;
+
+        photo_mode.hidden_ui.clear();
+        for (entity, mut visibility) in ui_query.iter_mut() {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+                photo_mode.hidden_ui.push(entity);
+            }
+        }
+
+        toggle_actions.enabled = false;
+        return;
+    }
+
+    let gamepad_exit = gamepad_input
+        .get_just_pressed()
+        .any(|button| button.button_type == GamepadButtonType::East);
+
+    if keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::F12]) || gamepad_exit {
+        photo_mode.camera = None;
+        photo_mode.playback = None;
+
+        if let Projection::Perspective(perspective) = &mut *projection {
+            perspective.fov = photo_mode.gameplay_fov;
+        }
+
+        for entity in std::mem::take(&mut photo_mode.hidden_ui) {
+            if let Ok((_, mut visibility)) = ui_query.get_mut(entity) {
+                *visibility = Visibility::Inherited;
+            }
+        }
+
+        photo_mode.time_scale = PhotoTimeScale::Normal;
+        photo_mode.time_scale.apply(&mut game_speed);
+        toggle_actions.enabled = true;
+    }
+}
+
+/// Changes the field of view, the flow of time and the flythrough keyframes.
+fn adjust_photo_settings(
+    keyboard_input: Res<Input<KeyCode>>,
+    real_time: Res<Time<Real>>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut photo_mode: ResMut<PhotoMode>,
+) {
+    let Some(photo_camera) = photo_mode.camera else {
+        return;
+    };
+
+    let delta_fov = PhotoMode::FOV_SPEED * real_time.delta_seconds();
+    if keyboard_input.pressed(KeyCode::BracketLeft) {
+        photo_mode.fov = (photo_mode.fov - delta_fov).max(PhotoMode::MIN_FOV);
+    }
+    if keyboard_input.pressed(KeyCode::BracketRight) {
+        photo_mode.fov = (photo_mode.fov + delta_fov).min(PhotoMode::MAX_FOV);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::T) {
+        photo_mode.time_scale = photo_mode.time_scale.next();
+        photo_mode.time_scale.apply(&mut game_speed);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::K) {
+        photo_mode.path.keyframes.push(photo_camera);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        photo_mode.path.keyframes.clear();
+        photo_mode.playback = None;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        photo_mode.playback = match photo_mode.playback {
+            Some(_) => None,
+            None if photo_mode.path.keyframes.len() >= 2 => Some(0.),
+            None => None,
+        };
+    }
+}
+
+/// Flies the photo mode camera around in response to player input.
+///
+/// This uses real time, so that the camera can still move while time is frozen.
+fn fly_photo_camera(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    real_time: Res<Time<Real>>,
+    mut photo_mode: ResMut<PhotoMode>,
+) {
+    // Player input is ignored during flythroughs
+    if photo_mode.playback.is_some() {
+        mouse_motion_events.clear();
+        return;
+    }
+
+    let Some(photo_camera) = photo_mode.camera.as_mut() else {
+        mouse_motion_events.clear();
+        return;
+    };
+
+    let delta_seconds = real_time.delta_seconds();
+
+    let mut direction = Vec3::ZERO;
+    for (key_code, offset) in [
+        (KeyCode::W, Vec3::NEG_Z),
+        (KeyCode::S, Vec3::Z),
+        (KeyCode::A, Vec3::NEG_X),
+        (KeyCode::D, Vec3::X),
+    ] {
+        if keyboard_input.pressed(key_code) {
+            direction += offset;
+        }
+    }
+
+    // Fly horizontally in the direction the camera is facing, regardless of its pitch
+    direction = Quat::from_rotation_y(photo_camera.yaw) * direction;
+
+    if keyboard_input.pressed(KeyCode::Space) {
+        direction += Vec3::Y;
+    }
+    if keyboard_input.pressed(KeyCode::ShiftLeft) {
+        direction -= Vec3::Y;
+    }
+
+    photo_camera.translation +=
+        direction.normalize_or_zero() * PhotoCamera::FLY_SPEED * delta_seconds;
+
+    let look_delta = PhotoCamera::LOOK_SPEED * delta_seconds;
+    let mut delta_yaw = 0.;
+    let mut delta_pitch = 0.;
+
+    if keyboard_input.pressed(KeyCode::Left) {
+        delta_yaw += look_delta;
+    }
+    if keyboard_input.pressed(KeyCode::Right) {
+        delta_yaw -= look_delta;
+    }
+    if keyboard_input.pressed(KeyCode::Up) {
+        delta_pitch += look_delta;
+    }
+    if keyboard_input.pressed(KeyCode::Down) {
+        delta_pitch -= look_delta;
+    }
+
+    if mouse_input.pressed(MouseButton::Middle) {
+        for mouse_motion in mouse_motion_events.read() {
+            delta_yaw -= mouse_motion.delta.x * PhotoCamera::DRAG_RATIO;
+            delta_pitch -= mouse_motion.delta.y * PhotoCamera::DRAG_RATIO;
+        }
+    } else {
+        mouse_motion_events.clear();
+    }
+
+    photo_camera.look(delta_yaw, delta_pitch);
+}
+
+/// Moves the camera along the flythrough path, stopping at the final keyframe.
+fn play_camera_path(real_time: Res<Time<Real>>, mut photo_mode: ResMut<PhotoMode>) {
+    let Some(elapsed) = photo_mode.playback else {
+        return;
+    };
+
+    let elapsed = elapsed + real_time.delta_seconds();
+    photo_mode.camera = photo_mode.path.sample(elapsed).or(photo_mode.camera);
+    photo_mode.playback = match elapsed < photo_mode.path.duration() {
+        true => Some(elapsed),
+        false => None,
+    };
+}
+
+/// Overwrites the camera's transform and field of view with those of the photo mode camera.
+fn apply_photo_camera(
+    photo_mode: Res<PhotoMode>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<Camera3d>>,
+) {
+    let Some(photo_camera) = photo_mode.camera else {
+        return;
+    };
+
+    let Ok((mut transform, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    *transform = photo_camera.transform();
+    if let Projection::Perspective(perspective) = &mut *projection {
+        perspective.fov = photo_mode.fov;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(x: f32) -> PhotoCamera {
+        PhotoCamera {
+            translation: Vec3::new(x, 10., 0.),
+            yaw: x,
+            pitch: 0.,
+        }
+    }
+
+    #[test]
+    fn camera_path_passes_through_keyframes() {
+        let path = CameraPath {
+            keyframes: vec![keyframe(0.), keyframe(3.), keyframe(-2.), keyframe(5.)],
+        };
+
+        for (i, expected) in path.keyframes.iter().enumerate() {
+            let sampled = path
+                .sample(i as f32 * CameraPath::SECONDS_PER_KEYFRAME)
+                .unwrap();
+            assert!(sampled.translation.distance(expected.translation) < 1e-4);
+            assert!((sampled.yaw - expected.yaw).abs() < 1e-4);
+        }
+
+        // Sampling past the end stays on the final keyframe
+        let sampled = path.sample(path.duration() + 10.).unwrap();
+        assert!(sampled.translation.distance(keyframe(5.).translation) < 1e-4);
+    }
+
+    #[test]
+    fn empty_camera_path_cannot_be_sampled() {
+        assert_eq!(CameraPath::default().sample(0.), None);
+        assert_eq!(CameraPath::default().duration(), 0.);
+    }
+
+    #[test]
+    fn photo_camera_round_trips_through_transform() {
+        let photo_camera = PhotoCamera {
+            translation: Vec3::new(1., 2., 3.),
+            yaw: 0.5,
+            pitch: -0.3,
+        };
+
+        let round_tripped = PhotoCamera::from_transform(&photo_camera.transform());
+        assert!(round_tripped.translation.distance(photo_camera.translation) < 1e-4);
+        assert!((round_tripped.yaw - photo_camera.yaw).abs() < 1e-4);
+        assert!((round_tripped.pitch - photo_camera.pitch).abs() < 1e-4);
+    }
+}
@@ -8,7 +8,14 @@ use bevy_mod_raycast::{
 use leafwing_input_manager::prelude::ActionState;
 
 use super::{InteractionSystem, PlayerAction};
-use crate::{asset_management::manifest::Id, geometry::VoxelPos, units::unit_manifest::Unit};
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    fog_of_war::FogOfWar,
+    geometry::VoxelPos,
+    structures::structure_manifest::Structure,
+    units::unit_manifest::Unit,
+};
 
 /// Controls raycasting.
 pub(super) struct PickingPlugin;
@@ -39,11 +46,14 @@ pub(crate) struct CursorPos {
     screen_pos: Option<Vec2>,
     /// The first unit hit by a cursor raycast, if any.
     hovered_unit: Option<Entity>,
+    /// The structure hit by the cursor raycast, if it was the nearest voxel.
+    hovered_structure: Option<Entity>,
 }
 
 impl CursorPos {
     /// Creates a new [`CursorPos`] with the given tile position.
-    #[cfg(test)]
+    ///
+    /// This is used to place the cursor without a window, such as when replaying recorded input.
     pub(crate) fn new(voxel_pos: VoxelPos) -> Self {
         Self {
             voxel_pos: Some(voxel_pos),
@@ -51,6 +61,22 @@ impl CursorPos {
         }
     }
 
+    /// Makes this cursor hover over the provided structure.
+    pub(crate) fn hovering_structure(self, structure_entity: Entity) -> Self {
+        Self {
+            hovered_structure: Some(structure_entity),
+            ..self
+        }
+    }
+
+    /// Makes this cursor hover over the provided unit.
+    pub(crate) fn hovering_unit(self, unit_entity: Entity) -> Self {
+        Self {
+            hovered_unit: Some(unit_entity),
+            ..self
+        }
+    }
+
     /// The position of the cursor in hex coordinates, if it is on the hex map.
     ///
     /// If the cursor is outside the map, this will return `None`.
@@ -67,6 +93,13 @@ impl CursorPos {
     pub(crate) fn maybe_unit(&self) -> Option<Entity> {
         self.hovered_unit
     }
+
+    /// The hovered structure, if available.
+    ///
+    /// Ghosts and previews are never returned: they are selected by their tile.
+    pub(crate) fn maybe_structure(&self) -> Option<Entity> {
+        self.hovered_structure
+    }
 }
 
 /// Updates the raycast with the cursor position
@@ -94,30 +127,48 @@ fn update_raycast_with_cursor(
 pub(crate) struct PickableVoxel;
 
 /// Updates the location of the cursor and what it is hovering over
-fn update_cursor_pos(
+///
+/// Tiles and units hidden by the [`FogOfWar`] cannot be hovered, and so cannot be selected or inspected.
+pub(super) fn update_cursor_pos(
     mut cursor_pos: ResMut<CursorPos>,
     camera_query: Query<
         (&mut RaycastSource<PickableVoxel>, &mut RaycastSource<Unit>),
         With<Camera>,
     >,
     voxel_query: Query<&VoxelPos>,
-    unit_query: Query<Entity, With<Id<Unit>>>,
+    structure_query: Query<(), (With<Id<Structure>>, Without<Ghost>, Without<Preview>)>,
+    unit_query: Query<(Entity, &VoxelPos), With<Id<Unit>>>,
+    maybe_fog_of_war: Option<Res<FogOfWar>>,
     mut cursor_moved_events: EventReader<CursorMoved>,
 ) {
     let Ok((voxel_raycast, unit_raycast)) = camera_query.get_single() else {
         return;
     };
 
-    cursor_pos.voxel_pos =
-        if let Some((entity, _intersection_data)) = voxel_raycast.get_nearest_intersection() {
-            voxel_query.get(entity).ok().copied()
-        } else {
-            None
-        };
+    let nearest_voxel = voxel_raycast
+        .get_nearest_intersection()
+        .and_then(|(entity, _intersection_data)| {
+            voxel_query
+                .get(entity)
+                .ok()
+                .map(|&voxel_pos| (entity, voxel_pos))
+        })
+        .filter(|(_, voxel_pos)| FogOfWar::is_visible(maybe_fog_of_war.as_deref(), voxel_pos.hex));
+
+    cursor_pos.voxel_pos = nearest_voxel.map(|(_, voxel_pos)| voxel_pos);
+    cursor_pos.hovered_structure = nearest_voxel
+        .map(|(entity, _)| entity)
+        .filter(|&entity| structure_query.contains(entity));
 
     cursor_pos.hovered_unit =
         if let Some((unit_entity, _intersection_data)) = unit_raycast.get_nearest_intersection() {
-            unit_query.get(unit_entity).ok()
+            unit_query
+                .get(unit_entity)
+                .ok()
+                .filter(|(_, voxel_pos)| {
+                    FogOfWar::is_visible(maybe_fog_of_war.as_deref(), voxel_pos.hex)
+                })
+                .map(|(unit_entity, _)| unit_entity)
         } else {
             None
         };
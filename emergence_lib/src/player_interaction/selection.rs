@@ -1,18 +1,32 @@
-//! Tiles can be selected, serving as a building block for clipboard, inspection and zoning operations.
+//! Tiles, structures and units can be selected, serving as a building block for clipboard, inspection and zoning operations.
+//!
+//! Clicking a unit or structure selects that entity directly, unless [`PlayerAction::SelectTiles`] is held.
+//! Holding [`PlayerAction::SelectSameType`] while clicking a structure instead selects every visible structure of that type nearby.
 
-use bevy::{prelude::*, utils::HashSet};
+use std::mem::{discriminant, Discriminant};
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 use emergence_macros::IterableEnum;
 use hexx::shapes::hexagon;
 use hexx::Hex;
 use hexx::HexIterExt;
 use leafwing_input_manager::prelude::ActionState;
 
+use crate::asset_management::manifest::Id;
+use crate::construction::ghosts::{Ghost, Preview};
+use crate::fog_of_war::FogOfWar;
 use crate::geometry::MapGeometry;
 use crate::geometry::VoxelObject;
 use crate::geometry::VoxelPos;
+use crate::structures::structure_manifest::Structure;
+use crate::units::unit_manifest::Unit;
 
 use crate as emergence_lib;
 
+use super::camera::mousewheel_adjustments;
 use super::clipboard::Tool;
 use super::{picking::CursorPos, InteractionSystem, PlayerAction};
 
@@ -23,6 +37,8 @@ impl Plugin for SelectionPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CurrentSelection>()
             .init_resource::<SelectionState>()
+            .init_resource::<LineSelectionSettings>()
+            .init_resource::<SameTypeSelectionSettings>()
             .init_resource::<HoveredTiles>()
             .add_systems(
                 Update,
@@ -36,7 +52,13 @@ impl Plugin for SelectionPlugin {
                     .in_set(InteractionSystem::SelectTiles)
                     .after(set_selection),
             )
-            .add_systems(Update, update_selection_radius);
+            .add_systems(
+                Update,
+                (restore_brush_size_for_tool, update_selection_radius)
+                    .chain()
+                    .after(mousewheel_adjustments)
+                    .before(InteractionSystem::SelectTiles),
+            );
     }
 }
 
@@ -63,26 +85,24 @@ impl SelectedVoxels {
     }
 
     /// Draws a hollow hexagonal ring of tiles.
-    fn draw_ring(center: VoxelPos, radius: u32) -> Vec<Hex> {
-        center.hex.ring(radius).collect()
+    fn draw_ring(center: VoxelPos, radius: u32) -> impl Iterator<Item = Hex> {
+        center.hex.ring(radius)
     }
 
     /// Draws a hexagon of tiles.
-    fn draw_hexagon(center: VoxelPos, radius: u32) -> Vec<Hex> {
-        hexagon(center.hex, radius).collect()
+    fn draw_hexagon(center: VoxelPos, radius: u32) -> impl Iterator<Item = Hex> {
+        hexagon(center.hex, radius)
     }
 
     /// Computes the set of hexagons between `start` and `end`, with a thickness determnind by `radius`.
-    fn draw_line(start: VoxelPos, end: VoxelPos, radius: u32) -> Vec<Hex> {
-        if radius == 0 {
-            start.hex.line_to(end.hex).collect()
-        } else {
-            let mut hex_vec = Vec::new();
-            for central_hex in start.hex.line_to(end.hex) {
-                hex_vec.extend(hexagon(central_hex, radius));
-            }
-            hex_vec
-        }
+    ///
+    /// Hexes may be repeated where the thickened line overlaps itself.
+    fn draw_line(start: VoxelPos, end: VoxelPos, radius: u32) -> impl Iterator<Item = Hex> {
+        // A hexagon of radius 0 is just the central hex
+        start
+            .hex
+            .line_to(end.hex)
+            .flat_map(move |central_hex| hexagon(central_hex, radius))
     }
 
     /// Handles all of the logic needed to add tiles to the selection.
@@ -129,20 +149,19 @@ impl SelectedVoxels {
         match selection_state.shape {
             SelectionShape::Single => HashSet::from_iter([hovered_tile]),
             SelectionShape::Area { center, radius } => SelectedVoxels::draw_hexagon(center, radius)
-                .iter()
-                .filter(|hex| map_geometry.is_valid(**hex))
+                .filter(|hex| map_geometry.is_valid(*hex))
                 .map(|hex| VoxelPos {
-                    hex: *hex,
-                    height: map_geometry.get_height(*hex).unwrap(),
+                    hex,
+                    height: map_geometry.get_height(hex).unwrap(),
                 })
                 .collect(),
             SelectionShape::Line { start } => {
-                SelectedVoxels::draw_line(start, hovered_tile, selection_state.brush_size)
-                    .iter()
-                    .filter(|hex| map_geometry.is_valid(**hex))
+                let end = selection_state.line_end(start, hovered_tile);
+                SelectedVoxels::draw_line(start, end, selection_state.brush_size)
+                    .filter(|hex| map_geometry.is_valid(*hex))
                     .map(|hex| VoxelPos {
-                        hex: *hex,
-                        height: map_geometry.get_height(*hex).unwrap(),
+                        hex,
+                        height: map_geometry.get_height(hex).unwrap(),
                     })
                     .collect()
             }
@@ -160,23 +179,28 @@ pub(crate) struct HoveredTiles {
 
 impl HoveredTiles {
     /// Updates the set of hovered actions based on the current cursor position and player inputs.
+    ///
+    /// This runs every frame, so the existing set is reused rather than reallocated.
     fn update(&mut self, hovered_tile: VoxelPos, selection_state: &SelectionState) {
-        let hex_vec = match selection_state.shape {
-            SelectionShape::Single => {
-                SelectedVoxels::draw_hexagon(hovered_tile, selection_state.brush_size)
-            }
+        self.hovered.clear();
+
+        match selection_state.shape {
+            SelectionShape::Single => self.hovered.extend(SelectedVoxels::draw_hexagon(
+                hovered_tile,
+                selection_state.brush_size,
+            )),
             SelectionShape::Area { center, radius } => {
-                let mut vec = SelectedVoxels::draw_ring(center, radius);
+                self.hovered
+                    .extend(SelectedVoxels::draw_ring(center, radius));
                 // Also show center of ring for clarity.
-                vec.push(hovered_tile.hex);
-                vec
-            }
-            SelectionShape::Line { start } => {
-                SelectedVoxels::draw_line(start, hovered_tile, selection_state.brush_size)
+                self.hovered.insert(hovered_tile.hex);
             }
-        };
-
-        self.hovered = HashSet::from_iter(hex_vec);
+            SelectionShape::Line { start } => self.hovered.extend(SelectedVoxels::draw_line(
+                start,
+                selection_state.line_end(start, hovered_tile),
+                selection_state.brush_size,
+            )),
+        }
     }
 }
 
@@ -247,8 +271,8 @@ fn update_selection_radius(
     actions: Res<ActionState<PlayerAction>>,
 ) {
     if actions.just_pressed(PlayerAction::IncreaseSelectionRadius) {
-        // This max brush size is set
-        selection_state.brush_size = (selection_state.brush_size + 1).min(10);
+        selection_state.brush_size =
+            (selection_state.brush_size + 1).min(SelectionState::MAX_BRUSH_SIZE);
     }
 
     if actions.just_pressed(PlayerAction::DecreaseSelectionRadius) {
@@ -257,12 +281,14 @@ fn update_selection_radius(
 }
 
 /// The game object(s) currently selected for inspection.
-#[derive(Resource, Debug, Default)]
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
 pub(crate) enum CurrentSelection {
     /// One or more tile is selected.
     ///
     /// Note that terraforming details are also displayed on the basis of the selected terrain.
     Voxels(SelectedVoxels),
+    /// A structure is selected
+    Structure(Entity),
     /// A unit is selected
     Unit(Entity),
     /// Nothing is selected
@@ -316,7 +342,8 @@ impl CurrentSelection {
     /// Determines the selection based on the cursor information.
     ///
     /// This handles the simple case, when we're selecting a new tile.
-    /// Ordinarily, just prioritize units > structures > terrain
+    /// Ordinarily, just prioritize units > structures > terrain.
+    /// Multiple selections, and selections made while [`PlayerAction::SelectTiles`] is held, always select terrain.
     fn update_from_cursor_pos(
         &mut self,
         cursor_pos: &CursorPos,
@@ -324,18 +351,55 @@ impl CurrentSelection {
         selection_state: &SelectionState,
         map_geometry: &MapGeometry,
     ) {
-        *self = if selection_state.multiple {
+        *self = if selection_state.multiple || selection_state.tiles_only {
             self.select_terrain(hovered_tile, selection_state, map_geometry)
         } else if let Some(unit_entity) = cursor_pos.maybe_unit() {
             CurrentSelection::Unit(unit_entity)
+        } else if let Some(structure_entity) = cursor_pos.maybe_structure() {
+            CurrentSelection::Structure(structure_entity)
         } else {
             self.select_terrain(hovered_tile, selection_state, map_geometry)
         }
     }
 
+    /// The structures that are selected, either directly or by selecting the tiles they stand on.
+    pub(crate) fn structures(&self, map_geometry: &MapGeometry) -> Vec<Entity> {
+        match self {
+            CurrentSelection::Voxels(selected_voxels) => selected_voxels
+                .iter()
+                .filter_map(|&voxel_pos| map_geometry.get_structure(voxel_pos))
+                .collect(),
+            CurrentSelection::Structure(structure_entity) => vec![*structure_entity],
+            CurrentSelection::Unit(_) | CurrentSelection::None => Vec::new(),
+        }
+    }
+
+    /// Selects the tiles under every structure of type `structure_id` that is visible and within `radius` tiles of `center`.
+    ///
+    /// Tiles are selected rather than the structures themselves, so bulk commands apply to the whole group.
+    fn select_same_type<'a>(
+        structure_id: Id<Structure>,
+        center: Hex,
+        radius: u32,
+        structures: impl IntoIterator<Item = (&'a Id<Structure>, &'a VoxelPos)>,
+        maybe_fog_of_war: Option<&FogOfWar>,
+    ) -> Self {
+        let mut selected_voxels = SelectedVoxels::default();
+        for (&id, &voxel_pos) in structures {
+            if id == structure_id
+                && voxel_pos.hex.unsigned_distance_to(center) <= radius
+                && FogOfWar::is_visible(maybe_fog_of_war, voxel_pos.hex)
+            {
+                selected_voxels.insert(voxel_pos);
+            }
+        }
+
+        CurrentSelection::Voxels(selected_voxels)
+    }
+
     /// Cycles through game objects on the same tile.
     ///
-    /// The order is units -> ghost structure -> ghost terrain -> structures -> terrain -> units.
+    /// The order is units -> structures -> terrain -> units.
     /// If a higher priority option is missing, later options in the chain are searched.
     /// If none of the options can be found, the selection is cleared completely.
     fn cycle_selection(
@@ -369,6 +433,9 @@ impl CurrentSelection {
     ) -> Option<Self> {
         match selection_variant {
             SelectionVariant::Unit => cursor_pos.maybe_unit().map(CurrentSelection::Unit),
+            SelectionVariant::Structure => cursor_pos
+                .maybe_structure()
+                .map(CurrentSelection::Structure),
             SelectionVariant::Voxel => {
                 let hovered_tile = cursor_pos.maybe_voxel_pos()?;
                 let mut selected_voxels = SelectedVoxels::default();
@@ -385,6 +452,8 @@ impl CurrentSelection {
 enum SelectionVariant {
     /// A unit.
     Unit,
+    /// A structure.
+    Structure,
     /// A voxel
     Voxel,
     /// No selection.
@@ -394,12 +463,13 @@ enum SelectionVariant {
 impl SelectionVariant {
     /// Get the next selection mode in the chain.
     ///
-    /// The order is units -> ghost structure -> ghost terrain -> structures -> terrain -> units.
+    /// The order is units -> structures -> terrain -> units.
     /// No path leads to None: it is instead the fallback if nothing can be found.
     fn next(&self) -> Self {
         match self {
             Self::None => Self::Unit,
-            Self::Unit => Self::Voxel,
+            Self::Unit => Self::Structure,
+            Self::Structure => Self::Voxel,
             Self::Voxel => Self::Unit,
         }
     }
@@ -428,23 +498,132 @@ impl From<&CurrentSelection> for SelectionVariant {
     fn from(selection: &CurrentSelection) -> Self {
         match selection {
             CurrentSelection::Voxels(_) => Self::Voxel,
+            CurrentSelection::Structure(_) => Self::Structure,
             CurrentSelection::Unit(_) => Self::Unit,
             CurrentSelection::None => Self::None,
         }
     }
 }
 
+/// Remembers the brush size used with each kind of [`Tool`], restoring it when the player switches back.
+fn restore_brush_size_for_tool(
+    tool: Res<Tool>,
+    mut selection_state: ResMut<SelectionState>,
+    mut previous_tool: Local<Option<Discriminant<Tool>>>,
+) {
+    if !tool.is_changed() {
+        return;
+    }
+
+    let current_tool = discriminant(&*tool);
+    if let Some(previous_tool) = *previous_tool {
+        if previous_tool != current_tool {
+            let brush_size = selection_state.brush_size;
+            selection_state
+                .saved_brush_sizes
+                .insert(previous_tool, brush_size);
+            selection_state.brush_size = selection_state
+                .saved_brush_sizes
+                .get(&current_tool)
+                .copied()
+                .unwrap_or_default();
+        }
+    }
+
+    *previous_tool = Some(current_tool);
+}
+
 /// Tracks what should be done with the selection (and hovered tiles) this frame.
 #[derive(Resource, Default, Debug)]
-struct SelectionState {
+pub(crate) struct SelectionState {
     /// What is the shape of the selection?
     shape: SelectionShape,
     /// What should be done to the selection?
     action: SelectionAction,
     /// Should the selection be erased or modified?
     multiple: bool,
-    /// The selection size to use for non-Area selections
+    /// The selection size to use for non-Area selections, and the minimum size of Area selections
     brush_size: u32,
+    /// The brush size last used with each kind of [`Tool`].
+    saved_brush_sizes: HashMap<Discriminant<Tool>, u32>,
+    /// Should line selections be snapped to the nearest hex axis or diagonal this frame?
+    snap_lines: bool,
+    /// Should tiles be selected, even if there is a unit or structure under the cursor?
+    tiles_only: bool,
+    /// Should clicking a structure select every nearby structure of the same type?
+    same_type: bool,
+}
+
+/// Player preferences for selecting every structure of the same type.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SameTypeSelectionSettings {
+    /// How far from the clicked structure, in tiles, structures of the same type are selected.
+    pub(crate) radius: u32,
+}
+
+impl Default for SameTypeSelectionSettings {
+    fn default() -> Self {
+        SameTypeSelectionSettings { radius: 20 }
+    }
+}
+
+/// Player preferences for drawing line selections.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct LineSelectionSettings {
+    /// Should lines snap to the nearest of the six hex axes or six diagonals by default?
+    ///
+    /// Holding [`PlayerAction::ToggleLineSnapping`] temporarily inverts this.
+    pub(crate) snap_to_axes: bool,
+}
+
+/// The twelve directions that line selections can snap to: the six hex axes, followed by the six diagonals between them.
+const SNAPPING_DIRECTIONS: [Hex; 12] = [
+    Hex::new(1, 0),
+    Hex::new(1, -1),
+    Hex::new(0, -1),
+    Hex::new(-1, 0),
+    Hex::new(-1, 1),
+    Hex::new(0, 1),
+    Hex::new(2, -1),
+    Hex::new(1, -2),
+    Hex::new(-1, -1),
+    Hex::new(-2, 1),
+    Hex::new(-1, 2),
+    Hex::new(1, 1),
+];
+
+/// Moves `end` onto the nearest line that runs from `start` along a hex axis or diagonal.
+fn snap_to_axes(start: Hex, end: Hex) -> Hex {
+    /// Converts a hex offset into a cartesian vector, so that angles and lengths can be compared.
+    fn to_cartesian(hex: Hex) -> Vec2 {
+        Vec2::new(
+            hex.x as f32 + hex.y as f32 / 2.,
+            hex.y as f32 * 3_f32.sqrt() / 2.,
+        )
+    }
+
+    let offset = to_cartesian(end - start);
+    if offset == Vec2::ZERO {
+        return end;
+    }
+
+    let (direction, projection) = SNAPPING_DIRECTIONS
+        .iter()
+        .map(|&direction| {
+            let unit = to_cartesian(direction).normalize();
+            (direction, offset.dot(unit))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap();
+
+    // Of the two nearest points on the line, prefer whichever is closer to the cursor, and then the shorter line
+    let ratio = projection / to_cartesian(direction).length();
+    let steps = [ratio.floor() as i32, ratio.ceil() as i32]
+        .into_iter()
+        .min_by_key(|&steps| (start + direction * steps).unsigned_distance_to(end))
+        .unwrap();
+
+    start + direction * steps
 }
 
 /// What should be done with the selected tiles
@@ -480,16 +659,50 @@ enum SelectionShape {
 }
 
 impl SelectionState {
+    /// The largest brush size that can be set.
+    const MAX_BRUSH_SIZE: u32 = 10;
+
+    /// The radius of the selection that would be made this frame, in tiles.
+    ///
+    /// For area selections, this is the distance dragged, but never less than the brush size.
+    pub(crate) fn radius(&self) -> u32 {
+        match self.shape {
+            SelectionShape::Area { radius, .. } => radius,
+            SelectionShape::Single | SelectionShape::Line { .. } => self.brush_size,
+        }
+    }
+
+    /// Is the player currently drawing an area or line selection?
+    pub(crate) fn is_shaped(&self) -> bool {
+        !matches!(self.shape, SelectionShape::Single)
+    }
+
+    /// Where a line selection starting at `start` should end, given the tile under the cursor.
+    fn line_end(&self, start: VoxelPos, hovered_tile: VoxelPos) -> VoxelPos {
+        match self.snap_lines {
+            true => VoxelPos {
+                hex: snap_to_axes(start.hex, hovered_tile.hex),
+                height: hovered_tile.height,
+            },
+            false => hovered_tile,
+        }
+    }
+
     /// Determine what selection state should be used this frame based on player actions
     fn compute(
         &mut self,
         tool: &Tool,
         actions: &ActionState<PlayerAction>,
+        line_selection_settings: &LineSelectionSettings,
         hovered_tile: VoxelPos,
     ) {
         use PlayerAction::*;
 
         self.multiple = actions.pressed(PlayerAction::Multiple);
+        self.tiles_only = actions.pressed(SelectTiles);
+        self.same_type = actions.pressed(SelectSameType);
+        self.snap_lines =
+            line_selection_settings.snap_to_axes != actions.pressed(ToggleLineSnapping);
 
         self.shape = if actions.pressed(Line) {
             let start = if let SelectionShape::Line { start } = self.shape {
@@ -505,7 +718,10 @@ impl SelectionState {
             } else {
                 hovered_tile
             };
-            let radius = hovered_tile.hex.unsigned_distance_to(center.hex);
+            let radius = hovered_tile
+                .hex
+                .unsigned_distance_to(center.hex)
+                .max(self.brush_size);
 
             SelectionShape::Area { center, radius }
         } else {
@@ -540,6 +756,11 @@ impl SelectionState {
         if !tool.is_empty() && !self.multiple {
             self.action = SelectionAction::Preview;
         }
+
+        // Measuring a region should never change what is selected
+        if actions.pressed(Measure) {
+            self.action = SelectionAction::Preview;
+        }
     }
 }
 
@@ -551,8 +772,12 @@ fn set_selection(
     actions: Res<ActionState<PlayerAction>>,
     mut hovered_tiles: ResMut<HoveredTiles>,
     mut selection_state: ResMut<SelectionState>,
+    line_selection_settings: Res<LineSelectionSettings>,
     mut last_tile_selected: Local<Option<VoxelPos>>,
     map_geometry: Res<MapGeometry>,
+    same_type_selection_settings: Res<SameTypeSelectionSettings>,
+    structure_query: Query<(&Id<Structure>, &VoxelPos), (Without<Ghost>, Without<Preview>)>,
+    maybe_fog_of_war: Option<Res<FogOfWar>>,
 ) {
     // Cast to ordinary references for ease of use
     let actions = &*actions;
@@ -564,7 +789,7 @@ fn set_selection(
     };
 
     // Compute how we should handle the selection based on the actions of the player
-    selection_state.compute(&tool, actions, hovered_tile);
+    selection_state.compute(&tool, actions, &line_selection_settings, hovered_tile);
 
     // Update hovered tiles
     hovered_tiles.update(hovered_tile, &selection_state);
@@ -573,12 +798,12 @@ fn set_selection(
     match (selection_state.action, selection_state.shape) {
         // No need to do work here, hovered tiles are always computed
         (SelectionAction::Preview, _) => (),
-        (SelectionAction::Select, SelectionShape::Line { .. }) => {
+        (SelectionAction::Select, SelectionShape::Line { start }) => {
             *current_selection =
                 current_selection.select_terrain(hovered_tile, &selection_state, map_geometry);
             // Let players chain lines head to tail nicely
             selection_state.shape = SelectionShape::Line {
-                start: hovered_tile,
+                start: selection_state.line_end(start, hovered_tile),
             };
         }
         (SelectionAction::Select, SelectionShape::Area { .. }) => {
@@ -597,7 +822,22 @@ fn set_selection(
             // Update the cache
             *last_tile_selected = cursor_pos.maybe_voxel_pos();
 
-            if same_tile_as_last_time
+            let same_type_target = cursor_pos
+                .maybe_structure()
+                .filter(|_| selection_state.same_type && !selection_state.multiple)
+                .and_then(|structure_entity| structure_query.get(structure_entity).ok());
+
+            if let Some((&structure_id, structure_pos)) = same_type_target {
+                if actions.just_pressed(PlayerAction::UseTool) {
+                    *current_selection = CurrentSelection::select_same_type(
+                        structure_id,
+                        structure_pos.hex,
+                        same_type_selection_settings.radius,
+                        &structure_query,
+                        maybe_fog_of_war.as_deref(),
+                    );
+                }
+            } else if same_tile_as_last_time
                 && !selection_state.multiple
                 && actions.just_pressed(PlayerAction::UseTool)
             {
@@ -625,7 +865,7 @@ fn set_selection(
                 _ => *current_selection = CurrentSelection::None,
             }
         }
-        (SelectionAction::Deselect, SelectionShape::Line { .. }) => {
+        (SelectionAction::Deselect, SelectionShape::Line { start }) => {
             match &mut *current_selection {
                 CurrentSelection::Voxels(ref mut selected_voxels) => {
                     if let Some(hovered_tile) = cursor_pos.maybe_voxel_pos() {
@@ -641,46 +881,119 @@ fn set_selection(
 
             // Let players chain lines head to tail nicely
             selection_state.shape = SelectionShape::Line {
-                start: hovered_tile,
+                start: selection_state.line_end(start, hovered_tile),
             };
         }
     }
 }
 
-/// Set tile interactions based on hover and selection state
+/// Set the [`ObjectInteraction`] of terrain, structures and units based on hover and selection state.
+///
+/// Objects are found using the [`MapGeometry`] spatial index, so every object stacked on a hovered or selected tile is affected.
+/// Units are not stored in the index, and are checked based on their position instead,
+/// so this is rerun whenever a unit moves.
 pub(super) fn set_tile_interactions(
     current_selection: Res<CurrentSelection>,
     hovered_tiles: Res<HoveredTiles>,
-    mut terrain_query: Query<(&VoxelPos, &mut ObjectInteraction)>,
+    map_geometry: Res<MapGeometry>,
+    unit_query: Query<(Entity, &VoxelPos), With<Id<Unit>>>,
+    moved_unit_query: Query<(), (With<Id<Unit>>, Changed<VoxelPos>)>,
+    mut interaction_query: Query<&mut ObjectInteraction>,
+    // Tracks which entities need to be reset when they are no longer hovered or selected
+    mut previously_interacted: Local<HashSet<Entity>>,
 ) {
-    if current_selection.is_changed() || hovered_tiles.is_changed() {
-        for (voxel_pos, mut object_interaction) in terrain_query.iter_mut() {
-            let hovered = hovered_tiles.contains(&voxel_pos.hex);
-            let selected = if let CurrentSelection::Voxels(selected_voxels) = &*current_selection {
-                selected_voxels.contains(voxel_pos)
-            } else {
-                false
-            };
+    if !current_selection.is_changed() && !hovered_tiles.is_changed() && moved_unit_query.is_empty()
+    {
+        return;
+    }
+
+    let selected_hexes: HashSet<Hex> = match &*current_selection {
+        CurrentSelection::Voxels(selected_voxels) => selected_voxels
+            .iter()
+            .map(|voxel_pos| voxel_pos.hex)
+            .collect(),
+        _ => HashSet::new(),
+    };
+
+    // Stores whether each entity is (hovered, selected).
+    // Multi-tile structures count as hovered or selected if any of their tiles are.
+    let mut interactions: HashMap<Entity, (bool, bool)> = HashMap::new();
+
+    for &hex in hovered_tiles.iter().chain(selected_hexes.iter()) {
+        let hovered = hovered_tiles.contains(&hex);
+        let selected = selected_hexes.contains(&hex);
+
+        for voxel_object in map_geometry.objects_in_column(hex) {
+            let interaction = interactions.entry(voxel_object.entity).or_default();
+            interaction.0 |= hovered;
+            interaction.1 |= selected;
+        }
+    }
+
+    if let CurrentSelection::Structure(structure_entity) = *current_selection {
+        interactions.entry(structure_entity).or_default().1 = true;
+    }
+
+    for (unit_entity, unit_pos) in unit_query.iter() {
+        let hovered = hovered_tiles.contains(&unit_pos.hex);
+        let selected = selected_hexes.contains(&unit_pos.hex)
+            || matches!(*current_selection, CurrentSelection::Unit(selected_unit) if selected_unit == unit_entity);
+
+        if hovered || selected {
+            interactions.insert(unit_entity, (hovered, selected));
+        }
+    }
+
+    for entity in previously_interacted.drain() {
+        if interactions.contains_key(&entity) {
+            continue;
+        }
 
-            *object_interaction = ObjectInteraction::new(hovered, selected);
+        if let Ok(mut object_interaction) = interaction_query.get_mut(entity) {
+            *object_interaction = ObjectInteraction::None;
+        }
+    }
+
+    for (entity, (hovered, selected)) in interactions {
+        if let Ok(mut object_interaction) = interaction_query.get_mut(entity) {
+            object_interaction.set_if_neq(ObjectInteraction::new(hovered, selected));
+            previously_interacted.insert(entity);
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use bevy::utils::HashSet;
+    use bevy::{prelude::World, utils::HashSet};
+    use hexx::Hex;
 
-    use super::SelectedVoxels;
+    use super::{snap_to_axes, SelectedVoxels, SelectionState};
     use crate::{
+        asset_management::manifest::Id,
         enum_iter::IterableEnum,
-        geometry::VoxelPos,
+        geometry::{MapGeometry, VoxelPos},
         player_interaction::{
             picking::CursorPos,
             selection::{CurrentSelection, SelectionVariant},
         },
     };
 
+    #[test]
+    fn lines_snap_to_axes_and_diagonals() {
+        let start = Hex::new(1, -1);
+        for (end, expected) in [
+            // Points already on an axis or diagonal are left alone
+            (Hex::new(0, 5), Hex::new(0, 5)),
+            (Hex::new(3, 3), Hex::new(3, 3)),
+            (Hex::ZERO, Hex::ZERO),
+            // Otherwise, the closest point on the nearest line is used
+            (Hex::new(5, 1), Hex::new(5, 0)),
+            (Hex::new(7, -2), Hex::new(6, -3)),
+        ] {
+            assert_eq!(snap_to_axes(start, start + end), start + expected);
+        }
+    }
+
     #[test]
     fn simple_selection() {
         let mut selected_voxels = SelectedVoxels::default();
@@ -739,6 +1052,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn clicking_a_structure_selects_it() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let structure_entity = world.spawn_empty().id();
+        let voxel_pos = VoxelPos::default();
+        let cursor_pos = CursorPos::new(voxel_pos).hovering_structure(structure_entity);
+
+        let mut current_selection = CurrentSelection::None;
+        current_selection.update_from_cursor_pos(
+            &cursor_pos,
+            voxel_pos,
+            &SelectionState::default(),
+            &map_geometry,
+        );
+        assert_eq!(
+            current_selection,
+            CurrentSelection::Structure(structure_entity)
+        );
+    }
+
+    #[test]
+    fn tiles_can_be_selected_under_structures() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let structure_entity = world.spawn_empty().id();
+        let voxel_pos = VoxelPos::default();
+        let cursor_pos = CursorPos::new(voxel_pos).hovering_structure(structure_entity);
+        let selection_state = SelectionState {
+            tiles_only: true,
+            ..Default::default()
+        };
+
+        let mut current_selection = CurrentSelection::None;
+        current_selection.update_from_cursor_pos(
+            &cursor_pos,
+            voxel_pos,
+            &selection_state,
+            &map_geometry,
+        );
+
+        let mut expected = SelectedVoxels::default();
+        expected.insert(voxel_pos);
+        assert_eq!(current_selection, CurrentSelection::Voxels(expected));
+    }
+
     #[test]
     fn next_never_returns_none() {
         for variant in SelectionVariant::variants() {
@@ -812,4 +1171,26 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn same_type_selection_only_picks_matching_structures_in_range() {
+        let leuco = Id::from_name("leuco".to_string());
+        let acacia = Id::from_name("acacia".to_string());
+        let near = VoxelPos::from_xy(1, 0);
+        let far = VoxelPos::from_xy(5, 0);
+        let other = VoxelPos::from_xy(0, 1);
+        let structures = [(leuco, near), (leuco, far), (acacia, other)];
+
+        let current_selection = CurrentSelection::select_same_type(
+            leuco,
+            Hex::ZERO,
+            3,
+            structures.iter().map(|(id, voxel_pos)| (id, voxel_pos)),
+            None,
+        );
+
+        let mut expected = SelectedVoxels::default();
+        expected.insert(near);
+        assert_eq!(current_selection, CurrentSelection::Voxels(expected));
+    }
 }
@@ -0,0 +1,327 @@
+//! Translates touchscreen gestures into [`PlayerAction`]s.
+//!
+//! - tap: use the current tool on the touched tile
+//! - drag: use the current tool on every tile dragged across
+//! - long press: select the touched tile for inspection
+//! - pinch: zoom the camera
+//! - two-finger drag: pan the camera
+
+use bevy::{input::touch::Touches, prelude::*, window::PrimaryWindow};
+use leafwing_input_manager::{
+    axislike::DualAxisData, plugin::InputManagerSystem, prelude::ActionState,
+};
+
+use super::{clipboard::Tool, PlayerAction};
+
+/// Recognizes touch gestures.
+pub(super) struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GestureRecognizer>().add_systems(
+            PreUpdate,
+            // Gestures must be applied after the input map has been read, or they would be overwritten
+            apply_touch_gestures.after(InputManagerSystem::Update),
+        );
+    }
+}
+
+/// A single finger touching the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TouchPoint {
+    /// Identifies this finger, for as long as it is touching the screen.
+    id: u64,
+    /// Where this finger is, in logical pixels from the top-left corner of the window.
+    position: Vec2,
+}
+
+/// A gesture made by the player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Gesture {
+    /// A single finger was briefly pressed and released in place.
+    Tap(Vec2),
+    /// A single finger was pressed and held in place.
+    LongPress(Vec2),
+    /// A single finger is moving across the screen.
+    Drag(Vec2),
+    /// Two fingers moved closer together or further apart.
+    ///
+    /// This stores the ratio of the new distance between the fingers to the old one.
+    Pinch(f32),
+    /// Two fingers moved across the screen together, by this many logical pixels.
+    Pan(Vec2),
+}
+
+/// What the player's fingers are currently doing.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum GestureState {
+    /// Nothing is touching the screen.
+    #[default]
+    Idle,
+    /// A single finger has been pressed, but it's not yet clear what gesture it is making.
+    Pressing {
+        /// The finger being tracked.
+        id: u64,
+        /// Where the finger was first pressed.
+        start: Vec2,
+        /// Where the finger is now.
+        position: Vec2,
+        /// How long the finger has been pressed for, in seconds.
+        held: f32,
+    },
+    /// A single finger is being dragged.
+    Dragging,
+    /// A single finger is being held in place after a long press.
+    LongPressed,
+    /// Two fingers are touching the screen.
+    TwoFingers {
+        /// The midpoint between the fingers on the previous frame.
+        center: Vec2,
+        /// The distance between the fingers on the previous frame.
+        distance: f32,
+    },
+    /// A multi-finger gesture has ended, but some fingers are still touching the screen.
+    ///
+    /// Nothing is recognized until all fingers have been lifted.
+    Cancelled,
+}
+
+/// Turns raw touches into [`Gesture`]s.
+#[derive(Resource, Debug, Default)]
+struct GestureRecognizer {
+    /// What the player's fingers are currently doing.
+    state: GestureState,
+}
+
+impl GestureRecognizer {
+    /// The distance in logical pixels that a finger must move before a press becomes a drag.
+    const DRAG_THRESHOLD: f32 = 10.;
+
+    /// The number of seconds that a finger must be held in place for a long press.
+    const LONG_PRESS_DURATION: f32 = 0.5;
+
+    /// The smallest change in finger spacing that counts as a pinch, as a fraction of the distance between them.
+    const PINCH_THRESHOLD: f32 = 0.01;
+
+    /// Updates the state of the gesture based on the fingers currently touching the screen,
+    /// returning any gestures that were recognized.
+    fn update(&mut self, touches: &[TouchPoint], delta_secs: f32) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+
+        match touches {
+            [] => {
+                if let GestureState::Pressing { position, .. } = self.state {
+                    gestures.push(Gesture::Tap(position));
+                }
+                self.state = GestureState::Idle;
+            }
+            [touch] => match self.state {
+                GestureState::Idle => {
+                    self.state = GestureState::Pressing {
+                        id: touch.id,
+                        start: touch.position,
+                        position: touch.position,
+                        held: 0.,
+                    }
+                }
+                GestureState::Pressing {
+                    id, start, held, ..
+                } if id == touch.id => {
+                    let held = held + delta_secs;
+
+                    if start.distance(touch.position) > Self::DRAG_THRESHOLD {
+                        self.state = GestureState::Dragging;
+                        gestures.push(Gesture::Drag(touch.position));
+                    } else if held >= Self::LONG_PRESS_DURATION {
+                        self.state = GestureState::LongPressed;
+                        gestures.push(Gesture::LongPress(touch.position));
+                    } else {
+                        self.state = GestureState::Pressing {
+                            id,
+                            start,
+                            position: touch.position,
+                            held,
+                        };
+                    }
+                }
+                GestureState::Dragging => gestures.push(Gesture::Drag(touch.position)),
+                GestureState::LongPressed | GestureState::Cancelled => (),
+                // A different finger, or one left over from a two-finger gesture
+                GestureState::Pressing { .. } | GestureState::TwoFingers { .. } => {
+                    self.state = GestureState::Cancelled
+                }
+            },
+            [first, second, ..] => {
+                let new_center = (first.position + second.position) / 2.;
+                let new_distance = first.position.distance(second.position);
+
+                if let GestureState::TwoFingers { center, distance } = self.state {
+                    let pan = new_center - center;
+                    if pan != Vec2::ZERO {
+                        gestures.push(Gesture::Pan(pan));
+                    }
+
+                    if distance > 0. {
+                        let ratio = new_distance / distance;
+                        if (ratio - 1.).abs() > Self::PINCH_THRESHOLD {
+                            gestures.push(Gesture::Pinch(ratio));
+                        }
+                    }
+                }
+
+                self.state = GestureState::TwoFingers {
+                    center: new_center,
+                    distance: new_distance,
+                };
+            }
+        }
+
+        gestures
+    }
+
+    /// The position of the single finger being tracked, if any.
+    fn single_finger(&self, touches: &[TouchPoint]) -> Option<Vec2> {
+        match (self.state, touches) {
+            (
+                GestureState::Pressing { .. } | GestureState::Dragging | GestureState::LongPressed,
+                [touch],
+            ) => Some(touch.position),
+            _ => None,
+        }
+    }
+}
+
+/// Presses the [`PlayerAction`]s that correspond to the gestures made on the touchscreen.
+fn apply_touch_gestures(
+    touches: Res<Touches>,
+    time: Res<Time>,
+    tool: Res<Tool>,
+    mut gesture_recognizer: ResMut<GestureRecognizer>,
+    mut actions: ResMut<ActionState<PlayerAction>>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut cursor_moved_events: EventWriter<CursorMoved>,
+) {
+    /// Converts a two-finger pan in logical pixels into the equivalent joystick input.
+    const PAN_SENSITIVITY: f32 = 0.05;
+
+    let mut touch_points: Vec<TouchPoint> = touches
+        .iter()
+        .map(|touch| TouchPoint {
+            id: touch.id(),
+            position: touch.position(),
+        })
+        .collect();
+    // Keep the order of fingers stable between frames
+    touch_points.sort_by_key(|touch| touch.id);
+
+    let gestures = gesture_recognizer.update(&touch_points, time.delta_seconds());
+
+    // Move the cursor along with a single finger, so that the touched tile is picked
+    if let Some(position) = gesture_recognizer.single_finger(&touch_points) {
+        if let Ok(window) = window_query.get_single() {
+            cursor_moved_events.send(CursorMoved { window, position });
+        }
+    }
+
+    for gesture in gestures {
+        match gesture {
+            Gesture::Tap(_) => actions.press(PlayerAction::UseTool),
+            Gesture::Drag(_) => {
+                actions.press(PlayerAction::UseTool);
+                actions.press(PlayerAction::Multiple);
+            }
+            Gesture::LongPress(_) => {
+                // Long presses only inspect, so they should never place the contents of the clipboard
+                if tool.is_empty() {
+                    actions.press(PlayerAction::UseTool);
+                }
+            }
+            Gesture::Pinch(ratio) => match ratio > 1. {
+                true => actions.press(PlayerAction::ZoomIn),
+                false => actions.press(PlayerAction::ZoomOut),
+            },
+            Gesture::Pan(delta) => {
+                // Dragging the map moves the camera in the opposite direction, and screen space is y-down
+                let axis = (Vec2::new(-delta.x, delta.y) * PAN_SENSITIVITY).clamp_length_max(1.);
+                actions.press(PlayerAction::Pan);
+                actions.action_data_mut(PlayerAction::Pan).axis_pair =
+                    Some(DualAxisData::from_xy(axis));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finger(id: u64, x: f32, y: f32) -> TouchPoint {
+        TouchPoint {
+            id,
+            position: Vec2::new(x, y),
+        }
+    }
+
+    #[test]
+    fn quick_presses_are_taps() {
+        let mut recognizer = GestureRecognizer::default();
+
+        assert!(recognizer.update(&[finger(0, 5., 5.)], 0.).is_empty());
+        assert!(recognizer.update(&[finger(0, 6., 5.)], 0.1).is_empty());
+        assert_eq!(
+            recognizer.update(&[], 0.1),
+            vec![Gesture::Tap(Vec2::new(6., 5.))]
+        );
+        assert_eq!(recognizer.state, GestureState::Idle);
+    }
+
+    #[test]
+    fn held_presses_are_long_presses() {
+        let mut recognizer = GestureRecognizer::default();
+
+        recognizer.update(&[finger(0, 5., 5.)], 0.);
+        assert_eq!(
+            recognizer.update(&[finger(0, 5., 5.)], GestureRecognizer::LONG_PRESS_DURATION),
+            vec![Gesture::LongPress(Vec2::new(5., 5.))]
+        );
+        // Releasing a long press is not also a tap
+        assert!(recognizer.update(&[], 0.1).is_empty());
+    }
+
+    #[test]
+    fn moving_presses_are_drags() {
+        let mut recognizer = GestureRecognizer::default();
+
+        recognizer.update(&[finger(0, 0., 0.)], 0.);
+        assert_eq!(
+            recognizer.update(&[finger(0, 50., 0.)], 0.1),
+            vec![Gesture::Drag(Vec2::new(50., 0.))]
+        );
+        assert_eq!(
+            recognizer.update(&[finger(0, 60., 0.)], 0.1),
+            vec![Gesture::Drag(Vec2::new(60., 0.))]
+        );
+        assert!(recognizer.update(&[], 0.1).is_empty());
+    }
+
+    #[test]
+    fn two_fingers_pinch_and_pan() {
+        let mut recognizer = GestureRecognizer::default();
+
+        assert!(recognizer
+            .update(&[finger(0, 0., 0.), finger(1, 100., 0.)], 0.)
+            .is_empty());
+        assert_eq!(
+            recognizer.update(&[finger(0, -50., 0.), finger(1, 150., 0.)], 0.1),
+            vec![Gesture::Pinch(2.)]
+        );
+        assert_eq!(
+            recognizer.update(&[finger(0, -50., 10.), finger(1, 150., 10.)], 0.1),
+            vec![Gesture::Pan(Vec2::new(0., 10.))]
+        );
+
+        // Lifting one finger doesn't start a new gesture
+        assert!(recognizer.update(&[finger(1, 150., 10.)], 0.1).is_empty());
+        assert!(recognizer.update(&[], 0.1).is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+//! Random events, like locust swarms or bumper blooms, shake up the colony from time to time.
+//!
+//! Each event is defined in the [`RandomEventManifest`], with a data-driven trigger [`Condition`](crate::milestones::conditions::Condition)
+//! that is checked against the state of the simulation, and a list of [`EventEffect`]s that are applied when it occurs.
+//! At most one event occurs each day, chosen at random from those whose triggers are met, weighted by their [`RandomEventData::weight`].
+
+use bevy::{prelude::*, utils::HashMap};
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
+
+use crate::{
+    asset_management::manifest::{plugin::ManifestPlugin, Id},
+    construction::ghosts::{Ghost, Preview},
+    factions::Factions,
+    geometry::{MapGeometry, VoxelPos},
+    items::{
+        ledger::{ItemLedger, ItemSource},
+        totals::ItemTotals,
+    },
+    litter::LitterCommandsExt,
+    milestones::conditions::ConditionContext,
+    organisms::energy::StartingEnergy,
+    player_interaction::clipboard::ClipboardData,
+    simulation::{
+        reports::DailyReports, rng::GlobalRng, time::InGameTime, weather::CurrentWeather,
+        SimulationSet,
+    },
+    structures::{
+        commands::StructureCommandsExt,
+        structure_manifest::{Structure, StructureManifest},
+    },
+    units::{census::Census, unit_assets::UnitHandles, unit_manifest::UnitManifest, UnitBundle},
+};
+
+use self::random_event_manifest::{
+    EventEffect, RandomEvent, RandomEventData, RandomEventManifest, RawRandomEventManifest,
+};
+
+pub mod random_event_manifest;
+
+/// Loads, triggers and applies random events.
+pub(crate) struct RandomEventsPlugin;
+
+impl Plugin for RandomEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ManifestPlugin::<RawRandomEventManifest>::new())
+            .init_resource::<RandomEventHistory>()
+            .add_event::<RandomEventOccurred>()
+            .add_systems(
+                FixedUpdate,
+                (roll_random_events, apply_random_events)
+                    .chain()
+                    .in_set(SimulationSet),
+            );
+    }
+}
+
+/// An event that is sent whenever a random event occurs.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct RandomEventOccurred {
+    /// The event that occurred.
+    pub event_id: Id<RandomEvent>,
+}
+
+/// Tracks when each random event last occurred.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct RandomEventHistory {
+    /// The last day on which events were rolled for.
+    last_rolled_day: u64,
+    /// The day on which each event last occurred.
+    last_occurred: HashMap<Id<RandomEvent>, u64>,
+}
+
+impl RandomEventHistory {
+    /// The chance that an event occurs on any given day, if any can.
+    const EVENT_CHANCE_PER_DAY: f64 = 0.2;
+
+    /// The day on which `event_id` last occurred, if it has ever occurred.
+    pub fn last_occurred(&self, event_id: Id<RandomEvent>) -> Option<u64> {
+        self.last_occurred.get(&event_id).copied()
+    }
+
+    /// Has the cooldown of the event finished by `day`?
+    fn is_ready(&self, event_id: Id<RandomEvent>, data: &RandomEventData, day: u64) -> bool {
+        self.last_occurred(event_id)
+            .map_or(true, |last_day| day >= last_day + data.cooldown_days)
+    }
+
+    /// Randomly chooses an event whose trigger is met and whose cooldown has finished.
+    fn choose_event(
+        &self,
+        random_event_manifest: &RandomEventManifest,
+        context: &ConditionContext,
+        rng: &mut impl Rng,
+    ) -> Option<Id<RandomEvent>> {
+        let mut candidates: Vec<(Id<RandomEvent>, f32)> = random_event_manifest
+            .data_map()
+            .iter()
+            .filter(|(&event_id, data)| {
+                data.weight > 0.
+                    && self.is_ready(event_id, data, context.elapsed_days)
+                    && data.trigger.is_satisfied(context)
+            })
+            .map(|(&event_id, data)| (event_id, data.weight))
+            .collect();
+
+        // Sort to make the choice independent of the iteration order of the manifest
+        candidates.sort_by_key(|(event_id, _)| *event_id);
+
+        candidates
+            .choose_weighted(rng, |(_, weight)| *weight)
+            .ok()
+            .map(|(event_id, _)| *event_id)
+    }
+}
+
+/// Once per day, randomly picks an event to occur.
+fn roll_random_events(
+    random_event_manifest: Res<RandomEventManifest>,
+    mut history: ResMut<RandomEventHistory>,
+    mut occurred_events: EventWriter<RandomEventOccurred>,
+    census: Res<Census>,
+    item_totals: Res<ItemTotals>,
+    daily_reports: Res<DailyReports>,
+    current_weather: Res<CurrentWeather>,
+    in_game_time: Res<InGameTime>,
+    mut rng: ResMut<GlobalRng>,
+) {
+    let today = in_game_time.rounded_elapsed_days();
+    if today == history.last_rolled_day {
+        return;
+    }
+    history.last_rolled_day = today;
+
+    let rng = rng.get_mut();
+    if !rng.gen_bool(RandomEventHistory::EVENT_CHANCE_PER_DAY) {
+        return;
+    }
+
+    let context = ConditionContext {
+        census: &census,
+        item_totals: &item_totals,
+        daily_reports: &daily_reports,
+        current_weather: &current_weather,
+        elapsed_days: today,
+        season: in_game_time.season(),
+    };
+
+    if let Some(event_id) = history.choose_event(&random_event_manifest, &context, rng) {
+        info!(
+            "Random event: {}",
+            random_event_manifest.get(event_id).title
+        );
+        history.last_occurred.insert(event_id, today);
+        occurred_events.send(RandomEventOccurred { event_id });
+    }
+}
+
+/// Applies the [`EventEffect`]s of each random event that occurred.
+fn apply_random_events(
+    mut occurred_events: EventReader<RandomEventOccurred>,
+    random_event_manifest: Res<RandomEventManifest>,
+    structure_query: Query<(&Id<Structure>, &VoxelPos), (Without<Ghost>, Without<Preview>)>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    maybe_unit_handles: Option<Res<UnitHandles>>,
+    factions: Res<Factions>,
+    map_geometry: Res<MapGeometry>,
+    mut rng: ResMut<GlobalRng>,
+    mut item_ledger: ResMut<ItemLedger>,
+    mut commands: Commands,
+) {
+    for occurred_event in occurred_events.read() {
+        let event_data = random_event_manifest.get(occurred_event.event_id);
+        let rng = rng.get_mut();
+
+        for effect in &event_data.effects {
+            match *effect {
+                EventEffect::SpawnUnits { unit_id, count } => {
+                    let locations = map_geometry
+                        .walkable_voxels()
+                        .into_iter()
+                        .choose_multiple(rng, count as usize);
+
+                    for voxel_pos in locations {
+                        let unit_data = unit_manifest.get(unit_id).clone();
+                        let faction = factions.starting_territory(voxel_pos.hex);
+                        let unit_bundle = match maybe_unit_handles {
+                            Some(ref unit_handles) => UnitBundle::newborn(
+                                unit_id,
+                                voxel_pos,
+                                unit_data,
+                                faction,
+                                unit_handles,
+                            ),
+                            None => {
+                                UnitBundle::testing(unit_id, voxel_pos, unit_data, faction, rng)
+                            }
+                        };
+
+                        commands.spawn(unit_bundle);
+                    }
+                }
+                EventEffect::SpawnStructures {
+                    structure_id,
+                    count,
+                } => {
+                    let footprint = &structure_manifest.get(structure_id).footprint;
+                    let locations = map_geometry
+                        .walkable_voxels()
+                        .into_iter()
+                        .choose_multiple(rng, count as usize);
+
+                    for voxel_pos in locations {
+                        let clipboard_data =
+                            ClipboardData::generate_from_id(structure_id, &structure_manifest);
+
+                        if map_geometry.is_footprint_valid(
+                            voxel_pos,
+                            footprint,
+                            clipboard_data.facing,
+                        ) && map_geometry
+                            .is_space_available(voxel_pos, footprint, clipboard_data.facing)
+                            .is_ok()
+                        {
+                            commands.spawn_structure(
+                                voxel_pos,
+                                clipboard_data,
+                                StartingEnergy::Random,
+                                None,
+                            );
+                        }
+                    }
+                }
+                EventEffect::DestroyStructures {
+                    structure_id,
+                    fraction,
+                } => {
+                    let mut locations: Vec<VoxelPos> = structure_query
+                        .iter()
+                        .filter(|(&id, _)| id == structure_id)
+                        .map(|(_, &voxel_pos)| voxel_pos)
+                        .collect();
+                    locations.sort_by_key(|voxel_pos| (voxel_pos.hex.x, voxel_pos.hex.y));
+
+                    let n_destroyed =
+                        (locations.len() as f32 * fraction.clamp(0., 1.)).round() as usize;
+                    for &voxel_pos in locations.choose_multiple(rng, n_destroyed) {
+                        commands.despawn_structure(voxel_pos);
+                    }
+                }
+                EventEffect::DropItems { item_id, count } => {
+                    let Some(voxel_pos) = map_geometry.walkable_voxels().into_iter().choose(rng)
+                    else {
+                        continue;
+                    };
+
+                    for _ in 0..count {
+                        commands.spawn_litter(voxel_pos, item_id);
+                    }
+                    item_ledger.record_produced(item_id, count, ItemSource::Event);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use crate::{milestones::conditions::Condition, simulation::time::Season};
+
+    use super::*;
+
+    fn random_event(trigger: Condition, cooldown_days: u64) -> RandomEventData {
+        RandomEventData {
+            title: "Test event".to_string(),
+            description: "Something happened".to_string(),
+            weight: 1.,
+            trigger,
+            cooldown_days,
+            effects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn events_respect_triggers_and_cooldowns() {
+        let mut manifest = RandomEventManifest::new();
+        manifest.insert(
+            "early".to_string(),
+            random_event(Condition::DaysSurvived(0), 5),
+        );
+        manifest.insert(
+            "late".to_string(),
+            random_event(Condition::DaysSurvived(100), 0),
+        );
+        let early = Id::from_name("early".to_string());
+
+        let census = Census::default();
+        let item_totals = ItemTotals::default();
+        let daily_reports = DailyReports::default();
+        let current_weather = CurrentWeather::default();
+        let context = |elapsed_days| ConditionContext {
+            census: &census,
+            item_totals: &item_totals,
+            daily_reports: &daily_reports,
+            current_weather: &current_weather,
+            elapsed_days,
+            season: Season::Spring,
+        };
+
+        let rng = &mut SmallRng::seed_from_u64(0);
+        let mut history = RandomEventHistory::default();
+        assert_eq!(
+            history.choose_event(&manifest, &context(1), rng),
+            Some(early)
+        );
+
+        history.last_occurred.insert(early, 1);
+        assert_eq!(history.choose_event(&manifest, &context(3), rng), None);
+        assert_eq!(
+            history.choose_event(&manifest, &context(6), rng),
+            Some(early)
+        );
+    }
+}
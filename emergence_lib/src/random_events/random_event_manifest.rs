@@ -0,0 +1,192 @@
+//! Defines write-only data for each random event.
+
+use bevy::{
+    asset::Asset,
+    reflect::{Reflect, TypePath, TypeUuid},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::{loader::IsRawManifest, Id, Manifest},
+    items::item_manifest::Item,
+    milestones::conditions::{Condition, RawCondition},
+    structures::structure_manifest::Structure,
+    units::unit_manifest::Unit,
+};
+
+/// The marker type for [`Id<RandomEvent>`](crate::asset_management::manifest::Id).
+#[derive(Reflect, Clone, Copy, PartialEq, Eq)]
+pub struct RandomEvent;
+/// Stores the read-only definitions for all random events.
+pub type RandomEventManifest = Manifest<RandomEvent, RandomEventData>;
+
+/// Data stored in a [`RandomEventManifest`] for each [`Id<RandomEvent>`](crate::asset_management::manifest::Id).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomEventData {
+    /// The name of the event, shown to the player.
+    pub title: String,
+    /// What happened, shown to the player.
+    pub description: String,
+    /// How likely this event is to be chosen, relative to the other events that can occur.
+    pub weight: f32,
+    /// The condition that must be met for this event to occur.
+    pub trigger: Condition,
+    /// The minimum number of days between occurrences of this event.
+    pub cooldown_days: u64,
+    /// What happens when this event occurs.
+    pub effects: Vec<EventEffect>,
+}
+
+/// A change made to the world when a random event occurs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventEffect {
+    /// Spawns units of the given species at random locations.
+    SpawnUnits {
+        /// The species of unit to spawn.
+        unit_id: Id<Unit>,
+        /// The number of units to spawn.
+        count: u32,
+    },
+    /// Spawns structures of the given type at random locations.
+    SpawnStructures {
+        /// The type of structure to spawn.
+        structure_id: Id<Structure>,
+        /// The number of structures to attempt to spawn.
+        ///
+        /// Locations that are not valid for the structure are skipped.
+        count: u32,
+    },
+    /// Destroys a fraction of the structures of the given type.
+    DestroyStructures {
+        /// The type of structure to destroy.
+        structure_id: Id<Structure>,
+        /// The fraction of these structures to destroy, between 0 and 1.
+        fraction: f32,
+    },
+    /// Drops items on the ground at a random location.
+    DropItems {
+        /// The item to drop.
+        item_id: Id<Item>,
+        /// The number of items to drop.
+        count: u32,
+    },
+}
+
+/// The unprocessed equivalent of [`RandomEventData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawRandomEventData {
+    /// The name of the event, shown to the player.
+    pub title: String,
+    /// What happened, shown to the player.
+    pub description: String,
+    /// How likely this event is to be chosen, relative to the other events that can occur.
+    pub weight: f32,
+    /// The condition that must be met for this event to occur.
+    pub trigger: RawCondition,
+    /// The minimum number of days between occurrences of this event.
+    pub cooldown_days: u64,
+    /// What happens when this event occurs.
+    pub effects: Vec<RawEventEffect>,
+}
+
+impl From<RawRandomEventData> for RandomEventData {
+    fn from(raw_data: RawRandomEventData) -> Self {
+        Self {
+            title: raw_data.title,
+            description: raw_data.description,
+            weight: raw_data.weight,
+            trigger: raw_data.trigger.into(),
+            cooldown_days: raw_data.cooldown_days,
+            effects: raw_data
+                .effects
+                .into_iter()
+                .map(EventEffect::from)
+                .collect(),
+        }
+    }
+}
+
+/// The unprocessed equivalent of [`EventEffect`], as written in manifest files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RawEventEffect {
+    /// Spawns units of the given species at random locations.
+    SpawnUnits {
+        /// The name of the species of unit to spawn.
+        unit: String,
+        /// The number of units to spawn.
+        count: u32,
+    },
+    /// Spawns structures of the given type at random locations.
+    SpawnStructures {
+        /// The name of the type of structure to spawn.
+        structure: String,
+        /// The number of structures to attempt to spawn.
+        count: u32,
+    },
+    /// Destroys a fraction of the structures of the given type.
+    DestroyStructures {
+        /// The name of the type of structure to destroy.
+        structure: String,
+        /// The fraction of these structures to destroy, between 0 and 1.
+        fraction: f32,
+    },
+    /// Drops items on the ground at a random location.
+    DropItems {
+        /// The name of the item to drop.
+        item: String,
+        /// The number of items to drop.
+        count: u32,
+    },
+}
+
+impl From<RawEventEffect> for EventEffect {
+    fn from(raw_effect: RawEventEffect) -> Self {
+        match raw_effect {
+            RawEventEffect::SpawnUnits { unit, count } => EventEffect::SpawnUnits {
+                unit_id: Id::from_name(unit),
+                count,
+            },
+            RawEventEffect::SpawnStructures { structure, count } => EventEffect::SpawnStructures {
+                structure_id: Id::from_name(structure),
+                count,
+            },
+            RawEventEffect::DestroyStructures {
+                structure,
+                fraction,
+            } => EventEffect::DestroyStructures {
+                structure_id: Id::from_name(structure),
+                fraction,
+            },
+            RawEventEffect::DropItems { item, count } => EventEffect::DropItems {
+                item_id: Id::from_name(item),
+                count,
+            },
+        }
+    }
+}
+
+/// The [`RandomEventManifest`] as seen in the manifest file.
+#[derive(Asset, Debug, Clone, Serialize, Deserialize, TypeUuid, TypePath, PartialEq)]
+#[uuid = "9e3f2a61-5c8d-4b7e-a104-2f6d8c3b5e91"]
+pub struct RawRandomEventManifest {
+    /// The data for each random event.
+    pub random_events: HashMap<String, RawRandomEventData>,
+}
+
+impl IsRawManifest for RawRandomEventManifest {
+    const EXTENSION: &'static str = "random_event_manifest.json";
+
+    type Marker = RandomEvent;
+    type Data = RandomEventData;
+
+    fn process(&self) -> Manifest<Self::Marker, Self::Data> {
+        let mut manifest = Manifest::new();
+
+        for (raw_id, raw_data) in self.random_events.clone() {
+            manifest.insert(raw_id, raw_data.into())
+        }
+
+        manifest
+    }
+}
@@ -0,0 +1,593 @@
+//! The on-disk format of saved games.
+//!
+//! Each save file begins with a small uncompressed header, containing the [`SaveMetadata`] and an optional thumbnail.
+//! This can be read on its own, so the load-game menu can list saves without decompressing and deserializing whole worlds.
+//! The serialized world follows, compressed with zstd.
+//!
+//! A CRC-32 checksum of everything after the checksum itself is stored in the header,
+//! so that truncated or corrupted saves are detected before they are loaded.
+//!
+//! The layout of a save file is:
+//!
+//! | Field               | Size             |
+//! |---------------------|------------------|
+//! | [`SaveFile::MAGIC`] | 8 bytes          |
+//! | format version      | 4 bytes          |
+//! | checksum            | 4 bytes          |
+//! | metadata length     | 4 bytes          |
+//! | metadata (JSON)     | metadata length  |
+//! | thumbnail length    | 4 bytes          |
+//! | thumbnail (PNG)     | thumbnail length |
+//! | world (zstd)        | rest of the file |
+//!
+//! All integers are stored as little-endian.
+//!
+//! Resources that are part of the game state are registered with [`SaveResourceExt::save_resource`],
+//! and are written to the world section by [`save_world`] and restored by [`load_world`].
+
+use std::{
+    cmp::Reverse,
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    log::warn,
+    prelude::{App, Resource, World},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{milestones::Profile, simulation::game_rules::GameRules};
+
+/// Information about a saved game that can be read without loading the world.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    /// The version of the game that created this save.
+    pub game_version: String,
+    /// The names of the mods that were active when the game was saved.
+    pub mods: Vec<String>,
+    /// How long the game has been played for, in wall-clock time.
+    pub play_time: Duration,
+    /// How many in-game days have elapsed.
+    pub elapsed_days: f32,
+    /// The rules that the game is played under.
+    pub game_rules: GameRules,
+}
+
+impl SaveMetadata {
+    /// Creates metadata for a game saved by the currently running version of the game.
+    pub fn new(
+        mods: Vec<String>,
+        play_time: Duration,
+        elapsed_days: f32,
+        game_rules: GameRules,
+    ) -> Self {
+        SaveMetadata {
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            mods,
+            play_time,
+            elapsed_days,
+            game_rules,
+        }
+    }
+}
+
+/// The header of a save file, which describes the save without containing the world itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSummary {
+    /// Information about the saved game.
+    pub metadata: SaveMetadata,
+    /// A PNG-encoded screenshot of the game when it was saved, if any.
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+/// A complete saved game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveFile {
+    /// The header of the save.
+    pub summary: SaveSummary,
+    /// The serialized state of the world, before compression.
+    pub world: Vec<u8>,
+}
+
+/// An error produced when reading or writing a [`SaveFile`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SaveFileError {
+    /// An [IO](std::io) Error
+    #[error("Could not access save file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [serde_json](serde_json) Error
+    #[error("Could not parse save metadata: {0}")]
+    Metadata(#[from] serde_json::Error),
+    /// The file does not start with [`SaveFile::MAGIC`].
+    #[error("Not an Emergence save file")]
+    NotASave,
+    /// The file was written in a format that this version of the game cannot read.
+    #[error("Unsupported save format version {0}")]
+    UnsupportedFormat(u32),
+    /// The contents of the file do not match its checksum.
+    #[error("Save file is corrupted: expected checksum {expected:#010x}, found {actual:#010x}")]
+    Corrupted {
+        /// The checksum stored in the file.
+        expected: u32,
+        /// The checksum of the file's actual contents.
+        actual: u32,
+    },
+    /// A section of the file is too large to be stored.
+    #[error("Save file section is too large: {0} bytes")]
+    TooLarge(usize),
+    /// A resource stored in the world could not be serialized or deserialized.
+    #[error("Could not read or write the saved {name}: {error}")]
+    Resource {
+        /// The name the resource was saved under.
+        name: &'static str,
+        /// The underlying error.
+        error: serde_json::Error,
+    },
+}
+
+impl SaveFile {
+    /// The bytes that every save file starts with.
+    pub const MAGIC: [u8; 8] = *b"EMERGSAV";
+
+    /// The version of the save file layout written by this version of the game.
+    ///
+    /// This should be incremented whenever the layout of the header changes.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// The zstd compression level used for the world.
+    ///
+    /// Saves are written while the game is running, so this favors speed over size.
+    const COMPRESSION_LEVEL: i32 = 3;
+
+    /// The file extension used for save files.
+    pub const EXTENSION: &'static str = "emsave";
+
+    /// Serializes, compresses and checksums this save.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SaveFileError> {
+        let metadata = serde_json::to_vec(&self.summary.metadata)?;
+        let thumbnail = self.summary.thumbnail.as_deref().unwrap_or_default();
+
+        let world = zstd::encode_all(self.world.as_slice(), Self::COMPRESSION_LEVEL)?;
+
+        let mut body = Vec::with_capacity(8 + metadata.len() + thumbnail.len() + world.len());
+        write_section(&mut body, &metadata)?;
+        write_section(&mut body, thumbnail)?;
+        body.extend_from_slice(&world);
+
+        let mut bytes = Vec::with_capacity(16 + body.len());
+        bytes.extend_from_slice(&Self::MAGIC);
+        bytes.extend_from_slice(&Self::FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+        bytes.extend_from_slice(&body);
+
+        Ok(bytes)
+    }
+
+    /// Verifies, decompresses and deserializes a save.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SaveFileError> {
+        let mut reader = bytes;
+        let expected = read_preamble(&mut reader)?;
+
+        let actual = crc32fast::hash(reader);
+        if actual != expected {
+            return Err(SaveFileError::Corrupted { expected, actual });
+        }
+
+        let summary = read_summary(&mut reader)?;
+        let world = zstd::decode_all(reader)?;
+
+        Ok(SaveFile { summary, world })
+    }
+
+    /// Writes this save to the file at `path`, replacing it if it already exists.
+    ///
+    /// The save is written to a temporary file, which is then renamed over `path`,
+    /// so a crash part way through never leaves a half-written save behind.
+    /// Any previous save at `path` is kept as a backup, which [`SaveFile::load`] falls back to.
+    pub fn save(&self, path: &Path) -> Result<(), SaveFileError> {
+        let bytes = self.to_bytes()?;
+
+        let temporary_path = Self::temporary_path(path);
+        let mut file = File::create(&temporary_path)?;
+        file.write_all(&bytes)?;
+        // The data must reach the disk before the rename does, or a crash could leave an empty file in its place
+        file.sync_all()?;
+        drop(file);
+
+        if path.exists() {
+            std::fs::rename(path, Self::backup_path(path))?;
+        }
+        std::fs::rename(&temporary_path, path)?;
+
+        Ok(())
+    }
+
+    /// Loads the save stored in the file at `path`.
+    ///
+    /// If that file is missing, truncated or corrupted, the backup made by the previous [`SaveFile::save`] is loaded instead.
+    /// The original error is returned if neither can be loaded.
+    pub fn load(path: &Path) -> Result<Self, SaveFileError> {
+        let error = match std::fs::read(path)
+            .map_err(SaveFileError::from)
+            .and_then(|bytes| Self::from_bytes(&bytes))
+        {
+            Ok(save_file) => return Ok(save_file),
+            Err(error) => error,
+        };
+
+        let backup_path = Self::backup_path(path);
+        match std::fs::read(&backup_path)
+            .map_err(SaveFileError::from)
+            .and_then(|bytes| Self::from_bytes(&bytes))
+        {
+            Ok(save_file) => {
+                warn!(
+                    "Could not load {}, so its backup was loaded instead: {error}",
+                    path.display()
+                );
+                Ok(save_file)
+            }
+            Err(_) => Err(error),
+        }
+    }
+
+    /// The file that a save at `path` is written to before it is complete.
+    fn temporary_path(path: &Path) -> PathBuf {
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".tmp");
+        path.with_file_name(file_name)
+    }
+
+    /// The file that the previous save at `path` is kept in.
+    pub fn backup_path(path: &Path) -> PathBuf {
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".bak");
+        path.with_file_name(file_name)
+    }
+
+    /// Reads only the header of the save stored in the file at `path`.
+    ///
+    /// The world is neither read nor verified, so this is cheap enough to call on every save in a directory.
+    pub fn load_summary(path: &Path) -> Result<SaveSummary, SaveFileError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        read_preamble(&mut reader)?;
+        read_summary(&mut reader)
+    }
+}
+
+/// A resource that is written to the world section of a save.
+#[derive(Clone, Copy)]
+struct SavedResource {
+    /// The stable name that the resource is stored under.
+    name: &'static str,
+    /// Serializes the resource, if it exists in the world.
+    save: fn(&World) -> Option<Result<serde_json::Value, serde_json::Error>>,
+    /// Replaces the resource in the world with the deserialized value.
+    load: fn(&mut World, serde_json::Value) -> Result<(), serde_json::Error>,
+}
+
+/// The resources that are stored in saves, as registered by [`SaveResourceExt::save_resource`].
+#[derive(Resource, Default)]
+pub(crate) struct SavedResources {
+    /// The registered resources, in the order they were registered.
+    resources: Vec<SavedResource>,
+}
+
+/// Extension methods for [`App`] for registering state that must survive saving and loading.
+pub(crate) trait SaveResourceExt {
+    /// Stores the resource `R` in saves under `name`.
+    ///
+    /// The name must stay the same between versions of the game, or older saves will lose this resource.
+    fn save_resource<R: Resource + Serialize + DeserializeOwned>(
+        &mut self,
+        name: &'static str,
+    ) -> &mut Self;
+}
+
+impl SaveResourceExt for App {
+    fn save_resource<R: Resource + Serialize + DeserializeOwned>(
+        &mut self,
+        name: &'static str,
+    ) -> &mut Self {
+        let mut saved_resources = self
+            .world
+            .get_resource_or_insert_with(SavedResources::default);
+
+        assert!(
+            saved_resources
+                .resources
+                .iter()
+                .all(|resource| resource.name != name),
+            "Two resources are saved as {name}"
+        );
+
+        saved_resources.resources.push(SavedResource {
+            name,
+            save: |world| world.get_resource::<R>().map(serde_json::to_value),
+            load: |world, value| {
+                world.insert_resource(serde_json::from_value::<R>(value)?);
+                Ok(())
+            },
+        });
+
+        self
+    }
+}
+
+/// Serializes every resource registered with [`SaveResourceExt::save_resource`], for the world section of a save.
+pub fn save_world(world: &World) -> Result<Vec<u8>, SaveFileError> {
+    let mut saved = serde_json::Map::new();
+
+    if let Some(saved_resources) = world.get_resource::<SavedResources>() {
+        for resource in &saved_resources.resources {
+            let Some(value) = (resource.save)(world) else {
+                continue;
+            };
+
+            let value = value.map_err(|error| SaveFileError::Resource {
+                name: resource.name,
+                error,
+            })?;
+            saved.insert(resource.name.to_string(), value);
+        }
+    }
+
+    Ok(serde_json::to_vec(&saved)?)
+}
+
+/// Restores the resources registered with [`SaveResourceExt::save_resource`] from the world section of a save.
+///
+/// Resources that are missing from the save are left untouched,
+/// so saves made before a resource was registered can still be loaded.
+pub fn load_world(world: &mut World, bytes: &[u8]) -> Result<(), SaveFileError> {
+    let mut saved: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(bytes)?;
+
+    let resources = match world.get_resource::<SavedResources>() {
+        Some(saved_resources) => saved_resources.resources.clone(),
+        None => Vec::new(),
+    };
+
+    for resource in resources {
+        let Some(value) = saved.remove(resource.name) else {
+            continue;
+        };
+
+        (resource.load)(world, value).map_err(|error| SaveFileError::Resource {
+            name: resource.name,
+            error,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Serializes a map as a list of key-value pairs.
+///
+/// Use with `#[serde(with = "crate::save_files::map_as_pairs")]` on maps whose keys, such as [`Id`](crate::asset_management::manifest::Id),
+/// can't be used as JSON object keys.
+pub(crate) mod map_as_pairs {
+    use std::hash::Hash;
+
+    use bevy::utils::HashMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes `map` as a sequence of `(key, value)` pairs.
+    pub(crate) fn serialize<K: Serialize, V: Serialize, S: Serializer>(
+        map: &HashMap<K, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map.iter())
+    }
+
+    /// Deserializes a map from a sequence of `(key, value)` pairs.
+    pub(crate) fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let pairs = Vec::<(K, V)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+/// Lists the saves stored for `profile`, along with their headers, from most to least recently saved.
+///
+/// Files that are not readable saves are skipped.
+pub fn list_saves(profile: &Profile) -> Vec<(PathBuf, SaveSummary)> {
+    let Ok(entries) = std::fs::read_dir(profile.saves_directory()) else {
+        return Vec::new();
+    };
+
+    let mut saves: Vec<(PathBuf, SaveSummary)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == SaveFile::EXTENSION)
+        })
+        .filter_map(|path| match SaveFile::load_summary(&path) {
+            Ok(summary) => Some((path, summary)),
+            Err(error) => {
+                warn!("Skipping save file {}: {error}", path.display());
+                None
+            }
+        })
+        .collect();
+
+    saves.sort_by_cached_key(|(path, _)| {
+        Reverse(
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+        )
+    });
+    saves
+}
+
+/// Writes a length-prefixed section of a save file.
+fn write_section(writer: &mut impl Write, section: &[u8]) -> Result<(), SaveFileError> {
+    let length =
+        u32::try_from(section.len()).map_err(|_| SaveFileError::TooLarge(section.len()))?;
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(section)?;
+    Ok(())
+}
+
+/// Reads a length-prefixed section of a save file.
+fn read_section(reader: &mut impl Read) -> Result<Vec<u8>, SaveFileError> {
+    let length = read_u32(reader)?;
+    let mut section = Vec::new();
+    reader.take(length as u64).read_to_end(&mut section)?;
+
+    if section.len() != length as usize {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(section)
+}
+
+/// Reads a little-endian `u32`.
+fn read_u32(reader: &mut impl Read) -> Result<u32, SaveFileError> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Checks that the reader contains a save file that can be read, returning its checksum.
+fn read_preamble(reader: &mut impl Read) -> Result<u32, SaveFileError> {
+    let mut magic = [0; 8];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| SaveFileError::NotASave)?;
+    if magic != SaveFile::MAGIC {
+        return Err(SaveFileError::NotASave);
+    }
+
+    let format_version = read_u32(reader)?;
+    if format_version != SaveFile::FORMAT_VERSION {
+        return Err(SaveFileError::UnsupportedFormat(format_version));
+    }
+
+    read_u32(reader)
+}
+
+/// Reads the [`SaveSummary`] that follows the preamble.
+fn read_summary(reader: &mut impl Read) -> Result<SaveSummary, SaveFileError> {
+    let metadata = serde_json::from_slice(&read_section(reader)?)?;
+    let thumbnail = read_section(reader)?;
+
+    Ok(SaveSummary {
+        metadata,
+        thumbnail: (!thumbnail.is_empty()).then_some(thumbnail),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save_file() -> SaveFile {
+        SaveFile {
+            summary: SaveSummary {
+                metadata: SaveMetadata::new(
+                    vec!["more_mushrooms".to_string()],
+                    Duration::from_secs(3600),
+                    12.5,
+                    GameRules::default(),
+                ),
+                thumbnail: Some(vec![0x89, b'P', b'N', b'G']),
+            },
+            world: b"a very repetitive world ".repeat(100),
+        }
+    }
+
+    #[test]
+    fn saves_round_trip() {
+        let save_file = save_file();
+        let bytes = save_file.to_bytes().unwrap();
+
+        // The world should have been compressed
+        assert!(bytes.len() < save_file.world.len());
+        assert_eq!(SaveFile::from_bytes(&bytes).unwrap(), save_file);
+    }
+
+    #[test]
+    fn summary_can_be_read_without_the_world() {
+        let save_file = save_file();
+        let bytes = save_file.to_bytes().unwrap();
+
+        let mut reader = bytes.as_slice();
+        read_preamble(&mut reader).unwrap();
+        assert_eq!(read_summary(&mut reader).unwrap(), save_file.summary);
+    }
+
+    #[test]
+    fn corrupted_saves_are_rejected() {
+        let mut bytes = save_file().to_bytes().unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+
+        assert!(matches!(
+            SaveFile::from_bytes(&bytes),
+            Err(SaveFileError::Corrupted { .. })
+        ));
+        assert!(matches!(
+            SaveFile::from_bytes(b"not a save"),
+            Err(SaveFileError::NotASave)
+        ));
+    }
+
+    /// A resource that is stored in saves.
+    #[derive(Resource, Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Counter(u32);
+
+    #[test]
+    fn saved_resources_round_trip() {
+        let mut app = App::new();
+        app.insert_resource(Counter(7))
+            .save_resource::<Counter>("counter");
+
+        let mut save_file = save_file();
+        save_file.world = save_world(&app.world).unwrap();
+        let loaded_save = SaveFile::from_bytes(&save_file.to_bytes().unwrap()).unwrap();
+
+        let mut loaded_app = App::new();
+        loaded_app
+            .init_resource::<Counter>()
+            .save_resource::<Counter>("counter");
+        load_world(&mut loaded_app.world, &loaded_save.world).unwrap();
+        assert_eq!(*loaded_app.world.resource::<Counter>(), Counter(7));
+
+        // Saves from before the resource was registered leave it untouched
+        load_world(&mut loaded_app.world, b"{}").unwrap();
+        assert_eq!(*loaded_app.world.resource::<Counter>(), Counter(7));
+    }
+
+    #[test]
+    fn truncated_saves_fall_back_to_the_backup() {
+        let directory = crate::testing::unique_temp_dir("emergence_truncated_saves_test");
+        let path = directory.join("colony.emsave");
+
+        let old_save = save_file();
+        let mut new_save = save_file();
+        new_save.summary.metadata.elapsed_days = 13.;
+
+        old_save.save(&path).unwrap();
+        new_save.save(&path).unwrap();
+        assert_eq!(SaveFile::load(&path).unwrap(), new_save);
+        assert!(!SaveFile::temporary_path(&path).exists());
+
+        // Simulate a crash that cut the newest save short
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        assert_eq!(SaveFile::load(&path).unwrap(), old_save);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,1661 @@
+//! Signals are used for pathfinding and decision-making.
+//!
+//! By collecting information about the local environment into a slowly updated, tile-centric data structure,
+//! we can scale path-finding and decisionmaking in a clear and comprehensible way.
+//!
+//! Each [`SignalType`] is stored in its own channel, which spreads and fades at the rate set in the [`SignalRegistry`].
+
+use crate as emergence_lib;
+use crate::construction::ghosts::WorkplaceId;
+use crate::crafting::item_tags::ItemKind;
+use crate::factions::{diplomacy::Relationships, Faction, Factions};
+use crate::items::item_manifest::ItemManifest;
+use crate::player_interaction::bulk_commands::{Disabled, Forbidden, Prioritized};
+use crate::structures::structure_manifest::{Structure, StructureManifest};
+use crate::terrain::terrain_manifest::TerrainManifest;
+use crate::units::actions::{DeliveryMode, Purpose};
+use crate::units::movement::MovementMode;
+use crate::units::occupancy::TileOccupancy;
+use crate::units::unit_manifest::{Unit, UnitManifest};
+use crate::water::WaterDepth;
+use bevy::{prelude::*, utils::HashMap};
+use core::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+use emergence_macros::IterableEnum;
+use hexx::Hex;
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+use std::ops::{Div, DivAssign, MulAssign};
+
+use crate::asset_management::manifest::Id;
+use crate::geometry::{Facing, Height, MapGeometry, VoxelPos};
+use crate::simulation::time::TickRate;
+use crate::simulation::weather::Wind;
+use crate::simulation::SimulationSet;
+use crate::units::goals::Goal;
+
+pub mod registry;
+
+pub use registry::{CustomSignal, RegisterSignalChannelExt, SignalParameters, SignalRegistry};
+
+/// The fraction of signals in each cell that will move to each of 6 neighbors each frame.
+///
+/// Higher values will result in more spread out signals.
+///
+/// If no neighbor exists, total diffusion will be reduced correspondingly.
+/// As a result, this value *must* be below 1/6,
+/// and probably should be below 1/7 to avoid weirdness.
+pub const DIFFUSION_FRACTION: f32 = 0.1;
+
+/// How strongly the wind skews the diffusion of signals.
+///
+/// At full wind speed, the tile directly downwind receives `1 + WIND_SIGNAL_BIAS` times its usual share,
+/// while the tile directly upwind receives `1 - WIND_SIGNAL_BIAS` times its usual share.
+/// This must be between 0 and 1.
+pub const WIND_SIGNAL_BIAS: f32 = 0.5;
+
+/// The fraction of signal that will decay at each step, at the default [`TickRate`].
+///
+/// Higher values lead to faster decay and improved signal responsiveness.
+/// This must always be between 0 and 1.
+pub const DEGRADATION_FRACTION: f32 = 0.01;
+
+/// The resources and systems need to work with signals
+pub(crate) struct SignalsPlugin;
+
+impl Plugin for SignalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SignalChannels>()
+            .init_resource::<SignalRegistry>()
+            .add_systems(
+                FixedUpdate,
+                (emit_signals, diffuse_signals, degrade_signals)
+                    .chain()
+                    .in_set(ManageSignals)
+                    .in_set(SimulationSet),
+            );
+    }
+}
+
+/// A public system set for the signals plugin.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ManageSignals;
+
+/// The central resource that tracks all signals, with a separate channel for each [`Faction`].
+///
+/// Units can only sense the signals in their own faction's channel.
+#[derive(Resource, Debug, Default)]
+pub struct SignalChannels {
+    /// The signals for each faction.
+    channels: HashMap<Faction, Signals>,
+    /// An empty set of signals, returned for factions that have not emitted anything yet.
+    empty: Signals,
+}
+
+impl SignalChannels {
+    /// The signals that can be sensed by members of `faction`.
+    pub fn get(&self, faction: Faction) -> &Signals {
+        self.channels.get(&faction).unwrap_or(&self.empty)
+    }
+
+    /// The signals that can be sensed by the player's units.
+    ///
+    /// These are the signals shown to the player.
+    pub fn player(&self) -> &Signals {
+        self.get(Faction::PLAYER)
+    }
+
+    /// A mutable reference to the signals of `faction`, creating the channel if needed.
+    pub fn get_mut(&mut self, faction: Faction) -> &mut Signals {
+        self.channels.entry(faction).or_default()
+    }
+
+    /// Iterates over the signals of every faction that has a channel.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Signals> {
+        self.channels.values_mut()
+    }
+}
+
+/// The signals that can be sensed by the members of a single faction.
+///
+/// The map of each [`SignalType`] is stored densely, in the order that the signal was first emitted,
+/// so that diffusion and decay can iterate over them without hashing.
+#[derive(Debug, Default)]
+pub struct Signals {
+    /// The index in `maps` of each signal type that has been emitted.
+    index: HashMap<SignalType, usize>,
+    /// The spatialized map for each signal.
+    maps: Vec<SignalMap>,
+}
+
+impl Signals {
+    /// The map of `signal_type`, if it has ever been emitted.
+    fn map(&self, signal_type: SignalType) -> Option<&SignalMap> {
+        self.index.get(&signal_type).map(|&index| &self.maps[index])
+    }
+
+    /// Returns the signal strength of `signal_type` at the given `voxel_pos`.
+    ///
+    /// Missing values will be filled with [`SignalStrength::ZERO`].
+    pub fn get(&self, signal_type: SignalType, voxel_pos: VoxelPos) -> SignalStrength {
+        match self.map(signal_type) {
+            Some(map) => map.get(voxel_pos),
+            None => SignalStrength::ZERO,
+        }
+    }
+
+    /// Returns `true` if any of the provided `signal_types` are detectable at the given `voxel_pos`.
+    pub fn detectable(&self, signal_types: Vec<SignalType>, voxel_pos: VoxelPos) -> bool {
+        signal_types
+            .iter()
+            .any(|signal_type| self.get(*signal_type, voxel_pos) > SignalStrength::ZERO)
+    }
+
+    /// Adds `signal_strength` of `signal_type` at `voxel_pos`.
+    pub fn add_signal(
+        &mut self,
+        signal_type: SignalType,
+        voxel_pos: VoxelPos,
+        signal_strength: SignalStrength,
+    ) {
+        let maps = &mut self.maps;
+        let &mut index = self.index.entry(signal_type).or_insert_with(|| {
+            maps.push(SignalMap::new(signal_type));
+            maps.len() - 1
+        });
+
+        self.maps[index].add_signal(voxel_pos, signal_strength);
+    }
+
+    /// Returns the complete set of signals at the given `voxel_pos`.
+    ///
+    /// This is useful for decision-making.
+    pub(crate) fn all_signals_at_position(&self, voxel_pos: VoxelPos) -> LocalSignals {
+        let mut all_signals = HashMap::new();
+        for map in self.maps.iter() {
+            all_signals.insert(map.signal_type, map.get(voxel_pos));
+        }
+
+        LocalSignals { map: all_signals }
+    }
+
+    /// Returns how strongly idle units are drawn towards `voxel_pos`.
+    ///
+    /// Wandering units drift towards tiles where this is higher, as that's where work is likely to be found.
+    /// Goal-relevant signals and [`SignalType::WorkNeeded`] draw units in,
+    /// while [`SignalType::Crowding`] pushes them away, so this can be negative.
+    /// [`SignalType::Unit`] signals are ignored: units flee from them, and should not be drawn to them.
+    ///
+    /// Each signal is scaled by the [`SignalKind::wander_weight`] of its kind.
+    /// Raw signal strengths vary by orders of magnitude, so they are compressed logarithmically first,
+    /// keeping wandering somewhat random even next to strong emitters.
+    pub(crate) fn wander_attraction(&self, voxel_pos: VoxelPos) -> f32 {
+        self.wander_attractions([voxel_pos])[0]
+    }
+
+    /// Returns the [`Signals::wander_attraction`] of each of the `voxels`.
+    ///
+    /// This visits each signal map only once, however many voxels are checked.
+    pub(crate) fn wander_attractions<const N: usize>(&self, voxels: [VoxelPos; N]) -> [f32; N] {
+        let mut attractions = [0.; N];
+
+        let relevant_maps = self
+            .maps
+            .iter()
+            .filter(|map| match Goal::try_from(map.signal_type) {
+                Ok(Goal::Avoid(_)) => false,
+                Ok(_) => true,
+                Err(()) => matches!(
+                    map.signal_type,
+                    SignalType::WorkNeeded | SignalType::Crowding
+                ),
+            });
+
+        for map in relevant_maps {
+            let wander_weight = SignalKind::from(map.signal_type).wander_weight();
+            for (attraction, &voxel_pos) in attractions.iter_mut().zip(voxels.iter()) {
+                *attraction += wander_weight * map.get(voxel_pos).value().ln_1p();
+            }
+        }
+
+        attractions
+    }
+
+    /// Returns the total strength of all signals of each [`SignalKind`], summed across the whole map.
+    pub(crate) fn total_strength_by_kind(&self) -> HashMap<SignalKind, f32> {
+        let mut totals = HashMap::new();
+        for map in self.maps.iter() {
+            let total: f32 = map.current.values().map(|strength| strength.value()).sum();
+            *totals.entry(SignalKind::from(map.signal_type)).or_default() += total;
+        }
+
+        totals
+    }
+
+    /// Returns the strongest goal related signal at the given `voxel_pos`.
+    ///
+    /// This is useful for visualization.
+    pub(crate) fn strongest_goal_signal_at_position(
+        &self,
+        voxel_pos: VoxelPos,
+    ) -> Option<(SignalType, SignalStrength)> {
+        let mut strongest_signal = None;
+        let mut strongest_strength = SignalStrength::ZERO;
+
+        for map in self.maps.iter() {
+            if Goal::try_from(map.signal_type).is_ok() {
+                let strength = map.get(voxel_pos);
+                if strength > strongest_strength {
+                    strongest_signal = Some(map.signal_type);
+                    strongest_strength = strength;
+                }
+            }
+        }
+
+        strongest_signal.map(|signal_type| (signal_type, strongest_strength))
+    }
+
+    /// Returns the adjacent, empty tile position that contains the highest sum signal strength that can be used to meet the provided `goal`.
+    ///
+    /// Neighboring tiles that are already full of units are treated as soft obstacles.
+    ///
+    /// Only tiles that can be reached by moving in the manner of `movement_mode` are considered.
+    ///
+    /// If no suitable tile exists, [`None`] will be returned instead.
+    pub(crate) fn upstream(
+        &self,
+        voxel_pos: VoxelPos,
+        goal: &Goal,
+        movement_mode: MovementMode,
+        item_manifest: &ItemManifest,
+        map_geometry: &MapGeometry,
+        tile_occupancy: &TileOccupancy,
+    ) -> Option<VoxelPos> {
+        let mut best_choice: Option<VoxelPos> = None;
+        let mut best_score = SignalStrength::ZERO;
+
+        for (possible_tile, current_score) in self.relevant_neighboring_signals(
+            voxel_pos,
+            goal,
+            movement_mode,
+            item_manifest,
+            map_geometry,
+        ) {
+            let current_score = if possible_tile == voxel_pos {
+                current_score
+            } else {
+                tile_occupancy.dampen(possible_tile, current_score)
+            };
+
+            if current_score > best_score {
+                best_score = current_score;
+                best_choice = Some(possible_tile);
+            }
+        }
+
+        if let Some(best_tile_pos) = best_choice {
+            if best_tile_pos == voxel_pos {
+                None
+            } else {
+                best_choice
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the adjacent, empty tile position that contains the lowest sum signal strength that can be used to meet the provided `goal`.
+    ///
+    /// Neighboring tiles that are already full of units are treated as soft obstacles.
+    ///
+    /// Only tiles that can be reached by moving in the manner of `movement_mode` are considered.
+    ///
+    /// If no suitable tile exists, [`None`] will be returned instead.
+    pub(crate) fn downstream(
+        &self,
+        voxel_pos: VoxelPos,
+        goal: &Goal,
+        movement_mode: MovementMode,
+        item_manifest: &ItemManifest,
+        map_geometry: &MapGeometry,
+        tile_occupancy: &TileOccupancy,
+    ) -> Option<VoxelPos> {
+        let mut best_choice: Option<VoxelPos> = None;
+        let mut best_score = SignalStrength::INFINITY;
+
+        for (possible_tile, current_score) in self.relevant_neighboring_signals(
+            voxel_pos,
+            goal,
+            movement_mode,
+            item_manifest,
+            map_geometry,
+        ) {
+            let current_score = if possible_tile == voxel_pos {
+                current_score
+            } else {
+                tile_occupancy.amplify(possible_tile, current_score)
+            };
+
+            if current_score < best_score {
+                best_score = current_score;
+                best_choice = Some(possible_tile);
+            }
+        }
+
+        if let Some(best_tile_pos) = best_choice {
+            if best_tile_pos == voxel_pos {
+                None
+            } else {
+                best_choice
+            }
+        } else {
+            None
+        }
+    }
+
+    /// The signal types that should be followed to meet the provided `goal`.
+    ///
+    /// This is empty for goals that do not follow signals.
+    fn relevant_signal_types(goal: &Goal, item_manifest: &ItemManifest) -> Vec<SignalType> {
+        match goal {
+            // Does not follow any signal
+            Goal::Wander { .. } => Vec::new(),
+            // Follows gradient of water depth instead of signal
+            Goal::Breathe => Vec::new(),
+            Goal::Fetch(item_kind)
+            | Goal::Eat(item_kind)
+            | Goal::Store(item_kind)
+            | Goal::Deliver(item_kind)
+            | Goal::Remove(item_kind) => SignalType::item_signal_types(
+                *item_kind,
+                item_manifest,
+                goal.delivery_mode().unwrap(),
+                goal.purpose(),
+            ),
+            Goal::Work(structure_id) => vec![SignalType::Work(*structure_id)],
+            Goal::Avoid(unit_id) => vec![SignalType::Unit(*unit_id)],
+            Goal::Demolish(structure_id) => vec![SignalType::Demolish(*structure_id)],
+            Goal::Rest => vec![SignalType::Shelter],
+        }
+    }
+
+    /// Returns the total strength of goal-relevant signals in `voxel_pos` and each of the neighbors reachable using `movement_mode`.
+    ///
+    /// This is computed lazily, as it is called for every unit that is following a signal.
+    fn relevant_neighboring_signals<'a>(
+        &'a self,
+        voxel_pos: VoxelPos,
+        goal: &Goal,
+        movement_mode: MovementMode,
+        item_manifest: &ItemManifest,
+        map_geometry: &'a MapGeometry,
+    ) -> impl Iterator<Item = (VoxelPos, SignalStrength)> + 'a {
+        let signal_types = Self::relevant_signal_types(goal, item_manifest);
+
+        // Goals that do not follow signals have no relevant neighbors at all
+        let neighborhood = (!signal_types.is_empty())
+            .then(|| neighborhood(voxel_pos, movement_mode, map_geometry))
+            .into_iter()
+            .flatten();
+
+        neighborhood.map(move |neighbor| {
+            let total_strength = signal_types
+                .iter()
+                .fold(SignalStrength::ZERO, |total, &signal_type| {
+                    total + self.get(signal_type, neighbor)
+                });
+
+            (neighbor, total_strength)
+        })
+    }
+
+    /// Diffuses signals from one cell into the next, skewed in the direction of the `wind`.
+    ///
+    /// The fraction of each signal that diffuses is given by `diffusion_fraction`,
+    /// so that each channel can spread at its own rate.
+    pub fn diffuse(
+        &mut self,
+        map_geometry: &MapGeometry,
+        diffusion_fraction: impl Fn(SignalType) -> f32 + Sync,
+        wind: &Wind,
+    ) {
+        // The bias for each of the six neighboring directions sums to zero,
+        // so the wind redistributes signal without creating or destroying any.
+        let wind_weights: [(Hex, f32); 6] = Hex::ZERO.all_neighbors().map(|offset| {
+            (
+                offset,
+                1. + WIND_SIGNAL_BIAS * wind.alignment(Hex::ZERO, offset),
+            )
+        });
+
+        self.maps.par_iter_mut().for_each(|signal_map| {
+            let diffusion_fraction = diffusion_fraction(signal_map.signal_type);
+            assert!((0.0..=1.0 / 6.0).contains(&diffusion_fraction));
+
+            for (&occupied_tile, original_strength) in signal_map
+                .current
+                .iter()
+                .filter(|(_, &strength)| strength != SignalStrength::ZERO)
+            {
+                let amount_to_send_to_each_neighbor = *original_strength * diffusion_fraction;
+
+                for neighbor in map_geometry.walkable_neighbors(occupied_tile) {
+                    let offset = neighbor.hex - occupied_tile.hex;
+                    let wind_weight = wind_weights
+                        .iter()
+                        .find(|(wind_offset, _)| *wind_offset == offset)
+                        .map_or(1., |(_, weight)| *weight);
+
+                    signal_map
+                        .pending_addition
+                        .push((neighbor, amount_to_send_to_each_neighbor * wind_weight));
+                }
+                signal_map.pending_removal.push((
+                    occupied_tile,
+                    // Signal that goes out of bounds or into an impassable tile is lost
+                    // This is both a simplification and a performance optimization
+                    // But it also has a gameplay effect: it makes circuitous routes less efficient
+                    amount_to_send_to_each_neighbor * 6.0,
+                ));
+            }
+
+            // We cannot do this in one step, as we need to avoid bizarre iteration order dependencies
+            signal_map.apply_pending_removals();
+            signal_map.apply_pending_additions();
+        });
+    }
+
+    /// Degrades signals, allowing them to approach an asymptotically constant level.
+    ///
+    /// The fraction of each signal that decays is given by `degradation_fraction`,
+    /// so that each channel can fade at its own rate.
+    pub fn degrade(&mut self, degradation_fraction: impl Fn(SignalType) -> f32 + Sync) {
+        /// The value below which decayed signals are eliminated completely
+        ///
+        /// Increasing this value will:
+        ///  - increase computational costs
+        ///  - increase the range at which tasks can be detected
+        ///  - increase the amount of time units will wait around for more production
+        const EPSILON_STRENGTH: SignalStrength = SignalStrength(1e-8);
+
+        self.maps.par_iter_mut().for_each(|signal_map| {
+            let degradation_fraction = degradation_fraction(signal_map.signal_type);
+            let mut tiles_to_clear: Vec<VoxelPos> = Vec::with_capacity(signal_map.current.len());
+
+            for (voxel_pos, signal_strength) in signal_map.current.iter_mut() {
+                let new_strength = *signal_strength * (1. - degradation_fraction);
+
+                if new_strength > EPSILON_STRENGTH {
+                    *signal_strength = new_strength;
+                } else {
+                    tiles_to_clear.push(*voxel_pos);
+                }
+            }
+
+            for tile_to_clear in tiles_to_clear {
+                signal_map.current.remove(&tile_to_clear);
+            }
+        });
+    }
+
+    /// Returns a random signal type present in the map.
+    pub(crate) fn random_signal_type(&self) -> Option<SignalType> {
+        let mut rng = rand::thread_rng();
+        self.maps.choose(&mut rng).map(|map| map.signal_type)
+    }
+}
+
+/// Iterates over `voxel_pos` itself, followed by each of the neighbors reachable using `movement_mode`.
+fn neighborhood(
+    voxel_pos: VoxelPos,
+    movement_mode: MovementMode,
+    map_geometry: &MapGeometry,
+) -> impl Iterator<Item = VoxelPos> + '_ {
+    std::iter::once(voxel_pos).chain(movement_mode.neighbors(voxel_pos, map_geometry))
+}
+
+/// All of the signals on a single tile.
+#[derive(Debug)]
+pub(crate) struct LocalSignals {
+    /// Internal data storage
+    map: HashMap<SignalType, SignalStrength>,
+}
+
+impl LocalSignals {
+    /// Returns the set of signals that might be used to pick a goal
+    pub(crate) fn goal_relevant_signals(&self) -> Vec<(&SignalType, &SignalStrength)> {
+        self.map
+            .iter()
+            .filter(|(signal_type, _signal_strength)| Goal::try_from(**signal_type).is_ok())
+            .collect()
+    }
+
+    /// The pretty formatting for this type.
+    pub(crate) fn display(
+        &self,
+        item_manifest: &ItemManifest,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+        unit_manifest: &UnitManifest,
+    ) -> String {
+        let mut string = String::default();
+
+        for signal_type in self.map.keys().sorted() {
+            let signal_strength = self.map.get(signal_type).unwrap().0;
+
+            let substring = format!(
+                "{}: {signal_strength:.3}\n",
+                signal_type.display(
+                    item_manifest,
+                    structure_manifest,
+                    terrain_manifest,
+                    unit_manifest
+                )
+            );
+
+            string += &substring;
+        }
+
+        string
+    }
+}
+
+/// Stores the [`SignalStrength`] of the given [`SignalType`] at each [`VoxelPos`].
+#[derive(Debug)]
+struct SignalMap {
+    /// The type of signal stored in this map.
+    signal_type: SignalType,
+    /// The current amount of signal at each location.
+    current: HashMap<VoxelPos, SignalStrength>,
+    /// The amount of signal that will be added to each location at the end of the frame.
+    pending_addition: Vec<(VoxelPos, SignalStrength)>,
+    /// The amount of signal that will be removed from each location at the end of the frame.
+    pending_removal: Vec<(VoxelPos, SignalStrength)>,
+}
+
+impl SignalMap {
+    /// Creates an empty map for `signal_type`.
+    fn new(signal_type: SignalType) -> Self {
+        SignalMap {
+            signal_type,
+            current: HashMap::default(),
+            pending_addition: Vec::new(),
+            pending_removal: Vec::new(),
+        }
+    }
+
+    /// Returns the signal strength at the given [`VoxelPos`].
+    ///
+    /// Missing values will be filled with [`SignalStrength::ZERO`].
+    fn get(&self, voxel_pos: VoxelPos) -> SignalStrength {
+        *self
+            .current
+            .get(&voxel_pos)
+            .unwrap_or(&SignalStrength::ZERO)
+    }
+
+    /// Returns a mutable reference to the signal strength at the given [`VoxelPos`].
+    ///
+    /// Missing values will be inserted with [`SignalStrength::ZERO`].
+    fn get_mut(&mut self, voxel_pos: VoxelPos) -> &mut SignalStrength {
+        self.current
+            .entry(voxel_pos)
+            .or_insert(SignalStrength::ZERO)
+    }
+
+    /// Adds the `signal_strength` to the signal at `voxel_pos`.
+    fn add_signal(&mut self, voxel_pos: VoxelPos, signal_strength: SignalStrength) {
+        *self.get_mut(voxel_pos) += signal_strength
+    }
+
+    /// Applies all pending additions to the current signal map.
+    ///
+    /// This clears the pending addition map.
+    fn apply_pending_additions(&mut self) {
+        for (voxel_pos, signal_strength) in self.pending_addition.drain(..) {
+            self.current
+                .entry(voxel_pos)
+                .and_modify(|current_strength| {
+                    *current_strength += signal_strength;
+                })
+                .or_insert(signal_strength);
+        }
+    }
+
+    /// Applies all pending removals to the current signal map.
+    ///
+    /// This clears the pending removal map.
+    fn apply_pending_removals(&mut self) {
+        for (voxel_pos, signal_strength) in self.pending_removal.drain(..) {
+            // We deliberately do not insert a zero or negative signal strength here if the entry is missing
+            // That would either be useless or a bug respectively.
+            self.current
+                .entry(voxel_pos)
+                .and_modify(|current_strength| {
+                    *current_strength -= signal_strength;
+                });
+        }
+    }
+}
+
+/// The variety of signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SignalType {
+    /// Take this item away from here.
+    Push(ItemKind),
+    /// Bring me an item of this type.
+    Pull(ItemKind),
+    /// Perform work at this type of structure.
+    Work(WorkplaceId),
+    /// Destroy a structure of this type
+    Demolish(Id<Structure>),
+    /// Has an item of this type, in case you were looking.
+    ///
+    /// The passive form of `Push`.
+    Contains(ItemKind),
+    /// Stores items of this type, in case you were looking.
+    ///
+    /// The passive form of `Pull`.
+    Stores(ItemKind),
+    /// Has a unit of this type.
+    Unit(Id<Unit>),
+    /// Has room for more units to rest here.
+    Shelter,
+    /// Some kind of work needs doing here.
+    ///
+    /// Idle units drift towards this, without committing to a specific goal.
+    WorkNeeded,
+    /// Units are gathered here.
+    ///
+    /// Unlike other signals, this repels idle units, so they spread out instead of clumping together.
+    Crowding,
+    /// A channel added by a mod or new content, registered with the [`SignalRegistry`].
+    Custom(Id<CustomSignal>),
+}
+
+impl SignalType {
+    /// The custom signal channel with the given `name`.
+    ///
+    /// Custom channels should be registered with [`SignalRegistry::register_custom`] before they are emitted.
+    pub fn custom(name: &str) -> Self {
+        SignalType::Custom(Id::from_name(name.to_string()))
+    }
+
+    /// Returns a list of all signals that are relevant to the provided [`ItemKind`].
+    ///
+    /// If `delivery_mode` is [`DeliveryMode::PickUp`], this will return [`SignalType::Push`] and [`SignalType::Contains`].
+    /// If `delivery_mode` is [`DeliveryMode::DropOff`], this will return [`SignalType::Pull`] and [`SignalType::Stores`].
+    /// If `purpose` is [`Purpose::Intrinsic`], [`SignalType::Stores`] and [`SignalType::Contains`] are excluded.
+    pub(crate) fn item_signal_types(
+        item_kind: ItemKind,
+        item_manifest: &ItemManifest,
+        delivery_mode: DeliveryMode,
+        purpose: Purpose,
+    ) -> Vec<SignalType> {
+        let mut signal_types = Vec::new();
+        let kinds = match item_kind {
+            ItemKind::Single(item_id) => item_manifest.kinds(item_id),
+            ItemKind::Tag(tag) => item_manifest.kinds_with_tag(tag),
+        };
+
+        for item_kind in kinds {
+            match (delivery_mode, purpose) {
+                (DeliveryMode::PickUp, Purpose::Intrinsic) => {
+                    signal_types.push(SignalType::Push(item_kind));
+                }
+                (DeliveryMode::PickUp, Purpose::Instrumental) => {
+                    signal_types.push(SignalType::Push(item_kind));
+                    signal_types.push(SignalType::Contains(item_kind));
+                }
+                (DeliveryMode::DropOff, Purpose::Intrinsic) => {
+                    signal_types.push(SignalType::Pull(item_kind));
+                }
+                (DeliveryMode::DropOff, Purpose::Instrumental) => {
+                    signal_types.push(SignalType::Pull(item_kind));
+                    signal_types.push(SignalType::Stores(item_kind));
+                }
+            }
+        }
+        signal_types
+    }
+
+    /// The pretty formatting for this type
+    pub(crate) fn display(
+        &self,
+        item_manifest: &ItemManifest,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+        unit_manifest: &UnitManifest,
+    ) -> String {
+        match self {
+            SignalType::Push(item_kind) => {
+                format!("Push({})", item_manifest.name_of_kind(*item_kind))
+            }
+            SignalType::Pull(item_kind) => {
+                format!("Pull({})", item_manifest.name_of_kind(*item_kind))
+            }
+            SignalType::Work(workplace_id) => {
+                format!(
+                    "Work({})",
+                    workplace_id.name(structure_manifest, terrain_manifest)
+                )
+            }
+            SignalType::Demolish(structure_id) => {
+                format!("Demolish({})", structure_manifest.name(*structure_id))
+            }
+            SignalType::Contains(item_kind) => {
+                format!("Contains({})", item_manifest.name_of_kind(*item_kind))
+            }
+            SignalType::Stores(item_kind) => {
+                format!("Stores({})", item_manifest.name_of_kind(*item_kind))
+            }
+            SignalType::Unit(unit_id) => format!("Unit({})", unit_manifest.name(*unit_id)),
+            SignalType::Shelter => "Shelter".to_string(),
+            SignalType::WorkNeeded => "Work needed".to_string(),
+            SignalType::Crowding => "Crowding".to_string(),
+            SignalType::Custom(custom_id) => format!("Custom({custom_id:?})"),
+        }
+    }
+}
+
+/// The data-less equivalent of [`SignalType`].
+///
+/// This has an infallible conversion from [`SignalType`] using the [`From`] trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IterableEnum)]
+pub enum SignalKind {
+    /// Take this item away from here.
+    Push,
+    /// Bring me an item of this type.
+    Pull,
+    /// Perform work at this type of structure.
+    Work,
+    /// Destroy a structure of this type
+    Demolish,
+    /// Has an item of this type, in case you were looking.
+    ///
+    /// The passive form of `Push`.
+    Contains,
+    /// Stores items of this type, in case you were looking.
+    ///
+    /// The passive form of `Pull`.
+    Stores,
+    /// Has a unit of this type.
+    Unit,
+    /// Has room for more units to rest here.
+    Shelter,
+    /// Some kind of work needs doing here.
+    WorkNeeded,
+    /// Units are gathered here.
+    Crowding,
+    /// A channel added by a mod or new content.
+    Custom,
+}
+
+impl SignalKind {
+    /// How strongly this kind of signal draws in idle units, per unit of signal strength.
+    ///
+    /// Negative weights push units away instead.
+    pub(crate) const fn wander_weight(&self) -> f32 {
+        match self {
+            SignalKind::Crowding => -1.,
+            _ => 1.,
+        }
+    }
+
+    /// Is this signal used to coordinate the hauling of items?
+    pub(crate) fn is_hauling(&self) -> bool {
+        matches!(
+            self,
+            SignalKind::Push | SignalKind::Pull | SignalKind::Contains | SignalKind::Stores
+        )
+    }
+
+    /// Is this signal used to draw units to its emitter, to haul items, work or rest there?
+    ///
+    /// [`Forbidden`] emitters do not emit these signals, as units will not use them.
+    pub(crate) fn is_invitation(&self) -> bool {
+        self.is_hauling()
+            || matches!(
+                self,
+                SignalKind::Work | SignalKind::WorkNeeded | SignalKind::Shelter
+            )
+    }
+}
+
+impl From<SignalType> for SignalKind {
+    fn from(signal_type: SignalType) -> Self {
+        match signal_type {
+            SignalType::Push(_) => SignalKind::Push,
+            SignalType::Pull(_) => SignalKind::Pull,
+            SignalType::Work(_) => SignalKind::Work,
+            SignalType::Demolish(_) => SignalKind::Demolish,
+            SignalType::Contains(_) => SignalKind::Contains,
+            SignalType::Stores(_) => SignalKind::Stores,
+            SignalType::Unit(_) => SignalKind::Unit,
+            SignalType::Shelter => SignalKind::Shelter,
+            SignalType::WorkNeeded => SignalKind::WorkNeeded,
+            SignalType::Crowding => SignalKind::Crowding,
+            SignalType::Custom(_) => SignalKind::Custom,
+        }
+    }
+}
+
+/// How strong a signal is.
+///
+/// This has a minimum value of 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct SignalStrength(f32);
+
+impl SignalStrength {
+    /// No signal is present.
+    pub const ZERO: SignalStrength = SignalStrength(0.);
+
+    /// An infinitely strong signal.
+    pub const INFINITY: SignalStrength = SignalStrength(f32::INFINITY);
+
+    /// Creates a new [`SignalStrength`], ensuring that it has a minimum value of 0.
+    pub fn new(value: f32) -> Self {
+        SignalStrength(value.max(0.))
+    }
+
+    /// The underlying value
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+}
+
+impl Add<SignalStrength> for SignalStrength {
+    type Output = SignalStrength;
+
+    fn add(self, rhs: SignalStrength) -> Self::Output {
+        SignalStrength(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign<SignalStrength> for SignalStrength {
+    fn add_assign(&mut self, rhs: SignalStrength) {
+        *self = *self + rhs
+    }
+}
+
+impl Sub<SignalStrength> for SignalStrength {
+    type Output = SignalStrength;
+
+    fn sub(self, rhs: SignalStrength) -> Self::Output {
+        SignalStrength((self.0 - rhs.0).max(0.))
+    }
+}
+
+impl SubAssign<SignalStrength> for SignalStrength {
+    fn sub_assign(&mut self, rhs: SignalStrength) {
+        *self = *self - rhs
+    }
+}
+
+impl Mul<f32> for SignalStrength {
+    type Output = SignalStrength;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        SignalStrength(self.0 * rhs)
+    }
+}
+
+impl MulAssign<f32> for SignalStrength {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs
+    }
+}
+
+impl Div<f32> for SignalStrength {
+    type Output = SignalStrength;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        SignalStrength(self.0 / rhs)
+    }
+}
+
+impl DivAssign<f32> for SignalStrength {
+    fn div_assign(&mut self, rhs: f32) {
+        *self = *self / rhs
+    }
+}
+
+/// The component that causes a game object to emit a signal.
+///
+/// This can change over time, and multiple signals may be emitted at once.
+#[derive(Default, Component, Debug, Clone)]
+pub(crate) struct Emitter {
+    /// The list of signals to emit at a provided
+    pub(crate) signals: Vec<(SignalType, SignalStrength)>,
+}
+
+impl Emitter {
+    /// The human-readable value of this component.
+    pub(crate) fn display(
+        &self,
+        item_manifest: &ItemManifest,
+        unit_manifest: &UnitManifest,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+    ) -> String {
+        self.signals
+            .iter()
+            .map(|(signal_type, signal_strength)| {
+                format!(
+                    "{}: {}",
+                    signal_type.display(
+                        item_manifest,
+                        structure_manifest,
+                        terrain_manifest,
+                        unit_manifest,
+                    ),
+                    signal_strength.value()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Emits signals from [`Emitter`] sources.
+///
+/// Emitters owned by a [`Faction`] only emit into that faction's channel,
+/// while wild emitters are sensed by every faction.
+///
+/// Emission is scaled by the [`TickRate`], so that the same amount of signal is emitted each second at any rate.
+fn emit_signals(
+    mut signal_channels: ResMut<SignalChannels>,
+    emitter_query: Query<(
+        &VoxelPos,
+        &Emitter,
+        Option<&Id<Structure>>,
+        Option<&Facing>,
+        Option<&Faction>,
+        Has<Forbidden>,
+        Has<Disabled>,
+        Has<Prioritized>,
+    )>,
+    factions: Res<Factions>,
+    relationships: Res<Relationships>,
+    structure_manifest: Res<StructureManifest>,
+    terrain_query: Query<&WaterDepth>,
+    map_geometry: Res<MapGeometry>,
+    tick_rate: Res<TickRate>,
+) {
+    /// Emits signals that correspond to a single [`Emitter`].
+    ///
+    /// Forbidden emitters do not emit any signals that invite units to haul, work or rest there,
+    /// disabled emitters do not ask for items to be delivered,
+    /// and prioritized emitters have all of their signals amplified.
+    fn emit(
+        signals: &mut Signals,
+        voxel_pos: VoxelPos,
+        emitter: &Emitter,
+        n_tiles: usize,
+        forbidden: bool,
+        disabled: bool,
+        prioritized: bool,
+        tick_rate: &TickRate,
+        sensitivity: impl Fn(SignalKind) -> f32,
+    ) {
+        let multiplier = tick_rate.per_tick_amount(match prioritized {
+            true => Prioritized::SIGNAL_MULTIPLIER,
+            false => 1.,
+        });
+
+        for (signal_type, signal_strength) in &emitter.signals {
+            let signal_kind = SignalKind::from(*signal_type);
+            if forbidden && signal_kind.is_invitation() {
+                continue;
+            }
+
+            if disabled && signal_kind == SignalKind::Pull {
+                continue;
+            }
+
+            let sensitivity = sensitivity(signal_kind);
+            if sensitivity <= 0. {
+                continue;
+            }
+
+            let signal_strength = *signal_strength * multiplier * sensitivity / n_tiles as f32;
+            signals.add_signal(*signal_type, voxel_pos, signal_strength);
+        }
+    }
+
+    for (
+        &center,
+        emitter,
+        maybe_structure_id,
+        maybe_facing,
+        maybe_faction,
+        forbidden,
+        disabled,
+        prioritized,
+    ) in emitter_query.iter()
+    {
+        // When the water is too deep, disable the flooded buildings to avoid drowning units constantly
+        if let Some(structure_id) = maybe_structure_id {
+            let structure_data = structure_manifest.get(*structure_id);
+            let terrain_entity = map_geometry.get_terrain(center.hex).unwrap();
+            let water_depth = terrain_query.get(terrain_entity).unwrap();
+            let structure_height = structure_data.footprint.max_height();
+
+            if Height::from(structure_height) < water_depth.surface_water_depth() {
+                continue;
+            }
+        }
+
+        // Owned signals are also sensed by any faction with a relationship to the owner
+        let recipients: Vec<Faction> =
+            match maybe_faction {
+                Some(&owner) => std::iter::once(owner)
+                    .chain(factions.iter().filter(|&faction| {
+                        faction != owner && relationships.can_sense(faction, owner)
+                    }))
+                    .collect(),
+                None => factions.iter().collect(),
+            };
+
+        for faction in recipients {
+            let sensitivity = |signal_kind: SignalKind| match maybe_faction {
+                Some(&owner) => relationships.sensitivity(faction, owner, signal_kind),
+                None => 1.,
+            };
+            let signals = signal_channels.get_mut(faction);
+
+            match maybe_structure_id {
+                // Signals should be emitted from all tiles in the footprint of a structure.
+                Some(structure_id) => {
+                    let facing = *maybe_facing.expect("Structures must have a facing");
+                    let footprint = &structure_manifest.get(*structure_id).footprint;
+
+                    let n_tiles = footprint.set.len();
+
+                    for voxel_pos in footprint.normalized(facing, center) {
+                        emit(
+                            signals,
+                            voxel_pos,
+                            emitter,
+                            n_tiles,
+                            forbidden,
+                            disabled,
+                            prioritized,
+                            &tick_rate,
+                            &sensitivity,
+                        );
+                    }
+                }
+                None => {
+                    emit(
+                        signals,
+                        center,
+                        emitter,
+                        1,
+                        forbidden,
+                        disabled,
+                        prioritized,
+                        &tick_rate,
+                        &sensitivity,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Spreads signals between tiles, at the rate registered for each channel.
+fn diffuse_signals(
+    mut signal_channels: ResMut<SignalChannels>,
+    signal_registry: Res<SignalRegistry>,
+    map_geometry: Res<MapGeometry>,
+    wind: Res<Wind>,
+    tick_rate: Res<TickRate>,
+) {
+    let diffusion_fraction = |signal_type: SignalType| {
+        tick_rate.per_tick_fraction(signal_registry.parameters(signal_type).diffusion_fraction)
+    };
+
+    for signals in signal_channels.iter_mut() {
+        signals.diffuse(&map_geometry, &diffusion_fraction, &wind);
+    }
+}
+
+/// Degrades signals, at the rate registered for each channel.
+fn degrade_signals(
+    mut signal_channels: ResMut<SignalChannels>,
+    signal_registry: Res<SignalRegistry>,
+    tick_rate: Res<TickRate>,
+) {
+    let degradation_fraction = |signal_type: SignalType| {
+        tick_rate.per_tick_fraction(signal_registry.parameters(signal_type).degradation_fraction)
+    };
+
+    for signals in signal_channels.iter_mut() {
+        signals.degrade(&degradation_fraction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::items::item_manifest::ItemData;
+
+    use super::*;
+
+    fn test_item() -> ItemKind {
+        ItemKind::Single(Id::from_name("12345".to_string()))
+    }
+
+    fn test_structure() -> Id<Structure> {
+        Id::from_name("67890".to_string())
+    }
+
+    fn test_manifest() -> ItemManifest {
+        let mut manifest = ItemManifest::new();
+        manifest.insert(
+            "12345".to_string(),
+            ItemData {
+                stack_size: 1,
+                compostable: false,
+                fluid: false,
+                buoyant: true,
+                seed: None,
+                corpse: false,
+            },
+        );
+        manifest
+    }
+
+    #[test]
+    fn pending_additions_are_applied() {
+        let mut signal_map = SignalMap::new(SignalType::Contains(test_item()));
+        signal_map
+            .pending_addition
+            .push((VoxelPos::ZERO.above(), SignalStrength(1.)));
+
+        assert_eq!(signal_map.get(VoxelPos::ZERO.above()), SignalStrength::ZERO);
+        signal_map.apply_pending_additions();
+        assert_eq!(signal_map.get(VoxelPos::ZERO.above()), SignalStrength(1.));
+    }
+
+    #[test]
+    fn signals_diffuse() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+
+        signals.add_signal(
+            SignalType::Contains(test_item()),
+            VoxelPos::ZERO.above(),
+            SignalStrength(1.),
+        );
+
+        assert_eq!(
+            signals.get(SignalType::Contains(test_item()), VoxelPos::ZERO.above()),
+            SignalStrength(1.)
+        );
+
+        signals.diffuse(&map_geometry, |_| 0.1, &Wind::CALM);
+
+        assert_eq!(signals.maps.len(), 1);
+        let signal_map = signals.maps.first().unwrap();
+        dbg!(&signal_map);
+
+        let current_signals = signal_map.current.clone();
+
+        assert_eq!(
+            current_signals.len(),
+            7,
+            "Signal should have diffused to all 7 neigboring tiles"
+        );
+        for (_, &signal_strength) in current_signals.iter() {
+            assert!(signal_strength > SignalStrength::ZERO);
+        }
+    }
+
+    #[test]
+    fn channels_diffuse_at_their_own_rate() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let mut registry = SignalRegistry::default();
+
+        let origin = VoxelPos::ZERO.above();
+        let neighbor = origin.neighbor(hexx::Direction::Top);
+        let still = registry.register_custom("still", SignalParameters::new(0., 0.));
+        let spreading = SignalType::Contains(test_item());
+
+        signals.add_signal(still, origin, SignalStrength(1.));
+        signals.add_signal(spreading, origin, SignalStrength(1.));
+        signals.diffuse(
+            &map_geometry,
+            |signal_type| registry.parameters(signal_type).diffusion_fraction,
+            &Wind::CALM,
+        );
+        signals.degrade(|signal_type| registry.parameters(signal_type).degradation_fraction);
+
+        assert_eq!(signals.get(still, origin), SignalStrength(1.));
+        assert_eq!(signals.get(still, neighbor), SignalStrength::ZERO);
+        assert!(signals.get(spreading, neighbor) > SignalStrength::ZERO);
+    }
+
+    #[test]
+    fn crowding_repels_wandering_units() {
+        let mut signals = Signals::default();
+        let voxel_pos = VoxelPos::ZERO;
+        let work = SignalType::Work(WorkplaceId::Structure(test_structure()));
+
+        signals.add_signal(work, voxel_pos, SignalStrength::new(1.));
+        signals.add_signal(SignalType::WorkNeeded, voxel_pos, SignalStrength::new(2.));
+        let uncrowded = signals.wander_attraction(voxel_pos);
+        assert!(uncrowded > 0.);
+
+        signals.add_signal(SignalType::Crowding, voxel_pos, SignalStrength::new(10.));
+        let crowded = signals.wander_attraction(voxel_pos);
+        assert!(crowded < 0.);
+
+        // Signals that units don't act on are ignored
+        signals.add_signal(SignalType::Shelter, voxel_pos, SignalStrength::new(10.));
+        assert_eq!(signals.wander_attraction(voxel_pos), crowded);
+    }
+
+    #[test]
+    fn strong_signals_do_not_swamp_wandering() {
+        let mut signals = Signals::default();
+        let near = VoxelPos::ZERO;
+        let far = near.neighbor(hexx::Direction::Top);
+        let work = SignalType::Work(WorkplaceId::Structure(test_structure()));
+
+        signals.add_signal(work, near, SignalStrength::new(100.));
+        signals.add_signal(work, far, SignalStrength::new(10.));
+
+        let [near_attraction, far_attraction] = signals.wander_attractions([near, far]);
+        assert!(near_attraction > far_attraction);
+        assert!(near_attraction < 5.);
+        assert_eq!(near_attraction, signals.wander_attraction(near));
+    }
+
+    #[test]
+    fn only_item_signals_are_hauling_signals() {
+        assert!(SignalKind::from(SignalType::Push(test_item())).is_hauling());
+        assert!(SignalKind::from(SignalType::Stores(test_item())).is_hauling());
+        assert!(!SignalKind::from(SignalType::Demolish(test_structure())).is_hauling());
+    }
+
+    #[test]
+    fn forbidden_emitters_still_ask_for_demolition() {
+        assert!(SignalKind::from(SignalType::Pull(test_item())).is_invitation());
+        assert!(SignalKind::Shelter.is_invitation());
+        assert!(SignalKind::Work.is_invitation());
+        assert!(!SignalKind::from(SignalType::Demolish(test_structure())).is_invitation());
+    }
+
+    #[test]
+    fn wind_biases_signal_diffusion() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let wind = Wind::new(0., 1.);
+
+        let origin = VoxelPos::ZERO.above();
+        let downwind = origin.neighbor(wind.hex_direction());
+        let upwind = VoxelPos {
+            hex: origin.hex - (downwind.hex - origin.hex),
+            height: origin.height,
+        };
+        let signal_type = SignalType::Contains(test_item());
+
+        signals.add_signal(signal_type, origin, SignalStrength(1.));
+        signals.diffuse(&map_geometry, |_| 0.1, &wind);
+
+        assert!(signals.get(signal_type, downwind) > signals.get(signal_type, upwind));
+    }
+
+    #[test]
+    fn neighboring_signals_checks_origin_tile() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+
+        signals.add_signal(
+            SignalType::Contains(test_item()),
+            VoxelPos::ZERO.above(),
+            SignalStrength(1.),
+        );
+
+        let neighboring_signals: HashMap<VoxelPos, SignalStrength> =
+            neighborhood(VoxelPos::ZERO.above(), MovementMode::Walking, &map_geometry)
+                .map(|voxel_pos| {
+                    let strength = signals.get(SignalType::Contains(test_item()), voxel_pos);
+                    (voxel_pos, strength)
+                })
+                .collect();
+
+        assert_eq!(neighboring_signals.len(), 7);
+
+        assert_eq!(
+            neighboring_signals.get(&VoxelPos::ZERO.above()),
+            Some(&SignalStrength(1.))
+        );
+    }
+
+    #[test]
+    fn steady_state_signals_do_not_depend_on_tick_rate() {
+        let steady_state = |tick_rate: TickRate| {
+            let mut signals = Signals::default();
+            let signal_type = SignalType::Push(test_item());
+            let strength = SignalStrength::new(tick_rate.per_tick_amount(1.));
+            let degradation_fraction = tick_rate.per_tick_fraction(DEGRADATION_FRACTION);
+
+            // A minute is plenty of time for the signal to settle
+            let n_ticks = tick_rate.ticks_per_second() as usize * 60;
+            for _ in 0..n_ticks {
+                signals.add_signal(signal_type, VoxelPos::ZERO, strength);
+                signals.degrade(|_| degradation_fraction);
+            }
+
+            signals.get(signal_type, VoxelPos::ZERO).value()
+        };
+
+        let slow = steady_state(TickRate::new(15.));
+        let fast = steady_state(TickRate::new(60.));
+        assert!(
+            (slow - fast).abs() / slow < 0.02,
+            "Steady state was {slow} at 15 ticks/s but {fast} at 60 ticks/s"
+        );
+    }
+
+    #[test]
+    fn factions_have_separate_signal_channels() {
+        let mut signal_channels = SignalChannels::default();
+        let rival = Faction(1);
+
+        signal_channels.get_mut(rival).add_signal(
+            SignalType::Contains(test_item()),
+            VoxelPos::ZERO,
+            SignalStrength(1.),
+        );
+
+        assert_eq!(
+            signal_channels
+                .get(rival)
+                .get(SignalType::Contains(test_item()), VoxelPos::ZERO),
+            SignalStrength(1.)
+        );
+        assert_eq!(
+            signal_channels
+                .player()
+                .get(SignalType::Contains(test_item()), VoxelPos::ZERO),
+            SignalStrength::ZERO
+        );
+    }
+
+    #[test]
+    fn upstream_returns_none_with_no_signals() {
+        let signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 10);
+        let item_manifest = test_manifest();
+
+        assert_eq!(
+            signals.upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Store(test_item()),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            ),
+            None
+        );
+        assert_eq!(
+            signals.upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Fetch(test_item()),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            ),
+            None
+        );
+        assert_eq!(
+            signals.upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Work(WorkplaceId::structure(test_structure())),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            ),
+            None
+        );
+        assert_eq!(
+            signals.upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::default(),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn upstream_returns_none_at_trivial_peak() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 10);
+        let item_manifest = test_manifest();
+
+        signals.add_signal(
+            SignalType::Pull(test_item()),
+            VoxelPos::ZERO.above(),
+            SignalStrength(1.),
+        );
+
+        assert_eq!(
+            signals.upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Store(test_item()),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn upstream_returns_none_at_peak() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 10);
+        let item_manifest = test_manifest();
+
+        signals.add_signal(
+            SignalType::Push(test_item()),
+            VoxelPos::ZERO.above(),
+            SignalStrength(1.),
+        );
+
+        for neighbor in map_geometry.walkable_neighbors(VoxelPos::ZERO.above()) {
+            signals.add_signal(SignalType::Push(test_item()), neighbor, SignalStrength(0.5));
+        }
+
+        assert_eq!(
+            signals.upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Fetch(test_item()),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    // The logic for Goal::DropOff is significantly more complex and worth testing separately
+    fn upstream_returns_none_at_peak_dropoff() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let item_manifest = test_manifest();
+
+        signals.add_signal(
+            SignalType::Pull(test_item()),
+            VoxelPos::ZERO.above(),
+            SignalStrength(1.),
+        );
+
+        for neighbor in map_geometry.walkable_neighbors(VoxelPos::ZERO.above()) {
+            signals.add_signal(SignalType::Pull(test_item()), neighbor, SignalStrength(0.5));
+        }
+
+        assert_eq!(
+            signals.upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Store(test_item()),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn upstream_returns_some_at_trivial_valley() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let item_manifest = test_manifest();
+
+        for neighbor in map_geometry.walkable_neighbors(VoxelPos::ZERO.above()) {
+            signals.add_signal(SignalType::Pull(test_item()), neighbor, SignalStrength(0.5));
+        }
+
+        assert!(signals
+            .upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Store(test_item()),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn upstream_returns_some_at_valley() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let item_manifest = test_manifest();
+
+        signals.add_signal(
+            SignalType::Pull(test_item()),
+            VoxelPos::ZERO.above(),
+            SignalStrength(0.5),
+        );
+
+        for neighbor in map_geometry.walkable_neighbors(VoxelPos::ZERO.above()) {
+            signals.add_signal(SignalType::Pull(test_item()), neighbor, SignalStrength(1.));
+        }
+
+        assert!(signals
+            .upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Store(test_item()),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &TileOccupancy::default()
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn upstream_avoids_full_tiles() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let item_manifest = test_manifest();
+        let mut tile_occupancy = TileOccupancy {
+            max_units_per_tile: Some(1),
+            ..Default::default()
+        };
+
+        let neighbors: Vec<VoxelPos> = map_geometry
+            .walkable_neighbors(VoxelPos::ZERO.above())
+            .collect();
+        let crowded = neighbors[0];
+        let quiet = neighbors[1];
+
+        signals.add_signal(SignalType::Pull(test_item()), crowded, SignalStrength(1.));
+        signals.add_signal(SignalType::Pull(test_item()), quiet, SignalStrength(0.5));
+        tile_occupancy.add(crowded);
+
+        assert_eq!(
+            signals.upstream(
+                VoxelPos::ZERO.above(),
+                &Goal::Store(test_item()),
+                MovementMode::Walking,
+                &item_manifest,
+                &map_geometry,
+                &tile_occupancy
+            ),
+            Some(quiet)
+        );
+    }
+
+    #[test]
+    fn item_signal_types_are_correct() {
+        let item_kind = test_item();
+        let item_manifest = test_manifest();
+
+        assert_eq!(
+            SignalType::item_signal_types(
+                item_kind,
+                &item_manifest,
+                DeliveryMode::PickUp,
+                Purpose::Intrinsic
+            ),
+            vec![SignalType::Push(item_kind)]
+        );
+        assert_eq!(
+            SignalType::item_signal_types(
+                item_kind,
+                &item_manifest,
+                DeliveryMode::PickUp,
+                Purpose::Instrumental
+            ),
+            vec![SignalType::Push(item_kind), SignalType::Contains(item_kind)]
+        );
+        assert_eq!(
+            SignalType::item_signal_types(
+                item_kind,
+                &item_manifest,
+                DeliveryMode::DropOff,
+                Purpose::Intrinsic
+            ),
+            vec![SignalType::Pull(item_kind)]
+        );
+        assert_eq!(
+            SignalType::item_signal_types(
+                item_kind,
+                &item_manifest,
+                DeliveryMode::DropOff,
+                Purpose::Instrumental
+            ),
+            vec![SignalType::Pull(item_kind), SignalType::Stores(item_kind)]
+        );
+    }
+}
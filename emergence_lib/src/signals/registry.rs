@@ -0,0 +1,203 @@
+//! Registration of signal channels, so that new content can control how its signals spread and fade.
+//!
+//! Every [`SignalType`] is its own channel: there is one for each item kind, each workplace, each unit type and so on.
+//! Channels can be given their own [`SignalParameters`] at startup, either individually or for a whole [`SignalKind`].
+//! Mods can also add entirely new channels by name, which are emitted as [`SignalType::Custom`].
+//!
+//! Channels that have not been registered use the parameters of their kind,
+//! falling back to [`SignalParameters::default`].
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::asset_management::manifest::Id;
+
+use super::{SignalKind, SignalType, DEGRADATION_FRACTION, DIFFUSION_FRACTION};
+
+/// The marker type for [`Id<CustomSignal>`], which identifies a [`SignalType::Custom`] channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomSignal;
+
+/// How quickly the signals in a channel spread and fade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalParameters {
+    /// The fraction of the signal in each tile that moves to each of its 6 neighbors every tick.
+    ///
+    /// See [`DIFFUSION_FRACTION`] for the constraints on this value.
+    pub diffusion_fraction: f32,
+    /// The fraction of the signal that decays every tick.
+    ///
+    /// See [`DEGRADATION_FRACTION`] for the constraints on this value.
+    pub degradation_fraction: f32,
+}
+
+impl Default for SignalParameters {
+    fn default() -> Self {
+        SignalParameters {
+            diffusion_fraction: DIFFUSION_FRACTION,
+            degradation_fraction: DEGRADATION_FRACTION,
+        }
+    }
+}
+
+impl SignalParameters {
+    /// Creates new parameters, at the default [`TickRate`](crate::simulation::time::TickRate).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `diffusion_fraction` is not between 0 and 1/6, or if `degradation_fraction` is not between 0 and 1.
+    pub fn new(diffusion_fraction: f32, degradation_fraction: f32) -> Self {
+        assert!((0.0..=1.0 / 6.0).contains(&diffusion_fraction));
+        assert!((0.0..=1.0).contains(&degradation_fraction));
+
+        SignalParameters {
+            diffusion_fraction,
+            degradation_fraction,
+        }
+    }
+}
+
+/// The registered signal channels, and how their signals spread and fade.
+#[derive(Resource, Debug, Default)]
+pub struct SignalRegistry {
+    /// The parameters of individually registered channels.
+    channels: HashMap<SignalType, SignalParameters>,
+    /// The parameters shared by every channel of a kind, unless overridden.
+    kinds: HashMap<SignalKind, SignalParameters>,
+    /// The names of the registered custom channels.
+    custom_names: HashMap<Id<CustomSignal>, String>,
+}
+
+impl SignalRegistry {
+    /// Sets the `parameters` of the channel for `signal_type`.
+    ///
+    /// This takes priority over any parameters registered for its [`SignalKind`].
+    pub fn register(&mut self, signal_type: SignalType, parameters: SignalParameters) {
+        self.channels.insert(signal_type, parameters);
+    }
+
+    /// Sets the `parameters` of every channel of `signal_kind` that has not been registered individually.
+    pub fn register_kind(&mut self, signal_kind: SignalKind, parameters: SignalParameters) {
+        self.kinds.insert(signal_kind, parameters);
+    }
+
+    /// Adds a new custom channel called `name`, returning the [`SignalType`] that should be emitted for it.
+    ///
+    /// The same signal type can later be recovered with [`SignalType::custom`].
+    pub fn register_custom(&mut self, name: &str, parameters: SignalParameters) -> SignalType {
+        let signal_type = SignalType::custom(name);
+        if let SignalType::Custom(custom_id) = signal_type {
+            self.custom_names.insert(custom_id, name.to_string());
+        }
+
+        self.register(signal_type, parameters);
+        signal_type
+    }
+
+    /// The parameters used by the channel for `signal_type`.
+    pub fn parameters(&self, signal_type: SignalType) -> SignalParameters {
+        self.channels
+            .get(&signal_type)
+            .or_else(|| self.kinds.get(&SignalKind::from(signal_type)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The name of the custom channel with the given `custom_id`, if it has been registered.
+    pub fn custom_name(&self, custom_id: Id<CustomSignal>) -> Option<&str> {
+        self.custom_names.get(&custom_id).map(String::as_str)
+    }
+}
+
+/// Registers signal channels with the [`SignalRegistry`] while the [`App`] is being built.
+pub trait RegisterSignalChannelExt {
+    /// Sets the `parameters` of the channel for `signal_type`.
+    fn register_signal_channel(
+        &mut self,
+        signal_type: SignalType,
+        parameters: SignalParameters,
+    ) -> &mut Self;
+
+    /// Sets the `parameters` of every channel of `signal_kind` that has not been registered individually.
+    fn register_signal_kind(
+        &mut self,
+        signal_kind: SignalKind,
+        parameters: SignalParameters,
+    ) -> &mut Self;
+
+    /// Adds a new custom channel called `name`, which is emitted as [`SignalType::custom`].
+    fn register_custom_signal(&mut self, name: &str, parameters: SignalParameters) -> &mut Self;
+}
+
+impl RegisterSignalChannelExt for App {
+    fn register_signal_channel(
+        &mut self,
+        signal_type: SignalType,
+        parameters: SignalParameters,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(SignalRegistry::default)
+            .register(signal_type, parameters);
+        self
+    }
+
+    fn register_signal_kind(
+        &mut self,
+        signal_kind: SignalKind,
+        parameters: SignalParameters,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(SignalRegistry::default)
+            .register_kind(signal_kind, parameters);
+        self
+    }
+
+    fn register_custom_signal(&mut self, name: &str, parameters: SignalParameters) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(SignalRegistry::default)
+            .register_custom(name, parameters);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{crafting::item_tags::ItemKind, structures::structure_manifest::Structure};
+
+    use super::*;
+
+    #[test]
+    fn channels_fall_back_to_their_kind_then_the_default() {
+        let mut registry = SignalRegistry::default();
+        let acacia = SignalType::Demolish(Id::<Structure>::from_name("acacia".to_string()));
+        let leuco = SignalType::Demolish(Id::<Structure>::from_name("leuco".to_string()));
+        let fast = SignalParameters::new(0.15, 0.1);
+        let slow = SignalParameters::new(0.01, 0.001);
+
+        assert_eq!(registry.parameters(acacia), SignalParameters::default());
+
+        registry.register_kind(SignalKind::Demolish, fast);
+        registry.register(leuco, slow);
+        assert_eq!(registry.parameters(acacia), fast);
+        assert_eq!(registry.parameters(leuco), slow);
+
+        let leaf = SignalType::Pull(ItemKind::Single(Id::from_name("leaf".to_string())));
+        assert_eq!(registry.parameters(leaf), SignalParameters::default());
+    }
+
+    #[test]
+    fn custom_channels_are_identified_by_name() {
+        let mut registry = SignalRegistry::default();
+        let parameters = SignalParameters::new(0.05, 0.02);
+
+        let signal_type = registry.register_custom("pheromone", parameters);
+        assert_eq!(signal_type, SignalType::custom("pheromone"));
+        assert_ne!(signal_type, SignalType::custom("nectar"));
+        assert_eq!(SignalKind::from(signal_type), SignalKind::Custom);
+        assert_eq!(registry.parameters(signal_type), parameters);
+
+        let SignalType::Custom(custom_id) = signal_type else {
+            unreachable!()
+        };
+        assert_eq!(registry.custom_name(custom_id), Some("pheromone"));
+    }
+}
@@ -0,0 +1,165 @@
+//! Rules chosen when starting a new game, which control how much pressure the colony is under.
+//!
+//! Rules can be set all at once by choosing a [`Difficulty`] preset, or tweaked individually.
+
+use bevy::prelude::*;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+/// The rules that the current game is played under.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameRules {
+    /// Does compostable litter rot away when it is left lying on the ground?
+    pub spoilage: bool,
+    /// How many units each colony starts with, relative to the standard rules.
+    ///
+    /// Every unit generated with the map joins a colony, so harder games start with fewer of them.
+    pub starting_workforce: f32,
+    /// How harsh the weather is, relative to the standard rules.
+    ///
+    /// Harsher weather has more clear days and fewer rainy ones.
+    pub weather_severity: f32,
+    /// How many wild structures (such as plants and fungi) start on the map, relative to the standard rules.
+    pub starting_resources: f32,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Difficulty::Standard.rules()
+    }
+}
+
+impl GameRules {
+    /// The multipliers that can be chosen for each of the adjustable rules.
+    pub const MULTIPLIERS: [f32; 4] = [0.5, 1., 1.5, 2.];
+
+    /// The smallest allowed value for any multiplier.
+    ///
+    /// Weather severity in particular is used as a divisor, and must never be zero.
+    const MIN_MULTIPLIER: f32 = 0.1;
+
+    /// The preset that these rules match, if any.
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        Difficulty::PRESETS
+            .into_iter()
+            .find(|difficulty| difficulty.rules() == *self)
+    }
+
+    /// The weather severity, clamped to a usable range.
+    pub(crate) fn weather_severity(&self) -> f32 {
+        self.weather_severity.max(Self::MIN_MULTIPLIER)
+    }
+
+    /// The chance that a starting unit is generated on a tile, given its standard `chance`.
+    pub(crate) fn starting_unit_chance(&self, chance: f32) -> f32 {
+        chance * self.starting_workforce.max(0.)
+    }
+
+    /// The chance that a wild structure is generated on a tile, given its standard `chance`.
+    pub(crate) fn starting_structure_chance(&self, chance: f32) -> f32 {
+        chance * self.starting_resources.max(0.)
+    }
+
+    /// Returns the next of the [`GameRules::MULTIPLIERS`] after `multiplier`, wrapping around to the smallest.
+    pub fn next_multiplier(multiplier: f32) -> f32 {
+        Self::MULTIPLIERS
+            .into_iter()
+            .find(|&candidate| candidate > multiplier)
+            .unwrap_or(Self::MULTIPLIERS[0])
+    }
+}
+
+/// A preset collection of [`GameRules`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// A relaxed game for building and experimenting.
+    Sandbox,
+    /// The game as it is intended to be played.
+    Standard,
+    /// Scarce resources and harsh weather.
+    Hardcore,
+}
+
+impl Difficulty {
+    /// All of the difficulty presets, from easiest to hardest.
+    pub const PRESETS: [Difficulty; 3] = [
+        Difficulty::Sandbox,
+        Difficulty::Standard,
+        Difficulty::Hardcore,
+    ];
+
+    /// The rules used by this preset.
+    pub fn rules(&self) -> GameRules {
+        match self {
+            Difficulty::Sandbox => GameRules {
+                spoilage: false,
+                starting_workforce: 2.,
+                weather_severity: 0.5,
+                starting_resources: 2.,
+            },
+            Difficulty::Standard => GameRules {
+                spoilage: true,
+                starting_workforce: 1.,
+                weather_severity: 1.,
+                starting_resources: 1.,
+            },
+            Difficulty::Hardcore => GameRules {
+                spoilage: true,
+                starting_workforce: 0.5,
+                weather_severity: 2.,
+                starting_resources: 0.5,
+            },
+        }
+    }
+
+    /// The next harder preset, wrapping around to the easiest.
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Sandbox => Difficulty::Standard,
+            Difficulty::Standard => Difficulty::Hardcore,
+            Difficulty::Hardcore => Difficulty::Sandbox,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_recognized() {
+        for difficulty in Difficulty::PRESETS {
+            assert_eq!(difficulty.rules().difficulty(), Some(difficulty));
+        }
+
+        let custom_rules = GameRules {
+            spoilage: false,
+            ..GameRules::default()
+        };
+        assert_eq!(custom_rules.difficulty(), None);
+    }
+
+    #[test]
+    fn multipliers_cycle() {
+        let mut multiplier = GameRules::MULTIPLIERS[0];
+        for expected in GameRules::MULTIPLIERS.into_iter().skip(1) {
+            multiplier = GameRules::next_multiplier(multiplier);
+            assert_eq!(multiplier, expected);
+        }
+
+        assert_eq!(
+            GameRules::next_multiplier(multiplier),
+            GameRules::MULTIPLIERS[0]
+        );
+    }
+
+    #[test]
+    fn weather_severity_is_never_zero() {
+        let rules = GameRules {
+            weather_severity: 0.,
+            ..GameRules::default()
+        };
+
+        assert!(rules.weather_severity() > 0.);
+    }
+}
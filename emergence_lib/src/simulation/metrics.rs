@@ -0,0 +1,327 @@
+//! Colony-level metrics that describe how well the colony works as a whole.
+//!
+//! No single unit is in charge, so the efficiency of the colony is an emergent property.
+//! The [`ColonyMetrics`] sample the behavior of every unit and crafter each tick,
+//! and summarize it as a set of [`DailyMetrics`] at the end of each in-game day.
+
+use std::{collections::VecDeque, fmt::Display};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    crafting::status::CraftingStatus,
+    geometry::VoxelPos,
+    units::{
+        goals::{Goal, GoalKind},
+        item_interaction::UnitInventory,
+    },
+};
+
+use super::{time::InGameTime, SimulationSet};
+
+/// Samples and summarizes the [`ColonyMetrics`].
+pub(super) struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColonyMetrics>().add_systems(
+            FixedUpdate,
+            (
+                advance_colony_metrics,
+                sample_colony_metrics.after(advance_colony_metrics),
+            )
+                .in_set(SimulationSet),
+        );
+    }
+}
+
+/// A summary of how effectively the colony worked over a single in-game day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DailyMetrics {
+    /// The in-game day that these metrics cover.
+    pub day: u64,
+    /// The Shannon entropy, in bits, of the time units spent on each kind of goal.
+    ///
+    /// Higher values mean that labor was spread more evenly across tasks.
+    pub task_allocation_entropy: f32,
+    /// The average number of tiles that items were carried before being put down.
+    pub average_haul_distance: f32,
+    /// The fraction of unit time spent pursuing a goal, rather than wandering.
+    pub signal_utilization: f32,
+    /// The fraction of time that crafters with a recipe set spent actually crafting.
+    pub production_efficiency: f32,
+}
+
+impl Display for DailyMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Task entropy: {:.2} bits
+Average haul: {:.1} tiles
+Signal utilization: {:.0}%
+Production efficiency: {:.0}%",
+            self.task_allocation_entropy,
+            self.average_haul_distance,
+            self.signal_utilization * 100.,
+            self.production_efficiency * 100.,
+        )
+    }
+}
+
+impl DailyMetrics {
+    /// The column names of the rows produced by [`DailyMetrics::to_csv_row`].
+    pub const CSV_HEADER: &'static str =
+        "day,task_allocation_entropy,average_haul_distance,signal_utilization,production_efficiency";
+
+    /// Formats these metrics as a single row of comma-separated values, without a trailing newline.
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            self.day,
+            self.task_allocation_entropy,
+            self.average_haul_distance,
+            self.signal_utilization,
+            self.production_efficiency,
+        )
+    }
+}
+
+/// The raw observations made so far in the current day.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct MetricsTally {
+    /// The number of unit-ticks spent on each kind of goal.
+    goal_ticks: HashMap<GoalKind, u64>,
+    /// The total distance, in tiles, of all completed hauls.
+    total_haul_distance: u64,
+    /// The number of completed hauls.
+    hauls: u64,
+    /// The number of crafter-ticks where a recipe was set.
+    crafter_ticks: u64,
+    /// The number of crafter-ticks where crafting was progressing.
+    working_ticks: u64,
+}
+
+impl MetricsTally {
+    /// Summarizes these observations for the provided `day`.
+    fn summarize(&self, day: u64) -> DailyMetrics {
+        let unit_ticks: u64 = self.goal_ticks.values().sum();
+        let wandering_ticks = self
+            .goal_ticks
+            .get(&GoalKind::Wander)
+            .copied()
+            .unwrap_or_default();
+
+        DailyMetrics {
+            day,
+            task_allocation_entropy: shannon_entropy(self.goal_ticks.values().copied()),
+            average_haul_distance: ratio(self.total_haul_distance, self.hauls),
+            signal_utilization: ratio(unit_ticks - wandering_ticks, unit_ticks),
+            production_efficiency: ratio(self.working_ticks, self.crafter_ticks),
+        }
+    }
+}
+
+/// Divides `numerator` by `denominator`, returning 0 if there is nothing to divide by.
+fn ratio(numerator: u64, denominator: u64) -> f32 {
+    if denominator == 0 {
+        0.
+    } else {
+        numerator as f32 / denominator as f32
+    }
+}
+
+/// Computes the Shannon entropy, in bits, of the distribution described by `counts`.
+fn shannon_entropy(counts: impl Iterator<Item = u64> + Clone) -> f32 {
+    let total: u64 = counts.clone().sum();
+    if total == 0 {
+        return 0.;
+    }
+
+    counts
+        .filter(|&count| count > 0)
+        .map(|count| {
+            let p = count as f32 / total as f32;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Emergent, colony-wide measures of efficiency, recorded over time.
+#[derive(Resource, Debug, Default)]
+pub struct ColonyMetrics {
+    /// The metrics of previous days, with the most recent day at the back.
+    history: VecDeque<DailyMetrics>,
+    /// The in-game day currently being recorded.
+    current_day: u64,
+    /// The observations made so far today.
+    today: MetricsTally,
+    /// Where each unit that is currently carrying something picked it up.
+    pickups: HashMap<Entity, VoxelPos>,
+}
+
+impl ColonyMetrics {
+    /// The number of days of metrics that are retained.
+    const HISTORY_LENGTH: usize = 20;
+
+    /// The metrics for the most recently completed day, if any.
+    pub fn latest(&self) -> Option<&DailyMetrics> {
+        self.history.back()
+    }
+
+    /// The metrics for all retained days, from oldest to newest.
+    pub fn history(&self) -> impl Iterator<Item = &DailyMetrics> {
+        self.history.iter()
+    }
+
+    /// The metrics for the current day, based on what has been observed so far.
+    pub fn today(&self) -> DailyMetrics {
+        self.today.summarize(self.current_day)
+    }
+
+    /// Summarizes the current day and starts a new one, if a new day has begun.
+    fn advance_to_day(&mut self, day: u64) {
+        if day == self.current_day {
+            return;
+        }
+
+        let summary = std::mem::take(&mut self.today).summarize(self.current_day);
+        self.history.push_back(summary);
+        while self.history.len() > Self::HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.current_day = day;
+    }
+
+    /// Records that a unit at `voxel_pos` is or isn't holding an item.
+    ///
+    /// A haul is complete once a unit that was carrying something puts it down.
+    fn record_carrying(&mut self, unit_entity: Entity, voxel_pos: VoxelPos, carrying: bool) {
+        if carrying {
+            self.pickups.entry(unit_entity).or_insert(voxel_pos);
+        } else if let Some(pickup_pos) = self.pickups.remove(&unit_entity) {
+            self.today.total_haul_distance +=
+                pickup_pos.hex.unsigned_distance_to(voxel_pos.hex) as u64;
+            self.today.hauls += 1;
+        }
+    }
+}
+
+/// Rolls the [`ColonyMetrics`] over to the next day when a new in-game day begins.
+fn advance_colony_metrics(
+    mut colony_metrics: ResMut<ColonyMetrics>,
+    in_game_time: Res<InGameTime>,
+) {
+    colony_metrics.advance_to_day(in_game_time.rounded_elapsed_days());
+}
+
+/// Observes what each unit and crafter is doing.
+fn sample_colony_metrics(
+    mut colony_metrics: ResMut<ColonyMetrics>,
+    unit_query: Query<(Entity, &VoxelPos, &Goal, &UnitInventory)>,
+    crafter_query: Query<&CraftingStatus>,
+) {
+    for (unit_entity, &voxel_pos, goal, unit_inventory) in unit_query.iter() {
+        *colony_metrics
+            .today
+            .goal_ticks
+            .entry(GoalKind::from(goal))
+            .or_default() += 1;
+
+        colony_metrics.record_carrying(
+            unit_entity,
+            voxel_pos,
+            unit_inventory.held_item().is_some(),
+        );
+    }
+
+    // Forget about units that died while carrying something
+    colony_metrics
+        .pickups
+        .retain(|&unit_entity, _| unit_query.contains(unit_entity));
+
+    for crafting_status in crafter_query.iter() {
+        match crafting_status {
+            // No recipe is set or the structure is switched off, so there's nothing to be efficient at
+            CraftingStatus::Idle | CraftingStatus::Disabled => (),
+            CraftingStatus::Working => {
+                colony_metrics.today.crafter_ticks += 1;
+                colony_metrics.today.working_ticks += 1;
+            }
+            _ => colony_metrics.today.crafter_ticks += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_measures_the_spread_of_labor() {
+        assert_eq!(shannon_entropy([].into_iter()), 0.);
+        assert_eq!(shannon_entropy([10].into_iter()), 0.);
+        assert_eq!(shannon_entropy([5, 5].into_iter()), 1.);
+        assert_eq!(shannon_entropy([3, 3, 3, 3, 0].into_iter()), 2.);
+    }
+
+    #[test]
+    fn hauls_are_measured_from_pickup_to_dropoff() {
+        let mut colony_metrics = ColonyMetrics::default();
+        let unit_entity = Entity::from_raw(0);
+
+        colony_metrics.record_carrying(unit_entity, VoxelPos::ZERO, true);
+        colony_metrics.record_carrying(unit_entity, VoxelPos::from_xy(1, 0), true);
+        colony_metrics.record_carrying(unit_entity, VoxelPos::from_xy(3, 0), false);
+        // Not carrying anything, so this isn't a haul
+        colony_metrics.record_carrying(unit_entity, VoxelPos::from_xy(5, 0), false);
+
+        assert_eq!(colony_metrics.today().average_haul_distance, 3.);
+    }
+
+    #[test]
+    fn history_rolls_over_each_day() {
+        let mut colony_metrics = ColonyMetrics::default();
+        colony_metrics.today.goal_ticks.insert(GoalKind::Wander, 1);
+        colony_metrics.today.goal_ticks.insert(GoalKind::Work, 3);
+
+        colony_metrics.advance_to_day(0);
+        assert!(colony_metrics.latest().is_none());
+
+        colony_metrics.advance_to_day(1);
+        let latest = colony_metrics.latest().unwrap();
+        assert_eq!(latest.day, 0);
+        assert_eq!(latest.signal_utilization, 0.75);
+        assert_eq!(
+            colony_metrics.today(),
+            DailyMetrics {
+                day: 1,
+                ..default()
+            }
+        );
+
+        for day in 2..(ColonyMetrics::HISTORY_LENGTH as u64 + 5) {
+            colony_metrics.advance_to_day(day);
+        }
+        assert_eq!(
+            colony_metrics.history().count(),
+            ColonyMetrics::HISTORY_LENGTH
+        );
+    }
+
+    #[test]
+    fn csv_rows_match_the_header() {
+        let metrics = DailyMetrics {
+            day: 3,
+            task_allocation_entropy: 1.5,
+            average_haul_distance: 4.,
+            signal_utilization: 0.25,
+            production_efficiency: 0.5,
+        };
+
+        assert_eq!(metrics.to_csv_row(), "3,1.5,4,0.25,0.5");
+        assert_eq!(
+            metrics.to_csv_row().split(',').count(),
+            DailyMetrics::CSV_HEADER.split(',').count()
+        );
+    }
+}
@@ -3,24 +3,41 @@
 //! All plugins in this module should work without rendering.
 
 use crate::asset_management::AssetState;
+use crate::automation::AutomationPlugin;
 use crate::construction::ConstructionPlugin;
 use crate::crafting::CraftingPlugin;
+use crate::factions::territory::TerritoryPlugin;
 use crate::geometry::sync_rotation_to_facing;
+use crate::items::ledger::ItemLedgerPlugin;
 use crate::light::LightPlugin;
+use crate::logistics::LogisticsPlugin;
+use crate::milestones::MilestonesPlugin;
 use crate::organisms::OrganismPlugin;
+use crate::random_events::RandomEventsPlugin;
 use crate::signals::SignalsPlugin;
-use crate::simulation::rng::GlobalRng;
+use crate::simulation::game_rules::GameRules;
+use crate::simulation::metrics::MetricsPlugin;
+use crate::simulation::reports::ReportsPlugin;
+use crate::simulation::rng::{GlobalRng, RngSeed};
+use crate::simulation::stepping::SteppingPlugin;
 use crate::simulation::time::TemporalPlugin;
 use crate::simulation::weather::WeatherPlugin;
 use crate::structures::StructuresPlugin;
+use crate::temperature::TemperaturePlugin;
 use crate::terrain::TerrainPlugin;
+use crate::trading::TradingPlugin;
 use crate::units::UnitsPlugin;
 use crate::water::WaterPlugin;
 use crate::world_gen::{GenerationConfig, GenerationPlugin, WorldGenState};
 use bevy::core::FrameCount;
 use bevy::prelude::*;
 
+pub mod game_rules;
+pub mod metrics;
+pub mod reports;
 pub mod rng;
+pub mod stepping;
+pub mod telemetry;
 pub mod time;
 pub mod weather;
 
@@ -34,11 +51,13 @@ impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
         info!("Building simulation plugin...");
         app.insert_resource(GlobalRng::new(self.gen_config.seed))
+            .insert_resource(RngSeed(self.gen_config.seed))
+            .init_resource::<GameRules>()
             .add_systems(FixedUpdate, sync_rotation_to_facing)
             .configure_sets(
                 FixedUpdate,
                 SimulationSet
-                    .run_if(in_state(PauseState::Playing))
+                    .run_if(is_unpaused)
                     .run_if(in_state(AssetState::FullyLoaded))
                     .run_if(world_gen_ready)
                     .run_if(max_ticks_not_reached),
@@ -57,17 +76,28 @@ impl Plugin for SimulationPlugin {
             .add_plugins(TerrainPlugin)
             .add_plugins(OrganismPlugin)
             .add_plugins(UnitsPlugin)
+            .add_plugins(LogisticsPlugin)
             .add_plugins(SignalsPlugin)
+            .add_plugins(TerritoryPlugin)
             .add_plugins(TemporalPlugin)
+            .add_plugins(SteppingPlugin)
             .add_plugins(LightPlugin)
             .add_plugins(WaterPlugin)
-            .add_plugins(WeatherPlugin);
+            .add_plugins(TemperaturePlugin)
+            .add_plugins(WeatherPlugin)
+            .add_plugins(ReportsPlugin)
+            .add_plugins(ItemLedgerPlugin)
+            .add_plugins(MetricsPlugin)
+            .add_plugins(MilestonesPlugin)
+            .add_plugins(RandomEventsPlugin)
+            .add_plugins(TradingPlugin)
+            .add_plugins(AutomationPlugin);
     }
 }
 
 /// Controls whether or not the game is paused.
 #[derive(States, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
-enum PauseState {
+pub(crate) enum PauseState {
     /// Game logic is running.
     #[default]
     Playing,
@@ -75,11 +105,19 @@ enum PauseState {
     Paused,
 }
 
+/// Is game logic running, either because the game is not paused or because a tick was requested?
+pub(crate) fn is_unpaused(
+    pause_state: Res<State<PauseState>>,
+    requested_tick: Option<Res<stepping::RequestedTick>>,
+) -> bool {
+    *pause_state.get() == PauseState::Playing || stepping::is_stepping(requested_tick)
+}
+
 /// Simulation systems.
 ///
 /// These:
 /// - are run in [`FixedUpdate`]
-/// - only run in [`PauseState::Playing`]
+/// - only run in [`PauseState::Playing`], unless the simulation is being stepped through
 /// - only run in [`AssetState::FullyLoaded`]
 #[derive(SystemSet, PartialEq, Eq, Hash, Debug, Clone)]
 pub(crate) struct SimulationSet;
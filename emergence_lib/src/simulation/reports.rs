@@ -0,0 +1,330 @@
+//! Summarizes what happened to the colony over the course of each in-game day.
+//!
+//! At each day boundary, a [`DailyReport`] is assembled from the item totals, the census,
+//! the colony's energy budget and any deaths recorded as [`TileEvent`]s.
+//! This gives players a regular rhythm of feedback about the health of their colony.
+
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    asset_management::manifest::Id,
+    items::{
+        item_manifest::{Item, ItemManifest},
+        totals::{update_item_totals, ItemTotals},
+    },
+    organisms::energy::{ColonyEnergy, Energy},
+    terrain::history::{DeathCause, TileEvent, TileEventKind},
+    units::{
+        census::{Census, VitalRecord},
+        unit_manifest::UnitManifest,
+    },
+    world_gen::WorldGenState,
+};
+
+use super::time::InGameTime;
+
+/// Assembles a [`DailyReport`] at the end of each in-game day.
+pub(super) struct ReportsPlugin;
+
+impl Plugin for ReportsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemTotals>()
+            .init_resource::<DailyReports>()
+            .add_systems(
+                Update,
+                (
+                    update_item_totals,
+                    record_incidents,
+                    // Run in Update so that the daily totals tracked in FixedUpdate have already rolled over
+                    compile_daily_report
+                        .after(update_item_totals)
+                        .after(record_incidents),
+                )
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// Notable and usually unwelcome events that occurred over the course of a day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Incidents {
+    /// The number of organisms that died, by cause.
+    deaths: HashMap<DeathCause, u32>,
+}
+
+impl Incidents {
+    /// The number of organisms that died of the given cause.
+    pub fn deaths(&self, cause: DeathCause) -> u32 {
+        self.deaths.get(&cause).copied().unwrap_or_default()
+    }
+
+    /// Did nothing noteworthy happen?
+    pub fn is_empty(&self) -> bool {
+        self.deaths.is_empty()
+    }
+}
+
+/// A summary of a single in-game day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyReport {
+    /// The in-game day that this report covers.
+    day: u64,
+    /// The change in the total number of each item over the day.
+    item_changes: HashMap<Id<Item>, i64>,
+    /// The births and deaths of each species of unit.
+    vital_record: VitalRecord,
+    /// The energy gained by the colony, net of all spending.
+    net_energy: Energy,
+    /// Deaths and other incidents.
+    incidents: Incidents,
+}
+
+impl DailyReport {
+    /// The in-game day that this report covers.
+    pub fn day(&self) -> u64 {
+        self.day
+    }
+
+    /// The change in the total number of the given item over the day.
+    pub fn item_change(&self, item_id: Id<Item>) -> i64 {
+        self.item_changes.get(&item_id).copied().unwrap_or_default()
+    }
+
+    /// The change in the total number of each item over the day.
+    ///
+    /// Items whose totals did not change are omitted.
+    pub fn item_changes(&self) -> &HashMap<Id<Item>, i64> {
+        &self.item_changes
+    }
+
+    /// Pretty formatting for this type.
+    pub(crate) fn display(
+        &self,
+        item_manifest: &ItemManifest,
+        unit_manifest: &UnitManifest,
+    ) -> String {
+        let mut string = format!(
+            "Report for day {}\nNet energy: {}",
+            self.day, self.net_energy
+        );
+
+        let mut item_changes: Vec<(&str, i64)> = self
+            .item_changes
+            .iter()
+            .map(|(item_id, change)| (item_manifest.name(*item_id), *change))
+            .collect();
+        item_changes.sort();
+
+        if !item_changes.is_empty() {
+            string += "\nProduction:";
+            for (name, change) in item_changes {
+                string += &format!("\n  {name}: {change:+}");
+            }
+        }
+
+        let vital_record = self.vital_record.display(unit_manifest);
+        if !vital_record.is_empty() {
+            string += &format!("\nPopulation:\n{vital_record}");
+        }
+
+        if !self.incidents.is_empty() {
+            string += "\nIncidents:";
+            for cause in [
+                DeathCause::Starvation,
+                DeathCause::Suffocation,
+                DeathCause::OldAge,
+            ] {
+                let deaths = self.incidents.deaths(cause);
+                if deaths > 0 {
+                    string += &format!("\n  {deaths} died of {cause}");
+                }
+            }
+        }
+
+        string
+    }
+}
+
+/// Computes the change in each item's total between two snapshots.
+///
+/// Items whose totals did not change are omitted.
+fn item_changes(
+    before: &HashMap<Id<Item>, u32>,
+    after: &HashMap<Id<Item>, u32>,
+) -> HashMap<Id<Item>, i64> {
+    let mut changes = HashMap::default();
+
+    for item_id in before.keys().chain(after.keys()) {
+        let before_count = before.get(item_id).copied().unwrap_or_default() as i64;
+        let after_count = after.get(item_id).copied().unwrap_or_default() as i64;
+        let change = after_count - before_count;
+
+        if change != 0 {
+            changes.insert(*item_id, change);
+        }
+    }
+
+    changes
+}
+
+/// The reports for the most recent in-game days.
+#[derive(Debug, Resource, Default)]
+pub struct DailyReports {
+    /// The stored reports, with the most recent day at the back.
+    reports: VecDeque<DailyReport>,
+    /// The in-game day currently being recorded.
+    current_day: u64,
+    /// The total number of each item at the start of the current day.
+    ///
+    /// This is `None` until the first snapshot has been taken.
+    items_at_start_of_day: Option<HashMap<Id<Item>, u32>>,
+    /// The incidents recorded so far today.
+    incidents_today: Incidents,
+    /// Has the most recent report been dismissed by the player?
+    dismissed: bool,
+}
+
+impl DailyReports {
+    /// The number of reports that are retained.
+    const MAX_REPORTS: usize = 20;
+
+    /// The most recent report, if any.
+    pub fn latest(&self) -> Option<&DailyReport> {
+        self.reports.back()
+    }
+
+    /// The most recent report, unless it has been dismissed.
+    pub fn undismissed(&self) -> Option<&DailyReport> {
+        match self.dismissed {
+            true => None,
+            false => self.latest(),
+        }
+    }
+
+    /// Hides the most recent report, until a new one is compiled.
+    pub fn dismiss(&mut self) {
+        self.dismissed = true;
+    }
+
+    /// All of the retained reports, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &DailyReport> {
+        self.reports.iter()
+    }
+
+    /// Stores a new report, discarding the oldest one if needed.
+    fn push(&mut self, report: DailyReport) {
+        self.reports.push_back(report);
+        while self.reports.len() > Self::MAX_REPORTS {
+            self.reports.pop_front();
+        }
+        self.dismissed = false;
+    }
+
+    /// Formats all of the retained reports as a plain-text log, suitable for saving to disk.
+    pub(crate) fn export(
+        &self,
+        item_manifest: &ItemManifest,
+        unit_manifest: &UnitManifest,
+    ) -> String {
+        self.iter()
+            .map(|report| report.display(item_manifest, unit_manifest))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Tallies up deaths as they happen.
+fn record_incidents(
+    mut tile_events: EventReader<TileEvent>,
+    mut daily_reports: ResMut<DailyReports>,
+) {
+    for tile_event in tile_events.read() {
+        if let TileEventKind::OrganismDied { cause, .. } = tile_event.kind {
+            *daily_reports
+                .incidents_today
+                .deaths
+                .entry(cause)
+                .or_default() += 1;
+        }
+    }
+}
+
+/// Compiles the [`DailyReport`] for the previous day whenever a new in-game day begins.
+fn compile_daily_report(
+    mut daily_reports: ResMut<DailyReports>,
+    in_game_time: Res<InGameTime>,
+    item_totals: Res<ItemTotals>,
+    census: Res<Census>,
+    colony_energy: Res<ColonyEnergy>,
+    item_manifest: Res<ItemManifest>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    let day = in_game_time.rounded_elapsed_days();
+
+    let Some(items_at_start_of_day) = &daily_reports.items_at_start_of_day else {
+        daily_reports.current_day = day;
+        daily_reports.items_at_start_of_day = Some(item_totals.counts().clone());
+        return;
+    };
+
+    if day == daily_reports.current_day {
+        return;
+    }
+
+    let report = DailyReport {
+        day: daily_reports.current_day,
+        item_changes: item_changes(items_at_start_of_day, item_totals.counts()),
+        vital_record: census.yesterday().cloned().unwrap_or_default(),
+        net_energy: colony_energy.net_yesterday(),
+        incidents: std::mem::take(&mut daily_reports.incidents_today),
+    };
+
+    info!("{}", report.display(&item_manifest, &unit_manifest));
+
+    daily_reports.push(report);
+    daily_reports.current_day = day;
+    daily_reports.items_at_start_of_day = Some(item_totals.counts().clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_items_are_omitted() {
+        let acacia_leaf = Id::from_name("acacia_leaf".to_string());
+        let leuco_chunk = Id::from_name("leuco_chunk".to_string());
+        let mud = Id::from_name("mud".to_string());
+
+        let before = HashMap::from_iter([(acacia_leaf, 5), (leuco_chunk, 3)]);
+        let after = HashMap::from_iter([(acacia_leaf, 5), (leuco_chunk, 1), (mud, 2)]);
+
+        let changes = item_changes(&before, &after);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[&leuco_chunk], -2);
+        assert_eq!(changes[&mud], 2);
+    }
+
+    #[test]
+    fn reports_are_bounded() {
+        let mut daily_reports = DailyReports::default();
+
+        for day in 0..(DailyReports::MAX_REPORTS as u64 + 5) {
+            daily_reports.push(DailyReport {
+                day,
+                item_changes: HashMap::default(),
+                vital_record: VitalRecord::default(),
+                net_energy: Energy(0.),
+                incidents: Incidents::default(),
+            });
+        }
+
+        assert_eq!(daily_reports.iter().count(), DailyReports::MAX_REPORTS);
+        assert_eq!(daily_reports.latest().unwrap().day(), 24);
+
+        daily_reports.dismiss();
+        assert!(daily_reports.undismissed().is_none());
+    }
+}
@@ -1,9 +1,15 @@
 //! Controls random number generation.
 //!
 //! Storing the random number generator in a resource allows us to generate worlds deterministically.
+//! Simulation systems each draw from their own [`SystemRng`], so that games replay identically from the same seed.
 // TODO: replace with bevy_turborand.
 
-use bevy::prelude::*;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
 use rand::{rngs::SmallRng, SeedableRng};
 
 /// A global source of entropy.
@@ -21,3 +27,66 @@ impl GlobalRng {
         &mut self.0
     }
 }
+
+/// The seed that each [`SystemRng`] is derived from.
+#[derive(Debug, Clone, Copy, Resource, PartialEq, Eq)]
+pub(crate) struct RngSeed(pub(crate) u64);
+
+/// A source of entropy owned by a single system.
+///
+/// Each system keeps its own generator, seeded from the [`RngSeed`] and a label unique to that system.
+/// Unlike the [`GlobalRng`], the numbers drawn do not depend on the order in which systems happen to run,
+/// so two games started from the same seed with the same inputs stay in sync.
+///
+/// If there is no [`RngSeed`], as in most tests, a seed of 0 is used.
+#[derive(SystemParam)]
+pub(crate) struct SystemRng<'w, 's> {
+    /// The seed of the current game.
+    seed: Option<Res<'w, RngSeed>>,
+    /// The generator for this system, created the first time it is needed.
+    rng: Local<'s, Option<SmallRng>>,
+}
+
+impl SystemRng<'_, '_> {
+    /// The generator for this system, which is identified by the `label`.
+    pub(crate) fn get(&mut self, label: &str) -> &mut SmallRng {
+        let seed = self.seed.as_deref().map_or(0, |seed| seed.0);
+
+        self.rng.get_or_insert_with(|| {
+            // The default hasher uses fixed keys, so this is the same on every machine
+            let mut hasher = DefaultHasher::new();
+            (seed, label).hash(&mut hasher);
+            SmallRng::seed_from_u64(hasher.finish())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+    use rand::Rng;
+
+    use super::*;
+
+    /// Draws a few numbers from a fresh [`SystemRng`] with the given `label`.
+    fn draw(world: &mut World, label: &'static str) -> Vec<u32> {
+        let mut system_state: SystemState<SystemRng> = SystemState::new(world);
+        let mut system_rng = system_state.get_mut(world);
+        let rng = system_rng.get(label);
+
+        (0..4).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn system_rngs_are_reproducible() {
+        let mut world = World::new();
+        world.insert_resource(RngSeed(7));
+
+        assert_eq!(draw(&mut world, "a"), draw(&mut world, "a"));
+        assert_ne!(draw(&mut world, "a"), draw(&mut world, "b"));
+
+        let first_seed = draw(&mut world, "a");
+        world.insert_resource(RngSeed(8));
+        assert_ne!(draw(&mut world, "a"), first_seed);
+    }
+}
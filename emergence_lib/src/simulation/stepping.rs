@@ -0,0 +1,196 @@
+//! Debug controls for watching the simulation closely: slow motion, and stepping one tick at a time.
+//!
+//! Rendering continues while the simulation is slowed or stepped,
+//! so the camera can move and debug gizmos can be inspected between ticks.
+//!
+//! Steps run the [`FixedUpdate`] schedule directly rather than waiting for virtual time to accumulate,
+//! so they work even while the game is paused, frozen by photo mode or behind a menu.
+//! The [control API](crate::control_api) steps the simulation in the same way.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::player_interaction::PlayerAction;
+
+use super::{time::GameSpeed, SimulationSet};
+
+/// Slows down or steps through the simulation when asked to.
+pub(super) struct SteppingPlugin;
+
+impl Plugin for SteppingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugStepping>()
+            .add_systems(
+                Update,
+                (
+                    control_stepping.run_if(resource_exists::<ActionState<PlayerAction>>()),
+                    apply_slow_motion,
+                    run_requested_steps,
+                )
+                    .chain(),
+            )
+            .configure_sets(FixedUpdate, SimulationSet.run_if(stepping_allows_tick));
+    }
+}
+
+/// The state of the simulation's debug speed controls.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct DebugStepping {
+    /// Is the simulation running at [`DebugStepping::SLOW_MOTION_SPEED`]?
+    pub slow_motion: bool,
+    /// Is the simulation frozen, except for requested steps?
+    pub frame_step: bool,
+    /// The number of ticks that have been requested, but not yet simulated.
+    pending_steps: u32,
+}
+
+impl DebugStepping {
+    /// The rate at which time passes in slow motion, relative to normal.
+    pub const SLOW_MOTION_SPEED: f32 = 0.1;
+
+    /// Freezes the simulation, then advances it by exactly one tick.
+    pub fn step(&mut self) {
+        self.frame_step = true;
+        self.pending_steps += 1;
+    }
+
+    /// Should the simulation advance this tick?
+    ///
+    /// While stepping frame by frame, only requested ticks advance the simulation.
+    fn allows_tick(&self, requested_tick: bool) -> bool {
+        !self.frame_step || requested_tick
+    }
+}
+
+/// Present while a requested tick is being simulated by [`run_requested_tick`].
+#[derive(Resource, Debug)]
+pub(crate) struct RequestedTick;
+
+/// Turns player input into changes to [`DebugStepping`].
+fn control_stepping(
+    actions: Res<ActionState<PlayerAction>>,
+    mut debug_stepping: ResMut<DebugStepping>,
+) {
+    if actions.just_pressed(PlayerAction::ToggleSlowMotion) {
+        debug_stepping.slow_motion = !debug_stepping.slow_motion;
+    }
+
+    if actions.just_pressed(PlayerAction::ToggleFrameStep) {
+        debug_stepping.frame_step = !debug_stepping.frame_step;
+        debug_stepping.pending_steps = 0;
+    }
+
+    if actions.just_pressed(PlayerAction::StepSimulation) {
+        debug_stepping.step();
+    }
+}
+
+/// Sets the slow motion factor of the [`GameSpeed`] to match [`DebugStepping`].
+fn apply_slow_motion(debug_stepping: Res<DebugStepping>, mut game_speed: ResMut<GameSpeed>) {
+    let slow_motion = match debug_stepping.slow_motion {
+        true => DebugStepping::SLOW_MOTION_SPEED,
+        false => 1.,
+    };
+
+    if game_speed.slow_motion != slow_motion {
+        game_speed.slow_motion = slow_motion;
+    }
+}
+
+/// While stepping frame by frame, the simulation only runs when a step has been requested.
+fn stepping_allows_tick(
+    debug_stepping: Res<DebugStepping>,
+    requested_tick: Option<Res<RequestedTick>>,
+) -> bool {
+    debug_stepping.allows_tick(requested_tick.is_some())
+}
+
+/// Is a requested tick being simulated right now?
+///
+/// Conditions that stop the simulation, such as pausing, should let these ticks through.
+pub(crate) fn is_stepping(requested_tick: Option<Res<RequestedTick>>) -> bool {
+    requested_tick.is_some()
+}
+
+/// Simulates a single tick by running [`FixedUpdate`] once, with a delta of exactly one fixed timestep.
+///
+/// Virtual time is not consulted, so the tick is taken even while it is paused.
+pub(crate) fn run_requested_tick(world: &mut World) {
+    let mut fixed_time = world.resource_mut::<Time<Fixed>>();
+    let timestep = fixed_time.timestep();
+    fixed_time.advance_by(timestep);
+    let fixed_time = fixed_time.as_generic();
+    *world.resource_mut::<Time>() = fixed_time;
+
+    world.insert_resource(RequestedTick);
+    world.run_schedule(FixedUpdate);
+    world.remove_resource::<RequestedTick>();
+
+    let virtual_time = world.resource::<Time<Virtual>>().as_generic();
+    *world.resource_mut::<Time>() = virtual_time;
+}
+
+/// Simulates each step requested through [`DebugStepping`].
+fn run_requested_steps(world: &mut World) {
+    let mut debug_stepping = world.resource_mut::<DebugStepping>();
+    if !debug_stepping.frame_step {
+        return;
+    }
+    let pending_steps = std::mem::take(&mut debug_stepping.pending_steps);
+
+    for _ in 0..pending_steps {
+        run_requested_tick(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// The deltas seen by each simulated tick.
+    #[derive(Resource, Default)]
+    struct SimulatedTicks(Vec<Duration>);
+
+    fn record_tick(time: Res<Time>, mut simulated_ticks: ResMut<SimulatedTicks>) {
+        simulated_ticks.0.push(time.delta());
+    }
+
+    #[test]
+    fn steps_advance_exactly_one_tick_while_paused() {
+        let mut world = World::new();
+        world.init_resource::<DebugStepping>();
+        world.init_resource::<SimulatedTicks>();
+        world.insert_resource(Time::<Fixed>::from_hz(10.));
+        let mut virtual_time = Time::<Virtual>::default();
+        virtual_time.pause();
+        world.insert_resource(virtual_time);
+        world.init_resource::<Time>();
+
+        let mut fixed_update = Schedule::new(FixedUpdate);
+        fixed_update
+            .add_systems(record_tick.in_set(SimulationSet))
+            .configure_sets(SimulationSet.run_if(stepping_allows_tick));
+        world.add_schedule(fixed_update);
+
+        let mut update = Schedule::default();
+        update.add_systems(run_requested_steps);
+
+        update.run(&mut world);
+        assert!(world.resource::<SimulatedTicks>().0.is_empty());
+
+        world.resource_mut::<DebugStepping>().step();
+        update.run(&mut world);
+        assert_eq!(
+            world.resource::<SimulatedTicks>().0,
+            vec![Duration::from_millis(100)]
+        );
+
+        // Ticks from the usual fixed timestep don't advance the simulation while stepping
+        world.run_schedule(FixedUpdate);
+        update.run(&mut world);
+        assert_eq!(world.resource::<SimulatedTicks>().0.len(), 1);
+        assert!(!world.contains_resource::<RequestedTick>());
+    }
+}
@@ -0,0 +1,349 @@
+//! Exports simulation metrics to disk, for analysis outside of the game.
+//!
+//! Telemetry is opt-in: add a [`TelemetryPlugin`] with a [`TelemetryConfig`] alongside the [`SimulationPlugin`](super::SimulationPlugin) to enable it.
+//! Records are appended to a single file as the simulation runs,
+//! either as CSV (with one column per item and signal kind) or as JSON Lines.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    enum_iter::IterableEnum,
+    items::{item_manifest::ItemManifest, totals::ItemTotals},
+    organisms::energy::ColonyEnergy,
+    signals::{SignalChannels, SignalKind},
+    units::census::Census,
+};
+
+use super::{time::InGameTime, SimulationSet};
+
+/// Periodically writes a [`TelemetryRecord`] to disk.
+pub struct TelemetryPlugin {
+    /// Where, how and how often records are written.
+    ///
+    /// If this is [`None`], no telemetry is exported.
+    pub config: Option<TelemetryConfig>,
+}
+
+impl TelemetryPlugin {
+    /// Configures telemetry using [`TelemetryConfig::from_env`].
+    pub fn from_env() -> Self {
+        TelemetryPlugin {
+            config: TelemetryConfig::from_env(),
+        }
+    }
+}
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(config) = &self.config else {
+            return;
+        };
+
+        info!("Exporting telemetry to {}", config.path.display());
+        app.insert_resource(TelemetryExporter::new(config.clone()))
+            .add_systems(FixedUpdate, export_telemetry.in_set(SimulationSet));
+    }
+}
+
+/// The file format used for exported telemetry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TelemetryFormat {
+    /// Comma-separated values, with a header row.
+    Csv,
+    /// One JSON object per line.
+    Json,
+}
+
+impl TelemetryFormat {
+    /// The conventional file extension for this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TelemetryFormat::Csv => "csv",
+            TelemetryFormat::Json => "jsonl",
+        }
+    }
+}
+
+/// How often telemetry records are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TelemetryFrequency {
+    /// Once per simulation tick.
+    EveryTick,
+    /// Once at the start of each in-game day.
+    Daily,
+}
+
+/// Settings for the [`TelemetryPlugin`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// The file that records are written to.
+    ///
+    /// Any existing file at this path is overwritten.
+    pub path: PathBuf,
+    /// The format of the file.
+    pub format: TelemetryFormat,
+    /// How often records are written.
+    pub frequency: TelemetryFrequency,
+}
+
+impl TelemetryConfig {
+    /// The environment variable that enables telemetry when set to an output path.
+    ///
+    /// The format is chosen based on the file extension, defaulting to CSV.
+    pub const PATH_VAR: &'static str = "EMERGENCE_TELEMETRY";
+
+    /// The environment variable that selects per-tick rather than daily records when set to `tick`.
+    pub const FREQUENCY_VAR: &'static str = "EMERGENCE_TELEMETRY_FREQUENCY";
+
+    /// Reads the telemetry settings from the environment.
+    ///
+    /// Returns [`None`] if telemetry has not been requested.
+    pub fn from_env() -> Option<Self> {
+        let path = PathBuf::from(std::env::var_os(Self::PATH_VAR)?);
+
+        let format = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json" | "jsonl") => TelemetryFormat::Json,
+            _ => TelemetryFormat::Csv,
+        };
+
+        let frequency = match std::env::var(Self::FREQUENCY_VAR).as_deref() {
+            Ok("tick") => TelemetryFrequency::EveryTick,
+            _ => TelemetryFrequency::Daily,
+        };
+
+        Some(TelemetryConfig {
+            path,
+            format,
+            frequency,
+        })
+    }
+}
+
+/// A snapshot of the state of the simulation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryRecord {
+    /// The number of simulation ticks that have elapsed.
+    pub tick: u64,
+    /// The number of in-game days that have elapsed.
+    pub day: f32,
+    /// The number of living units.
+    pub population: usize,
+    /// The total energy stored across all organisms.
+    pub energy: f32,
+    /// The total number of each item, by name.
+    pub items: BTreeMap<String, u32>,
+    /// The total strength of each kind of signal across the map.
+    pub signals: BTreeMap<String, f32>,
+}
+
+impl TelemetryRecord {
+    /// The header row for CSV output.
+    ///
+    /// Every record for the same manifest has the same columns, in the same order.
+    fn csv_header(&self) -> String {
+        let mut columns = vec![
+            "tick".to_string(),
+            "day".to_string(),
+            "population".to_string(),
+            "energy".to_string(),
+        ];
+        columns.extend(self.items.keys().map(|name| format!("item:{name}")));
+        columns.extend(self.signals.keys().map(|name| format!("signal:{name}")));
+
+        columns.join(",")
+    }
+
+    /// The data row for CSV output.
+    fn csv_row(&self) -> String {
+        let mut values = vec![
+            self.tick.to_string(),
+            self.day.to_string(),
+            self.population.to_string(),
+            self.energy.to_string(),
+        ];
+        values.extend(self.items.values().map(u32::to_string));
+        values.extend(self.signals.values().map(f32::to_string));
+
+        values.join(",")
+    }
+}
+
+/// Tracks the output file and when the next record is due.
+#[derive(Resource, Debug)]
+struct TelemetryExporter {
+    /// Where, how and how often records are written.
+    config: TelemetryConfig,
+    /// The open output file.
+    ///
+    /// This is [`None`] until the first record is written, or if writing has failed.
+    writer: Option<BufWriter<File>>,
+    /// Has writing failed?
+    ///
+    /// Once an error has occurred, no further records are written.
+    failed: bool,
+    /// The number of simulation ticks that have elapsed.
+    tick: u64,
+    /// The in-game day on which the most recent record was written.
+    last_recorded_day: Option<u64>,
+}
+
+impl TelemetryExporter {
+    /// Creates a new exporter, which will open its file once the first record is due.
+    fn new(config: TelemetryConfig) -> Self {
+        TelemetryExporter {
+            config,
+            writer: None,
+            failed: false,
+            tick: 0,
+            last_recorded_day: None,
+        }
+    }
+
+    /// Is a record due on the provided in-game `day`?
+    fn is_due(&self, day: u64) -> bool {
+        match self.config.frequency {
+            TelemetryFrequency::EveryTick => true,
+            TelemetryFrequency::Daily => self.last_recorded_day != Some(day),
+        }
+    }
+
+    /// Appends the `record` to the output file, opening it if needed.
+    fn write(&mut self, record: &TelemetryRecord) -> std::io::Result<()> {
+        let writer = match self.writer.take() {
+            Some(writer) => writer,
+            None => {
+                if let Some(parent) = self.config.path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let mut writer = BufWriter::new(File::create(&self.config.path)?);
+                if self.config.format == TelemetryFormat::Csv {
+                    writeln!(writer, "{}", record.csv_header())?;
+                }
+                writer
+            }
+        };
+        let writer = self.writer.insert(writer);
+
+        match self.config.format {
+            TelemetryFormat::Csv => writeln!(writer, "{}", record.csv_row())?,
+            TelemetryFormat::Json => {
+                serde_json::to_writer(&mut *writer, record)?;
+                writeln!(writer)?;
+            }
+        }
+
+        // Flush every record, so the file is usable even if the game is closed abruptly
+        writer.flush()
+    }
+}
+
+/// Writes a [`TelemetryRecord`] to disk whenever one is due.
+fn export_telemetry(
+    mut exporter: ResMut<TelemetryExporter>,
+    in_game_time: Res<InGameTime>,
+    census: Res<Census>,
+    colony_energy: Res<ColonyEnergy>,
+    item_totals: Res<ItemTotals>,
+    item_manifest: Res<ItemManifest>,
+    signal_channels: Res<SignalChannels>,
+) {
+    exporter.tick += 1;
+
+    let day = in_game_time.rounded_elapsed_days();
+    if exporter.failed || !exporter.is_due(day) {
+        return;
+    }
+    exporter.last_recorded_day = Some(day);
+
+    // Include every item and signal kind, even when absent, so that CSV columns stay consistent
+    let items = item_manifest
+        .variants()
+        .into_iter()
+        .map(|item_id| {
+            let count = item_totals
+                .counts()
+                .get(&item_id)
+                .copied()
+                .unwrap_or_default();
+            (item_manifest.name(item_id).to_string(), count)
+        })
+        .collect();
+
+    let signal_totals = signal_channels.player().total_strength_by_kind();
+    let signals = SignalKind::variants()
+        .map(|signal_kind| {
+            let total = signal_totals.get(&signal_kind).copied().unwrap_or_default();
+            (format!("{signal_kind:?}"), total)
+        })
+        .collect();
+
+    let record = TelemetryRecord {
+        tick: exporter.tick,
+        day: in_game_time.elapsed_days(),
+        population: census.total_population(),
+        energy: colony_energy.stored().0,
+        items,
+        signals,
+    };
+
+    if let Err(error) = exporter.write(&record) {
+        error!(
+            "Could not export telemetry to {}: {error}",
+            exporter.config.path.display()
+        );
+        exporter.failed = true;
+        exporter.writer = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> TelemetryRecord {
+        TelemetryRecord {
+            tick: 7,
+            day: 1.5,
+            population: 12,
+            energy: 300.,
+            items: BTreeMap::from_iter([("acacia_leaf".to_string(), 4), ("mud".to_string(), 0)]),
+            signals: BTreeMap::from_iter([("Push".to_string(), 2.5), ("Work".to_string(), 0.)]),
+        }
+    }
+
+    #[test]
+    fn csv_rows_line_up_with_the_header() {
+        let record = record();
+
+        assert_eq!(
+            record.csv_header(),
+            "tick,day,population,energy,item:acacia_leaf,item:mud,signal:Push,signal:Work"
+        );
+        assert_eq!(record.csv_row(), "7,1.5,12,300,4,0,2.5,0");
+    }
+
+    #[test]
+    fn daily_telemetry_is_written_once_per_day() {
+        let mut exporter = TelemetryExporter::new(TelemetryConfig {
+            path: PathBuf::from("telemetry.csv"),
+            format: TelemetryFormat::Csv,
+            frequency: TelemetryFrequency::Daily,
+        });
+
+        assert!(exporter.is_due(0));
+        exporter.last_recorded_day = Some(0);
+        assert!(!exporter.is_due(0));
+        assert!(exporter.is_due(1));
+
+        exporter.config.frequency = TelemetryFrequency::EveryTick;
+        assert!(exporter.is_due(0));
+    }
+}
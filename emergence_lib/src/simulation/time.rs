@@ -22,7 +22,9 @@ pub(crate) struct TemporalPlugin;
 impl Plugin for TemporalPlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<PauseState>()
-            .insert_resource(Time::<Fixed>::from_hz(30.))
+            .init_resource::<TickRate>()
+            .init_resource::<GameSpeed>()
+            .insert_resource(Time::<Fixed>::from_hz(TickRate::DEFAULT))
             .add_systems(
                 FixedUpdate,
                 (
@@ -33,11 +35,135 @@ impl Plugin for TemporalPlugin {
                     .chain()
                     .in_set(SimulationSet),
             )
-            .add_systems(Update, pause_game)
+            .add_systems(Update, (pause_game, apply_tick_rate, apply_game_speed))
             .init_resource::<InGameTime>();
     }
 }
 
+/// How many times per second the simulation is advanced.
+///
+/// This is independent of both the framerate and the game speed:
+/// lower tick rates make the simulation coarser, but cheaper to run.
+/// Time-based logic should use the fixed timestep from [`Time`],
+/// while anything that happens once per tick should be scaled with [`TickRate::per_tick_fraction`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct TickRate {
+    /// The number of simulation ticks per second.
+    ticks_per_second: f64,
+}
+
+impl TickRate {
+    /// The tick rate that per-tick constants are tuned for.
+    pub const DEFAULT: f64 = 30.;
+
+    /// The lowest supported tick rate.
+    pub const MIN: f64 = 10.;
+
+    /// The highest supported tick rate.
+    pub const MAX: f64 = 60.;
+
+    /// The tick rates offered in the settings menu.
+    pub const PRESETS: [f64; 5] = [10., 15., 20., 30., 60.];
+
+    /// Creates a new tick rate, clamped between [`TickRate::MIN`] and [`TickRate::MAX`].
+    pub fn new(ticks_per_second: f64) -> Self {
+        TickRate {
+            ticks_per_second: ticks_per_second.clamp(Self::MIN, Self::MAX),
+        }
+    }
+
+    /// The number of simulation ticks per second.
+    pub fn ticks_per_second(&self) -> f64 {
+        self.ticks_per_second
+    }
+
+    /// The next of the [`TickRate::PRESETS`], wrapping back around to the slowest.
+    pub fn next(&self) -> Self {
+        let next = Self::PRESETS
+            .iter()
+            .copied()
+            .find(|&preset| preset > self.ticks_per_second)
+            .unwrap_or(Self::PRESETS[0]);
+
+        TickRate::new(next)
+    }
+
+    /// Converts a `fraction` that is applied once per tick at the [`TickRate::DEFAULT`] rate to the equivalent fraction at this rate.
+    ///
+    /// Applying the result once per tick has the same effect over a second as the original did,
+    /// whether it is a decay fraction or the chance of an event.
+    pub fn per_tick_fraction(&self, fraction: f32) -> f32 {
+        let default_ticks_per_tick = (Self::DEFAULT / self.ticks_per_second) as f32;
+        1. - (1. - fraction).powf(default_ticks_per_tick)
+    }
+
+    /// Converts an `amount` that is added once per tick at the [`TickRate::DEFAULT`] rate to the equivalent amount at this rate.
+    ///
+    /// Adding the result once per tick adds the same total over a second as the original did.
+    pub fn per_tick_amount(&self, amount: f32) -> f32 {
+        amount * (Self::DEFAULT / self.ticks_per_second) as f32
+    }
+}
+
+impl Default for TickRate {
+    fn default() -> Self {
+        TickRate::new(Self::DEFAULT)
+    }
+}
+
+/// Changes the fixed timestep whenever the [`TickRate`] changes.
+fn apply_tick_rate(tick_rate: Res<TickRate>, mut fixed_time: ResMut<Time<Fixed>>) {
+    if tick_rate.is_changed() {
+        fixed_time.set_timestep_hz(tick_rate.ticks_per_second());
+    }
+}
+
+/// How quickly the in-game clock runs, relative to normal.
+///
+/// Each feature that speeds up, slows down or freezes time owns one of these factors,
+/// so that they can be combined rather than overwriting each other.
+/// The combined speed is applied to [`Time<Virtual>`] by [`apply_game_speed`]: nothing else should change it.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct GameSpeed {
+    /// The speed set by the slow motion debug control.
+    pub slow_motion: f32,
+    /// The speed set by photo mode.
+    ///
+    /// A speed of 0 freezes time.
+    pub photo_mode: f32,
+}
+
+impl GameSpeed {
+    /// The rate at which time passes, relative to normal.
+    pub fn relative_speed(&self) -> f32 {
+        self.slow_motion * self.photo_mode
+    }
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        GameSpeed {
+            slow_motion: 1.,
+            photo_mode: 1.,
+        }
+    }
+}
+
+/// Applies the combined [`GameSpeed`] to the in-game clock whenever it changes.
+fn apply_game_speed(game_speed: Res<GameSpeed>, mut virtual_time: ResMut<Time<Virtual>>) {
+    if !game_speed.is_changed() {
+        return;
+    }
+
+    let relative_speed = game_speed.relative_speed();
+    if relative_speed > 0. {
+        virtual_time.unpause();
+        virtual_time.set_relative_speed(relative_speed);
+    } else {
+        virtual_time.pause();
+    }
+}
+
 /// Stores the in game time.
 #[derive(Resource)]
 pub struct InGameTime {
@@ -108,7 +234,44 @@ impl TimeOfDay {
     }
 }
 
+/// A season of the in-game year.
+///
+/// These are evenly spaced throughout the year, which begins at the start of spring.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Season {
+    /// Warming up
+    Spring,
+    /// The warmest season
+    Summer,
+    /// Cooling down
+    Autumn,
+    /// The coldest season
+    Winter,
+}
+
+impl Season {
+    /// Returns the season that contains the given fraction of a year.
+    ///
+    /// Values outside of [0.0, 1.0] are modulo'd to fit the range.
+    pub fn from_fraction_of_year(fraction: f32) -> Self {
+        let fraction = fraction.rem_euclid(1.0);
+
+        if fraction < 0.25 {
+            Season::Spring
+        } else if fraction < 0.5 {
+            Season::Summer
+        } else if fraction < 0.75 {
+            Season::Autumn
+        } else {
+            Season::Winter
+        }
+    }
+}
+
 impl InGameTime {
+    /// The number of in-game days in each year.
+    pub const DAYS_PER_YEAR: f32 = 20.;
+
     /// How many days have elapsed total?
     pub fn elapsed_days(&self) -> f32 {
         self.elapsed_time.0
@@ -125,6 +288,18 @@ impl InGameTime {
         self.elapsed_time.0 % 1.0
     }
 
+    /// How far are we through the year?
+    ///
+    /// This begins at the start of spring.
+    pub fn fraction_of_year(&self) -> f32 {
+        (self.elapsed_time.0 / Self::DAYS_PER_YEAR) % 1.0
+    }
+
+    /// What season is it?
+    pub fn season(&self) -> Season {
+        Season::from_fraction_of_year(self.fraction_of_year())
+    }
+
     /// What time of day is it?
     pub fn time_of_day(&self) -> TimeOfDay {
         TimeOfDay::from_fraction_of_day(self.fraction_of_day())
@@ -147,8 +322,9 @@ impl Display for InGameTime {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{} days elapsed\n{:.2}h ({})",
+            "{} days elapsed ({})\n{:.2}h ({})",
             self.rounded_elapsed_days(),
+            self.season(),
             self.twenty_four_hour_time(),
             self.time_of_day()
         )
@@ -273,3 +449,62 @@ fn record_elapsed_time_for_lifecycles(
         lifecycle.record_elapsed_time(delta_days);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_speed_factors_are_combined() {
+        let mut app = App::new();
+        app.init_resource::<Time<Virtual>>()
+            .init_resource::<GameSpeed>()
+            .add_systems(Update, apply_game_speed);
+
+        app.world.resource_mut::<GameSpeed>().slow_motion = 0.1;
+        app.world.resource_mut::<GameSpeed>().photo_mode = 0.25;
+        app.update();
+        let virtual_time = app.world.resource::<Time<Virtual>>();
+        assert!((virtual_time.relative_speed() - 0.025).abs() < 1e-6);
+        assert!(!virtual_time.is_paused());
+
+        // Leaving photo mode restores slow motion, rather than resetting to normal speed
+        app.world.resource_mut::<GameSpeed>().photo_mode = 0.;
+        app.update();
+        assert!(app.world.resource::<Time<Virtual>>().is_paused());
+
+        app.world.resource_mut::<GameSpeed>().photo_mode = 1.;
+        app.update();
+        let virtual_time = app.world.resource::<Time<Virtual>>();
+        assert!((virtual_time.relative_speed() - 0.1).abs() < 1e-6);
+        assert!(!virtual_time.is_paused());
+    }
+
+    #[test]
+    fn tick_rates_are_clamped() {
+        assert_eq!(TickRate::new(1.).ticks_per_second(), TickRate::MIN);
+        assert_eq!(TickRate::new(1000.).ticks_per_second(), TickRate::MAX);
+    }
+
+    #[test]
+    fn per_tick_fractions_compound_to_the_same_rate() {
+        let fraction = 0.1;
+        assert_eq!(TickRate::default().per_tick_fraction(fraction), fraction);
+
+        // Two ticks at half the rate must match a single tick at the default rate
+        let half_rate = TickRate::new(TickRate::DEFAULT * 2.);
+        let scaled = half_rate.per_tick_fraction(fraction);
+        let retained = (1. - scaled) * (1. - scaled);
+        assert!((retained - (1. - fraction)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn presets_cycle() {
+        let mut tick_rate = TickRate::new(TickRate::PRESETS[0]);
+        for _ in TickRate::PRESETS {
+            tick_rate = tick_rate.next();
+        }
+
+        assert_eq!(tick_rate, TickRate::new(TickRate::PRESETS[0]));
+    }
+}
@@ -1,12 +1,20 @@
 //! Varies the weather each day.
+//!
+//! The weather also sets the wind, which blows across the whole map.
+
+use std::f32::consts::TAU;
 
 use bevy::prelude::*;
 use derive_more::Display;
 use emergence_macros::IterableEnum;
-use rand::rngs::ThreadRng;
+use hexx::{Direction, Hex};
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::Rng;
 
 use crate as emergence_lib;
+use crate::geometry::xz_offset;
+use crate::simulation::game_rules::GameRules;
+use crate::simulation::rng::SystemRng;
 use crate::simulation::time::InGameTime;
 
 /// A plugin that handles weather.
@@ -14,10 +22,12 @@ pub(crate) struct WeatherPlugin;
 
 impl Plugin for WeatherPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CurrentWeather>().add_systems(
-            FixedUpdate,
-            (set_daily_weather,).in_set(super::SimulationSet),
-        );
+        app.init_resource::<CurrentWeather>()
+            .init_resource::<Wind>()
+            .add_systems(
+                FixedUpdate,
+                (set_daily_weather,).in_set(super::SimulationSet),
+            );
     }
 }
 
@@ -28,6 +38,8 @@ pub struct CurrentWeather {
     last_updated: u32,
     /// The current weather.
     weather: Weather,
+    /// The number of consecutive days, up to and including today, without rain.
+    days_without_rain: u32,
 }
 
 impl Default for CurrentWeather {
@@ -35,6 +47,7 @@ impl Default for CurrentWeather {
         Self {
             last_updated: 0,
             weather: Weather::Clear,
+            days_without_rain: 0,
         }
     }
 }
@@ -46,6 +59,7 @@ impl CurrentWeather {
         Self {
             last_updated: 0,
             weather,
+            days_without_rain: 0,
         }
     }
 
@@ -53,10 +67,25 @@ impl CurrentWeather {
     pub(crate) fn get(&self) -> Weather {
         self.weather
     }
+
+    /// The number of consecutive days, up to and including today, without rain.
+    pub fn days_without_rain(&self) -> u32 {
+        self.days_without_rain
+    }
+
+    /// Replaces today's weather with the provided `weather`.
+    ///
+    /// The weather will change as usual at the start of the next day.
+    pub(crate) fn set(&mut self, weather: Weather) {
+        if weather == Weather::Rainy {
+            self.days_without_rain = 0;
+        }
+        self.weather = weather;
+    }
 }
 
 /// A type of weather.
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Display, IterableEnum)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Display, IterableEnum)]
 pub(crate) enum Weather {
     /// A clear day.
     Clear,
@@ -68,8 +97,12 @@ pub(crate) enum Weather {
 
 impl Weather {
     /// Chooses a random weather.
-    fn random(rng: &mut ThreadRng) -> Self {
-        match rng.gen_range(0..3) {
+    ///
+    /// At a `severity` of 1, each kind of weather is equally likely.
+    /// Higher severities make clear days more common and rainy days rarer.
+    fn random(rng: &mut impl Rng, severity: f32) -> Self {
+        let weights = [severity, 1., severity.recip()];
+        match WeightedIndex::new(weights).unwrap().sample(rng) {
             0 => Self::Clear,
             1 => Self::Cloudy,
             2 => Self::Rainy,
@@ -88,14 +121,166 @@ impl Weather {
             Self::Rainy => 1.,
         }
     }
+
+    /// The typical strength of the wind for this kind of weather.
+    fn typical_wind_speed(self) -> f32 {
+        match self {
+            Self::Clear => 0.2,
+            Self::Cloudy => 0.4,
+            Self::Rainy => 0.7,
+        }
+    }
+}
+
+/// The wind blowing across the map.
+///
+/// This is a single global vector, which shifts each day along with the weather.
+/// Signals, seeds and other light things are carried along with it.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Wind {
+    /// The direction that the wind is blowing towards, in world-space radians.
+    direction: f32,
+    /// The strength of the wind, between 0.0 (calm) and 1.0 (a gale).
+    speed: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Wind::CALM
+    }
 }
 
-/// Sets the weather for the day.
-fn set_daily_weather(in_game_time: Res<InGameTime>, mut current_weather: ResMut<CurrentWeather>) {
+impl Wind {
+    /// No wind at all.
+    pub const CALM: Wind = Wind {
+        direction: 0.,
+        speed: 0.,
+    };
+
+    /// The largest change in wind direction from one day to the next, in radians.
+    const MAX_DAILY_VEER: f32 = TAU / 6.;
+
+    /// Creates a new [`Wind`], clamping its `speed` to between 0.0 and 1.0.
+    pub fn new(direction: f32, speed: f32) -> Self {
+        Wind {
+            direction: direction.rem_euclid(TAU),
+            speed: speed.clamp(0., 1.),
+        }
+    }
+
+    /// The direction that the wind is blowing towards, in world-space radians.
+    pub fn direction(&self) -> f32 {
+        self.direction
+    }
+
+    /// The strength of the wind, between 0.0 (calm) and 1.0 (a gale).
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// The wind as a world-space vector in the XZ plane, whose length is the wind speed.
+    pub fn velocity(&self) -> Vec2 {
+        Vec2::new(self.direction.cos(), self.direction.sin()) * self.speed
+    }
+
+    /// How closely a step from `from` to `to` lines up with the wind.
+    ///
+    /// This is scaled by the wind speed, and ranges from `-speed` (directly upwind) to `speed` (directly downwind).
+    pub fn alignment(&self, from: Hex, to: Hex) -> f32 {
+        let step = xz_offset(from, to);
+        if step == Vec2::ZERO {
+            return 0.;
+        }
+
+        step.normalize().dot(self.velocity())
+    }
+
+    /// The hex direction that most closely matches the direction of the wind.
+    pub fn hex_direction(&self) -> Direction {
+        *Direction::ALL_DIRECTIONS
+            .iter()
+            .max_by(|a, b| {
+                let a = self.alignment(Hex::ZERO, Hex::ZERO.neighbor(**a));
+                let b = self.alignment(Hex::ZERO, Hex::ZERO.neighbor(**b));
+                a.total_cmp(&b)
+            })
+            .unwrap()
+    }
+
+    /// Randomly shifts the wind for a new day, based on the `weather`.
+    fn shift(&mut self, weather: Weather, rng: &mut impl Rng) {
+        let direction = self.direction + rng.gen_range(-Self::MAX_DAILY_VEER..Self::MAX_DAILY_VEER);
+        let speed = weather.typical_wind_speed() * rng.gen_range(0.5..1.5);
+
+        *self = Wind::new(direction, speed);
+    }
+}
+
+impl std::fmt::Display for Wind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.0}% towards {:.0}°",
+            self.speed * 100.,
+            self.direction.to_degrees()
+        )
+    }
+}
+
+/// Sets the weather and wind for the day.
+fn set_daily_weather(
+    in_game_time: Res<InGameTime>,
+    mut current_weather: ResMut<CurrentWeather>,
+    mut wind: ResMut<Wind>,
+    game_rules: Res<GameRules>,
+    mut system_rng: SystemRng,
+) {
     let current_day = in_game_time.elapsed_days() as u32;
     if current_weather.last_updated != current_day {
         current_weather.last_updated = current_day;
-        let rng = &mut rand::thread_rng();
-        current_weather.weather = Weather::random(rng);
+        let rng = system_rng.get("set_daily_weather");
+        current_weather.weather = Weather::random(rng, game_rules.weather_severity());
+        current_weather.days_without_rain = match current_weather.weather {
+            Weather::Rainy => 0,
+            _ => current_weather.days_without_rain + 1,
+        };
+        wind.shift(current_weather.weather, rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calm_wind_has_no_alignment() {
+        let neighbor = Hex::ZERO.neighbor(Direction::Top);
+        assert_eq!(Wind::CALM.alignment(Hex::ZERO, neighbor), 0.);
+    }
+
+    #[test]
+    fn wind_aligns_with_its_hex_direction() {
+        for angle in [0., 1., 2., 3., 4., 5., 6.] {
+            let wind = Wind::new(angle, 1.);
+            let downwind = Hex::ZERO.neighbor(wind.hex_direction());
+
+            assert!(wind.alignment(Hex::ZERO, downwind) > 0.);
+            assert!(wind.alignment(downwind, Hex::ZERO) < 0.);
+        }
+    }
+
+    #[test]
+    fn severe_weather_rains_less() {
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let rainy_days = |severity: f32| {
+            let rng = &mut SmallRng::seed_from_u64(0);
+            (0..1000)
+                .filter(|_| Weather::random(rng, severity) == Weather::Rainy)
+                .count()
+        };
+
+        assert!(rainy_days(2.) < rainy_days(1.));
+        assert!(rainy_days(1.) < rainy_days(0.5));
     }
 }
@@ -11,16 +11,25 @@ use crate::{
         recipe::RecipeManifest,
         CraftingBundle,
     },
+    factions::Faction,
     geometry::{Facing, MapGeometry, VoxelPos},
     graphics::InheritedMaterial,
     items::{inventory::Inventory, item_manifest::ItemManifest},
     organisms::{energy::StartingEnergy, OrganismBundle},
-    player_interaction::clipboard::ClipboardData,
+    player_interaction::{clipboard::ClipboardData, feedback::InteractionEvent},
     signals::Emitter,
+    simulation::rng::GlobalRng,
+    terrain::{
+        history::{TileEvent, TileEventKind},
+        terrain_manifest::{Terrain, TerrainManifest},
+    },
+    trading::TradingPost,
+    units::rest::ShelterOccupants,
 };
 
 use super::{
     logistic_buildings::{AbsorbsItems, ReleasesItems},
+    resource_nodes::ResourceNode,
     structure_assets::StructureHandles,
     structure_manifest::{Structure, StructureKind, StructureManifest},
     Landmark, StructureBundle,
@@ -28,14 +37,16 @@ use super::{
 
 /// An extension trait for [`Commands`] for working with structures.
 pub(crate) trait StructureCommandsExt {
-    /// Spawns a structure defined by `data` at `voxel_pos`.
+    /// Spawns a structure defined by `data` at `voxel_pos`, owned by `owner`.
     ///
+    /// Structures without an owner are wild.
     /// Has no effect if the tile position is already occupied by an existing structure.
     fn spawn_structure(
         &mut self,
         voxel_pos: VoxelPos,
         data: ClipboardData,
         starting_energy: StartingEnergy,
+        owner: Option<Faction>,
     );
 
     /// Despawns any structure at the provided `voxel_pos`.
@@ -43,10 +54,10 @@ pub(crate) trait StructureCommandsExt {
     /// Has no effect if the tile position is already empty.
     fn despawn_structure(&mut self, voxel_pos: VoxelPos);
 
-    /// Spawns a ghost with data defined by `data` at `voxel_pos`.
+    /// Spawns a ghost with data defined by `data` at `voxel_pos`, to be built by `owner`.
     ///
     /// Replaces any existing ghost.
-    fn spawn_ghost_structure(&mut self, voxel_pos: VoxelPos, data: ClipboardData);
+    fn spawn_ghost_structure(&mut self, voxel_pos: VoxelPos, data: ClipboardData, owner: Faction);
 
     /// Despawns any ghost at the provided `voxel_pos`.
     ///
@@ -65,11 +76,13 @@ impl<'w, 's> StructureCommandsExt for Commands<'w, 's> {
         voxel_pos: VoxelPos,
         data: ClipboardData,
         starting_energy: StartingEnergy,
+        owner: Option<Faction>,
     ) {
         self.add(SpawnStructureCommand {
             center: voxel_pos,
             data,
             starting_energy,
+            owner,
         });
     }
 
@@ -77,10 +90,11 @@ impl<'w, 's> StructureCommandsExt for Commands<'w, 's> {
         self.add(DespawnStructureCommand { center: voxel_pos });
     }
 
-    fn spawn_ghost_structure(&mut self, voxel_pos: VoxelPos, data: ClipboardData) {
+    fn spawn_ghost_structure(&mut self, voxel_pos: VoxelPos, data: ClipboardData, owner: Faction) {
         self.add(SpawnStructureGhostCommand {
             center: voxel_pos,
             data,
+            owner,
         });
     }
 
@@ -104,6 +118,8 @@ struct SpawnStructureCommand {
     data: ClipboardData,
     /// The amount of energy to give the organism.
     starting_energy: StartingEnergy,
+    /// The faction that owns the structure, if any.
+    owner: Option<Faction>,
 }
 
 impl Command for SpawnStructureCommand {
@@ -164,6 +180,10 @@ impl Command for SpawnStructureCommand {
 
         let structure_entity = world.spawn(structure_bundle).id();
 
+        if let Some(owner) = self.owner {
+            world.entity_mut(structure_entity).insert(owner);
+        }
+
         // PERF: these operations could be done in a single archetype move with more branching
         if let Some(organism_details) = &structure_data.organism_variety {
             let mut energy_pool = organism_details.energy_pool.clone();
@@ -172,8 +192,9 @@ impl Command for SpawnStructureCommand {
                     energy_pool.set_current(energy);
                 },
                 StartingEnergy::Random => {
-                    let rng = &mut rand::thread_rng();
-                    energy_pool.randomize(rng)
+                    // Commands are applied in order, so the shared generator stays deterministic
+                    let mut rng = world.get_resource_or_insert_with(|| GlobalRng::new(0));
+                    energy_pool.randomize(rng.get_mut())
                 },
                 StartingEnergy::Full => {},
                 StartingEnergy::NotAnOrganism => panic!("All organisms must have energy pools, and this variant should never be constructed for organisms."),
@@ -238,6 +259,16 @@ impl Command for SpawnStructureCommand {
                     })
                     .insert(Emitter::default());
             }
+            StructureKind::TradingPost { max_slot_count } => {
+                world
+                    .entity_mut(structure_entity)
+                    .insert(TradingPost::default())
+                    .insert(InputInventory::NULL)
+                    .insert(OutputInventory {
+                        inventory: Inventory::new(max_slot_count, None),
+                    })
+                    .insert(Emitter::default());
+            }
         }
 
         // TODO: yeet StructureKind and just do this everywhere
@@ -247,6 +278,36 @@ impl Command for SpawnStructureCommand {
                 .insert(vegetative_reproduction);
         }
 
+        if let Some(nursery) = &structure_data.nursery {
+            world.entity_mut(structure_entity).insert(nursery.clone());
+        }
+
+        if let Some(shelter) = &structure_data.shelter {
+            world
+                .entity_mut(structure_entity)
+                .insert(ShelterOccupants::new(shelter))
+                .insert(Emitter::default());
+        }
+
+        if let Some(nutrient_link) = &structure_data.nutrient_link {
+            world
+                .entity_mut(structure_entity)
+                .insert(nutrient_link.clone());
+        }
+
+        if let Some(resource_node_data) = &structure_data.resource_node {
+            world
+                .entity_mut(structure_entity)
+                .insert(ResourceNode::new(resource_node_data))
+                .insert(OutputInventory {
+                    inventory: Inventory::new_from_item(
+                        resource_node_data.item,
+                        resource_node_data.capacity,
+                    ),
+                })
+                .insert(Emitter::default());
+        }
+
         let mut geometry = world.resource_mut::<MapGeometry>();
         // We've already verified that we can build here, so we can safely unwrap at this point
         geometry
@@ -259,6 +320,11 @@ impl Command for SpawnStructureCommand {
                 structure_entity,
             )
             .unwrap();
+
+        world.send_event(TileEvent {
+            hex: self.center.hex,
+            kind: TileEventKind::StructureBuilt(structure_id),
+        });
     }
 }
 
@@ -294,15 +360,54 @@ impl Command for DespawnStructureCommand {
         let structure_entity = maybe_entity.unwrap();
         // Make sure to despawn all children, which represent the meshes stored in the loaded gltf scene.
         world.entity_mut(structure_entity).despawn_recursive();
+
+        world.send_event(TileEvent {
+            hex: self.center.hex,
+            kind: TileEventKind::StructureRemoved(structure_id),
+        });
     }
 }
 
+/// Can a structure of type `structure_id` be placed at `center`, given the terrain beneath it?
+///
+/// Organisms can be planted on any terrain, just as they can spread onto it on their own.
+/// Other structures need every terrain tile under their footprint to be buildable.
+/// Tiles whose terrain can't be found are treated as unbuildable.
+fn is_terrain_buildable(
+    world: &World,
+    center: VoxelPos,
+    structure_id: Id<Structure>,
+    facing: Facing,
+) -> bool {
+    let structure_data = world.resource::<StructureManifest>().get(structure_id);
+    if structure_data.organism_variety.is_some() {
+        return true;
+    }
+
+    let map_geometry = world.resource::<MapGeometry>();
+    let terrain_manifest = world.resource::<TerrainManifest>();
+
+    structure_data
+        .footprint
+        .normalized(facing, center)
+        .iter()
+        .all(|voxel_pos| {
+            map_geometry
+                .get_terrain(voxel_pos.hex)
+                .ok()
+                .and_then(|terrain_entity| world.get::<Id<Terrain>>(terrain_entity))
+                .is_some_and(|&terrain_id| terrain_manifest.get(terrain_id).buildable)
+        })
+}
+
 /// A [`Command`] used to spawn a ghost via [`StructureCommandsExt`].
 struct SpawnStructureGhostCommand {
     /// The tile position at which to spawn the structure.
     center: VoxelPos,
     /// Data about the structure to spawn.
     data: ClipboardData,
+    /// The faction that will build and own the structure.
+    owner: Faction,
 }
 
 impl Command for SpawnStructureGhostCommand {
@@ -313,6 +418,7 @@ impl Command for SpawnStructureGhostCommand {
         // Check that the tile is within the bounds of the map
         if !map_geometry.is_valid(self.center.hex) {
             warn!("Tried to spawn a structure outside of the map bounds.");
+            InteractionEvent::PlacementFailed(self.center).send(world);
             return;
         }
 
@@ -328,6 +434,13 @@ impl Command for SpawnStructureGhostCommand {
             .is_err()
         {
             warn!("Tried to spawn a structure in an occupied location.");
+            InteractionEvent::PlacementFailed(self.center).send(world);
+            return;
+        }
+
+        if !is_terrain_buildable(world, self.center, structure_id, facing) {
+            warn!("Tried to spawn a structure on unbuildable terrain.");
+            InteractionEvent::PlacementFailed(self.center).send(world);
             return;
         }
 
@@ -341,11 +454,21 @@ impl Command for SpawnStructureGhostCommand {
             }
         }
 
+        // Ghosts are placed again every frame while the button is held, which is not worth reporting
+        let mut replaced_identical_ghost = false;
+
         for ghost_entity in existing_ghosts {
             let facing = *world.entity(ghost_entity).get::<Facing>().unwrap();
             let center = *world.entity(ghost_entity).get::<VoxelPos>().unwrap();
             let structure_id = *world.entity(ghost_entity).get::<Id<Structure>>().unwrap();
 
+            if center == self.center
+                && facing == self.data.facing
+                && structure_id == self.data.structure_id
+            {
+                replaced_identical_ghost = true;
+            }
+
             let structure_manifest = world.resource::<StructureManifest>();
             let footprint = structure_manifest.footprint(structure_id).clone();
 
@@ -382,6 +505,7 @@ impl Command for SpawnStructureGhostCommand {
                 inherited_material,
                 world_pos,
             ))
+            .insert(self.owner)
             .id();
 
         // Update the index to reflect the new state
@@ -394,6 +518,10 @@ impl Command for SpawnStructureGhostCommand {
                 .add_ghost_structure(facing, self.center, footprint, ghost_entity)
                 .unwrap();
         });
+
+        if !replaced_identical_ghost {
+            InteractionEvent::PlacementSucceeded(self.center).send(world);
+        }
     }
 }
 
@@ -454,7 +582,8 @@ impl Command for SpawnStructurePreviewCommand {
         // Check that the tiles needed are appropriate.
         let forbidden = geometry
             .is_space_available(self.center, &structure_data.footprint, self.data.facing)
-            .is_err();
+            .is_err()
+            || !is_terrain_buildable(world, self.center, structure_id, self.data.facing);
 
         // Fetch the scene and material to use
         let structure_handles = world.resource::<StructureHandles>();
@@ -6,10 +6,9 @@ use crate::{
     crafting::{
         inventories::{InputInventory, OutputInventory},
         item_tags::ItemKind,
-        recipe::RecipeInput,
     },
     geometry::{Facing, Height, MapGeometry, VoxelPos},
-    items::item_manifest::ItemManifest,
+    items::{inventory::Inventory, item_manifest::ItemManifest, ItemCount},
     litter::Litter,
     signals::{Emitter, SignalStrength, SignalType},
     simulation::SimulationSet,
@@ -51,20 +50,14 @@ fn release_items(
         let litter_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
         let mut litter = litter_query.get_mut(litter_entity).unwrap();
 
-        let cloned_inventory = input_inventory.clone();
-        for item_slot in cloned_inventory.iter() {
-            let item_count = item_slot.item_count();
-
-            if litter
-                .contents
-                .add_item_all_or_nothing(&item_count, &item_manifest)
-                .is_ok()
-            {
-                let recipe_input = RecipeInput::Exact(vec![item_count]);
-                input_inventory
-                    .consume_items(&recipe_input, &item_manifest)
-                    .unwrap();
-            }
+        let item_counts = non_empty_item_counts(input_inventory.inventory());
+        for item_count in item_counts {
+            // Stacks that don't fit stay put until the pile is cleared
+            let _ = input_inventory.inventory_mut().transfer_items(
+                &[item_count],
+                &mut litter.contents,
+                &item_manifest,
+            );
         }
     }
 }
@@ -87,17 +80,12 @@ fn absorb_items(
         let litter_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
         let mut litter = litter_query.get_mut(litter_entity).unwrap();
 
-        let on_ground = litter.contents.clone();
-
-        for item_slot in on_ground.iter() {
-            let item_count = item_slot.item_count();
-
-            if output_inventory
-                .add_item_all_or_nothing(&item_count, &item_manifest)
-                .is_ok()
-            {
-                litter.contents.try_remove_item(&item_count).unwrap();
-            }
+        for item_count in non_empty_item_counts(&litter.contents) {
+            let _ = litter.contents.transfer_items(
+                &[item_count],
+                &mut output_inventory,
+                &item_manifest,
+            );
         }
 
         // Only absorb floating items if the structure is tall enough.
@@ -105,21 +93,26 @@ fn absorb_items(
         let water_depth = water_depth_query.get(terrain_entity).unwrap();
 
         if Height::from(footprint.max_height()) > water_depth.surface_water_depth() {
-            let floating = litter.contents.clone();
-            for item_slot in floating.iter() {
-                let item_count = item_slot.item_count();
-
-                if output_inventory
-                    .add_item_all_or_nothing(&item_count, &item_manifest)
-                    .is_ok()
-                {
-                    litter.contents.try_remove_item(&item_count).unwrap();
-                }
+            for item_count in non_empty_item_counts(&litter.contents) {
+                let _ = litter.contents.transfer_items(
+                    &[item_count],
+                    &mut output_inventory,
+                    &item_manifest,
+                );
             }
         }
     }
 }
 
+/// The contents of each occupied slot in `inventory`.
+fn non_empty_item_counts(inventory: &Inventory) -> Vec<ItemCount> {
+    inventory
+        .iter()
+        .filter(|item_slot| !item_slot.is_empty())
+        .map(|item_slot| item_slot.item_count())
+        .collect()
+}
+
 /// Sets the emitters for logistic buildings.
 fn logistic_buildings_signals(
     mut release_query: Query<
@@ -21,12 +21,14 @@ use crate::{
 
 use self::{
     logistic_buildings::LogisticsPlugin,
+    resource_nodes::ResourceNodePlugin,
     structure_assets::StructureHandles,
     structure_manifest::{RawStructureManifest, Structure},
 };
 
 pub(crate) mod commands;
 pub(crate) mod logistic_buildings;
+pub mod resource_nodes;
 mod structure_assets;
 pub mod structure_manifest;
 
@@ -37,6 +39,7 @@ impl Plugin for StructuresPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ManifestPlugin::<RawStructureManifest>::new())
             .add_plugins(LogisticsPlugin)
+            .add_plugins(ResourceNodePlugin)
             .add_asset_collection::<StructureHandles>();
     }
 }
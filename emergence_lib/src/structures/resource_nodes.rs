@@ -0,0 +1,251 @@
+//! Wild resources that units can gather from, such as springs and berry patches.
+//!
+//! Resource nodes hold a limited stock of a single item, which regrows slowly over time.
+//! Once the player marks a node for harvest, its stock is made available to units,
+//! who will carry it off just like the output of any other structure.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::{inventories::OutputInventory, item_tags::ItemKind},
+    items::{
+        item_manifest::{Item, ItemManifest},
+        ledger::{ItemLedger, ItemSource},
+        ItemCount,
+    },
+    signals::{Emitter, SignalStrength, SignalType},
+    simulation::{time::InGameTime, SimulationSet},
+};
+
+/// Systems that deplete and regrow resource nodes.
+pub(super) struct ResourceNodePlugin;
+
+impl Plugin for ResourceNodePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                regrow_resource_nodes,
+                harvest_resource_nodes.after(regrow_resource_nodes),
+                resource_node_signals.after(harvest_resource_nodes),
+            )
+                .in_set(SimulationSet),
+        );
+    }
+}
+
+/// The harvestable resources provided by a structure.
+///
+/// This is set on a per-structure basis in the manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceNodeData {
+    /// The item that can be harvested.
+    pub item: Id<Item>,
+    /// The maximum number of items that can be stockpiled at once.
+    pub capacity: u32,
+    /// The number of items that regrow each in-game day.
+    pub regrowth_per_day: f32,
+}
+
+/// The unprocessed equivalent of [`ResourceNodeData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawResourceNodeData {
+    /// The item that can be harvested.
+    pub item: String,
+    /// The maximum number of items that can be stockpiled at once.
+    pub capacity: u32,
+    /// The number of items that regrow each in-game day.
+    pub regrowth_per_day: f32,
+}
+
+impl From<RawResourceNodeData> for ResourceNodeData {
+    fn from(raw: RawResourceNodeData) -> Self {
+        Self {
+            item: Id::from_name(raw.item),
+            capacity: raw.capacity,
+            regrowth_per_day: raw.regrowth_per_day,
+        }
+    }
+}
+
+/// A wild source of items, which depletes when harvested and regrows over time.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct ResourceNode {
+    /// The item that can be harvested.
+    item_id: Id<Item>,
+    /// The number of items currently available.
+    ///
+    /// This is fractional to allow for gradual regrowth.
+    stock: f32,
+    /// The maximum number of items that can be stockpiled at once.
+    capacity: u32,
+    /// The number of items that regrow each in-game day.
+    regrowth_per_day: f32,
+}
+
+impl ResourceNode {
+    /// Creates a new, fully stocked [`ResourceNode`].
+    pub fn new(data: &ResourceNodeData) -> Self {
+        ResourceNode {
+            item_id: data.item,
+            stock: data.capacity as f32,
+            capacity: data.capacity,
+            regrowth_per_day: data.regrowth_per_day,
+        }
+    }
+
+    /// The item that can be harvested.
+    pub fn item_id(&self) -> Id<Item> {
+        self.item_id
+    }
+
+    /// The number of whole items that are currently available to harvest.
+    pub fn stock(&self) -> u32 {
+        self.stock.floor() as u32
+    }
+
+    /// The maximum number of items that can be stockpiled at once.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Is there nothing left to harvest?
+    pub fn is_depleted(&self) -> bool {
+        self.stock() == 0
+    }
+
+    /// Regrows resources over the provided number of in-game days.
+    fn regrow(&mut self, days: f32) {
+        self.stock = (self.stock + self.regrowth_per_day * days).min(self.capacity as f32);
+    }
+
+    /// Removes up to `count` whole items from the stock, returning the number actually removed.
+    fn take(&mut self, count: u32) -> u32 {
+        let taken = count.min(self.stock());
+        self.stock -= taken as f32;
+        taken
+    }
+
+    /// Pretty formatting for this type.
+    pub(crate) fn display(&self, item_manifest: &ItemManifest) -> String {
+        format!(
+            "{} ({}/{}, {:.1} per day)",
+            item_manifest.name(self.item_id),
+            self.stock(),
+            self.capacity,
+            self.regrowth_per_day
+        )
+    }
+}
+
+/// Marker component for resource nodes that the player wants harvested.
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub(crate) struct MarkedForHarvest;
+
+/// Regrows all resource nodes, whether or not they are being harvested.
+fn regrow_resource_nodes(
+    mut query: Query<&mut ResourceNode>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+) {
+    let delta_days = time.delta().as_secs_f32() / in_game_time.seconds_per_day();
+
+    for mut resource_node in query.iter_mut() {
+        resource_node.regrow(delta_days);
+    }
+}
+
+/// Moves the stock of resource nodes that are marked for harvest into their output inventory,
+/// where units can pick them up.
+fn harvest_resource_nodes(
+    mut query: Query<(&mut ResourceNode, &mut OutputInventory), With<MarkedForHarvest>>,
+    item_manifest: Res<ItemManifest>,
+    mut item_ledger: ResMut<ItemLedger>,
+) {
+    for (mut resource_node, mut output_inventory) in query.iter_mut() {
+        if resource_node.is_depleted() {
+            continue;
+        }
+
+        let item_id = resource_node.item_id();
+        let space = output_inventory.remaining_space_for_item(item_id, &item_manifest);
+        let taken = resource_node.take(space);
+        if taken > 0 {
+            // We checked that there was space above
+            output_inventory
+                .try_add_item(&ItemCount::new(item_id, taken), &item_manifest)
+                .unwrap();
+            item_ledger.record_produced(item_id, taken, ItemSource::Harvesting);
+        }
+    }
+}
+
+/// Causes resource nodes that are being harvested to ask for their contents to be carried away.
+fn resource_node_signals(
+    mut query: Query<
+        (&mut Emitter, &OutputInventory, Option<&MarkedForHarvest>),
+        With<ResourceNode>,
+    >,
+) {
+    /// Controls how strong the signal is for resource nodes.
+    const RESOURCE_NODE_SIGNAL_STRENGTH: f32 = 10.;
+
+    let signal_strength = SignalStrength::new(RESOURCE_NODE_SIGNAL_STRENGTH);
+
+    for (mut emitter, output_inventory, maybe_marked) in query.iter_mut() {
+        emitter.signals.clear();
+
+        if maybe_marked.is_none() {
+            continue;
+        }
+
+        for item_slot in output_inventory.iter() {
+            if !item_slot.is_empty() {
+                let signal_type = SignalType::Push(ItemKind::Single(item_slot.item_id()));
+                emitter.signals.push((signal_type, signal_strength));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A resource node used for testing.
+    fn berry_bush() -> ResourceNode {
+        ResourceNode::new(&ResourceNodeData {
+            item: Id::from_name("berry".to_string()),
+            capacity: 5,
+            regrowth_per_day: 2.,
+        })
+    }
+
+    #[test]
+    fn harvesting_depletes_resource_nodes() {
+        let mut node = berry_bush();
+        assert_eq!(node.stock(), 5);
+
+        assert_eq!(node.take(3), 3);
+        assert_eq!(node.stock(), 2);
+        assert_eq!(node.take(3), 2);
+        assert!(node.is_depleted());
+    }
+
+    #[test]
+    fn resource_nodes_regrow_up_to_capacity() {
+        let mut node = berry_bush();
+        node.take(5);
+
+        node.regrow(0.25);
+        assert_eq!(node.stock(), 0);
+        node.regrow(0.25);
+        assert_eq!(node.stock(), 1);
+
+        node.regrow(10.);
+        assert_eq!(node.stock(), node.capacity());
+    }
+}
@@ -1,13 +1,13 @@
 //! Asset loading for structures
 
 use crate::{
-    asset_management::{manifest::Id, AssetState, Loadable},
+    asset_management::{manifest::Id, models::ModelManifest, AssetState, Loadable},
     enum_iter::IterableEnum,
     geometry::hexagonal_column,
     player_interaction::selection::ObjectInteraction,
     structures::structure_manifest::{Structure, StructureManifest},
 };
-use bevy::{asset::LoadState, prelude::*, utils::HashMap};
+use bevy::{asset::UntypedHandle, prelude::*, utils::HashMap};
 
 /// Stores material handles for the different tile types.
 #[derive(Resource)]
@@ -49,11 +49,12 @@ impl Loadable for StructureHandles {
 
         let structure_manifest = world.resource::<StructureManifest>();
         let structure_names = structure_manifest.names();
+        let model_manifest = world.resource::<ModelManifest>();
         let asset_server = world.resource::<AssetServer>();
 
         for name in structure_names {
             let structure_id = Id::from_name(name.to_string());
-            let structure_path = format!("structures/{name}.gltf#Scene0");
+            let structure_path = model_manifest.scene_path("structures", name);
             let scene = asset_server.load(structure_path);
             handles.scenes.insert(structure_id, scene);
         }
@@ -61,21 +62,10 @@ impl Loadable for StructureHandles {
         world.insert_resource(handles);
     }
 
-    fn load_state(&self, asset_server: &AssetServer) -> Option<LoadState> {
-        for (structure, scene_handle) in &self.scenes {
-            let scene_load_state = asset_server.get_load_state(scene_handle);
-
-            if scene_load_state != Some(LoadState::Loaded) {
-                let maybe_path = asset_server.get_path(scene_handle);
-                let path = maybe_path
-                    .map(|p| format!("{:?}", p.path()))
-                    .unwrap_or("unknown_path".to_string());
-
-                info!("Structure {structure:?}'s scene at {path} is {scene_load_state:?}");
-                return scene_load_state;
-            }
-        }
-
-        Some(LoadState::Loaded)
+    fn handles(&self) -> Vec<UntypedHandle> {
+        self.scenes
+            .values()
+            .map(|scene_handle| scene_handle.clone_weak().untyped())
+            .collect()
     }
 }
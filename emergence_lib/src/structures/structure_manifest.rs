@@ -3,12 +3,17 @@
 use crate::{
     asset_management::manifest::{loader::IsRawManifest, Id, Manifest},
     construction::{ConstructionData, ConstructionStrategy, RawConstructionStrategy},
-    crafting::recipe::{ActiveRecipe, RawActiveRecipe},
+    crafting::{
+        hatching::Nursery,
+        recipe::{ActiveRecipe, RawActiveRecipe},
+    },
     items::item_manifest::Item,
     organisms::{
+        nutrient_network::NutrientLink,
         vegetative_reproduction::{RawVegetativeReproduction, VegetativeReproduction},
         OrganismId, OrganismVariety, RawOrganismVariety,
     },
+    units::rest::Shelter,
     water::roots::RootZone,
 };
 use bevy::{
@@ -18,7 +23,10 @@ use bevy::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::Footprint;
+use super::{
+    resource_nodes::{RawResourceNodeData, ResourceNodeData},
+    Footprint,
+};
 
 /// The marker type for [`Id<Structure>`](super::Id).
 #[derive(Reflect, Clone, Copy, PartialEq, Eq)]
@@ -75,6 +83,16 @@ pub struct StructureData {
     pub can_walk_through: bool,
     /// Can units walk on top of this structure?
     pub can_walk_on_roof: bool,
+    /// The wild resources that can be harvested from this structure, if any.
+    ///
+    /// This should only be used for structures that do not otherwise have an inventory.
+    pub resource_node: Option<ResourceNodeData>,
+    /// Does this structure speed up hatching at nearby crafters? If so, how?
+    pub nursery: Option<Nursery>,
+    /// Can units rest inside of this structure? If so, how many?
+    pub shelter: Option<Shelter>,
+    /// Does this structure connect nearby living structures into a nutrient network? If so, how?
+    pub nutrient_link: Option<NutrientLink>,
 }
 
 #[cfg(test)]
@@ -91,6 +109,10 @@ impl StructureData {
             root_zone: None,
             can_walk_through: true,
             can_walk_on_roof: false,
+            resource_node: None,
+            nursery: None,
+            shelter: None,
+            nutrient_link: None,
         }
     }
 
@@ -106,6 +128,10 @@ impl StructureData {
             root_zone: None,
             can_walk_through: true,
             can_walk_on_roof: false,
+            resource_node: None,
+            nursery: None,
+            shelter: None,
+            nutrient_link: None,
         }
     }
 
@@ -121,6 +147,10 @@ impl StructureData {
             root_zone: None,
             can_walk_through: false,
             can_walk_on_roof: false,
+            resource_node: None,
+            nursery: None,
+            shelter: None,
+            nutrient_link: None,
         }
     }
 }
@@ -148,6 +178,17 @@ pub struct RawStructureData {
     pub can_walk_through: bool,
     /// Can units walk on top of this structure?
     pub can_walk_on_roof: bool,
+    /// The wild resources that can be harvested from this structure, if any.
+    pub resource_node: Option<RawResourceNodeData>,
+    /// Does this structure speed up hatching at nearby crafters? If so, how?
+    pub nursery: Option<Nursery>,
+    /// Can units rest inside of this structure? If so, how many?
+    pub shelter: Option<Shelter>,
+    /// Does this structure connect nearby living structures into a nutrient network? If so, how?
+    pub nutrient_link: Option<NutrientLink>,
+    /// Descriptive text shown in the codex, if any.
+    #[serde(default)]
+    pub flavor_text: Option<String>,
 }
 
 impl From<RawStructureData> for StructureData {
@@ -162,6 +203,10 @@ impl From<RawStructureData> for StructureData {
             root_zone: raw.root_zone,
             can_walk_through: raw.can_walk_through,
             can_walk_on_roof: raw.can_walk_on_roof,
+            resource_node: raw.resource_node.map(Into::into),
+            nursery: raw.nursery,
+            shelter: raw.shelter,
+            nutrient_link: raw.nutrient_link,
         }
     }
 }
@@ -189,6 +234,11 @@ pub enum StructureKind {
     Releaser,
     /// A structure that takes in items.
     Absorber,
+    /// A structure where goods are exchanged with visiting caravans.
+    TradingPost {
+        /// The number of slots used to hold goods received from trades.
+        max_slot_count: usize,
+    },
 }
 
 /// The unprocessed equivalent of [`StructureKind`].
@@ -214,6 +264,11 @@ pub enum RawStructureKind {
     Releaser,
     /// A structure that takes in items.
     Absorber,
+    /// A structure where goods are exchanged with visiting caravans.
+    TradingPost {
+        /// The number of slots used to hold goods received from trades.
+        max_slot_count: usize,
+    },
 }
 
 impl From<RawStructureKind> for StructureKind {
@@ -233,6 +288,9 @@ impl From<RawStructureKind> for StructureKind {
             RawStructureKind::Landmark => Self::Landmark,
             RawStructureKind::Releaser => Self::Releaser,
             RawStructureKind::Absorber => Self::Absorber,
+            RawStructureKind::TradingPost { max_slot_count } => {
+                Self::TradingPost { max_slot_count }
+            }
         }
     }
 }
@@ -292,9 +350,10 @@ impl IsRawManifest for RawStructureManifest {
         let mut manifest = Manifest::new();
 
         for (raw_id, raw_data) in self.structure_types.clone() {
+            let flavor_text = raw_data.flavor_text.clone();
             let data = raw_data.into();
 
-            manifest.insert(raw_id, data)
+            manifest.insert_with_flavor_text(raw_id, data, flavor_text)
         }
 
         manifest
@@ -0,0 +1,188 @@
+//! Temperature varies across the map and over time, and organisms and recipes respond to it.
+//!
+//! Each tile has a target temperature, set by the season, the time of day and its height.
+//! Nearby water dampens these swings, keeping wet tiles mild.
+//! Actual temperatures drift towards this target and towards those of neighboring tiles,
+//! smoothing out sharp differences across the map.
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+use derive_more::{Add, Sub};
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{Height, MapGeometry, VoxelPos},
+    simulation::{time::InGameTime, SimulationSet},
+    terrain::terrain_manifest::Terrain,
+    water::WaterDepth,
+};
+
+/// Computes the temperature of each tile.
+pub(super) struct TemperaturePlugin;
+
+impl Plugin for TemperaturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, update_temperature.in_set(SimulationSet));
+    }
+}
+
+/// The temperature of a tile, in degrees Celsius.
+#[derive(
+    Component, Debug, Clone, Copy, PartialEq, PartialOrd, Add, Sub, Serialize, Deserialize,
+)]
+pub struct Temperature(pub f32);
+
+impl Temperature {
+    /// The temperature of a tile at sea level, averaged over the day and year.
+    pub const MILD: Temperature = Temperature(15.);
+
+    /// The difference between the average temperature and that of midsummer or midwinter.
+    const SEASONAL_VARIATION: f32 = 12.;
+
+    /// The difference between the daily average temperature and that of the warmest or coldest time of day.
+    const DAILY_VARIATION: f32 = 6.;
+
+    /// The drop in temperature for each unit of height.
+    const LAPSE_RATE: f32 = 0.6;
+
+    /// The fraction of seasonal and daily variation that is removed by standing water.
+    const WATER_DAMPING: f32 = 0.6;
+
+    /// Water tables closer to the surface than this dampen temperature swings, although less than standing water.
+    const SHALLOW_WATER_DEPTH: Height = Height(1.);
+
+    /// The fraction of the year at which it is warmest: the middle of summer.
+    const WARMEST_FRACTION_OF_YEAR: f32 = 0.375;
+
+    /// The fraction of the day at which it is warmest: the middle of the afternoon.
+    const WARMEST_FRACTION_OF_DAY: f32 = 0.45;
+
+    /// Computes the temperature that a tile will tend towards, ignoring the influence of its neighbors.
+    fn target(
+        fraction_of_year: f32,
+        fraction_of_day: f32,
+        height: Height,
+        water_depth: &WaterDepth,
+    ) -> Temperature {
+        let seasonal =
+            (std::f32::consts::TAU * (fraction_of_year - Self::WARMEST_FRACTION_OF_YEAR)).cos();
+        let daily =
+            (std::f32::consts::TAU * (fraction_of_day - Self::WARMEST_FRACTION_OF_DAY)).cos();
+
+        let damping = match water_depth {
+            WaterDepth::Flooded(..) => Self::WATER_DAMPING,
+            WaterDepth::Underground(depth) if *depth < Self::SHALLOW_WATER_DEPTH => {
+                Self::WATER_DAMPING / 2.
+            }
+            WaterDepth::Underground(..) | WaterDepth::Dry => 0.,
+        };
+
+        let variation =
+            (Self::SEASONAL_VARIATION * seasonal + Self::DAILY_VARIATION * daily) * (1. - damping);
+
+        Temperature(Self::MILD.0 + variation - Self::LAPSE_RATE * height.0)
+    }
+}
+
+impl Default for Temperature {
+    fn default() -> Self {
+        Temperature::MILD
+    }
+}
+
+impl Display for Temperature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1}°C", self.0)
+    }
+}
+
+/// The fraction of the way to its target that a tile's temperature moves each in-game day.
+const RELAXATION_RATE: f32 = 8.;
+
+/// How much weight is given to the temperature of neighboring tiles, rather than the tile's own target.
+///
+/// This should be between 0.0 and 1.0.
+const SPATIAL_SMOOTHING: f32 = 0.5;
+
+/// Moves the temperature of each tile towards its target, blended with that of its neighbors.
+fn update_temperature(
+    mut terrain_query: Query<(&VoxelPos, &WaterDepth, &mut Temperature), With<Id<Terrain>>>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+) {
+    let delta_days = time.delta().as_secs_f32() / in_game_time.seconds_per_day();
+    let relaxation = (RELAXATION_RATE * delta_days).min(1.);
+    if relaxation <= 0. {
+        return;
+    }
+
+    let current_temperatures: HashMap<Hex, Temperature> = terrain_query
+        .iter()
+        .map(|(voxel_pos, _, temperature)| (voxel_pos.hex, *temperature))
+        .collect();
+
+    let fraction_of_year = in_game_time.fraction_of_year();
+    let fraction_of_day = in_game_time.fraction_of_day();
+
+    for (voxel_pos, water_depth, mut temperature) in terrain_query.iter_mut() {
+        let target = Temperature::target(
+            fraction_of_year,
+            fraction_of_day,
+            voxel_pos.height(),
+            water_depth,
+        );
+
+        let neighbor_temperatures: Vec<f32> = map_geometry
+            .valid_neighbors(voxel_pos.hex)
+            .filter_map(|neighbor| current_temperatures.get(&neighbor))
+            .map(|temperature| temperature.0)
+            .collect();
+
+        let blended_target = if neighbor_temperatures.is_empty() {
+            target.0
+        } else {
+            let neighbor_average =
+                neighbor_temperatures.iter().sum::<f32>() / neighbor_temperatures.len() as f32;
+            target.0 * (1. - SPATIAL_SMOOTHING) + neighbor_average * SPATIAL_SMOOTHING
+        };
+
+        temperature.0 += (blended_target - temperature.0) * relaxation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summer_is_warmer_than_winter() {
+        let summer = Temperature::target(0.375, 0.45, Height(0.), &WaterDepth::Dry);
+        let winter = Temperature::target(0.875, 0.45, Height(0.), &WaterDepth::Dry);
+
+        assert!(summer > winter);
+    }
+
+    #[test]
+    fn high_ground_is_colder() {
+        let low = Temperature::target(0., 0., Height(0.), &WaterDepth::Dry);
+        let high = Temperature::target(0., 0., Height(10.), &WaterDepth::Dry);
+
+        assert!(low > high);
+    }
+
+    #[test]
+    fn water_dampens_temperature_swings() {
+        let flooded = WaterDepth::Flooded(Height(1.));
+
+        let dry_swing = Temperature::target(0.375, 0.45, Height(0.), &WaterDepth::Dry)
+            - Temperature::target(0.875, 0.95, Height(0.), &WaterDepth::Dry);
+        let wet_swing = Temperature::target(0.375, 0.45, Height(0.), &flooded)
+            - Temperature::target(0.875, 0.95, Height(0.), &flooded);
+
+        assert!(wet_swing < dry_swing);
+    }
+}
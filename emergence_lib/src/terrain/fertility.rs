@@ -0,0 +1,306 @@
+//! Dead biomass decomposes into soil fertility, which in turn speeds the growth of rooted organisms.
+//!
+//! Compostable litter left lying on a tile slowly rots away, enriching the soil beneath it.
+//! Living units also return nutrients to the soil wherever they go,
+//! so the routes that they travel most often slowly become lush.
+//! Fertility is drawn down by the organisms growing on the tile,
+//! and slowly returns to the baseline of the terrain type on its own.
+
+use std::fmt::Display;
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    items::{item_manifest::ItemManifest, ItemCount},
+    litter::Litter,
+    simulation::{
+        game_rules::GameRules,
+        time::{Days, InGameTime},
+    },
+    terrain::terrain_manifest::{Terrain, TerrainManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+};
+
+/// The fertility of the soil on a terrain tile.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct SoilFertility(f32);
+
+impl SoilFertility {
+    /// The maximum fertility that soil can have.
+    pub const MAX: SoilFertility = SoilFertility(100.);
+
+    /// The largest speedup to growth granted by perfectly fertile soil.
+    ///
+    /// A value of 0.5 means that organisms grow up to 50% faster.
+    const MAX_GROWTH_BONUS: f32 = 0.5;
+
+    /// Creates a new [`SoilFertility`], clamped to be between 0 and [`SoilFertility::MAX`].
+    pub fn new(value: f32) -> Self {
+        SoilFertility(value.clamp(0., Self::MAX.0))
+    }
+
+    /// The current fertility of the soil.
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+
+    /// Adds fertility to the soil, up to [`SoilFertility::MAX`].
+    pub fn add(&mut self, amount: f32) {
+        *self = SoilFertility::new(self.0 + amount);
+    }
+
+    /// Removes up to `amount` of fertility from the soil, returning the amount actually removed.
+    pub fn drain(&mut self, amount: f32) -> f32 {
+        let drained = amount.min(self.0);
+        self.0 -= drained;
+        drained
+    }
+
+    /// Moves the fertility of the soil `fraction` of the way back towards its `baseline`.
+    ///
+    /// Enriched soil leaches away, while depleted soil slowly recovers.
+    pub fn settle_towards(&mut self, baseline: f32, fraction: f32) {
+        let fraction = fraction.clamp(0., 1.);
+        *self = SoilFertility::new(self.0 + (baseline - self.0) * fraction);
+    }
+
+    /// The factor by which growth is sped up on this soil.
+    ///
+    /// This is 1.0 for barren soil, scaling linearly up to `1 + MAX_GROWTH_BONUS` for perfectly fertile soil.
+    pub fn growth_multiplier(&self) -> f32 {
+        1. + Self::MAX_GROWTH_BONUS * self.0 / Self::MAX.0
+    }
+}
+
+impl Display for SoilFertility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}/{:.0}", self.0, Self::MAX.0)
+    }
+}
+
+/// Tracks how far along the compostable contents of a pile of litter are in rotting away.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub(crate) struct Decomposition {
+    /// The time spent decomposing the current item.
+    progress: Days,
+}
+
+impl Default for Decomposition {
+    fn default() -> Self {
+        Decomposition {
+            progress: Days::ZERO,
+        }
+    }
+}
+
+impl Decomposition {
+    /// The time it takes for a single compostable item to decompose.
+    const TIME_PER_ITEM: Days = Days(0.25);
+
+    /// Advances decomposition by `delta`, returning `true` if an item has finished decomposing.
+    fn advance(&mut self, delta: Days) -> bool {
+        self.progress += delta;
+
+        if self.progress >= Self::TIME_PER_ITEM {
+            self.progress = Days::ZERO;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The amount of fertility added to the soil by each decomposed item.
+const FERTILITY_PER_ITEM: f32 = 5.;
+
+/// The fraction of the gap between soil fertility and its baseline that is closed each day.
+const SETTLING_RATE: f32 = 0.05;
+
+/// Compostable litter decomposes over time, adding fertility to the soil beneath it.
+///
+/// Nothing decomposes if [`GameRules::spoilage`] is turned off.
+pub(super) fn decompose_litter(
+    mut litter_query: Query<(&mut Litter, &mut Decomposition, &VoxelPos)>,
+    mut soil_query: Query<&mut SoilFertility>,
+    item_manifest: Res<ItemManifest>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+    game_rules: Res<GameRules>,
+) {
+    if !game_rules.spoilage {
+        return;
+    }
+
+    let delta = Days(time.delta().as_secs_f32() / in_game_time.seconds_per_day());
+
+    for (mut litter, mut decomposition, voxel_pos) in litter_query.iter_mut() {
+        let maybe_compostable = litter
+            .contents
+            .iter()
+            .map(|item_slot| item_slot.item_id())
+            .find(|item_id| item_manifest.get(*item_id).compostable);
+
+        let Some(item_id) = maybe_compostable else {
+            *decomposition = Decomposition::default();
+            continue;
+        };
+
+        if !decomposition.advance(delta) {
+            continue;
+        }
+
+        if litter
+            .contents
+            .try_remove_item(&ItemCount::one(item_id))
+            .is_err()
+        {
+            continue;
+        }
+
+        if let Ok(terrain_entity) = map_geometry.get_terrain(voxel_pos.hex) {
+            if let Ok(mut soil_fertility) = soil_query.get_mut(terrain_entity) {
+                soil_fertility.add(FERTILITY_PER_ITEM);
+            }
+        }
+    }
+}
+
+/// Units enrich the soil of each tile that they step onto, by the [`nutrients_per_step`](crate::units::unit_manifest::UnitData::nutrients_per_step) of their species.
+///
+/// Units that stand still, however long for, add nothing.
+pub(super) fn deposit_unit_nutrients(
+    unit_query: Query<(Ref<VoxelPos>, &Id<Unit>), Changed<VoxelPos>>,
+    mut soil_query: Query<&mut SoilFertility>,
+    unit_manifest: Res<UnitManifest>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for (voxel_pos, &unit_id) in unit_query.iter() {
+        // Being born is not a step
+        if voxel_pos.is_added() {
+            continue;
+        }
+
+        let nutrients_per_step = unit_manifest.get(unit_id).nutrients_per_step;
+        if nutrients_per_step <= 0. {
+            continue;
+        }
+
+        if let Ok(terrain_entity) = map_geometry.get_terrain(voxel_pos.hex) {
+            if let Ok(mut soil_fertility) = soil_query.get_mut(terrain_entity) {
+                soil_fertility.add(nutrients_per_step);
+            }
+        }
+    }
+}
+
+/// Soil fertility slowly returns to the [`base_fertility`](crate::terrain::terrain_manifest::TerrainData::base_fertility) of its terrain type.
+pub(super) fn settle_soil_fertility(
+    mut soil_query: Query<(&mut SoilFertility, &Id<Terrain>)>,
+    terrain_manifest: Res<TerrainManifest>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+) {
+    let delta_days = time.delta().as_secs_f32() / in_game_time.seconds_per_day();
+
+    for (mut soil_fertility, &terrain_id) in soil_query.iter_mut() {
+        let baseline = terrain_manifest.get(terrain_id).base_fertility;
+        // Avoid triggering change detection on settled soil
+        if soil_fertility.value() != baseline {
+            soil_fertility.settle_towards(baseline, SETTLING_RATE * delta_days);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::{basic_needs::Diet, unit_manifest::UnitData};
+
+    #[test]
+    fn soil_fertility_is_bounded() {
+        let mut soil_fertility = SoilFertility::new(-10.);
+        assert_eq!(soil_fertility.value(), 0.);
+
+        soil_fertility.add(1000.);
+        assert_eq!(soil_fertility, SoilFertility::MAX);
+
+        assert_eq!(soil_fertility.drain(30.), 30.);
+        assert_eq!(soil_fertility.drain(1000.), 70.);
+        assert_eq!(soil_fertility.value(), 0.);
+    }
+
+    #[test]
+    fn fertile_soil_speeds_growth() {
+        assert_eq!(SoilFertility::new(0.).growth_multiplier(), 1.);
+        assert!(SoilFertility::MAX.growth_multiplier() > 1.);
+    }
+
+    #[test]
+    fn soil_fertility_settles_towards_baseline() {
+        let mut enriched = SoilFertility::new(80.);
+        enriched.settle_towards(20., 0.5);
+        assert_eq!(enriched.value(), 50.);
+
+        let mut depleted = SoilFertility::new(0.);
+        depleted.settle_towards(20., 0.25);
+        assert_eq!(depleted.value(), 5.);
+
+        depleted.settle_towards(20., 1.);
+        assert_eq!(depleted.value(), 20.);
+    }
+
+    #[test]
+    fn units_enrich_the_soil_once_per_step() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let here = VoxelPos::ZERO;
+        let there = VoxelPos::from_xy(1, 0);
+        for voxel_pos in [here, there] {
+            let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
+            world
+                .entity_mut(terrain_entity)
+                .insert(SoilFertility::default());
+        }
+        world.insert_resource(map_geometry);
+
+        let mut unit_data = UnitData::simple("ant", Diet::simple("leaf"));
+        unit_data.nutrients_per_step = 1.;
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert("ant".to_string(), unit_data);
+        world.insert_resource(unit_manifest);
+
+        let unit_entity = world
+            .spawn((here, Id::<Unit>::from_name("ant".to_string())))
+            .id();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(deposit_unit_nutrients);
+        let fertility = |world: &World, voxel_pos: VoxelPos| {
+            let map_geometry = world.resource::<MapGeometry>();
+            let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
+            world.get::<SoilFertility>(terrain_entity).unwrap().value()
+        };
+
+        // Neither arriving in the world nor standing around counts as a step
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+        assert_eq!(fertility(&world, here), 0.);
+
+        *world.get_mut::<VoxelPos>(unit_entity).unwrap() = there;
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+        assert_eq!(fertility(&world, here), 0.);
+        assert_eq!(fertility(&world, there), 1.);
+    }
+
+    #[test]
+    fn decomposition_takes_time() {
+        let mut decomposition = Decomposition::default();
+        assert!(!decomposition.advance(Days(0.1)));
+        assert!(decomposition.advance(Days(0.2)));
+        // Progress resets after each item
+        assert!(!decomposition.advance(Days(0.1)));
+    }
+}
@@ -0,0 +1,205 @@
+//! A compact record of what has happened on each tile.
+//!
+//! When a patch of the colony collapses, the player needs to be able to work out why.
+//! Each terrain tile keeps a short, bounded list of the most recent notable events that occurred on it:
+//! terraforming, structures being built or removed, and organisms dying.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use hexx::Hex;
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::terraform::TerraformingAction,
+    geometry::MapGeometry,
+    organisms::OrganismId,
+    simulation::time::InGameTime,
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::unit_manifest::UnitManifest,
+};
+
+use super::terrain_manifest::TerrainManifest;
+
+/// Why an organism died.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeathCause {
+    /// The organism ran out of energy.
+    Starvation,
+    /// The organism ran out of oxygen.
+    Suffocation,
+    /// The organism reached the end of its lifespan.
+    OldAge,
+}
+
+impl Display for DeathCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            DeathCause::Starvation => "starvation",
+            DeathCause::Suffocation => "suffocation",
+            DeathCause::OldAge => "old age",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// Something notable that happened on a tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TileEventKind {
+    /// The terrain was raised, lowered or changed.
+    Terraformed(TerraformingAction),
+    /// A structure was spawned.
+    StructureBuilt(Id<Structure>),
+    /// A structure was despawned.
+    StructureRemoved(Id<Structure>),
+    /// An organism died.
+    OrganismDied {
+        /// The organism that died.
+        organism_id: OrganismId,
+        /// Why it died.
+        cause: DeathCause,
+    },
+}
+
+impl TileEventKind {
+    /// Pretty formatting for this type.
+    fn display(
+        &self,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+        unit_manifest: &UnitManifest,
+    ) -> String {
+        match self {
+            TileEventKind::Terraformed(terraforming_action) => {
+                format!(
+                    "Terraformed: {}",
+                    terraforming_action.display(terrain_manifest)
+                )
+            }
+            TileEventKind::StructureBuilt(structure_id) => {
+                format!("{} built", structure_manifest.name(*structure_id))
+            }
+            TileEventKind::StructureRemoved(structure_id) => {
+                format!("{} removed", structure_manifest.name(*structure_id))
+            }
+            TileEventKind::OrganismDied { organism_id, cause } => format!(
+                "{} died of {cause}",
+                organism_id.display(structure_manifest, unit_manifest)
+            ),
+        }
+    }
+}
+
+/// An event that records something notable happening on a tile.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct TileEvent {
+    /// The tile where this happened.
+    pub hex: Hex,
+    /// What happened.
+    pub kind: TileEventKind,
+}
+
+/// A single entry in a [`TileHistory`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HistoryEntry {
+    /// The in-game day on which this happened.
+    day: u64,
+    /// What happened.
+    kind: TileEventKind,
+}
+
+/// The most recent notable events that happened on a terrain tile, oldest first.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct TileHistory {
+    /// The recorded events.
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl TileHistory {
+    /// The maximum number of events stored for each tile.
+    ///
+    /// Once full, the oldest events are discarded.
+    pub const CAPACITY: usize = 8;
+
+    /// Records that `kind` happened on `day`, discarding the oldest entry if needed.
+    fn record(&mut self, day: u64, kind: TileEventKind) {
+        if self.entries.len() >= Self::CAPACITY {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(HistoryEntry { day, kind });
+    }
+
+    /// The number of recorded events.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Has nothing been recorded?
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Pretty formatting for this type, with the most recent events first.
+    pub(crate) fn display(
+        &self,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+        unit_manifest: &UnitManifest,
+    ) -> String {
+        self.entries
+            .iter()
+            .rev()
+            .map(|entry| {
+                format!(
+                    "\n  Day {}: {}",
+                    entry.day,
+                    entry
+                        .kind
+                        .display(structure_manifest, terrain_manifest, unit_manifest)
+                )
+            })
+            .collect()
+    }
+}
+
+/// Stores each [`TileEvent`] in the [`TileHistory`] of the tile where it happened.
+pub(super) fn record_tile_history(
+    mut tile_events: EventReader<TileEvent>,
+    mut history_query: Query<&mut TileHistory>,
+    map_geometry: Res<MapGeometry>,
+    in_game_time: Res<InGameTime>,
+) {
+    let day = in_game_time.rounded_elapsed_days();
+
+    for tile_event in tile_events.read() {
+        let Ok(terrain_entity) = map_geometry.get_terrain(tile_event.hex) else {
+            continue;
+        };
+
+        if let Ok(mut tile_history) = history_query.get_mut(terrain_entity) {
+            tile_history.record(day, tile_event.kind);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_history_is_bounded() {
+        let mut tile_history = TileHistory::default();
+        let structure_id = Id::from_name("test".to_string());
+
+        for day in 0..(TileHistory::CAPACITY as u64 + 3) {
+            tile_history.record(day, TileEventKind::StructureBuilt(structure_id));
+        }
+
+        assert_eq!(tile_history.len(), TileHistory::CAPACITY);
+        // The oldest entries are discarded first
+        assert_eq!(tile_history.entries.front().unwrap().day, 3);
+    }
+}
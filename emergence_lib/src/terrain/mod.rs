@@ -14,17 +14,25 @@ use crate::player_interaction::picking::PickableVoxel;
 use crate::player_interaction::selection::ObjectInteraction;
 use crate::signals::Emitter;
 use crate::simulation::SimulationSet;
+use crate::temperature::Temperature;
 use crate::water::{WaterBundle, WaterSet};
 
+use self::fertility::{
+    decompose_litter, deposit_unit_nutrients, settle_soil_fertility, SoilFertility,
+};
+use self::history::{record_tile_history, TileEvent, TileHistory};
 use self::terrain_assets::TerrainHandles;
 use self::terrain_manifest::{RawTerrainManifest, Terrain, TerrainManifest};
 use crate::litter::{
-    carry_floating_litter_with_current, clear_empty_litter, make_litter_float, set_litter_emitters,
-    LitterEmitters,
+    carry_floating_litter_with_current, clear_empty_litter, make_litter_float, merge_litter_piles,
+    set_litter_emitters, LitterEmitters,
 };
 
+pub mod fertility;
+pub mod history;
 pub(crate) mod terrain_assets;
 pub mod terrain_manifest;
+pub mod trails;
 
 /// All logic and initialization needed for terrain.
 pub(crate) struct TerrainPlugin;
@@ -33,6 +41,7 @@ impl Plugin for TerrainPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ManifestPlugin::<RawTerrainManifest>::new())
             .add_asset_collection::<TerrainHandles>()
+            .add_event::<TileEvent>()
             .add_systems(
                 FixedUpdate,
                 (
@@ -45,10 +54,17 @@ impl Plugin for TerrainPlugin {
                     // because we care about cleaning up litter inventories before we try and drift
                     // but we also want to clean up after because we may have condensed litter inventories by drifting
                     clear_empty_litter.before(carry_floating_litter_with_current),
-                    clear_empty_litter.after(carry_floating_litter_with_current),
+                    merge_litter_piles.after(carry_floating_litter_with_current),
+                    clear_empty_litter.after(merge_litter_piles),
                     set_litter_emitters
-                        .after(carry_floating_litter_with_current)
+                        .after(merge_litter_piles)
                         .in_set(LitterEmitters),
+                    decompose_litter,
+                    deposit_unit_nutrients,
+                    settle_soil_fertility,
+                    record_tile_history,
+                    trails::wear_trails,
+                    trails::regrow_trails.after(trails::wear_trails),
                 )
                     .in_set(SimulationSet),
             );
@@ -84,6 +100,12 @@ pub(crate) struct TerrainBundle {
     output_inventory: OutputInventory,
     /// Any active terraforming processes.
     terraforming_action: TerraformingAction,
+    /// The fertility of the soil, enriched by decomposing litter.
+    soil_fertility: SoilFertility,
+    /// The current temperature of this tile.
+    temperature: Temperature,
+    /// The most recent notable events that happened on this tile.
+    tile_history: TileHistory,
 }
 
 impl TerrainBundle {
@@ -123,6 +145,9 @@ impl TerrainBundle {
             input_inventory: InputInventory::NULL,
             output_inventory: OutputInventory::NULL,
             terraforming_action: TerraformingAction::None,
+            soil_fertility: SoilFertility::new(terrain_data.base_fertility),
+            temperature: Temperature::default(),
+            tile_history: TileHistory::default(),
         }
     }
 
@@ -142,12 +167,15 @@ impl TerrainBundle {
             input_inventory: InputInventory::NULL,
             output_inventory: OutputInventory::NULL,
             terraforming_action: TerraformingAction::None,
+            soil_fertility: SoilFertility::default(),
+            temperature: Temperature::default(),
+            tile_history: TileHistory::default(),
         }
     }
 }
 
 /// Updates the game state appropriately whenever the height of a tile is changed.
-fn respond_to_height_changes(
+pub(crate) fn respond_to_height_changes(
     mut terrain_query: Query<(Ref<VoxelPos>, &mut Transform, &Children), With<Id<Terrain>>>,
     mut column_query: Query<&mut Transform, (With<Parent>, Without<VoxelPos>)>,
     mut map_geometry: ResMut<MapGeometry>,
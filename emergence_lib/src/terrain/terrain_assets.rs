@@ -1,9 +1,9 @@
 //! Asset loading for terrain
 
-use bevy::{asset::LoadState, prelude::*, utils::HashMap};
+use bevy::{asset::UntypedHandle, prelude::*, utils::HashMap};
 
 use crate::{
-    asset_management::{manifest::Id, AssetState, Loadable},
+    asset_management::{manifest::Id, models::ModelManifest, AssetState, Loadable},
     enum_iter::IterableEnum,
     geometry::{hexagonal_column, Height},
     graphics::palette::environment::COLUMN_COLOR,
@@ -35,11 +35,12 @@ impl Loadable for TerrainHandles {
 
     fn initialize(world: &mut World) {
         let names = world.resource::<TerrainManifest>().names();
+        let model_manifest = world.resource::<ModelManifest>();
         let asset_server = world.resource::<AssetServer>();
 
         let mut scenes = HashMap::new();
         for name in names {
-            let path_string = format!("terrain/{name}.gltf#Scene0");
+            let path_string = model_manifest.scene_path("terrain", name);
             let scene = asset_server.load(path_string);
             scenes.insert(Id::from_name(name.to_string()), scene);
         }
@@ -86,21 +87,10 @@ impl Loadable for TerrainHandles {
         });
     }
 
-    fn load_state(&self, asset_server: &AssetServer) -> Option<LoadState> {
-        for (terrain, scene_handle) in &self.scenes {
-            let scene_load_state = asset_server.get_load_state(scene_handle);
-
-            if scene_load_state != Some(LoadState::Loaded) {
-                let maybe_path = asset_server.get_path(scene_handle);
-                let path = maybe_path
-                    .map(|p| format!("{:?}", p.path()))
-                    .unwrap_or("unknown_path".to_string());
-
-                info!("Terrain {terrain:?}'s scene at {path} is {scene_load_state:?}");
-                return scene_load_state;
-            }
-        }
-
-        Some(LoadState::Loaded)
+    fn handles(&self) -> Vec<UntypedHandle> {
+        self.scenes
+            .values()
+            .map(|scene_handle| scene_handle.clone_weak().untyped())
+            .collect()
     }
 }
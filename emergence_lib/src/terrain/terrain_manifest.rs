@@ -8,7 +8,7 @@ use bevy::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    asset_management::manifest::{loader::IsRawManifest, Manifest},
+    asset_management::manifest::{loader::IsRawManifest, Id, Manifest},
     water::{
         water_dynamics::{SoilWaterEvaporationRate, SoilWaterFlowRate},
         SoilWaterCapacity,
@@ -47,6 +47,25 @@ pub struct TerrainData {
     /// This is relative to empty space, which has an evaporation rate of 1.0.
     /// Generally this value should be between 0.05 and 0.5.
     pub soil_water_evaporation_rate: SoilWaterEvaporationRate,
+    /// Can structures be placed on this terrain type?
+    ///
+    /// This only restricts what the player can build: organisms can still be planted on unbuildable terrain,
+    /// and spread onto it on their own.
+    pub buildable: bool,
+    /// The fertility of freshly generated soil of this terrain type.
+    ///
+    /// Soil fertility slowly returns to this value over time.
+    /// This should be between 0 and [`SoilFertility::MAX`](crate::terrain::fertility::SoilFertility::MAX).
+    pub base_fertility: f32,
+    /// Is this the terrain that heavily trafficked tiles are worn into?
+    ///
+    /// See [`trails`](crate::terrain::trails) for how trails form and regrow.
+    /// At most one terrain type should be a trail.
+    #[serde(default)]
+    pub trail: bool,
+    /// Descriptive text shown in the codex, if any.
+    #[serde(default)]
+    pub flavor_text: Option<String>,
 }
 
 impl Default for TerrainData {
@@ -56,10 +75,24 @@ impl Default for TerrainData {
             soil_water_capacity: SoilWaterCapacity::default(),
             soil_water_flow_rate: SoilWaterFlowRate::default(),
             soil_water_evaporation_rate: SoilWaterEvaporationRate::default(),
+            buildable: true,
+            base_fertility: 0.,
+            trail: false,
+            flavor_text: None,
         }
     }
 }
 
+impl TerrainManifest {
+    /// The terrain type that heavily trafficked tiles are worn into, if any.
+    pub fn trail(&self) -> Option<Id<Terrain>> {
+        self.data_map()
+            .iter()
+            .find(|(_, terrain_data)| terrain_data.trail)
+            .map(|(&terrain_id, _)| terrain_id)
+    }
+}
+
 /// The [`TerrainManifest`] as seen in the manifest file.
 #[derive(Asset, Debug, Clone, Serialize, Deserialize, TypeUuid, TypePath, PartialEq)]
 #[uuid = "8d6b3b65-9b11-42a9-a795-f95b06653070"]
@@ -78,8 +111,10 @@ impl IsRawManifest for RawTerrainManifest {
         let mut manifest = Manifest::new();
 
         for (raw_id, raw_data) in self.terrain_types.clone() {
+            let flavor_text = raw_data.flavor_text.clone();
+
             // No additional preprocessing is needed.
-            manifest.insert(raw_id, raw_data)
+            manifest.insert_with_flavor_text(raw_id, raw_data, flavor_text)
         }
 
         manifest
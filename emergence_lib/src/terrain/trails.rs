@@ -0,0 +1,150 @@
+//! Trails form where units walk often, and regrow once they fall out of use.
+//!
+//! Tiles with heavy traffic, as recorded in the [`TrafficHeatmap`],
+//! are worn down into the terrain type marked as the [`TerrainManifest::trail`].
+//! Trails are quick to walk along, so busy routes attract even more traffic,
+//! in the same way that ant trails are reinforced.
+//! Once traffic dies down, the tile regrows into the terrain that it was before it was worn.
+
+use bevy::{ecs::query::WorldQuery, prelude::*};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    units::traffic::TrafficHeatmap,
+    water::{
+        water_dynamics::{SoilWaterEvaporationRate, SoilWaterFlowRate},
+        SoilWaterCapacity,
+    },
+};
+
+use super::{
+    terrain_assets::TerrainHandles,
+    terrain_manifest::{Terrain, TerrainManifest},
+};
+
+/// Marks a terrain tile that has been worn into a trail.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WornTrail {
+    /// The terrain that the tile was before it was worn, which it regrows into.
+    pub original_terrain: Id<Terrain>,
+}
+
+impl WornTrail {
+    /// Tiles with at least this much recent traffic are worn into trails.
+    pub const WEAR_THRESHOLD: f32 = 20.;
+
+    /// Trails with less than this much recent traffic regrow.
+    ///
+    /// This is much lower than [`WornTrail::WEAR_THRESHOLD`], so that trails don't flicker in and out of existence.
+    pub const REGROWTH_THRESHOLD: f32 = 2.;
+}
+
+/// The components of a terrain tile that change when it is worn into a trail or regrows.
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub(super) struct TerrainTypeQuery {
+    /// The type of terrain.
+    terrain_id: &'static mut Id<Terrain>,
+    /// The scene used to draw the tile.
+    scene_handle: &'static mut Handle<Scene>,
+    /// The amount of water that the soil can hold.
+    soil_water_capacity: &'static mut SoilWaterCapacity,
+    /// How quickly water flows through the soil.
+    soil_water_flow_rate: &'static mut SoilWaterFlowRate,
+    /// How quickly water evaporates from the soil.
+    soil_water_evaporation_rate: &'static mut SoilWaterEvaporationRate,
+}
+
+impl TerrainTypeQueryItem<'_> {
+    /// Switches this terrain tile to `new_terrain_id`.
+    ///
+    /// Soil fertility and water are kept, as the soil itself has not been replaced.
+    fn change_terrain_type(
+        &mut self,
+        new_terrain_id: Id<Terrain>,
+        terrain_manifest: &TerrainManifest,
+        maybe_terrain_handles: Option<&TerrainHandles>,
+    ) {
+        let terrain_data = terrain_manifest.get(new_terrain_id);
+        *self.terrain_id = new_terrain_id;
+        *self.soil_water_capacity = terrain_data.soil_water_capacity;
+        *self.soil_water_flow_rate = terrain_data.soil_water_flow_rate;
+        *self.soil_water_evaporation_rate = terrain_data.soil_water_evaporation_rate;
+
+        if let Some(scene) = maybe_terrain_handles
+            .and_then(|terrain_handles| terrain_handles.scenes.get(&new_terrain_id))
+        {
+            *self.scene_handle = scene.clone_weak();
+        }
+    }
+}
+
+/// Wears heavily trafficked tiles into trails.
+pub(super) fn wear_trails(
+    mut terrain_query: Query<TerrainTypeQuery, Without<WornTrail>>,
+    traffic_heatmap: Res<TrafficHeatmap>,
+    map_geometry: Res<MapGeometry>,
+    terrain_manifest: Res<TerrainManifest>,
+    maybe_terrain_handles: Option<Res<TerrainHandles>>,
+    mut commands: Commands,
+) {
+    let Some(trail_id) = terrain_manifest.trail() else {
+        return;
+    };
+
+    for (voxel_pos, traffic) in traffic_heatmap.iter() {
+        if traffic < WornTrail::WEAR_THRESHOLD {
+            continue;
+        }
+
+        // Units walk in the voxels above the terrain, and the footprints of structures are not worn
+        if map_geometry.get_structure(voxel_pos).is_some() {
+            continue;
+        }
+
+        let Ok(terrain_entity) = map_geometry.get_terrain(voxel_pos.hex) else {
+            continue;
+        };
+
+        let Ok(mut terrain) = terrain_query.get_mut(terrain_entity) else {
+            continue;
+        };
+
+        let original_terrain = *terrain.terrain_id;
+        if original_terrain == trail_id {
+            continue;
+        }
+
+        terrain.change_terrain_type(
+            trail_id,
+            &terrain_manifest,
+            maybe_terrain_handles.as_deref(),
+        );
+        commands
+            .entity(terrain_entity)
+            .insert(WornTrail { original_terrain });
+    }
+}
+
+/// Regrows trails that are no longer used into the terrain that they were worn from.
+pub(super) fn regrow_trails(
+    mut trail_query: Query<(Entity, &VoxelPos, &WornTrail, TerrainTypeQuery)>,
+    traffic_heatmap: Res<TrafficHeatmap>,
+    terrain_manifest: Res<TerrainManifest>,
+    maybe_terrain_handles: Option<Res<TerrainHandles>>,
+    mut commands: Commands,
+) {
+    for (terrain_entity, voxel_pos, worn_trail, mut terrain) in trail_query.iter_mut() {
+        if traffic_heatmap.get(voxel_pos.above()) >= WornTrail::REGROWTH_THRESHOLD {
+            continue;
+        }
+
+        terrain.change_terrain_type(
+            worn_trail.original_terrain,
+            &terrain_manifest,
+            maybe_terrain_handles.as_deref(),
+        );
+        commands.entity(terrain_entity).remove::<WornTrail>();
+    }
+}
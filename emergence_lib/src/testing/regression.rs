@@ -0,0 +1,313 @@
+//! Regression tests for game balance, which compare the outcome of a simulation run against a saved "golden" snapshot.
+//!
+//! Each scenario runs the headless simulation for a fixed number of ticks, with a fixed timestep,
+//! and then records key aggregates: the population of each species, the total of each item and the total strength of each kind of signal.
+//!
+//! Every scenario is run with the fixed [`REGRESSION_SEED`], so the simulation draws the same random numbers each time.
+//! Floating point results can still differ slightly between platforms, so snapshots are compared with a [`Tolerance`],
+//! which is loose enough to absorb this noise but tight enough to catch refactors that silently change balance.
+//!
+//! Golden snapshots are stored as JSON in `tests/golden`, and a missing snapshot is a test failure.
+//! Set the `UPDATE_GOLDEN_SNAPSHOTS` environment variable to record new snapshots, after checking that a balance change is intended.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    items::{item_manifest::ItemManifest, totals::ItemTotals},
+    signals::SignalChannels,
+    units::{census::Census, unit_manifest::UnitManifest},
+    world_gen::{GenerationConfig, WorldGenState},
+};
+
+use super::simulation_app;
+
+/// The environment variable which, when set, causes golden snapshots to be overwritten rather than checked.
+pub const UPDATE_GOLDEN_ENV_VAR: &str = "UPDATE_GOLDEN_SNAPSHOTS";
+
+/// The seed used for the world generation and simulation randomness of every regression scenario.
+pub const REGRESSION_SEED: u64 = 0xE4E5_6E7C;
+
+/// The maximum number of frames to wait for world generation to complete before giving up.
+const MAX_WORLD_GEN_FRAMES: u32 = 10_000;
+
+/// Key aggregates of the simulation state, used to detect changes in game balance.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulationSnapshot {
+    /// The number of simulation ticks that were run before this snapshot was taken.
+    pub ticks: u32,
+    /// The number of living units of each species, keyed by name.
+    pub population: BTreeMap<String, usize>,
+    /// The total number of each item across all inventories, keyed by name.
+    pub item_totals: BTreeMap<String, u32>,
+    /// The total strength of each kind of signal across the map.
+    pub signal_totals: BTreeMap<String, f32>,
+}
+
+/// How far a value may drift from its golden snapshot before the test fails.
+///
+/// A value passes if `|actual - expected| <= absolute + relative * |expected|`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// The allowed difference, as a fraction of the expected value.
+    pub relative: f32,
+    /// The allowed difference, regardless of the expected value.
+    ///
+    /// This prevents small counts from failing due to noise.
+    pub absolute: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance {
+            relative: 0.25,
+            absolute: 2.0,
+        }
+    }
+}
+
+impl Tolerance {
+    /// Is `actual` close enough to `expected`?
+    pub fn accepts(&self, actual: f32, expected: f32) -> bool {
+        (actual - expected).abs() <= self.absolute + self.relative * expected.abs()
+    }
+}
+
+impl SimulationSnapshot {
+    /// Records the current state of the simulation stored in `world`.
+    pub fn capture(world: &World, ticks: u32) -> Self {
+        let census = world.resource::<Census>();
+        let unit_manifest = world.resource::<UnitManifest>();
+        let item_totals = world.resource::<ItemTotals>();
+        let item_manifest = world.resource::<ItemManifest>();
+        let signals = world.resource::<SignalChannels>().player();
+
+        let population = unit_manifest
+            .variants()
+            .into_iter()
+            .map(|unit_id| {
+                (
+                    unit_manifest.name(unit_id).to_string(),
+                    census.population(unit_id),
+                )
+            })
+            .collect();
+
+        let item_totals = item_totals
+            .counts()
+            .iter()
+            .map(|(&item_id, &count)| (item_manifest.name(item_id).to_string(), count))
+            .collect();
+
+        let signal_totals = signals
+            .total_strength_by_kind()
+            .into_iter()
+            .map(|(signal_kind, total)| (format!("{signal_kind:?}"), total))
+            .collect();
+
+        SimulationSnapshot {
+            ticks,
+            population,
+            item_totals,
+            signal_totals,
+        }
+    }
+
+    /// Compares this snapshot to the `golden` snapshot, returning a description of each value that drifted too far.
+    ///
+    /// Values missing from either snapshot are treated as zero.
+    pub fn compare(&self, golden: &SimulationSnapshot, tolerance: Tolerance) -> Vec<String> {
+        let mut differences = Vec::new();
+
+        if self.ticks != golden.ticks {
+            differences.push(format!(
+                "ticks: ran for {} ticks, but the golden snapshot ran for {}",
+                self.ticks, golden.ticks
+            ));
+        }
+
+        compare_maps(
+            "population",
+            &self.population,
+            &golden.population,
+            |&count| count as f32,
+            tolerance,
+            &mut differences,
+        );
+        compare_maps(
+            "item_totals",
+            &self.item_totals,
+            &golden.item_totals,
+            |&count| count as f32,
+            tolerance,
+            &mut differences,
+        );
+        compare_maps(
+            "signal_totals",
+            &self.signal_totals,
+            &golden.signal_totals,
+            |&total| total,
+            tolerance,
+            &mut differences,
+        );
+
+        differences
+    }
+}
+
+/// Compares each entry in `actual` and `expected`, recording any that differ by more than the `tolerance` in `differences`.
+fn compare_maps<T>(
+    category: &str,
+    actual: &BTreeMap<String, T>,
+    expected: &BTreeMap<String, T>,
+    to_f32: impl Fn(&T) -> f32,
+    tolerance: Tolerance,
+    differences: &mut Vec<String>,
+) {
+    let keys: BTreeSet<&String> = actual.keys().chain(expected.keys()).collect();
+
+    for key in keys {
+        let actual_value = actual.get(key).map_or(0., &to_f32);
+        let expected_value = expected.get(key).map_or(0., &to_f32);
+
+        if !tolerance.accepts(actual_value, expected_value) {
+            differences.push(format!(
+                "{category}.{key}: expected {expected_value}, found {actual_value}"
+            ));
+        }
+    }
+}
+
+/// Creates a [`simulation_app`] and runs it until a world has been generated using `gen_config`.
+///
+/// Time is advanced manually by exactly one fixed timestep per frame, so that the result does not depend on how fast the machine is.
+pub fn generate_world(gen_config: GenerationConfig) -> App {
+    let mut app = simulation_app(gen_config);
+    let timestep = Time::<Fixed>::default().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+
+    let mut world_gen_frames = 0;
+    while *app.world.resource::<State<WorldGenState>>().get() != WorldGenState::Complete {
+        assert!(
+            world_gen_frames < MAX_WORLD_GEN_FRAMES,
+            "World generation did not complete within {MAX_WORLD_GEN_FRAMES} frames"
+        );
+        app.update();
+        world_gen_frames += 1;
+    }
+
+    app
+}
+
+/// Generates a world using `gen_config`, then runs the simulation for `ticks` fixed timesteps and captures a snapshot.
+///
+/// The seed of `gen_config` is replaced by [`REGRESSION_SEED`], so that every run draws the same random numbers.
+/// See [`generate_world`] for how time is advanced.
+pub fn run_scenario(gen_config: GenerationConfig, ticks: u32) -> SimulationSnapshot {
+    let mut app = generate_world(GenerationConfig {
+        seed: REGRESSION_SEED,
+        ..gen_config
+    });
+
+    for _ in 0..ticks {
+        app.update();
+    }
+
+    SimulationSnapshot::capture(&app.world, ticks)
+}
+
+/// The path to the golden snapshot with the given `name`.
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.json"))
+}
+
+/// Checks `snapshot` against the golden snapshot called `name`, panicking with a list of differences if it has drifted.
+///
+/// If [`UPDATE_GOLDEN_ENV_VAR`] is set, the snapshot is saved instead.
+/// Otherwise, a missing golden snapshot fails the test, so that a forgotten snapshot cannot silently pass.
+pub fn assert_matches_golden(name: &str, snapshot: &SimulationSnapshot, tolerance: Tolerance) {
+    let path = golden_path(name);
+
+    if std::env::var_os(UPDATE_GOLDEN_ENV_VAR).is_some() {
+        let contents = serde_json::to_string_pretty(snapshot).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        warn!("Recorded golden snapshot {}", path.display());
+        return;
+    }
+
+    assert!(
+        path.exists(),
+        "Golden snapshot {} does not exist. Record it by rerunning with {UPDATE_GOLDEN_ENV_VAR}=1, then commit it.",
+        path.display()
+    );
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let golden: SimulationSnapshot = serde_json::from_str(&contents).unwrap();
+    let differences = snapshot.compare(&golden, tolerance);
+
+    assert!(
+        differences.is_empty(),
+        "Simulation drifted from golden snapshot {}:\n{}\n\nIf this change in balance is intended, rerun with {UPDATE_GOLDEN_ENV_VAR}=1.",
+        path.display(),
+        differences.join("\n")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small snapshot to compare against.
+    fn golden() -> SimulationSnapshot {
+        SimulationSnapshot {
+            ticks: 100,
+            population: BTreeMap::from_iter([("ant".to_string(), 40)]),
+            item_totals: BTreeMap::from_iter([("leaf".to_string(), 100)]),
+            signal_totals: BTreeMap::from_iter([("Work".to_string(), 10.)]),
+        }
+    }
+
+    #[test]
+    fn tolerance_accepts_small_drift() {
+        let tolerance = Tolerance {
+            relative: 0.1,
+            absolute: 1.,
+        };
+
+        assert!(tolerance.accepts(100., 100.));
+        assert!(tolerance.accepts(110., 100.));
+        assert!(tolerance.accepts(1., 0.));
+        assert!(!tolerance.accepts(112., 100.));
+        assert!(!tolerance.accepts(2., 0.));
+    }
+
+    #[test]
+    fn identical_snapshots_match() {
+        let golden = golden();
+        assert!(golden.compare(&golden, Tolerance::default()).is_empty());
+    }
+
+    #[test]
+    fn drifted_and_missing_values_are_reported() {
+        let golden = golden();
+        let mut actual = golden.clone();
+        actual.population.insert("ant".to_string(), 80);
+        actual.item_totals.clear();
+        actual.signal_totals.insert("Push".to_string(), 50.);
+
+        let differences = actual.compare(&golden, Tolerance::default());
+        assert_eq!(differences.len(), 3, "{differences:?}");
+        assert!(differences[0].starts_with("population.ant"));
+        assert!(differences[1].starts_with("item_totals.leaf"));
+        assert!(differences[2].starts_with("signal_totals.Push"));
+    }
+}
@@ -0,0 +1,558 @@
+//! Caravans visit from off the map to trade with the colony.
+//!
+//! Every few days, a [`Caravan`] arrives and stays for a while, as long as the colony has built a [`TradingPost`].
+//! While it is here, it makes a handful of [`TradeOffer`]s, each exchanging some of the colony's items for some of its own.
+//! Prices are set by [`ItemPrices`], based on the [`DailyReports`]:
+//! items that the colony has been producing in bulk are cheap, while those it has been using up are dear.
+//!
+//! Accepting an offer with an [`AcceptTradeOffer`] event turns it into an order at a trading post.
+//! The post requests the payment through the logistics network like any other input inventory,
+//! and once it has all been delivered, the goods are placed in the post's output inventory to be hauled away.
+//! Orders are honored even if the caravan leaves before they are paid for.
+
+use bevy::{prelude::*, utils::HashMap};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::{
+        inventories::{InputInventory, OutputInventory},
+        item_tags::ItemKind,
+    },
+    items::{
+        inventory::Inventory,
+        item_manifest::{Item, ItemManifest},
+        ledger::{ItemLedger, ItemSink, ItemSource},
+        totals::ItemTotals,
+        ItemCount,
+    },
+    signals::{Emitter, SignalStrength, SignalType},
+    simulation::{reports::DailyReports, rng::GlobalRng, time::InGameTime, SimulationSet},
+};
+
+/// Sends caravans to the colony, and carries out the trades that the player agrees to.
+pub(crate) struct TradingPlugin;
+
+impl Plugin for TradingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Caravans>()
+            .add_event::<AcceptTradeOffer>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    manage_caravans,
+                    accept_trade_offers,
+                    complete_trades,
+                    trading_post_signals,
+                )
+                    .chain()
+                    .in_set(SimulationSet),
+            );
+    }
+}
+
+/// A structure where the colony exchanges goods with visiting caravans.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct TradingPost {
+    /// The trade that is waiting to be paid for here, if any.
+    order: Option<TradeOffer>,
+}
+
+impl TradingPost {
+    /// The trade that is waiting to be paid for here, if any.
+    pub fn order(&self) -> Option<&TradeOffer> {
+        self.order.as_ref()
+    }
+}
+
+/// An exchange of items proposed by a caravan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeOffer {
+    /// Identifies this offer, even as other offers are accepted and removed.
+    pub id: u64,
+    /// The items that the colony gives up.
+    pub pay: ItemCount,
+    /// The items that the colony gets in return.
+    pub receive: ItemCount,
+}
+
+impl TradeOffer {
+    /// The pretty text formatting of this type.
+    pub fn display(&self, item_manifest: &ItemManifest) -> String {
+        format!(
+            "{} for {}",
+            self.pay.display(item_manifest),
+            self.receive.display(item_manifest)
+        )
+    }
+}
+
+/// A group of traders visiting from off the map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Caravan {
+    /// The day on which the caravan leaves.
+    departure_day: u64,
+    /// The trades that have not yet been accepted.
+    offers: Vec<TradeOffer>,
+}
+
+impl Caravan {
+    /// The day on which the caravan leaves.
+    pub fn departure_day(&self) -> u64 {
+        self.departure_day
+    }
+
+    /// The trades that have not yet been accepted.
+    pub fn offers(&self) -> &[TradeOffer] {
+        &self.offers
+    }
+
+    /// Describes each offer, ready to be shown to the player.
+    pub(crate) fn listings(
+        &self,
+        item_totals: &ItemTotals,
+        item_manifest: &ItemManifest,
+    ) -> Vec<TradeOfferListing> {
+        self.offers
+            .iter()
+            .map(|offer| {
+                let available = item_totals
+                    .counts()
+                    .get(&offer.pay.item_id)
+                    .copied()
+                    .unwrap_or_default();
+
+                TradeOfferListing {
+                    offer_id: offer.id,
+                    description: offer.display(item_manifest),
+                    affordable: available >= offer.pay.count,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A single [`TradeOffer`], as presented to the player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeOfferListing {
+    /// The [`TradeOffer::id`] of the offer, used to accept it with [`AcceptTradeOffer`].
+    pub offer_id: u64,
+    /// A description of what is exchanged.
+    pub description: String,
+    /// Does the colony currently have enough items to pay for this trade?
+    pub affordable: bool,
+}
+
+/// Tracks the visiting caravan and when the next one will arrive.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Caravans {
+    /// The caravan that is currently visiting, if any.
+    visiting: Option<Caravan>,
+    /// The earliest day on which the next caravan can arrive.
+    next_arrival_day: u64,
+    /// The [`TradeOffer::id`] given to the next offer that is made.
+    next_offer_id: u64,
+}
+
+impl Default for Caravans {
+    fn default() -> Self {
+        Caravans {
+            visiting: None,
+            next_arrival_day: Self::DAYS_BETWEEN_CARAVANS,
+            next_offer_id: 0,
+        }
+    }
+}
+
+impl Caravans {
+    /// The number of days between one caravan leaving and the next arriving.
+    const DAYS_BETWEEN_CARAVANS: u64 = 5;
+
+    /// The number of days that each caravan stays for.
+    const DAYS_VISITING: u64 = 2;
+
+    /// The number of offers that each caravan makes.
+    const OFFERS_PER_CARAVAN: usize = 3;
+
+    /// The caravan that is currently visiting, if any.
+    pub fn visiting(&self) -> Option<&Caravan> {
+        self.visiting.as_ref()
+    }
+}
+
+/// Accepts one of the visiting caravan's [`TradeOffer`]s, to be paid for at the given [`TradingPost`].
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct AcceptTradeOffer {
+    /// The [`TradeOffer::id`] of the offer.
+    pub offer_id: u64,
+    /// The trading post where the trade is carried out.
+    pub trading_post: Entity,
+}
+
+/// How much each item is worth to caravans, relative to an item with a price of 1.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ItemPrices {
+    /// The average change in the colony's total of each item per day.
+    average_changes: HashMap<Id<Item>, f32>,
+}
+
+impl ItemPrices {
+    /// The daily change in an item's total that halves or doubles its price.
+    const PRICE_SENSITIVITY: f32 = 10.;
+
+    /// The lowest price that any item can have.
+    const MIN_PRICE: f32 = 0.25;
+
+    /// The highest price that any item can have.
+    const MAX_PRICE: f32 = 4.;
+
+    /// Computes prices from the colony's recent production.
+    pub fn from_reports(daily_reports: &DailyReports) -> Self {
+        let mut totals: HashMap<Id<Item>, i64> = HashMap::default();
+        let mut n_reports = 0;
+
+        for report in daily_reports.iter() {
+            n_reports += 1;
+            for (&item_id, &change) in report.item_changes() {
+                *totals.entry(item_id).or_default() += change;
+            }
+        }
+
+        let average_changes = totals
+            .into_iter()
+            .map(|(item_id, total)| (item_id, total as f32 / n_reports.max(1) as f32))
+            .collect();
+
+        ItemPrices { average_changes }
+    }
+
+    /// The price of `item_id`.
+    ///
+    /// Items that the colony has been producing are cheaper, while those it has been consuming are more expensive.
+    pub fn price(&self, item_id: Id<Item>) -> f32 {
+        let average_change = self
+            .average_changes
+            .get(&item_id)
+            .copied()
+            .unwrap_or_default();
+
+        2f32.powf(-average_change / Self::PRICE_SENSITIVITY)
+            .clamp(Self::MIN_PRICE, Self::MAX_PRICE)
+    }
+
+    /// The number of `receive` items that are worth the same as `pay_count` of the `pay` items.
+    ///
+    /// This is always at least 1, and never more than can fit in a single stack.
+    fn exchange(
+        &self,
+        pay: Id<Item>,
+        pay_count: u32,
+        receive: Id<Item>,
+        item_manifest: &ItemManifest,
+    ) -> u32 {
+        let value = pay_count as f32 * self.price(pay);
+        let receive_count = (value / self.price(receive)).round() as u32;
+        receive_count.clamp(1, item_manifest.get(receive).stack_size)
+    }
+
+    /// Randomly generates up to `n` offers, paid for with items that the colony has.
+    ///
+    /// Each offer takes the next id from `next_offer_id`.
+    fn generate_offers(
+        &self,
+        n: usize,
+        item_totals: &ItemTotals,
+        item_manifest: &ItemManifest,
+        next_offer_id: &mut u64,
+        rng: &mut impl Rng,
+    ) -> Vec<TradeOffer> {
+        let mut payable: Vec<(Id<Item>, u32)> = item_totals
+            .counts()
+            .iter()
+            .filter(|(&item_id, &count)| count > 0 && !item_manifest.get(item_id).corpse)
+            .map(|(&item_id, &count)| (item_id, count))
+            .collect();
+        payable.sort();
+
+        let mut goods: Vec<Id<Item>> = item_manifest
+            .variants()
+            .into_iter()
+            .filter(|&item_id| !item_manifest.get(item_id).corpse)
+            .collect();
+        goods.sort();
+
+        let mut offers = Vec::with_capacity(n);
+        for &(pay, available) in payable.choose_multiple(rng, n) {
+            let candidates: Vec<Id<Item>> = goods
+                .iter()
+                .copied()
+                .filter(|&item_id| item_id != pay)
+                .collect();
+            let Some(&receive) = candidates.choose(rng) else {
+                continue;
+            };
+
+            let max_pay_count = available.min(item_manifest.get(pay).stack_size);
+            let pay_count = rng.gen_range(1..=max_pay_count);
+            let receive_count = self.exchange(pay, pay_count, receive, item_manifest);
+
+            offers.push(TradeOffer {
+                id: *next_offer_id,
+                pay: ItemCount::new(pay, pay_count),
+                receive: ItemCount::new(receive, receive_count),
+            });
+            *next_offer_id += 1;
+        }
+
+        offers
+    }
+}
+
+/// Sends caravans away when their visit is over, and brings new ones in when it is time.
+fn manage_caravans(
+    mut caravans: ResMut<Caravans>,
+    trading_post_query: Query<(), With<TradingPost>>,
+    in_game_time: Res<InGameTime>,
+    daily_reports: Res<DailyReports>,
+    item_totals: Res<ItemTotals>,
+    item_manifest: Res<ItemManifest>,
+    mut rng: ResMut<GlobalRng>,
+) {
+    let today = in_game_time.rounded_elapsed_days();
+
+    if let Some(caravan) = &caravans.visiting {
+        if today >= caravan.departure_day {
+            info!("The caravan has left.");
+            caravans.visiting = None;
+            caravans.next_arrival_day = today + Caravans::DAYS_BETWEEN_CARAVANS;
+        }
+        return;
+    }
+
+    // Caravans only visit colonies that have somewhere to trade
+    if today < caravans.next_arrival_day || trading_post_query.is_empty() {
+        return;
+    }
+
+    let prices = ItemPrices::from_reports(&daily_reports);
+    let offers = prices.generate_offers(
+        Caravans::OFFERS_PER_CARAVAN,
+        &item_totals,
+        &item_manifest,
+        &mut caravans.next_offer_id,
+        rng.get_mut(),
+    );
+
+    info!("A caravan has arrived with {} offers.", offers.len());
+    caravans.visiting = Some(Caravan {
+        departure_day: today + Caravans::DAYS_VISITING,
+        offers,
+    });
+}
+
+/// Turns accepted offers into orders at trading posts.
+fn accept_trade_offers(
+    mut accept_events: EventReader<AcceptTradeOffer>,
+    mut caravans: ResMut<Caravans>,
+    mut trading_post_query: Query<(&mut TradingPost, &mut InputInventory)>,
+) {
+    for event in accept_events.read() {
+        let Some(caravan) = &mut caravans.visiting else {
+            warn!("Tried to accept a trade, but no caravan is visiting.");
+            continue;
+        };
+
+        let Some(offer_index) = caravan
+            .offers
+            .iter()
+            .position(|offer| offer.id == event.offer_id)
+        else {
+            warn!("Trade offer {} does not exist.", event.offer_id);
+            continue;
+        };
+
+        let Ok((mut trading_post, mut input_inventory)) =
+            trading_post_query.get_mut(event.trading_post)
+        else {
+            warn!("Trades can only be carried out at a trading post.");
+            continue;
+        };
+
+        if trading_post.order.is_some() {
+            warn!("This trading post is already busy with another trade.");
+            continue;
+        }
+
+        let offer = caravan.offers.remove(offer_index);
+        *input_inventory = InputInventory::Exact {
+            inventory: Inventory::empty_from_item(offer.pay.item_id, offer.pay.count),
+        };
+        trading_post.order = Some(offer);
+    }
+}
+
+/// Hands over the goods at each trading post whose order has been fully paid for.
+fn complete_trades(
+    mut trading_post_query: Query<(&mut TradingPost, &mut InputInventory, &mut OutputInventory)>,
+    item_manifest: Res<ItemManifest>,
+    mut item_ledger: ResMut<ItemLedger>,
+) {
+    for (mut trading_post, mut input_inventory, mut output_inventory) in
+        trading_post_query.iter_mut()
+    {
+        let Some(order) = &trading_post.order else {
+            continue;
+        };
+
+        if input_inventory.inventory().item_count(order.pay.item_id) < order.pay.count {
+            continue;
+        }
+
+        output_inventory.clear_empty_slots();
+        // If the goods don't fit, the caravan waits until the output inventory has been emptied
+        if output_inventory
+            .give_items(&[order.receive.clone()], &item_manifest)
+            .is_ok()
+        {
+            item_ledger.record_produced(
+                order.receive.item_id,
+                order.receive.count,
+                ItemSource::Trading,
+            );
+            // Any overpayment is kept by the caravan too
+            for item_slot in input_inventory.iter() {
+                item_ledger.record_consumed(
+                    item_slot.item_id(),
+                    item_slot.count(),
+                    ItemSink::Trading,
+                );
+            }
+
+            *input_inventory = InputInventory::NULL;
+            trading_post.order = None;
+        }
+    }
+}
+
+/// Sets the emitters for trading posts, so that payments are delivered and goods are collected.
+fn trading_post_signals(
+    mut trading_post_query: Query<
+        (&mut Emitter, &InputInventory, &OutputInventory),
+        With<TradingPost>,
+    >,
+) {
+    /// Controls how strong the signal is for trading posts.
+    const TRADING_SIGNAL_STRENGTH: f32 = 10.;
+
+    let signal_strength = SignalStrength::new(TRADING_SIGNAL_STRENGTH);
+
+    for (mut emitter, input_inventory, output_inventory) in trading_post_query.iter_mut() {
+        emitter.signals.clear();
+
+        for item_slot in input_inventory.iter() {
+            if !item_slot.is_full() {
+                let signal_type = SignalType::Pull(ItemKind::Single(item_slot.item_id()));
+                emitter.signals.push((signal_type, signal_strength));
+            }
+        }
+
+        for item_slot in output_inventory.iter() {
+            if !item_slot.is_empty() {
+                let signal_type = SignalType::Push(ItemKind::Single(item_slot.item_id()));
+                emitter.signals.push((signal_type, signal_strength));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::item_manifest;
+
+    use super::*;
+
+    #[test]
+    fn surplus_items_are_cheap() {
+        let leaf = Id::from_name("leaf".to_string());
+        let mushroom = Id::from_name("mushroom".to_string());
+        let prices = ItemPrices {
+            average_changes: HashMap::from_iter([(leaf, 10.), (mushroom, -10.)]),
+        };
+
+        assert_eq!(prices.price(leaf), 0.5);
+        assert_eq!(prices.price(mushroom), 2.);
+        assert_eq!(prices.price(Id::from_name("unknown".to_string())), 1.);
+
+        let item_manifest = item_manifest();
+        assert_eq!(prices.exchange(leaf, 8, mushroom, &item_manifest), 2);
+        assert_eq!(prices.exchange(mushroom, 8, leaf, &item_manifest), 10);
+    }
+
+    #[test]
+    fn prices_are_bounded() {
+        let leaf = Id::from_name("leaf".to_string());
+        let prices = ItemPrices {
+            average_changes: HashMap::from_iter([(leaf, 1000.)]),
+        };
+
+        assert_eq!(prices.price(leaf), ItemPrices::MIN_PRICE);
+        assert_eq!(prices.exchange(leaf, 1, leaf, &item_manifest()), 1);
+    }
+
+    #[test]
+    fn offers_are_accepted_by_id() {
+        let leaf = Id::from_name("leaf".to_string());
+        let mushroom = Id::from_name("mushroom".to_string());
+        let offer = |id, pay| TradeOffer {
+            id,
+            pay: ItemCount::new(pay, 1),
+            receive: ItemCount::new(leaf, 1),
+        };
+
+        let mut world = World::new();
+        world.init_resource::<Events<AcceptTradeOffer>>();
+        world.insert_resource(Caravans {
+            visiting: Some(Caravan {
+                departure_day: 1,
+                offers: vec![offer(5, mushroom), offer(7, leaf)],
+            }),
+            next_arrival_day: 0,
+            next_offer_id: 8,
+        });
+        let trading_posts: Vec<Entity> = (0..2)
+            .map(|_| {
+                world
+                    .spawn((TradingPost::default(), InputInventory::NULL))
+                    .id()
+            })
+            .collect();
+
+        // Accepting the first offer shifts the second one down, but it is still found by its id
+        for (offer_id, &trading_post) in [5, 7].iter().zip(&trading_posts) {
+            world.send_event(AcceptTradeOffer {
+                offer_id: *offer_id,
+                trading_post,
+            });
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(accept_trade_offers);
+        schedule.run(&mut world);
+
+        let order = |world: &World, trading_post| {
+            world
+                .get::<TradingPost>(trading_post)
+                .unwrap()
+                .order()
+                .cloned()
+        };
+        assert_eq!(order(&world, trading_posts[0]), Some(offer(5, mushroom)));
+        assert_eq!(order(&world, trading_posts[1]), Some(offer(7, leaf)));
+        assert!(world
+            .resource::<Caravans>()
+            .visiting()
+            .unwrap()
+            .offers()
+            .is_empty());
+    }
+}
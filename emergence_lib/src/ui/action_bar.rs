@@ -0,0 +1,128 @@
+//! A bar of buttons for issuing bulk commands to the current selection.
+
+use bevy::prelude::*;
+
+use crate::{
+    enum_iter::IterableEnum,
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    player_interaction::{
+        bulk_commands::{BulkCommand, IssueBulkCommand},
+        selection::CurrentSelection,
+    },
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Displays the context action bar.
+pub(super) struct ActionBarPlugin;
+
+impl Plugin for ActionBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_action_bar)
+            .add_systems(Update, (show_action_bar, press_action_buttons));
+    }
+}
+
+/// Marker component for the action bar UI.
+#[derive(Component)]
+struct ActionBar;
+
+/// A button on the action bar, which issues the contained command when pressed.
+#[derive(Component, Debug)]
+struct ActionButton(BulkCommand);
+
+/// Initializes the action bar, with one button for each [`BulkCommand`].
+fn spawn_action_bar(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::BLACK,
+    };
+
+    let action_bar_entity = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(4.),
+                    display: Display::None,
+                    ..default()
+                },
+                ..default()
+            },
+            ActionBar,
+        ))
+        .with_children(|parent| {
+            for bulk_command in BulkCommand::variants() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(4.)),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                            ..default()
+                        },
+                        ActionButton(bulk_command),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(format!("{bulk_command}"), style.clone()),
+                            ..default()
+                        });
+                    });
+            }
+        })
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(action_bar_entity);
+}
+
+/// Only shows the action bar while tiles are selected.
+fn show_action_bar(
+    current_selection: Res<CurrentSelection>,
+    mut action_bar_query: Query<&mut Style, With<ActionBar>>,
+) {
+    if !current_selection.is_changed() {
+        return;
+    }
+
+    let display = match *current_selection {
+        CurrentSelection::Voxels(ref selected_voxels) if !selected_voxels.is_empty() => {
+            Display::Flex
+        }
+        CurrentSelection::Structure(_) => Display::Flex,
+        _ => Display::None,
+    };
+
+    let mut style = action_bar_query.single_mut();
+    style.display = display;
+}
+
+/// Issues the command for each action button that is pressed, and highlights hovered buttons.
+fn press_action_buttons(
+    mut button_query: Query<
+        (&Interaction, &ActionButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut bulk_command_events: EventWriter<IssueBulkCommand>,
+) {
+    for (interaction, action_button, mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed => {
+                bulk_command_events.send(IssueBulkCommand(action_button.0));
+                BackgroundColor(MENU_HIGHLIGHT_COLOR)
+            }
+            Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+    }
+}
@@ -0,0 +1,78 @@
+//! Summarizes a large destructive action while it waits to be confirmed.
+
+use bevy::prelude::*;
+
+use crate::{construction::confirmation::PendingConfirmation, world_gen::WorldGenState};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Shows what a pending destructive action will do, and how to confirm or cancel it.
+pub(super) struct ConfirmationPanelPlugin;
+
+impl Plugin for ConfirmationPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_confirmation_panel)
+            .add_systems(
+                Update,
+                update_confirmation_panel.run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// Marker component for the confirmation panel UI.
+#[derive(Component)]
+struct ConfirmationPanel;
+
+/// Initializes the confirmation panel, hidden until a destructive action needs confirming.
+fn spawn_confirmation_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let confirmation_panel_entity = commands
+        .spawn(TextBundle {
+            text: Text::from_section("", style),
+            style: Style {
+                display: Display::None,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::rgba(0., 0., 0., 0.6)),
+            ..default()
+        })
+        .insert(ConfirmationPanel)
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(confirmation_panel_entity);
+}
+
+/// Displays the summary of the pending plan.
+fn update_confirmation_panel(
+    pending_confirmation: Res<PendingConfirmation>,
+    mut confirmation_panel_query: Query<(&mut Text, &mut Style), With<ConfirmationPanel>>,
+) {
+    if !pending_confirmation.is_changed() {
+        return;
+    }
+
+    let (mut text, mut style) = confirmation_panel_query.single_mut();
+
+    let Some(plan) = pending_confirmation.plan() else {
+        style.display = Display::None;
+        return;
+    };
+
+    style.display = Display::Flex;
+    text.sections[0].value = format!(
+        "{}?\nPress Enter to confirm, or right-click to cancel.",
+        plan.summary()
+    );
+}
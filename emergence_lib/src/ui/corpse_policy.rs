@@ -0,0 +1,107 @@
+//! Lets the player choose what happens to the corpses of dead organisms.
+
+use bevy::prelude::*;
+
+use crate::{
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    multiplayer::actions::{SharedAction, SharedActions},
+    organisms::corpses::CorpsePolicy,
+};
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// Displays and edits the default handling of the [`CorpsePolicy`].
+pub(super) struct CorpsePolicyPlugin;
+
+impl Plugin for CorpsePolicyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_corpse_policy_button)
+            .add_systems(
+                Update,
+                (press_corpse_policy_button, update_corpse_policy_label).chain(),
+            );
+    }
+}
+
+/// A button that toggles the default handling of corpses when pressed.
+#[derive(Component)]
+struct CorpsePolicyButton;
+
+/// Adds the corpse policy button to the left panel.
+fn spawn_corpse_policy_button(
+    mut commands: Commands,
+    left_panel_query: Query<Entity, With<LeftPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 14.,
+        color: Color::BLACK,
+    };
+
+    let button_entity = commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(2.)),
+                    ..default()
+                },
+                background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                ..default()
+            },
+            CorpsePolicyButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                // The label is filled in by `update_corpse_policy_label`
+                text: Text::from_section("", text_style),
+                ..default()
+            });
+        })
+        .id();
+
+    let left_panel_entity = left_panel_query.single();
+    commands.entity(left_panel_entity).add_child(button_entity);
+}
+
+/// Toggles the default handling of corpses when the button is pressed.
+fn press_corpse_policy_button(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<CorpsePolicyButton>),
+    >,
+    corpse_policy: Res<CorpsePolicy>,
+    mut shared_actions: SharedActions,
+) {
+    for (interaction, mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction == Interaction::Pressed {
+            shared_actions.take(SharedAction::SetCorpseHandling {
+                handling: corpse_policy.default_handling.toggle(),
+            });
+        }
+    }
+}
+
+/// Shows the current default handling of corpses on the button.
+fn update_corpse_policy_label(
+    button_query: Query<(Ref<CorpsePolicyButton>, &Children)>,
+    mut text_query: Query<&mut Text>,
+    corpse_policy: Res<CorpsePolicy>,
+) {
+    for (button, children) in button_query.iter() {
+        if !corpse_policy.is_changed() && !button.is_added() {
+            continue;
+        }
+
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = format!("Corpses: {}", corpse_policy.default_handling);
+            }
+        }
+    }
+}
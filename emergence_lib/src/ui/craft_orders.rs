@@ -0,0 +1,224 @@
+//! Lists the open [`CraftOrder`]s, and lets the player order more of whatever the selected crafter makes.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::{
+        orders::CraftOrder,
+        recipe::{ActiveRecipe, RecipeManifest},
+    },
+    geometry::MapGeometry,
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    items::item_manifest::{Item, ItemManifest},
+    multiplayer::actions::{SharedAction, SharedActions},
+    player_interaction::selection::CurrentSelection,
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Displays the craft order panel.
+pub(super) struct CraftOrdersPlugin;
+
+impl Plugin for CraftOrdersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_craft_order_panel)
+            .add_systems(
+                Update,
+                (press_craft_order_buttons, update_craft_order_panel)
+                    .chain()
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// Marker component for the text listing the open orders.
+#[derive(Component)]
+struct CraftOrderText;
+
+/// The buttons on the craft order panel.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum CraftOrderButton {
+    /// Orders this many of the selected crafter's product.
+    Order(u32),
+    /// Cancels the most recently placed order.
+    CancelNewest,
+}
+
+/// Initializes the craft order panel.
+fn spawn_craft_order_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let button_style = TextStyle {
+        color: Color::BLACK,
+        ..text_style.clone()
+    };
+
+    let craft_order_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("", text_style),
+                    ..default()
+                },
+                CraftOrderText,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(4.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (button, label) in [
+                        (CraftOrderButton::Order(5), "Order 5"),
+                        (CraftOrderButton::Order(10), "Order 10"),
+                        (CraftOrderButton::CancelNewest, "Cancel newest"),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::all(Val::Px(4.)),
+                                        ..default()
+                                    },
+                                    background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                                    ..default()
+                                },
+                                button,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle {
+                                    text: Text::from_section(label, button_style.clone()),
+                                    ..default()
+                                });
+                            });
+                    }
+                });
+        })
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(craft_order_entity);
+}
+
+/// The item made by the selected crafter, if any.
+///
+/// Only the first output of the crafter's recipe is considered.
+fn selected_product(
+    current_selection: &CurrentSelection,
+    map_geometry: &MapGeometry,
+    active_recipe_query: &Query<&ActiveRecipe>,
+    recipe_manifest: &RecipeManifest,
+) -> Option<Id<Item>> {
+    current_selection
+        .structures(map_geometry)
+        .into_iter()
+        .filter_map(|structure_entity| active_recipe_query.get(structure_entity).ok())
+        .find_map(|active_recipe| *active_recipe.recipe_id())
+        .and_then(|recipe_id| {
+            recipe_manifest
+                .get(recipe_id)
+                .outputs
+                .item_ids()
+                .first()
+                .copied()
+        })
+}
+
+/// Places or cancels orders when the corresponding button is pressed.
+fn press_craft_order_buttons(
+    mut button_query: Query<
+        (&Interaction, &CraftOrderButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    order_query: Query<&CraftOrder>,
+    current_selection: Res<CurrentSelection>,
+    map_geometry: Res<MapGeometry>,
+    active_recipe_query: Query<&ActiveRecipe>,
+    recipe_manifest: Res<RecipeManifest>,
+    mut shared_actions: SharedActions,
+) {
+    for (interaction, button, mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            CraftOrderButton::Order(count) => {
+                if let Some(item_id) = selected_product(
+                    &current_selection,
+                    &map_geometry,
+                    &active_recipe_query,
+                    &recipe_manifest,
+                ) {
+                    shared_actions.take(SharedAction::PlaceCraftOrder { item_id, count });
+                }
+            }
+            CraftOrderButton::CancelNewest => {
+                if let Some(order) = order_query.iter().max_by_key(|order| order.number) {
+                    shared_actions.take(SharedAction::CancelCraftOrder {
+                        number: order.number,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Lists the open orders, oldest first, along with their progress.
+fn update_craft_order_panel(
+    mut text_query: Query<&mut Text, With<CraftOrderText>>,
+    order_query: Query<&CraftOrder>,
+    item_manifest: Res<ItemManifest>,
+    recipe_manifest: Res<RecipeManifest>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let mut orders: Vec<&CraftOrder> = order_query.iter().collect();
+    orders.sort_by_key(|order| order.number);
+
+    let new_value = match orders.is_empty() {
+        true => "No craft orders".to_string(),
+        false => {
+            let orders: Vec<String> = orders
+                .into_iter()
+                .map(|order| order.display(&item_manifest, &recipe_manifest))
+                .collect();
+            format!("Craft orders:\n{}", orders.join("\n"))
+        }
+    };
+
+    if text.sections[0].value != new_value {
+        text.sections[0].value = new_value;
+    }
+}
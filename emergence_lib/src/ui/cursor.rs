@@ -1,13 +1,25 @@
 //! Controls the appearance of the player's cursor.
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    window::{CursorIcon, PrimaryWindow},
+};
 
 use crate::{
-    asset_management::AssetState, construction::terraform::TerraformingTool,
-    player_interaction::clipboard::Tool, ui::ui_assets::CHOICE_ICON_SIZE,
+    asset_management::AssetState,
+    construction::terraform::TerraformingTool,
+    graphics::palette::ui::{CURSOR_INVALID_COLOR, CURSOR_VALID_COLOR},
+    items::item_manifest::ItemManifest,
+    player_interaction::{
+        cursor_mode::{CursorAction, CursorMode, TargetValidity},
+        measure::Measurement,
+        selection::SelectionState,
+    },
+    ui::ui_assets::CHOICE_ICON_SIZE,
+    world_gen::WorldGenState,
 };
 
-use super::ui_assets::Icons;
+use super::{ui_assets::Icons, FiraSansFontFamily};
 
 /// The plugin that adds the cursor to the UI and controls its appearance.
 pub(super) struct CursorPlugin;
@@ -15,7 +27,13 @@ pub(super) struct CursorPlugin;
 impl Plugin for CursorPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, track_cursor.map(std::mem::drop))
-            .add_systems(Update, set_cursor.run_if(in_state(AssetState::FullyLoaded)));
+            .add_systems(Update, set_cursor.run_if(in_state(AssetState::FullyLoaded)))
+            .add_systems(Startup, (spawn_brush_radius_label, spawn_measurement_label))
+            .add_systems(
+                Update,
+                (update_brush_radius_label, update_measurement_label)
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
     }
 }
 
@@ -23,24 +41,50 @@ impl Plugin for CursorPlugin {
 #[derive(Component, Debug, Default, Clone, Copy)]
 struct Cursor;
 
-/// Changes the cursor's UI element based on the current [`Tool`] contents
+/// Marker component for the text that displays the selection radius next to the cursor
+#[derive(Component, Debug, Default, Clone, Copy)]
+struct BrushRadiusLabel;
+
+/// Marker component for the floating readout of the current [`Measurement`]
+#[derive(Component, Debug, Default, Clone, Copy)]
+struct MeasurementLabel;
+
+/// Changes the cursor's UI element and the window's cursor icon based on the current [`CursorMode`].
+///
+/// Both the icon's tint and the system cursor show whether clicking will succeed.
 fn set_cursor(
-    tool: Res<Tool>,
-    mut cursor_query: Query<&mut UiImage, With<Cursor>>,
+    cursor_mode: Res<CursorMode>,
+    mut cursor_query: Query<(&mut UiImage, &mut BackgroundColor), With<Cursor>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
     terraforming_icons: Res<Icons<TerraformingTool>>,
     mut commands: Commands,
 ) {
-    if let Ok(mut cursor_image) = cursor_query.get_single_mut() {
-        if tool.is_changed() {
-            *cursor_image = match *tool {
+    if let Ok((mut cursor_image, mut cursor_tint)) = cursor_query.get_single_mut() {
+        if cursor_mode.is_changed() {
+            *cursor_image = match cursor_mode.action {
                 // Use the matching icon for the terraforming tool
-                Tool::Terraform(terraforming_tool) => terraforming_icons.get(terraforming_tool),
+                CursorAction::Terraform(terraforming_tool) => {
+                    terraforming_icons.get(terraforming_tool)
+                }
                 // Ghosts are used instead for structures
-                Tool::Structures(_) => Handle::default(),
+                CursorAction::Place => Handle::default(),
                 // No need to show a custom cursor if we have nothing selected
-                Tool::None => Handle::default(),
+                CursorAction::Select | CursorAction::Measure | CursorAction::Demolish => {
+                    Handle::default()
+                }
+            }
+            .into();
+
+            // Only tool icons are tinted: otherwise, the system cursor icon shows validity
+            cursor_tint.0 = match (cursor_mode.action, cursor_mode.validity) {
+                (CursorAction::Terraform(_), TargetValidity::Valid) => CURSOR_VALID_COLOR,
+                (CursorAction::Terraform(_), TargetValidity::Invalid) => CURSOR_INVALID_COLOR,
+                _ => Color::WHITE,
+            };
+
+            if let Ok(mut window) = window_query.get_single_mut() {
+                window.cursor.icon = cursor_icon(*cursor_mode);
             }
-            .into()
         }
     } else {
         commands.spawn((
@@ -62,6 +106,20 @@ fn set_cursor(
     }
 }
 
+/// The system cursor icon that best describes the [`CursorMode`].
+fn cursor_icon(cursor_mode: CursorMode) -> CursorIcon {
+    if cursor_mode.validity == TargetValidity::Invalid {
+        return CursorIcon::NotAllowed;
+    }
+
+    match cursor_mode.action {
+        CursorAction::Select => CursorIcon::Default,
+        CursorAction::Measure => CursorIcon::Cell,
+        CursorAction::Terraform(_) | CursorAction::Place => CursorIcon::Crosshair,
+        CursorAction::Demolish => CursorIcon::Pointer,
+    }
+}
+
 /// Moves the cursor to follow the mouse position
 fn track_cursor(
     mut cursor_query: Query<&mut Style, With<Cursor>>,
@@ -75,3 +133,114 @@ fn track_cursor(
     cursor_style.bottom = Val::Px(mouse_position.y - CHOICE_ICON_SIZE / 2.);
     Some(())
 }
+
+/// Spawns the [`BrushRadiusLabel`], which starts hidden.
+fn spawn_brush_radius_label(mut commands: Commands, fonts: Res<FiraSansFontFamily>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: fonts.regular.clone_weak(),
+                    font_size: 16.,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        BrushRadiusLabel,
+    ));
+}
+
+/// Shows the current selection radius next to the mouse, whenever it is larger than a single tile or a shaped selection is being drawn.
+fn update_brush_radius_label(
+    selection_state: Res<SelectionState>,
+    mut label_query: Query<(&mut Text, &mut Style, &mut Visibility), With<BrushRadiusLabel>>,
+    window_query: Query<&Window>,
+) {
+    /// How far the label is offset from the mouse, in pixels.
+    const LABEL_OFFSET: f32 = 16.;
+
+    let Ok((mut text, mut style, mut visibility)) = label_query.get_single_mut() else {
+        return;
+    };
+
+    let maybe_mouse_position = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
+    let radius = selection_state.radius();
+    let Some(mouse_position) =
+        maybe_mouse_position.filter(|_| radius > 0 || selection_state.is_shaped())
+    else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Visible;
+    style.left = Val::Px(mouse_position.x + LABEL_OFFSET);
+    style.top = Val::Px(mouse_position.y + LABEL_OFFSET);
+    text.sections[0].value = format!("Radius: {radius}");
+}
+
+/// Spawns the [`MeasurementLabel`], which starts hidden.
+fn spawn_measurement_label(mut commands: Commands, fonts: Res<FiraSansFontFamily>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: fonts.regular.clone_weak(),
+                    font_size: 16.,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            background_color: BackgroundColor(Color::rgba(0., 0., 0., 0.6)),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        MeasurementLabel,
+    ));
+}
+
+/// Shows the statistics of the measured region next to the mouse, while the player is measuring.
+fn update_measurement_label(
+    measurement: Res<Measurement>,
+    item_manifest: Res<ItemManifest>,
+    mut label_query: Query<(&mut Text, &mut Style, &mut Visibility), With<MeasurementLabel>>,
+    window_query: Query<&Window>,
+) {
+    /// How far the label is offset from the mouse, in pixels.
+    ///
+    /// This is further than the brush radius label, so the two don't overlap.
+    const LABEL_OFFSET: f32 = 40.;
+
+    let Ok((mut text, mut style, mut visibility)) = label_query.get_single_mut() else {
+        return;
+    };
+
+    let maybe_mouse_position = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
+    let (Some(report), Some(mouse_position)) = (measurement.report(), maybe_mouse_position) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Visible;
+    style.left = Val::Px(mouse_position.x + LABEL_OFFSET);
+    style.top = Val::Px(mouse_position.y + LABEL_OFFSET);
+    text.sections[0].value = report.display(&item_manifest);
+}
@@ -0,0 +1,186 @@
+//! Shows the most recent daily report, which the player can dismiss or export to disk.
+
+use bevy::prelude::*;
+
+use crate::{
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    items::item_manifest::ItemManifest,
+    simulation::reports::DailyReports,
+    units::unit_manifest::UnitManifest,
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Displays the daily report panel.
+pub(super) struct DailyReportPlugin;
+
+impl Plugin for DailyReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_daily_report_panel)
+            .add_systems(
+                Update,
+                (press_daily_report_buttons, update_daily_report_panel)
+                    .chain()
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// The file that daily reports are exported to, relative to the working directory.
+const EXPORT_PATH: &str = "daily_reports.log";
+
+/// Marker component for the daily report panel.
+#[derive(Component)]
+struct DailyReportPanel;
+
+/// Marker component for the text of the daily report.
+#[derive(Component)]
+struct DailyReportText;
+
+/// The buttons on the daily report panel.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum DailyReportButton {
+    /// Hides the report until the next one is compiled.
+    Dismiss,
+    /// Saves all retained reports to [`EXPORT_PATH`].
+    Export,
+}
+
+/// Initializes the daily report panel, hidden until the first report is compiled.
+fn spawn_daily_report_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let button_style = TextStyle {
+        color: Color::BLACK,
+        ..text_style.clone()
+    };
+
+    let daily_report_entity = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    display: Display::None,
+                    ..default()
+                },
+                ..default()
+            },
+            DailyReportPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("", text_style),
+                    ..default()
+                },
+                DailyReportText,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(4.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (button, label) in [
+                        (DailyReportButton::Dismiss, "Dismiss"),
+                        (DailyReportButton::Export, "Export"),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::all(Val::Px(4.)),
+                                        ..default()
+                                    },
+                                    background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                                    ..default()
+                                },
+                                button,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle {
+                                    text: Text::from_section(label, button_style.clone()),
+                                    ..default()
+                                });
+                            });
+                    }
+                });
+        })
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(daily_report_entity);
+}
+
+/// Dismisses or exports the daily reports when the corresponding button is pressed.
+fn press_daily_report_buttons(
+    mut button_query: Query<
+        (&Interaction, &DailyReportButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut daily_reports: ResMut<DailyReports>,
+    item_manifest: Res<ItemManifest>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    for (interaction, button, mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            DailyReportButton::Dismiss => daily_reports.dismiss(),
+            DailyReportButton::Export => {
+                let log = daily_reports.export(&item_manifest, &unit_manifest);
+                match std::fs::write(EXPORT_PATH, log) {
+                    Ok(()) => info!("Exported daily reports to {EXPORT_PATH}"),
+                    Err(error) => error!("Could not export daily reports: {error}"),
+                }
+            }
+        }
+    }
+}
+
+/// Shows the most recent report until it is dismissed.
+fn update_daily_report_panel(
+    daily_reports: Res<DailyReports>,
+    mut panel_query: Query<&mut Style, With<DailyReportPanel>>,
+    mut text_query: Query<&mut Text, With<DailyReportText>>,
+    item_manifest: Res<ItemManifest>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    if !daily_reports.is_changed() {
+        return;
+    }
+
+    let mut style = panel_query.single_mut();
+    let mut text = text_query.single_mut();
+
+    match daily_reports.undismissed() {
+        Some(report) => {
+            style.display = Display::Flex;
+            text.sections[0].value = report.display(&item_manifest, &unit_manifest);
+        }
+        None => style.display = Display::None,
+    }
+}
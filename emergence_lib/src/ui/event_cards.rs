@@ -0,0 +1,102 @@
+//! Shows a card describing each random event as it occurs.
+
+use bevy::prelude::*;
+
+use crate::{
+    graphics::palette::ui::MENU_NEUTRAL_COLOR,
+    random_events::{random_event_manifest::RandomEventManifest, RandomEventOccurred},
+};
+
+use super::FiraSansFontFamily;
+
+/// Displays a card for each [`RandomEventOccurred`] event.
+pub(super) struct EventCardsPlugin;
+
+impl Plugin for EventCardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_event_cards, dismiss_event_cards)
+                .chain()
+                .run_if(resource_exists::<RandomEventManifest>()),
+        );
+    }
+}
+
+/// A card describing a random event, which is dismissed when clicked or after a while.
+#[derive(Component)]
+struct EventCard {
+    /// The number of seconds until the card is dismissed automatically.
+    time_remaining: f32,
+}
+
+impl EventCard {
+    /// The number of seconds that each card is shown for.
+    const DURATION: f32 = 15.;
+}
+
+/// Spawns a card for each random event that has just occurred.
+fn spawn_event_cards(
+    mut occurred_events: EventReader<RandomEventOccurred>,
+    random_event_manifest: Res<RandomEventManifest>,
+    fonts: Res<FiraSansFontFamily>,
+    mut commands: Commands,
+) {
+    for occurred_event in occurred_events.read() {
+        let event_data = random_event_manifest.get(occurred_event.event_id);
+
+        commands
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(16.),
+                        left: Val::Percent(35.),
+                        width: Val::Percent(30.),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(8.)),
+                        row_gap: Val::Px(4.),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                    ..default()
+                },
+                EventCard {
+                    time_remaining: EventCard::DURATION,
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    &event_data.title,
+                    TextStyle {
+                        font: fonts.regular.clone_weak(),
+                        font_size: 24.,
+                        color: Color::BLACK,
+                    },
+                ));
+                parent.spawn(TextBundle::from_section(
+                    &event_data.description,
+                    TextStyle {
+                        font: fonts.regular.clone_weak(),
+                        font_size: 16.,
+                        color: Color::BLACK,
+                    },
+                ));
+            });
+    }
+}
+
+/// Removes event cards that have been clicked or have timed out.
+fn dismiss_event_cards(
+    mut card_query: Query<(Entity, &mut EventCard, &Interaction)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut card, interaction) in card_query.iter_mut() {
+        card.time_remaining -= time.delta_seconds();
+
+        if card.time_remaining <= 0. || *interaction == Interaction::Pressed {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Lets the player choose which items should be delivered first.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::{manifest::Id, AssetState},
+    crafting::item_tags::ItemTag,
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    items::item_manifest::{Item, ItemManifest},
+    logistics::HaulingPriorities,
+    multiplayer::actions::{SharedAction, SharedActions},
+};
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// Displays and edits the [`HaulingPriorities`].
+pub(super) struct HaulingPrioritiesPlugin;
+
+impl Plugin for HaulingPrioritiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hauling_priority_panel)
+            .add_systems(
+                Update,
+                (
+                    populate_hauling_priority_buttons,
+                    press_hauling_priority_buttons,
+                    update_hauling_priority_labels,
+                )
+                    .chain()
+                    .run_if(in_state(AssetState::FullyLoaded)),
+            );
+    }
+}
+
+/// Marker component for the node that holds one button per item.
+#[derive(Component)]
+struct HaulingPriorityList;
+
+/// A button that cycles the hauling priority of an item when pressed.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct HaulingPriorityButton(Id<Item>);
+
+/// Initializes the empty hauling priority panel.
+///
+/// The buttons are added once the item manifest has loaded.
+fn spawn_hauling_priority_panel(
+    mut commands: Commands,
+    left_panel_query: Query<Entity, With<LeftPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let panel_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section("Hauling priorities", text_style),
+                ..default()
+            });
+
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                HaulingPriorityList,
+            ));
+        })
+        .id();
+
+    let left_panel_entity = left_panel_query.single();
+    commands.entity(left_panel_entity).add_child(panel_entity);
+}
+
+/// Creates a button for each item that units can carry, whenever the item manifest changes.
+fn populate_hauling_priority_buttons(
+    list_query: Query<Entity, With<HaulingPriorityList>>,
+    item_manifest: Res<ItemManifest>,
+    fonts: Res<FiraSansFontFamily>,
+    mut commands: Commands,
+) {
+    if !item_manifest.is_changed() {
+        return;
+    }
+
+    let button_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 14.,
+        color: Color::BLACK,
+    };
+
+    // Fluids are never hauled, so they don't need a priority
+    let mut item_ids: Vec<Id<Item>> = item_manifest
+        .variants()
+        .into_iter()
+        .filter(|&item_id| !item_manifest.has_tag(item_id, ItemTag::Fluid))
+        .collect();
+    // Sort to ensure a stable ordering
+    item_ids.sort_by_key(|&item_id| item_manifest.name(item_id).to_string());
+
+    let list_entity = list_query.single();
+    commands
+        .entity(list_entity)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for item_id in item_ids {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(2.)),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                            ..default()
+                        },
+                        HaulingPriorityButton(item_id),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            // The label is filled in by `update_hauling_priority_labels`
+                            text: Text::from_section("", button_style.clone()),
+                            ..default()
+                        });
+                    });
+            }
+        });
+}
+
+/// Cycles the priority of an item when its button is pressed.
+fn press_hauling_priority_buttons(
+    mut button_query: Query<
+        (&Interaction, &HaulingPriorityButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    hauling_priorities: Res<HaulingPriorities>,
+    mut shared_actions: SharedActions,
+) {
+    for (interaction, &HaulingPriorityButton(item_id), mut background_color) in
+        button_query.iter_mut()
+    {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction == Interaction::Pressed {
+            shared_actions.take(SharedAction::SetHaulingPriority {
+                item_id,
+                priority: hauling_priorities.get(item_id).cycle(),
+            });
+        }
+    }
+}
+
+/// Shows the current priority of each item on its button.
+fn update_hauling_priority_labels(
+    button_query: Query<(Ref<HaulingPriorityButton>, &Children)>,
+    mut text_query: Query<&mut Text>,
+    hauling_priorities: Res<HaulingPriorities>,
+    item_manifest: Res<ItemManifest>,
+) {
+    for (button, children) in button_query.iter() {
+        if !hauling_priorities.is_changed() && !button.is_added() {
+            continue;
+        }
+
+        let HaulingPriorityButton(item_id) = *button;
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = format!(
+                    "{}: {}",
+                    item_manifest.name(item_id),
+                    hauling_priorities.get(item_id)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Shows the progress of asset loading, and lets the player decide what to do about assets that failed to load.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::{AssetState, AssetsToLoad},
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+};
+
+use super::FiraSansFontFamily;
+
+/// Displays the loading screen until all assets are loaded.
+pub(super) struct LoadingScreenPlugin;
+
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_loading_screen)
+            .add_systems(
+                Update,
+                (press_loading_buttons, update_loading_screen)
+                    .chain()
+                    .run_if(not(in_state(AssetState::FullyLoaded))),
+            )
+            .add_systems(OnEnter(AssetState::FullyLoaded), despawn_loading_screen);
+    }
+}
+
+/// The root node of the loading screen.
+#[derive(Component)]
+struct LoadingScreen;
+
+/// The text that describes the loading progress.
+#[derive(Component)]
+struct LoadingText;
+
+/// A button on the loading screen, which is only shown when assets have failed to load.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum LoadingButton {
+    /// Try to load the failed assets again.
+    Retry,
+    /// Give up on the failed assets, and start the game with placeholders instead.
+    ///
+    /// This is only offered once the manifests have loaded, as the game can't run without them.
+    Continue,
+}
+
+impl LoadingButton {
+    /// The text displayed on this button.
+    fn label(&self) -> &'static str {
+        match self {
+            LoadingButton::Retry => "Retry",
+            LoadingButton::Continue => "Continue without them",
+        }
+    }
+}
+
+/// Spawns the loading screen, which is filled in by [`update_loading_screen`].
+fn spawn_loading_screen(mut commands: Commands, fonts: Res<FiraSansFontFamily>) {
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 20.,
+        color: Color::WHITE,
+    };
+    let button_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 20.,
+        color: Color::BLACK,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(16.),
+                    left: Val::Percent(30.),
+                    width: Val::Percent(40.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.)),
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0., 0., 0., 0.8)),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("Loading...", text_style),
+                LoadingText,
+            ));
+
+            for button in [LoadingButton::Retry, LoadingButton::Continue] {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(8.)),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            button.label(),
+                            button_style.clone(),
+                        ));
+                    });
+            }
+        });
+}
+
+/// Describes the loading progress and any failures, and shows the buttons that apply.
+fn update_loading_screen(
+    assets_to_load: Res<AssetsToLoad>,
+    asset_state: Res<State<AssetState>>,
+    mut text_query: Query<&mut Text, With<LoadingText>>,
+    mut button_query: Query<(&LoadingButton, &mut Visibility)>,
+) {
+    if !assets_to_load.is_changed() && !asset_state.is_changed() {
+        return;
+    }
+
+    let failures = assets_to_load.failures();
+    let (loaded, total) = assets_to_load.overall_progress();
+
+    let mut string = match asset_state.get() {
+        AssetState::LoadManifests => "Loading manifests...".to_string(),
+        _ => format!("Loading assets: {loaded}/{total}"),
+    };
+    if !failures.is_empty() {
+        string += &format!("\n{} assets failed to load:", failures.len());
+        for path in &failures {
+            string += &format!("\n{path}");
+        }
+    }
+
+    for mut text in text_query.iter_mut() {
+        if let Some(section) = text.sections.first_mut() {
+            section.value = string.clone();
+        }
+    }
+
+    for (button, mut visibility) in button_query.iter_mut() {
+        let shown = match button {
+            LoadingButton::Retry => !failures.is_empty(),
+            LoadingButton::Continue => {
+                !failures.is_empty() && *asset_state.get() == AssetState::LoadAssets
+            }
+        };
+
+        visibility.set_if_neq(match shown {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        });
+    }
+}
+
+/// Retries or skips failed assets when the corresponding button is pressed.
+fn press_loading_buttons(
+    mut button_query: Query<
+        (&Interaction, &LoadingButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    asset_server: Res<AssetServer>,
+    mut assets_to_load: ResMut<AssetsToLoad>,
+) {
+    for (interaction, button, mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            LoadingButton::Retry => {
+                let failures: Vec<String> = assets_to_load
+                    .failures()
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+
+                for path in failures {
+                    info!("Retrying {path}");
+                    asset_server.reload(path);
+                }
+            }
+            LoadingButton::Continue => {
+                warn!("Continuing without assets that failed to load");
+                assets_to_load.skip_failures();
+            }
+        }
+    }
+}
+
+/// Removes the loading screen once everything has loaded.
+fn despawn_loading_screen(
+    mut commands: Commands,
+    screen_query: Query<Entity, With<LoadingScreen>>,
+) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
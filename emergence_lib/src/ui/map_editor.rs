@@ -0,0 +1,141 @@
+//! The palette of brushes shown in the map editor.
+
+use bevy::prelude::*;
+
+use crate::{
+    game_state::GameState,
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    map_editor::{EditorBrush, MapEditor, SaveScenario},
+    structures::structure_manifest::StructureManifest,
+    terrain::terrain_manifest::TerrainManifest,
+};
+
+use super::FiraSansFontFamily;
+
+/// Displays the brushes of the map editor, and a button to save the map.
+pub(super) struct MapEditorPanelPlugin;
+
+impl Plugin for MapEditorPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::MapEditor), spawn_map_editor_panel)
+            .add_systems(
+                Update,
+                press_map_editor_buttons.run_if(|game_state: Option<Res<State<GameState>>>| {
+                    game_state.is_some_and(|game_state| *game_state.get() == GameState::MapEditor)
+                }),
+            );
+    }
+}
+
+/// The root node of the map editor panel.
+#[derive(Component)]
+struct MapEditorPanel;
+
+/// A button in the map editor panel.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+enum MapEditorButton {
+    /// Switches to the given brush.
+    Brush(EditorBrush),
+    /// Saves the map as a new scenario.
+    Save,
+}
+
+/// Spawns the map editor panel, the first time that the editor is opened.
+///
+/// The panel is kept while the pause menu is open, so it is only spawned once.
+fn spawn_map_editor_panel(
+    panel_query: Query<(), With<MapEditorPanel>>,
+    terrain_manifest: Res<TerrainManifest>,
+    structure_manifest: Res<StructureManifest>,
+    fonts: Res<FiraSansFontFamily>,
+    mut commands: Commands,
+) {
+    if !panel_query.is_empty() {
+        return;
+    }
+
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 14.,
+        color: Color::BLACK,
+    };
+
+    let buttons = EditorBrush::all(&terrain_manifest, &structure_manifest)
+        .into_iter()
+        .map(|brush| {
+            (
+                MapEditorButton::Brush(brush),
+                brush.display(&terrain_manifest, &structure_manifest),
+            )
+        })
+        .chain([(MapEditorButton::Save, "Save map".to_string())]);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.),
+                    width: Val::Percent(100.),
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(4.),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            },
+            MapEditorPanel,
+        ))
+        .with_children(|parent| {
+            for (button, label) in buttons {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(4.)),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
+        });
+}
+
+/// Switches brushes and saves the map when the buttons of the panel are pressed.
+///
+/// The button of the current brush stays highlighted.
+fn press_map_editor_buttons(
+    mut button_query: Query<(&Interaction, &MapEditorButton, &mut BackgroundColor)>,
+    mut map_editor: ResMut<MapEditor>,
+    mut save_events: EventWriter<SaveScenario>,
+) {
+    for (interaction, &button, _) in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MapEditorButton::Brush(brush) => map_editor.brush = brush,
+            MapEditorButton::Save => save_events.send(SaveScenario),
+        }
+    }
+
+    for (interaction, &button, mut background_color) in button_query.iter_mut() {
+        let is_current_brush = button == MapEditorButton::Brush(map_editor.brush);
+        let color = match (interaction, is_current_brush) {
+            (Interaction::Pressed | Interaction::Hovered, _) | (_, true) => MENU_HIGHLIGHT_COLOR,
+            (Interaction::None, false) => MENU_NEUTRAL_COLOR,
+        };
+
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+}
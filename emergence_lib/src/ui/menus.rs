@@ -0,0 +1,539 @@
+//! The main menu, new game setup, load game, settings and pause menu screens.
+
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+
+use crate::{
+    game_state::{GameState, MenuHistory},
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    milestones::Profile,
+    player_interaction::selection::LineSelectionSettings,
+    save_files::list_saves,
+    simulation::{
+        game_rules::{Difficulty, GameRules},
+        time::TickRate,
+    },
+    world_gen::{preview::WorldPreview, scenario::list_scenarios, GenerationConfig},
+};
+
+use super::FiraSansFontFamily;
+
+/// Displays the screen for each menu [`GameState`].
+pub(super) struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::MainMenu), spawn_menu_camera)
+            .add_systems(OnEnter(GameState::Playing), despawn_menu_camera)
+            .add_systems(OnEnter(GameState::MapEditor), despawn_menu_camera)
+            .add_systems(
+                Update,
+                (
+                    press_menu_buttons,
+                    update_menu_button_labels,
+                    update_world_preview,
+                )
+                    .chain()
+                    .run_if(|game_state: Option<Res<State<GameState>>>| {
+                        game_state.is_some_and(|game_state| game_state.get().is_menu())
+                    }),
+            );
+
+        for menu in [
+            GameState::MainMenu,
+            GameState::NewGame,
+            GameState::LoadGame,
+            GameState::Settings,
+            GameState::PauseMenu,
+        ] {
+            app.add_systems(OnEnter(menu), spawn_menu_screen)
+                .add_systems(OnExit(menu), despawn_menu_screen);
+        }
+    }
+}
+
+/// The camera used to display the menus before the world has been generated.
+#[derive(Component)]
+struct MenuCamera;
+
+/// The root node of the current menu screen.
+#[derive(Component)]
+struct MenuScreen;
+
+/// Displays a [`WorldPreview`] of the world that will be generated.
+#[derive(Component)]
+struct WorldPreviewImage;
+
+/// A button in a menu, which performs its command when pressed.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct MenuButton(MenuCommand);
+
+/// What happens when a [`MenuButton`] is pressed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuCommand {
+    /// Opens another menu, which can be returned from.
+    Open(GameState),
+    /// Returns to the previous screen.
+    Back,
+    /// Closes the pause menu, returning to the game.
+    Resume,
+    /// Generates a new world and starts playing.
+    StartGame,
+    /// Generates a new world and opens it in the map editor.
+    EditMap,
+    /// Switches to the next hand-made [`Scenario`](crate::world_gen::scenario::Scenario), or back to a randomly generated map.
+    CycleScenario,
+    /// Picks a new random seed for world generation, rerolling the previewed world.
+    RandomizeSeed,
+    /// Switches to the next of the [`MenuCommand::MAP_RADII`].
+    CycleMapSize,
+    /// Switches to the next [`Difficulty`] preset, replacing any custom rules.
+    CycleDifficulty,
+    /// Turns [`GameRules::spoilage`] on or off.
+    ToggleSpoilage,
+    /// Switches to the next [`GameRules::starting_workforce`].
+    CycleStartingWorkforce,
+    /// Switches to the next [`GameRules::weather_severity`].
+    CycleWeatherSeverity,
+    /// Switches to the next [`GameRules::starting_resources`].
+    CycleStartingResources,
+    /// Switches between fullscreen and windowed mode.
+    ToggleFullscreen,
+    /// Turns [`LineSelectionSettings::snap_to_axes`] on or off.
+    ToggleLineSnapping,
+    /// Switches to the next of the [`TickRate::PRESETS`].
+    CycleTickRate,
+    /// Closes the game.
+    Quit,
+}
+
+impl MenuCommand {
+    /// The map radii that can be chosen when starting a new game.
+    const MAP_RADII: [u32; 3] = [15, 30, 50];
+
+    /// The text displayed on buttons with this command.
+    fn label(
+        &self,
+        generation_config: &GenerationConfig,
+        game_rules: &GameRules,
+        line_selection_settings: &LineSelectionSettings,
+        tick_rate: &TickRate,
+        window: Option<&Window>,
+    ) -> String {
+        /// Formats a multiplier relative to the standard rules.
+        fn percent(multiplier: f32) -> String {
+            format!("{:.0}%", multiplier * 100.)
+        }
+
+        match self {
+            MenuCommand::Open(GameState::NewGame) => "New Game".to_string(),
+            MenuCommand::Open(GameState::LoadGame) => "Load Game".to_string(),
+            MenuCommand::Open(GameState::Settings) => "Settings".to_string(),
+            MenuCommand::Open(game_state) => format!("{game_state:?}"),
+            MenuCommand::Back => "Back".to_string(),
+            MenuCommand::Resume => "Resume".to_string(),
+            MenuCommand::StartGame => "Start".to_string(),
+            MenuCommand::EditMap => "Edit map".to_string(),
+            MenuCommand::CycleScenario => match &generation_config.scenario {
+                Some(scenario) => format!("Map: {}", scenario.name),
+                None => "Map: Generated".to_string(),
+            },
+            MenuCommand::RandomizeSeed => format!("Reroll (seed {})", generation_config.seed),
+            MenuCommand::CycleMapSize => {
+                format!("Map radius: {}", generation_config.effective_map_radius())
+            }
+            MenuCommand::CycleDifficulty => match game_rules.difficulty() {
+                Some(difficulty) => format!("Difficulty: {difficulty}"),
+                None => "Difficulty: Custom".to_string(),
+            },
+            MenuCommand::ToggleSpoilage => match game_rules.spoilage {
+                true => "Spoilage: On".to_string(),
+                false => "Spoilage: Off".to_string(),
+            },
+            MenuCommand::CycleStartingWorkforce => {
+                format!(
+                    "Starting workforce: {}",
+                    percent(game_rules.starting_workforce)
+                )
+            }
+            MenuCommand::CycleWeatherSeverity => {
+                format!("Weather severity: {}", percent(game_rules.weather_severity))
+            }
+            MenuCommand::CycleStartingResources => format!(
+                "Starting resources: {}",
+                percent(game_rules.starting_resources)
+            ),
+            MenuCommand::ToggleFullscreen => match window.map(|window| window.mode) {
+                Some(WindowMode::Windowed) => "Display: Windowed".to_string(),
+                _ => "Display: Fullscreen".to_string(),
+            },
+            MenuCommand::ToggleLineSnapping => match line_selection_settings.snap_to_axes {
+                true => "Snap lines to axes: On".to_string(),
+                false => "Snap lines to axes: Off".to_string(),
+            },
+            MenuCommand::CycleTickRate => {
+                format!("Simulation rate: {} ticks/s", tick_rate.ticks_per_second())
+            }
+            MenuCommand::Quit => "Quit".to_string(),
+        }
+    }
+}
+
+/// Spawns a camera to render the main menu.
+fn spawn_menu_camera(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), MenuCamera));
+}
+
+/// Removes the menu camera once the game has started, as the game has its own.
+fn despawn_menu_camera(mut commands: Commands, camera_query: Query<Entity, With<MenuCamera>>) {
+    for entity in camera_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Builds the screen for the menu that was just opened.
+fn spawn_menu_screen(
+    mut commands: Commands,
+    game_state: Res<State<GameState>>,
+    fonts: Res<FiraSansFontFamily>,
+    profile: Res<Profile>,
+) {
+    let game_state = *game_state.get();
+
+    let title_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 48.,
+        color: Color::WHITE,
+    };
+    let button_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 24.,
+        color: Color::BLACK,
+    };
+    let detail_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 18.,
+        color: Color::WHITE,
+    };
+
+    let (title, commands_to_show) = match game_state {
+        GameState::MainMenu => (
+            "Emergence",
+            vec![
+                MenuCommand::Open(GameState::NewGame),
+                MenuCommand::Open(GameState::LoadGame),
+                MenuCommand::Open(GameState::Settings),
+                MenuCommand::Quit,
+            ],
+        ),
+        GameState::NewGame => (
+            "New Game",
+            vec![
+                MenuCommand::CycleScenario,
+                MenuCommand::RandomizeSeed,
+                MenuCommand::CycleMapSize,
+                MenuCommand::CycleDifficulty,
+                MenuCommand::ToggleSpoilage,
+                MenuCommand::CycleStartingWorkforce,
+                MenuCommand::CycleWeatherSeverity,
+                MenuCommand::CycleStartingResources,
+                MenuCommand::StartGame,
+                MenuCommand::EditMap,
+                MenuCommand::Back,
+            ],
+        ),
+        GameState::LoadGame => ("Load Game", vec![MenuCommand::Back]),
+        GameState::Settings => (
+            "Settings",
+            vec![
+                MenuCommand::ToggleFullscreen,
+                MenuCommand::ToggleLineSnapping,
+                MenuCommand::CycleTickRate,
+                MenuCommand::Back,
+            ],
+        ),
+        GameState::PauseMenu => (
+            "Paused",
+            vec![
+                MenuCommand::Resume,
+                MenuCommand::Open(GameState::Settings),
+                MenuCommand::Quit,
+            ],
+        ),
+        GameState::Playing | GameState::MapEditor => return,
+    };
+
+    // The pause menu is drawn over the game, which should remain visible behind it
+    let background_alpha = match game_state {
+        GameState::PauseMenu | GameState::Settings => 0.7,
+        _ => 1.,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.05, 0.05, 0.05, background_alpha)),
+                // Menus are drawn over the rest of the UI
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            MenuScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(title, title_style));
+
+            if game_state == GameState::LoadGame {
+                let saves = list_saves(&profile);
+                if saves.is_empty() {
+                    parent.spawn(TextBundle::from_section(
+                        "No saved games",
+                        detail_style.clone(),
+                    ));
+                }
+
+                for (path, summary) in saves {
+                    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                    let metadata = summary.metadata;
+                    let minutes_played = metadata.play_time.as_secs() / 60;
+                    parent.spawn(TextBundle::from_section(
+                        format!(
+                            "{name}: day {:.0}, played for {}h {:02}m, {} (version {})",
+                            metadata.elapsed_days,
+                            minutes_played / 60,
+                            minutes_played % 60,
+                            metadata
+                                .game_rules
+                                .difficulty()
+                                .map_or("Custom".to_string(), |difficulty| difficulty.to_string()),
+                            metadata.game_version
+                        ),
+                        detail_style.clone(),
+                    ));
+                }
+
+                // FIXME: load the selected save, once worlds can be serialized
+                parent.spawn(TextBundle::from_section(
+                    "Loading saved worlds is not supported yet",
+                    detail_style.clone(),
+                ));
+            }
+
+            if game_state == GameState::NewGame {
+                parent.spawn((
+                    ImageBundle {
+                        style: Style {
+                            width: Val::Px(WORLD_PREVIEW_SIZE as f32),
+                            height: Val::Px(WORLD_PREVIEW_SIZE as f32),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    WorldPreviewImage,
+                ));
+            }
+
+            for command in commands_to_show {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(300.),
+                                padding: UiRect::all(Val::Px(8.)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                            ..default()
+                        },
+                        MenuButton(command),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            // The label is filled in by `update_menu_button_labels`
+                            text: Text::from_section("", button_style.clone()),
+                            ..default()
+                        });
+                    });
+            }
+        });
+}
+
+/// The width and height of the [`WorldPreview`] shown in the new game menu, in pixels.
+const WORLD_PREVIEW_SIZE: u32 = 256;
+
+/// Regenerates the [`WorldPreview`] whenever the settings used to generate the world change.
+fn update_world_preview(
+    mut image_query: Query<(Ref<WorldPreviewImage>, &mut UiImage)>,
+    generation_config: Res<GenerationConfig>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (preview_image, mut ui_image) in image_query.iter_mut() {
+        if !generation_config.is_changed() && !preview_image.is_added() {
+            continue;
+        }
+
+        let preview = WorldPreview::generate(&generation_config);
+        let old_handle = std::mem::replace(
+            &mut ui_image.texture,
+            images.add(preview.to_image(WORLD_PREVIEW_SIZE)),
+        );
+        // The default image is shared, so only previews that we created should be removed
+        if old_handle.is_strong() {
+            images.remove(&old_handle);
+        }
+    }
+}
+
+/// Removes the screen for the menu that was just closed.
+fn despawn_menu_screen(mut commands: Commands, screen_query: Query<Entity, With<MenuScreen>>) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Performs the commands of any menu buttons that are pressed.
+fn press_menu_buttons(
+    mut button_query: Query<
+        (&Interaction, &MenuButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut menu_history: ResMut<MenuHistory>,
+    mut generation_config: ResMut<GenerationConfig>,
+    mut game_rules: ResMut<GameRules>,
+    mut line_selection_settings: ResMut<LineSelectionSettings>,
+    mut tick_rate: ResMut<TickRate>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut app_exit_events: EventWriter<AppExit>,
+    profile: Res<Profile>,
+) {
+    for (interaction, &MenuButton(command), mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match command {
+            MenuCommand::Open(menu) => {
+                next_game_state.set(menu_history.open(*game_state.get(), menu));
+            }
+            MenuCommand::Back | MenuCommand::Resume => {
+                if let Some(previous) = menu_history.back() {
+                    next_game_state.set(previous);
+                }
+            }
+            MenuCommand::StartGame => {
+                menu_history.clear();
+                next_game_state.set(GameState::Playing);
+            }
+            MenuCommand::EditMap => {
+                menu_history.clear();
+                next_game_state.set(GameState::MapEditor);
+            }
+            MenuCommand::CycleScenario => {
+                let scenarios = list_scenarios(&profile);
+                let current_index = generation_config.scenario.as_ref().and_then(|current| {
+                    scenarios
+                        .iter()
+                        .position(|scenario| scenario.name == current.name)
+                });
+                let next_index = match current_index {
+                    Some(index) => index + 1,
+                    None => 0,
+                };
+                // Wrap back around to a generated map after the last scenario
+                generation_config.scenario = scenarios.into_iter().nth(next_index);
+            }
+            MenuCommand::RandomizeSeed => generation_config.seed = rand::random(),
+            MenuCommand::CycleMapSize => {
+                let radii = MenuCommand::MAP_RADII;
+                generation_config.map_radius = radii
+                    .iter()
+                    .copied()
+                    .find(|&radius| radius > generation_config.map_radius)
+                    .unwrap_or(radii[0]);
+            }
+            MenuCommand::CycleDifficulty => {
+                let difficulty = game_rules
+                    .difficulty()
+                    .map_or(Difficulty::Standard, |difficulty| difficulty.next());
+                *game_rules = difficulty.rules();
+            }
+            MenuCommand::ToggleSpoilage => game_rules.spoilage = !game_rules.spoilage,
+            MenuCommand::CycleStartingWorkforce => {
+                game_rules.starting_workforce =
+                    GameRules::next_multiplier(game_rules.starting_workforce);
+            }
+            MenuCommand::CycleWeatherSeverity => {
+                game_rules.weather_severity =
+                    GameRules::next_multiplier(game_rules.weather_severity);
+            }
+            MenuCommand::CycleStartingResources => {
+                game_rules.starting_resources =
+                    GameRules::next_multiplier(game_rules.starting_resources);
+            }
+            MenuCommand::ToggleFullscreen => {
+                if let Ok(mut window) = window_query.get_single_mut() {
+                    window.mode = match window.mode {
+                        WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+                        _ => WindowMode::Windowed,
+                    };
+                }
+            }
+            MenuCommand::ToggleLineSnapping => {
+                line_selection_settings.snap_to_axes = !line_selection_settings.snap_to_axes;
+            }
+            MenuCommand::CycleTickRate => *tick_rate = tick_rate.next(),
+            MenuCommand::Quit => app_exit_events.send(AppExit),
+        }
+    }
+}
+
+/// Shows the current settings on the menu buttons.
+fn update_menu_button_labels(
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+    generation_config: Res<GenerationConfig>,
+    game_rules: Res<GameRules>,
+    line_selection_settings: Res<LineSelectionSettings>,
+    tick_rate: Res<TickRate>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let window = window_query.get_single().ok();
+
+    for (MenuButton(command), children) in button_query.iter() {
+        let label = command.label(
+            &generation_config,
+            &game_rules,
+            &line_selection_settings,
+            &tick_rate,
+            window,
+        );
+
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                if text.sections[0].value != label {
+                    text.sections[0].value = label.clone();
+                }
+            }
+        }
+    }
+}
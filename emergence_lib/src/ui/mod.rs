@@ -2,32 +2,71 @@
 
 use crate::{
     asset_management::{manifest::Id, AssetCollectionExt},
+    codex::CodexPlugin,
     construction::terraform::TerraformingTool,
     structures::structure_manifest::Structure,
     ui::{
+        action_bar::ActionBarPlugin,
+        confirmation::ConfirmationPanelPlugin,
+        corpse_policy::CorpsePolicyPlugin,
+        craft_orders::CraftOrdersPlugin,
         cursor::CursorPlugin,
+        daily_report::DailyReportPlugin,
+        event_cards::EventCardsPlugin,
+        hauling_priorities::HaulingPrioritiesPlugin,
+        loading_screen::LoadingScreenPlugin,
+        map_editor::MapEditorPanelPlugin,
+        menus::MenuPlugin,
+        nicknames::RenamePlugin,
+        output_routing::OutputRoutingPanelPlugin,
         overlay::OverlayMenuPlugin,
+        population_targets::PopulationTargetsPlugin,
+        production_planner::ProductionPlannerPlugin,
         production_statistics::ProductionStatisticsPlugin,
+        recipe_queue::RecipeQueuePanelPlugin,
+        resources_overview::ResourcesOverviewPlugin,
+        search::SearchPlugin,
         select_structure::SelectStructurePlugin,
         select_terraforming::SelectTerraformingPlugin,
         selection_details::SelectionDetailsPlugin,
         status::{CraftingProgress, StatusPlugin},
+        trading::TradingPanelPlugin,
         ui_assets::{Icons, UiElements},
+        work_orders::WorkOrderListPlugin,
     },
     units::{goals::GoalKind, unit_manifest::Unit},
 };
 use bevy::prelude::*;
 use bevy_screen_diagnostics::{ScreenDiagnosticsPlugin, ScreenFrameDiagnosticsPlugin};
 
+mod action_bar;
+mod confirmation;
+mod corpse_policy;
+mod craft_orders;
 mod cursor;
+mod daily_report;
+mod event_cards;
+mod hauling_priorities;
+mod loading_screen;
+mod map_editor;
+mod menus;
+mod nicknames;
+mod output_routing;
 mod overlay;
+mod population_targets;
+mod production_planner;
 mod production_statistics;
+mod recipe_queue;
+mod resources_overview;
+mod search;
 mod select_structure;
 mod select_terraforming;
 mod selection_details;
 mod status;
+mod trading;
 mod ui_assets;
 mod wheel_menu;
+mod work_orders;
 
 /// The font handles for the `FiraSans` font family.
 ///
@@ -62,10 +101,31 @@ impl Plugin for UiPlugin {
         .add_plugins(CursorPlugin)
         .add_plugins(SelectionDetailsPlugin)
         .add_plugins(ProductionStatisticsPlugin)
+        .add_plugins(ProductionPlannerPlugin)
         .add_plugins(StatusPlugin)
         .add_plugins(OverlayMenuPlugin)
         .add_plugins(SelectStructurePlugin)
-        .add_plugins(SelectTerraformingPlugin);
+        .add_plugins(SelectTerraformingPlugin)
+        .add_plugins(WorkOrderListPlugin)
+        .add_plugins(ActionBarPlugin)
+        .add_plugins(SearchPlugin)
+        .add_plugins(RenamePlugin)
+        .add_plugins(RecipeQueuePanelPlugin)
+        .add_plugins(OutputRoutingPanelPlugin)
+        .add_plugins(ConfirmationPanelPlugin)
+        .add_plugins(DailyReportPlugin)
+        .add_plugins(ResourcesOverviewPlugin)
+        .add_plugins(CraftOrdersPlugin)
+        .add_plugins(HaulingPrioritiesPlugin)
+        .add_plugins(CorpsePolicyPlugin)
+        .add_plugins(PopulationTargetsPlugin)
+        .add_plugins(MenuPlugin)
+        .add_plugins(LoadingScreenPlugin)
+        .add_plugins(EventCardsPlugin)
+        .add_plugins(TradingPanelPlugin)
+        .add_plugins(MapEditorPanelPlugin)
+        // The codex is generated here, as only the UI needs it
+        .add_plugins(CodexPlugin);
     }
 }
 
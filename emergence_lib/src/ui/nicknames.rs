@@ -0,0 +1,206 @@
+//! A text box for giving the selected unit or structure a [`Nickname`].
+//!
+//! While the text box is open, keystrokes are captured as text rather than treated as keybindings.
+
+use bevy::{prelude::*, utils::HashSet};
+use leafwing_input_manager::prelude::{ActionState, ToggleActions};
+
+use crate::{
+    geometry::{MapGeometry, VoxelKind},
+    player_interaction::{
+        feedback::InteractionEvent,
+        nicknames::{Nickname, SetNickname},
+        selection::CurrentSelection,
+        PlayerAction,
+    },
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Lets players name the selected unit or structure.
+pub(super) struct RenamePlugin;
+
+impl Plugin for RenamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RenameState>()
+            .add_systems(Startup, spawn_rename_panel)
+            .add_systems(
+                Update,
+                (
+                    open_rename,
+                    edit_rename.after(open_rename),
+                    update_rename_panel.after(edit_rename),
+                )
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// The current state of the rename box.
+#[derive(Resource, Debug, Default)]
+struct RenameState {
+    /// The entity being renamed, if the rename box is open.
+    target: Option<Entity>,
+    /// The text that the player has typed.
+    name: String,
+}
+
+impl RenameState {
+    /// Closes the rename box.
+    fn close(&mut self) {
+        *self = RenameState::default();
+    }
+}
+
+/// Marker component for the rename panel UI.
+#[derive(Component)]
+struct RenamePanel;
+
+/// Initializes the rename panel, hidden until renaming begins.
+fn spawn_rename_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let rename_panel_entity = commands
+        .spawn(TextBundle {
+            text: Text::from_section("", style),
+            style: Style {
+                display: Display::None,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(RenamePanel)
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(rename_panel_entity);
+}
+
+/// Finds the single unit or structure that is currently selected, if any.
+pub(super) fn selected_nameable(
+    current_selection: &CurrentSelection,
+    map_geometry: &MapGeometry,
+) -> Option<Entity> {
+    match current_selection {
+        CurrentSelection::Structure(entity) | CurrentSelection::Unit(entity) => Some(*entity),
+        CurrentSelection::Voxels(selected_voxels) => {
+            // Structures that span several voxels show up once per voxel
+            let structures: HashSet<Entity> = selected_voxels
+                .voxel_objects(map_geometry)
+                .into_iter()
+                .filter(|voxel_object| {
+                    matches!(voxel_object.object_kind, VoxelKind::Structure { .. })
+                })
+                .map(|voxel_object| voxel_object.entity)
+                .collect();
+
+            match structures.len() {
+                1 => structures.into_iter().next(),
+                _ => None,
+            }
+        }
+        CurrentSelection::None => None,
+    }
+}
+
+/// Opens the rename box for the selected unit or structure, pre-filled with its current nickname.
+fn open_rename(
+    actions: Res<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    nickname_query: Query<&Nickname>,
+    map_geometry: Res<MapGeometry>,
+    mut rename_state: ResMut<RenameState>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+    mut interaction_events: EventWriter<InteractionEvent>,
+) {
+    if !actions.just_pressed(PlayerAction::Rename) {
+        return;
+    }
+
+    let Some(target) = selected_nameable(&current_selection, &map_geometry) else {
+        interaction_events.send(InteractionEvent::InvalidAction);
+        return;
+    };
+
+    *rename_state = RenameState {
+        target: Some(target),
+        name: nickname_query
+            .get(target)
+            .map(|nickname| nickname.as_str().to_string())
+            .unwrap_or_default(),
+    };
+    toggle_actions.enabled = false;
+}
+
+/// Handles typing into the open rename box.
+///
+/// Enter applies the new name (clearing it if the box is empty), and Escape cancels.
+fn edit_rename(
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut rename_state: ResMut<RenameState>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+    mut set_nickname_events: EventWriter<SetNickname>,
+) {
+    let Some(target) = rename_state.target else {
+        received_characters.clear();
+        return;
+    };
+
+    for received_character in received_characters.read() {
+        if !received_character.char.is_control()
+            && rename_state.name.chars().count() < Nickname::MAX_LENGTH
+        {
+            rename_state.name.push(received_character.char);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        rename_state.name.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        set_nickname_events.send(SetNickname {
+            entity: target,
+            nickname: Nickname::new(&rename_state.name),
+        });
+        rename_state.close();
+        toggle_actions.enabled = true;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        rename_state.close();
+        toggle_actions.enabled = true;
+    }
+}
+
+/// Displays the rename box.
+fn update_rename_panel(
+    rename_state: Res<RenameState>,
+    mut rename_panel_query: Query<(&mut Text, &mut Style), With<RenamePanel>>,
+) {
+    if !rename_state.is_changed() {
+        return;
+    }
+
+    let (mut text, mut style) = rename_panel_query.single_mut();
+
+    if rename_state.target.is_none() {
+        style.display = Display::None;
+        return;
+    }
+
+    style.display = Display::Flex;
+    text.sections[0].value = format!("Name: {}_", rename_state.name);
+}
@@ -0,0 +1,259 @@
+//! Lets the player choose where the outputs of the selected structure are taken.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    crafting::inventories::{OutputInventory, StorageInventory},
+    geometry::{MapGeometry, VoxelPos},
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    logistics::{OutputRouting, SetOutputRouting},
+    multiplayer::actions::{SharedAction, SharedActions, SharedRouting},
+    player_interaction::selection::CurrentSelection,
+    structures::structure_manifest::{Structure, StructureManifest},
+    world_gen::WorldGenState,
+};
+
+use super::{nicknames::selected_nameable, FiraSansFontFamily, RightPanel};
+
+/// Displays and edits the [`OutputRouting`] of the selected structure.
+pub(super) struct OutputRoutingPanelPlugin;
+
+impl Plugin for OutputRoutingPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_output_routing_panel)
+            .add_systems(
+                Update,
+                (update_output_routing_panel, press_output_routing_buttons)
+                    .chain()
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// The number of nearby storage structures offered as destinations.
+const N_STORAGE_CHOICES: usize = 3;
+
+/// Marker component for the root node of the output routing panel.
+#[derive(Component)]
+struct OutputRoutingPanel;
+
+/// Marker component for the text describing the current routing.
+#[derive(Component)]
+struct OutputRoutingLabel;
+
+/// Marker component for the node that holds the buttons.
+#[derive(Component)]
+struct OutputRoutingButtonList;
+
+/// A button that changes the routing of a structure when pressed.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct OutputRoutingButton(SetOutputRouting);
+
+/// Initializes the output routing panel, hidden until a structure with outputs is selected.
+fn spawn_output_routing_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let panel_entity = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.),
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                ..default()
+            },
+            OutputRoutingPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("", text_style),
+                    ..default()
+                },
+                OutputRoutingLabel,
+            ));
+
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        column_gap: Val::Px(2.),
+                        row_gap: Val::Px(2.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                OutputRoutingButtonList,
+            ));
+        })
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands.entity(right_panel_entity).add_child(panel_entity);
+}
+
+/// Shows the routing of the selected structure, and rebuilds the buttons whenever a different structure is selected.
+///
+/// Besides the general options, the nearest few storage structures are offered as destinations.
+fn update_output_routing_panel(
+    current_selection: Res<CurrentSelection>,
+    map_geometry: Res<MapGeometry>,
+    output_query: Query<
+        (&VoxelPos, Option<&OutputRouting>),
+        (With<OutputInventory>, Without<Ghost>, Without<Preview>),
+    >,
+    storage_query: Query<
+        (Entity, &VoxelPos, &Id<Structure>),
+        (With<StorageInventory>, Without<Ghost>, Without<Preview>),
+    >,
+    mut panel_query: Query<&mut Style, With<OutputRoutingPanel>>,
+    mut label_query: Query<&mut Text, With<OutputRoutingLabel>>,
+    list_query: Query<Entity, With<OutputRoutingButtonList>>,
+    structure_manifest: Res<StructureManifest>,
+    fonts: Res<FiraSansFontFamily>,
+    mut previous_target: Local<Option<Entity>>,
+    mut commands: Commands,
+) {
+    let target = selected_nameable(&current_selection, &map_geometry)
+        .and_then(|entity| Some((entity, output_query.get(entity).ok()?)));
+
+    let mut panel_style = panel_query.single_mut();
+    let Some((target_entity, (&voxel_pos, maybe_routing))) = target else {
+        panel_style.display = Display::None;
+        *previous_target = None;
+        return;
+    };
+
+    panel_style.display = Display::Flex;
+    label_query.single_mut().sections[0].value = format!(
+        "Outputs routed to: {}",
+        maybe_routing.map_or("Any".to_string(), |routing| routing.to_string())
+    );
+
+    if *previous_target == Some(target_entity) {
+        return;
+    }
+    *previous_target = Some(target_entity);
+
+    let mut nearby_storage: Vec<(Entity, u32, Id<Structure>)> = storage_query
+        .iter()
+        .filter(|(entity, ..)| *entity != target_entity)
+        .map(|(entity, storage_pos, &structure_id)| {
+            (
+                entity,
+                storage_pos.hex.unsigned_distance_to(voxel_pos.hex),
+                structure_id,
+            )
+        })
+        .collect();
+    nearby_storage.sort_by_key(|&(entity, distance, _)| (distance, entity));
+    nearby_storage.truncate(N_STORAGE_CHOICES);
+
+    let button_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 14.,
+        color: Color::BLACK,
+    };
+
+    let buttons =
+        [
+            ("Any".to_string(), None),
+            ("Hold".to_string(), Some(OutputRouting::Hold)),
+            (
+                "Nearest storage".to_string(),
+                Some(OutputRouting::NearestStorage),
+            ),
+        ]
+        .into_iter()
+        .chain(nearby_storage.into_iter().map(
+            |(storage_entity, distance, structure_id)| {
+                (
+                    format!(
+                        "{} ({distance} tiles)",
+                        structure_manifest.name(structure_id)
+                    ),
+                    Some(OutputRouting::Storage(storage_entity)),
+                )
+            },
+        ));
+
+    commands
+        .entity(list_query.single())
+        .despawn_descendants()
+        .with_children(|parent| {
+            for (label, routing) in buttons {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(2.)),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                            ..default()
+                        },
+                        OutputRoutingButton(SetOutputRouting {
+                            structure: target_entity,
+                            routing,
+                        }),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(label, button_style.clone()),
+                            ..default()
+                        });
+                    });
+            }
+        });
+}
+
+/// Routes the outputs of the selected structure when a button is pressed.
+fn press_output_routing_buttons(
+    mut button_query: Query<
+        (&Interaction, &OutputRoutingButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    position_query: Query<&VoxelPos>,
+    mut shared_actions: SharedActions,
+) {
+    for (interaction, &OutputRoutingButton(event), mut background_color) in button_query.iter_mut()
+    {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Ok(&structure) = position_query.get(event.structure) else {
+            continue;
+        };
+
+        let routing = match event.routing {
+            Some(routing) => match SharedRouting::from_routing(routing, &position_query) {
+                Some(routing) => Some(routing),
+                None => continue,
+            },
+            None => None,
+        };
+
+        shared_actions.take(SharedAction::RouteOutputs { structure, routing });
+    }
+}
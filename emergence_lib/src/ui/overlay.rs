@@ -2,10 +2,14 @@
 
 use crate::{
     asset_management::AssetState,
-    graphics::overlay::{OverlayType, TileOverlay},
+    factions::Factions,
+    graphics::{
+        overlay::{OverlayType, TileOverlay},
+        palette::infovis::FACTION_COLORS,
+    },
     items::item_manifest::ItemManifest,
     player_interaction::PlayerAction,
-    signals::{SignalKind, Signals},
+    signals::{SignalChannels, SignalKind},
     structures::structure_manifest::StructureManifest,
     terrain::terrain_manifest::TerrainManifest,
     units::unit_manifest::UnitManifest,
@@ -43,7 +47,7 @@ fn select_overlay(
     // FIXME: use an actual UI widget for this...
     player_actions: Res<ActionState<PlayerAction>>,
     mut tile_overlay: ResMut<TileOverlay>,
-    signals: Res<Signals>,
+    signal_channels: Res<SignalChannels>,
 ) {
     if player_actions.just_pressed(PlayerAction::ToggleStrongestSignalOverlay) {
         if tile_overlay.overlay_type != OverlayType::StrongestSignal {
@@ -55,7 +59,7 @@ fn select_overlay(
 
     if player_actions.just_pressed(PlayerAction::ToggleSignalOverlay) {
         // FIXME: this is very silly, but it's the easiest way to get and cycle signal types
-        tile_overlay.overlay_type = signals.random_signal_type().into();
+        tile_overlay.overlay_type = signal_channels.player().random_signal_type().into();
     }
 
     if player_actions.just_pressed(PlayerAction::ToggleWaterTableOverlay) {
@@ -74,6 +78,27 @@ fn select_overlay(
             _ => OverlayType::LightLevel,
         };
     }
+
+    if player_actions.just_pressed(PlayerAction::ToggleTemperatureOverlay) {
+        tile_overlay.overlay_type = match tile_overlay.overlay_type {
+            OverlayType::Temperature => OverlayType::None,
+            _ => OverlayType::Temperature,
+        };
+    }
+
+    if player_actions.just_pressed(PlayerAction::ToggleTerritoryOverlay) {
+        tile_overlay.overlay_type = match tile_overlay.overlay_type {
+            OverlayType::Territory => OverlayType::None,
+            _ => OverlayType::Territory,
+        };
+    }
+
+    if player_actions.just_pressed(PlayerAction::ToggleTrafficOverlay) {
+        tile_overlay.overlay_type = match tile_overlay.overlay_type {
+            OverlayType::Traffic => OverlayType::None,
+            _ => OverlayType::Traffic,
+        };
+    }
 }
 
 /// Creates the UI needed to display the overlay.
@@ -132,6 +157,7 @@ fn update_signal_type_display(
     structure_manifest: Res<StructureManifest>,
     terrain_manifest: Res<TerrainManifest>,
     unit_manifest: Res<UnitManifest>,
+    factions: Res<Factions>,
 ) {
     let mut text = text_query.get_mut(overlay_menu.signal_type_entity).unwrap();
     let mut legend = image_query.get_mut(overlay_menu.legend_entity).unwrap();
@@ -277,5 +303,56 @@ fn update_signal_type_display(
             // TODO: add a legend for light levels
             legend.texture = Handle::default();
         }
+        OverlayType::Temperature => {
+            text.sections = vec![TextSection {
+                value: "Temperature".to_string(),
+                style: TextStyle {
+                    font: fonts.regular.clone_weak(),
+                    font_size,
+                    color: Color::WHITE,
+                },
+            }];
+
+            legend.texture = tile_overlay.temperature_legend_image_handle();
+        }
+        OverlayType::Territory => {
+            text.sections = factions
+                .iter()
+                .map(|faction| TextSection {
+                    value: format!("{faction}\n"),
+                    style: TextStyle {
+                        font: fonts.regular.clone_weak(),
+                        font_size,
+                        color: FACTION_COLORS[faction.0 as usize % FACTION_COLORS.len()].with_a(1.),
+                    },
+                })
+                .collect();
+
+            legend.texture = Handle::default();
+        }
+        OverlayType::Traffic => {
+            text.sections = vec![TextSection {
+                value: "Unit traffic".to_string(),
+                style: TextStyle {
+                    font: fonts.regular.clone_weak(),
+                    font_size,
+                    color: Color::WHITE,
+                },
+            }];
+
+            legend.texture = tile_overlay.traffic_legend_image_handle();
+        }
+        OverlayType::PathPreview => {
+            text.sections = vec![TextSection {
+                value: "Path preview".to_string(),
+                style: TextStyle {
+                    font: fonts.regular.clone_weak(),
+                    font_size,
+                    color: Color::WHITE,
+                },
+            }];
+
+            legend.texture = Handle::default();
+        }
     }
 }
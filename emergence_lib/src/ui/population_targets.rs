@@ -0,0 +1,281 @@
+//! Lets the player cap the population of each species.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::{manifest::Id, AssetState},
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    multiplayer::actions::{SharedAction, SharedActions},
+    units::{
+        census::{Census, PopulationTargets},
+        unit_manifest::{Unit, UnitManifest},
+    },
+};
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// Displays and edits the [`PopulationTargets`].
+pub(super) struct PopulationTargetsPlugin;
+
+impl Plugin for PopulationTargetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_population_target_panel)
+            .add_systems(
+                Update,
+                (
+                    populate_population_target_rows,
+                    press_population_target_buttons,
+                    update_population_target_labels,
+                )
+                    .chain()
+                    .run_if(in_state(AssetState::FullyLoaded)),
+            );
+    }
+}
+
+/// How much each press of a button changes the target population.
+const TARGET_STEP: usize = 5;
+
+/// Marker component for the node that holds one row per species.
+#[derive(Component)]
+struct PopulationTargetList;
+
+/// The label showing the population and target of a species.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct PopulationTargetLabel(Id<Unit>);
+
+/// A button that changes the population target of a species when pressed.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct PopulationTargetButton {
+    /// The species whose target is changed.
+    unit_id: Id<Unit>,
+    /// How the target is changed.
+    change: TargetChange,
+}
+
+/// The ways that a [`PopulationTargetButton`] can change a population target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetChange {
+    /// Lowers the target, starting from the current population if there is no target yet.
+    Lower,
+    /// Raises the target.
+    Raise,
+    /// Removes the target entirely.
+    Clear,
+}
+
+impl TargetChange {
+    /// The text shown on the button.
+    fn label(&self) -> &'static str {
+        match self {
+            TargetChange::Lower => "-",
+            TargetChange::Raise => "+",
+            TargetChange::Clear => "Any",
+        }
+    }
+
+    /// The target of `unit_id` after this change, or `None` if its population may grow freely.
+    fn target(
+        &self,
+        unit_id: Id<Unit>,
+        population_targets: &PopulationTargets,
+        census: &Census,
+    ) -> Option<usize> {
+        let current_target = population_targets.target(unit_id);
+
+        match self {
+            TargetChange::Lower => {
+                let target = current_target.unwrap_or_else(|| census.population(unit_id));
+                Some(target.saturating_sub(TARGET_STEP))
+            }
+            // Without a target the population is already unlimited
+            TargetChange::Raise => current_target.map(|target| target + TARGET_STEP),
+            TargetChange::Clear => None,
+        }
+    }
+}
+
+/// Initializes the empty population target panel.
+///
+/// The rows are added once the unit manifest has loaded.
+fn spawn_population_target_panel(
+    mut commands: Commands,
+    left_panel_query: Query<Entity, With<LeftPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let panel_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section("Population targets", text_style),
+                ..default()
+            });
+
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                PopulationTargetList,
+            ));
+        })
+        .id();
+
+    let left_panel_entity = left_panel_query.single();
+    commands.entity(left_panel_entity).add_child(panel_entity);
+}
+
+/// Creates a row of buttons for each species, whenever the unit manifest changes.
+fn populate_population_target_rows(
+    list_query: Query<Entity, With<PopulationTargetList>>,
+    unit_manifest: Res<UnitManifest>,
+    fonts: Res<FiraSansFontFamily>,
+    mut commands: Commands,
+) {
+    if !unit_manifest.is_changed() {
+        return;
+    }
+
+    let label_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 14.,
+        color: Color::WHITE,
+    };
+
+    let button_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 14.,
+        color: Color::BLACK,
+    };
+
+    let mut unit_ids: Vec<Id<Unit>> = unit_manifest.variants().into_iter().collect();
+    // Sort to ensure a stable ordering
+    unit_ids.sort_by_key(|&unit_id| unit_manifest.name(unit_id).to_string());
+
+    let list_entity = list_query.single();
+    commands
+        .entity(list_entity)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for unit_id in unit_ids {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(4.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle {
+                                // The label is filled in by `update_population_target_labels`
+                                text: Text::from_section("", label_style.clone()),
+                                ..default()
+                            },
+                            PopulationTargetLabel(unit_id),
+                        ));
+
+                        for change in [
+                            TargetChange::Lower,
+                            TargetChange::Raise,
+                            TargetChange::Clear,
+                        ] {
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: Style {
+                                            padding: UiRect::all(Val::Px(2.)),
+                                            ..default()
+                                        },
+                                        background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                                        ..default()
+                                    },
+                                    PopulationTargetButton { unit_id, change },
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle {
+                                        text: Text::from_section(
+                                            change.label(),
+                                            button_style.clone(),
+                                        ),
+                                        ..default()
+                                    });
+                                });
+                        }
+                    });
+            }
+        });
+}
+
+/// Changes the population target of a species when one of its buttons is pressed.
+fn press_population_target_buttons(
+    mut button_query: Query<
+        (&Interaction, &PopulationTargetButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    population_targets: Res<PopulationTargets>,
+    census: Res<Census>,
+    mut shared_actions: SharedActions,
+) {
+    for (interaction, button, mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction == Interaction::Pressed {
+            shared_actions.take(SharedAction::SetPopulationTarget {
+                unit_id: button.unit_id,
+                target: button
+                    .change
+                    .target(button.unit_id, &population_targets, &census),
+            });
+        }
+    }
+}
+
+/// Shows the current population and target of each species.
+fn update_population_target_labels(
+    mut label_query: Query<(Ref<PopulationTargetLabel>, &mut Text)>,
+    population_targets: Res<PopulationTargets>,
+    census: Res<Census>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    for (label, mut text) in label_query.iter_mut() {
+        if !population_targets.is_changed() && !census.is_changed() && !label.is_added() {
+            continue;
+        }
+
+        let PopulationTargetLabel(unit_id) = *label;
+        let target = match population_targets.target(unit_id) {
+            Some(target) => target.to_string(),
+            None => "any".to_string(),
+        };
+
+        text.sections[0].value = format!(
+            "{}: {} / {}",
+            unit_manifest.name(unit_id),
+            census.population(unit_id),
+            target
+        );
+    }
+}
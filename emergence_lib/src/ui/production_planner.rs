@@ -0,0 +1,110 @@
+//! Shows what it takes to keep the selected crafter's recipe running, using the [`RecipeGraph`].
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::AssetState,
+    crafting::{
+        recipe::{ActiveRecipe, RecipeManifest},
+        recipe_graph::{ProductionTarget, RecipeGraph},
+    },
+    geometry::MapGeometry,
+    items::item_manifest::ItemManifest,
+    player_interaction::selection::CurrentSelection,
+    structures::structure_manifest::StructureManifest,
+    units::unit_manifest::UnitManifest,
+};
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// Displays the production plan for the selected crafter.
+pub(super) struct ProductionPlannerPlugin;
+
+impl Plugin for ProductionPlannerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_production_planner)
+            .add_systems(
+                Update,
+                update_production_planner.run_if(in_state(AssetState::FullyLoaded)),
+            );
+    }
+}
+
+/// Marker component for the production planner text.
+#[derive(Component)]
+struct ProductionPlanner;
+
+/// Spawns the (initially empty) production planner text in the left panel.
+fn spawn_production_planner(
+    mut commands: Commands,
+    left_panel_query: Query<Entity, With<LeftPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 20.,
+        color: Color::WHITE,
+    };
+
+    let production_planner_entity = commands
+        .spawn((TextBundle::from_section("", style), ProductionPlanner))
+        .id();
+
+    let left_panel_entity = left_panel_query.single();
+    commands
+        .entity(left_panel_entity)
+        .add_child(production_planner_entity);
+}
+
+/// Shows the inputs and crafters needed to make one of the selected crafter's products every minute.
+///
+/// Only the first output of the recipe is planned for, or the unit that it hatches.
+fn update_production_planner(
+    mut text_query: Query<&mut Text, With<ProductionPlanner>>,
+    current_selection: Res<CurrentSelection>,
+    map_geometry: Res<MapGeometry>,
+    active_recipe_query: Query<&ActiveRecipe>,
+    recipe_graph: Res<RecipeGraph>,
+    item_manifest: Res<ItemManifest>,
+    recipe_manifest: Res<RecipeManifest>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let selected_recipe = current_selection
+        .structures(&map_geometry)
+        .into_iter()
+        .filter_map(|structure_entity| active_recipe_query.get(structure_entity).ok())
+        .find_map(|active_recipe| *active_recipe.recipe_id());
+
+    let target = selected_recipe.and_then(|recipe_id| {
+        let recipe_data = recipe_manifest.get(recipe_id);
+        match recipe_data.hatches {
+            Some(unit_id) => Some(ProductionTarget::Unit(unit_id)),
+            None => recipe_data
+                .outputs
+                .item_ids()
+                .first()
+                .map(|&item_id| ProductionTarget::Item(item_id)),
+        }
+    });
+
+    let new_value = match target {
+        Some(target) => {
+            let plan = recipe_graph.plan(target, 1., &recipe_manifest);
+            format!(
+                "To make 1 {}/min:\n{}\n",
+                target.name(&item_manifest, &unit_manifest),
+                plan.display(&item_manifest, &recipe_manifest, &structure_manifest)
+            )
+        }
+        None => String::new(),
+    };
+
+    if text.sections[0].value != new_value {
+        text.sections[0].value = new_value;
+    }
+}
@@ -0,0 +1,251 @@
+//! Lets the player queue up recipes for the selected crafter.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    crafting::{
+        queue::{EditRecipeQueue, RecipeQueue, RecipeQueueEdit},
+        recipe::{ActiveRecipe, Recipe, RecipeManifest},
+        recipe_graph::RecipeGraph,
+    },
+    geometry::{MapGeometry, VoxelPos},
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    multiplayer::actions::{SharedAction, SharedActions},
+    player_interaction::selection::CurrentSelection,
+    structures::structure_manifest::Structure,
+    world_gen::WorldGenState,
+};
+
+use super::{nicknames::selected_nameable, FiraSansFontFamily, RightPanel};
+
+/// Displays and edits the [`RecipeQueue`] of the selected crafter.
+pub(super) struct RecipeQueuePanelPlugin;
+
+impl Plugin for RecipeQueuePanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_recipe_queue_panel)
+            .add_systems(
+                Update,
+                (update_recipe_queue_panel, press_recipe_queue_buttons)
+                    .chain()
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// Marker component for the root node of the recipe queue panel.
+#[derive(Component)]
+struct RecipeQueuePanel;
+
+/// Marker component for the text describing the current queue.
+#[derive(Component)]
+struct RecipeQueueLabel;
+
+/// Marker component for the node that holds the buttons.
+#[derive(Component)]
+struct RecipeQueueButtonList;
+
+/// A button that edits the queue of a crafter when pressed.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct RecipeQueueButton(EditRecipeQueue);
+
+/// Initializes the recipe queue panel, hidden until a crafter is selected.
+fn spawn_recipe_queue_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let panel_entity = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.),
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                ..default()
+            },
+            RecipeQueuePanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("", text_style),
+                    ..default()
+                },
+                RecipeQueueLabel,
+            ));
+
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        column_gap: Val::Px(2.),
+                        row_gap: Val::Px(2.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                RecipeQueueButtonList,
+            ));
+        })
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands.entity(right_panel_entity).add_child(panel_entity);
+}
+
+/// Shows the queue of the selected crafter, and rebuilds the buttons whenever the selected crafter or its queue changes.
+fn update_recipe_queue_panel(
+    current_selection: Res<CurrentSelection>,
+    map_geometry: Res<MapGeometry>,
+    crafter_query: Query<
+        (&Id<Structure>, Option<&RecipeQueue>),
+        (With<ActiveRecipe>, Without<Ghost>, Without<Preview>),
+    >,
+    mut panel_query: Query<&mut Style, With<RecipeQueuePanel>>,
+    mut label_query: Query<&mut Text, With<RecipeQueueLabel>>,
+    list_query: Query<Entity, With<RecipeQueueButtonList>>,
+    recipe_graph: Res<RecipeGraph>,
+    recipe_manifest: Res<RecipeManifest>,
+    fonts: Res<FiraSansFontFamily>,
+    mut previous_target: Local<Option<(Entity, Vec<Id<Recipe>>)>>,
+    mut commands: Commands,
+) {
+    let target = selected_nameable(&current_selection, &map_geometry)
+        .and_then(|entity| Some((entity, crafter_query.get(entity).ok()?)));
+
+    let mut panel_style = panel_query.single_mut();
+    let Some((target_entity, (&structure_id, maybe_queue))) = target else {
+        panel_style.display = Display::None;
+        *previous_target = None;
+        return;
+    };
+
+    // Sort to ensure a stable ordering
+    let mut recipe_ids: Vec<Id<Recipe>> = recipe_manifest
+        .variants()
+        .into_iter()
+        .filter(|&recipe_id| recipe_graph.crafters(recipe_id).contains(&structure_id))
+        .collect();
+    recipe_ids.sort_by_key(|&recipe_id| recipe_manifest.name(recipe_id).to_string());
+
+    if recipe_ids.is_empty() {
+        panel_style.display = Display::None;
+        *previous_target = None;
+        return;
+    }
+
+    panel_style.display = Display::Flex;
+    label_query.single_mut().sections[0].value = format!(
+        "Recipe queue: {}",
+        maybe_queue.map_or("Empty".to_string(), |queue| queue.display(&recipe_manifest))
+    );
+
+    let queued: Vec<Id<Recipe>> = maybe_queue.map_or(Vec::new(), |queue| queue.recipes().to_vec());
+    let target = Some((target_entity, queued));
+    if *previous_target == target {
+        return;
+    }
+    *previous_target = target;
+
+    let button_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 14.,
+        color: Color::BLACK,
+    };
+
+    let buttons = recipe_ids
+        .into_iter()
+        .map(|recipe_id| {
+            (
+                format!("+ {}", recipe_manifest.name(recipe_id)),
+                RecipeQueueEdit::Push(recipe_id),
+            )
+        })
+        .chain(
+            maybe_queue
+                .map_or(&[][..], |queue| queue.recipes())
+                .iter()
+                .enumerate()
+                .map(|(index, &recipe_id)| {
+                    (
+                        format!("- {}", recipe_manifest.name(recipe_id)),
+                        RecipeQueueEdit::Remove(index),
+                    )
+                }),
+        )
+        .chain(std::iter::once((
+            "Clear".to_string(),
+            RecipeQueueEdit::Clear,
+        )));
+
+    commands
+        .entity(list_query.single())
+        .despawn_descendants()
+        .with_children(|parent| {
+            for (label, edit) in buttons {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(2.)),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                            ..default()
+                        },
+                        RecipeQueueButton(EditRecipeQueue {
+                            structure: target_entity,
+                            edit,
+                        }),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(label, button_style.clone()),
+                            ..default()
+                        });
+                    });
+            }
+        });
+}
+
+/// Edits the recipe queue of the selected crafter when a button is pressed.
+fn press_recipe_queue_buttons(
+    mut button_query: Query<
+        (&Interaction, &RecipeQueueButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    position_query: Query<&VoxelPos>,
+    mut shared_actions: SharedActions,
+) {
+    for (interaction, &RecipeQueueButton(event), mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if let Ok(&structure) = position_query.get(event.structure) {
+            shared_actions.take(SharedAction::EditRecipeQueue {
+                structure,
+                edit: event.edit,
+            });
+        }
+    }
+}
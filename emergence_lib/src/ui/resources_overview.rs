@@ -0,0 +1,102 @@
+//! Shows where all of the colony's items are, and where they came from, using the [`ItemLedger`].
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    items::{item_manifest::ItemManifest, ledger::ItemLedger},
+    player_interaction::PlayerAction,
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Displays the resources overview screen.
+pub(super) struct ResourcesOverviewPlugin;
+
+impl Plugin for ResourcesOverviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_resources_overview)
+            .add_systems(
+                Update,
+                (toggle_resources_overview, update_resources_overview)
+                    .chain()
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// Marker component for the text of the resources overview.
+#[derive(Component)]
+struct ResourcesOverview;
+
+/// Spawns the resources overview in the right panel, hidden until it is toggled on.
+fn spawn_resources_overview(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let resources_overview_entity = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", style),
+                style: Style {
+                    display: Display::None,
+                    ..default()
+                },
+                ..default()
+            },
+            ResourcesOverview,
+        ))
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(resources_overview_entity);
+}
+
+/// Shows or hides the resources overview when [`PlayerAction::ToggleResourcesOverview`] is pressed.
+fn toggle_resources_overview(
+    player_actions: Res<ActionState<PlayerAction>>,
+    mut style_query: Query<&mut Style, With<ResourcesOverview>>,
+) {
+    if !player_actions.just_pressed(PlayerAction::ToggleResourcesOverview) {
+        return;
+    }
+
+    let Ok(mut style) = style_query.get_single_mut() else {
+        return;
+    };
+
+    style.display = match style.display {
+        Display::None => Display::Flex,
+        _ => Display::None,
+    };
+}
+
+/// Keeps the resources overview up to date while it is shown.
+fn update_resources_overview(
+    mut text_query: Query<(&mut Text, &Style), With<ResourcesOverview>>,
+    item_ledger: Res<ItemLedger>,
+    item_manifest: Res<ItemManifest>,
+) {
+    let Ok((mut text, style)) = text_query.get_single_mut() else {
+        return;
+    };
+
+    if style.display == Display::None {
+        return;
+    }
+
+    let new_value = format!("Resources\n{}", item_ledger.display(&item_manifest));
+    if text.sections[0].value != new_value {
+        text.sections[0].value = new_value;
+    }
+}
@@ -0,0 +1,304 @@
+//! Finds structures, stored items and units by name or [`Nickname`], and jumps the camera to them.
+//!
+//! While the search box is open, keystrokes are captured as text rather than treated as keybindings.
+
+use bevy::{prelude::*, utils::HashSet};
+use leafwing_input_manager::prelude::{ActionState, ToggleActions};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::Ghost,
+    crafting::inventories::StorageInventory,
+    geometry::VoxelPos,
+    items::item_manifest::{Item, ItemManifest},
+    player_interaction::{camera::FocusCamera, nicknames::Nickname, PlayerAction},
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Lets players search for things by name.
+pub(super) struct SearchPlugin;
+
+impl Plugin for SearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SearchState>()
+            .add_systems(Startup, spawn_search_panel)
+            .add_systems(
+                Update,
+                (
+                    open_search,
+                    edit_search.after(open_search),
+                    update_search_matches.after(edit_search),
+                    update_search_panel.after(update_search_matches),
+                )
+                    .run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// The maximum number of matches to list.
+const MAX_SEARCH_MATCHES: usize = 10;
+
+/// A single object whose name matched the search query.
+#[derive(Debug, Clone, PartialEq)]
+struct SearchMatch {
+    /// A human-readable description of the match.
+    description: String,
+    /// Where the match can be found.
+    voxel_pos: VoxelPos,
+}
+
+/// The current state of the search box.
+#[derive(Resource, Debug, Default)]
+struct SearchState {
+    /// Is the search box open?
+    open: bool,
+    /// The text that the player has typed.
+    query: String,
+    /// Everything that matches the current query, in display order.
+    matches: Vec<SearchMatch>,
+    /// The index of the highlighted match.
+    highlighted: usize,
+}
+
+impl SearchState {
+    /// Opens the search box with an empty query.
+    fn open(&mut self) {
+        *self = SearchState {
+            open: true,
+            ..default()
+        };
+    }
+
+    /// Closes the search box.
+    fn close(&mut self) {
+        *self = SearchState::default();
+    }
+
+    /// Moves the highlight by `delta` matches, wrapping around at either end.
+    fn move_highlight(&mut self, delta: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+
+        let n = self.matches.len() as isize;
+        self.highlighted = (self.highlighted as isize + delta).rem_euclid(n) as usize;
+    }
+}
+
+/// Marker component for the search panel UI.
+#[derive(Component)]
+struct SearchPanel;
+
+/// Initializes the search panel, hidden until the search is opened.
+fn spawn_search_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let search_panel_entity = commands
+        .spawn(TextBundle {
+            text: Text::from_section("", style),
+            style: Style {
+                display: Display::None,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(SearchPanel)
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(search_panel_entity);
+}
+
+/// Opens the search box, and stops keystrokes from being handled as keybindings until it is closed.
+fn open_search(
+    actions: Res<ActionState<PlayerAction>>,
+    mut search_state: ResMut<SearchState>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+) {
+    if actions.just_pressed(PlayerAction::Search) {
+        search_state.open();
+        toggle_actions.enabled = false;
+    }
+}
+
+/// Handles typing into the open search box.
+///
+/// Enter jumps to the highlighted match, the arrow keys move the highlight and Escape closes the search.
+fn edit_search(
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut search_state: ResMut<SearchState>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+    mut focus_events: EventWriter<FocusCamera>,
+) {
+    if !search_state.open {
+        received_characters.clear();
+        return;
+    }
+
+    for received_character in received_characters.read() {
+        if !received_character.char.is_control() {
+            search_state.query.push(received_character.char);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        search_state.query.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        search_state.move_highlight(1);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        search_state.move_highlight(-1);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        if let Some(search_match) = search_state.matches.get(search_state.highlighted) {
+            focus_events.send(FocusCamera(search_match.voxel_pos));
+        }
+        search_state.close();
+        toggle_actions.enabled = true;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        search_state.close();
+        toggle_actions.enabled = true;
+    }
+}
+
+/// Finds everything whose name matches the current query.
+fn update_search_matches(
+    mut search_state: ResMut<SearchState>,
+    structure_query: Query<(&Id<Structure>, &VoxelPos, Option<&Nickname>), Without<Ghost>>,
+    storage_query: Query<(&StorageInventory, &VoxelPos)>,
+    unit_query: Query<(&Id<Unit>, &VoxelPos, Option<&Nickname>)>,
+    structure_manifest: Res<StructureManifest>,
+    item_manifest: Res<ItemManifest>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    if !search_state.open {
+        return;
+    }
+
+    let structure_ids: HashSet<Id<Structure>> = structure_manifest
+        .search(&search_state.query)
+        .into_iter()
+        .collect();
+    let item_ids: HashSet<Id<Item>> = item_manifest
+        .search(&search_state.query)
+        .into_iter()
+        .collect();
+    let unit_ids: HashSet<Id<Unit>> = unit_manifest
+        .search(&search_state.query)
+        .into_iter()
+        .collect();
+
+    let mut matches = Vec::new();
+
+    for (structure_id, &voxel_pos, maybe_nickname) in structure_query.iter() {
+        if structure_ids.contains(structure_id)
+            || maybe_nickname.is_some_and(|nickname| nickname.matches(&search_state.query))
+        {
+            matches.push(SearchMatch {
+                description: describe(
+                    structure_manifest.name(*structure_id),
+                    maybe_nickname,
+                    voxel_pos,
+                ),
+                voxel_pos,
+            });
+        }
+    }
+
+    for (storage_inventory, &voxel_pos) in storage_query.iter() {
+        for item_slot in storage_inventory.iter() {
+            if item_ids.contains(&item_slot.item_id()) && !item_slot.is_empty() {
+                matches.push(SearchMatch {
+                    description: format!(
+                        "{} {} stored at {voxel_pos}",
+                        item_slot.count(),
+                        item_manifest.name(item_slot.item_id())
+                    ),
+                    voxel_pos,
+                });
+            }
+        }
+    }
+
+    for (unit_id, &voxel_pos, maybe_nickname) in unit_query.iter() {
+        if unit_ids.contains(unit_id)
+            || maybe_nickname.is_some_and(|nickname| nickname.matches(&search_state.query))
+        {
+            matches.push(SearchMatch {
+                description: describe(unit_manifest.name(*unit_id), maybe_nickname, voxel_pos),
+                voxel_pos,
+            });
+        }
+    }
+
+    // Sort so that the highlighted match does not jump around from frame to frame
+    matches.sort_by(|a, b| a.description.cmp(&b.description));
+    matches.truncate(MAX_SEARCH_MATCHES);
+
+    // Avoid triggering change detection needlessly
+    if search_state.matches != matches {
+        search_state.highlighted = search_state
+            .highlighted
+            .min(matches.len().saturating_sub(1));
+        search_state.matches = matches;
+    }
+}
+
+/// Describes a structure or unit of type `name` at `voxel_pos`, leading with its nickname if it has one.
+fn describe(name: &str, maybe_nickname: Option<&Nickname>, voxel_pos: VoxelPos) -> String {
+    match maybe_nickname {
+        Some(nickname) => format!("{nickname} ({name}) at {voxel_pos}"),
+        None => format!("{name} at {voxel_pos}"),
+    }
+}
+
+/// Displays the search box and its matches.
+fn update_search_panel(
+    search_state: Res<SearchState>,
+    mut search_panel_query: Query<(&mut Text, &mut Style), With<SearchPanel>>,
+) {
+    if !search_state.is_changed() {
+        return;
+    }
+
+    let (mut text, mut style) = search_panel_query.single_mut();
+
+    if !search_state.open {
+        style.display = Display::None;
+        return;
+    }
+
+    style.display = Display::Flex;
+
+    let mut string = format!("Search: {}_", search_state.query);
+    for (i, search_match) in search_state.matches.iter().enumerate() {
+        let marker = match i == search_state.highlighted {
+            true => ">",
+            false => " ",
+        };
+        string += &format!("\n{marker} {}", search_match.description);
+    }
+
+    text.sections[0].value = string;
+}
@@ -12,7 +12,7 @@ use crate::{
         selection::CurrentSelection,
         InteractionSystem,
     },
-    signals::Signals,
+    signals::SignalChannels,
     structures::structure_manifest::StructureManifest,
     terrain::terrain_manifest::TerrainManifest,
     units::unit_manifest::UnitManifest,
@@ -310,7 +310,7 @@ fn get_details(
     map_geometry: Res<MapGeometry>,
     structure_manifest: Res<StructureManifest>,
     unit_manifest: Res<UnitManifest>,
-    signals: Res<Signals>,
+    signal_channels: Res<SignalChannels>,
 ) -> Result<(), QueryEntityError> {
     *selection_details = match &*current_selection {
         CurrentSelection::Voxels(selected_voxels) => {
@@ -331,7 +331,12 @@ fn get_details(
                             depth_to_water_table: *terrain_query_item.water_depth,
                             shade: terrain_query_item.shade.clone(),
                             recieved_light: terrain_query_item.recieved_light.clone(),
-                            signals: signals.all_signals_at_position(*terrain_query_item.voxel_pos),
+                            soil_fertility: *terrain_query_item.soil_fertility,
+                            temperature: *terrain_query_item.temperature,
+                            tile_history: terrain_query_item.tile_history.clone(),
+                            signals: signal_channels
+                                .player()
+                                .all_signals_at_position(*terrain_query_item.voxel_pos),
                             maybe_terraforming_details: terrain_query_item
                                 .maybe_terraforming_details
                                 .map(|q| terrain_details::TerraformingDetails {
@@ -344,43 +349,12 @@ fn get_details(
                                 .collect(),
                         })
                     }
-                    VoxelKind::Structure { .. } => {
-                        let structure_query_item = structure_query.get(voxel_object.entity)?;
-
-                        // Not all structures are organisms
-                        let maybe_organism_details =
-                organism_query
-                    .get(voxel_object.entity)
-                    .ok()
-                    .map(|query_item| OrganismDetails {
-                        prototypical_form: structure_manifest
-                            .get(*structure_query_item.structure_id)
-                            .organism_variety.as_ref()
-                            .expect("All structures with organism components must be registered in the manifest as organisms")
-                            .prototypical_form,
-                        lifecycle: query_item.lifecycle.clone(),
-                        energy_pool: query_item.energy_pool.clone(),
-                        oxygen_pool: query_item.oxygen_pool.clone(),
-                    });
-
-                        SelectionDetails::Structure(StructureDetails {
-                            entity: structure_query_item.entity,
-                            voxel_pos: *structure_query_item.voxel_pos,
-                            structure_id: *structure_query_item.structure_id,
-                            maybe_organism_details,
-                            marked_for_removal: structure_query_item.marked_for_removal.is_some(),
-                            emitter: structure_query_item.emitter.cloned(),
-                            storage_inventory: structure_query_item.storage_inventory.cloned(),
-                            input_inventory: structure_query_item.input_inventory.cloned(),
-                            output_inventory: structure_query_item.output_inventory.cloned(),
-                            crafting_state: structure_query_item.crafting_state.cloned(),
-                            active_recipe: structure_query_item.active_recipe.cloned(),
-                            workers_present: structure_query_item.workers_present.cloned(),
-                            vegetative_reproduction: structure_query_item
-                                .vegetative_reproduction
-                                .cloned(),
-                        })
-                    }
+                    VoxelKind::Structure { .. } => SelectionDetails::Structure(structure_details(
+                        voxel_object.entity,
+                        &structure_query,
+                        &organism_query,
+                        &structure_manifest,
+                    )?),
                     VoxelKind::GhostStructure => {
                         let ghost_query_item = ghost_structure_query.get(voxel_object.entity)?;
                         SelectionDetails::GhostStructure(GhostStructureDetails {
@@ -397,6 +371,14 @@ fn get_details(
                 SelectionDetails::None
             }
         }
+        CurrentSelection::Structure(structure_entity) => {
+            SelectionDetails::Structure(structure_details(
+                *structure_entity,
+                &structure_query,
+                &organism_query,
+                &structure_manifest,
+            )?)
+        }
         CurrentSelection::Unit(unit_entity) => {
             let unit_query_item = unit_query.get(*unit_entity)?;
             // All units are organisms
@@ -409,6 +391,7 @@ fn get_details(
                 lifecycle: organism_query_item.lifecycle.clone(),
                 energy_pool: organism_query_item.energy_pool.clone(),
                 oxygen_pool: organism_query_item.oxygen_pool.clone(),
+                maybe_dormant: organism_query_item.maybe_dormant.cloned(),
             };
 
             let unit_data = unit_manifest.get(*unit_query_item.unit_id);
@@ -423,6 +406,11 @@ fn get_details(
                 action: unit_query_item.action.clone(),
                 impatience_pool: unit_query_item.impatience_pool.clone(),
                 age: unit_query_item.age.clone(),
+                fatigue: unit_query_item.fatigue.clone(),
+                stats: unit_query_item.stats.clone(),
+                faction: *unit_query_item.faction,
+                nickname: unit_query_item.nickname.cloned(),
+                favorite: unit_query_item.favorite,
                 organism_details,
                 walkable_neighbors: map_geometry
                     .walkable_neighbors(*unit_query_item.voxel_pos)
@@ -435,6 +423,60 @@ fn get_details(
     Ok(())
 }
 
+/// Collects the [`StructureDetails`] of the structure `entity`.
+fn structure_details(
+    entity: Entity,
+    structure_query: &Query<StructureDetailsQuery>,
+    organism_query: &Query<OrganismDetailsQuery>,
+    structure_manifest: &StructureManifest,
+) -> Result<StructureDetails, QueryEntityError> {
+    let structure_query_item = structure_query.get(entity)?;
+
+    // Not all structures are organisms
+    let maybe_organism_details = organism_query.get(entity).ok().map(|query_item| OrganismDetails {
+        prototypical_form: structure_manifest
+            .get(*structure_query_item.structure_id)
+            .organism_variety
+            .as_ref()
+            .expect("All structures with organism components must be registered in the manifest as organisms")
+            .prototypical_form,
+        lifecycle: query_item.lifecycle.clone(),
+        energy_pool: query_item.energy_pool.clone(),
+        oxygen_pool: query_item.oxygen_pool.clone(),
+        maybe_dormant: query_item.maybe_dormant.cloned(),
+    });
+
+    Ok(StructureDetails {
+        entity: structure_query_item.entity,
+        voxel_pos: *structure_query_item.voxel_pos,
+        structure_id: *structure_query_item.structure_id,
+        maybe_organism_details,
+        marked_for_removal: structure_query_item.marked_for_removal.is_some(),
+        emitter: structure_query_item.emitter.cloned(),
+        storage_inventory: structure_query_item.storage_inventory.cloned(),
+        input_inventory: structure_query_item.input_inventory.cloned(),
+        output_inventory: structure_query_item.output_inventory.cloned(),
+        tanks: structure_query_item.tanks.cloned(),
+        crafting_state: structure_query_item.crafting_state.cloned(),
+        crafting_status: structure_query_item.crafting_status.cloned(),
+        crafting_history: structure_query_item.crafting_history.cloned(),
+        recipe_queue: structure_query_item.recipe_queue.cloned(),
+        output_routing: structure_query_item.output_routing.copied(),
+        active_recipe: structure_query_item.active_recipe.cloned(),
+        workers_present: structure_query_item.workers_present.cloned(),
+        shelter_occupants: structure_query_item.shelter_occupants.cloned(),
+        owner: structure_query_item.owner.copied(),
+        vegetative_reproduction: structure_query_item.vegetative_reproduction.cloned(),
+        resource_node: structure_query_item.resource_node.cloned(),
+        marked_for_harvest: structure_query_item.marked_for_harvest.is_some(),
+        forbidden: structure_query_item.forbidden.is_some(),
+        prioritized: structure_query_item.prioritized.is_some(),
+        disabled: structure_query_item.disabled.is_some(),
+        nickname: structure_query_item.nickname.cloned(),
+        favorite: structure_query_item.favorite,
+    })
+}
+
 /// If something went wrong in [`get_details`], clear the selection.
 pub(crate) fn clear_details_on_error(
     In(result): In<Result<(), QueryEntityError>>,
@@ -531,7 +573,10 @@ mod organism_details {
     use bevy::ecs::query::WorldQuery;
 
     use crate::{
-        organisms::{energy::EnergyPool, lifecycle::Lifecycle, oxygen::OxygenPool, OrganismId},
+        organisms::{
+            dormancy::Dormant, energy::EnergyPool, lifecycle::Lifecycle, oxygen::OxygenPool,
+            OrganismId,
+        },
         structures::structure_manifest::StructureManifest,
         units::unit_manifest::UnitManifest,
     };
@@ -545,6 +590,8 @@ mod organism_details {
         pub(super) energy_pool: &'static EnergyPool,
         /// The currrent and max oxygen
         pub(super) oxygen_pool: &'static OxygenPool,
+        /// Is this organism dormant?
+        pub(super) maybe_dormant: Option<&'static Dormant>,
     }
 
     /// Detailed info about a given organism.
@@ -558,6 +605,8 @@ mod organism_details {
         pub(super) energy_pool: EnergyPool,
         /// The currrent and max oxygen
         pub(super) oxygen_pool: OxygenPool,
+        /// Is this organism dormant?
+        pub(super) maybe_dormant: Option<Dormant>,
     }
 
     impl OrganismDetails {
@@ -575,12 +624,18 @@ mod organism_details {
             let energy_pool = &self.energy_pool;
             let oxygen_pool = &self.oxygen_pool;
 
-            format!(
+            let mut string = format!(
                 "Prototypical form: {prototypical_form}
 Lifecycle: {lifecycle}
 Energy: {energy_pool}
 Oxygen: {oxygen_pool}"
-            )
+            );
+
+            if let Some(dormant) = &self.maybe_dormant {
+                string += &format!("\n{dormant}");
+            }
+
+            string
         }
     }
 }
@@ -595,16 +650,28 @@ mod structure_details {
         construction::demolition::MarkedForDemolition,
         crafting::{
             inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
+            queue::RecipeQueue,
             recipe::{ActiveRecipe, RecipeManifest},
+            status::CraftingStatus,
+            throughput::CraftingHistory,
             workers::WorkersPresent,
         },
+        factions::Faction,
         geometry::VoxelPos,
-        items::item_manifest::ItemManifest,
+        items::{bulk::Tanks, item_manifest::ItemManifest},
+        logistics::OutputRouting,
         organisms::vegetative_reproduction::VegetativeReproduction,
+        player_interaction::{
+            bulk_commands::{Disabled, Favorite, Forbidden, Prioritized},
+            nicknames::Nickname,
+        },
         signals::Emitter,
-        structures::structure_manifest::{Structure, StructureManifest},
+        structures::{
+            resource_nodes::{MarkedForHarvest, ResourceNode},
+            structure_manifest::{Structure, StructureManifest},
+        },
         terrain::terrain_manifest::TerrainManifest,
-        units::unit_manifest::UnitManifest,
+        units::{rest::ShelterOccupants, unit_manifest::UnitManifest},
         water::emitters::WaterEmitter,
     };
 
@@ -621,14 +688,28 @@ mod structure_details {
         pub(crate) input_inventory: Option<&'static InputInventory>,
         /// The inventory for the output items.
         pub(crate) output_inventory: Option<&'static OutputInventory>,
+        /// The tanks for bulk resources, if any.
+        pub(crate) tanks: Option<&'static Tanks>,
         /// If this structure stores things, its inventory.
         pub(crate) storage_inventory: Option<&'static StorageInventory>,
         /// The recipe used, if any.
         pub(crate) active_recipe: Option<&'static ActiveRecipe>,
         /// The state of the ongoing crafting process.
         pub(crate) crafting_state: Option<&'static CraftingState>,
+        /// Why crafting is or isn't progressing.
+        pub(crate) crafting_status: Option<&'static CraftingStatus>,
+        /// The most recently completed crafts.
+        pub(crate) crafting_history: Option<&'static CraftingHistory>,
+        /// The recipes queued up to be crafted in order, if any.
+        pub(crate) recipe_queue: Option<&'static RecipeQueue>,
+        /// Where the outputs of this structure are taken, if anywhere in particular.
+        pub(crate) output_routing: Option<&'static OutputRouting>,
         /// The workers present at this structure.
         pub(crate) workers_present: Option<&'static WorkersPresent>,
+        /// The units resting in this structure.
+        pub(crate) shelter_occupants: Option<&'static ShelterOccupants>,
+        /// The faction that owns this structure, if any.
+        pub(crate) owner: Option<&'static Faction>,
         /// Is this structure marked for removal?
         pub(super) marked_for_removal: Option<&'static MarkedForDemolition>,
         /// What signals is this structure emitting?
@@ -637,6 +718,20 @@ mod structure_details {
         pub(super) maybe_water_emitter: Option<&'static WaterEmitter>,
         /// The vegetative reproduction strategy, if any.
         pub(crate) vegetative_reproduction: Option<&'static VegetativeReproduction>,
+        /// The wild resources that can be harvested here, if any.
+        pub(crate) resource_node: Option<&'static ResourceNode>,
+        /// Is this structure marked for harvest?
+        pub(super) marked_for_harvest: Option<&'static MarkedForHarvest>,
+        /// Is hauling to and from this structure forbidden?
+        pub(super) forbidden: Option<&'static Forbidden>,
+        /// Has this structure been prioritized?
+        pub(super) prioritized: Option<&'static Prioritized>,
+        /// Has this structure been switched off?
+        pub(super) disabled: Option<&'static Disabled>,
+        /// The name given to this structure by the player, if any.
+        pub(super) nickname: Option<&'static Nickname>,
+        /// Is this structure one of the player's favorites?
+        pub(super) favorite: Has<Favorite>,
     }
 
     /// Detailed info about a given structure.
@@ -658,16 +753,44 @@ mod structure_details {
         pub(crate) input_inventory: Option<InputInventory>,
         /// The inventory for the output items.
         pub(crate) output_inventory: Option<OutputInventory>,
+        /// The tanks for bulk resources, if any.
+        pub(crate) tanks: Option<Tanks>,
         /// If this structure stores things, its inventory.
         pub(crate) storage_inventory: Option<StorageInventory>,
         /// The recipe used, if any.
         pub(crate) active_recipe: Option<ActiveRecipe>,
         /// The state of the ongoing crafting process.
         pub(crate) crafting_state: Option<CraftingState>,
+        /// Why crafting is or isn't progressing.
+        pub(crate) crafting_status: Option<CraftingStatus>,
+        /// The most recently completed crafts.
+        pub(crate) crafting_history: Option<CraftingHistory>,
+        /// The recipes queued up to be crafted in order, if any.
+        pub(crate) recipe_queue: Option<RecipeQueue>,
+        /// Where the outputs of this structure are taken, if anywhere in particular.
+        pub(crate) output_routing: Option<OutputRouting>,
         /// The number of workers that are presently working on this.
         pub(crate) workers_present: Option<WorkersPresent>,
+        /// The number of units that are presently resting in this.
+        pub(crate) shelter_occupants: Option<ShelterOccupants>,
+        /// The faction that owns this structure, if any.
+        pub(crate) owner: Option<Faction>,
         /// The vegetative reproduction strategy, if any.
         pub(crate) vegetative_reproduction: Option<VegetativeReproduction>,
+        /// The wild resources that can be harvested here, if any.
+        pub(crate) resource_node: Option<ResourceNode>,
+        /// Is this structure marked for harvest?
+        pub(crate) marked_for_harvest: bool,
+        /// Is hauling to and from this structure forbidden?
+        pub(crate) forbidden: bool,
+        /// Has this structure been prioritized?
+        pub(crate) prioritized: bool,
+        /// Has this structure been switched off?
+        pub(crate) disabled: bool,
+        /// The name given to this structure by the player, if any.
+        pub(crate) nickname: Option<Nickname>,
+        /// Is this structure one of the player's favorites?
+        pub(crate) favorite: bool,
     }
 
     impl StructureDetails {
@@ -702,10 +825,34 @@ Tile: {voxel_pos}
 Height: {height}"
             );
 
+            if let Some(nickname) = &self.nickname {
+                string += &format!("\nNickname: {nickname}");
+            }
+
+            if self.favorite {
+                string += "\nFavorite";
+            }
+
+            if let Some(owner) = &self.owner {
+                string += &format!("\nOwner: {owner}");
+            }
+
             if self.marked_for_removal {
                 string += "\nMarked for removal!";
             }
 
+            if self.forbidden {
+                string += "\nHauling forbidden";
+            }
+
+            if self.prioritized {
+                string += "\nPrioritized";
+            }
+
+            if self.disabled {
+                string += "\nDisabled";
+            }
+
             if let Some(storage) = &self.storage_inventory {
                 string += &format!("\nStoring: {}", storage.display(item_manifest));
             }
@@ -718,24 +865,50 @@ Height: {height}"
                 string += &format!("\nOutput: {}", output.display(item_manifest));
             }
 
+            if let Some(tanks) = &self.tanks {
+                string += &format!("\nTanks: {}", tanks.display(item_manifest));
+            }
+
             if let Some(recipe) = &self.active_recipe {
                 string += &format!("\nRecipe: {}", recipe.display(recipe_manifest));
                 if let Some(recipe_id) = recipe.recipe_id() {
                     string += &format!(
                         "\nRecipe data: {}",
-                        recipe_manifest.get(*recipe_id).display(item_manifest)
+                        recipe_manifest
+                            .get(*recipe_id)
+                            .display(item_manifest, unit_manifest)
                     );
                 }
             }
 
+            if let Some(recipe_queue) = &self.recipe_queue {
+                string += &format!("\nRecipe queue: {}", recipe_queue.display(recipe_manifest));
+            }
+
+            if let Some(output_routing) = &self.output_routing {
+                string += &format!("\nOutputs routed to: {output_routing}");
+            }
+
             if let Some(crafting_state) = &self.crafting_state {
                 string += &format!("\nCrafting state: {crafting_state}");
             }
 
+            if let Some(crafting_status) = &self.crafting_status {
+                string += &format!("\nStatus: {}", crafting_status.display(item_manifest));
+            }
+
+            if let Some(crafting_history) = &self.crafting_history {
+                string += &format!("\n{}", crafting_history.display(item_manifest));
+            }
+
             if let Some(workers_present) = &self.workers_present {
                 string += &format!("\nWorkers present: {workers_present}");
             }
 
+            if let Some(shelter_occupants) = &self.shelter_occupants {
+                string += &format!("\nResting: {shelter_occupants}");
+            }
+
             if let Some(root_zone) = &structure_manifest.get(self.structure_id).root_zone {
                 string += &format!("\n{root_zone}",);
             }
@@ -748,6 +921,13 @@ Height: {height}"
                 string += &format!("\nVegetative reproduction: {vegetative_reproduction}",);
             }
 
+            if let Some(resource_node) = &self.resource_node {
+                string += &format!("\nResources: {}", resource_node.display(item_manifest));
+                if self.marked_for_harvest {
+                    string += "\nMarked for harvest";
+                }
+            }
+
             string
         }
     }
@@ -766,7 +946,12 @@ mod terrain_details {
         light::shade::{ReceivedLight, Shade},
         signals::LocalSignals,
         structures::structure_manifest::StructureManifest,
-        terrain::terrain_manifest::{Terrain, TerrainManifest},
+        temperature::Temperature,
+        terrain::{
+            fertility::SoilFertility,
+            history::TileHistory,
+            terrain_manifest::{Terrain, TerrainManifest},
+        },
         units::unit_manifest::UnitManifest,
         water::WaterDepth,
     };
@@ -782,6 +967,12 @@ mod terrain_details {
         pub(super) shade: &'static Shade,
         /// The recieved light of the tile
         pub(super) recieved_light: &'static ReceivedLight,
+        /// The fertility of the soil
+        pub(super) soil_fertility: &'static SoilFertility,
+        /// The current temperature of the tile
+        pub(super) temperature: &'static Temperature,
+        /// What has happened on this tile recently
+        pub(super) tile_history: &'static TileHistory,
         /// The type of terrain
         pub(super) terrain_id: &'static Id<Terrain>,
         /// The depth of water on this tile
@@ -842,6 +1033,12 @@ Output: {output}"
         pub(super) shade: Shade,
         /// The recieved light of the tile
         pub(super) recieved_light: ReceivedLight,
+        /// The fertility of the soil
+        pub(super) soil_fertility: SoilFertility,
+        /// The current temperature of the tile
+        pub(super) temperature: Temperature,
+        /// What has happened on this tile recently
+        pub(super) tile_history: TileHistory,
         /// The signals on this tile
         pub(super) signals: LocalSignals,
         /// The details about the terraforming process, if any
@@ -866,6 +1063,8 @@ Output: {output}"
             let depth_to_water_table = &self.depth_to_water_table;
             let shade = &self.shade;
             let recieved_light = &self.recieved_light;
+            let soil_fertility = &self.soil_fertility;
+            let temperature = &self.temperature;
             let signals = self.signals.display(
                 item_manifest,
                 structure_manifest,
@@ -887,9 +1086,20 @@ Height: {height}
 Water Table: {depth_to_water_table}
 Shade: {shade}
 Current Light: {recieved_light}
+Soil Fertility: {soil_fertility}
+Temperature: {temperature}
 Walkable Neighbors: {walkable_neighbors}"
             );
 
+            let base_string = if self.tile_history.is_empty() {
+                base_string
+            } else {
+                let tile_history =
+                    self.tile_history
+                        .display(structure_manifest, terrain_manifest, unit_manifest);
+                format!("{base_string}\nHistory:{tile_history}")
+            };
+
             if let Some(terraforming_details) = &self.maybe_terraforming_details {
                 let terraforming_details =
                     terraforming_details.display(item_manifest, terrain_manifest);
@@ -907,8 +1117,10 @@ mod unit_details {
 
     use crate::{
         asset_management::manifest::Id,
+        factions::Faction,
         geometry::VoxelPos,
         items::item_manifest::ItemManifest,
+        player_interaction::{bulk_commands::Favorite, nicknames::Nickname},
         structures::structure_manifest::StructureManifest,
         terrain::terrain_manifest::TerrainManifest,
         units::{
@@ -918,6 +1130,8 @@ mod unit_details {
             goals::Goal,
             impatience::ImpatiencePool,
             item_interaction::UnitInventory,
+            rest::Fatigue,
+            stats::UnitStats,
             unit_manifest::{Unit, UnitManifest},
         },
     };
@@ -943,6 +1157,16 @@ mod unit_details {
         pub(super) impatience_pool: &'static ImpatiencePool,
         /// The current and max age of this unit.
         pub(super) age: &'static Age,
+        /// How tired this unit is.
+        pub(super) fatigue: &'static Fatigue,
+        /// The unit's current physical abilities.
+        pub(super) stats: &'static UnitStats,
+        /// The colony that this unit belongs to.
+        pub(super) faction: &'static Faction,
+        /// The name given to this unit by the player, if any.
+        pub(super) nickname: Option<&'static Nickname>,
+        /// Is this unit one of the player's favorites?
+        pub(super) favorite: Has<Favorite>,
     }
 
     /// Detailed info about a given unit.
@@ -968,6 +1192,16 @@ mod unit_details {
         pub(super) impatience_pool: ImpatiencePool,
         /// The current and max age of this unit.
         pub(super) age: Age,
+        /// How tired this unit is.
+        pub(super) fatigue: Fatigue,
+        /// The unit's current physical abilities.
+        pub(super) stats: UnitStats,
+        /// The colony that this unit belongs to.
+        pub(super) faction: Faction,
+        /// The name given to this unit by the player, if any.
+        pub(super) nickname: Option<Nickname>,
+        /// Is this unit one of the player's favorites?
+        pub(super) favorite: bool,
         /// The set of voxels that this unit can walk to
         pub(super) walkable_neighbors: Vec<VoxelPos>,
     }
@@ -998,6 +1232,9 @@ mod unit_details {
                 .organism_details
                 .display(structure_manifest, unit_manifest);
             let age = &self.age;
+            let fatigue = &self.fatigue;
+            let stats = &self.stats;
+            let faction = &self.faction;
             let walkable_neighbors = self
                 .walkable_neighbors
                 .iter()
@@ -1005,9 +1242,20 @@ mod unit_details {
                 .collect::<Vec<_>>()
                 .join("\n ");
 
-            format!(
-                "Entity: {entity:?}
+            let mut string = format!("Entity: {entity:?}");
+
+            if let Some(nickname) = &self.nickname {
+                string += &format!("\nNickname: {nickname}");
+            }
+
+            if self.favorite {
+                string += "\nFavorite";
+            }
+
+            string += &format!(
+                "
 Unit type: {unit_name}
+Faction: {faction}
 Tile: {voxel_pos}
 Walkable Neighbors: {walkable_neighbors}
 Diet: {diet}
@@ -1016,8 +1264,12 @@ Goal: {goal}
 Action: {action}
 Impatience: {impatience_pool}
 Age: {age}
+Fatigue: {fatigue}
+Stats: {stats}
 {organism_details}"
-            )
+            );
+
+            string
         }
     }
 }
@@ -10,7 +10,7 @@ use leafwing_input_manager::prelude::ActionState;
 use crate::{
     asset_management::{manifest::Id, AssetState},
     construction::terraform::TerraformingAction,
-    crafting::inventories::CraftingState,
+    crafting::{inventories::CraftingState, status::CraftingStatus},
     player_interaction::PlayerAction,
     units::{
         goals::{Goal, GoalKind},
@@ -126,6 +126,19 @@ impl From<&CraftingState> for CraftingProgress {
     }
 }
 
+impl CraftingProgress {
+    /// Picks the icon for a crafting structure, flagging it as stalled if its [`CraftingStatus`] says that it is stuck.
+    fn new(state: &CraftingState, maybe_status: Option<&CraftingStatus>) -> Self {
+        match maybe_status {
+            Some(
+                CraftingStatus::MissingInputs { .. } | CraftingStatus::MissingBulkInputs { .. },
+            ) => CraftingProgress::NeedsInput,
+            Some(CraftingStatus::OutputFull) => CraftingProgress::FullAndBlocked,
+            _ => CraftingProgress::from(state),
+        }
+    }
+}
+
 /// Cycles between status display options.
 fn cycle_status_visualization(
     mut status_visualization: ResMut<StatusVisualization>,
@@ -200,7 +213,7 @@ fn add_status_displays(
 fn display_status(
     status_visualization: Res<StatusVisualization>,
     unit_query: Query<(&Goal, &StatusParent)>,
-    crafting_query: Query<(&CraftingState, &StatusParent)>,
+    crafting_query: Query<(&CraftingState, Option<&CraftingStatus>, &StatusParent)>,
     mut status_icon_query: Query<
         (&mut Handle<BillboardTexture>, &mut Visibility),
         With<StatusDisplay>,
@@ -210,11 +223,11 @@ fn display_status(
     goal_icons: Res<Icons<GoalKind>>,
 ) {
     if status_visualization.structures_enabled() {
-        for (crafting_state, status) in crafting_query.iter() {
+        for (crafting_state, maybe_crafting_status, status) in crafting_query.iter() {
             let (mut status_icon, mut visibility) =
                 status_icon_query.get_mut(status.entity).unwrap();
 
-            let crafting_progress = CraftingProgress::from(crafting_state);
+            let crafting_progress = CraftingProgress::new(crafting_state, maybe_crafting_status);
             let image_handle = crafting_progress_icons.get(crafting_progress);
 
             // PERF: this is dumb to reinsert every frame
@@ -0,0 +1,174 @@
+//! Lists the offers of the visiting caravan, and lets the player accept them.
+
+use bevy::prelude::*;
+
+use crate::{
+    geometry::VoxelPos,
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    items::{item_manifest::ItemManifest, totals::ItemTotals},
+    multiplayer::actions::{SharedAction, SharedActions},
+    trading::{Caravans, TradeOfferListing, TradingPost},
+};
+
+use super::FiraSansFontFamily;
+
+/// Displays the [`TradeOfferListing`](crate::trading::TradeOfferListing)s of the visiting caravan.
+pub(super) struct TradingPanelPlugin;
+
+impl Plugin for TradingPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_trading_panel, accept_offers)
+                .chain()
+                .run_if(resource_exists::<Caravans>()),
+        );
+    }
+}
+
+/// The root node of the trading panel.
+#[derive(Component)]
+struct TradingPanel;
+
+/// A button that accepts the offer with this [`TradeOffer::id`](crate::trading::TradeOffer::id).
+#[derive(Component)]
+struct OfferButton(u64);
+
+/// Rebuilds the trading panel whenever the caravan's offers, or whether the colony can afford them, change.
+///
+/// Offers that the colony can't currently afford are listed, but can't be accepted.
+fn update_trading_panel(
+    caravans: Res<Caravans>,
+    item_totals: Res<ItemTotals>,
+    item_manifest: Res<ItemManifest>,
+    panel_query: Query<Entity, With<TradingPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+    // The departure day and listings that are currently shown, if any
+    mut displayed: Local<Option<(u64, Vec<TradeOfferListing>)>>,
+    mut commands: Commands,
+) {
+    if !caravans.is_changed() && !item_totals.is_changed() {
+        return;
+    }
+
+    let to_display = caravans.visiting().map(|caravan| {
+        (
+            caravan.departure_day(),
+            caravan.listings(&item_totals, &item_manifest),
+        )
+    });
+    if *displayed == to_display {
+        return;
+    }
+    *displayed = to_display;
+
+    for entity in panel_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some((departure_day, listings)) = &*displayed else {
+        return;
+    };
+
+    let text_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::BLACK,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(16.),
+                    right: Val::Px(16.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                ..default()
+            },
+            TradingPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Caravan (leaves on day {departure_day})"),
+                TextStyle {
+                    font_size: 20.,
+                    ..text_style.clone()
+                },
+            ));
+
+            for listing in listings {
+                let description = TextBundle::from_section(
+                    listing.description.clone(),
+                    TextStyle {
+                        color: match listing.affordable {
+                            true => Color::BLACK,
+                            false => Color::GRAY,
+                        },
+                        ..text_style.clone()
+                    },
+                );
+
+                if !listing.affordable {
+                    parent.spawn(description.with_style(Style {
+                        padding: UiRect::all(Val::Px(4.)),
+                        ..default()
+                    }));
+                    continue;
+                }
+
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(4.)),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(MENU_NEUTRAL_COLOR),
+                            ..default()
+                        },
+                        OfferButton(listing.offer_id),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(description);
+                    });
+            }
+        });
+}
+
+/// Accepts offers when their buttons are pressed, using the first idle trading post.
+fn accept_offers(
+    mut button_query: Query<
+        (&Interaction, &OfferButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    trading_post_query: Query<(&VoxelPos, &TradingPost)>,
+    mut shared_actions: SharedActions,
+) {
+    for (interaction, offer_button, mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => BackgroundColor(MENU_HIGHLIGHT_COLOR),
+            Interaction::None => BackgroundColor(MENU_NEUTRAL_COLOR),
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match trading_post_query
+            .iter()
+            .find(|(_, trading_post)| trading_post.order().is_none())
+        {
+            Some((&trading_post, _)) => shared_actions.take(SharedAction::AcceptTradeOffer {
+                offer_id: offer_button.0,
+                trading_post,
+            }),
+            None => warn!("Every trading post is busy with another trade."),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Loads and manages asset state for in-game UI
 
-use bevy::{asset::LoadState, prelude::*, utils::HashMap};
+use bevy::{asset::UntypedHandle, prelude::*, utils::HashMap};
 use core::fmt::Debug;
 use core::hash::Hash;
 
@@ -38,8 +38,8 @@ impl Loadable for UiElements {
         });
     }
 
-    fn load_state(&self, asset_server: &AssetServer) -> Option<bevy::asset::LoadState> {
-        asset_server.get_load_state(&self.hex_menu_background)
+    fn handles(&self) -> Vec<UntypedHandle> {
+        vec![self.hex_menu_background.clone_weak().untyped()]
     }
 }
 
@@ -207,6 +207,7 @@ impl FromWorld for Icons<GoalKind> {
             GoalKind::Breathe,
             asset_server.load("icons/goals/breathe.png"),
         );
+        map.insert(GoalKind::Rest, asset_server.load("icons/goals/rest.png"));
 
         Icons { map }
     }
@@ -223,21 +224,10 @@ where
         world.insert_resource(icons);
     }
 
-    fn load_state(&self, asset_server: &AssetServer) -> Option<LoadState> {
-        for (data, icon_handle) in &self.map {
-            let load_state = asset_server.get_load_state(icon_handle);
-
-            if load_state != Some(LoadState::Loaded) {
-                let maybe_path = asset_server.get_path(icon_handle);
-                let path = maybe_path
-                    .map(|p| format!("{:?}", p.path()))
-                    .unwrap_or("unknown_path".to_string());
-
-                info!("Icon {data:?}'s scene at {path} is {load_state:?}");
-                return load_state;
-            }
-        }
-
-        Some(LoadState::Loaded)
+    fn handles(&self) -> Vec<UntypedHandle> {
+        self.map
+            .values()
+            .map(|icon_handle| icon_handle.clone_weak().untyped())
+            .collect()
     }
 }
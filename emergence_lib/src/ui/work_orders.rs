@@ -0,0 +1,97 @@
+//! Lists the outstanding work orders, so players can see what the colony has been asked to do.
+
+use bevy::prelude::*;
+
+use crate::{
+    construction::work_orders::{WorkOrder, WorkOrderState},
+    structures::structure_manifest::StructureManifest,
+    terrain::terrain_manifest::TerrainManifest,
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Displays the list of work orders.
+pub(super) struct WorkOrderListPlugin;
+
+impl Plugin for WorkOrderListPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_work_order_list).add_systems(
+            Update,
+            update_work_order_list.run_if(in_state(WorldGenState::Complete)),
+        );
+    }
+}
+
+/// Marker component for the work order list UI.
+#[derive(Component)]
+struct WorkOrderList;
+
+/// The maximum number of individual work orders to list.
+const MAX_LISTED_WORK_ORDERS: usize = 10;
+
+/// Initializes the work order list.
+fn spawn_work_order_list(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let work_order_list_entity = commands
+        .spawn(TextBundle {
+            text: Text::from_section("", style),
+            ..Default::default()
+        })
+        .insert(WorkOrderList)
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(work_order_list_entity);
+}
+
+/// Summarizes the work orders by state, and lists the first few in detail.
+///
+/// Blocked work orders are listed first, as they are the ones that need the player's attention.
+fn update_work_order_list(
+    mut text_query: Query<&mut Text, With<WorkOrderList>>,
+    work_order_query: Query<&WorkOrder>,
+    structure_manifest: Res<StructureManifest>,
+    terrain_manifest: Res<TerrainManifest>,
+) {
+    let mut text = text_query.single_mut();
+
+    let count = |state: WorkOrderState| {
+        work_order_query
+            .iter()
+            .filter(|work_order| work_order.state() == state)
+            .count()
+    };
+
+    let mut string = format!(
+        "Work orders: {} ({} pending, {} assigned, {} in progress, {} blocked)",
+        work_order_query.iter().len(),
+        count(WorkOrderState::Pending),
+        count(WorkOrderState::Assigned),
+        count(WorkOrderState::InProgress),
+        count(WorkOrderState::Blocked),
+    );
+
+    let mut work_orders: Vec<&WorkOrder> = work_order_query.iter().collect();
+    work_orders.sort_by_key(|work_order| work_order.state() != WorkOrderState::Blocked);
+
+    for work_order in work_orders.into_iter().take(MAX_LISTED_WORK_ORDERS) {
+        string += &format!(
+            "\n{}",
+            work_order.display(&structure_manifest, &terrain_manifest)
+        );
+    }
+
+    text.sections[0].value = string;
+}
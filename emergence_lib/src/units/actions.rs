@@ -3,10 +3,10 @@
 use bevy::{
     ecs::{query::WorldQuery, system::SystemParam},
     prelude::*,
-    utils::Duration,
+    utils::{Duration, Instant},
 };
 use leafwing_abilities::prelude::Pool;
-use rand::{rngs::ThreadRng, seq::SliceRandom, thread_rng};
+use rand::{rngs::SmallRng, seq::SliceRandom};
 
 use crate::{
     asset_management::manifest::Id,
@@ -16,17 +16,27 @@ use crate::{
         terraform::TerraformingAction,
     },
     crafting::{
-        inventories::{
-            AddToInputError, CraftingState, InputInventory, OutputInventory, StorageInventory,
-        },
+        inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
         item_tags::ItemKind,
         workers::WorkersPresent,
     },
-    geometry::{Facing, Height, MapGeometry, RotationDirection, VoxelPos},
-    items::{errors::AddOneItemError, item_manifest::ItemManifest, ItemCount},
+    factions::{diplomacy::Relationships, territory::Territory, Faction},
+    geometry::{weighted_random_direction, Facing, MapGeometry, RotationDirection, VoxelPos},
+    items::{
+        inventory::Inventory,
+        item_manifest::ItemManifest,
+        ledger::{ItemLedger, ItemSink},
+        ItemCount,
+    },
     litter::{Litter, LitterCommandsExt},
-    organisms::{energy::EnergyPool, lifecycle::Lifecycle},
-    signals::{SignalType, Signals},
+    logistics::{HaulingJob, OutputRouting},
+    organisms::{
+        energy::{ColonyEnergy, Energy, EnergyPool},
+        lifecycle::Lifecycle,
+    },
+    player_interaction::bulk_commands::Forbidden,
+    signals::{SignalChannels, SignalType, Signals},
+    simulation::{rng::SystemRng, weather::Wind},
     structures::{commands::StructureCommandsExt, structure_manifest::Structure},
     terrain::terrain_manifest::{Terrain, TerrainManifest},
     water::WaterDepth,
@@ -36,9 +46,23 @@ use super::{
     goals::Goal,
     impatience::ImpatiencePool,
     item_interaction::UnitInventory,
+    movement::MovementMode,
+    occupancy::TileOccupancy,
+    rest::{Fatigue, ShelterCapacity, ShelterOccupants},
+    scheduling::{AiBudget, LastThought, ThinkingQueue},
+    stats::UnitStats,
     unit_manifest::{Unit, UnitManifest},
 };
 
+/// The energy spent to take a single step on flat ground with empty hands.
+const MOVEMENT_ENERGY_COST: Energy = Energy(0.15);
+
+/// The extra fraction of [`MOVEMENT_ENERGY_COST`] spent for each item carried.
+const CARRIED_ITEM_ENERGY_MULTIPLIER: f32 = 0.25;
+
+/// The energy spent each time a unit performs work or demolition on site.
+const WORK_ENERGY_COST: Energy = Energy(0.05);
+
 /// Ticks the timer for each [`CurrentAction`].
 pub(super) fn advance_action_timer(mut units_query: Query<&mut CurrentAction>, time: Res<Time>) {
     let delta = time.delta();
@@ -49,53 +73,104 @@ pub(super) fn advance_action_timer(mut units_query: Query<&mut CurrentAction>, t
 }
 
 /// Choose the unit's action for this turn
+///
+/// Only the units in the [`ThinkingQueue`] are considered.
 pub(super) fn choose_actions(
-    mut units_query: Query<
-        (
-            &VoxelPos,
-            &Facing,
-            &Goal,
-            &mut CurrentAction,
-            &UnitInventory,
-        ),
-        With<Id<Unit>>,
-    >,
+    mut units_query: Query<(
+        Entity,
+        &VoxelPos,
+        &Facing,
+        &Goal,
+        &mut CurrentAction,
+        &mut LastThought,
+        &UnitInventory,
+        &MovementMode,
+        &UnitStats,
+        &Faction,
+        Option<&HaulingJob>,
+    )>,
     // We shouldn't be dropping off new stuff at structures that are about to be destroyed!
-    input_inventory_query: Query<&InputInventory, Without<MarkedForDemolition>>,
+    input_inventory_query: Query<
+        &InputInventory,
+        (Without<MarkedForDemolition>, Without<Forbidden>),
+    >,
     // But we can take their items away
-    output_inventory_query: Query<&OutputInventory>,
-    storage_inventory_query: Query<&StorageInventory>,
+    output_inventory_query: Query<(&OutputInventory, Has<OutputRouting>), Without<Forbidden>>,
+    storage_inventory_query: Query<&StorageInventory, Without<Forbidden>>,
     workplace_query: WorkplaceQuery,
     demolition_query: DemolitionQuery,
+    shelter_query: Query<&ShelterOccupants, Without<Forbidden>>,
     map_geometry: Res<MapGeometry>,
-    signals: Res<Signals>,
+    signal_channels: Res<SignalChannels>,
+    territory: Res<Territory>,
+    relationships: Res<Relationships>,
     terrain_query: Query<&Id<Terrain>>,
-    litter_query: Query<&Litter>,
+    litter_query: Query<&Litter, Without<Forbidden>>,
     water_depth_query: Query<&WaterDepth>,
     terrain_manifest: Res<TerrainManifest>,
     item_manifest: Res<ItemManifest>,
+    tile_occupancy: Res<TileOccupancy>,
+    wind: Res<Wind>,
+    ai_budget: Res<AiBudget>,
+    mut thinking_queue: ResMut<ThinkingQueue>,
+    mut system_rng: SystemRng,
 ) {
-    let rng = &mut thread_rng();
+    let rng = system_rng.get("choose_actions");
+    let started = Instant::now();
+
+    let mut units_iter = units_query.iter_many_mut(thinking_queue.units());
+    while let Some((
+        unit_entity,
+        &unit_pos,
+        facing,
+        goal,
+        mut current_action,
+        mut last_thought,
+        unit_inventory,
+        &movement_mode,
+        unit_stats,
+        &faction,
+        maybe_hauling_job,
+    )) = units_iter.fetch_next()
+    {
+        // Units that miss out will be first in line next tick
+        if thinking_queue.out_of_time(started, &ai_budget) {
+            break;
+        }
+
+        last_thought.update(&thinking_queue);
 
-    for (&unit_pos, facing, goal, mut current_action, unit_inventory) in units_query.iter_mut() {
         if current_action.finished() {
             let previous_action = current_action.action.clone();
+            let signals = signal_channels.get(faction);
 
             *current_action = match goal {
                 // Drop whatever you're holding before wandering further
-                Goal::Wander { .. } => match unit_inventory.held_item {
+                Goal::Wander { .. } => match unit_inventory.held_item() {
                     Some(_) => CurrentAction::abandon(
                         previous_action,
                         unit_pos,
+                        facing,
+                        movement_mode,
                         unit_inventory,
+                        signals,
+                        &territory,
+                        &relationships,
+                        faction,
                         &map_geometry,
-                        &terrain_manifest,
                         &terrain_query,
+                        &terrain_manifest,
                         rng,
                     ),
                     None => CurrentAction::wander(
                         previous_action,
                         unit_pos,
+                        facing,
+                        movement_mode,
+                        signals,
+                        &territory,
+                        &relationships,
+                        faction,
                         &map_geometry,
                         &terrain_query,
                         &terrain_manifest,
@@ -107,74 +182,95 @@ pub(super) fn choose_actions(
                 | Goal::Store(item_kind)
                 | Goal::Remove(item_kind) => {
                     // If we're holding the wrong thing, drop it.
-                    if unit_inventory.is_some()
-                        && !item_kind.matches(unit_inventory.unwrap(), &item_manifest)
+                    if unit_inventory
+                        .held_item()
+                        .is_some_and(|held_item| !item_kind.matches(held_item, &item_manifest))
                     {
                         CurrentAction::abandon(
                             previous_action,
                             unit_pos,
+                            facing,
+                            movement_mode,
                             unit_inventory,
+                            signals,
+                            &territory,
+                            &relationships,
+                            faction,
                             &map_geometry,
-                            &terrain_manifest,
                             &terrain_query,
+                            &terrain_manifest,
                             rng,
                         )
                     } else {
                         CurrentAction::find(
+                            unit_entity,
                             unit_inventory,
                             *item_kind,
                             goal.delivery_mode().unwrap(),
                             goal.purpose(),
                             unit_pos,
                             facing,
+                            movement_mode,
                             goal,
+                            maybe_hauling_job,
                             &input_inventory_query,
                             &output_inventory_query,
                             &storage_inventory_query,
                             &litter_query,
-                            &signals,
+                            signals,
                             rng,
                             &item_manifest,
                             &terrain_query,
                             &terrain_manifest,
                             &map_geometry,
+                            &tile_occupancy,
                         )
                     }
                 }
                 Goal::Eat(item_kind) => {
-                    if let Some(held_item) = unit_inventory.held_item {
+                    if let Some(held_item) = unit_inventory.held_item() {
                         if item_kind.matches(held_item, &item_manifest) {
                             CurrentAction::eat()
                         } else {
                             CurrentAction::abandon(
                                 previous_action,
                                 unit_pos,
+                                facing,
+                                movement_mode,
                                 unit_inventory,
+                                signals,
+                                &territory,
+                                &relationships,
+                                faction,
                                 &map_geometry,
-                                &terrain_manifest,
                                 &terrain_query,
+                                &terrain_manifest,
                                 rng,
                             )
                         }
                     } else {
                         CurrentAction::find(
+                            unit_entity,
                             unit_inventory,
                             *item_kind,
                             DeliveryMode::PickUp,
                             Purpose::Instrumental,
                             unit_pos,
                             facing,
+                            movement_mode,
                             goal,
+                            None,
                             &input_inventory_query,
                             &output_inventory_query,
                             &storage_inventory_query,
                             &litter_query,
-                            &signals,
+                            signals,
                             rng,
                             &item_manifest,
                             &terrain_query,
                             &terrain_manifest,
                             &map_geometry,
+                            &tile_occupancy,
                         )
                     }
                 }
@@ -182,67 +278,116 @@ pub(super) fn choose_actions(
                     *structure_id,
                     unit_pos,
                     facing,
+                    movement_mode,
                     &workplace_query,
-                    &signals,
+                    signals,
                     rng,
                     &terrain_query,
                     &terrain_manifest,
                     &item_manifest,
                     &map_geometry,
+                    &tile_occupancy,
                 ),
                 Goal::Demolish(structure_id) => CurrentAction::find_demolition_site(
                     *structure_id,
                     unit_pos,
                     facing,
+                    movement_mode,
                     &demolition_query,
-                    &signals,
+                    signals,
                     rng,
                     &item_manifest,
                     &terrain_query,
                     &terrain_manifest,
                     &map_geometry,
+                    &tile_occupancy,
                 ),
                 Goal::Avoid(unit_id) => CurrentAction::avoid(
                     *unit_id,
                     unit_pos,
                     facing,
-                    &signals,
+                    movement_mode,
+                    signals,
                     &item_manifest,
                     &terrain_query,
                     &terrain_manifest,
                     &map_geometry,
+                    &tile_occupancy,
                 ),
                 Goal::Breathe => CurrentAction::find_oxygen(
                     unit_pos,
                     facing,
+                    movement_mode,
                     &water_depth_query,
                     &terrain_query,
                     &terrain_manifest,
                     &map_geometry,
                     rng,
                 ),
-            }
+                Goal::Rest => CurrentAction::find_shelter(
+                    unit_pos,
+                    facing,
+                    movement_mode,
+                    &shelter_query,
+                    signals,
+                    rng,
+                    &item_manifest,
+                    &terrain_query,
+                    &terrain_manifest,
+                    &map_geometry,
+                    &tile_occupancy,
+                ),
+            };
+
+            current_action.adjust_for_movement(
+                unit_pos,
+                facing,
+                movement_mode,
+                unit_stats.speed,
+                &wind,
+            );
         }
     }
+
+    thinking_queue.record_time(started);
 }
 
 /// Exhaustively handles the setup for each planned action
 pub(super) fn start_actions(
-    mut unit_query: Query<(Entity, &mut CurrentAction)>,
+    mut unit_query: Query<(Entity, &mut CurrentAction, &Fatigue, &UnitStats)>,
     mut workplace_query: Query<&mut WorkersPresent>,
+    mut shelter_query: Query<&mut ShelterOccupants>,
+    shelter_capacity: Res<ShelterCapacity>,
 ) {
-    for (worker_entity, mut action) in unit_query.iter_mut() {
+    for (worker_entity, mut action, fatigue, unit_stats) in unit_query.iter_mut() {
         if action.just_started {
             if let Some(workplace_entity) = action.action().workplace() {
                 if let Ok(mut workers_present) = workplace_query.get_mut(workplace_entity) {
+                    // Tired, very young or very old units, and colonies without enough shelter, work more slowly
+                    let efficiency = fatigue.work_efficiency()
+                        * shelter_capacity.work_speed()
+                        * unit_stats.work_rate;
+
                     // This has a side effect of adding the worker to the workplace
-                    let result = workers_present.add_worker(worker_entity);
+                    let result = workers_present.add_worker(worker_entity, efficiency);
                     if result.is_err() {
                         *action = CurrentAction::idle();
                     }
                 }
             }
 
+            if let UnitAction::Rest {
+                shelter_entity: Some(shelter_entity),
+            } = action.action()
+            {
+                if let Ok(mut shelter_occupants) = shelter_query.get_mut(*shelter_entity) {
+                    // Someone else may have taken the last spot
+                    if shelter_occupants.add_resident(worker_entity).is_err() {
+                        *action = CurrentAction::idle();
+                    }
+                }
+            }
+
             action.just_started = false;
         }
     }
@@ -260,18 +405,25 @@ pub(super) fn finish_actions(
         )>,
     >,
     mut workplace_query: Query<(&CraftingState, &mut WorkersPresent)>,
+    mut shelter_query: Query<&mut ShelterOccupants>,
     // This must be compatible with unit_query
     structure_query: Query<&VoxelPos, (With<Id<Structure>>, Without<Goal>)>,
     item_manifest: Res<ItemManifest>,
     unit_manifest: Res<UnitManifest>,
-    signals: Res<Signals>,
+    signal_channels: Res<SignalChannels>,
     map_geometry: Res<MapGeometry>,
+    mut tile_occupancy: ResMut<TileOccupancy>,
+    mut item_ledger: ResMut<ItemLedger>,
+    mut colony_energy: ResMut<ColonyEnergy>,
     mut commands: Commands,
 ) {
     let item_manifest = &*item_manifest;
 
     for mut unit in unit_query.iter_mut() {
-        if unit.action.finished() {
+        if unit.action.finished() && !unit.action.applied {
+            unit.action.applied = true;
+            let mut energy_cost = unit.action.energy_cost(unit.unit_inventory.count());
+
             // Take workers off of the job once actions complete
             if let Some(workplace_entity) = unit.action.action().workplace() {
                 if let Ok(workplace) = workplace_query.get_mut(workplace_entity) {
@@ -296,41 +448,72 @@ pub(super) fn finish_actions(
                         mut maybe_litter,
                     )) = inventory_query.get_mut(*output_entity)
                     {
-                        *unit.goal = match unit.unit_inventory.held_item {
+                        *unit.goal = match unit.unit_inventory.held_item() {
                             // We shouldn't be holding anything yet, but if we are get rid of it
                             Some(held_item_id) => Goal::Store(ItemKind::Single(held_item_id)),
                             None => {
+                                // Items reserved for someone else are left where they are
                                 let maybe_item_id = if let Some(ref output_inventory) =
                                     maybe_output_inventory
                                 {
-                                    output_inventory.matching_item_id(*item_kind, item_manifest)
+                                    output_inventory.available_item_id(
+                                        *item_kind,
+                                        unit.entity,
+                                        item_manifest,
+                                    )
                                 } else if let Some(ref storage_inventory) = maybe_storage_inventory
                                 {
-                                    storage_inventory.matching_item_id(*item_kind, item_manifest)
+                                    storage_inventory.available_item_id(
+                                        *item_kind,
+                                        unit.entity,
+                                        item_manifest,
+                                    )
                                 } else if let Some(ref litter) = maybe_litter {
-                                    litter.matching_item_id(*item_kind, item_manifest)
+                                    litter.available_item_id(*item_kind, unit.entity, item_manifest)
                                 } else {
                                     // The entity must have either an output, storage or litter inventory
                                     unreachable!()
-                                };
+                                }
+                                // Some units can't lift heavy items
+                                .filter(|&item_id| {
+                                    unit.movement_mode.can_carry(item_id, item_manifest)
+                                });
 
                                 if let Some(item_id) = maybe_item_id {
-                                    let item_count = ItemCount::new(item_id, 1);
-
+                                    let available = match (
+                                        &maybe_output_inventory,
+                                        &maybe_storage_inventory,
+                                        &maybe_litter,
+                                    ) {
+                                        (Some(output_inventory), _, _) => output_inventory
+                                            .item_count_available_to(unit.entity, item_id),
+                                        (_, Some(storage_inventory), _) => storage_inventory
+                                            .item_count_available_to(unit.entity, item_id),
+                                        (_, _, Some(litter)) => {
+                                            litter.item_count_available_to(unit.entity, item_id)
+                                        }
+                                        _ => unreachable!(),
+                                    };
+                                    // Grab as many as we can carry in one trip
+                                    let carry_capacity = unit.stats.carry_capacity;
+                                    let item_count =
+                                        ItemCount::new(item_id, available.min(carry_capacity));
+                                    let item_counts = [item_count.clone()];
+                                    // The unit either picks up its whole load, or nothing at all.
+                                    // Its own reservation is used up, leaving those of other units alone.
                                     let transfer_result = match (
                                         &mut maybe_output_inventory,
                                         &mut maybe_storage_inventory,
                                         &mut maybe_litter,
                                     ) {
-                                        (Some(ref mut output_inventory), _, _) => {
-                                            output_inventory.remove_item_all_or_nothing(&item_count)
-                                        }
+                                        (Some(ref mut output_inventory), _, _) => output_inventory
+                                            .take_items_for(unit.entity, &item_counts),
                                         (_, Some(ref mut storage_inventory), _) => {
                                             storage_inventory
-                                                .remove_item_all_or_nothing(&item_count)
+                                                .take_items_for(unit.entity, &item_counts)
                                         }
                                         (_, _, Some(ref mut litter)) => {
-                                            litter.remove_item_all_or_nothing(&item_count)
+                                            litter.take_items_for(unit.entity, &item_counts)
                                         }
                                         // The entity must have either an output, storage or litter inventory
                                         _ => unreachable!(),
@@ -339,8 +522,8 @@ pub(super) fn finish_actions(
                                     // If our unit's all loaded, swap to delivering it
                                     match transfer_result {
                                         Ok(()) => {
-                                            unit.unit_inventory.held_item = Some(item_id);
-                                            if signals.detectable(
+                                            unit.unit_inventory.hold(item_id, item_count.count);
+                                            if signal_channels.get(*unit.faction).detectable(
                                                 SignalType::item_signal_types(
                                                     *item_kind,
                                                     item_manifest,
@@ -373,46 +556,60 @@ pub(super) fn finish_actions(
                     item_kind,
                     input_entity,
                 } => {
-                    if let Ok((maybe_input_inventory, _, maybe_storage_inventory, _)) =
+                    if let Ok((mut maybe_input_inventory, _, mut maybe_storage_inventory, _)) =
                         inventory_query.get_mut(*input_entity)
                     {
-                        *unit.goal = match unit.unit_inventory.held_item {
+                        *unit.goal = match unit.unit_inventory.held_item() {
                             // We should be holding something, if we're not find something else to do
                             None => Goal::default(),
                             Some(held_item_id) => {
                                 if item_kind.matches(held_item_id, item_manifest) {
-                                    let item_count = ItemCount::new(held_item_id, 1);
-                                    let transfer_result = if let Some(mut input_inventory) =
-                                        maybe_input_inventory
-                                    {
-                                        input_inventory.fill_with_items(&item_count, item_manifest)
-                                    } else if let Some(mut storage_inventory) =
-                                        maybe_storage_inventory
-                                    {
-                                        let storage_result = storage_inventory
-                                            .add_item_all_or_nothing(&item_count, item_manifest);
-                                        match storage_result {
-                                            Ok(()) => Ok(()),
-                                            Err(AddOneItemError { excess_count }) => {
-                                                Err(AddToInputError::NotEnoughSpace {
-                                                    excess_count,
-                                                })
-                                            }
-                                        }
-                                    } else {
-                                        unreachable!()
+                                    // Hand over as many of the carried items as will fit
+                                    let free_space =
+                                        match (&maybe_input_inventory, &maybe_storage_inventory) {
+                                            (Some(input_inventory), _) => input_inventory
+                                                .inventory()
+                                                .remaining_space_for_item(
+                                                    held_item_id,
+                                                    item_manifest,
+                                                ),
+                                            (_, Some(storage_inventory)) => storage_inventory
+                                                .remaining_space_for_item(
+                                                    held_item_id,
+                                                    item_manifest,
+                                                ),
+                                            _ => unreachable!(),
+                                        };
+                                    let item_count = ItemCount::new(
+                                        held_item_id,
+                                        unit.unit_inventory.count().min(free_space),
+                                    );
+
+                                    let transfer_result = match (
+                                        &mut maybe_input_inventory,
+                                        &mut maybe_storage_inventory,
+                                    ) {
+                                        (Some(ref mut input_inventory), _) => input_inventory
+                                            .fill_with_items(&item_count, item_manifest),
+                                        (_, Some(ref mut storage_inventory)) => storage_inventory
+                                            .give_items(&[item_count.clone()], item_manifest),
+                                        _ => unreachable!(),
+                                    };
+
+                                    let delivered = match transfer_result {
+                                        Ok(()) => item_count.count,
+                                        Err(..) => 0,
                                     };
+                                    unit.unit_inventory.release(delivered);
 
                                     // If our unit is unloaded, swap to wandering to find something else to do
-                                    match transfer_result {
-                                        Ok(()) => {
-                                            unit.unit_inventory.held_item = None;
-                                            Goal::default()
-                                        }
-                                        Err(..) => {
+                                    if unit.unit_inventory.held_item().is_none() {
+                                        Goal::default()
+                                    } else {
+                                        if delivered == 0 {
                                             unit.impatience.increment();
-                                            Goal::Store(ItemKind::Single(held_item_id))
                                         }
+                                        Goal::Store(ItemKind::Single(held_item_id))
                                     }
                                 } else {
                                     // Somehow we're holding the wrong thing
@@ -430,16 +627,27 @@ pub(super) fn finish_actions(
                     RotationDirection::Right => unit.facing.rotate_clockwise(),
                 },
                 UnitAction::MoveForward => {
-                    if let Some(target_voxel) = map_geometry
-                        .walkable_neighbor_in_direction(*unit.voxel_pos, unit.facing.direction)
-                    {
-                        *unit.voxel_pos = target_voxel;
-                        unit.transform.translation = target_voxel.inside_voxel();
+                    if let Some(target_voxel) = unit.movement_mode.neighbor_in_direction(
+                        *unit.voxel_pos,
+                        unit.facing.direction,
+                        &map_geometry,
+                    ) {
+                        if tile_occupancy.is_full(target_voxel) {
+                            // Wait in line for space to free up, giving up eventually
+                            unit.impatience.increment();
+                            // Steps are only paid for once they succeed
+                            energy_cost = Energy(0.);
+                        } else {
+                            tile_occupancy.move_unit(*unit.voxel_pos, target_voxel);
+                            *unit.voxel_pos = target_voxel;
+                            unit.transform.translation = target_voxel.inside_voxel();
+                        }
                     } else {
                         warn!(
                             "Unit {:?} tried to move forward but no walkable voxel in direction {:?}",
                             unit.entity, unit.facing.direction
                         );
+                        energy_cost = Energy(0.);
                     }
                 }
                 UnitAction::Work { structure_entity } => {
@@ -468,13 +676,14 @@ pub(super) fn finish_actions(
                     *unit.goal = Goal::default();
                 }
                 UnitAction::Eat => {
-                    if let Some(held_item) = unit.unit_inventory.held_item {
+                    if let Some(held_item) = unit.unit_inventory.held_item() {
                         let unit_data = unit_manifest.get(*unit.unit_id);
 
                         let diet = &unit_data.diet;
 
                         if diet.item_kind().matches(held_item, item_manifest) {
-                            unit.unit_inventory.held_item = None;
+                            let eaten = unit.unit_inventory.release(1);
+                            item_ledger.record_consumed(held_item, eaten, ItemSink::Eaten);
 
                             let proposed = unit.energy_pool.current() + diet.energy();
                             unit.energy_pool.set_current(proposed);
@@ -483,14 +692,27 @@ pub(super) fn finish_actions(
                     }
                 }
                 UnitAction::Abandon => {
-                    if let Some(held_item) = unit.unit_inventory.held_item {
-                        commands.spawn_litter(*unit.voxel_pos, held_item);
-                        unit.unit_inventory.held_item = None;
+                    if let Some(held_item) = unit.unit_inventory.held_item() {
+                        for _ in 0..unit.unit_inventory.count() {
+                            commands.spawn_litter(*unit.voxel_pos, held_item);
+                        }
+                        unit.unit_inventory.clear();
                     } else {
                         unit.impatience.increment();
                     }
                 }
+                UnitAction::Rest { shelter_entity } => {
+                    // Make room for the next unit; we'll check in again if we're still tired
+                    if let Some(shelter_entity) = shelter_entity {
+                        if let Ok(mut shelter_occupants) = shelter_query.get_mut(*shelter_entity) {
+                            shelter_occupants.remove_resident(unit.entity);
+                        }
+                    }
+                }
             }
+
+            let spent = unit.energy_pool.debit(energy_cost);
+            colony_energy.record_spending(spent);
         }
     }
 }
@@ -506,7 +728,7 @@ pub(super) struct ActionDataQuery {
     /// The unit's goal
     goal: &'static mut Goal,
     /// The unit's action
-    action: &'static CurrentAction,
+    action: &'static mut CurrentAction,
     /// The unit's progress towards any transformations
     lifecycle: &'static mut Lifecycle,
     /// What the unit is holding
@@ -521,6 +743,12 @@ pub(super) struct ActionDataQuery {
     impatience: &'static mut ImpatiencePool,
     /// The direction this unit is facing
     facing: &'static mut Facing,
+    /// How this unit gets around
+    movement_mode: &'static MovementMode,
+    /// The unit's current physical abilities
+    stats: &'static UnitStats,
+    /// The colony that this unit belongs to
+    faction: &'static Faction,
 }
 
 /// An action that a unit can take.
@@ -564,6 +792,11 @@ pub(super) enum UnitAction {
     Eat,
     /// Abandon whatever you are currently holding, dropping it on the ground
     Abandon,
+    /// Rest to recover from fatigue.
+    Rest {
+        /// The shelter being rested in, if any.
+        shelter_entity: Option<Entity>,
+    },
 }
 
 impl UnitAction {
@@ -610,6 +843,10 @@ impl UnitAction {
             UnitAction::MoveForward => "Moving forward".to_string(),
             UnitAction::Eat => "Eating".to_string(),
             UnitAction::Abandon => "Abandoning held object".to_string(),
+            UnitAction::Rest { shelter_entity } => match shelter_entity {
+                Some(shelter_entity) => format!("Resting in {shelter_entity:?}"),
+                None => "Resting in the open".to_string(),
+            },
         }
     }
 
@@ -626,6 +863,7 @@ impl UnitAction {
             UnitAction::Idle => 0.1,
             UnitAction::Spin { .. } => 0.1,
             UnitAction::MoveForward => 0.3,
+            UnitAction::Rest { .. } => 1.0,
         };
 
         Duration::from_secs_f32(seconds)
@@ -641,6 +879,11 @@ pub(crate) struct CurrentAction {
     timer: Timer,
     /// Did this action just start?
     just_started: bool,
+    /// Have the effects of this finished action been applied?
+    ///
+    /// Units that did not get to think this tick keep their finished action until they do,
+    /// and it must not take effect again in the meantime.
+    applied: bool,
 }
 
 impl Default for CurrentAction {
@@ -657,6 +900,7 @@ impl CurrentAction {
             action,
             timer: Timer::new(duration, TimerMode::Once),
             just_started: true,
+            applied: false,
         }
     }
 
@@ -676,6 +920,37 @@ impl CurrentAction {
         &self.action
     }
 
+    /// The structure or terrain entity that this action is targeting, if any.
+    pub(crate) fn target_entity(&self) -> Option<Entity> {
+        self.action.workplace()
+    }
+
+    /// Is this unit doing work on site, rather than moving items around?
+    pub(crate) fn is_labor(&self) -> bool {
+        matches!(
+            self.action,
+            UnitAction::Work { .. } | UnitAction::Demolish { .. }
+        )
+    }
+
+    /// The energy spent by a unit carrying `carried` items when this action completes.
+    ///
+    /// Steps cost more the longer they take, so rough terrain, headwinds and heavy loads all add up.
+    /// Labor costs a flat amount per action, and everything else is free.
+    pub(super) fn energy_cost(&self, carried: u32) -> Energy {
+        match self.action {
+            UnitAction::MoveForward => {
+                let relative_duration = self.timer.duration().as_secs_f32()
+                    / UnitAction::MoveForward.duration().as_secs_f32();
+                let load_multiplier = 1. + CARRIED_ITEM_ENERGY_MULTIPLIER * carried as f32;
+
+                MOVEMENT_ENERGY_COST * (relative_duration * load_multiplier)
+            }
+            UnitAction::Work { .. } | UnitAction::Demolish { .. } => WORK_ENERGY_COST,
+            _ => Energy(0.),
+        }
+    }
+
     /// Have we waited long enough to perform this action?
     pub(super) fn finished(&self) -> bool {
         self.timer.finished()
@@ -687,66 +962,98 @@ impl CurrentAction {
     /// The only exception is if the storage inventory is full, in which case the unit will pick up items from there.
     ///
     /// Items will never be dropped off at litter, and will only be picked up from litter if no other local options are available.
+    /// Items reserved for other units or orders are never picked up.
+    /// [`Forbidden`] structures and litter are ignored entirely.
+    ///
+    /// Units with a [`HaulingJob`] will only interact with the structures named in their job,
+    /// and head straight for them rather than following signals.
     fn find(
+        unit_entity: Entity,
         unit_inventory: &UnitInventory,
         item_kind: ItemKind,
         delivery_mode: DeliveryMode,
         purpose: Purpose,
         unit_pos: VoxelPos,
         facing: &Facing,
+        movement_mode: MovementMode,
         goal: &Goal,
-        input_inventory_query: &Query<&InputInventory, Without<MarkedForDemolition>>,
-        output_inventory_query: &Query<&OutputInventory>,
-        storage_inventory_query: &Query<&StorageInventory>,
-        litter_query: &Query<&Litter>,
+        maybe_hauling_job: Option<&HaulingJob>,
+        input_inventory_query: &Query<
+            &InputInventory,
+            (Without<MarkedForDemolition>, Without<Forbidden>),
+        >,
+        output_inventory_query: &Query<(&OutputInventory, Has<OutputRouting>), Without<Forbidden>>,
+        storage_inventory_query: &Query<&StorageInventory, Without<Forbidden>>,
+        litter_query: &Query<&Litter, Without<Forbidden>>,
         signals: &Signals,
-        rng: &mut ThreadRng,
+        rng: &mut SmallRng,
         item_manifest: &ItemManifest,
         terrain_query: &Query<&Id<Terrain>>,
         terrain_manifest: &TerrainManifest,
         map_geometry: &MapGeometry,
+        tile_occupancy: &TileOccupancy,
     ) -> CurrentAction {
         let mut candidates: Vec<(Entity, VoxelPos)> = Vec::new();
-        let held_item = unit_inventory.held_item;
+        let held_item = unit_inventory.held_item();
 
         // If we're not holding anyhing, we can't drop it off
         if held_item.is_none() && delivery_mode == DeliveryMode::DropOff {
             return CurrentAction::idle();
         }
 
-        for voxel_pos in unit_pos.reachable_neighbors() {
+        let maybe_job_target = maybe_hauling_job.map(|job| job.target(unit_inventory));
+
+        for voxel_pos in movement_mode.reachable_voxels(unit_pos, map_geometry) {
             if let Some(candidate) = map_geometry.get_candidate(voxel_pos, delivery_mode) {
+                if maybe_job_target.is_some_and(|(target_entity, _)| target_entity != candidate) {
+                    continue;
+                }
+
+                // Items reserved by someone else can't be taken
+                let available = |inventory: &Inventory| {
+                    inventory
+                        .available_item_id(item_kind, unit_entity, item_manifest)
+                        .is_some()
+                };
+
+                // Routed outputs may only be taken by a hauling job, which must be targeting this candidate
+                let available_output = |output_inventory: &OutputInventory, routed: bool| {
+                    (!routed || maybe_job_target.is_some()) && available(output_inventory)
+                };
+
                 match (delivery_mode, purpose) {
                     (DeliveryMode::PickUp, Purpose::Intrinsic) => {
-                        if let Ok(output_inventory) = output_inventory_query.get(candidate) {
-                            if output_inventory.contains_kind(item_kind, item_manifest) {
+                        if let Ok((output_inventory, routed)) =
+                            output_inventory_query.get(candidate)
+                        {
+                            if available_output(output_inventory, routed) {
                                 candidates.push((candidate, voxel_pos));
                             }
                         }
 
                         if let Ok(storage_inventory) = storage_inventory_query.get(candidate) {
-                            if storage_inventory.is_full()
-                                && storage_inventory.contains_kind(item_kind, item_manifest)
-                            {
+                            if storage_inventory.is_full() && available(storage_inventory) {
                                 candidates.push((candidate, voxel_pos));
                             }
                         }
                     }
                     (DeliveryMode::PickUp, Purpose::Instrumental) => {
-                        if let Ok(output_inventory) = output_inventory_query.get(candidate) {
-                            if output_inventory.contains_kind(item_kind, item_manifest) {
+                        if let Ok((output_inventory, routed)) =
+                            output_inventory_query.get(candidate)
+                        {
+                            if available_output(output_inventory, routed) {
                                 candidates.push((candidate, voxel_pos));
                             }
                         }
 
                         if let Ok(storage_inventory) = storage_inventory_query.get(candidate) {
-                            if storage_inventory.contains_kind(item_kind, item_manifest) {
+                            if available(storage_inventory) {
                                 candidates.push((candidate, voxel_pos));
                             }
                         }
 
                         if let Ok(litter) = litter_query.get(candidate) {
-                            if litter.contains_kind(item_kind, item_manifest) {
+                            if available(litter) {
                                 candidates.push((candidate, voxel_pos));
                             }
                         }
@@ -788,12 +1095,28 @@ impl CurrentAction {
                     CurrentAction::dropoff(item_kind, *entity, facing, unit_pos, *voxel_pos)
                 }
             }
-        } else if let Some(upstream) = signals.upstream(unit_pos, goal, item_manifest, map_geometry)
+        } else if let Some(upstream) = maybe_job_target
+            .and_then(|(_, target_pos)| {
+                movement_mode.step_towards(unit_pos, target_pos.hex, map_geometry)
+            })
+            // Don't push into a crowd; follow signals around it instead
+            .filter(|&step| !tile_occupancy.is_full(step))
+            .or_else(|| {
+                signals.upstream(
+                    unit_pos,
+                    goal,
+                    movement_mode,
+                    item_manifest,
+                    map_geometry,
+                    tile_occupancy,
+                )
+            })
         {
             CurrentAction::move_or_spin(
                 unit_pos,
                 upstream,
                 facing,
+                movement_mode,
                 terrain_query,
                 terrain_manifest,
                 map_geometry,
@@ -808,32 +1131,42 @@ impl CurrentAction {
         workplace_id: WorkplaceId,
         unit_pos: VoxelPos,
         facing: &Facing,
+        movement_mode: MovementMode,
         workplace_query: &WorkplaceQuery,
         signals: &Signals,
-        rng: &mut ThreadRng,
+        rng: &mut SmallRng,
         terrain_query: &Query<&Id<Terrain>>,
         terrain_manifest: &TerrainManifest,
         item_manifest: &ItemManifest,
         map_geometry: &MapGeometry,
+        tile_occupancy: &TileOccupancy,
     ) -> CurrentAction {
         let ahead = unit_pos.neighbor(facing.direction);
         if let Some(workplace) =
-            workplace_query.needs_work(unit_pos, ahead, workplace_id, map_geometry)
+            workplace_query.needs_work(unit_pos, ahead, workplace_id, movement_mode, map_geometry)
         {
             CurrentAction::work(workplace)
         // Let units work even if they're standing on the structure
         // This is particularly relevant in the case of ghosts, where it's easy enough to end up on top of the structure trying to work on it
-        } else if let Some(workplace) =
-            workplace_query.needs_work(unit_pos, unit_pos, workplace_id, map_geometry)
-        {
+        } else if let Some(workplace) = workplace_query.needs_work(
+            unit_pos,
+            unit_pos,
+            workplace_id,
+            movement_mode,
+            map_geometry,
+        ) {
             CurrentAction::work(workplace)
         } else {
             let mut workplaces: Vec<(Entity, VoxelPos)> = Vec::new();
 
-            for neighbor in unit_pos.reachable_neighbors() {
-                if let Some(workplace) =
-                    workplace_query.needs_work(unit_pos, neighbor, workplace_id, map_geometry)
-                {
+            for neighbor in movement_mode.reachable_voxels(unit_pos, map_geometry) {
+                if let Some(workplace) = workplace_query.needs_work(
+                    unit_pos,
+                    neighbor,
+                    workplace_id,
+                    movement_mode,
+                    map_geometry,
+                ) {
                     workplaces.push((workplace, neighbor));
                 }
             }
@@ -843,6 +1176,7 @@ impl CurrentAction {
                     unit_pos,
                     chosen_workplace.1,
                     facing,
+                    movement_mode,
                     terrain_query,
                     terrain_manifest,
                     map_geometry,
@@ -850,13 +1184,16 @@ impl CurrentAction {
             } else if let Some(upstream) = signals.upstream(
                 unit_pos,
                 &Goal::Work(workplace_id),
+                movement_mode,
                 item_manifest,
                 map_geometry,
+                tile_occupancy,
             ) {
                 CurrentAction::move_or_spin(
                     unit_pos,
                     upstream,
                     facing,
+                    movement_mode,
                     terrain_query,
                     terrain_manifest,
                     map_geometry,
@@ -867,36 +1204,120 @@ impl CurrentAction {
         }
     }
 
+    /// Attempt to find a shelter with room to rest in.
+    ///
+    /// If no shelter can be found, units will rest wherever they are.
+    fn find_shelter(
+        unit_pos: VoxelPos,
+        facing: &Facing,
+        movement_mode: MovementMode,
+        shelter_query: &Query<&ShelterOccupants, Without<Forbidden>>,
+        signals: &Signals,
+        rng: &mut SmallRng,
+        item_manifest: &ItemManifest,
+        terrain_query: &Query<&Id<Terrain>>,
+        terrain_manifest: &TerrainManifest,
+        map_geometry: &MapGeometry,
+        tile_occupancy: &TileOccupancy,
+    ) -> CurrentAction {
+        let shelter_with_room = |voxel_pos: VoxelPos| {
+            // This is only a viable target if the unit can reach it!
+            if !movement_mode.can_reach(unit_pos, voxel_pos) {
+                return None;
+            }
+
+            map_geometry.get_structure(voxel_pos).filter(|&entity| {
+                shelter_query
+                    .get(entity)
+                    .is_ok_and(|shelter_occupants| shelter_occupants.has_room())
+            })
+        };
+
+        let ahead = unit_pos.neighbor(facing.direction);
+        // Let units rest when standing on the shelter too
+        if let Some(shelter) = shelter_with_room(ahead).or_else(|| shelter_with_room(unit_pos)) {
+            CurrentAction::rest(Some(shelter))
+        } else {
+            let shelters: Vec<VoxelPos> = movement_mode
+                .reachable_voxels(unit_pos, map_geometry)
+                .into_iter()
+                .filter(|&neighbor| shelter_with_room(neighbor).is_some())
+                .collect();
+
+            if let Some(&chosen_shelter) = shelters.choose(rng) {
+                CurrentAction::move_or_spin(
+                    unit_pos,
+                    chosen_shelter,
+                    facing,
+                    movement_mode,
+                    terrain_query,
+                    terrain_manifest,
+                    map_geometry,
+                )
+            } else if let Some(upstream) = signals.upstream(
+                unit_pos,
+                &Goal::Rest,
+                movement_mode,
+                item_manifest,
+                map_geometry,
+                tile_occupancy,
+            ) {
+                CurrentAction::move_or_spin(
+                    unit_pos,
+                    upstream,
+                    facing,
+                    movement_mode,
+                    terrain_query,
+                    terrain_manifest,
+                    map_geometry,
+                )
+            } else {
+                CurrentAction::rest(None)
+            }
+        }
+    }
+
     /// Attempt to find a structure of type `structure_id` to perform work
     fn find_demolition_site(
         structure_id: Id<Structure>,
         unit_pos: VoxelPos,
         facing: &Facing,
+        movement_mode: MovementMode,
         demolition_query: &DemolitionQuery,
         signals: &Signals,
-        rng: &mut ThreadRng,
+        rng: &mut SmallRng,
         item_manifest: &ItemManifest,
         terrain_query: &Query<&Id<Terrain>>,
         terrain_manifest: &TerrainManifest,
         map_geometry: &MapGeometry,
+        tile_occupancy: &TileOccupancy,
     ) -> CurrentAction {
         let ahead = unit_pos.neighbor(facing.direction);
-        if let Some(workplace) =
-            demolition_query.needs_demolition(unit_pos, ahead, structure_id, map_geometry)
-        {
+        if let Some(workplace) = demolition_query.needs_demolition(
+            unit_pos,
+            ahead,
+            structure_id,
+            movement_mode,
+            map_geometry,
+        ) {
             CurrentAction::demolish(workplace)
-        } else if let Some(workplace) =
-            demolition_query.needs_demolition(unit_pos, unit_pos, structure_id, map_geometry)
-        {
+        } else if let Some(workplace) = demolition_query.needs_demolition(
+            unit_pos,
+            unit_pos,
+            structure_id,
+            movement_mode,
+            map_geometry,
+        ) {
             CurrentAction::demolish(workplace)
         } else {
             let mut demo_sites: Vec<(Entity, VoxelPos)> = Vec::new();
 
-            for neighbor in unit_pos.reachable_neighbors() {
+            for neighbor in movement_mode.reachable_voxels(unit_pos, map_geometry) {
                 if let Some(demo_site) = demolition_query.needs_demolition(
                     unit_pos,
                     neighbor,
                     structure_id,
+                    movement_mode,
                     map_geometry,
                 ) {
                     demo_sites.push((demo_site, neighbor));
@@ -908,6 +1329,7 @@ impl CurrentAction {
                     unit_pos,
                     chosen_demo_site.1,
                     facing,
+                    movement_mode,
                     terrain_query,
                     terrain_manifest,
                     map_geometry,
@@ -915,13 +1337,16 @@ impl CurrentAction {
             } else if let Some(upstream) = signals.upstream(
                 unit_pos,
                 &Goal::Demolish(structure_id),
+                movement_mode,
                 item_manifest,
                 map_geometry,
+                tile_occupancy,
             ) {
                 CurrentAction::move_or_spin(
                     unit_pos,
                     upstream,
                     facing,
+                    movement_mode,
                     terrain_query,
                     terrain_manifest,
                     map_geometry,
@@ -932,6 +1357,30 @@ impl CurrentAction {
         }
     }
 
+    /// Adjusts the duration of a planned step to account for how this unit gets around.
+    ///
+    /// Walking units keep the terrain and path speed already applied to the step.
+    /// Flying units ignore the terrain underneath them, but are pushed around by the wind.
+    fn adjust_for_movement(
+        &mut self,
+        unit_pos: VoxelPos,
+        facing: &Facing,
+        movement_mode: MovementMode,
+        speed: f32,
+        wind: &Wind,
+    ) {
+        if matches!(self.action, UnitAction::MoveForward) {
+            let target = unit_pos.hex.neighbor(facing.direction);
+            let speed = speed * movement_mode.speed_multiplier(unit_pos.hex, target, wind);
+            let base_duration = match movement_mode {
+                MovementMode::Walking => self.timer.duration(),
+                MovementMode::Flying => UnitAction::MoveForward.duration(),
+            };
+
+            self.timer = Timer::from_seconds(base_duration.as_secs_f32() / speed, TimerMode::Once);
+        }
+    }
+
     /// Spins 60 degrees left or right.
     pub(super) fn spin(rotation_direction: RotationDirection) -> Self {
         CurrentAction::new(UnitAction::Spin { rotation_direction })
@@ -959,7 +1408,7 @@ impl CurrentAction {
     }
 
     /// Spins 60 degrees in a random direction
-    pub(super) fn random_spin(rng: &mut ThreadRng) -> Self {
+    pub(super) fn random_spin(rng: &mut SmallRng) -> Self {
         let rotation_direction = RotationDirection::random(rng);
 
         CurrentAction::spin(rotation_direction)
@@ -970,19 +1419,27 @@ impl CurrentAction {
         goal: &Goal,
         current_tile: VoxelPos,
         facing: &Facing,
+        movement_mode: MovementMode,
         signals: &Signals,
         item_manifest: &ItemManifest,
         terrain_query: &Query<&Id<Terrain>>,
         terrain_manifest: &TerrainManifest,
         map_geometry: &MapGeometry,
+        tile_occupancy: &TileOccupancy,
     ) -> Self {
-        if let Some(target_tile) =
-            signals.downstream(current_tile, goal, item_manifest, map_geometry)
-        {
+        if let Some(target_tile) = signals.downstream(
+            current_tile,
+            goal,
+            movement_mode,
+            item_manifest,
+            map_geometry,
+            tile_occupancy,
+        ) {
             CurrentAction::move_or_spin(
                 current_tile,
                 target_tile,
                 facing,
+                movement_mode,
                 terrain_query,
                 terrain_manifest,
                 map_geometry,
@@ -1011,20 +1468,29 @@ impl CurrentAction {
             terrain_manifest.get(*terrain_standing_on).walking_speed
         };
 
+        CurrentAction::step(walking_speed)
+    }
+
+    /// Takes a single step forward, at `walking_speed` relative to walking on ordinary ground.
+    fn step(walking_speed: f32) -> Self {
         let walking_duration = UnitAction::MoveForward.duration().as_secs_f32() / walking_speed;
 
         CurrentAction {
             action: UnitAction::MoveForward,
             timer: Timer::from_seconds(walking_duration, TimerMode::Once),
             just_started: true,
+            applied: false,
         }
     }
 
     /// Attempt to move toward the `target_tile_pos`.
+    ///
+    /// If the way ahead cannot be traveled using `movement_mode`, the unit waits instead.
     pub(super) fn move_or_spin(
         unit_pos: VoxelPos,
         target_tile_pos: VoxelPos,
         facing: &Facing,
+        movement_mode: MovementMode,
         terrain_query: &Query<&Id<Terrain>>,
         terrain_manifest: &TerrainManifest,
         map_geometry: &MapGeometry,
@@ -1032,7 +1498,14 @@ impl CurrentAction {
         let required_direction = unit_pos.hex.main_direction_to(target_tile_pos.hex);
 
         if required_direction == facing.direction {
-            CurrentAction::move_forward(unit_pos, map_geometry, terrain_query, terrain_manifest)
+            if movement_mode
+                .neighbor_in_direction(unit_pos, required_direction, map_geometry)
+                .is_some()
+            {
+                CurrentAction::move_forward(unit_pos, map_geometry, terrain_query, terrain_manifest)
+            } else {
+                CurrentAction::idle()
+            }
         } else {
             CurrentAction::spin_towards(facing, required_direction)
         }
@@ -1088,6 +1561,11 @@ impl CurrentAction {
         CurrentAction::new(UnitAction::Eat)
     }
 
+    /// Rest, inside of the specified shelter if any
+    pub(super) fn rest(shelter_entity: Option<Entity>) -> Self {
+        CurrentAction::new(UnitAction::Rest { shelter_entity })
+    }
+
     /// Work at the specified structure
     pub(super) fn work(structure_entity: Entity) -> Self {
         CurrentAction::new(UnitAction::Work { structure_entity })
@@ -1104,18 +1582,30 @@ impl CurrentAction {
     pub(super) fn abandon(
         previous_action: UnitAction,
         unit_pos: VoxelPos,
+        facing: &Facing,
+        movement_mode: MovementMode,
         unit_inventory: &UnitInventory,
+        signals: &Signals,
+        territory: &Territory,
+        relationships: &Relationships,
+        faction: Faction,
         map_geometry: &MapGeometry,
-        terrain_manifest: &TerrainManifest,
         terrain_query: &Query<&Id<Terrain>>,
-        rng: &mut ThreadRng,
+        terrain_manifest: &TerrainManifest,
+        rng: &mut SmallRng,
     ) -> Self {
-        if unit_inventory.held_item.is_some() {
+        if unit_inventory.held_item().is_some() {
             CurrentAction::new(UnitAction::Abandon)
         } else {
             CurrentAction::wander(
                 previous_action,
                 unit_pos,
+                facing,
+                movement_mode,
+                signals,
+                territory,
+                relationships,
+                faction,
                 map_geometry,
                 terrain_query,
                 terrain_manifest,
@@ -1126,20 +1616,79 @@ impl CurrentAction {
 
     /// Wander around randomly.
     ///
-    /// This will alternate between moving forward and spinning.
+    /// This will alternate between moving forward and turning.
+    /// Units are more likely to turn towards neighboring tiles with stronger goal-related signals,
+    /// and towards stronger [`SignalType::WorkNeeded`] signals,
+    /// so they tend to drift towards places where there is work to be done.
+    /// [`SignalType::Crowding`] signals count against a direction, so idle units spread out rather than clumping together.
+    /// They are less likely to turn towards tiles in territory controlled by a faction that doesn't welcome them.
     pub(super) fn wander(
         previous_action: UnitAction,
         unit_pos: VoxelPos,
+        facing: &Facing,
+        movement_mode: MovementMode,
+        signals: &Signals,
+        territory: &Territory,
+        relationships: &Relationships,
+        faction: Faction,
         map_geometry: &MapGeometry,
         terrain_query: &Query<&Id<Terrain>>,
         terrain_manifest: &TerrainManifest,
-        rng: &mut ThreadRng,
+        rng: &mut SmallRng,
     ) -> Self {
-        match previous_action {
-            UnitAction::Spin { .. } => {
+        /// The weight given to each walkable direction, regardless of the signals there.
+        ///
+        /// This keeps wandering random when there are no signals nearby.
+        const BASE_WANDER_WEIGHT: f32 = 1.0;
+
+        /// The lowest weight that a walkable direction can be given, no matter how crowded it is.
+        ///
+        /// This must be greater than 0, so that units can still move through crowds.
+        const MIN_WANDER_WEIGHT: f32 = 0.05;
+
+        if let UnitAction::Spin { .. } = previous_action {
+            return CurrentAction::move_forward(
+                unit_pos,
+                map_geometry,
+                terrain_query,
+                terrain_manifest,
+            );
+        }
+
+        let neighbors = hexx::Direction::ALL_DIRECTIONS.map(|direction| {
+            movement_mode.neighbor_in_direction(unit_pos, direction, map_geometry)
+        });
+        // Unreachable neighbors are never chosen, so what's looked up for them doesn't matter
+        let attractions =
+            signals.wander_attractions(neighbors.map(|neighbor| neighbor.unwrap_or(unit_pos)));
+
+        let chosen_direction = weighted_random_direction(rng, |direction| {
+            let index = hexx::Direction::ALL_DIRECTIONS
+                .iter()
+                .position(|&candidate| candidate == direction)
+                .unwrap();
+
+            match neighbors[index] {
+                Some(neighbor) => {
+                    let weight = (BASE_WANDER_WEIGHT + attractions[index]).max(MIN_WANDER_WEIGHT);
+
+                    if territory.is_trespassing(neighbor.hex, faction, relationships) {
+                        weight * Territory::TRESPASS_WEIGHT
+                    } else {
+                        weight
+                    }
+                }
+                None => 0.,
+            }
+        });
+
+        match chosen_direction {
+            Some(direction) if direction == facing.direction => {
                 CurrentAction::move_forward(unit_pos, map_geometry, terrain_query, terrain_manifest)
             }
-            _ => CurrentAction::random_spin(rng),
+            Some(direction) => CurrentAction::spin_towards(facing, direction),
+            // Boxed in: spinning is all we can do
+            None => CurrentAction::random_spin(rng),
         }
     }
 
@@ -1148,11 +1697,13 @@ impl CurrentAction {
         unit_id: Id<Unit>,
         current_tile: VoxelPos,
         facing: &Facing,
+        movement_mode: MovementMode,
         signals: &Signals,
         item_manifest: &ItemManifest,
         terrain_query: &Query<&Id<Terrain>>,
         terrain_manifest: &TerrainManifest,
         map_geometry: &MapGeometry,
+        tile_occupancy: &TileOccupancy,
     ) -> Self {
         /// The relative signal strength threshold at which we will stop avoiding the source of our discomfort.
         ///
@@ -1172,11 +1723,13 @@ impl CurrentAction {
                     &Goal::Avoid(unit_id),
                     current_tile,
                     facing,
+                    movement_mode,
                     signals,
                     item_manifest,
                     terrain_query,
                     terrain_manifest,
                     map_geometry,
+                    tile_occupancy,
                 );
             }
         }
@@ -1189,11 +1742,12 @@ impl CurrentAction {
     fn find_oxygen(
         current_tile: VoxelPos,
         facing: &Facing,
+        movement_mode: MovementMode,
         water_depth_query: &Query<&WaterDepth>,
         terrain_query: &Query<&Id<Terrain>>,
         terrain_manifest: &TerrainManifest,
         map_geometry: &MapGeometry,
-        rng: &mut ThreadRng,
+        rng: &mut SmallRng,
     ) -> Self {
         let terrain_entity = map_geometry.get_terrain(current_tile.hex).unwrap();
         let current_depth = water_depth_query
@@ -1203,8 +1757,8 @@ impl CurrentAction {
         let mut candidates = Vec::new();
 
         // Find all adjacent tiles that are shallower than the current tile.
-        for adjacent_tile in map_geometry.walkable_neighbors(current_tile) {
-            let adjacent_terrain_entity = map_geometry.get_terrain(current_tile.hex).unwrap();
+        for adjacent_tile in movement_mode.neighbors(current_tile, map_geometry) {
+            let adjacent_terrain_entity = map_geometry.get_terrain(adjacent_tile.hex).unwrap();
             let adjacent_depth = water_depth_query
                 .get(adjacent_terrain_entity)
                 .unwrap()
@@ -1221,6 +1775,7 @@ impl CurrentAction {
                 current_tile,
                 *target_tile,
                 facing,
+                movement_mode,
                 terrain_query,
                 terrain_manifest,
                 map_geometry,
@@ -1243,6 +1798,7 @@ pub(crate) struct WorkplaceQuery<'w, 's> {
             AnyOf<(&'static Id<Structure>, &'static TerraformingAction)>,
             &'static WorkersPresent,
         ),
+        Without<Forbidden>,
     >,
 }
 
@@ -1255,10 +1811,11 @@ impl<'w, 's> WorkplaceQuery<'w, 's> {
         current: VoxelPos,
         target: VoxelPos,
         workplace_id: WorkplaceId,
+        movement_mode: MovementMode,
         map_geometry: &MapGeometry,
     ) -> Option<Entity> {
         // This is only a viable target if the unit can reach it!
-        if current.abs_height_diff(target) > Height::MAX_STEP {
+        if !movement_mode.can_reach(current, target) {
             return None;
         }
 
@@ -1303,3 +1860,293 @@ pub(crate) enum Purpose {
     /// This will take / place items from storage.
     Instrumental,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset_management::manifest::Manifest,
+        geometry::DiscreteHeight,
+        items::{inventory::Inventory, item_manifest::Item},
+        organisms::dormancy::{DormancyCause, Dormant},
+        structures::Footprint,
+        units::{basic_needs::rest_while_dormant, scheduling::schedule_thinking},
+    };
+    use bevy::core::FrameCount;
+
+    fn adjusted_step(walking_speed: f32, movement_mode: MovementMode) -> CurrentAction {
+        let mut action = CurrentAction::step(walking_speed);
+        action.adjust_for_movement(
+            VoxelPos::ZERO,
+            &Facing {
+                direction: hexx::Direction::Top,
+            },
+            movement_mode,
+            1.0,
+            &Wind::default(),
+        );
+        action
+    }
+
+    #[test]
+    fn fast_terrain_shortens_steps() {
+        let ordinary = adjusted_step(1.0, MovementMode::Walking);
+        let fast = adjusted_step(2.0, MovementMode::Walking);
+        assert!(fast.timer.duration() < ordinary.timer.duration());
+
+        // The step finishes sooner on fast terrain
+        let mut fast = fast;
+        fast.timer.tick(ordinary.timer.duration().mul_f32(0.75));
+        assert!(fast.finished());
+    }
+
+    #[test]
+    fn steps_on_slow_terrain_cost_more_energy() {
+        let slow = adjusted_step(0.5, MovementMode::Walking);
+        let ordinary = adjusted_step(1.0, MovementMode::Walking);
+        let fast = adjusted_step(2.0, MovementMode::Walking);
+
+        assert!(slow.energy_cost(0).0 > ordinary.energy_cost(0).0);
+        assert!(ordinary.energy_cost(0).0 > fast.energy_cost(0).0);
+        // Carrying items makes every step harder
+        assert!(ordinary.energy_cost(2).0 > ordinary.energy_cost(0).0);
+    }
+
+    #[test]
+    fn flying_ignores_terrain_speed() {
+        let ordinary = adjusted_step(1.0, MovementMode::Flying);
+        let fast = adjusted_step(2.0, MovementMode::Flying);
+        assert_eq!(fast.timer.duration(), ordinary.timer.duration());
+    }
+    #[test]
+    fn finished_actions_are_applied_once_while_units_wait_to_think() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        world.insert_resource(map_geometry);
+        world.insert_resource::<ItemManifest>(Manifest::new());
+        world.insert_resource::<UnitManifest>(Manifest::new());
+        world.init_resource::<SignalChannels>();
+        world.init_resource::<TileOccupancy>();
+        world.init_resource::<ItemLedger>();
+        world.init_resource::<ColonyEnergy>();
+        world.init_resource::<ThinkingQueue>();
+        world.init_resource::<FrameCount>();
+        // Fewer units can think each tick than are waiting to
+        world.insert_resource(AiBudget {
+            max_units_per_tick: 1,
+            ..default()
+        });
+
+        let units: Vec<Entity> = (0..3)
+            .map(|_| {
+                let mut action = CurrentAction::idle();
+                let duration = action.timer.duration();
+                action.timer.tick(duration);
+
+                world
+                    .spawn((
+                        Id::<Unit>::from_name("unit".to_string()),
+                        Goal::default(),
+                        action,
+                        Lifecycle::default(),
+                        UnitInventory::default(),
+                        Transform::default(),
+                        VoxelPos::ZERO,
+                        EnergyPool::simple(10.),
+                        ImpatiencePool::new(10),
+                        Facing {
+                            direction: hexx::Direction::Top,
+                        },
+                        MovementMode::default(),
+                        UnitStats {
+                            speed: 1.,
+                            carry_capacity: 1,
+                            work_rate: 1.,
+                        },
+                        Faction::default(),
+                        LastThought::default(),
+                    ))
+                    .id()
+            })
+            .collect();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((finish_actions, schedule_thinking).chain());
+        for _ in 0..3 {
+            schedule.run(&mut world);
+        }
+
+        assert_eq!(world.resource::<ThinkingQueue>().units().len(), 1);
+        for unit in units {
+            // Idling once makes the unit a little impatient, but only once
+            assert_eq!(
+                world.get::<ImpatiencePool>(unit).unwrap().to_string(),
+                "1/10"
+            );
+        }
+    }
+
+    #[test]
+    fn starving_dormant_units_still_eat() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        world.insert_resource(map_geometry);
+        world.insert_resource::<ItemManifest>(Manifest::new());
+        world.insert_resource::<TerrainManifest>(Manifest::new());
+        world.init_resource::<SignalChannels>();
+        world.init_resource::<Territory>();
+        world.init_resource::<Relationships>();
+        world.init_resource::<TileOccupancy>();
+        world.init_resource::<Wind>();
+        world.init_resource::<AiBudget>();
+        world.init_resource::<ThinkingQueue>();
+        world.init_resource::<FrameCount>();
+
+        let food = Id::<Item>::from_name("food".to_string());
+        let mut spawn_hungry_unit = |cause: DormancyCause| {
+            let mut action = CurrentAction::idle();
+            let duration = action.timer.duration();
+            action.timer.tick(duration);
+
+            let mut unit_inventory = UnitInventory::default();
+            unit_inventory.hold(food, 1);
+
+            world
+                .spawn((
+                    Id::<Unit>::from_name("unit".to_string()),
+                    Goal::Eat(ItemKind::Single(food)),
+                    action,
+                    unit_inventory,
+                    VoxelPos::ZERO,
+                    ImpatiencePool::new(10),
+                    Facing {
+                        direction: hexx::Direction::Top,
+                    },
+                    MovementMode::default(),
+                    UnitStats {
+                        speed: 1.,
+                        carry_capacity: 1,
+                        work_rate: 1.,
+                    },
+                    Faction::default(),
+                    LastThought::default(),
+                    Dormant::new(cause, 0.5),
+                ))
+                .id()
+        };
+
+        let starving_unit = spawn_hungry_unit(DormancyCause::Starvation);
+        let frozen_unit = spawn_hungry_unit(DormancyCause::Frost);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((schedule_thinking, choose_actions, rest_while_dormant).chain());
+        schedule.run(&mut world);
+
+        assert!(matches!(
+            world.get::<CurrentAction>(starving_unit).unwrap().action(),
+            UnitAction::Eat
+        ));
+        assert!(matches!(
+            world.get::<CurrentAction>(frozen_unit).unwrap().action(),
+            UnitAction::Idle
+        ));
+    }
+
+    /// Does a unit that wants food pick it up from a neighboring structure with the given `routing`?
+    fn picks_up_routed_output(routing: Option<OutputRouting>, hauling: bool) -> bool {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+        world.insert_resource::<ItemManifest>(Manifest::new());
+        world.insert_resource::<TerrainManifest>(Manifest::new());
+        world.init_resource::<SignalChannels>();
+        world.init_resource::<Territory>();
+        world.init_resource::<Relationships>();
+        world.init_resource::<TileOccupancy>();
+        world.init_resource::<Wind>();
+        world.init_resource::<AiBudget>();
+        world.init_resource::<ThinkingQueue>();
+        world.init_resource::<FrameCount>();
+
+        let food = Id::<Item>::from_name("food".to_string());
+        let structure_pos = VoxelPos {
+            hex: hexx::Hex::new(1, 0),
+            height: DiscreteHeight(1),
+        };
+        let mut structure = world.spawn(OutputInventory {
+            inventory: Inventory::full_from_item(food, 1),
+        });
+        if let Some(routing) = routing {
+            structure.insert(routing);
+        }
+        let structure = structure.id();
+        map_geometry
+            .add_structure(
+                structure_pos,
+                Facing::default(),
+                &Footprint::default(),
+                false,
+                false,
+                structure,
+            )
+            .unwrap();
+        world.insert_resource(map_geometry);
+
+        let mut action = CurrentAction::idle();
+        let duration = action.timer.duration();
+        action.timer.tick(duration);
+
+        let mut unit = world.spawn((
+            Id::<Unit>::from_name("unit".to_string()),
+            Goal::Fetch(ItemKind::Single(food)),
+            action,
+            UnitInventory::default(),
+            VoxelPos {
+                hex: hexx::Hex::ZERO,
+                height: DiscreteHeight(1),
+            },
+            ImpatiencePool::new(10),
+            Facing::default(),
+            MovementMode::default(),
+            UnitStats {
+                speed: 1.,
+                carry_capacity: 1,
+                work_rate: 1.,
+            },
+            Faction::default(),
+            LastThought::default(),
+        ));
+        if hauling {
+            unit.insert(HaulingJob {
+                item_id: food,
+                source: structure,
+                source_pos: structure_pos,
+                destination: structure,
+                destination_pos: structure_pos,
+            });
+        }
+        let unit = unit.id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((schedule_thinking, choose_actions).chain());
+        schedule.run(&mut world);
+
+        matches!(
+            world.get::<CurrentAction>(unit).unwrap().action(),
+            UnitAction::PickUp { output_entity, .. } if *output_entity == structure
+        )
+    }
+
+    #[test]
+    fn routed_outputs_are_only_picked_up_by_hauling_jobs() {
+        assert!(picks_up_routed_output(None, false));
+        assert!(!picks_up_routed_output(Some(OutputRouting::Hold), false));
+        assert!(!picks_up_routed_output(
+            Some(OutputRouting::NearestStorage),
+            false
+        ));
+        assert!(picks_up_routed_output(
+            Some(OutputRouting::NearestStorage),
+            true
+        ));
+    }
+}
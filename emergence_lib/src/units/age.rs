@@ -9,7 +9,12 @@ use bevy::prelude::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use crate::asset_management::manifest::Id;
+use crate::geometry::VoxelPos;
+use crate::organisms::OrganismId;
 use crate::simulation::time::{Days, InGameTime};
+use crate::terrain::history::{DeathCause, TileEvent, TileEventKind};
+use crate::units::unit_manifest::Unit;
 
 /// The age of a unit, in in-game days.
 #[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -46,6 +51,14 @@ impl Age {
     pub fn max(&self) -> Days {
         self.max
     }
+
+    /// How far through its life this unit is, between 0 (newborn) and 1 (about to die of old age).
+    pub fn fraction_of_life(&self) -> f32 {
+        match self.max > Days::ZERO {
+            true => (self.current.0 / self.max.0).clamp(0., 1.),
+            false => 1.,
+        }
+    }
 }
 
 impl Display for Age {
@@ -54,21 +67,166 @@ impl Display for Age {
     }
 }
 
+/// How a stat changes over the course of a unit's life.
+///
+/// The curve is made of `(fraction_of_life, multiplier)` points, sorted by fraction of life.
+/// Multipliers are linearly interpolated between points, and held constant before the first and after the last point.
+/// A curve with no points is flat, and always returns a multiplier of 1.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawAgeCurve")]
+pub struct AgeCurve {
+    /// The points defining the curve.
+    points: Vec<(f32, f32)>,
+}
+
+/// An [`AgeCurve`] as written in the manifest, whose points may be in any order.
+#[derive(Deserialize)]
+struct RawAgeCurve {
+    /// The points defining the curve.
+    points: Vec<(f32, f32)>,
+}
+
+impl From<RawAgeCurve> for AgeCurve {
+    fn from(raw: RawAgeCurve) -> Self {
+        AgeCurve::new(raw.points)
+    }
+}
+
+impl AgeCurve {
+    /// Creates a new curve from a list of `(fraction_of_life, multiplier)` points.
+    ///
+    /// The points are sorted by fraction of life.
+    pub fn new(points: impl IntoIterator<Item = (f32, f32)>) -> Self {
+        let mut points: Vec<(f32, f32)> = points.into_iter().collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Self { points }
+    }
+
+    /// The multiplier for a unit that is `fraction_of_life` of the way through its life.
+    pub fn multiplier(&self, fraction_of_life: f32) -> f32 {
+        let Some(&(first_fraction, first_multiplier)) = self.points.first() else {
+            return 1.;
+        };
+
+        if fraction_of_life <= first_fraction {
+            return first_multiplier;
+        }
+
+        for window in self.points.windows(2) {
+            let (start_fraction, start_multiplier) = window[0];
+            let (end_fraction, end_multiplier) = window[1];
+
+            if fraction_of_life <= end_fraction {
+                let span = end_fraction - start_fraction;
+                if span <= 0. {
+                    return end_multiplier;
+                }
+
+                let t = (fraction_of_life - start_fraction) / span;
+                return start_multiplier + t * (end_multiplier - start_multiplier);
+            }
+        }
+
+        // We're past the last point
+        self.points.last().unwrap().1
+    }
+}
+
+/// How the stats of a unit type change as its members age.
+///
+/// Each curve scales the corresponding base stat in the unit manifest.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgeCurves {
+    /// Scales how fast the unit moves.
+    #[serde(default)]
+    pub speed: AgeCurve,
+    /// Scales how many items the unit can carry at once.
+    #[serde(default)]
+    pub carry_capacity: AgeCurve,
+    /// Scales how effectively the unit works at structures.
+    #[serde(default)]
+    pub work_rate: AgeCurve,
+}
+
 /// Advances the age of all units by the elapsed time and kills them if they are too old.
 pub(super) fn aging(
     mut commands: Commands,
     time: Res<Time>,
     in_game_time: Res<InGameTime>,
-    mut query: Query<(&mut Age, Entity)>,
+    mut query: Query<(&mut Age, Entity, &Id<Unit>, &VoxelPos)>,
+    mut tile_events: EventWriter<TileEvent>,
 ) {
     let delta_time = time.delta().as_secs_f32();
     let delta_days = Days(delta_time / in_game_time.seconds_per_day());
 
-    for (mut age, entity) in query.iter_mut() {
+    for (mut age, entity, &unit_id, voxel_pos) in query.iter_mut() {
         age.current += delta_days;
 
         if age.current > age.max {
+            tile_events.send(TileEvent {
+                hex: voxel_pos.hex,
+                kind: TileEventKind::OrganismDied {
+                    organism_id: OrganismId::Unit(unit_id),
+                    cause: DeathCause::OldAge,
+                },
+            });
             commands.entity(entity).despawn_recursive();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_curves_do_nothing() {
+        let curve = AgeCurve::default();
+
+        assert_eq!(curve.multiplier(0.), 1.);
+        assert_eq!(curve.multiplier(0.5), 1.);
+        assert_eq!(curve.multiplier(1.), 1.);
+    }
+
+    #[test]
+    fn curves_interpolate_between_points() {
+        // Points are sorted when the curve is created
+        let curve = AgeCurve::new([(0.75, 1.), (0.25, 1.), (0., 0.5), (1., 0.5)]);
+
+        assert_eq!(curve.multiplier(0.), 0.5);
+        assert_eq!(curve.multiplier(0.125), 0.75);
+        assert_eq!(curve.multiplier(0.5), 1.);
+        assert_eq!(curve.multiplier(0.875), 0.75);
+        assert_eq!(curve.multiplier(1.), 0.5);
+    }
+
+    #[test]
+    fn deserialized_curves_are_sorted() {
+        let curve: AgeCurve =
+            serde_json::from_str(r#"{"points": [[1.0, 2.0], [0.0, 0.5]]}"#).unwrap();
+
+        assert_eq!(curve, AgeCurve::new([(0., 0.5), (1., 2.)]));
+        assert_eq!(curve.multiplier(0.5), 1.25);
+    }
+
+    #[test]
+    fn curves_are_constant_past_their_ends() {
+        let curve = AgeCurve::new([(0.25, 0.5), (0.75, 2.)]);
+
+        assert_eq!(curve.multiplier(0.), 0.5);
+        assert_eq!(curve.multiplier(1.), 2.);
+    }
+
+    #[test]
+    fn fraction_of_life_is_bounded() {
+        let mut age = Age::newborn(Days(4.));
+        assert_eq!(age.fraction_of_life(), 0.);
+
+        age.current = Days(1.);
+        assert_eq!(age.fraction_of_life(), 0.25);
+
+        age.current = Days(5.);
+        assert_eq!(age.fraction_of_life(), 1.);
+    }
+}
@@ -8,12 +8,14 @@ use crate::{
     crafting::item_tags::ItemKind,
     items::item_manifest::{Item, ItemManifest},
     organisms::{
+        dormancy::Dormant,
         energy::{Energy, EnergyPool},
         oxygen::OxygenPool,
     },
 };
 
 use super::{
+    actions::{CurrentAction, UnitAction},
     goals::Goal,
     item_interaction::UnitInventory,
     unit_manifest::{Unit, UnitManifest},
@@ -77,7 +79,7 @@ impl Diet {
     }
 
     /// The kind of item that this unit must consume.
-    pub(super) fn item_kind(&self) -> ItemKind {
+    pub(crate) fn item_kind(&self) -> ItemKind {
         self.item_kind
     }
 
@@ -117,7 +119,7 @@ pub(super) fn check_for_hunger(
     for (mut goal, energy_pool, unit_id, unit_inventory) in unit_query.iter_mut() {
         if energy_pool.is_hungry() {
             // Make sure to put down any item we're holding before eating
-            if let Some(item) = unit_inventory.held_item {
+            if let Some(item) = unit_inventory.held_item() {
                 if *goal == Goal::Store(ItemKind::Single(item)) {
                     continue;
                 };
@@ -132,3 +134,16 @@ pub(super) fn check_for_hunger(
         }
     }
 }
+
+/// Dormant units stop whatever they are doing and wait for conditions to improve.
+///
+/// Starving units are the exception: they keep looking for food, as that is the only way to recover.
+pub(super) fn rest_while_dormant(
+    mut unit_query: Query<(&mut CurrentAction, &Dormant), With<Id<Unit>>>,
+) {
+    for (mut current_action, dormant) in unit_query.iter_mut() {
+        if dormant.blocks_actions() && !matches!(current_action.action(), UnitAction::Idle) {
+            *current_action = CurrentAction::idle();
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Tracks the population of units, and uses this information to regulate reproduction.
+//!
+//! Without some form of feedback, unit populations tend to boom and then crash as food runs out.
+//! The [`Census`] records who is alive, and who was born or died recently,
+//! while the [`PopulationTargets`] allow players to cap the population of each species.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    ecs::query::Has,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::recipe::{Recipe, RecipeManifest},
+    items::item_manifest::ItemManifest,
+    organisms::OrganismId,
+    simulation::time::{Days, InGameTime},
+};
+
+use super::{
+    age::Age,
+    unit_manifest::{Unit, UnitManifest},
+};
+
+/// The broad stage of life that a unit is in, based on its [`Age`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LifeStage {
+    /// A young unit.
+    Juvenile,
+    /// A fully grown unit.
+    Adult,
+    /// A unit that is nearing the end of its life.
+    Elder,
+}
+
+impl LifeStage {
+    /// The fraction of a unit's maximum age that it spends as a [`LifeStage::Juvenile`].
+    const JUVENILE_FRACTION: f32 = 0.2;
+
+    /// The fraction of a unit's maximum age after which it is considered a [`LifeStage::Elder`].
+    const ELDER_FRACTION: f32 = 0.8;
+
+    /// Determines the life stage of a unit of the provided `age`.
+    pub fn from_age(age: &Age) -> Self {
+        if age.max() <= Days::ZERO {
+            return LifeStage::Adult;
+        }
+
+        let fraction = age.current().0 / age.max().0;
+
+        if fraction < Self::JUVENILE_FRACTION {
+            LifeStage::Juvenile
+        } else if fraction < Self::ELDER_FRACTION {
+            LifeStage::Adult
+        } else {
+            LifeStage::Elder
+        }
+    }
+}
+
+/// The births and deaths of each species over the course of a single in-game day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VitalRecord {
+    /// The number of units born, by species.
+    births: HashMap<Id<Unit>, u32>,
+    /// The number of units that died, by species.
+    deaths: HashMap<Id<Unit>, u32>,
+}
+
+impl VitalRecord {
+    /// The number of units of the given species born on this day.
+    pub fn births(&self, unit_id: Id<Unit>) -> u32 {
+        self.births.get(&unit_id).copied().unwrap_or_default()
+    }
+
+    /// The number of units of the given species that died on this day.
+    pub fn deaths(&self, unit_id: Id<Unit>) -> u32 {
+        self.deaths.get(&unit_id).copied().unwrap_or_default()
+    }
+
+    /// Returns a human-readable summary of the births and deaths of each species.
+    pub fn display(&self, unit_manifest: &UnitManifest) -> String {
+        let species: HashSet<Id<Unit>> = self
+            .births
+            .keys()
+            .chain(self.deaths.keys())
+            .copied()
+            .collect();
+
+        let mut lines: Vec<String> = species
+            .into_iter()
+            .map(|unit_id| {
+                format!(
+                    "{}: {} born, {} died",
+                    unit_manifest.name(unit_id),
+                    self.births(unit_id),
+                    self.deaths(unit_id)
+                )
+            })
+            .collect();
+        lines.sort();
+
+        lines.join("\n")
+    }
+}
+
+/// Tracks the population of units.
+#[derive(Debug, Resource, Default)]
+pub struct Census {
+    /// The species and life stage of each living unit.
+    living: HashMap<Entity, (Id<Unit>, LifeStage)>,
+    /// The births and deaths recorded so far today.
+    today: VitalRecord,
+    /// The in-game day that `today` corresponds to.
+    current_day: u64,
+    /// The births and deaths of previous days, with the most recent day at the back.
+    history: VecDeque<VitalRecord>,
+}
+
+impl Census {
+    /// The number of previous days whose births and deaths are retained.
+    const HISTORY_LENGTH: usize = 10;
+
+    /// The total number of living units of any kind.
+    pub fn total_population(&self) -> usize {
+        self.living.len()
+    }
+
+    /// The number of living units of the given species.
+    pub fn population(&self, unit_id: Id<Unit>) -> usize {
+        self.living
+            .values()
+            .filter(|(species, _)| *species == unit_id)
+            .count()
+    }
+
+    /// The number of living units of the given species in the given [`LifeStage`].
+    pub fn population_by_stage(&self, unit_id: Id<Unit>, life_stage: LifeStage) -> usize {
+        self.living
+            .values()
+            .filter(|(species, stage)| *species == unit_id && *stage == life_stage)
+            .count()
+    }
+
+    /// All species that currently have at least one living member.
+    pub fn species(&self) -> HashSet<Id<Unit>> {
+        self.living.values().map(|(species, _)| *species).collect()
+    }
+
+    /// The births and deaths recorded on the most recent complete day, if any.
+    pub fn yesterday(&self) -> Option<&VitalRecord> {
+        self.history.back()
+    }
+
+    /// The births and deaths recorded on previous days, from oldest to newest.
+    pub fn history(&self) -> impl Iterator<Item = &VitalRecord> {
+        self.history.iter()
+    }
+
+    /// The average number of births per day for the given species, over the recorded history.
+    ///
+    /// Returns 0 if no full days have been recorded yet.
+    pub fn birth_rate(&self, unit_id: Id<Unit>) -> f32 {
+        if self.history.is_empty() {
+            return 0.;
+        }
+
+        let total: u32 = self.history.iter().map(|day| day.births(unit_id)).sum();
+        total as f32 / self.history.len() as f32
+    }
+
+    /// The average number of deaths per day for the given species, over the recorded history.
+    ///
+    /// Returns 0 if no full days have been recorded yet.
+    pub fn death_rate(&self, unit_id: Id<Unit>) -> f32 {
+        if self.history.is_empty() {
+            return 0.;
+        }
+
+        let total: u32 = self.history.iter().map(|day| day.deaths(unit_id)).sum();
+        total as f32 / self.history.len() as f32
+    }
+
+    /// Records that a unit is alive.
+    ///
+    /// If the unit was not previously known and `newborn` is true, this counts as a birth.
+    fn record_living(
+        &mut self,
+        entity: Entity,
+        unit_id: Id<Unit>,
+        life_stage: LifeStage,
+        newborn: bool,
+    ) {
+        if self.living.insert(entity, (unit_id, life_stage)).is_none() && newborn {
+            *self.today.births.entry(unit_id).or_default() += 1;
+        }
+    }
+
+    /// Updates the life stage of a unit that is already known to be alive.
+    fn record_life_stage(&mut self, entity: Entity, life_stage: LifeStage) {
+        if let Some((_, stage)) = self.living.get_mut(&entity) {
+            *stage = life_stage;
+        }
+    }
+
+    /// Records that a unit has died or otherwise been removed from the world.
+    fn record_death(&mut self, entity: Entity) {
+        if let Some((unit_id, _)) = self.living.remove(&entity) {
+            *self.today.deaths.entry(unit_id).or_default() += 1;
+        }
+    }
+
+    /// Moves the records for the current day into the history if a new day has begun.
+    ///
+    /// Returns `true` if the day changed.
+    fn advance_to_day(&mut self, day: u64) -> bool {
+        if day == self.current_day {
+            return false;
+        }
+
+        self.current_day = day;
+        self.history.push_back(std::mem::take(&mut self.today));
+        while self.history.len() > Self::HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+
+        true
+    }
+
+    /// Returns a human-readable summary of the population of each species.
+    pub fn display(&self, unit_manifest: &UnitManifest) -> String {
+        let mut string = format!("Population: {}", self.total_population());
+
+        for unit_id in self.species() {
+            string += &format!(
+                "\n{}: {} ({} juvenile, {} adult, {} elder), {:.1} births/day, {:.1} deaths/day",
+                unit_manifest.name(unit_id),
+                self.population(unit_id),
+                self.population_by_stage(unit_id, LifeStage::Juvenile),
+                self.population_by_stage(unit_id, LifeStage::Adult),
+                self.population_by_stage(unit_id, LifeStage::Elder),
+                self.birth_rate(unit_id),
+                self.death_rate(unit_id),
+            );
+        }
+
+        string
+    }
+}
+
+/// Marks units that were born or hatched during play, rather than generated with the world.
+///
+/// Units with this marker are counted as births by the [`Census`] when they first appear.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub(crate) struct Newborn;
+
+/// Updates the [`Census`] as units are born, grow up and die.
+///
+/// Births and deaths are tracked as units are added and removed,
+/// while life stages change slowly and are only refreshed once per in-game day.
+pub(super) fn update_census(
+    mut census: ResMut<Census>,
+    in_game_time: Res<InGameTime>,
+    new_unit_query: Query<(Entity, &Id<Unit>, &Age, Has<Newborn>), Added<Id<Unit>>>,
+    unit_query: Query<(Entity, &Age), With<Id<Unit>>>,
+    mut removed_units: RemovedComponents<Id<Unit>>,
+) {
+    let new_day = census.advance_to_day(in_game_time.rounded_elapsed_days());
+
+    for entity in removed_units.read() {
+        census.record_death(entity);
+    }
+
+    for (entity, &unit_id, age, newborn) in new_unit_query.iter() {
+        census.record_living(entity, unit_id, LifeStage::from_age(age), newborn);
+    }
+
+    if new_day {
+        for (entity, age) in unit_query.iter() {
+            census.record_life_stage(entity, LifeStage::from_age(age));
+        }
+    }
+}
+
+/// The desired population of each species, as set by the player.
+///
+/// Recipes that produce reproductive items (such as eggs) for a species
+/// at or above its target population will not start.
+#[derive(Debug, Resource, Default)]
+pub struct PopulationTargets {
+    /// The maximum desired population of each species.
+    ///
+    /// Species without an entry are not limited.
+    targets: HashMap<Id<Unit>, usize>,
+    /// The recipes that are currently blocked due to overpopulation.
+    throttled_recipes: HashSet<Id<Recipe>>,
+}
+
+impl PopulationTargets {
+    /// Sets the target population for the given species.
+    pub fn set_target(&mut self, unit_id: Id<Unit>, target: usize) {
+        self.targets.insert(unit_id, target);
+    }
+
+    /// Removes any population target for the given species.
+    pub fn clear_target(&mut self, unit_id: Id<Unit>) {
+        self.targets.remove(&unit_id);
+    }
+
+    /// The target population of the given species, if any.
+    pub fn target(&self, unit_id: Id<Unit>) -> Option<usize> {
+        self.targets.get(&unit_id).copied()
+    }
+
+    /// Is the population of the given species at or above its target?
+    pub fn is_over_target(&self, unit_id: Id<Unit>, census: &Census) -> bool {
+        match self.target(unit_id) {
+            Some(target) => census.population(unit_id) >= target,
+            None => false,
+        }
+    }
+
+    /// Should crafting of the given recipe be paused to limit population growth?
+    pub fn is_throttled(&self, recipe_id: Id<Recipe>) -> bool {
+        self.throttled_recipes.contains(&recipe_id)
+    }
+}
+
+/// Determines which recipes should be paused because they would produce overpopulated species.
+pub(super) fn throttle_reproduction(
+    mut population_targets: ResMut<PopulationTargets>,
+    census: Res<Census>,
+    recipe_manifest: Res<RecipeManifest>,
+    item_manifest: Res<ItemManifest>,
+) {
+    let mut throttled_recipes = HashSet::new();
+
+    for (&recipe_id, recipe_data) in recipe_manifest.data_map() {
+        for item_id in recipe_data.outputs.item_ids() {
+            if let Some(OrganismId::Unit(unit_id)) = item_manifest.get(item_id).seed {
+                if population_targets.is_over_target(unit_id, &census) {
+                    throttled_recipes.insert(recipe_id);
+                }
+            }
+        }
+
+        if let Some(unit_id) = recipe_data.hatches {
+            if population_targets.is_over_target(unit_id, &census) {
+                throttled_recipes.insert(recipe_id);
+            }
+        }
+    }
+
+    population_targets.throttled_recipes = throttled_recipes;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The species used in these tests.
+    fn crab() -> Id<Unit> {
+        Id::from_name("crab".to_string())
+    }
+
+    #[test]
+    fn life_stage_follows_age() {
+        let mut age = Age::newborn(Days(10.));
+        assert_eq!(LifeStage::from_age(&age), LifeStage::Juvenile);
+
+        age = Age::randomized(&mut rand::thread_rng(), Days(0.));
+        assert_eq!(LifeStage::from_age(&age), LifeStage::Adult);
+    }
+
+    #[test]
+    fn births_and_deaths_are_recorded() {
+        let mut census = Census::default();
+        let entity = Entity::from_raw(0);
+
+        census.record_living(entity, crab(), LifeStage::Juvenile, true);
+        // Seeing the same unit again is not a new birth
+        census.record_living(entity, crab(), LifeStage::Adult, true);
+        assert_eq!(census.population(crab()), 1);
+        assert_eq!(census.population_by_stage(crab(), LifeStage::Adult), 1);
+
+        census.record_death(entity);
+        assert_eq!(census.total_population(), 0);
+
+        census.advance_to_day(1);
+        assert_eq!(census.birth_rate(crab()), 1.);
+        assert_eq!(census.death_rate(crab()), 1.);
+    }
+
+    #[test]
+    fn only_newborn_units_count_as_births() {
+        let mut world = World::new();
+        world.init_resource::<Census>();
+        world.init_resource::<InGameTime>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(update_census);
+
+        // Units generated with the world may happen to be exactly zero days old
+        world.spawn((crab(), Age::newborn(Days(10.))));
+        schedule.run(&mut world);
+
+        let hatchling = world.spawn((crab(), Age::newborn(Days(10.)), Newborn)).id();
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+
+        let census = world.resource::<Census>();
+        assert_eq!(census.population(crab()), 2);
+        assert_eq!(census.today.births(crab()), 1);
+
+        world.despawn(hatchling);
+        schedule.run(&mut world);
+
+        let census = world.resource::<Census>();
+        assert_eq!(census.population(crab()), 1);
+        assert_eq!(census.today.deaths(crab()), 1);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut census = Census::default();
+        for day in 1..=(Census::HISTORY_LENGTH as u64 * 2) {
+            census.advance_to_day(day);
+        }
+
+        assert_eq!(census.history().count(), Census::HISTORY_LENGTH);
+    }
+
+    #[test]
+    fn population_targets_compare_to_census() {
+        let mut census = Census::default();
+        let mut targets = PopulationTargets::default();
+        census.record_living(Entity::from_raw(0), crab(), LifeStage::Adult, false);
+
+        assert!(!targets.is_over_target(crab(), &census));
+        targets.set_target(crab(), 1);
+        assert!(targets.is_over_target(crab(), &census));
+        targets.clear_target(crab());
+        assert!(!targets.is_over_target(crab(), &census));
+    }
+}
@@ -1,25 +1,30 @@
 //! What are units attempting to achieve?
 
 use bevy::prelude::*;
+use bevy::utils::Instant;
 use rand::distributions::WeightedIndex;
 use rand::prelude::Distribution;
-use rand::rngs::ThreadRng;
-use rand::thread_rng;
+use rand::rngs::SmallRng;
+use serde::{Deserialize, Serialize};
 
 use crate::asset_management::manifest::Id;
 use crate::construction::ghosts::WorkplaceId;
 use crate::crafting::item_tags::ItemKind;
-use crate::geometry::VoxelPos;
+use crate::factions::Faction;
+use crate::geometry::{MapGeometry, VoxelPos};
 use crate::items::item_manifest::ItemManifest;
-use crate::signals::{SignalType, Signals};
+use crate::organisms::energy::EnergyPool;
+use crate::signals::{SignalChannels, SignalType};
+use crate::simulation::rng::SystemRng;
 use crate::structures::structure_manifest::{Structure, StructureManifest};
 use crate::terrain::terrain_manifest::TerrainManifest;
 
 use super::actions::{DeliveryMode, Purpose};
 use super::impatience::ImpatiencePool;
 use super::item_interaction::UnitInventory;
-use super::unit_manifest::{Unit, UnitManifest};
-use super::WanderingBehavior;
+use super::perception::LocalPerception;
+use super::scheduling::{AiBudget, ThinkingQueue};
+use super::unit_manifest::{Unit, UnitData, UnitManifest};
 
 /// A unit's current goals.
 ///
@@ -62,11 +67,13 @@ pub(crate) enum Goal {
     Breathe,
     /// Trying to avoid a specific unit.
     Avoid(Id<Unit>),
+    /// Attempting to recover from fatigue, ideally in a shelter.
+    Rest,
 }
 
 /// The data-less version of [`Goal`].
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-pub(crate) enum GoalKind {
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GoalKind {
     /// Attempting to find something useful to do.
     Wander,
     /// Attempting to pick up an object, so it can be taken away from a structure that actively rejects it.
@@ -87,6 +94,25 @@ pub(crate) enum GoalKind {
     Avoid,
     /// Trying to get to oxygen.
     Breathe,
+    /// Attempting to recover from fatigue, ideally in a shelter.
+    Rest,
+}
+
+impl GoalKind {
+    /// Does pursuing this goal cost energy beyond simply walking around?
+    ///
+    /// Hauling adds to the load that units carry, while working and demolition are paid for on site.
+    pub(crate) fn is_strenuous(&self) -> bool {
+        matches!(
+            self,
+            GoalKind::Remove
+                | GoalKind::Fetch
+                | GoalKind::Deliver
+                | GoalKind::Store
+                | GoalKind::Work
+                | GoalKind::Demolish
+        )
+    }
 }
 
 impl From<&Goal> for GoalKind {
@@ -102,6 +128,7 @@ impl From<&Goal> for GoalKind {
             Goal::Eat(_) => GoalKind::Eat,
             Goal::Avoid(_) => GoalKind::Avoid,
             Goal::Breathe => GoalKind::Breathe,
+            Goal::Rest => GoalKind::Rest,
         }
     }
 }
@@ -129,6 +156,14 @@ impl TryFrom<SignalType> for Goal {
             SignalType::Contains(_) => Err(()),
             SignalType::Stores(_) => Err(()),
             SignalType::Unit(unit) => Ok(Goal::Avoid(unit)),
+            // Units only seek out shelter once they're tired
+            SignalType::Shelter => Err(()),
+            // Idle units drift towards work, but only commit to the specific signals they find there
+            SignalType::WorkNeeded => Err(()),
+            // Crowds only push wandering units away
+            SignalType::Crowding => Err(()),
+            // Custom signals are only followed by the behaviors that were written for them
+            SignalType::Custom(_) => Err(()),
         }
     }
 }
@@ -147,6 +182,7 @@ impl Goal {
             Goal::Eat(_) => Some(DeliveryMode::PickUp),
             Goal::Avoid(_) => None,
             Goal::Breathe => None,
+            Goal::Rest => None,
         }
     }
 
@@ -162,6 +198,7 @@ impl Goal {
             Goal::Demolish(_) => Purpose::Intrinsic,
             Goal::Eat(_) => Purpose::Instrumental,
             Goal::Breathe => Purpose::Instrumental,
+            Goal::Rest => Purpose::Instrumental,
             Goal::Avoid(_) => Purpose::Instrumental,
         }
     }
@@ -195,11 +232,15 @@ impl Goal {
             Goal::Eat(item_kind) => format!("Eat {}", item_manifest.name_of_kind(*item_kind)),
             Goal::Avoid(unit) => format!("Avoid {}", unit_manifest.name(*unit)),
             Goal::Breathe => "Breathe".to_string(),
+            Goal::Rest => "Rest".to_string(),
         }
     }
 }
 
 /// Choose this unit's new goal if needed
+///
+/// Only the units in the [`ThinkingQueue`] are considered.
+/// Each unit decides based on its own [`LocalPerception`], rather than the global state of the world.
 pub(super) fn choose_goal(
     mut units_query: Query<(
         &VoxelPos,
@@ -207,20 +248,39 @@ pub(super) fn choose_goal(
         &mut ImpatiencePool,
         &UnitInventory,
         &Id<Unit>,
+        &Faction,
+        &EnergyPool,
     )>,
     unit_manifest: Res<UnitManifest>,
     item_manifest: Res<ItemManifest>,
-    signals: Res<Signals>,
+    signal_channels: Res<SignalChannels>,
+    map_geometry: Res<MapGeometry>,
+    ai_budget: Res<AiBudget>,
+    mut thinking_queue: ResMut<ThinkingQueue>,
+    mut system_rng: SystemRng,
 ) {
-    let rng = &mut thread_rng();
+    let rng = system_rng.get("choose_goal");
+    let started = Instant::now();
 
-    for (&voxel_pos, mut goal, mut impatience_pool, unit_inventory, &unit_id) in
-        units_query.iter_mut()
+    let mut units_iter = units_query.iter_many_mut(thinking_queue.units());
+    while let Some((
+        &voxel_pos,
+        mut goal,
+        mut impatience_pool,
+        unit_inventory,
+        &unit_id,
+        &faction,
+        energy_pool,
+    )) = units_iter.fetch_next()
     {
+        if thinking_queue.out_of_time(started, &ai_budget) {
+            break;
+        }
+
         // If we're out of patience, give up and choose a new goal
         if impatience_pool.is_full() {
             // If you're holding something, try to put it away nicely
-            *goal = if let Some(held_item) = unit_inventory.held_item {
+            *goal = if let Some(held_item) = unit_inventory.held_item() {
                 match &*goal {
                     Goal::Store(item_kind) | Goal::Deliver(item_kind) => {
                         // If we ran out of patience while trying to store something, we should just give up and drop it
@@ -246,37 +306,53 @@ pub(super) fn choose_goal(
         }
 
         if let Goal::Wander { remaining_actions } = *goal {
-            let wandering_behavior = &unit_manifest.get(unit_id).wandering_behavior;
+            let unit_data = unit_manifest.get(unit_id);
+            let perception = LocalPerception::sense(
+                signal_channels.get(faction),
+                voxel_pos,
+                unit_data.sensing_radius,
+                &map_geometry,
+            );
             *goal = compute_new_goal(
                 unit_id,
                 remaining_actions,
-                voxel_pos,
-                wandering_behavior,
+                unit_data,
+                energy_pool,
                 rng,
-                &signals,
+                &perception,
             );
 
             // Reset impatience when we choose a new goal
             impatience_pool.reset();
         }
     }
+
+    thinking_queue.record_time(started);
 }
 
+/// The lowest weight that a strenuous goal can be given, relative to its signal strength.
+///
+/// This ensures that exhausted units still pitch in when nothing else needs doing.
+const MIN_STRENUOUS_GOAL_WEIGHT: f32 = 0.1;
+
 /// Pick a new goal when wandering.
 ///
 // By default, goals are reset to wandering when completed.
 /// If anything fails, just keep wandering for now.
+///
+/// Strenuous goals are weighted by how much energy the unit has left,
+/// so that tired units favor goals that let them recover.
 fn compute_new_goal(
     unit_id: Id<Unit>,
     mut remaining_actions: Option<u16>,
-    voxel_pos: VoxelPos,
-    wandering_behavior: &WanderingBehavior,
-    rng: &mut ThreadRng,
-    signals: &Signals,
+    unit_data: &UnitData,
+    energy_pool: &EnergyPool,
+    rng: &mut SmallRng,
+    perception: &LocalPerception,
 ) -> Goal {
     // When we first get a wandering goal, pick a number of actions to take before picking a new goal.
     if remaining_actions.is_none() {
-        let number_of_actions = wandering_behavior.sample(rng);
+        let number_of_actions = unit_data.wandering_behavior.sample(rng);
         remaining_actions = Some(number_of_actions);
     }
 
@@ -289,9 +365,8 @@ fn compute_new_goal(
         }
     }
 
-    // Pick a new goal based on the signals at this tile
-    let current_signals = signals.all_signals_at_position(voxel_pos);
-    let mut goal_relevant_signals = current_signals.goal_relevant_signals();
+    // Pick a new goal based on the signals that this unit can sense
+    let mut goal_relevant_signals = perception.goal_relevant_signals();
 
     // Only try to avoid units of the same type
     goal_relevant_signals.retain(|(signal_type, _)| {
@@ -302,14 +377,27 @@ fn compute_new_goal(
         }
     });
 
-    if let Ok(goal_weights) = WeightedIndex::new(
-        goal_relevant_signals
-            .iter()
-            .map(|(_type, strength)| strength.value()),
-    ) {
+    // Only pursue goals that this species is capable of
+    goal_relevant_signals.retain(|(signal_type, _)| match Goal::try_from(*signal_type) {
+        Ok(goal) => unit_data.allows_goal(GoalKind::from(&goal)),
+        Err(()) => false,
+    });
+
+    let energy_weight = energy_pool.fraction_full().max(MIN_STRENUOUS_GOAL_WEIGHT);
+
+    if let Ok(goal_weights) =
+        WeightedIndex::new(goal_relevant_signals.iter().map(|(signal_type, strength)| {
+            // Every goal-relevant signal type converts to a goal, as checked above
+            let goal = Goal::try_from(*signal_type).unwrap();
+            match GoalKind::from(&goal).is_strenuous() {
+                true => strength.value() * energy_weight,
+                false => strength.value(),
+            }
+        }))
+    {
         let selected_goal_index = goal_weights.sample(rng);
         if let Some(selected_signal) = goal_relevant_signals.get(selected_goal_index) {
-            let selected_signal_type = *selected_signal.0;
+            let selected_signal_type = selected_signal.0;
             selected_signal_type.try_into().unwrap()
         } else {
             Goal::Wander { remaining_actions }
@@ -8,19 +8,83 @@ use crate::{
 };
 
 /// The item(s) that a unit is carrying.
-#[derive(Component, Default, Clone, Debug, Deref, DerefMut)]
+#[derive(Component, Default, Clone, Debug)]
 pub(crate) struct UnitInventory {
-    /// The single item the unit is currently holding
-    pub(crate) held_item: Option<Id<Item>>,
+    /// The type of item the unit is currently holding
+    held_item: Option<Id<Item>>,
+    /// How many of the held item the unit is carrying
+    count: u32,
 }
 
 impl UnitInventory {
+    /// The type of item that the unit is carrying, if any.
+    pub(crate) fn held_item(&self) -> Option<Id<Item>> {
+        self.held_item
+    }
+
+    /// The number of items that the unit is carrying.
+    pub(crate) fn count(&self) -> u32 {
+        match self.held_item {
+            Some(_) => self.count,
+            None => 0,
+        }
+    }
+
+    /// Replaces the contents of this inventory with `count` items of type `item_id`.
+    ///
+    /// Holding zero items empties the inventory.
+    pub(crate) fn hold(&mut self, item_id: Id<Item>, count: u32) {
+        if count == 0 {
+            self.clear();
+        } else {
+            self.held_item = Some(item_id);
+            self.count = count;
+        }
+    }
+
+    /// Removes up to `count` items from this inventory, returning the number actually removed.
+    pub(crate) fn release(&mut self, count: u32) -> u32 {
+        let released = count.min(self.count());
+        self.count -= released;
+        if self.count == 0 {
+            self.held_item = None;
+        }
+        released
+    }
+
+    /// Empties this inventory.
+    pub(crate) fn clear(&mut self) {
+        self.held_item = None;
+        self.count = 0;
+    }
+
     /// Pretty foramtting for this type.
     pub(crate) fn display(&self, item_manifest: &ItemManifest) -> String {
-        if let Some(item) = self.held_item {
-            item_manifest.name(item).to_string()
-        } else {
-            "Nothing".to_string()
+        match self.held_item {
+            Some(item) if self.count > 1 => {
+                format!("{} ({})", item_manifest.name(item), self.count)
+            }
+            Some(item) => item_manifest.name(item).to_string(),
+            None => "Nothing".to_string(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releasing_items_empties_inventory() {
+        let item_id = Id::from_name("test".to_string());
+        let mut unit_inventory = UnitInventory::default();
+        unit_inventory.hold(item_id, 3);
+        assert_eq!(unit_inventory.count(), 3);
+
+        assert_eq!(unit_inventory.release(2), 2);
+        assert_eq!(unit_inventory.held_item(), Some(item_id));
+        assert_eq!(unit_inventory.release(5), 1);
+        assert_eq!(unit_inventory.held_item(), None);
+        assert_eq!(unit_inventory.count(), 0);
+    }
+}
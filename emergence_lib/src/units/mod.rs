@@ -5,22 +5,31 @@ use crate::{
         manifest::{plugin::ManifestPlugin, Id, Manifest},
         AssetCollectionExt,
     },
+    factions::Faction,
     geometry::{Facing, VoxelPos},
-    player_interaction::InteractionSystem,
+    player_interaction::{selection::ObjectInteraction, InteractionSystem},
     signals::{Emitter, SignalStrength, SignalType},
     simulation::SimulationSet,
 };
 use bevy::prelude::*;
 use bevy_mod_raycast::deferred::RaycastMesh;
-use rand::{distributions::WeightedIndex, prelude::Distribution, rngs::ThreadRng, Rng};
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
 use serde::{Deserialize, Serialize};
 
 use self::{
     actions::CurrentAction,
     age::Age,
+    census::{Census, PopulationTargets},
     goals::Goal,
     impatience::ImpatiencePool,
     item_interaction::UnitInventory,
+    movement::MovementMode,
+    occupancy::TileOccupancy,
+    pathfinding::WalkingSpeeds,
+    rest::{Fatigue, ShelterCapacity},
+    scheduling::{AiBudget, LastThought, ThinkingQueue},
+    stats::UnitStats,
+    traffic::TrafficHeatmap,
     unit_assets::UnitHandles,
     unit_manifest::{RawUnitManifest, Unit, UnitData},
 };
@@ -30,9 +39,18 @@ use crate::organisms::OrganismBundle;
 pub(crate) mod actions;
 pub mod age;
 pub mod basic_needs;
-pub(crate) mod goals;
+pub mod census;
+pub mod goals;
 pub(crate) mod impatience;
 pub(crate) mod item_interaction;
+pub mod movement;
+pub mod occupancy;
+pub mod pathfinding;
+pub(crate) mod perception;
+pub mod rest;
+pub mod scheduling;
+pub mod stats;
+pub mod traffic;
 pub(crate) mod unit_assets;
 pub mod unit_manifest;
 
@@ -55,7 +73,7 @@ impl Default for WanderingBehavior {
 
 impl WanderingBehavior {
     /// Randomly choose the number of actions to take while wandering.
-    fn sample(&self, rng: &mut ThreadRng) -> u16 {
+    fn sample(&self, rng: &mut impl Rng) -> u16 {
         let weights = self.wander_durations.iter().map(|(_, weight)| *weight);
         let dist = WeightedIndex::new(weights).unwrap();
         let index = dist.sample(rng);
@@ -80,8 +98,12 @@ pub(crate) struct UnitBundle {
     voxel_pos: VoxelPos,
     /// The direction that the unit is facing.
     facing: Facing,
+    /// How the unit gets around.
+    movement_mode: MovementMode,
     /// What is the unit working towards.
     current_goal: Goal,
+    /// When this unit last had a chance to think.
+    last_thought: LastThought,
     /// How frustrated this unit is.
     ///
     /// When full, the current goal will be abandoned.
@@ -94,10 +116,18 @@ pub(crate) struct UnitBundle {
     emitter: Emitter,
     /// The current and max age of the unit.
     age: Age,
+    /// The unit's physical abilities, which depend on its age.
+    stats: UnitStats,
+    /// How tired the unit is.
+    fatigue: Fatigue,
+    /// The colony that the unit belongs to.
+    faction: Faction,
     /// Organism data
     organism_bundle: OrganismBundle,
     /// Makes units pickable
     raycast_mesh: RaycastMesh<Unit>,
+    /// How is this unit being interacted with
+    object_interaction: ObjectInteraction,
     /// The mesh used for raycasting
     mesh: Handle<Mesh>,
     /// The child scene that contains the gltF model used
@@ -111,6 +141,25 @@ impl UnitBundle {
     /// and increase the frequency at which units attempt to flee crowding.
     const UNIT_EMITTER_STRENGTH: f32 = 0.5;
 
+    /// The signals given off by a unit of type `unit_id`.
+    ///
+    /// Units of species that dislike crowds also emit a [`SignalType::Crowding`] signal.
+    fn emitter(unit_id: Id<Unit>, unit_data: &UnitData) -> Emitter {
+        let mut signals = vec![(
+            SignalType::Unit(unit_id),
+            SignalStrength::new(Self::UNIT_EMITTER_STRENGTH),
+        )];
+
+        if unit_data.crowding_repulsion > 0. {
+            signals.push((
+                SignalType::Crowding,
+                SignalStrength::new(unit_data.crowding_repulsion),
+            ));
+        }
+
+        Emitter { signals }
+    }
+
     /// Initializes a new unit.
     ///
     /// It will be just born, and full.
@@ -118,6 +167,7 @@ impl UnitBundle {
         unit_id: Id<Unit>,
         voxel_pos: VoxelPos,
         unit_data: UnitData,
+        faction: Faction,
         unit_handles: &UnitHandles,
     ) -> Self {
         let scene_handle = unit_handles.scenes.get(&unit_id).unwrap();
@@ -126,22 +176,23 @@ impl UnitBundle {
             unit_id,
             voxel_pos,
             facing: Facing::default(),
+            movement_mode: unit_data.movement_mode,
             current_goal: Goal::default(),
+            last_thought: LastThought::default(),
             impatience: ImpatiencePool::new(unit_data.max_impatience),
             current_action: CurrentAction::default(),
             held_item: UnitInventory::default(),
-            emitter: Emitter {
-                signals: vec![(
-                    SignalType::Unit(unit_id),
-                    SignalStrength::new(Self::UNIT_EMITTER_STRENGTH),
-                )],
-            },
+            emitter: Self::emitter(unit_id, &unit_data),
+            stats: UnitStats::new(&unit_data, &Age::newborn(unit_data.max_age)),
             age: Age::newborn(unit_data.max_age),
+            fatigue: Fatigue::default(),
+            faction,
             organism_bundle: OrganismBundle::new(
                 unit_data.organism_variety.energy_pool,
                 unit_data.organism_variety.lifecycle,
             ),
             raycast_mesh: RaycastMesh::default(),
+            object_interaction: ObjectInteraction::None,
             mesh: unit_handles.picking_mesh.clone_weak(),
             scene_bundle: SceneBundle {
                 scene: scene_handle.clone_weak(),
@@ -158,6 +209,7 @@ impl UnitBundle {
         unit_id: Id<Unit>,
         voxel_pos: VoxelPos,
         unit_data: UnitData,
+        faction: Faction,
         unit_handles: &UnitHandles,
         rng: &mut impl Rng,
     ) -> Self {
@@ -170,19 +222,20 @@ impl UnitBundle {
             unit_id,
             voxel_pos,
             facing: Facing::default(),
+            movement_mode: unit_data.movement_mode,
             current_goal: Goal::default(),
+            last_thought: LastThought::default(),
             impatience: ImpatiencePool::new(unit_data.max_impatience),
             current_action: CurrentAction::default(),
             held_item: UnitInventory::default(),
-            emitter: Emitter {
-                signals: vec![(
-                    SignalType::Unit(unit_id),
-                    SignalStrength::new(Self::UNIT_EMITTER_STRENGTH),
-                )],
-            },
+            emitter: Self::emitter(unit_id, &unit_data),
+            stats: UnitStats::new(&unit_data, &age),
             age,
+            fatigue: Fatigue::default(),
+            faction,
             organism_bundle: OrganismBundle::new(energy_pool, unit_data.organism_variety.lifecycle),
             raycast_mesh: RaycastMesh::default(),
+            object_interaction: ObjectInteraction::None,
             mesh: unit_handles.picking_mesh.clone_weak(),
             scene_bundle: SceneBundle {
                 scene: scene_handle.clone_weak(),
@@ -197,6 +250,7 @@ impl UnitBundle {
         unit_id: Id<Unit>,
         voxel_pos: VoxelPos,
         unit_data: UnitData,
+        faction: Faction,
         rng: &mut impl Rng,
     ) -> Self {
         let scene_handle = Handle::default();
@@ -208,19 +262,20 @@ impl UnitBundle {
             unit_id,
             voxel_pos,
             facing: Facing::default(),
+            movement_mode: unit_data.movement_mode,
             current_goal: Goal::default(),
+            last_thought: LastThought::default(),
             impatience: ImpatiencePool::new(unit_data.max_impatience),
             current_action: CurrentAction::default(),
             held_item: UnitInventory::default(),
-            emitter: Emitter {
-                signals: vec![(
-                    SignalType::Unit(unit_id),
-                    SignalStrength::new(Self::UNIT_EMITTER_STRENGTH),
-                )],
-            },
+            emitter: Self::emitter(unit_id, &unit_data),
+            stats: UnitStats::new(&unit_data, &age),
             age,
+            fatigue: Fatigue::default(),
+            faction,
             organism_bundle: OrganismBundle::new(energy_pool, unit_data.organism_variety.lifecycle),
             raycast_mesh: RaycastMesh::default(),
+            object_interaction: ObjectInteraction::None,
             mesh: Handle::default(),
             scene_bundle: SceneBundle {
                 scene: scene_handle.clone_weak(),
@@ -250,10 +305,20 @@ impl Plugin for UnitsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ManifestPlugin::<RawUnitManifest>::new())
             .add_asset_collection::<UnitHandles>()
+            .init_resource::<Census>()
+            .init_resource::<PopulationTargets>()
+            .init_resource::<TileOccupancy>()
+            .init_resource::<ShelterCapacity>()
+            .init_resource::<AiBudget>()
+            .init_resource::<ThinkingQueue>()
+            .init_resource::<TrafficHeatmap>()
+            .init_resource::<WalkingSpeeds>()
+            .add_systems(Update, pathfinding::update_walking_speeds)
             .add_systems(
                 FixedUpdate,
                 (
                     actions::advance_action_timer.in_set(UnitSystem::AdvanceTimers),
+                    occupancy::count_tile_occupancy.before(UnitSystem::Act),
                     actions::start_actions
                         .in_set(UnitSystem::Act)
                         .before(actions::finish_actions),
@@ -263,21 +328,79 @@ impl Plugin for UnitsPlugin {
                         // This must occur after MarkedForDemolition is added,
                         // or we'll get a panic due to inserting a component on a despawned entity
                         .after(InteractionSystem::ManagePreviews),
+                    scheduling::schedule_thinking
+                        .after(UnitSystem::Act)
+                        .before(UnitSystem::ChooseGoal),
                     goals::choose_goal.in_set(UnitSystem::ChooseGoal),
                     actions::choose_actions
                         .in_set(UnitSystem::ChooseNewAction)
                         .after(UnitSystem::Act)
                         .after(UnitSystem::ChooseGoal),
+                    rest::check_for_rest
+                        .before(UnitSystem::ChooseNewAction)
+                        .after(UnitSystem::ChooseGoal),
+                    // Hunger is more urgent than rest, so it should overwrite
                     basic_needs::check_for_hunger
+                        .after(rest::check_for_rest)
                         // Avoid a delay
                         .before(UnitSystem::ChooseNewAction)
                         // Make sure to overwrite any existing goal
                         .after(UnitSystem::ChooseGoal),
                     // Oxygen is more important than hunger, so it should overwrite
                     basic_needs::check_for_oxygen.after(basic_needs::check_for_hunger),
+                    // Dormancy overrides any other action
+                    basic_needs::rest_while_dormant.after(UnitSystem::ChooseNewAction),
+                    rest::manage_fatigue.after(UnitSystem::AdvanceTimers),
+                    rest::release_shelter_slots.before(rest::set_shelter_emitters),
+                    rest::set_shelter_emitters,
                     age::aging,
+                    stats::update_unit_stats.after(age::aging),
+                    census::update_census.before(age::aging),
+                    census::throttle_reproduction.after(census::update_census),
+                    rest::update_shelter_capacity.after(census::update_census),
                 )
                     .in_set(SimulationSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (traffic::decay_traffic, traffic::record_traffic)
+                    .chain()
+                    .after(UnitSystem::Act)
+                    .in_set(SimulationSet),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::Signals;
+
+    #[test]
+    fn crowds_of_shipped_units_repel_wanderers() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../emergence_game/assets/manifests/base_game.unit_manifest.json"
+        );
+        let json = std::fs::read_to_string(path).unwrap();
+        let raw_unit_manifest: RawUnitManifest = serde_json::from_str(&json).unwrap();
+
+        for (name, raw_unit_data) in raw_unit_manifest.unit_types {
+            let unit_data = UnitData::from(raw_unit_data);
+            if unit_data.crowding_repulsion <= 0. {
+                continue;
+            }
+
+            let mut signals = Signals::default();
+            let emitter = UnitBundle::emitter(Id::from_name(name.clone()), &unit_data);
+            for (signal_type, signal_strength) in emitter.signals {
+                signals.add_signal(signal_type, VoxelPos::ZERO, signal_strength);
+            }
+
+            assert!(
+                signals.wander_attraction(VoxelPos::ZERO) < 0.,
+                "A crowd of {name} attracts wandering units"
             );
+        }
     }
 }
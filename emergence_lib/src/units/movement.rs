@@ -0,0 +1,262 @@
+//! Units can get around in different ways, each with its own pathing rules.
+
+use bevy::prelude::*;
+use hexx::{shapes::hexagon, Direction, Hex};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::item_tags::ItemTag,
+    geometry::{DiscreteHeight, Height, MapGeometry, VoxelPos},
+    items::item_manifest::{Item, ItemManifest},
+    simulation::weather::Wind,
+};
+
+/// How a unit moves from one voxel to the next.
+///
+/// This is defined per unit type in the [`UnitManifest`](super::unit_manifest::UnitManifest).
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementMode {
+    /// Walks along the ground, climbing or descending at most one voxel per step.
+    #[default]
+    Walking,
+    /// Flies just above the tallest object in each column, ignoring obstacles and height.
+    ///
+    /// Flying units are sped up or slowed down by the wind, and cannot carry heavy (non-buoyant) items.
+    Flying,
+}
+
+impl MovementMode {
+    /// Controls how strongly the wind affects the speed of flying units.
+    ///
+    /// A flying unit moving directly downwind in a gale is sped up by this fraction,
+    /// and slowed down by the same fraction when flying directly upwind.
+    /// This must be between 0 and 1.
+    const WIND_SPEED_BIAS: f32 = 0.5;
+
+    /// The voxel reached by moving one tile from `voxel_pos` in `direction`, if that move is possible.
+    pub(crate) fn neighbor_in_direction(
+        self,
+        voxel_pos: VoxelPos,
+        direction: Direction,
+        map_geometry: &MapGeometry,
+    ) -> Option<VoxelPos> {
+        match self {
+            MovementMode::Walking => {
+                map_geometry.walkable_neighbor_in_direction(voxel_pos, direction)
+            }
+            MovementMode::Flying => map_geometry.flight_neighbor_in_direction(voxel_pos, direction),
+        }
+    }
+
+    /// Iterates over the voxels that can be reached by moving a single tile from `voxel_pos`.
+    pub(crate) fn neighbors(
+        self,
+        voxel_pos: VoxelPos,
+        map_geometry: &MapGeometry,
+    ) -> impl Iterator<Item = VoxelPos> + '_ {
+        Direction::ALL_DIRECTIONS
+            .into_iter()
+            .filter_map(move |direction| {
+                self.neighbor_in_direction(voxel_pos, direction, map_geometry)
+            })
+    }
+
+    /// Returns the neighbor of `voxel_pos` that is closest to `target`, if any are closer than `voxel_pos` itself.
+    ///
+    /// This is a greedy step: it can get stuck behind obstacles, so callers should have a fallback.
+    #[must_use]
+    pub(crate) fn step_towards(
+        self,
+        voxel_pos: VoxelPos,
+        target: Hex,
+        map_geometry: &MapGeometry,
+    ) -> Option<VoxelPos> {
+        let current_distance = voxel_pos.hex.unsigned_distance_to(target);
+
+        self.neighbors(voxel_pos, map_geometry)
+            .map(|neighbor| (neighbor, neighbor.hex.unsigned_distance_to(target)))
+            .filter(|&(_, distance)| distance < current_distance)
+            .min_by_key(|&(_, distance)| distance)
+            .map(|(neighbor, _)| neighbor)
+    }
+
+    /// Can a unit standing at `unit_pos` interact with an object at `target`, on the same or an adjacent tile?
+    ///
+    /// Walking units can only reach a single voxel up or down, while flying units can reach all the way down to the ground.
+    pub(crate) fn can_reach(self, unit_pos: VoxelPos, target: VoxelPos) -> bool {
+        match self {
+            MovementMode::Walking => unit_pos.abs_height_diff(target) <= Height::MAX_STEP,
+            MovementMode::Flying => target.height <= unit_pos.height,
+        }
+    }
+
+    /// The voxels on the same or adjacent tiles that a unit standing at `unit_pos` could interact with.
+    pub(crate) fn reachable_voxels(
+        self,
+        unit_pos: VoxelPos,
+        map_geometry: &MapGeometry,
+    ) -> Vec<VoxelPos> {
+        match self {
+            MovementMode::Walking => unit_pos.reachable_neighbors().to_vec(),
+            MovementMode::Flying => hexagon(unit_pos.hex, 1)
+                .filter_map(|hex| {
+                    map_geometry
+                        .get_height(hex)
+                        .ok()
+                        .map(|height| (hex, height))
+                })
+                .flat_map(|(hex, terrain_height)| {
+                    (terrain_height.0..=unit_pos.height.0).map(move |height| VoxelPos {
+                        hex,
+                        height: DiscreteHeight(height),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// The relative speed of a step from `from` to `to`, compared to moving in still air.
+    pub(crate) fn speed_multiplier(self, from: Hex, to: Hex, wind: &Wind) -> f32 {
+        match self {
+            MovementMode::Walking => 1.0,
+            MovementMode::Flying => 1.0 + Self::WIND_SPEED_BIAS * wind.alignment(from, to),
+        }
+    }
+
+    /// Can units that move in this way carry the item `item_id`?
+    pub(crate) fn can_carry(self, item_id: Id<Item>, item_manifest: &ItemManifest) -> bool {
+        match self {
+            MovementMode::Walking => true,
+            MovementMode::Flying => item_manifest.has_tag(item_id, ItemTag::Buoyant),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::DiscreteHeight;
+
+    #[test]
+    fn flying_ignores_height_differences() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 3);
+        let start = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight::ONE,
+        };
+        let cliff = Hex::ZERO.neighbor(Direction::Top);
+        map_geometry.update_height(cliff, DiscreteHeight(5));
+
+        assert_eq!(
+            MovementMode::Walking.neighbor_in_direction(start, Direction::Top, &map_geometry),
+            None
+        );
+        assert_eq!(
+            MovementMode::Flying.neighbor_in_direction(start, Direction::Top, &map_geometry),
+            Some(VoxelPos {
+                hex: cliff,
+                height: DiscreteHeight(6),
+            })
+        );
+    }
+
+    #[test]
+    fn walking_neighbors_match_the_walkable_neighbors() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let start = VoxelPos::ZERO.above();
+
+        let mut neighbors: Vec<VoxelPos> = MovementMode::Walking
+            .neighbors(start, &map_geometry)
+            .collect();
+        let mut walkable_neighbors: Vec<VoxelPos> =
+            map_geometry.walkable_neighbors(start).collect();
+        neighbors.sort_by_key(|voxel_pos| (voxel_pos.hex.x, voxel_pos.hex.y));
+        walkable_neighbors.sort_by_key(|voxel_pos| (voxel_pos.hex.x, voxel_pos.hex.y));
+
+        assert_eq!(neighbors, walkable_neighbors);
+    }
+
+    #[test]
+    fn step_towards_moves_closer_to_the_target() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let start = VoxelPos {
+            hex: Hex::new(-2, 0),
+            height: DiscreteHeight::ONE,
+        };
+        let target = Hex::new(2, 0);
+
+        let step = MovementMode::Walking
+            .step_towards(start, target, &map_geometry)
+            .unwrap();
+        assert_eq!(step.hex.unsigned_distance_to(target), 3);
+
+        // There is nowhere closer to go once the target has been reached
+        let arrived = VoxelPos {
+            hex: target,
+            height: DiscreteHeight::ONE,
+        };
+        assert_eq!(
+            MovementMode::Walking.step_towards(arrived, target, &map_geometry),
+            None
+        );
+    }
+
+    #[test]
+    fn flying_units_step_over_cliffs_towards_their_target() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 3);
+        let start = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight::ONE,
+        };
+        let cliff = Hex::ZERO.neighbor(Direction::Top);
+        map_geometry.update_height(cliff, DiscreteHeight(5));
+        let target = cliff.neighbor(Direction::Top);
+
+        assert_eq!(
+            MovementMode::Walking
+                .step_towards(start, target, &map_geometry)
+                .map(|voxel_pos| voxel_pos.hex),
+            None
+        );
+        assert_eq!(
+            MovementMode::Flying
+                .step_towards(start, target, &map_geometry)
+                .map(|voxel_pos| voxel_pos.hex),
+            Some(cliff)
+        );
+    }
+
+    #[test]
+    fn flying_units_reach_down_to_the_ground() {
+        let high = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(4),
+        };
+        let ground = VoxelPos {
+            hex: Hex::ZERO.neighbor(Direction::Top),
+            height: DiscreteHeight::ONE,
+        };
+
+        assert!(!MovementMode::Walking.can_reach(high, ground));
+        assert!(MovementMode::Flying.can_reach(high, ground));
+    }
+
+    #[test]
+    fn wind_only_affects_flying() {
+        let wind = Wind::new(0., 1.);
+        let from = Hex::ZERO;
+        let downwind = Hex::ZERO.neighbor(wind.hex_direction());
+
+        assert_eq!(
+            MovementMode::Walking.speed_multiplier(from, downwind, &wind),
+            1.0
+        );
+        assert!(MovementMode::Flying.speed_multiplier(from, downwind, &wind) > 1.0);
+        assert!(MovementMode::Flying.speed_multiplier(downwind, from, &wind) < 1.0);
+    }
+}
@@ -0,0 +1,148 @@
+//! Limits how many units can crowd into a single voxel.
+//!
+//! This limit is off by default; set [`TileOccupancy::max_units_per_tile`] to enable it.
+//! Full voxels are treated as soft obstacles when choosing where to move,
+//! and units that try to step into a full voxel wait in line instead.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{asset_management::manifest::Id, geometry::VoxelPos, signals::SignalStrength};
+
+use super::unit_manifest::Unit;
+
+/// The number of units standing in each voxel.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct TileOccupancy {
+    /// The number of units in each occupied voxel.
+    counts: HashMap<VoxelPos, u16>,
+    /// The maximum number of units that can share a voxel.
+    ///
+    /// If this is [`None`], any number of units can share a voxel.
+    /// Defaults to [`None`].
+    pub max_units_per_tile: Option<u16>,
+}
+
+impl TileOccupancy {
+    /// The factor that signal strengths in full voxels are multiplied by when following signals.
+    ///
+    /// This must be between 0 and 1.
+    /// Lower values make units more reluctant to path through crowded voxels.
+    const FULL_TILE_PENALTY: f32 = 0.1;
+
+    /// The number of units in the provided `voxel_pos`.
+    pub fn get(&self, voxel_pos: VoxelPos) -> u16 {
+        self.counts.get(&voxel_pos).copied().unwrap_or_default()
+    }
+
+    /// Can no more units enter the provided `voxel_pos`?
+    pub fn is_full(&self, voxel_pos: VoxelPos) -> bool {
+        match self.max_units_per_tile {
+            Some(max) => self.get(voxel_pos) >= max,
+            None => false,
+        }
+    }
+
+    /// Records that a unit has entered the provided `voxel_pos`.
+    pub(crate) fn add(&mut self, voxel_pos: VoxelPos) {
+        *self.counts.entry(voxel_pos).or_default() += 1;
+    }
+
+    /// Records that a unit has left the provided `voxel_pos`.
+    pub(crate) fn remove(&mut self, voxel_pos: VoxelPos) {
+        if let Some(count) = self.counts.get_mut(&voxel_pos) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.counts.remove(&voxel_pos);
+            }
+        }
+    }
+
+    /// Records that a unit has moved from `from` to `to`.
+    pub(crate) fn move_unit(&mut self, from: VoxelPos, to: VoxelPos) {
+        self.remove(from);
+        self.add(to);
+    }
+
+    /// Weakens the signal `strength` in `voxel_pos` if that voxel is full, making it less attractive to move towards.
+    pub(crate) fn dampen(&self, voxel_pos: VoxelPos, strength: SignalStrength) -> SignalStrength {
+        if self.is_full(voxel_pos) {
+            strength * Self::FULL_TILE_PENALTY
+        } else {
+            strength
+        }
+    }
+
+    /// Strengthens the signal `strength` in `voxel_pos` if that voxel is full, making it less attractive to flee towards.
+    pub(crate) fn amplify(&self, voxel_pos: VoxelPos, strength: SignalStrength) -> SignalStrength {
+        if self.is_full(voxel_pos) {
+            strength / Self::FULL_TILE_PENALTY
+        } else {
+            strength
+        }
+    }
+}
+
+/// Recounts the units in each voxel.
+///
+/// Movement updates the counts as it happens, but this catches units that are spawned or despawned.
+pub(super) fn count_tile_occupancy(
+    unit_query: Query<&VoxelPos, With<Id<Unit>>>,
+    mut tile_occupancy: ResMut<TileOccupancy>,
+) {
+    tile_occupancy.counts.clear();
+    for &voxel_pos in unit_query.iter() {
+        tile_occupancy.add(voxel_pos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_fill_up_at_the_limit() {
+        let mut tile_occupancy = TileOccupancy {
+            max_units_per_tile: Some(2),
+            ..default()
+        };
+        let voxel_pos = VoxelPos::ZERO;
+
+        tile_occupancy.add(voxel_pos);
+        assert!(!tile_occupancy.is_full(voxel_pos));
+        tile_occupancy.add(voxel_pos);
+        assert!(tile_occupancy.is_full(voxel_pos));
+
+        tile_occupancy.move_unit(voxel_pos, voxel_pos.above());
+        assert!(!tile_occupancy.is_full(voxel_pos));
+        assert_eq!(tile_occupancy.get(voxel_pos.above()), 1);
+    }
+
+    #[test]
+    fn no_limit_means_never_full() {
+        let mut tile_occupancy = TileOccupancy {
+            max_units_per_tile: None,
+            ..default()
+        };
+
+        for _ in 0..100 {
+            tile_occupancy.add(VoxelPos::ZERO);
+        }
+
+        assert!(!tile_occupancy.is_full(VoxelPos::ZERO));
+        let strength = SignalStrength::new(1.0);
+        assert_eq!(tile_occupancy.dampen(VoxelPos::ZERO, strength), strength);
+    }
+
+    #[test]
+    fn full_tiles_are_penalized() {
+        let mut tile_occupancy = TileOccupancy {
+            max_units_per_tile: Some(1),
+            ..default()
+        };
+        tile_occupancy.add(VoxelPos::ZERO);
+        let strength = SignalStrength::new(1.0);
+
+        assert!(tile_occupancy.dampen(VoxelPos::ZERO, strength) < strength);
+        assert!(tile_occupancy.amplify(VoxelPos::ZERO, strength) > strength);
+    }
+}
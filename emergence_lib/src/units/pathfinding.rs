@@ -0,0 +1,411 @@
+//! Finds complete paths across the map, and explains the cost of each step.
+//!
+//! Units ordinarily navigate by following signals, one step at a time.
+//! Full paths are only computed on request, such as when previewing where a unit would go.
+
+use core::cmp::Ordering;
+use core::fmt::Write;
+use std::collections::BinaryHeap;
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::{Direction, Hex};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    terrain::terrain_manifest::{Terrain, TerrainManifest},
+};
+
+use super::movement::MovementMode;
+
+/// A single step along an [`ExplainedPath`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathStep {
+    /// The voxel reached by this step.
+    pub voxel_pos: VoxelPos,
+    /// The cost of this step alone.
+    pub step_cost: f32,
+    /// The total cost of the path up to and including this step.
+    pub cost_so_far: f32,
+}
+
+/// A path between two voxels, along with the cost of each step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainedPath {
+    /// The steps taken, in order, starting with the first move away from the start.
+    ///
+    /// This is empty if the start and goal are on the same tile.
+    pub steps: Vec<PathStep>,
+    /// The voxels that were considered while searching for this path, in the order they were explored.
+    pub explored: Vec<VoxelPos>,
+}
+
+impl ExplainedPath {
+    /// The cost of walking every step of this path.
+    pub fn total_cost(&self) -> f32 {
+        self.steps.last().map_or(0., |step| step.cost_so_far)
+    }
+
+    /// Describes this path step by step, for debugging.
+    pub fn explain(&self) -> String {
+        let mut string = format!(
+            "{} steps, total cost {:.1} ({} voxels explored)",
+            self.steps.len(),
+            self.total_cost(),
+            self.explored.len()
+        );
+
+        for step in &self.steps {
+            let hex = step.voxel_pos.hex;
+            // Writing to a string can't fail
+            let _ = write!(
+                string,
+                "\n  ({}, {}) at height {}: +{:.1} = {:.1}",
+                hex.x, hex.y, step.voxel_pos.height, step.step_cost, step.cost_so_far
+            );
+        }
+
+        string
+    }
+}
+
+/// The extra cost of climbing up a single voxel while walking.
+const CLIMB_COST: f32 = 0.5;
+
+/// The largest number of voxels that will be explored before giving up on finding a path.
+const MAX_EXPLORED: usize = 10_000;
+
+/// How quickly units can walk across each tile.
+///
+/// Walking steps onto faster terrain, such as trails, are cheaper.
+/// As a resource, this is kept up to date by [`update_walking_speeds`].
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub(crate) struct WalkingSpeeds {
+    /// The walking speed multiplier of each tile.
+    ///
+    /// Tiles that are missing are walked at normal speed.
+    speeds: HashMap<Hex, f32>,
+    /// The fastest walking speed of any tile, which is never less than 1.
+    max_speed: f32,
+}
+
+impl Default for WalkingSpeeds {
+    fn default() -> Self {
+        WalkingSpeeds {
+            speeds: HashMap::default(),
+            max_speed: 1.0,
+        }
+    }
+}
+
+impl WalkingSpeeds {
+    /// Records the walking speed multiplier of each tile.
+    ///
+    /// These values should always be strictly positive.
+    pub(crate) fn new(speeds: impl IntoIterator<Item = (Hex, f32)>) -> Self {
+        let speeds: HashMap<Hex, f32> = speeds.into_iter().collect();
+        let max_speed = speeds.values().copied().fold(1.0, f32::max);
+
+        WalkingSpeeds { speeds, max_speed }
+    }
+
+    /// The walking speed multiplier of the tile at `hex`.
+    fn get(&self, hex: Hex) -> f32 {
+        self.speeds.get(&hex).copied().unwrap_or(1.0)
+    }
+
+    /// Changes the walking speed multiplier of the tile at `hex`.
+    ///
+    /// The maximum speed is never lowered, since overestimating it only makes the search explore a little more.
+    pub(crate) fn set(&mut self, hex: Hex, speed: f32) {
+        self.speeds.insert(hex, speed);
+        self.max_speed = self.max_speed.max(speed);
+    }
+}
+
+/// Updates the [`WalkingSpeeds`] of tiles whose terrain has changed, such as when a trail is worn.
+pub(crate) fn update_walking_speeds(
+    terrain_query: Query<(&VoxelPos, &Id<Terrain>), Changed<Id<Terrain>>>,
+    maybe_terrain_manifest: Option<Res<TerrainManifest>>,
+    mut walking_speeds: ResMut<WalkingSpeeds>,
+) {
+    let Some(terrain_manifest) = maybe_terrain_manifest else {
+        return;
+    };
+
+    for (voxel_pos, &terrain_id) in terrain_query.iter() {
+        walking_speeds.set(
+            voxel_pos.hex,
+            terrain_manifest.get(terrain_id).walking_speed,
+        );
+    }
+}
+
+/// The cost of moving a single step from `from` to `to`.
+///
+/// Walking is cheaper across faster terrain.
+/// This is never less than the distance divided by [`WalkingSpeeds::max_speed`],
+/// so that scaled distance is an admissible heuristic.
+fn step_cost(
+    movement_mode: MovementMode,
+    from: VoxelPos,
+    to: VoxelPos,
+    walking_speeds: &WalkingSpeeds,
+) -> f32 {
+    match movement_mode {
+        MovementMode::Walking => {
+            let climb = to.height.0.saturating_sub(from.height.0);
+            (1.0 + CLIMB_COST * climb as f32) / walking_speeds.get(to.hex)
+        }
+        MovementMode::Flying => 1.0,
+    }
+}
+
+/// A voxel waiting to be explored, ordered so that the cheapest estimate is popped first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Frontier {
+    /// The estimated total cost of a path through this voxel.
+    estimate: f32,
+    /// The voxel to explore.
+    voxel_pos: VoxelPos,
+}
+
+impl Eq for Frontier {}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, as BinaryHeap is a max-heap
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// Finds the cheapest path from `start` to any voxel in the column at `goal`, moving in the manner of `movement_mode`.
+///
+/// Returns [`None`] if the goal cannot be reached.
+pub(crate) fn find_path(
+    start: VoxelPos,
+    goal: Hex,
+    movement_mode: MovementMode,
+    map_geometry: &MapGeometry,
+    walking_speeds: &WalkingSpeeds,
+) -> Option<ExplainedPath> {
+    // Each step costs at least this much, so it can be used to estimate the remaining cost
+    let min_step_cost = 1.0 / walking_speeds.max_speed;
+
+    let mut frontier = BinaryHeap::new();
+    // For each voxel reached, the cheapest known cost and the voxel it was reached from
+    let mut best: HashMap<VoxelPos, (f32, Option<VoxelPos>)> = HashMap::default();
+
+    best.insert(start, (0., None));
+    frontier.push(Frontier {
+        estimate: start.hex.unsigned_distance_to(goal) as f32 * min_step_cost,
+        voxel_pos: start,
+    });
+
+    let mut explored = Vec::new();
+    while let Some(Frontier { voxel_pos, .. }) = frontier.pop() {
+        explored.push(voxel_pos);
+
+        if voxel_pos.hex == goal {
+            return Some(reconstruct_path(
+                voxel_pos,
+                &best,
+                movement_mode,
+                walking_speeds,
+                explored,
+            ));
+        }
+
+        if explored.len() >= MAX_EXPLORED {
+            break;
+        }
+
+        let cost = best[&voxel_pos].0;
+        for direction in Direction::ALL_DIRECTIONS {
+            let Some(neighbor) =
+                movement_mode.neighbor_in_direction(voxel_pos, direction, map_geometry)
+            else {
+                continue;
+            };
+
+            let neighbor_cost =
+                cost + step_cost(movement_mode, voxel_pos, neighbor, walking_speeds);
+            let improved = best
+                .get(&neighbor)
+                .map_or(true, |&(existing_cost, _)| neighbor_cost < existing_cost);
+
+            if improved {
+                best.insert(neighbor, (neighbor_cost, Some(voxel_pos)));
+                frontier.push(Frontier {
+                    estimate: neighbor_cost
+                        + neighbor.hex.unsigned_distance_to(goal) as f32 * min_step_cost,
+                    voxel_pos: neighbor,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Walks back from `end` to the start of the search, collecting the steps taken.
+fn reconstruct_path(
+    end: VoxelPos,
+    best: &HashMap<VoxelPos, (f32, Option<VoxelPos>)>,
+    movement_mode: MovementMode,
+    walking_speeds: &WalkingSpeeds,
+    explored: Vec<VoxelPos>,
+) -> ExplainedPath {
+    let mut steps = Vec::new();
+    let mut current = end;
+    while let Some(&(cost_so_far, Some(previous))) = best.get(&current) {
+        steps.push(PathStep {
+            voxel_pos: current,
+            step_cost: step_cost(movement_mode, previous, current, walking_speeds),
+            cost_so_far,
+        });
+        current = previous;
+    }
+
+    steps.reverse();
+    ExplainedPath { steps, explored }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::geometry::DiscreteHeight;
+
+    use super::*;
+
+    #[test]
+    fn paths_on_flat_ground_are_straight() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let start = VoxelPos {
+            hex: Hex::new(-2, 0),
+            height: DiscreteHeight::ONE,
+        };
+
+        let path = find_path(
+            start,
+            Hex::new(2, 0),
+            MovementMode::Walking,
+            &map_geometry,
+            &WalkingSpeeds::default(),
+        )
+        .unwrap();
+        assert_eq!(path.steps.len(), 4);
+        assert_eq!(path.total_cost(), 4.);
+        assert_eq!(path.steps.last().unwrap().voxel_pos.hex, Hex::new(2, 0));
+
+        let path = find_path(
+            start,
+            start.hex,
+            MovementMode::Walking,
+            &map_geometry,
+            &WalkingSpeeds::default(),
+        )
+        .unwrap();
+        assert!(path.steps.is_empty());
+        assert_eq!(path.total_cost(), 0.);
+    }
+
+    #[test]
+    fn climbing_costs_extra() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 3);
+        map_geometry.update_height(Hex::ZERO, DiscreteHeight::ONE);
+
+        let start = VoxelPos {
+            hex: Hex::new(-1, 0),
+            height: DiscreteHeight::ONE,
+        };
+        let goal = Hex::new(1, 0);
+
+        // Going over the hill is still cheaper than walking around it
+        let path = find_path(
+            start,
+            goal,
+            MovementMode::Walking,
+            &map_geometry,
+            &WalkingSpeeds::default(),
+        )
+        .unwrap();
+        let step_costs: Vec<f32> = path.steps.iter().map(|step| step.step_cost).collect();
+        assert_eq!(step_costs, vec![1. + CLIMB_COST, 1.]);
+        assert_eq!(path.total_cost(), 2. + CLIMB_COST);
+    }
+
+    #[test]
+    fn paths_detour_along_fast_terrain() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let start = VoxelPos {
+            hex: Hex::new(-1, 0),
+            height: DiscreteHeight::ONE,
+        };
+        let goal = Hex::new(1, 0);
+
+        // The direct route crosses slow ground, while a trail runs around it
+        let detour = [Hex::new(0, -1), Hex::new(1, -1), goal];
+        let walking_speeds = WalkingSpeeds::new(
+            detour
+                .into_iter()
+                .map(|hex| (hex, 4.))
+                .chain([(Hex::ZERO, 0.5)]),
+        );
+
+        let path = find_path(
+            start,
+            goal,
+            MovementMode::Walking,
+            &map_geometry,
+            &walking_speeds,
+        )
+        .unwrap();
+        let hexes: Vec<Hex> = path.steps.iter().map(|step| step.voxel_pos.hex).collect();
+        assert_eq!(hexes, detour.to_vec());
+        assert_eq!(path.total_cost(), 0.75);
+    }
+
+    #[test]
+    fn updated_walking_speeds_match_rebuilt_ones() {
+        let mut walking_speeds = WalkingSpeeds::default();
+        walking_speeds.set(Hex::ZERO, 0.5);
+        walking_speeds.set(Hex::new(1, 0), 4.);
+
+        assert_eq!(
+            walking_speeds,
+            WalkingSpeeds::new([(Hex::ZERO, 0.5), (Hex::new(1, 0), 4.)])
+        );
+    }
+
+    #[test]
+    fn unreachable_goals_have_no_path() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let start = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight::ONE,
+        };
+
+        assert_eq!(
+            find_path(
+                start,
+                Hex::new(10, 0),
+                MovementMode::Walking,
+                &map_geometry,
+                &WalkingSpeeds::default()
+            ),
+            None
+        );
+    }
+}
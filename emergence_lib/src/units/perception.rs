@@ -0,0 +1,146 @@
+//! What units can sense of the world around them.
+//!
+//! Units never consult the global state of the world when choosing what to do.
+//! Instead, each unit builds a [`LocalPerception`] from the signals within its species' sensing radius,
+//! and decides based on that snapshot alone.
+//! This keeps coordination stigmergic: units only cooperate through the traces that they leave in their surroundings.
+
+use bevy::utils::{HashMap, HashSet};
+
+use crate::{
+    geometry::{MapGeometry, VoxelPos},
+    signals::{SignalStrength, SignalType, Signals},
+};
+
+/// How much weaker signals seem for each tile of distance between them and the unit.
+///
+/// This must be between 0 and 1.
+/// Lower values make units more strongly prefer work that is close by.
+pub(crate) const PERCEPTION_FALLOFF: f32 = 0.5;
+
+/// A snapshot of everything that a single unit can sense from where it stands.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct LocalPerception {
+    /// The strongest perceived strength of each goal-relevant signal type, after accounting for distance.
+    goal_signals: HashMap<SignalType, SignalStrength>,
+}
+
+impl LocalPerception {
+    /// Senses the goal-relevant `signals` on every tile that can be walked to from `voxel_pos` in `sensing_radius` steps.
+    ///
+    /// Signals on more distant tiles are dampened by [`PERCEPTION_FALLOFF`] for each step.
+    pub(crate) fn sense(
+        signals: &Signals,
+        voxel_pos: VoxelPos,
+        sensing_radius: u32,
+        map_geometry: &MapGeometry,
+    ) -> Self {
+        let mut goal_signals: HashMap<SignalType, SignalStrength> = HashMap::new();
+
+        for (sensed_pos, distance) in sensed_tiles(voxel_pos, sensing_radius, map_geometry) {
+            let falloff = PERCEPTION_FALLOFF.powi(distance as i32);
+            let local_signals = signals.all_signals_at_position(sensed_pos);
+
+            for (&signal_type, &signal_strength) in local_signals.goal_relevant_signals() {
+                let perceived_strength = signal_strength * falloff;
+                if perceived_strength <= SignalStrength::ZERO {
+                    continue;
+                }
+
+                let strongest = goal_signals.entry(signal_type).or_default();
+                if perceived_strength > *strongest {
+                    *strongest = perceived_strength;
+                }
+            }
+        }
+
+        LocalPerception { goal_signals }
+    }
+
+    /// The goal-relevant signals that were perceived, and how strong each of them seemed.
+    pub(crate) fn goal_relevant_signals(&self) -> Vec<(SignalType, SignalStrength)> {
+        self.goal_signals
+            .iter()
+            .map(|(&signal_type, &signal_strength)| (signal_type, signal_strength))
+            .collect()
+    }
+}
+
+/// Every tile that can be walked to from `center` in at most `radius` steps, and the number of steps needed.
+fn sensed_tiles(center: VoxelPos, radius: u32, map_geometry: &MapGeometry) -> Vec<(VoxelPos, u32)> {
+    let mut seen = HashSet::new();
+    seen.insert(center);
+    let mut sensed = vec![(center, 0)];
+    let mut frontier = vec![center];
+
+    for distance in 1..=radius {
+        let mut next_frontier = Vec::new();
+        for voxel_pos in frontier {
+            for neighbor in map_geometry.walkable_neighbors(voxel_pos) {
+                if seen.insert(neighbor) {
+                    sensed.push((neighbor, distance));
+                    next_frontier.push(neighbor);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    sensed
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::World;
+
+    use crate::{
+        asset_management::manifest::Id, construction::ghosts::WorkplaceId,
+        structures::structure_manifest::Structure,
+    };
+
+    use super::*;
+
+    fn work_signal() -> SignalType {
+        SignalType::Work(WorkplaceId::Structure(Id::<Structure>::from_name(
+            "leuco".to_string(),
+        )))
+    }
+
+    #[test]
+    fn units_only_sense_within_their_radius() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let origin = VoxelPos::ZERO.above();
+        let nearby = map_geometry.walkable_neighbors(origin).next().unwrap();
+
+        let mut signals = Signals::default();
+        signals.add_signal(work_signal(), nearby, SignalStrength::new(1.));
+
+        let blind = LocalPerception::sense(&signals, origin, 0, &map_geometry);
+        assert!(blind.goal_relevant_signals().is_empty());
+
+        let perceptive = LocalPerception::sense(&signals, origin, 1, &map_geometry);
+        assert_eq!(
+            perceptive.goal_relevant_signals(),
+            vec![(work_signal(), SignalStrength::new(PERCEPTION_FALLOFF))]
+        );
+    }
+
+    #[test]
+    fn nearby_signals_outweigh_distant_ones() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let origin = VoxelPos::ZERO.above();
+        let nearby = map_geometry.walkable_neighbors(origin).next().unwrap();
+
+        let mut signals = Signals::default();
+        signals.add_signal(work_signal(), origin, SignalStrength::new(1.));
+        signals.add_signal(work_signal(), nearby, SignalStrength::new(1.5));
+
+        let perception = LocalPerception::sense(&signals, origin, 2, &map_geometry);
+        assert_eq!(
+            perception.goal_relevant_signals(),
+            vec![(work_signal(), SignalStrength::new(1.))]
+        );
+    }
+}
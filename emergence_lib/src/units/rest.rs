@@ -0,0 +1,382 @@
+//! Units grow tired over time, and must rest to recover.
+//!
+//! Tired units work more slowly.
+//! Units rest best inside of a [`Shelter`], each of which can only house a few units at once.
+//! When the colony doesn't have enough shelter for everyone, all work slows down.
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::signals::{Emitter, SignalStrength, SignalType};
+
+use super::{
+    actions::{CurrentAction, UnitAction},
+    census::Census,
+    goals::Goal,
+    item_interaction::UnitInventory,
+};
+
+/// A structure that units can rest inside of.
+///
+/// Shelters manage their own signals, so they should not also be storage or crafting structures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shelter {
+    /// The number of units that can rest here at once.
+    pub capacity: u8,
+}
+
+/// The units currently resting in a [`Shelter`].
+#[derive(Component, Debug, Clone, PartialEq)]
+pub(crate) struct ShelterOccupants {
+    /// The units resting here.
+    residents: HashSet<Entity>,
+    /// The maximum number of units that can rest here at once.
+    capacity: u8,
+}
+
+impl ShelterOccupants {
+    /// Creates a new, empty [`ShelterOccupants`] for the provided `shelter`.
+    pub(crate) fn new(shelter: &Shelter) -> Self {
+        ShelterOccupants {
+            residents: HashSet::new(),
+            capacity: shelter.capacity,
+        }
+    }
+
+    /// The number of units resting here.
+    pub(crate) fn current(&self) -> u8 {
+        self.residents.len() as u8
+    }
+
+    /// The maximum number of units that can rest here at once.
+    pub(crate) fn capacity(&self) -> u8 {
+        self.capacity
+    }
+
+    /// Can more units rest here?
+    pub(crate) fn has_room(&self) -> bool {
+        self.current() < self.capacity
+    }
+
+    /// Lets a unit rest here if there is room.
+    pub(crate) fn add_resident(&mut self, unit_entity: Entity) -> Result<(), ()> {
+        if self.has_room() {
+            self.residents.insert(unit_entity);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Records that a unit has stopped resting here.
+    pub(crate) fn remove_resident(&mut self, unit_entity: Entity) {
+        self.residents.remove(&unit_entity);
+    }
+
+    /// Removes every resident for which `still_resting` returns `false`.
+    fn retain_residents(&mut self, mut still_resting: impl FnMut(Entity) -> bool) {
+        self.residents
+            .retain(|&unit_entity| still_resting(unit_entity));
+    }
+}
+
+impl Display for ShelterOccupants {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} / {}", self.current(), self.capacity)
+    }
+}
+
+/// How tired a unit is.
+///
+/// Fatigue builds up while units are awake, and is recovered by resting.
+#[derive(Component, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fatigue {
+    /// The current fatigue, between 0 and [`Fatigue::MAX`].
+    current: f32,
+}
+
+impl Fatigue {
+    /// The most tired a unit can become.
+    pub const MAX: f32 = 100.;
+
+    /// The fraction of [`Fatigue::MAX`] at which units will stop what they're doing to rest.
+    const TIRED_THRESHOLD: f32 = 0.8;
+
+    /// The fatigue gained per second while not resting.
+    const ACCUMULATION_RATE: f32 = 0.5;
+
+    /// The fatigue recovered per second while resting in a shelter.
+    const SHELTERED_RECOVERY_RATE: f32 = 5.;
+
+    /// The fatigue recovered per second while resting without shelter.
+    const EXPOSED_RECOVERY_RATE: f32 = 2.;
+
+    /// How effectively an exhausted unit works, as a fraction of a fully rested unit.
+    const MIN_WORK_EFFICIENCY: f32 = 0.5;
+
+    /// The current fatigue, between 0 and [`Fatigue::MAX`].
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// Is this unit tired enough that it should rest?
+    pub(crate) fn is_tired(&self) -> bool {
+        self.current >= Self::TIRED_THRESHOLD * Self::MAX
+    }
+
+    /// Has this unit fully recovered?
+    pub(crate) fn is_rested(&self) -> bool {
+        self.current <= 0.
+    }
+
+    /// Builds up fatigue over `delta_secs` of activity.
+    fn tire(&mut self, delta_secs: f32) {
+        self.current = (self.current + Self::ACCUMULATION_RATE * delta_secs).min(Self::MAX);
+    }
+
+    /// Recovers from fatigue over `delta_secs` of rest.
+    fn recover(&mut self, delta_secs: f32, sheltered: bool) {
+        let rate = if sheltered {
+            Self::SHELTERED_RECOVERY_RATE
+        } else {
+            Self::EXPOSED_RECOVERY_RATE
+        };
+
+        self.current = (self.current - rate * delta_secs).max(0.);
+    }
+
+    /// How effectively this unit works, as a fraction of a fully rested unit.
+    pub(crate) fn work_efficiency(&self) -> f32 {
+        1. - (1. - Self::MIN_WORK_EFFICIENCY) * self.current / Self::MAX
+    }
+}
+
+impl Display for Fatigue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}/{}", self.current, Self::MAX)
+    }
+}
+
+/// The total room in shelters across the colony, compared to the number of units that need it.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct ShelterCapacity {
+    /// The number of units that can rest in shelters at once.
+    capacity: usize,
+    /// The number of living units.
+    population: usize,
+}
+
+impl ShelterCapacity {
+    /// The speed at which work is done when there is no shelter at all.
+    const MIN_WORK_SPEED: f32 = 0.75;
+
+    /// The number of units that can rest in shelters at once.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of living units.
+    pub fn population(&self) -> usize {
+        self.population
+    }
+
+    /// The factor that all work across the colony is multiplied by.
+    ///
+    /// This is 1 when there is enough shelter for every unit, and falls as the shortage grows.
+    pub fn work_speed(&self) -> f32 {
+        if self.capacity >= self.population {
+            return 1.;
+        }
+
+        let sheltered_fraction = self.capacity as f32 / self.population as f32;
+        Self::MIN_WORK_SPEED + (1. - Self::MIN_WORK_SPEED) * sheltered_fraction
+    }
+}
+
+/// Units grow tired while active, and recover while resting.
+pub(super) fn manage_fatigue(
+    mut unit_query: Query<(&mut Fatigue, &CurrentAction)>,
+    time: Res<Time>,
+) {
+    let delta_secs = time.delta_seconds();
+
+    for (mut fatigue, current_action) in unit_query.iter_mut() {
+        match current_action.action() {
+            UnitAction::Rest { shelter_entity } => {
+                fatigue.recover(delta_secs, shelter_entity.is_some())
+            }
+            _ => fatigue.tire(delta_secs),
+        }
+    }
+}
+
+/// Swaps the goal to [`Goal::Rest`] when units are tired.
+///
+/// Units that are carrying an item finish their delivery first.
+pub(super) fn check_for_rest(mut unit_query: Query<(&mut Goal, &Fatigue, &UnitInventory)>) {
+    for (mut goal, fatigue, unit_inventory) in unit_query.iter_mut() {
+        if fatigue.is_tired() {
+            // Eating and breathing are more urgent than sleeping
+            if matches!(*goal, Goal::Eat(..) | Goal::Breathe | Goal::Rest) {
+                continue;
+            }
+
+            // Make sure to put down any item we're holding before resting
+            if unit_inventory.held_item().is_some() {
+                continue;
+            }
+
+            *goal = Goal::Rest;
+        } else if *goal == Goal::Rest && fatigue.is_rested() {
+            *goal = Goal::default();
+        }
+    }
+}
+
+/// Shelters with room to spare advertise themselves to tired units.
+pub(super) fn set_shelter_emitters(mut shelter_query: Query<(&ShelterOccupants, &mut Emitter)>) {
+    for (shelter_occupants, mut emitter) in shelter_query.iter_mut() {
+        emitter.signals.clear();
+
+        if shelter_occupants.has_room() {
+            emitter
+                .signals
+                .push((SignalType::Shelter, SignalStrength::new(10.)));
+        }
+    }
+}
+
+/// Frees up room in shelters whose residents have died or stopped resting there.
+///
+/// Residents are normally removed when their rest finishes,
+/// but units can die or have their action replaced partway through.
+pub(super) fn release_shelter_slots(
+    mut shelter_query: Query<(Entity, &mut ShelterOccupants)>,
+    unit_query: Query<&CurrentAction>,
+) {
+    for (shelter_entity, mut shelter_occupants) in shelter_query.iter_mut() {
+        shelter_occupants.retain_residents(|unit_entity| {
+            unit_query.get(unit_entity).is_ok_and(|current_action| {
+                matches!(
+                    current_action.action(),
+                    UnitAction::Rest {
+                        shelter_entity: Some(resting_at),
+                    } if *resting_at == shelter_entity
+                )
+            })
+        });
+    }
+}
+
+/// Compares the total room in shelters to the number of living units.
+pub(super) fn update_shelter_capacity(
+    shelter_query: Query<&ShelterOccupants>,
+    census: Res<Census>,
+    mut shelter_capacity: ResMut<ShelterCapacity>,
+) {
+    let capacity = shelter_query
+        .iter()
+        .map(|shelter_occupants| shelter_occupants.capacity() as usize)
+        .sum();
+
+    shelter_capacity.set_if_neq(ShelterCapacity {
+        capacity,
+        population: census.total_population(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tired_units_work_slower() {
+        let mut fatigue = Fatigue::default();
+        assert!(fatigue.is_rested());
+        assert_eq!(fatigue.work_efficiency(), 1.);
+
+        fatigue.tire(1000.);
+        assert!(fatigue.is_tired());
+        assert_eq!(fatigue.current(), Fatigue::MAX);
+        assert_eq!(fatigue.work_efficiency(), Fatigue::MIN_WORK_EFFICIENCY);
+    }
+
+    #[test]
+    fn shelter_speeds_recovery() {
+        let mut sheltered = Fatigue {
+            current: Fatigue::MAX,
+        };
+        let mut exposed = sheltered.clone();
+
+        sheltered.recover(1., true);
+        exposed.recover(1., false);
+        assert!(sheltered.current() < exposed.current());
+
+        sheltered.recover(1000., true);
+        assert!(sheltered.is_rested());
+    }
+
+    #[test]
+    fn shelter_shortages_slow_the_colony() {
+        let enough = ShelterCapacity {
+            capacity: 10,
+            population: 5,
+        };
+        assert_eq!(enough.work_speed(), 1.);
+
+        let none = ShelterCapacity {
+            capacity: 0,
+            population: 5,
+        };
+        assert_eq!(none.work_speed(), ShelterCapacity::MIN_WORK_SPEED);
+
+        let some = ShelterCapacity {
+            capacity: 2,
+            population: 5,
+        };
+        assert!(some.work_speed() > none.work_speed());
+        assert!(some.work_speed() < enough.work_speed());
+    }
+
+    #[test]
+    fn shelters_fill_up() {
+        let mut shelter_occupants = ShelterOccupants::new(&Shelter { capacity: 1 });
+        let first = Entity::from_raw(0);
+        let second = Entity::from_raw(1);
+
+        assert!(shelter_occupants.add_resident(first).is_ok());
+        assert!(!shelter_occupants.has_room());
+        assert!(shelter_occupants.add_resident(second).is_err());
+
+        shelter_occupants.remove_resident(first);
+        assert!(shelter_occupants.has_room());
+    }
+
+    #[test]
+    fn dead_and_distracted_residents_are_released() {
+        let mut world = World::new();
+        let shelter_entity = world
+            .spawn(ShelterOccupants::new(&Shelter { capacity: 3 }))
+            .id();
+
+        let resting = world.spawn(CurrentAction::rest(Some(shelter_entity))).id();
+        let distracted = world.spawn(CurrentAction::idle()).id();
+        let dead = world.spawn(CurrentAction::rest(Some(shelter_entity))).id();
+
+        let mut shelter_occupants = world.get_mut::<ShelterOccupants>(shelter_entity).unwrap();
+        for unit_entity in [resting, distracted, dead] {
+            shelter_occupants.add_resident(unit_entity).unwrap();
+        }
+        world.despawn(dead);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(release_shelter_slots);
+        schedule.run(&mut world);
+
+        let shelter_occupants = world.get::<ShelterOccupants>(shelter_entity).unwrap();
+        assert_eq!(shelter_occupants.current(), 1);
+        assert!(shelter_occupants.residents.contains(&resting));
+    }
+}
@@ -0,0 +1,136 @@
+//! Spreads unit decision-making across ticks, so that large colonies can't stall a frame.
+//!
+//! Rather than every unit thinking every tick, [`schedule_thinking`] picks a limited number of units that need to,
+//! starting with those that have waited the longest.
+//! Goal and action selection then only consider those units,
+//! and stop early once the [`AiBudget`] for the current frame has been spent.
+//! Any units that were skipped are first in line on the next tick.
+//! Skipped units keep their finished action until they get to think, but its effects are only applied once.
+
+use bevy::{
+    core::FrameCount,
+    prelude::*,
+    utils::{Duration, Instant},
+};
+
+use super::{actions::CurrentAction, goals::Goal, impatience::ImpatiencePool};
+
+/// Limits how much work is spent on unit decision-making.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct AiBudget {
+    /// The largest number of units that can think in a single tick.
+    pub max_units_per_tick: usize,
+    /// The total time that unit decision-making can take in a single frame, across all ticks.
+    pub max_time_per_frame: Duration,
+}
+
+impl Default for AiBudget {
+    fn default() -> Self {
+        AiBudget {
+            max_units_per_tick: 512,
+            max_time_per_frame: Duration::from_millis(4),
+        }
+    }
+}
+
+/// The tick on which this unit last thought about what to do.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LastThought(u64);
+
+impl LastThought {
+    /// Records that the unit is thinking during the current tick of `thinking_queue`.
+    pub(super) fn update(&mut self, thinking_queue: &ThinkingQueue) {
+        self.0 = thinking_queue.tick;
+    }
+
+    /// Did the unit think during the most recent tick of `thinking_queue`?
+    pub(crate) fn is_latest(&self, thinking_queue: &ThinkingQueue) -> bool {
+        self.0 == thinking_queue.tick
+    }
+}
+
+/// The units that are allowed to think during the current tick, in order of priority.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct ThinkingQueue {
+    /// The number of ticks that have been scheduled so far.
+    tick: u64,
+    /// The frame during which [`ThinkingQueue::time_spent`] was accumulated.
+    frame: u32,
+    /// The time spent thinking so far this frame.
+    time_spent: Duration,
+    /// The units that should think this tick, most stale first.
+    units: Vec<Entity>,
+}
+
+impl ThinkingQueue {
+    /// The units that should think this tick, most stale first.
+    pub(super) fn units(&self) -> &[Entity] {
+        &self.units
+    }
+
+    /// Has the frame's budget been spent, including the time since `started`?
+    pub(super) fn out_of_time(&self, started: Instant, ai_budget: &AiBudget) -> bool {
+        self.time_spent.saturating_add(started.elapsed()) >= ai_budget.max_time_per_frame
+    }
+
+    /// Adds the time since `started` to the time spent this frame.
+    pub(super) fn record_time(&mut self, started: Instant) {
+        self.time_spent += started.elapsed();
+    }
+}
+
+/// Picks which units think this tick.
+pub(super) fn schedule_thinking(
+    unit_query: Query<(Entity, &LastThought, &Goal, &ImpatiencePool, &CurrentAction)>,
+    ai_budget: Res<AiBudget>,
+    frame_count: Res<FrameCount>,
+    mut thinking_queue: ResMut<ThinkingQueue>,
+) {
+    thinking_queue.tick += 1;
+    if thinking_queue.frame != frame_count.0 {
+        thinking_queue.frame = frame_count.0;
+        thinking_queue.time_spent = Duration::ZERO;
+    }
+
+    // Units that are busy and content have nothing to think about
+    let candidates = unit_query
+        .iter()
+        .filter(|(_, _, goal, impatience_pool, current_action)| {
+            current_action.finished()
+                || impatience_pool.is_full()
+                || matches!(goal, Goal::Wander { .. })
+        })
+        .map(|(entity, last_thought, ..)| (entity, last_thought.0));
+
+    thinking_queue.units = most_stale(candidates, ai_budget.max_units_per_tick);
+}
+
+/// Returns up to `n` of the `candidates`, starting with those that last thought the longest time ago.
+///
+/// Ties are broken by [`Entity`], so the order is deterministic.
+fn most_stale(candidates: impl IntoIterator<Item = (Entity, u64)>, n: usize) -> Vec<Entity> {
+    let mut candidates: Vec<(Entity, u64)> = candidates.into_iter().collect();
+    candidates.sort_unstable_by_key(|&(entity, last_thought)| (last_thought, entity));
+
+    candidates
+        .into_iter()
+        .take(n)
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stalest_units_think_first() {
+        let a = Entity::from_raw(0);
+        let b = Entity::from_raw(1);
+        let c = Entity::from_raw(2);
+
+        assert_eq!(most_stale([(a, 5), (b, 2), (c, 9)], 2), vec![b, a]);
+        assert_eq!(most_stale([(c, 3), (b, 3), (a, 3)], 3), vec![a, b, c]);
+        assert_eq!(most_stale([(a, 1)], 0), Vec::<Entity>::new());
+    }
+}
@@ -0,0 +1,119 @@
+//! Stats derived from a unit's species and age.
+//!
+//! Young and old units are typically weaker than those in their prime,
+//! as described by the [`AgeCurves`](super::age::AgeCurves) of their species.
+
+use std::fmt::{Display, Formatter};
+
+use bevy::prelude::*;
+
+use crate::asset_management::manifest::Id;
+
+use super::{
+    age::Age,
+    unit_manifest::{Unit, UnitData, UnitManifest},
+};
+
+/// The current physical abilities of a unit.
+///
+/// These are recomputed as the unit ages: read them from here, rather than from the [`UnitManifest`].
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct UnitStats {
+    /// How fast this unit moves, relative to a typical unit.
+    pub speed: f32,
+    /// How many items of the same type this unit can carry at once.
+    ///
+    /// This is always at least 1.
+    pub carry_capacity: u32,
+    /// How effectively this unit works at structures, relative to a typical unit.
+    pub work_rate: f32,
+}
+
+impl UnitStats {
+    /// Computes the stats of a unit of type `unit_data` with the provided `age`.
+    pub fn new(unit_data: &UnitData, age: &Age) -> Self {
+        let fraction_of_life = age.fraction_of_life();
+        let curves = &unit_data.age_curves;
+
+        let carry_capacity =
+            unit_data.carry_capacity as f32 * curves.carry_capacity.multiplier(fraction_of_life);
+
+        UnitStats {
+            speed: unit_data.speed * curves.speed.multiplier(fraction_of_life),
+            carry_capacity: (carry_capacity.round() as u32).max(1),
+            work_rate: curves.work_rate.multiplier(fraction_of_life),
+        }
+    }
+}
+
+impl Display for UnitStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.0}% speed, carries {}, {:.0}% work rate",
+            self.speed * 100.,
+            self.carry_capacity,
+            self.work_rate * 100.
+        )
+    }
+}
+
+/// Updates the [`UnitStats`] of each unit to match its age.
+pub(super) fn update_unit_stats(
+    mut unit_query: Query<(&Id<Unit>, &Age, &mut UnitStats)>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    for (&unit_id, age, mut stats) in unit_query.iter_mut() {
+        let unit_data = unit_manifest.get(unit_id);
+        stats.set_if_neq(UnitStats::new(unit_data, age));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        simulation::time::Days,
+        units::{age::AgeCurve, basic_needs::Diet},
+    };
+
+    #[test]
+    fn stats_follow_the_age_curves() {
+        let mut unit_data = UnitData::simple("ant", Diet::simple("leuco_chunk"));
+        unit_data.speed = 2.;
+        unit_data.carry_capacity = 4;
+        unit_data.age_curves.speed = AgeCurve::new([(0., 0.5), (0.5, 1.)]);
+        unit_data.age_curves.carry_capacity = AgeCurve::new([(0., 0.25), (0.5, 1.)]);
+        unit_data.age_curves.work_rate = AgeCurve::new([(0.5, 1.), (1., 0.5)]);
+
+        let newborn = UnitStats::new(&unit_data, &Age::newborn(Days(10.)));
+        assert_eq!(
+            newborn,
+            UnitStats {
+                speed: 1.,
+                carry_capacity: 1,
+                work_rate: 1.,
+            }
+        );
+
+        // Units without a lifespan are treated as being at the end of their life
+        let elderly = UnitStats::new(&unit_data, &Age::newborn(Days(0.)));
+        assert_eq!(
+            elderly,
+            UnitStats {
+                speed: 2.,
+                carry_capacity: 4,
+                work_rate: 0.5,
+            }
+        );
+    }
+
+    #[test]
+    fn carry_capacity_is_at_least_one() {
+        let mut unit_data = UnitData::simple("ant", Diet::simple("leuco_chunk"));
+        unit_data.age_curves.carry_capacity = AgeCurve::new([(0., 0.)]);
+
+        let stats = UnitStats::new(&unit_data, &Age::newborn(Days(10.)));
+        assert_eq!(stats.carry_capacity, 1);
+    }
+}
@@ -0,0 +1,94 @@
+//! Records where units actually walk, as a slowly fading heatmap of traffic.
+//!
+//! Every time a unit steps onto a tile, the traffic on that tile goes up by one.
+//! Traffic decays over time, so the [`TrafficHeatmap`] shows the colony's current desire paths,
+//! rather than everywhere that units have ever been.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{asset_management::manifest::Id, geometry::VoxelPos, simulation::time::TickRate};
+
+use super::unit_manifest::Unit;
+
+/// How many units have recently walked through each tile.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct TrafficHeatmap {
+    /// The recent traffic through each voxel that units walk in.
+    ///
+    /// Tiles without any recent traffic are omitted.
+    traffic: HashMap<VoxelPos, f32>,
+}
+
+impl TrafficHeatmap {
+    /// The fraction of traffic that fades away each tick, at the default [`TickRate`].
+    pub const DECAY_FRACTION: f32 = 0.002;
+
+    /// Below this level of traffic, tiles are forgotten entirely.
+    const MIN_TRAFFIC: f32 = 0.01;
+
+    /// The recent traffic through `voxel_pos`.
+    ///
+    /// This is the number of times that a unit has stepped there, discounted by how long ago it was.
+    pub fn get(&self, voxel_pos: VoxelPos) -> f32 {
+        self.traffic.get(&voxel_pos).copied().unwrap_or_default()
+    }
+
+    /// Records that a unit has stepped into `voxel_pos`.
+    pub(crate) fn record_step(&mut self, voxel_pos: VoxelPos) {
+        *self.traffic.entry(voxel_pos).or_default() += 1.;
+    }
+
+    /// Fades all traffic by `decay_fraction`, forgetting tiles that are barely used.
+    pub(crate) fn decay(&mut self, decay_fraction: f32) {
+        self.traffic.retain(|_, traffic| {
+            *traffic *= 1. - decay_fraction;
+            *traffic >= Self::MIN_TRAFFIC
+        });
+    }
+
+    /// Iterates over every tile with recent traffic.
+    pub fn iter(&self) -> impl Iterator<Item = (VoxelPos, f32)> + '_ {
+        self.traffic
+            .iter()
+            .map(|(&voxel_pos, &traffic)| (voxel_pos, traffic))
+    }
+}
+
+/// Counts each step that a unit takes onto a new tile.
+pub(super) fn record_traffic(
+    unit_query: Query<&VoxelPos, (With<Id<Unit>>, Changed<VoxelPos>)>,
+    mut traffic_heatmap: ResMut<TrafficHeatmap>,
+) {
+    for &voxel_pos in unit_query.iter() {
+        traffic_heatmap.record_step(voxel_pos);
+    }
+}
+
+/// Fades out old traffic.
+pub(super) fn decay_traffic(mut traffic_heatmap: ResMut<TrafficHeatmap>, tick_rate: Res<TickRate>) {
+    traffic_heatmap.decay(tick_rate.per_tick_fraction(TrafficHeatmap::DECAY_FRACTION));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traffic_accumulates_then_fades() {
+        let mut heatmap = TrafficHeatmap::default();
+        let path = VoxelPos::from_xy(1, 2);
+        heatmap.record_step(path);
+        heatmap.record_step(path);
+        assert_eq!(heatmap.get(path), 2.);
+        assert_eq!(heatmap.get(VoxelPos::ZERO), 0.);
+
+        heatmap.decay(0.5);
+        assert_eq!(heatmap.get(path), 1.);
+
+        // Barely used tiles are eventually forgotten
+        for _ in 0..10 {
+            heatmap.decay(0.5);
+        }
+        assert_eq!(heatmap.iter().count(), 0);
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     geometry::hexagonal_column,
     units::unit_manifest::{Unit, UnitManifest},
 };
-use bevy::{asset::LoadState, prelude::*, utils::HashMap};
+use bevy::{asset::UntypedHandle, prelude::*, utils::HashMap};
 
 /// Stores material handles for the different tile types.
 #[derive(Resource)]
@@ -50,21 +50,10 @@ impl Loadable for UnitHandles {
         world.insert_resource(handles);
     }
 
-    fn load_state(&self, asset_server: &AssetServer) -> Option<LoadState> {
-        for (unit, scene_handle) in &self.scenes {
-            let scene_load_state = asset_server.get_load_state(scene_handle);
-
-            if scene_load_state != Some(LoadState::Loaded) {
-                let maybe_path = asset_server.get_path(scene_handle);
-                let path = maybe_path
-                    .map(|p| format!("{:?}", p.path()))
-                    .unwrap_or("unknown_path".to_string());
-
-                info!("Unit {unit:?}'s scene at {path} is {scene_load_state:?}");
-                return scene_load_state;
-            }
-        }
-
-        Some(LoadState::Loaded)
+    fn handles(&self) -> Vec<UntypedHandle> {
+        self.scenes
+            .values()
+            .map(|scene_handle| scene_handle.clone_weak().untyped())
+            .collect()
     }
 }
@@ -3,7 +3,7 @@
 use bevy::{
     asset::Asset,
     reflect::{Reflect, TypePath, TypeUuid},
-    utils::HashMap,
+    utils::{HashMap, HashSet},
 };
 use serde::{Deserialize, Serialize};
 
@@ -11,7 +11,10 @@ use crate::{
     asset_management::manifest::loader::IsRawManifest,
     organisms::{OrganismVariety, RawOrganismVariety},
     simulation::time::Days,
-    units::{basic_needs::Diet, WanderingBehavior},
+    units::{
+        age::AgeCurves, basic_needs::Diet, goals::GoalKind, movement::MovementMode,
+        WanderingBehavior,
+    },
 };
 
 use super::{basic_needs::RawDiet, Manifest};
@@ -37,9 +40,51 @@ pub struct UnitData {
     ///
     /// This stores a [`WeightedIndex`](rand::distributions::WeightedIndex) to allow for multimodal distributions.
     pub wandering_behavior: WanderingBehavior,
+    /// How units of this type get around.
+    pub movement_mode: MovementMode,
+    /// How fast units of this type move, relative to a typical unit.
+    ///
+    /// This must be positive.
+    pub speed: f32,
+    /// How many items of the same type units of this type can carry at once.
+    ///
+    /// This must be at least 1.
+    pub carry_capacity: u32,
+    /// How the speed, carry capacity and work rate of units of this type change as they age.
+    pub age_curves: AgeCurves,
+    /// How many tiles away units of this type can sense signals when choosing a goal.
+    ///
+    /// At 0, units only sense the tile that they are standing on.
+    pub sensing_radius: u32,
+    /// The strength of the [`SignalType::Crowding`](crate::signals::SignalType::Crowding) signal emitted by each unit of this type.
+    ///
+    /// Where many units gather, these signals add up, and idle units wander away from the crowd.
+    /// At 0, units of this type do not mind crowds.
+    pub crowding_repulsion: f32,
+    /// The soil fertility that each unit of this type adds to a tile whenever it steps onto it.
+    ///
+    /// Units wander widely, so this slowly enriches the routes that they travel most often.
+    /// Corpses are handled separately, as litter that decomposes into the soil.
+    pub nutrients_per_step: f32,
+    /// The goals that units of this type will choose to pursue based on signals.
+    ///
+    /// If this is [`None`], all goals are allowed.
+    /// Wandering, eating, breathing and resting are always allowed, as they are needed to survive.
+    pub allowed_goals: Option<HashSet<GoalKind>>,
 }
 
 impl UnitData {
+    /// Can units of this type pursue goals of the provided `goal_kind`?
+    pub fn allows_goal(&self, goal_kind: GoalKind) -> bool {
+        match goal_kind {
+            GoalKind::Wander | GoalKind::Eat | GoalKind::Breathe | GoalKind::Rest => true,
+            _ => match &self.allowed_goals {
+                Some(allowed_goals) => allowed_goals.contains(&goal_kind),
+                None => true,
+            },
+        }
+    }
+
     /// Constructs a new [`UnitData`] from the given [`OrganismVariety`] and [`Diet`].
     #[cfg(test)]
     pub fn simple(name: &str, diet: Diet) -> Self {
@@ -49,6 +94,14 @@ impl UnitData {
             max_impatience: 10,
             max_age: Days(10.0),
             wandering_behavior: WanderingBehavior::default(),
+            movement_mode: MovementMode::Walking,
+            speed: 1.0,
+            carry_capacity: 1,
+            age_curves: AgeCurves::default(),
+            sensing_radius: 0,
+            crowding_repulsion: 0.,
+            nutrients_per_step: 0.,
+            allowed_goals: None,
         }
     }
 }
@@ -68,6 +121,42 @@ pub struct RawUnitData {
     ///
     /// This stores a [`WeightedIndex`](rand::distributions::WeightedIndex) to allow for multimodal distributions.
     pub wandering_behavior: WanderingBehavior,
+    /// How units of this type get around.
+    ///
+    /// Units walk unless otherwise specified.
+    #[serde(default)]
+    pub movement_mode: MovementMode,
+    /// How fast units of this type move, relative to a typical unit.
+    pub speed: f32,
+    /// How many items of the same type units of this type can carry at once.
+    pub carry_capacity: u32,
+    /// How the speed, carry capacity and work rate of units of this type change as they age.
+    ///
+    /// Stats do not change with age unless otherwise specified.
+    #[serde(default)]
+    pub age_curves: AgeCurves,
+    /// How many tiles away units of this type can sense signals when choosing a goal.
+    ///
+    /// Units only sense the tile that they are standing on unless otherwise specified.
+    #[serde(default)]
+    pub sensing_radius: u32,
+    /// The strength of the crowding signal emitted by each unit of this type.
+    ///
+    /// Units do not mind crowds unless otherwise specified.
+    #[serde(default)]
+    pub crowding_repulsion: f32,
+    /// The soil fertility that each unit of this type adds to a tile whenever it steps onto it.
+    ///
+    /// Units do not enrich the soil unless otherwise specified.
+    #[serde(default)]
+    pub nutrients_per_step: f32,
+    /// The goals that units of this type will choose to pursue based on signals.
+    ///
+    /// If this is omitted, all goals are allowed.
+    pub allowed_goals: Option<HashSet<GoalKind>>,
+    /// Descriptive text shown in the codex, if any.
+    #[serde(default)]
+    pub flavor_text: Option<String>,
 }
 
 impl From<RawUnitData> for UnitData {
@@ -78,12 +167,32 @@ impl From<RawUnitData> for UnitData {
             raw.max_age
         );
 
+        assert!(
+            raw.speed > 0.0,
+            "Unit speed must be positive (got {})",
+            raw.speed
+        );
+
+        assert!(
+            raw.carry_capacity > 0,
+            "Unit carry capacity must be at least 1 (got {})",
+            raw.carry_capacity
+        );
+
         Self {
             organism_variety: raw.organism_variety.into(),
             diet: raw.diet.into(),
             max_impatience: raw.max_impatience,
             max_age: Days(raw.max_age),
             wandering_behavior: raw.wandering_behavior,
+            movement_mode: raw.movement_mode,
+            speed: raw.speed,
+            carry_capacity: raw.carry_capacity,
+            age_curves: raw.age_curves,
+            sensing_radius: raw.sensing_radius,
+            crowding_repulsion: raw.crowding_repulsion,
+            nutrients_per_step: raw.nutrients_per_step,
+            allowed_goals: raw.allowed_goals,
         }
     }
 }
@@ -106,10 +215,11 @@ impl IsRawManifest for RawUnitManifest {
         let mut manifest = Manifest::new();
 
         for (raw_id, raw_data) in self.unit_types.clone() {
+            let flavor_text = raw_data.flavor_text.clone();
             let data = raw_data.into();
 
             // No additional preprocessing is needed.
-            manifest.insert(raw_id, data)
+            manifest.insert_with_flavor_text(raw_id, data, flavor_text)
         }
 
         manifest
@@ -0,0 +1,421 @@
+//! Lets an audience vote on events that happen in the game.
+//!
+//! Viewer events are opt-in: add a [`ViewerEventsPlugin`] with a [`ViewerEventsConfig`] to enable them.
+//! A chat bot or stream overlay running on the same machine connects over TCP,
+//! and forwards each viewer's vote as a line of JSON, such as `{"viewer": "name", "command": "weather rainy"}`.
+//! At the end of each voting period, the most popular command is carried out.
+//!
+//! Only commands on a fixed whitelist are accepted, so viewers cannot do anything that the game does not allow.
+
+use std::{
+    io::{ErrorKind, Read},
+    net::{SocketAddr, TcpListener, TcpStream},
+};
+
+use bevy::prelude::*;
+use rand::seq::IteratorRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    enum_iter::IterableEnum,
+    factions::Factions,
+    geometry::MapGeometry,
+    multiplayer::split_messages,
+    simulation::{
+        rng::GlobalRng,
+        weather::{CurrentWeather, Weather},
+        SimulationSet,
+    },
+    units::{
+        unit_assets::UnitHandles,
+        unit_manifest::{Unit, UnitManifest},
+        UnitBundle,
+    },
+};
+
+/// Collects votes from viewers, and carries out the winning [`ViewerCommand`].
+pub struct ViewerEventsPlugin {
+    /// Where to listen for votes, and what viewers may vote for.
+    ///
+    /// If this is [`None`], viewer events are disabled.
+    pub config: Option<ViewerEventsConfig>,
+}
+
+impl ViewerEventsPlugin {
+    /// Configures viewer events using [`ViewerEventsConfig::from_env`].
+    pub fn from_env() -> Self {
+        ViewerEventsPlugin {
+            config: ViewerEventsConfig::from_env(),
+        }
+    }
+}
+
+impl Plugin for ViewerEventsPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(config) = &self.config else {
+            return;
+        };
+
+        // Votes should only come from a trusted bridge on this machine
+        if !config.address.ip().is_loopback() {
+            error!(
+                "Viewer events must listen on a local address, but {} was requested",
+                config.address
+            );
+            return;
+        }
+
+        let listener = match TcpListener::bind(config.address)
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+        {
+            Ok(listener) => listener,
+            Err(error) => {
+                error!("Could not listen for viewer events: {error}");
+                return;
+            }
+        };
+
+        info!("Listening for viewer events on {}", config.address);
+        app.insert_resource(ViewerVotes::new(config.clone(), listener))
+            .add_systems(
+                FixedUpdate,
+                (collect_votes, resolve_votes).chain().in_set(SimulationSet),
+            );
+    }
+}
+
+/// Settings for the [`ViewerEventsPlugin`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewerEventsConfig {
+    /// The local address that votes are received on.
+    pub address: SocketAddr,
+    /// The number of seconds that each round of voting lasts.
+    pub voting_period: f32,
+    /// The names of the units that viewers may spawn.
+    pub spawnable_units: Vec<String>,
+}
+
+impl ViewerEventsConfig {
+    /// The environment variable that enables viewer events when set to a local address.
+    pub const ADDRESS_VAR: &'static str = "EMERGENCE_VIEWER_EVENTS";
+
+    /// The environment variable that sets which units can be spawned, as a comma-separated list.
+    pub const SPAWNABLE_UNITS_VAR: &'static str = "EMERGENCE_VIEWER_SPAWNS";
+
+    /// The default number of seconds that each round of voting lasts.
+    const DEFAULT_VOTING_PERIOD: f32 = 60.;
+
+    /// Reads the viewer event settings from the environment.
+    ///
+    /// Returns [`None`] if viewer events have not been requested, or if the address is invalid.
+    pub fn from_env() -> Option<Self> {
+        let address = std::env::var(Self::ADDRESS_VAR).ok()?;
+        let address = match address.parse() {
+            Ok(address) => address,
+            Err(error) => {
+                error!("Invalid viewer events address {address}: {error}");
+                return None;
+            }
+        };
+
+        let spawnable_units = std::env::var(Self::SPAWNABLE_UNITS_VAR)
+            .unwrap_or_else(|_| "basket_crab".to_string())
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        Some(ViewerEventsConfig {
+            address,
+            voting_period: Self::DEFAULT_VOTING_PERIOD,
+            spawnable_units,
+        })
+    }
+}
+
+/// A single vote, as sent by the chat bridge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewerVote {
+    /// The name of the viewer who voted.
+    ///
+    /// Each viewer only gets one vote per round.
+    pub viewer: String,
+    /// The command that they voted for, as typed in chat.
+    pub command: String,
+}
+
+/// An in-game event that viewers can vote for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ViewerCommand {
+    /// Change today's weather.
+    SetWeather(Weather),
+    /// Spawn a unit of this type somewhere on the map.
+    SpawnUnit(Id<Unit>),
+}
+
+impl ViewerCommand {
+    /// Parses a command typed in chat, such as `weather rainy` or `spawn basket_crab`.
+    ///
+    /// Only whitelisted commands are accepted: units can only be spawned if they are listed in the `config`.
+    fn parse(
+        text: &str,
+        config: &ViewerEventsConfig,
+        unit_manifest: &UnitManifest,
+    ) -> Result<ViewerCommand, String> {
+        let text = text.trim().to_lowercase();
+        let (verb, argument) = text.split_once(' ').unwrap_or((text.as_str(), ""));
+        let argument = argument.trim();
+
+        match verb {
+            "weather" => Weather::variants()
+                .find(|weather| weather.to_string().to_lowercase() == argument)
+                .map(ViewerCommand::SetWeather)
+                .ok_or_else(|| format!("Unknown weather {argument}")),
+            "spawn" => {
+                if !config.spawnable_units.iter().any(|name| name == argument) {
+                    return Err(format!("{argument} cannot be spawned by viewers"));
+                }
+
+                unit_manifest
+                    .variants()
+                    .into_iter()
+                    .find(|&unit_id| unit_manifest.name(unit_id) == argument)
+                    .map(ViewerCommand::SpawnUnit)
+                    .ok_or_else(|| format!("Unknown unit {argument}"))
+            }
+            _ => Err(format!("Unknown command {text}")),
+        }
+    }
+}
+
+/// The votes cast in the current round.
+#[derive(Debug, Default)]
+struct VoteTally {
+    /// Each viewer's vote, in the order that viewers first voted.
+    votes: Vec<(String, ViewerCommand)>,
+}
+
+impl VoteTally {
+    /// Records the `viewer`'s vote, replacing any earlier vote of theirs.
+    fn vote(&mut self, viewer: String, command: ViewerCommand) {
+        match self.votes.iter_mut().find(|(voter, _)| *voter == viewer) {
+            Some((_, previous)) => *previous = command,
+            None => self.votes.push((viewer, command)),
+        }
+    }
+
+    /// Ends the round, returning the most popular command, if any votes were cast.
+    ///
+    /// Ties are won by the command that was voted for first.
+    fn close(&mut self) -> Option<ViewerCommand> {
+        let mut counts: Vec<(ViewerCommand, usize)> = Vec::new();
+        for (_, command) in self.votes.drain(..) {
+            match counts.iter_mut().find(|(counted, _)| *counted == command) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((command, 1)),
+            }
+        }
+
+        counts
+            .into_iter()
+            .fold(None, |winner, (command, count)| match winner {
+                Some((_, best)) if best >= count => winner,
+                _ => Some((command, count)),
+            })
+            .map(|(command, _)| command)
+    }
+}
+
+/// The connections to chat bridges, and the votes received from them.
+#[derive(Resource, Debug)]
+struct ViewerVotes {
+    /// Where votes are received from, and what viewers may vote for.
+    config: ViewerEventsConfig,
+    /// Listens for new chat bridges.
+    listener: TcpListener,
+    /// The open connections, along with any partial message received from each.
+    connections: Vec<(TcpStream, Vec<u8>)>,
+    /// The votes cast in the current round.
+    tally: VoteTally,
+    /// The number of seconds until the current round of voting ends.
+    time_remaining: f32,
+}
+
+impl ViewerVotes {
+    /// Starts the first round of voting.
+    fn new(config: ViewerEventsConfig, listener: TcpListener) -> Self {
+        ViewerVotes {
+            time_remaining: config.voting_period,
+            config,
+            listener,
+            connections: Vec::new(),
+            tally: VoteTally::default(),
+        }
+    }
+
+    /// Reads all votes that have arrived, dropping any connections that have closed.
+    fn receive(&mut self) -> Vec<ViewerVote> {
+        let mut votes = Vec::new();
+        let mut chunk = [0; 4096];
+
+        self.connections.retain_mut(|(stream, buffer)| {
+            let open = loop {
+                match stream.read(&mut chunk) {
+                    Ok(0) => break false,
+                    Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => break true,
+                    Err(error) => {
+                        warn!("Lost connection to viewer events bridge: {error}");
+                        break false;
+                    }
+                }
+            };
+
+            votes.extend(split_messages::<ViewerVote>(buffer));
+            open
+        });
+
+        votes
+    }
+}
+
+/// Accepts new chat bridges, and tallies the votes they send.
+fn collect_votes(mut viewer_votes: ResMut<ViewerVotes>, unit_manifest: Res<UnitManifest>) {
+    loop {
+        match viewer_votes.listener.accept() {
+            Ok((stream, address)) => match stream.set_nonblocking(true) {
+                Ok(()) => {
+                    info!("Viewer events bridge connected from {address}");
+                    viewer_votes.connections.push((stream, Vec::new()));
+                }
+                Err(error) => warn!("Could not accept viewer events bridge: {error}"),
+            },
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) => {
+                warn!("Could not accept viewer events bridge: {error}");
+                break;
+            }
+        }
+    }
+
+    for vote in viewer_votes.receive() {
+        match ViewerCommand::parse(&vote.command, &viewer_votes.config, &unit_manifest) {
+            Ok(command) => viewer_votes.tally.vote(vote.viewer, command),
+            Err(reason) => debug!("Ignoring vote from {}: {reason}", vote.viewer),
+        }
+    }
+}
+
+/// Carries out the most popular command at the end of each round of voting.
+fn resolve_votes(
+    mut viewer_votes: ResMut<ViewerVotes>,
+    time: Res<Time>,
+    mut current_weather: ResMut<CurrentWeather>,
+    mut commands: Commands,
+    unit_manifest: Res<UnitManifest>,
+    maybe_unit_handles: Option<Res<UnitHandles>>,
+    factions: Res<Factions>,
+    map_geometry: Res<MapGeometry>,
+    mut rng: ResMut<GlobalRng>,
+) {
+    viewer_votes.time_remaining -= time.delta_seconds();
+    if viewer_votes.time_remaining > 0. {
+        return;
+    }
+    viewer_votes.time_remaining = viewer_votes.config.voting_period;
+
+    let Some(command) = viewer_votes.tally.close() else {
+        return;
+    };
+
+    info!("Viewers voted for {command:?}");
+    match command {
+        ViewerCommand::SetWeather(weather) => current_weather.set(weather),
+        ViewerCommand::SpawnUnit(unit_id) => {
+            let Some(voxel_pos) = map_geometry
+                .walkable_voxels()
+                .into_iter()
+                .choose(rng.get_mut())
+            else {
+                return;
+            };
+
+            let unit_data = unit_manifest.get(unit_id).clone();
+            let faction = factions.starting_territory(voxel_pos.hex);
+            let unit_bundle = match maybe_unit_handles {
+                Some(unit_handles) => {
+                    UnitBundle::newborn(unit_id, voxel_pos, unit_data, faction, &unit_handles)
+                }
+                None => UnitBundle::testing(unit_id, voxel_pos, unit_data, faction, rng.get_mut()),
+            };
+
+            commands.spawn(unit_bundle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ViewerEventsConfig {
+        ViewerEventsConfig {
+            address: "127.0.0.1:0".parse().unwrap(),
+            voting_period: 1.,
+            spawnable_units: vec!["ant".to_string()],
+        }
+    }
+
+    #[test]
+    fn only_whitelisted_commands_are_accepted() {
+        let unit_manifest = UnitManifest::new();
+        let config = config();
+
+        assert_eq!(
+            ViewerCommand::parse(" Weather Rainy ", &config, &unit_manifest),
+            Ok(ViewerCommand::SetWeather(Weather::Rainy))
+        );
+        assert!(ViewerCommand::parse("weather hail", &config, &unit_manifest).is_err());
+        assert!(ViewerCommand::parse("despawn everything", &config, &unit_manifest).is_err());
+        // Not on the whitelist
+        assert!(ViewerCommand::parse("spawn basket_crab", &config, &unit_manifest).is_err());
+        // On the whitelist, but not in the manifest
+        assert!(ViewerCommand::parse("spawn ant", &config, &unit_manifest).is_err());
+    }
+
+    #[test]
+    fn the_most_popular_command_wins() {
+        let mut tally = VoteTally::default();
+        assert_eq!(tally.close(), None);
+
+        let clear = ViewerCommand::SetWeather(Weather::Clear);
+        let rainy = ViewerCommand::SetWeather(Weather::Rainy);
+
+        tally.vote("a".to_string(), clear);
+        tally.vote("b".to_string(), rainy);
+        assert_eq!(tally.close(), Some(clear));
+        assert_eq!(tally.close(), None);
+
+        tally.vote("a".to_string(), clear);
+        tally.vote("b".to_string(), rainy);
+        tally.vote("c".to_string(), rainy);
+        assert_eq!(tally.close(), Some(rainy));
+    }
+
+    #[test]
+    fn viewers_can_change_their_vote() {
+        let mut tally = VoteTally::default();
+        let clear = ViewerCommand::SetWeather(Weather::Clear);
+        let rainy = ViewerCommand::SetWeather(Weather::Rainy);
+
+        tally.vote("a".to_string(), clear);
+        tally.vote("b".to_string(), clear);
+        tally.vote("a".to_string(), rainy);
+        tally.vote("a".to_string(), rainy);
+        tally.vote("c".to_string(), rainy);
+
+        assert_eq!(tally.votes.len(), 3);
+        assert_eq!(tally.close(), Some(rainy));
+    }
+}
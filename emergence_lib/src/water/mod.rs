@@ -11,7 +11,7 @@ use bevy::prelude::*;
 use derive_more::{Add, AddAssign, Sub, SubAssign};
 use serde::{Deserialize, Serialize};
 
-use crate::geometry::{VoxelPos, MAP_LAYOUT};
+use crate::geometry::{direction_angle, VoxelPos};
 use crate::simulation::time::Days;
 use crate::{
     asset_management::manifest::Id,
@@ -117,14 +117,14 @@ impl WaterConfig {
         },
     };
 
-    /// Converts a number of items of water to a [`Volume`] of water.
-    pub(crate) fn items_to_tiles(&self, items: u32) -> Volume {
-        Volume(items as f32 / self.water_items_per_tile)
+    /// Converts an amount of water to a [`Volume`] of water.
+    pub(crate) fn items_to_tiles(&self, items: f32) -> Volume {
+        Volume(items / self.water_items_per_tile)
     }
 
-    /// Converts a [`Volume`] of water to an equivalent number of items of water.
-    pub(crate) fn tiles_to_items(&self, height: Volume) -> u32 {
-        (height.0 * self.water_items_per_tile) as u32
+    /// Converts a [`Volume`] of water to an equivalent amount of water.
+    pub(crate) fn tiles_to_items(&self, height: Volume) -> f32 {
+        height.0 * self.water_items_per_tile
     }
 }
 
@@ -451,7 +451,7 @@ impl FlowVelocity {
     /// Converts a [`hexx::Direction`] and magnitude into a [`FlowVelocity`].
     fn from_hex_direction(direction: hexx::Direction, magnitude: Volume) -> Self {
         // Empirically this seems to be the correct angle.
-        let angle = direction.angle(MAP_LAYOUT.orientation) + PI;
+        let angle = direction_angle(direction) + PI;
         let x = magnitude * angle.cos();
         let z = magnitude * angle.sin();
 
@@ -7,9 +7,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::Id,
-    crafting::inventories::{CraftingState, InputInventory},
+    crafting::inventories::CraftingState,
     geometry::{Height, MapGeometry, Volume, VoxelPos},
-    items::{item_manifest::ItemManifest, ItemCount},
+    items::bulk::{BulkQuantity, Tanks},
     structures::structure_manifest::{Structure, StructureManifest},
 };
 use bevy::prelude::*;
@@ -68,39 +68,30 @@ impl Display for RootZone {
     }
 }
 
-/// Draws water from the water table if and only if the structure needs more water.
+/// Draws water from the water table into the tanks of structures that need more water.
 // PERF: we could store RootZone as a component on the structure at the cost of some memory.
 // This would give us faster lookups, but force duplication.
 pub(super) fn draw_water_from_roots(
     water_config: Res<WaterConfig>,
-    mut structure_query: Query<(
-        &VoxelPos,
-        &Id<Structure>,
-        &CraftingState,
-        &mut InputInventory,
-    )>,
+    mut structure_query: Query<(&VoxelPos, &Id<Structure>, &CraftingState, &mut Tanks)>,
     water_depth_query: Query<&WaterDepth>,
     mut water_volume_query: Query<&mut WaterVolume>,
     structure_manifest: Res<StructureManifest>,
-    item_manifest: Res<ItemManifest>,
     map_geometry: Res<MapGeometry>,
 ) {
     // TODO: only do this during CraftingState::NeedsInput
-    for (&center, &structure_id, crafting_state, mut input_inventory) in structure_query.iter_mut()
-    {
+    for (&center, &structure_id, crafting_state, mut tanks) in structure_query.iter_mut() {
         if crafting_state != &CraftingState::NeedsInput {
             continue;
         };
 
-        let water_items_requested = input_inventory
-            .inventory()
-            .remaining_space_for_item(Id::water(), &item_manifest);
+        let water_requested = tanks.remaining_space(Id::water());
 
-        if water_items_requested == 0 {
+        if water_requested <= 0. {
             continue;
         };
 
-        let water_tiles_requested = water_config.items_to_tiles(water_items_requested);
+        let water_tiles_requested = water_config.items_to_tiles(water_requested);
 
         let root_zone = match &structure_manifest.get(structure_id).root_zone {
             Some(root_zone) => root_zone,
@@ -124,10 +115,9 @@ pub(super) fn draw_water_from_roots(
             total_water += water_volume.remove(water_per_tile);
         }
 
-        let water_items_produced = water_config.tiles_to_items(total_water);
-        let _ = input_inventory.fill_with_items(
-            &ItemCount::new(Id::water(), water_items_produced),
-            &item_manifest,
-        );
+        tanks.fill_all(&[BulkQuantity::new(
+            Id::water(),
+            water_config.tiles_to_items(total_water),
+        )]);
     }
 }
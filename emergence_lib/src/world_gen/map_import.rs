@@ -0,0 +1,237 @@
+//! Turns images into [`Scenario`]s, so that maps can be drawn in an image editor or taken from real topography.
+//!
+//! A [`MapImport`] is described by a small JSON file with the `.mapimport` extension,
+//! stored next to its images in the scenarios directory of a [`Profile`](crate::milestones::Profile).
+//! The brightness of each pixel in the grayscale heightmap sets the height of the tile above it,
+//! while the color of each pixel in the optional biome map picks its terrain type.
+//!
+//! Each tile is sampled from the single pixel under its center.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use bevy::math::Vec2;
+use hexx::{shapes::hexagon, Hex};
+use image::{GrayImage, RgbImage};
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::{hex_to_xz, DiscreteHeight};
+
+use super::scenario::{Scenario, ScenarioError, ScenarioTile};
+
+/// The settings used to convert a heightmap, and optionally a biome map, into a [`Scenario`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapImport {
+    /// The name shown when choosing this map.
+    pub name: String,
+    /// The grayscale image whose brightness sets the height of each tile.
+    ///
+    /// Relative paths are relative to the file describing this import.
+    pub heightmap: PathBuf,
+    /// The image whose colors set the terrain type of each tile.
+    ///
+    /// This should be the same size as the heightmap.
+    /// If this is missing, terrain types are generated randomly.
+    #[serde(default)]
+    pub biome_map: Option<PathBuf>,
+    /// The width of a tile, measured in pixels of the source images.
+    ///
+    /// Larger values produce smaller maps from the same image.
+    #[serde(default = "MapImport::default_pixels_per_tile")]
+    pub pixels_per_tile: f32,
+    /// The height of tiles under pure white pixels.
+    ///
+    /// Black pixels are always at [`DiscreteHeight::ZERO`].
+    #[serde(default = "MapImport::default_max_height")]
+    pub max_height: DiscreteHeight,
+    /// The color used for each terrain type in the biome map, keyed by the name of the terrain type.
+    ///
+    /// Pixels are matched to the closest color, so slightly blurred or antialiased images still work.
+    #[serde(default)]
+    pub biome_colors: BTreeMap<String, [u8; 3]>,
+}
+
+impl MapImport {
+    /// The file extension used for files describing a map import.
+    pub const EXTENSION: &'static str = "mapimport";
+
+    /// The default value of [`MapImport::pixels_per_tile`].
+    fn default_pixels_per_tile() -> f32 {
+        1.
+    }
+
+    /// The default value of [`MapImport::max_height`].
+    fn default_max_height() -> DiscreteHeight {
+        DiscreteHeight(20)
+    }
+
+    /// Loads the import described by the file at `path`, and converts its images into a [`Scenario`].
+    pub fn load(path: &Path) -> Result<Scenario, ScenarioError> {
+        let json = std::fs::read_to_string(path)?;
+        let map_import: MapImport = serde_json::from_str(&json)?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        let heightmap = image::open(directory.join(&map_import.heightmap))?.to_luma8();
+        let biome_map = match &map_import.biome_map {
+            Some(biome_map) => Some(image::open(directory.join(biome_map))?.to_rgb8()),
+            None => None,
+        };
+
+        Ok(map_import.to_scenario(&heightmap, biome_map.as_ref()))
+    }
+
+    /// The radius of the largest map that fits inside of an image with the given dimensions.
+    pub fn map_radius(&self, width: u32, height: u32) -> u32 {
+        let pixels_per_tile = self.pixels_per_tile.max(f32::EPSILON);
+        // With flat-topped hexes, the outermost tile centers are 1.5 tiles apart horizontally, and √3 tiles apart vertically
+        let horizontal = (width as f32 / 2. - 0.5) / (1.5 * pixels_per_tile);
+        let vertical = (height as f32 / 2. - 0.5) / (3f32.sqrt() * pixels_per_tile);
+
+        horizontal.min(vertical).max(0.).floor() as u32
+    }
+
+    /// Converts `heightmap` and `biome_map` into a [`Scenario`], with no starting structures.
+    pub fn to_scenario(&self, heightmap: &GrayImage, biome_map: Option<&RgbImage>) -> Scenario {
+        let map_radius = self.map_radius(heightmap.width(), heightmap.height());
+
+        let tiles = hexagon(Hex::ZERO, map_radius)
+            .map(|hex| {
+                let (x, y) = self.pixel(hex, heightmap.width(), heightmap.height());
+                let brightness = heightmap.get_pixel(x, y).0[0];
+
+                let terrain = biome_map.and_then(|biome_map| {
+                    let (x, y) = self.pixel(hex, biome_map.width(), biome_map.height());
+                    self.closest_terrain(biome_map.get_pixel(x, y).0)
+                });
+
+                ScenarioTile {
+                    hex,
+                    terrain,
+                    height: self.height(brightness),
+                    water: None,
+                }
+            })
+            .collect();
+
+        Scenario {
+            name: self.name.clone(),
+            map_radius,
+            tiles,
+            structures: Vec::new(),
+            units: Vec::new(),
+        }
+    }
+
+    /// The pixel under the center of `hex`, in an image with the given dimensions.
+    ///
+    /// The center of the map is placed at the center of the image.
+    fn pixel(&self, hex: Hex, width: u32, height: u32) -> (u32, u32) {
+        let center = Vec2::new(width as f32, height as f32) / 2.;
+        let pos = center + hex_to_xz(hex) * self.pixels_per_tile;
+
+        let x = (pos.x.max(0.) as u32).min(width.saturating_sub(1));
+        let y = (pos.y.max(0.) as u32).min(height.saturating_sub(1));
+        (x, y)
+    }
+
+    /// The height of a tile under a heightmap pixel with the given `brightness`.
+    fn height(&self, brightness: u8) -> DiscreteHeight {
+        let fraction = brightness as f32 / u8::MAX as f32;
+        DiscreteHeight((fraction * self.max_height.0 as f32).round() as u8)
+    }
+
+    /// The terrain type whose color in [`MapImport::biome_colors`] is closest to `color`.
+    fn closest_terrain(&self, color: [u8; 3]) -> Option<String> {
+        let distance = |other: &[u8; 3]| -> u32 {
+            color
+                .iter()
+                .zip(other)
+                .map(|(&a, &b)| (a as i32 - b as i32).pow(2) as u32)
+                .sum()
+        };
+
+        self.biome_colors
+            .iter()
+            .min_by_key(|(_, other)| distance(other))
+            .map(|(terrain, _)| terrain.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Luma, Rgb};
+
+    use super::*;
+
+    fn map_import() -> MapImport {
+        MapImport {
+            name: "Test".to_string(),
+            heightmap: PathBuf::from("heightmap.png"),
+            biome_map: None,
+            pixels_per_tile: 2.,
+            max_height: DiscreteHeight(10),
+            biome_colors: BTreeMap::from([
+                ("grassy".to_string(), [0, 255, 0]),
+                ("rocky".to_string(), [128, 128, 128]),
+            ]),
+        }
+    }
+
+    #[test]
+    fn map_fits_inside_of_image() {
+        let map_import = map_import();
+        let map_radius = map_import.map_radius(64, 64);
+        assert!(map_radius > 0);
+
+        for hex in hexagon(Hex::ZERO, map_radius) {
+            let pos = Vec2::new(32., 32.) + hex_to_xz(hex) * map_import.pixels_per_tile;
+            assert!(
+                pos.x >= 0. && pos.x < 64.,
+                "{hex:?} is outside of the image"
+            );
+            assert!(
+                pos.y >= 0. && pos.y < 64.,
+                "{hex:?} is outside of the image"
+            );
+        }
+    }
+
+    #[test]
+    fn larger_scale_produces_smaller_maps() {
+        let mut map_import = map_import();
+        let small_scale = map_import.map_radius(64, 64);
+        map_import.pixels_per_tile *= 2.;
+        let large_scale = map_import.map_radius(64, 64);
+
+        assert!(large_scale < small_scale);
+    }
+
+    #[test]
+    fn brightness_sets_height() {
+        let map_import = map_import();
+        let black = GrayImage::from_pixel(16, 16, Luma([0]));
+        let white = GrayImage::from_pixel(16, 16, Luma([u8::MAX]));
+
+        for tile in map_import.to_scenario(&black, None).tiles {
+            assert_eq!(tile.height, DiscreteHeight::ZERO);
+            assert_eq!(tile.terrain, None);
+        }
+
+        for tile in map_import.to_scenario(&white, None).tiles {
+            assert_eq!(tile.height, map_import.max_height);
+        }
+    }
+
+    #[test]
+    fn biome_colors_are_matched_to_the_closest_terrain() {
+        let map_import = map_import();
+        let heightmap = GrayImage::from_pixel(16, 16, Luma([0]));
+        let biome_map = RgbImage::from_pixel(16, 16, Rgb([20, 230, 10]));
+
+        for tile in map_import.to_scenario(&heightmap, Some(&biome_map)).tiles {
+            assert_eq!(tile.terrain.as_deref(), Some("grassy"));
+        }
+    }
+}
@@ -1,10 +1,13 @@
 //! Generating starting terrain and organisms
 use crate::asset_management::manifest::Id;
 use crate::asset_management::AssetState;
+use crate::factions::{diplomacy::Relationships, Factions};
+use crate::game_state::GameState;
 use crate::structures::structure_manifest::Structure;
 use crate::terrain::terrain_manifest::Terrain;
 use crate::units::unit_manifest::Unit;
 use crate::utils::noise::SimplexSettings;
+use crate::world_gen::scenario::Scenario;
 use crate::world_gen::structure_generation::generate_structures;
 use crate::world_gen::unit_generation::{generate_units, randomize_starting_organisms};
 
@@ -16,6 +19,9 @@ use bevy::prelude::*;
 use bevy::utils::HashMap;
 use bevy_framepace::{FramepaceSettings, Limiter};
 
+pub mod map_import;
+pub mod preview;
+pub mod scenario;
 mod structure_generation;
 mod terrain_generation;
 mod unit_generation;
@@ -31,6 +37,8 @@ impl Plugin for GenerationPlugin {
         info!("Building Generation plugin...");
         app.add_state::<WorldGenState>()
             .insert_resource(self.config.clone())
+            .insert_resource(Factions::new(self.config.n_ai_factions))
+            .insert_resource(self.config.relationships.clone())
             .add_systems(
                 OnEnter(WorldGenState::Generating),
                 (
@@ -79,9 +87,17 @@ impl WorldGenState {
         mut next_world_gen_state: ResMut<NextState<WorldGenState>>,
         mut maybe_frame_pace_settings: Option<ResMut<FramepaceSettings>>,
         maybe_asset_state: Option<Res<State<AssetState>>>,
+        maybe_game_state: Option<Res<State<GameState>>>,
     ) {
         match world_gen_state.get() {
             WorldGenState::Waiting => {
+                // Wait for the player to start a game from the menus
+                if let Some(game_state) = maybe_game_state {
+                    if !game_state.get().is_in_world() {
+                        return;
+                    }
+                }
+
                 if let Some(frame_pace_settings) = maybe_frame_pace_settings.as_mut() {
                     // Don't limit the tick rate while generating the world
                     if !matches!(frame_pace_settings.limiter, Limiter::Off) {
@@ -130,9 +146,21 @@ pub struct GenerationConfig {
     /// The seed used to generate the world.
     pub seed: u64,
     /// Radius of the map.
-    pub(super) map_radius: u32,
+    pub map_radius: u32,
+    /// The hand-made map to play on, if any.
+    ///
+    /// When set, this replaces the generated terrain, water, landmarks and structures,
+    /// and its radius is used in place of [`GenerationConfig::map_radius`].
+    pub scenario: Option<Scenario>,
     /// How long to simulate the world before starting the game.
     number_of_burn_in_ticks: u32,
+    /// The number of AI-controlled colonies that compete with the player.
+    ///
+    /// The map is split into starting territories, one per faction, and each generated unit joins the faction whose territory it starts in.
+    /// None of the built-in configurations include any rivals, so the player starts with every unit.
+    pub n_ai_factions: u8,
+    /// How each faction treats the others at the start of the game.
+    relationships: Relationships,
     /// Chance that each tile contains a landmark of the given type.
     landmark_chances: HashMap<Id<Structure>, f32>,
     /// Chance that each tile contains a unit of the given type.
@@ -171,7 +199,10 @@ impl GenerationConfig {
         GenerationConfig {
             seed: 0,
             map_radius: 30,
+            scenario: None,
             number_of_burn_in_ticks: 0,
+            n_ai_factions: 0,
+            relationships: Relationships::default(),
             unit_chances,
             landmark_chances,
             structure_chances,
@@ -193,6 +224,13 @@ impl GenerationConfig {
         }
     }
 
+    /// The radius of the map that will be generated.
+    pub fn effective_map_radius(&self) -> u32 {
+        self.scenario
+            .as_ref()
+            .map_or(self.map_radius, |scenario| scenario.map_radius)
+    }
+
     /// A small flat map for testing.
     pub fn flat() -> Self {
         let mut terrain_weights: HashMap<Id<Terrain>, f32> = HashMap::new();
@@ -216,7 +254,10 @@ impl GenerationConfig {
         GenerationConfig {
             seed: 0,
             map_radius: 10,
+            scenario: None,
             number_of_burn_in_ticks: 0,
+            n_ai_factions: 0,
+            relationships: Relationships::default(),
             unit_chances,
             landmark_chances,
             structure_chances,
@@ -258,7 +299,10 @@ impl GenerationConfig {
         GenerationConfig {
             seed: 0,
             map_radius: 3,
+            scenario: None,
             number_of_burn_in_ticks: 0,
+            n_ai_factions: 0,
+            relationships: Relationships::default(),
             unit_chances,
             landmark_chances,
             structure_chances,
@@ -285,6 +329,7 @@ impl GenerationConfig {
 mod tests {
     use crate::asset_management::manifest::DummyManifestPlugin;
     use crate::geometry::{MapGeometry, VoxelPos};
+    use crate::simulation::game_rules::Difficulty;
     use crate::simulation::rng::GlobalRng;
     use crate::water::WaterConfig;
 
@@ -401,6 +446,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn harder_games_start_with_fewer_units() {
+        let count_units = |difficulty: Difficulty| {
+            let mut config = GenerationConfig::testing();
+            config.unit_chances =
+                HashMap::from_iter([(Id::from_name("simple_unit".to_string()), 0.4)]);
+
+            let mut app = App::new();
+            app.add_plugins(DummyManifestPlugin);
+            app.insert_resource(config);
+            app.insert_resource(Factions::default());
+            app.insert_resource(difficulty.rules());
+            app.insert_resource(GlobalRng::new(0));
+            app.add_systems(Startup, (generate_terrain, generate_units).chain());
+
+            app.update();
+
+            app.world
+                .query_filtered::<(), With<Id<Unit>>>()
+                .iter(&app.world)
+                .count()
+        };
+
+        let easy = count_units(Difficulty::Sandbox);
+        let hard = count_units(Difficulty::Hardcore);
+        assert!(
+            hard < easy,
+            "Hardcore started with {hard} units, but Sandbox started with {easy}"
+        );
+    }
+
     #[test]
     fn can_generate_landmarks() {
         let mut app = App::new();
@@ -0,0 +1,162 @@
+//! A cheap preview of the world that a [`GenerationConfig`] will produce, shown before the game starts.
+//!
+//! Only the terrain is previewed: this skips spawning entities, landmarks, water and organisms entirely,
+//! so it is fast enough to regenerate every time the player changes the seed.
+//! Tiles set by a hand-made [`Scenario`](super::scenario::Scenario) are previewed as they were authored.
+
+use std::hash::{Hash, Hasher};
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    utils::HashMap,
+};
+use hexx::{shapes::hexagon, Hex};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{xz_to_hex, DiscreteHeight},
+    simulation::rng::GlobalRng,
+    terrain::terrain_manifest::Terrain,
+};
+
+use super::{
+    terrain_generation::{choose_terrain, terrain_height},
+    GenerationConfig,
+};
+
+/// The terrain of each tile in the world that a [`GenerationConfig`] will generate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldPreview {
+    /// The radius of the map.
+    map_radius: u32,
+    /// The terrain type and height of each tile.
+    tiles: HashMap<Hex, (Id<Terrain>, DiscreteHeight)>,
+}
+
+impl WorldPreview {
+    /// Computes the terrain that would be generated by `generation_config`.
+    ///
+    /// This consumes random numbers in the same order as full world generation,
+    /// so the preview matches the world that is eventually generated from the same seed.
+    pub fn generate(generation_config: &GenerationConfig) -> Self {
+        let mut rng = GlobalRng::new(generation_config.seed);
+        let terrain_weights = &generation_config.terrain_weights;
+        let terrain_variants: Vec<Id<Terrain>> = terrain_weights.keys().copied().collect();
+
+        let map_radius = generation_config.effective_map_radius();
+        let scenario_tiles = generation_config
+            .scenario
+            .as_ref()
+            .map(|scenario| scenario.tile_map())
+            .unwrap_or_default();
+
+        let tiles = hexagon(Hex::ZERO, map_radius)
+            .map(|hex| {
+                let terrain = choose_terrain(rng.get_mut(), &terrain_variants, terrain_weights);
+                let tile = match scenario_tiles.get(&hex) {
+                    Some(tile) => match &tile.terrain {
+                        Some(name) => (Id::from_name(name.clone()), tile.height),
+                        None => (terrain, tile.height),
+                    },
+                    None => (terrain, terrain_height(hex, generation_config)),
+                };
+                (hex, tile)
+            })
+            .collect();
+
+        WorldPreview { map_radius, tiles }
+    }
+
+    /// The terrain type and height of the tile at `hex`, if it is on the map.
+    pub fn get(&self, hex: Hex) -> Option<(Id<Terrain>, DiscreteHeight)> {
+        self.tiles.get(&hex).copied()
+    }
+
+    /// Draws the preview as a square image, `size` pixels across.
+    ///
+    /// Each terrain type is drawn in its own color, with higher tiles drawn brighter.
+    pub fn to_image(&self, size: u32) -> Image {
+        let extent = Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        };
+
+        let max_height = self
+            .tiles
+            .values()
+            .map(|(_, height)| height.0)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+
+        // Flat hexes are about 2 units wide, so this fits the whole map in the image with a small margin
+        let world_width = 2. * (self.map_radius as f32 + 1.) * 1.1;
+        let scale = world_width / size as f32;
+
+        let mut data = Vec::with_capacity(size as usize * size as usize * 4);
+        for row in 0..size {
+            for column in 0..size {
+                let xz = Vec2::new(
+                    (column as f32 - size as f32 / 2.) * scale,
+                    (row as f32 - size as f32 / 2.) * scale,
+                );
+
+                let color = match self.get(xz_to_hex(xz)) {
+                    Some((terrain, height)) => {
+                        let brightness = 0.3 + 0.5 * height.0 as f32 / max_height as f32;
+                        Self::terrain_color(terrain, brightness)
+                    }
+                    None => Color::NONE,
+                };
+
+                data.extend(color.as_rgba_u8());
+            }
+        }
+
+        Image::new(
+            extent,
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    /// A distinct color for each terrain type, with the given lightness.
+    fn terrain_color(terrain: Id<Terrain>, lightness: f32) -> Color {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        terrain.hash(&mut hasher);
+        let hue = (hasher.finish() % 360) as f32;
+
+        Color::hsl(hue, 0.5, lightness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_depend_on_the_seed() {
+        let mut generation_config = GenerationConfig::testing();
+        generation_config.map_radius = 10;
+
+        let preview = WorldPreview::generate(&generation_config);
+        assert_eq!(preview, WorldPreview::generate(&generation_config));
+        assert_eq!(preview.tiles.len(), hexagon(Hex::ZERO, 10).count());
+
+        generation_config.seed += 1;
+        assert_ne!(preview, WorldPreview::generate(&generation_config));
+    }
+
+    #[test]
+    fn preview_images_have_the_requested_size() {
+        let preview = WorldPreview::generate(&GenerationConfig::testing());
+        let image = preview.to_image(32);
+
+        assert_eq!(image.texture_descriptor.size.width, 32);
+        assert_eq!(image.texture_descriptor.size.height, 32);
+        assert_eq!(image.data.len(), 32 * 32 * 4);
+    }
+}
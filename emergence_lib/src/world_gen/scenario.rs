@@ -0,0 +1,304 @@
+//! Hand-made maps, authored in the map editor and played from the new game menu.
+//!
+//! A [`Scenario`] replaces the randomly generated terrain, water and starting structures of a world.
+//! Units are generated as usual, unless the scenario lists the units that it starts with.
+//!
+//! Terrain and structures are referred to by name rather than by [`Id`](crate::asset_management::manifest::Id),
+//! so that scenarios survive changes to the manifests.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{log::warn, prelude::*, utils::HashMap};
+use hexx::{Direction, Hex};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    geometry::{DiscreteHeight, Facing, MapGeometry, VoxelPos},
+    milestones::Profile,
+    player_interaction::{bulk_commands::Favorite, nicknames::Nickname},
+    structures::structure_manifest::{Structure, StructureManifest},
+    terrain::{
+        terrain_manifest::{Terrain, TerrainManifest},
+        trails::WornTrail,
+    },
+    units::unit_manifest::{Unit, UnitManifest},
+    water::WaterVolume,
+};
+
+use super::map_import::MapImport;
+
+/// A hand-made map, which is loaded in place of a randomly generated world.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    /// The name shown when choosing this scenario.
+    pub name: String,
+    /// The radius of the map.
+    pub map_radius: u32,
+    /// The terrain of each tile.
+    ///
+    /// Tiles that are missing are generated randomly.
+    pub tiles: Vec<ScenarioTile>,
+    /// The structures that the world starts with, including its flora.
+    pub structures: Vec<ScenarioStructure>,
+    /// The units that the world starts with.
+    ///
+    /// If this is empty, units are generated randomly.
+    #[serde(default)]
+    pub units: Vec<ScenarioUnit>,
+}
+
+/// The terrain of a single tile in a [`Scenario`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioTile {
+    /// The position of the tile.
+    pub hex: Hex,
+    /// The name of the terrain type.
+    ///
+    /// If this is missing, the terrain type is generated randomly.
+    #[serde(default)]
+    pub terrain: Option<String>,
+    /// The height of the terrain.
+    pub height: DiscreteHeight,
+    /// The volume of water stored on and in the tile.
+    ///
+    /// If this is missing, the tile starts with the usual amount of water.
+    #[serde(default)]
+    pub water: Option<f32>,
+}
+
+/// A structure that a [`Scenario`] starts with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioStructure {
+    /// The position of the tile that the structure stands on.
+    pub hex: Hex,
+    /// The name of the structure type.
+    pub structure: String,
+    /// The direction that the structure faces.
+    pub facing: Direction,
+    /// The name given to this structure by the player, if any.
+    #[serde(default)]
+    pub nickname: Option<String>,
+    /// Has the player marked this structure as a favorite?
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// A unit that a [`Scenario`] starts with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioUnit {
+    /// The position of the tile that the unit stands on.
+    pub hex: Hex,
+    /// The name of the unit type.
+    pub unit: String,
+    /// The name given to this unit by the player, if any.
+    #[serde(default)]
+    pub nickname: Option<String>,
+    /// Has the player marked this unit as a favorite?
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// An error produced when reading or writing a [`Scenario`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ScenarioError {
+    /// An [IO](std::io) Error
+    #[error("Could not access scenario file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [serde_json](serde_json) Error
+    #[error("Could not parse scenario: {0}")]
+    Json(#[from] serde_json::Error),
+    /// An [image](image) Error, produced when importing a map
+    #[error("Could not read map image: {0}")]
+    Image(#[from] image::ImageError),
+}
+
+impl Scenario {
+    /// The file extension used for scenario files.
+    pub const EXTENSION: &'static str = "scenario";
+
+    /// The tiles of this scenario, indexed by their position.
+    pub fn tile_map(&self) -> HashMap<Hex, &ScenarioTile> {
+        self.tiles.iter().map(|tile| (tile.hex, tile)).collect()
+    }
+
+    /// Records the terrain, water, structures and units of the current `world` as a scenario called `name`.
+    ///
+    /// Ghosts and previews are not part of the world, and are skipped.
+    /// Trails are worn by units as they play, so the terrain that each trail was worn from is recorded instead.
+    pub fn capture(world: &mut World, name: String) -> Self {
+        let mut terrain_query =
+            world.query::<(&Id<Terrain>, &VoxelPos, &WaterVolume, Option<&WornTrail>)>();
+        let mut structure_query = world.query_filtered::<(
+            &Id<Structure>,
+            &VoxelPos,
+            &Facing,
+            Option<&Nickname>,
+            Has<Favorite>,
+        ), (Without<Ghost>, Without<Preview>)>();
+        let mut unit_query =
+            world.query::<(&Id<Unit>, &VoxelPos, Option<&Nickname>, Has<Favorite>)>();
+        let terrain_manifest = world.resource::<TerrainManifest>();
+        let structure_manifest = world.resource::<StructureManifest>();
+        let unit_manifest = world.resource::<UnitManifest>();
+
+        Scenario {
+            name,
+            map_radius: world.resource::<MapGeometry>().radius,
+            tiles: terrain_query
+                .iter(world)
+                .map(|(&terrain_id, voxel_pos, water_volume, maybe_worn_trail)| {
+                    let terrain_id = maybe_worn_trail
+                        .map_or(terrain_id, |worn_trail| worn_trail.original_terrain);
+
+                    ScenarioTile {
+                        hex: voxel_pos.hex,
+                        terrain: Some(terrain_manifest.name(terrain_id).to_string()),
+                        height: voxel_pos.height,
+                        water: Some(water_volume.volume().0),
+                    }
+                })
+                .collect(),
+            structures: structure_query
+                .iter(world)
+                .map(
+                    |(&structure_id, voxel_pos, facing, maybe_nickname, favorite)| {
+                        ScenarioStructure {
+                            hex: voxel_pos.hex,
+                            structure: structure_manifest.name(structure_id).to_string(),
+                            facing: facing.direction,
+                            nickname: maybe_nickname.map(|nickname| nickname.as_str().to_string()),
+                            favorite,
+                        }
+                    },
+                )
+                .collect(),
+            units: unit_query
+                .iter(world)
+                .map(
+                    |(&unit_id, voxel_pos, maybe_nickname, favorite)| ScenarioUnit {
+                        hex: voxel_pos.hex,
+                        unit: unit_manifest.name(unit_id).to_string(),
+                        nickname: maybe_nickname.map(|nickname| nickname.as_str().to_string()),
+                        favorite,
+                    },
+                )
+                .collect(),
+        }
+    }
+
+    /// Writes this scenario to the file at `path`, replacing it if it already exists.
+    pub fn save(&self, path: &Path) -> Result<(), ScenarioError> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Loads the scenario stored in the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, ScenarioError> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// A path in the scenarios directory of `profile` that no scenario has been saved to yet.
+    pub fn unused_path(profile: &Profile) -> PathBuf {
+        let directory = profile.scenarios_directory();
+
+        (1..)
+            .map(|n| directory.join(format!("map_{n}.{}", Self::EXTENSION)))
+            .find(|path| !path.exists())
+            .unwrap()
+    }
+}
+
+/// Lists the scenarios stored for `profile`, sorted by name.
+///
+/// This includes maps imported from images, which are described by [`MapImport`] files.
+/// Files that are not readable scenarios are skipped.
+pub fn list_scenarios(profile: &Profile) -> Vec<Scenario> {
+    let Ok(entries) = std::fs::read_dir(profile.scenarios_directory()) else {
+        return Vec::new();
+    };
+
+    let mut scenarios: Vec<Scenario> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let result = match path.extension()?.to_str()? {
+                Scenario::EXTENSION => Scenario::load(&path),
+                MapImport::EXTENSION => MapImport::load(&path),
+                _ => return None,
+            };
+            Some((path, result))
+        })
+        .filter_map(|(path, result)| match result {
+            Ok(scenario) => Some(scenario),
+            Err(error) => {
+                warn!("Skipping scenario {}: {error}", path.display());
+                None
+            }
+        })
+        .collect();
+
+    scenarios.sort_by(|a, b| a.name.cmp(&b.name));
+    scenarios
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario() -> Scenario {
+        Scenario {
+            name: "Test".to_string(),
+            map_radius: 2,
+            tiles: vec![ScenarioTile {
+                hex: Hex::new(1, -1),
+                terrain: Some("grassy".to_string()),
+                height: DiscreteHeight(3),
+                water: Some(0.5),
+            }],
+            structures: vec![ScenarioStructure {
+                hex: Hex::new(1, -1),
+                structure: "acacia".to_string(),
+                facing: Direction::Top,
+                nickname: Some("Old Gnarly".to_string()),
+                favorite: true,
+            }],
+            units: vec![ScenarioUnit {
+                hex: Hex::new(0, 1),
+                unit: "ant".to_string(),
+                nickname: Some("Queenie".to_string()),
+                favorite: true,
+            }],
+        }
+    }
+
+    #[test]
+    fn scenarios_round_trip() {
+        let scenario = scenario();
+        let json = serde_json::to_string(&scenario).unwrap();
+        let deserialized: Scenario = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(scenario, deserialized);
+    }
+
+    #[test]
+    fn tiles_can_be_looked_up_by_position() {
+        let scenario = scenario();
+        let tile_map = scenario.tile_map();
+
+        assert_eq!(
+            tile_map[&Hex::new(1, -1)].terrain.as_deref(),
+            Some("grassy")
+        );
+        assert!(!tile_map.contains_key(&Hex::ZERO));
+    }
+}
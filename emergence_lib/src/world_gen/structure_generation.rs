@@ -1,8 +1,12 @@
 //! Initializes organisms in the world.
 
-use crate::geometry::{Facing, MapGeometry};
+use crate::asset_management::manifest::Id;
+use crate::geometry::{Facing, MapGeometry, VoxelPos};
 use crate::organisms::energy::StartingEnergy;
+use crate::player_interaction::bulk_commands::Favorite;
 use crate::player_interaction::clipboard::ClipboardData;
+use crate::player_interaction::nicknames::Nickname;
+use crate::simulation::game_rules::GameRules;
 use crate::simulation::rng::GlobalRng;
 use crate::structures::commands::StructureCommandsExt;
 use crate::structures::structure_manifest::StructureManifest;
@@ -10,7 +14,7 @@ use crate::structures::structure_manifest::StructureManifest;
 use bevy::prelude::*;
 use rand::Rng;
 
-use super::GenerationConfig;
+use super::{scenario::Scenario, GenerationConfig};
 
 /// Create starting structures according to [`GenerationConfig`], and randomly place them on
 /// top of the terrain.
@@ -19,14 +23,20 @@ pub(super) fn generate_structures(
     config: Res<GenerationConfig>,
     structure_manifest: Res<StructureManifest>,
     map_geometry: Res<MapGeometry>,
+    game_rules: Res<GameRules>,
     mut rng: ResMut<GlobalRng>,
 ) {
+    if let Some(scenario) = &config.scenario {
+        spawn_scenario_structures(scenario, &mut commands, &structure_manifest, &map_geometry);
+        return;
+    }
+
     info!("Generating structures...");
 
     // Collect out so we can mutate the height map to flatten the terrain while in the loop
     for voxel_pos in map_geometry.walkable_voxels() {
         for (&structure_id, &chance) in &config.structure_chances {
-            if rng.gen::<f32>() < chance {
+            if rng.gen::<f32>() < game_rules.starting_structure_chance(chance) {
                 let mut clipboard_data =
                     ClipboardData::generate_from_id(structure_id, &structure_manifest);
                 let facing = Facing::random(rng.get_mut());
@@ -43,9 +53,80 @@ pub(super) fn generate_structures(
                         voxel_pos,
                         ClipboardData::generate_from_id(structure_id, &structure_manifest),
                         StartingEnergy::Random,
+                        None,
                     );
                 }
             }
         }
     }
 }
+
+/// Places the structures of a hand-made [`Scenario`] on top of its terrain.
+///
+/// Structures that are unknown, or that don't fit where they were placed, are skipped.
+fn spawn_scenario_structures(
+    scenario: &Scenario,
+    commands: &mut Commands,
+    structure_manifest: &StructureManifest,
+    map_geometry: &MapGeometry,
+) {
+    info!(
+        "Placing the structures of the {} scenario...",
+        scenario.name
+    );
+
+    for scenario_structure in &scenario.structures {
+        let structure_id = Id::from_name(scenario_structure.structure.clone());
+        if !structure_manifest.data_map().contains_key(&structure_id) {
+            warn!(
+                "Unknown structure type {} in scenario",
+                scenario_structure.structure
+            );
+            continue;
+        }
+
+        let Ok(height) = map_geometry.get_height(scenario_structure.hex) else {
+            continue;
+        };
+        let voxel_pos = VoxelPos {
+            hex: scenario_structure.hex,
+            height,
+        }
+        .above();
+
+        let mut clipboard_data = ClipboardData::generate_from_id(structure_id, structure_manifest);
+        clipboard_data.facing = Facing {
+            direction: scenario_structure.facing,
+        };
+
+        let starting_energy = match structure_manifest.get(structure_id).organism_variety {
+            Some(_) => StartingEnergy::Random,
+            None => StartingEnergy::NotAnOrganism,
+        };
+
+        commands.spawn_structure(voxel_pos, clipboard_data, starting_energy, None);
+
+        let nickname = scenario_structure
+            .nickname
+            .as_deref()
+            .and_then(Nickname::new);
+        let favorite = scenario_structure.favorite;
+
+        if nickname.is_some() || favorite {
+            // The structure only exists once the spawn command above has been applied
+            commands.add(move |world: &mut World| {
+                let Some(entity) = world.resource::<MapGeometry>().get_structure(voxel_pos) else {
+                    return;
+                };
+
+                let mut entity_mut = world.entity_mut(entity);
+                if let Some(nickname) = nickname {
+                    entity_mut.insert(nickname);
+                }
+                if favorite {
+                    entity_mut.insert(Favorite);
+                }
+            });
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     asset_management::manifest::Id,
-    geometry::{DiscreteHeight, Facing, MapGeometry, VoxelPos},
+    geometry::{DiscreteHeight, Facing, MapGeometry, Volume, VoxelPos},
     organisms::energy::StartingEnergy,
     player_interaction::clipboard::ClipboardData,
     simulation::rng::GlobalRng,
@@ -15,7 +15,7 @@ use crate::{
     utils::noise::simplex_noise,
     water::{WaterConfig, WaterVolume},
 };
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use hexx::{shapes::hexagon, Hex};
 use rand::{seq::SliceRandom, Rng};
 
@@ -25,36 +25,35 @@ use super::GenerationConfig;
 pub(crate) fn generate_terrain(world: &mut World) {
     info!("Generating terrain...");
     let generation_config = world.resource::<GenerationConfig>().clone();
-    let map_radius = generation_config.map_radius;
-    let terrain_weights = generation_config.terrain_weights;
+    let map_radius = generation_config.effective_map_radius();
+    let terrain_weights = &generation_config.terrain_weights;
     let terrain_variants: Vec<Id<Terrain>> = terrain_weights.keys().copied().collect();
+    let scenario_tiles = generation_config
+        .scenario
+        .as_ref()
+        .map(|scenario| scenario.tile_map())
+        .unwrap_or_default();
 
     let map_geometry = MapGeometry::new(world, map_radius);
     world.insert_resource(map_geometry);
 
     for hex in hexagon(Hex::ZERO, map_radius) {
         let mut rng = world.resource_mut::<GlobalRng>();
-
-        // FIXME: can we not just sample from our terrain_weights directly?
-        let &terrain_id = terrain_variants
-            .choose_weighted(rng.get_mut(), |terrain_type| {
-                terrain_weights.get(terrain_type).unwrap()
-            })
-            .unwrap();
-
-        // Heights are generated in f32 world coordinates to start
-        let hex_height = simplex_noise(
-            hex,
-            &generation_config.low_frequency_noise,
-            generation_config.seed,
-        ) + simplex_noise(
-            hex,
-            &generation_config.high_frequency_noise,
-            generation_config.seed,
-        );
-
-        // And then discretized to the nearest integer height before being used
-        let height = DiscreteHeight::from_world_pos(hex_height);
+        // Random numbers are drawn even for tiles set by the scenario, so the rest of the world is unaffected by its contents
+        let generated_terrain_id =
+            choose_terrain(rng.get_mut(), &terrain_variants, terrain_weights);
+        let (terrain_id, height) = match scenario_tiles.get(&hex) {
+            Some(tile) => match &tile.terrain {
+                Some(name) if is_known_terrain(world, name) => {
+                    (Id::from_name(name.clone()), tile.height)
+                }
+                _ => (generated_terrain_id, tile.height),
+            },
+            None => (
+                generated_terrain_id,
+                terrain_height(hex, &generation_config),
+            ),
+        };
         let map_geometry = world.resource::<MapGeometry>();
         let entity = map_geometry.get_terrain(hex).unwrap();
         let voxel_pos = VoxelPos { hex, height };
@@ -92,6 +91,59 @@ pub(crate) fn generate_terrain(world: &mut World) {
     }
 }
 
+/// Is `name` a terrain type in the [`TerrainManifest`]?
+///
+/// Unknown terrain types found in a scenario are replaced with generated terrain, rather than crashing the game.
+/// The height of the tile is still taken from the scenario.
+fn is_known_terrain(world: &World, name: &str) -> bool {
+    match world.get_resource::<TerrainManifest>() {
+        Some(terrain_manifest) => {
+            let known = terrain_manifest
+                .data_map()
+                .contains_key(&Id::from_name(name.to_string()));
+            if !known {
+                warn!("Unknown terrain type {name} in scenario");
+            }
+            known
+        }
+        // Without a manifest, there is nothing to check against
+        None => true,
+    }
+}
+
+/// Randomly picks the terrain type of a tile, using the terrain weights of the [`GenerationConfig`].
+///
+/// This is shared with [`WorldPreview`](super::preview::WorldPreview), so previews match the generated world.
+pub(super) fn choose_terrain(
+    rng: &mut impl Rng,
+    terrain_variants: &[Id<Terrain>],
+    terrain_weights: &HashMap<Id<Terrain>, f32>,
+) -> Id<Terrain> {
+    // FIXME: can we not just sample from our terrain_weights directly?
+    *terrain_variants
+        .choose_weighted(rng, |terrain_type| {
+            terrain_weights.get(terrain_type).unwrap()
+        })
+        .unwrap()
+}
+
+/// The height of the terrain at `hex`, which depends only on the seed and noise settings.
+pub(super) fn terrain_height(hex: Hex, generation_config: &GenerationConfig) -> DiscreteHeight {
+    // Heights are generated in f32 world coordinates to start
+    let hex_height = simplex_noise(
+        hex,
+        &generation_config.low_frequency_noise,
+        generation_config.seed,
+    ) + simplex_noise(
+        hex,
+        &generation_config.high_frequency_noise,
+        generation_config.seed,
+    );
+
+    // And then discretized to the nearest integer height before being used
+    DiscreteHeight::from_world_pos(hex_height)
+}
+
 /// Places landmarks according to [`GenerationConfig`].
 pub(super) fn generate_landmarks(
     mut commands: Commands,
@@ -100,6 +152,11 @@ pub(super) fn generate_landmarks(
     map_geometry: Res<MapGeometry>,
     mut rng: ResMut<GlobalRng>,
 ) {
+    // Scenarios place their own landmarks
+    if generation_config.scenario.is_some() {
+        return;
+    }
+
     info!("Generating landmarks...");
 
     for voxel_pos in map_geometry.walkable_voxels() {
@@ -121,6 +178,7 @@ pub(super) fn generate_landmarks(
                         voxel_pos,
                         ClipboardData::generate_from_id(structure_id, &structure_manifest),
                         StartingEnergy::NotAnOrganism,
+                        None,
                     );
                 }
             }
@@ -129,11 +187,28 @@ pub(super) fn generate_landmarks(
 }
 
 /// Sets the starting water table
+///
+/// Tiles set by a scenario start with the water that they were saved with, if any.
 pub(super) fn initialize_water_table(
-    mut water_query: Query<&mut WaterVolume>,
+    mut water_query: Query<(&mut WaterVolume, &VoxelPos)>,
     water_config: Res<WaterConfig>,
+    generation_config: Res<GenerationConfig>,
 ) {
-    for mut water_volume in water_query.iter_mut() {
-        *water_volume = WaterVolume::new(water_config.initial_water);
+    let scenario_tiles = generation_config
+        .scenario
+        .as_ref()
+        .map(|scenario| scenario.tile_map())
+        .unwrap_or_default();
+
+    for (mut water_volume, voxel_pos) in water_query.iter_mut() {
+        let volume = match scenario_tiles
+            .get(&voxel_pos.hex)
+            .and_then(|tile| tile.water)
+        {
+            Some(water) => Volume(water.max(0.)),
+            None => water_config.initial_water,
+        };
+
+        *water_volume = WaterVolume::new(volume);
     }
 }
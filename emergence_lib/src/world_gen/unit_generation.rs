@@ -3,53 +3,102 @@
 use crate::asset_management::manifest::Id;
 use crate::crafting::inventories::{CraftingState, InputInventory, OutputInventory};
 use crate::crafting::recipe::{ActiveRecipe, RecipeManifest};
-use crate::geometry::MapGeometry;
+use crate::factions::Factions;
+use crate::geometry::{MapGeometry, VoxelPos};
 use crate::organisms::energy::EnergyPool;
+use crate::player_interaction::bulk_commands::Favorite;
+use crate::player_interaction::nicknames::Nickname;
+use crate::simulation::game_rules::GameRules;
 use crate::simulation::rng::GlobalRng;
 use crate::structures::structure_manifest::Structure;
 use crate::units::unit_assets::UnitHandles;
-use crate::units::unit_manifest::UnitManifest;
+use crate::units::unit_manifest::{Unit, UnitManifest};
 use crate::units::UnitBundle;
 
 use bevy::prelude::*;
+use rand::rngs::SmallRng;
 use rand::Rng;
 
 use super::GenerationConfig;
 
 /// Create starting units according to [`GenerationConfig`], and randomly place them on
 /// passable tiles.
+///
+/// If the [`Scenario`](super::scenario::Scenario) lists its own units, exactly those are created instead.
+/// Each unit joins the faction whose starting territory it was placed in.
 pub(super) fn generate_units(
     mut commands: Commands,
     config: Res<GenerationConfig>,
+    factions: Res<Factions>,
     maybe_unit_handles: Option<Res<UnitHandles>>,
     unit_manifest: Res<UnitManifest>,
     map_geometry: Res<MapGeometry>,
+    game_rules: Res<GameRules>,
     mut rng: ResMut<GlobalRng>,
 ) {
     info!("Generating units...");
 
-    // Collect out so we can mutate the height map to flatten the terrain while in the loop
+    let unit_bundle = |unit_id: Id<Unit>, voxel_pos: VoxelPos, rng: &mut SmallRng| {
+        let faction = factions.starting_territory(voxel_pos.hex);
+        if let Some(ref unit_handles) = maybe_unit_handles {
+            UnitBundle::randomized(
+                unit_id,
+                voxel_pos,
+                unit_manifest.get(unit_id).clone(),
+                faction,
+                unit_handles,
+                rng,
+            )
+        } else {
+            UnitBundle::testing(
+                unit_id,
+                voxel_pos,
+                unit_manifest.get(unit_id).clone(),
+                faction,
+                rng,
+            )
+        }
+    };
+
+    let scenario_units = config
+        .scenario
+        .as_ref()
+        .map_or(&[][..], |scenario| scenario.units.as_slice());
+
+    if !scenario_units.is_empty() {
+        for scenario_unit in scenario_units {
+            let unit_id = Id::<Unit>::from_name(scenario_unit.unit.clone());
+            if !unit_manifest.data_map().contains_key(&unit_id) {
+                warn!("Unknown unit type {} in scenario", scenario_unit.unit);
+                continue;
+            }
+
+            let Ok(height) = map_geometry.get_height(scenario_unit.hex) else {
+                continue;
+            };
+            let voxel_pos = VoxelPos {
+                hex: scenario_unit.hex,
+                height,
+            }
+            .above();
+
+            let mut entity_commands =
+                commands.spawn(unit_bundle(unit_id, voxel_pos, rng.get_mut()));
+            if let Some(nickname) = scenario_unit.nickname.as_deref().and_then(Nickname::new) {
+                entity_commands.insert(nickname);
+            }
+            if scenario_unit.favorite {
+                entity_commands.insert(Favorite);
+            }
+        }
+
+        return;
+    }
+
     for voxel_pos in map_geometry.walkable_voxels() {
         for (&unit_id, &chance) in &config.unit_chances {
-            if rng.gen::<f32>() < chance {
-                let unit_bundle = if let Some(ref unit_handles) = maybe_unit_handles {
-                    UnitBundle::randomized(
-                        unit_id,
-                        voxel_pos,
-                        unit_manifest.get(unit_id).clone(),
-                        unit_handles,
-                        rng.get_mut(),
-                    )
-                } else {
-                    UnitBundle::testing(
-                        unit_id,
-                        voxel_pos,
-                        unit_manifest.get(unit_id).clone(),
-                        rng.get_mut(),
-                    )
-                };
-
-                commands.spawn(unit_bundle);
+            if rng.gen::<f32>() < game_rules.starting_unit_chance(chance) {
+                commands.spawn(unit_bundle(unit_id, voxel_pos, rng.get_mut()));
             }
         }
     }
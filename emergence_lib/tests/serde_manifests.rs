@@ -1,7 +1,9 @@
-use bevy::utils::HashMap;
+use bevy::utils::{HashMap, HashSet};
 use emergence_lib::{
+    asset_management::models::{ModelData, ModelState, NodeCondition, RawModelManifest},
     construction::RawConstructionStrategy,
     crafting::{
+        hatching::Nursery,
         item_tags::ItemTag,
         recipe::{
             RawActiveRecipe, RawRecipeData, RawRecipeInput, RawRecipeManifest, RecipeConditions,
@@ -11,19 +13,30 @@ use emergence_lib::{
     geometry::Height,
     items::item_manifest::{RawItemData, RawItemManifest},
     light::Illuminance,
+    milestones::{
+        conditions::RawCondition,
+        milestone_manifest::{RawMilestoneData, RawMilestoneManifest},
+    },
     organisms::{
         energy::{Energy, EnergyPool},
         lifecycle::{RawLifePath, RawLifecycle},
         vegetative_reproduction::RawVegetativeReproduction,
         RawOrganismId, RawOrganismVariety,
     },
+    random_events::random_event_manifest::{
+        RawEventEffect, RawRandomEventData, RawRandomEventManifest,
+    },
     structures::{
+        resource_nodes::RawResourceNodeData,
         structure_manifest::{RawStructureData, RawStructureKind, RawStructureManifest},
         Footprint,
     },
     terrain::terrain_manifest::{RawTerrainManifest, TerrainData},
     units::{
+        age::{AgeCurve, AgeCurves},
         basic_needs::RawDiet,
+        goals::GoalKind,
+        movement::MovementMode,
         unit_manifest::{RawUnitData, RawUnitManifest},
         WanderingBehavior,
     },
@@ -47,6 +60,8 @@ fn can_serialize_item_manifest() {
                     fluid: false,
                     buoyant: true,
                     seed: None,
+                    corpse: false,
+                    flavor_text: Some("A test description.".to_string()),
                 },
             ),
             (
@@ -57,6 +72,8 @@ fn can_serialize_item_manifest() {
                     fluid: false,
                     buoyant: false,
                     seed: Some(RawOrganismId::Structure("test_organism".to_string())),
+                    corpse: false,
+                    flavor_text: None,
                 },
             ),
             (
@@ -67,6 +84,8 @@ fn can_serialize_item_manifest() {
                     fluid: true,
                     buoyant: false,
                     seed: None,
+                    corpse: false,
+                    flavor_text: None,
                 },
             ),
         ]),
@@ -94,6 +113,10 @@ fn can_serialize_terrain_manifest() {
                 soil_water_capacity: SoilWaterCapacity(0.3),
                 soil_water_flow_rate: SoilWaterFlowRate(0.1),
                 soil_water_evaporation_rate: SoilWaterEvaporationRate(0.2),
+                buildable: true,
+                base_fertility: 20.0,
+                trail: false,
+                flavor_text: None,
             },
         )]),
     };
@@ -121,6 +144,8 @@ fn can_serialize_unit_manifest() {
                         prototypical_form: RawOrganismId::unit("ant"),
                         lifecycle: RawLifecycle::STATIC,
                         energy_pool: EnergyPool::simple(100.),
+                        dormancy: None,
+                        corpse: Some("ant_corpse".to_string()),
                     },
                     diet: RawDiet::new("leuco_chunk", 50.),
                     max_impatience: 10,
@@ -130,6 +155,20 @@ fn can_serialize_unit_manifest() {
                         (16, 0.1),
                     ]),
                     max_age: 10.,
+                    movement_mode: MovementMode::Walking,
+                    speed: 1.0,
+                    carry_capacity: 1,
+                    // Young and old units are weaker
+                    age_curves: AgeCurves {
+                        speed: AgeCurve::new([(0., 0.5), (0.2, 1.), (0.8, 1.), (1., 0.6)]),
+                        carry_capacity: AgeCurve::default(),
+                        work_rate: AgeCurve::new([(0.8, 1.), (1., 0.5)]),
+                    },
+                    sensing_radius: 2,
+                    crowding_repulsion: 0.2,
+                    nutrients_per_step: 0.01,
+                    allowed_goals: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -139,11 +178,22 @@ fn can_serialize_unit_manifest() {
                         prototypical_form: RawOrganismId::unit("test_unit"),
                         lifecycle: RawLifecycle::STATIC,
                         energy_pool: EnergyPool::simple(50.),
+                        dormancy: None,
+                        corpse: None,
                     },
                     diet: RawDiet::new("acacia_leaf", 0.),
                     max_impatience: 0,
                     wandering_behavior: WanderingBehavior::from_iter([(0, 0.7), (16, 0.1)]),
                     max_age: 0.2,
+                    movement_mode: MovementMode::Flying,
+                    speed: 1.5,
+                    carry_capacity: 3,
+                    age_curves: AgeCurves::default(),
+                    sensing_radius: 0,
+                    crowding_repulsion: 0.,
+                    nutrients_per_step: 0.,
+                    allowed_goals: Some(HashSet::from_iter([GoalKind::Fetch, GoalKind::Deliver])),
+                    flavor_text: None,
                 },
             ),
         ]),
@@ -174,12 +224,17 @@ fn can_serialize_recipe_manifest() {
                         // Output can be stochastic
                         ("acacia_seed".to_string(), 0.1),
                     ]),
+                    // Fluids are measured continuously
+                    bulk_inputs: HashMap::from_iter([("water".to_string(), 1.)]),
+                    bulk_outputs: HashMap::new(),
                     craft_time: 3.,
                     conditions: Some(RecipeConditions::new(
                         0,
                         Threshold::new(Illuminance::DimlyLit, Illuminance::BrightlyLit),
                     )),
                     energy: Some(Energy(20.)),
+                    hatches: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -190,9 +245,13 @@ fn can_serialize_recipe_manifest() {
                         count: 1,
                     },
                     outputs: HashMap::from_iter([("leuco_chunk".to_string(), 1.)]),
+                    bulk_inputs: HashMap::new(),
+                    bulk_outputs: HashMap::new(),
                     craft_time: 2.,
                     conditions: None,
                     energy: Some(Energy(40.)),
+                    hatches: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -200,12 +259,31 @@ fn can_serialize_recipe_manifest() {
                 RawRecipeData {
                     inputs: RawRecipeInput::single("leuco_chunk", 1),
                     outputs: HashMap::from_iter([("ant_egg".to_string(), 1.)]),
+                    bulk_inputs: HashMap::new(),
+                    bulk_outputs: HashMap::new(),
                     craft_time: 10.,
                     conditions: Some(RecipeConditions {
                         workers_required: 2,
                         allowable_light_range: None,
+                        allowable_temperature_range: None,
                     }),
                     energy: None,
+                    hatches: None,
+                    flavor_text: None,
+                },
+            ),
+            (
+                "hatch_ants".to_string(),
+                RawRecipeData {
+                    inputs: RawRecipeInput::single("ant_egg", 1),
+                    outputs: HashMap::new(),
+                    bulk_inputs: HashMap::new(),
+                    bulk_outputs: HashMap::new(),
+                    craft_time: 20.,
+                    conditions: None,
+                    energy: None,
+                    hatches: Some("ant".to_string()),
+                    flavor_text: None,
                 },
             ),
         ]),
@@ -234,6 +312,8 @@ fn can_serialize_structure_manifest() {
                         prototypical_form: RawOrganismId::structure("leuco"),
                         lifecycle: RawLifecycle::STATIC,
                         energy_pool: EnergyPool::simple(100.),
+                        dormancy: None,
+                        corpse: None,
                     }),
                     kind: RawStructureKind::Crafting {
                         starting_recipe: RawActiveRecipe::new("leuco_chunk_production"),
@@ -246,8 +326,13 @@ fn can_serialize_structure_manifest() {
                     footprint: Some(Footprint::single()),
                     root_zone: None,
                     can_walk_on_roof: false,
+                    resource_node: None,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    nursery: None,
+                    shelter: None,
+                    nutrient_link: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -263,8 +348,40 @@ fn can_serialize_structure_manifest() {
                     footprint: Some(Footprint::single()),
                     root_zone: None,
                     can_walk_on_roof: false,
+                    resource_node: None,
                     can_walk_through: true,
                     vegetative_reproduction: None,
+                    nursery: None,
+                    shelter: None,
+                    nutrient_link: None,
+                    flavor_text: None,
+                },
+            ),
+            (
+                "hatchery".to_string(),
+                RawStructureData {
+                    organism_variety: None,
+                    kind: RawStructureKind::Crafting {
+                        starting_recipe: RawActiveRecipe::new("hatch_ants"),
+                    },
+                    construction_strategy: RawConstructionStrategy::Direct {
+                        work: Some(5.),
+                        materials: HashMap::from_iter([("leuco_chunk".to_string(), 2)]),
+                    },
+                    max_workers: 2,
+                    footprint: Some(Footprint::single()),
+                    root_zone: None,
+                    can_walk_on_roof: false,
+                    resource_node: None,
+                    can_walk_through: false,
+                    vegetative_reproduction: None,
+                    nursery: Some(Nursery {
+                        radius: 3,
+                        hatching_speedup: 0.5,
+                    }),
+                    shelter: None,
+                    nutrient_link: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -277,8 +394,17 @@ fn can_serialize_structure_manifest() {
                     footprint: None,
                     root_zone: None,
                     can_walk_on_roof: false,
+                    resource_node: Some(RawResourceNodeData {
+                        item: "water".to_string(),
+                        capacity: 10,
+                        regrowth_per_day: 20.,
+                    }),
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    nursery: None,
+                    shelter: None,
+                    nutrient_link: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -292,6 +418,8 @@ fn can_serialize_structure_manifest() {
                             time_required: Some(1.),
                         }]),
                         energy_pool: EnergyPool::simple(75.),
+                        dormancy: None,
+                        corpse: None,
                     }),
                     kind: RawStructureKind::Crafting {
                         starting_recipe: RawActiveRecipe::new("acacia_leaf_production"),
@@ -304,8 +432,13 @@ fn can_serialize_structure_manifest() {
                     footprint: Some(Footprint::single()),
                     root_zone: None,
                     can_walk_on_roof: false,
+                    resource_node: None,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    nursery: None,
+                    shelter: None,
+                    nutrient_link: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -315,6 +448,8 @@ fn can_serialize_structure_manifest() {
                         prototypical_form: RawOrganismId::structure("acacia"),
                         lifecycle: RawLifecycle::STATIC,
                         energy_pool: EnergyPool::simple(300.),
+                        dormancy: None,
+                        corpse: None,
                     }),
                     kind: RawStructureKind::Crafting {
                         starting_recipe: RawActiveRecipe::new("acacia_leaf_production"),
@@ -329,11 +464,16 @@ fn can_serialize_structure_manifest() {
                         radius: 2,
                     }),
                     can_walk_on_roof: false,
+                    resource_node: None,
                     can_walk_through: false,
                     vegetative_reproduction: Some(RawVegetativeReproduction {
                         period: 10.,
                         energy_threshold: 30.,
                     }),
+                    nursery: None,
+                    shelter: None,
+                    nutrient_link: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -351,8 +491,13 @@ fn can_serialize_structure_manifest() {
                     footprint: Some(Footprint::hexagon(1)),
                     root_zone: None,
                     can_walk_on_roof: false,
+                    resource_node: None,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    nursery: None,
+                    shelter: None,
+                    nutrient_link: None,
+                    flavor_text: None,
                 },
             ),
             (
@@ -371,8 +516,13 @@ fn can_serialize_structure_manifest() {
                     footprint: Some(Footprint::single()),
                     root_zone: None,
                     can_walk_on_roof: false,
+                    resource_node: None,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    nursery: None,
+                    shelter: None,
+                    nutrient_link: None,
+                    flavor_text: None,
                 },
             ),
         ]),
@@ -388,3 +538,122 @@ fn can_serialize_structure_manifest() {
     // Check that the deserialized version is the same as the original
     assert_eq!(raw_structure_manifest, deserialized);
 }
+
+#[test]
+fn can_serialize_milestone_manifest() {
+    // Create a new raw milestone manifest
+    let raw_milestone_manifest = RawMilestoneManifest {
+        milestones: HashMap::from_iter(vec![(
+            "test_milestone".to_string(),
+            RawMilestoneData {
+                description: "Test milestone".to_string(),
+                condition: RawCondition::All(vec![
+                    RawCondition::Population {
+                        unit: "test_unit".to_string(),
+                        count: 100,
+                    },
+                    RawCondition::DaysWithoutRain(7),
+                ]),
+            },
+        )]),
+    };
+
+    // Serialize it
+    let serialized = serde_json::to_string(&raw_milestone_manifest).unwrap();
+    println!("{}", &serialized);
+
+    // Deserialize it
+    let deserialized: RawMilestoneManifest = serde_json::from_str(&serialized).unwrap();
+
+    // Check that the deserialized version is the same as the original
+    assert_eq!(raw_milestone_manifest, deserialized);
+}
+
+#[test]
+fn can_serialize_random_event_manifest() {
+    // Create a new raw random event manifest
+    let raw_random_event_manifest = RawRandomEventManifest {
+        random_events: HashMap::from_iter(vec![(
+            "test_event".to_string(),
+            RawRandomEventData {
+                title: "Test event".to_string(),
+                description: "Something happened".to_string(),
+                weight: 1.,
+                trigger: RawCondition::DaysSurvived(3),
+                cooldown_days: 5,
+                effects: vec![
+                    RawEventEffect::DestroyStructures {
+                        structure: "test_structure".to_string(),
+                        fraction: 0.5,
+                    },
+                    RawEventEffect::DropItems {
+                        item: "test_item".to_string(),
+                        count: 3,
+                    },
+                ],
+            },
+        )]),
+    };
+
+    // Serialize it
+    let serialized = serde_json::to_string(&raw_random_event_manifest).unwrap();
+    println!("{}", &serialized);
+
+    // Deserialize it
+    let deserialized: RawRandomEventManifest = serde_json::from_str(&serialized).unwrap();
+
+    // Check that the deserialized version is the same as the original
+    assert_eq!(raw_random_event_manifest, deserialized);
+}
+
+#[test]
+fn can_serialize_model_manifest() {
+    // Create a new raw model manifest
+    let raw_model_manifest = RawModelManifest {
+        models: HashMap::from_iter(vec![
+            (
+                "structures/test_structure".to_string(),
+                ModelData {
+                    scene: "structures/test_structure.gltf#Scene1".to_string(),
+                    conditional_nodes: HashMap::from_iter(vec![
+                        (
+                            "flowers".to_string(),
+                            NodeCondition {
+                                state: ModelState::Grown(0.5),
+                                visible: true,
+                            },
+                        ),
+                        (
+                            "lights".to_string(),
+                            NodeCondition {
+                                state: ModelState::Disabled,
+                                visible: false,
+                            },
+                        ),
+                    ]),
+                    tinted_nodes: vec!["banner".to_string()],
+                    highlighted_nodes: vec!["roof".to_string()],
+                },
+            ),
+            (
+                "terrain/test_terrain".to_string(),
+                ModelData {
+                    scene: "terrain/test_terrain.gltf#Scene0".to_string(),
+                    conditional_nodes: HashMap::new(),
+                    tinted_nodes: Vec::new(),
+                    highlighted_nodes: Vec::new(),
+                },
+            ),
+        ]),
+    };
+
+    // Serialize it
+    let serialized = serde_json::to_string(&raw_model_manifest).unwrap();
+    println!("{}", &serialized);
+
+    // Deserialize it
+    let deserialized: RawModelManifest = serde_json::from_str(&serialized).unwrap();
+
+    // Check that the deserialized version is the same as the original
+    assert_eq!(raw_model_manifest, deserialized);
+}
@@ -0,0 +1,22 @@
+//! Checks that game balance has not drifted, by comparing simulation runs against golden snapshots.
+//!
+//! See [`emergence_lib::testing::regression`] for details on how to record new snapshots.
+
+use emergence_lib::testing::regression::{assert_matches_golden, run_scenario, Tolerance};
+use emergence_lib::world_gen::GenerationConfig;
+
+#[test]
+#[ignore = "Cannot end-to-end test game without a GPU."]
+fn testing_world_is_stable() {
+    let snapshot = run_scenario(GenerationConfig::testing(), 1000);
+
+    assert_matches_golden("testing_world", &snapshot, Tolerance::default());
+}
+
+#[test]
+#[ignore = "Cannot end-to-end test game without a GPU."]
+fn standard_world_is_stable() {
+    let snapshot = run_scenario(GenerationConfig::standard(), 1000);
+
+    assert_matches_golden("standard_world", &snapshot, Tolerance::default());
+}